@@ -0,0 +1,181 @@
+//! Convert LightGBM / XGBoost models into the deterministic D-GBDT format.
+//!
+//! ## Usage
+//!
+//! ```bash
+//! cargo run -p ippan-ai-core --bin import_gbdt -- \
+//!     --input model.txt --out ai_training/ippan_d_gbdt_v4.json \
+//!     --samples validation.csv --max-error 0.0001 --report import_report.json
+//! ```
+//!
+//! The output file holds the canonical JSON bytes, so its BLAKE3 digest is
+//! the hash `compute_model_hash` reports and `promote_fairness_model.py`
+//! pins in `config/dlc.toml`. `generate_manifest --model <out>` turns it
+//! into a manifest entry with the same digests.
+//!
+//! Samples are CSV rows of raw (unscaled) feature values. With a header row
+//! whose names cover the model's features, columns are matched by name;
+//! otherwise they are read positionally.
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use ippan_ai_core::gbdt::{import_model, ImportOptions, SourceFormat, SCALE};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(name = "import_gbdt")]
+struct Opt {
+    /// LightGBM text/JSON or XGBoost JSON model to convert
+    #[arg(short, long)]
+    input: PathBuf,
+
+    /// Where to write the canonical D-GBDT JSON
+    #[arg(short, long)]
+    out: PathBuf,
+
+    /// Source format: lightgbm-text, lightgbm-json, xgboost-dump or
+    /// xgboost-json (detected when omitted)
+    #[arg(long)]
+    format: Option<String>,
+
+    /// CSV of raw feature rows used to measure precision loss
+    #[arg(long)]
+    samples: Option<PathBuf>,
+
+    /// Fail when the largest prediction error on the samples exceeds this
+    #[arg(long, requires = "samples")]
+    max_error: Option<f64>,
+
+    /// Comma-separated feature order for the emitted model
+    #[arg(long, value_delimiter = ',')]
+    features: Option<Vec<String>>,
+
+    /// Multiplier applied to raw feature values at inference time
+    #[arg(long, default_value_t = SCALE)]
+    feature_scale: i64,
+
+    /// Margin added to every prediction (XGBoost dumps omit it)
+    #[arg(long)]
+    base_score: Option<f64>,
+
+    /// Write the import summary as JSON
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::parse();
+
+    let contents = fs::read_to_string(&opt.input)
+        .with_context(|| format!("Failed to read {}", opt.input.display()))?;
+    let format = match &opt.format {
+        Some(format) => format.parse::<SourceFormat>()?,
+        None => SourceFormat::detect(&contents)
+            .context("Unable to detect the model format; pass --format")?,
+    };
+
+    let options = ImportOptions {
+        feature_scale: opt.feature_scale,
+        feature_names: opt.features.clone(),
+        base_score: opt.base_score,
+    };
+    let imported = import_model(&contents, format, &options)
+        .with_context(|| format!("Failed to import {} as {format}", opt.input.display()))?;
+
+    let precision = match &opt.samples {
+        Some(path) => {
+            let samples = load_samples(path, &imported.feature_names)?;
+            Some(imported.precision_report(&samples)?)
+        }
+        None => None,
+    };
+    let report = imported.report(precision)?;
+
+    imported
+        .model
+        .save_json(&opt.out)
+        .with_context(|| format!("Failed to write {}", opt.out.display()))?;
+
+    println!("Imported {format} model from {}", opt.input.display());
+    println!("  trees:      {}", report.trees);
+    println!("  nodes:      {}", report.nodes);
+    if let Some(objective) = &report.objective {
+        println!("  objective:  {objective}");
+    }
+    println!("  output:     {}", opt.out.display());
+    println!("  blake3:     {}", report.model_hash);
+    println!("  sha256:     {}", report.sha256);
+    println!("  size_bytes: {}", report.size_bytes);
+    if let Some(precision) = &report.precision {
+        println!(
+            "  precision:  {} samples, max |err| {:.9}, mean |err| {:.9}, {} path mismatches",
+            precision.samples,
+            precision.max_abs_error,
+            precision.mean_abs_error,
+            precision.path_mismatches
+        );
+    }
+
+    if let Some(path) = &opt.report {
+        let json = serde_json::to_string_pretty(&report)?;
+        fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    if let (Some(limit), Some(precision)) = (opt.max_error, &report.precision) {
+        if precision.max_abs_error > limit {
+            bail!(
+                "Quantization error {:.9} exceeds --max-error {limit}",
+                precision.max_abs_error
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Read sample rows, reordering named columns to the model's feature order.
+fn load_samples(path: &Path, feature_names: &[String]) -> Result<Vec<Vec<f64>>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .peekable();
+
+    let parse_row = |line: &str| -> Option<Vec<f64>> {
+        line.split(',')
+            .map(|cell| cell.trim().parse::<f64>().ok())
+            .collect()
+    };
+
+    let mut columns: Option<Vec<usize>> = None;
+    if let Some(first) = lines.peek() {
+        if parse_row(first).is_none() {
+            let header: Vec<&str> = first.split(',').map(str::trim).collect();
+            let named: Option<Vec<usize>> = feature_names
+                .iter()
+                .map(|name| header.iter().position(|column| column == name))
+                .collect();
+            columns = named.filter(|named| !named.is_empty());
+            lines.next();
+        }
+    }
+
+    lines
+        .enumerate()
+        .map(|(index, line)| {
+            let row = parse_row(line)
+                .with_context(|| format!("Invalid sample row {}: {line}", index + 1))?;
+            match &columns {
+                Some(columns) => columns
+                    .iter()
+                    .map(|column| row.get(*column).copied())
+                    .collect::<Option<Vec<_>>>()
+                    .with_context(|| format!("Sample row {} is missing columns", index + 1)),
+                None => Ok(row),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,643 @@
+//! Input drift monitoring for deterministic models
+//!
+//! Live feature values are folded into per-feature [`QuantileSketch`]es and
+//! compared with the training [`DistributionSummary`] recorded in the model
+//! manifest using the population stability index (PSI). Everything is
+//! integer arithmetic: quantiles are feature values, fractions are parts per
+//! million and PSI is fixed-point at [`SCALE`] (`100_000` = 0.1).
+
+use crate::errors::{AiCoreError, Result};
+use crate::fixed::{Fixed, SCALE};
+use crate::monitoring::{Alert, AlertSeverity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Quantile bins recorded per training feature by default (deciles).
+pub const DEFAULT_SUMMARY_BINS: usize = 10;
+
+/// Floor (ppm) applied to bin fractions so empty bins keep PSI finite.
+pub const PSI_EPSILON: i64 = 100;
+
+/// ln(2) at [`SCALE`].
+const LN_2: i64 = 693_147;
+
+/// Streaming histogram with fixed, inclusive bucket upper bounds.
+///
+/// Bucket `i` holds values in `(bounds[i - 1], bounds[i]]`; a final bucket
+/// holds everything above the last bound. Counts at a bound are exact,
+/// anything in between is interpolated linearly inside its bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantileSketch {
+    bounds: Vec<i64>,
+    counts: Vec<u64>,
+    total: u64,
+    min: i64,
+    max: i64,
+}
+
+impl QuantileSketch {
+    /// Sketch with the given bucket upper bounds (sorted and deduplicated).
+    pub fn with_bounds(mut bounds: Vec<i64>) -> Self {
+        bounds.sort_unstable();
+        bounds.dedup();
+        Self {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+            total: 0,
+            min: i64::MAX,
+            max: i64::MIN,
+        }
+    }
+
+    /// Sketch with up to `buckets` equal-width buckets covering `[lo, hi]`.
+    pub fn uniform(lo: i64, hi: i64, buckets: usize) -> Self {
+        Self::with_bounds(uniform_bounds(lo, hi, buckets))
+    }
+
+    /// Add one observation.
+    pub fn insert(&mut self, value: i64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        self.counts[bucket] += 1;
+        self.total += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Fold `other` into this sketch; both must share the same bounds.
+    pub fn merge(&mut self, other: &QuantileSketch) -> Result<()> {
+        if self.bounds != other.bounds {
+            return Err(AiCoreError::InvalidParameters(
+                "cannot merge quantile sketches with different bounds".to_string(),
+            ));
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        Ok(())
+    }
+
+    /// Number of observations.
+    pub fn count(&self) -> u64 {
+        self.total
+    }
+
+    /// Smallest observed value.
+    pub fn min(&self) -> Option<i64> {
+        (self.total > 0).then_some(self.min)
+    }
+
+    /// Largest observed value.
+    pub fn max(&self) -> Option<i64> {
+        (self.total > 0).then_some(self.max)
+    }
+
+    /// Number of observations `<= value`.
+    pub fn count_le(&self, value: i64) -> u64 {
+        if self.total == 0 || value < self.min {
+            return 0;
+        }
+        if value >= self.max {
+            return self.total;
+        }
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        let below: u64 = self.counts[..bucket].iter().sum();
+        let count = self.counts[bucket];
+        if self.bounds.get(bucket) == Some(&value) {
+            return below + count;
+        }
+        let (lower, upper) = self.bucket_range(bucket);
+        if value < lower {
+            return below;
+        }
+        if value >= upper {
+            return below + count;
+        }
+        let covered = (value - lower) as u128 + 1;
+        let width = (upper - lower) as u128 + 1;
+        below + (count as u128 * covered / width) as u64
+    }
+
+    /// Value at quantile `q` (ppm, clamped to `[0, SCALE]`).
+    pub fn quantile(&self, q: i64) -> Option<i64> {
+        if self.total == 0 {
+            return None;
+        }
+        let q = q.clamp(0, SCALE) as u128;
+        let target = (q * self.total as u128).div_ceil(SCALE as u128).max(1) as u64;
+        let mut below = 0u64;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            if count == 0 || below + count < target {
+                below += count;
+                continue;
+            }
+            let (lower, upper) = self.bucket_range(bucket);
+            let offset = (target - below) as i128;
+            let span = (upper - lower) as i128;
+            let value = lower as i128 + span * offset / count as i128;
+            return Some(value as i64);
+        }
+        Some(self.max)
+    }
+
+    /// Observed value range inside `bucket`.
+    fn bucket_range(&self, bucket: usize) -> (i64, i64) {
+        let lower = match bucket {
+            0 => self.min,
+            _ => self.bounds[bucket - 1].saturating_add(1).max(self.min),
+        };
+        let upper = self
+            .bounds
+            .get(bucket)
+            .copied()
+            .unwrap_or(self.max)
+            .min(self.max);
+        (lower, upper.max(lower))
+    }
+}
+
+/// Up to `buckets` inclusive upper bounds splitting `[lo, hi]` evenly.
+pub fn uniform_bounds(lo: i64, hi: i64, buckets: usize) -> Vec<i64> {
+    if hi < lo {
+        return Vec::new();
+    }
+    let width = hi as i128 - lo as i128 + 1;
+    let buckets = (buckets.max(1) as i128).min(width);
+    (1..=buckets)
+        .map(|i| (lo as i128 + i * width / buckets - 1) as i64)
+        .collect()
+}
+
+/// Training distribution of one feature, as reference bins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureDistribution {
+    pub feature: String,
+    /// Training samples the summary was built from
+    pub count: u64,
+    pub min: i64,
+    pub max: i64,
+    /// Inclusive upper bin edges: `min - 1`, the training quantiles and `max`
+    pub cut_points: Vec<i64>,
+    /// Share of training samples per bin (ppm); one more entry than
+    /// `cut_points`, the first and last bins are empty by construction
+    pub fractions: Vec<i64>,
+}
+
+impl FeatureDistribution {
+    /// Summarise `values` into at most `bins` quantile bins.
+    pub fn from_values(feature: impl Into<String>, values: &[i64], bins: usize) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len();
+        let min = sorted[0];
+        let max = sorted[n - 1];
+        let bins = bins.max(1);
+
+        let mut cut_points = vec![min.saturating_sub(1)];
+        for k in 1..bins {
+            let rank = (k * n).div_ceil(bins).max(1);
+            cut_points.push(sorted[rank - 1]);
+        }
+        cut_points.push(max);
+        cut_points.sort_unstable();
+        cut_points.dedup();
+
+        let mut fractions = Vec::with_capacity(cut_points.len() + 1);
+        let mut previous = 0i64;
+        for cut in &cut_points {
+            let below = sorted.partition_point(|&value| value <= *cut);
+            let cumulative = (below as i128 * SCALE as i128 / n as i128) as i64;
+            fractions.push(cumulative - previous);
+            previous = cumulative;
+        }
+        fractions.push(SCALE - previous);
+
+        Some(Self {
+            feature: feature.into(),
+            count: n as u64,
+            min,
+            max,
+            cut_points,
+            fractions,
+        })
+    }
+
+    /// Population stability index of `live` against this distribution, at
+    /// [`SCALE`]. `None` while `live` is empty.
+    pub fn psi(&self, live: &QuantileSketch) -> Option<i64> {
+        let total = live.count();
+        if total == 0 {
+            return None;
+        }
+        let mut psi = 0i64;
+        let mut previous = 0i64;
+        for (bin, &expected) in self.fractions.iter().enumerate() {
+            let cumulative = match self.cut_points.get(bin) {
+                Some(&cut) => (live.count_le(cut) as i128 * SCALE as i128 / total as i128) as i64,
+                None => SCALE,
+            };
+            psi += psi_term(cumulative - previous, expected);
+            previous = cumulative;
+        }
+        Some(psi)
+    }
+}
+
+/// Per-feature training distributions stored alongside a model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistributionSummary {
+    pub features: Vec<FeatureDistribution>,
+}
+
+impl DistributionSummary {
+    /// Summarise the columns of `rows`, named by `names`, into `bins` bins.
+    pub fn from_rows<S: AsRef<str>>(names: &[S], rows: &[Vec<i64>], bins: usize) -> Self {
+        let features = names
+            .iter()
+            .enumerate()
+            .filter_map(|(column, name)| {
+                let values: Vec<i64> = rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .copied()
+                    .collect();
+                FeatureDistribution::from_values(name.as_ref(), &values, bins)
+            })
+            .collect();
+        Self { features }
+    }
+
+    /// Distribution recorded for `feature`.
+    pub fn feature(&self, feature: &str) -> Option<&FeatureDistribution> {
+        self.features.iter().find(|dist| dist.feature == feature)
+    }
+
+    /// Check that the summary covers exactly the features in `names`.
+    pub fn require_features<S: AsRef<str>>(&self, names: &[S]) -> Result<()> {
+        if let Some(unknown) = self
+            .features
+            .iter()
+            .find(|dist| !names.iter().any(|name| name.as_ref() == dist.feature))
+        {
+            return Err(AiCoreError::InvalidParameters(format!(
+                "training distribution has unknown feature `{}`",
+                unknown.feature
+            )));
+        }
+        if let Some(missing) = names
+            .iter()
+            .find(|name| self.feature(name.as_ref()).is_none())
+        {
+            return Err(AiCoreError::InvalidParameters(format!(
+                "training distribution is missing feature `{}`",
+                missing.as_ref()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Thresholds for [`DriftMonitor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftConfig {
+    /// PSI (at SCALE) raising a warning; 0.1 is the usual "moderate shift"
+    pub psi_warning: i64,
+    /// PSI (at SCALE) raising a critical alert; 0.25 is a significant shift
+    pub psi_critical: i64,
+    /// Live samples a feature needs before its PSI is reported
+    pub min_samples: u64,
+    /// Samples per feature after which a new window starts
+    pub window: u64,
+    /// Uniform sketch buckets added on top of the reference cut points
+    pub sketch_buckets: usize,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            psi_warning: 100_000,
+            psi_critical: 250_000,
+            min_samples: 100,
+            window: 10_000,
+            sketch_buckets: 64,
+        }
+    }
+}
+
+/// Drift of one feature in a [`DriftReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureDrift {
+    pub feature: String,
+    /// Live samples behind `psi`
+    pub samples: u64,
+    /// PSI at SCALE; `None` below [`DriftConfig::min_samples`]
+    pub psi: Option<i64>,
+    pub live_median: Option<i64>,
+}
+
+/// Drift of every monitored feature, in reference order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub features: Vec<FeatureDrift>,
+}
+
+#[derive(Debug, Clone)]
+struct FeatureWindow {
+    current: QuantileSketch,
+    /// Last full window, reported while `current` is still filling
+    completed: Option<QuantileSketch>,
+}
+
+/// Tracks live feature values against a training [`DistributionSummary`].
+///
+/// Each feature keeps a tumbling window of [`DriftConfig::window`] samples
+/// so that old traffic does not mask a recent shift.
+#[derive(Debug, Clone)]
+pub struct DriftMonitor {
+    config: DriftConfig,
+    reference: DistributionSummary,
+    windows: BTreeMap<String, FeatureWindow>,
+}
+
+impl DriftMonitor {
+    pub fn new(reference: DistributionSummary, config: DriftConfig) -> Self {
+        let windows = reference
+            .features
+            .iter()
+            .map(|dist| {
+                // Leave room on both sides so live tails outside the
+                // training range still get usable quantiles.
+                let span = dist.max.saturating_sub(dist.min).max(1);
+                let mut bounds = uniform_bounds(
+                    dist.min.saturating_sub(span),
+                    dist.max.saturating_add(span),
+                    config.sketch_buckets,
+                );
+                bounds.extend_from_slice(&dist.cut_points);
+                let window = FeatureWindow {
+                    current: QuantileSketch::with_bounds(bounds),
+                    completed: None,
+                };
+                (dist.feature.clone(), window)
+            })
+            .collect();
+        Self {
+            config,
+            reference,
+            windows,
+        }
+    }
+
+    pub fn config(&self) -> &DriftConfig {
+        &self.config
+    }
+
+    pub fn reference(&self) -> &DistributionSummary {
+        &self.reference
+    }
+
+    /// Record a live value; features without a reference are an error, as
+    /// they mean the live and training feature vectors disagree.
+    pub fn observe(&mut self, feature: &str, value: i64) -> Result<()> {
+        let Some(window) = self.windows.get_mut(feature) else {
+            return Err(AiCoreError::InvalidParameters(format!(
+                "no training distribution for feature `{feature}`"
+            )));
+        };
+        if window.current.count() >= self.config.window.max(1) {
+            let fresh = QuantileSketch::with_bounds(window.current.bounds.clone());
+            window.completed = Some(std::mem::replace(&mut window.current, fresh));
+        }
+        window.current.insert(value);
+        Ok(())
+    }
+
+    /// Record one feature row, with values in `names` order.
+    pub fn observe_row<S: AsRef<str>>(&mut self, names: &[S], values: &[i64]) -> Result<()> {
+        if names.len() != values.len() {
+            return Err(AiCoreError::InvalidParameters(format!(
+                "{} feature names for {} values",
+                names.len(),
+                values.len()
+            )));
+        }
+        for (name, &value) in names.iter().zip(values) {
+            self.observe(name.as_ref(), value)?;
+        }
+        Ok(())
+    }
+
+    /// PSI and live median of every reference feature.
+    pub fn report(&self) -> DriftReport {
+        let features = self
+            .reference
+            .features
+            .iter()
+            .map(|dist| {
+                let sketch =
+                    self.windows
+                        .get(&dist.feature)
+                        .map(|window| match &window.completed {
+                            Some(completed) if window.current.count() < self.config.min_samples => {
+                                completed
+                            }
+                            _ => &window.current,
+                        });
+                let samples = sketch.map_or(0, QuantileSketch::count);
+                let ready = samples > 0 && samples >= self.config.min_samples;
+                FeatureDrift {
+                    feature: dist.feature.clone(),
+                    samples,
+                    psi: sketch.filter(|_| ready).and_then(|sketch| dist.psi(sketch)),
+                    live_median: sketch.and_then(|sketch| sketch.quantile(SCALE / 2)),
+                }
+            })
+            .collect();
+        DriftReport { features }
+    }
+
+    /// Alerts for every feature whose PSI crosses a threshold.
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.report()
+            .features
+            .into_iter()
+            .filter_map(|drift| {
+                let psi = drift.psi?;
+                let (threshold, severity) = if psi >= self.config.psi_critical {
+                    (self.config.psi_critical, AlertSeverity::Critical)
+                } else if psi >= self.config.psi_warning {
+                    (self.config.psi_warning, AlertSeverity::Warning)
+                } else {
+                    return None;
+                };
+                Some(Alert {
+                    metric_name: format!("feature_psi.{}", drift.feature),
+                    value: Fixed::from_micro(psi),
+                    threshold: Fixed::from_micro(threshold),
+                    severity,
+                })
+            })
+            .collect()
+    }
+}
+
+/// `(a - e) * ln(a / e)` for bin fractions in ppm, at [`SCALE`].
+fn psi_term(actual: i64, expected: i64) -> i64 {
+    let actual = actual.max(PSI_EPSILON);
+    let expected = expected.max(PSI_EPSILON);
+    let log_ratio = ln_fixed(actual) - ln_fixed(expected);
+    ((actual - expected) as i128 * log_ratio as i128 / SCALE as i128) as i64
+}
+
+/// Natural logarithm of a positive fixed-point value, at [`SCALE`].
+fn ln_fixed(x: i64) -> i64 {
+    debug_assert!(x > 0);
+    let scale = SCALE as i128;
+    let mut mantissa = x.max(1) as i128;
+    let mut exponent = 0i64;
+    while mantissa >= 2 * scale {
+        mantissa /= 2;
+        exponent += 1;
+    }
+    while mantissa < scale {
+        mantissa *= 2;
+        exponent -= 1;
+    }
+    // ln(m) = 2 atanh((m - 1) / (m + 1)) with m in [1, 2)
+    let z = (mantissa - scale) * scale / (mantissa + scale);
+    let z_squared = z * z / scale;
+    let mut term = z;
+    let mut sum = 0i128;
+    let mut divisor = 1i128;
+    while term != 0 {
+        sum += term / divisor;
+        term = term * z_squared / scale;
+        divisor += 2;
+    }
+    exponent * LN_2 + (2 * sum) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spread(lo: i64, hi: i64, n: i64) -> Vec<i64> {
+        (0..n).map(|i| lo + (hi - lo) * i / (n - 1)).collect()
+    }
+
+    #[test]
+    fn test_ln_fixed_matches_known_values() {
+        assert_eq!(ln_fixed(SCALE), 0);
+        assert!((ln_fixed(2 * SCALE) - LN_2).abs() <= 2);
+        assert!((ln_fixed(SCALE / 10) + 2_302_585).abs() <= 5);
+        assert!((ln_fixed(PSI_EPSILON) + 9_210_340).abs() <= 10);
+    }
+
+    #[test]
+    fn test_sketch_counts_and_quantiles() {
+        let mut sketch = QuantileSketch::uniform(0, 9_999, 100);
+        for value in 0..10_000 {
+            sketch.insert(value);
+        }
+        assert_eq!(sketch.count(), 10_000);
+        assert_eq!(sketch.count_le(4_999), 5_000);
+        assert_eq!(sketch.count_le(-1), 0);
+        assert_eq!(sketch.count_le(20_000), 10_000);
+        assert!((sketch.quantile(SCALE / 2).unwrap() - 4_999).abs() <= 100);
+        assert!((sketch.quantile(900_000).unwrap() - 8_999).abs() <= 100);
+
+        let mut other = QuantileSketch::uniform(0, 9_999, 100);
+        other.insert(42);
+        sketch.merge(&other).unwrap();
+        assert_eq!(sketch.count(), 10_001);
+        assert!(sketch.merge(&QuantileSketch::uniform(0, 99, 10)).is_err());
+    }
+
+    #[test]
+    fn test_summary_bins_cover_training_data() {
+        let summary = DistributionSummary::from_rows(
+            &["a", "b"],
+            &spread(0, 999, 1_000)
+                .into_iter()
+                .map(|value| vec![value, 7])
+                .collect::<Vec<_>>(),
+            DEFAULT_SUMMARY_BINS,
+        );
+        let a = summary.feature("a").unwrap();
+        assert_eq!(a.cut_points.len(), 11);
+        assert_eq!(a.fractions.iter().sum::<i64>(), SCALE);
+        assert_eq!(a.fractions[0], 0);
+        assert_eq!(*a.fractions.last().unwrap(), 0);
+
+        // A constant column collapses to a single populated bin.
+        let b = summary.feature("b").unwrap();
+        assert_eq!(b.cut_points, vec![6, 7]);
+        assert_eq!(b.fractions, vec![0, SCALE, 0]);
+
+        assert!(summary.require_features(&["b", "a"]).is_ok());
+        assert!(summary.require_features(&["a"]).is_err());
+        assert!(summary.require_features(&["a", "b", "c"]).is_err());
+    }
+
+    #[test]
+    fn test_monitor_flags_shifted_features_only() {
+        let rows: Vec<Vec<i64>> = spread(0, 10_000, 2_000)
+            .into_iter()
+            .map(|value| vec![value, value])
+            .collect();
+        let summary = DistributionSummary::from_rows(&["stable", "shifted"], &rows, 10);
+        let mut monitor = DriftMonitor::new(summary, DriftConfig::default());
+
+        for value in spread(0, 10_000, 500) {
+            monitor.observe("stable", value).unwrap();
+            monitor.observe("shifted", 5_000 + value / 2).unwrap();
+        }
+        assert!(monitor.observe("unknown", 1).is_err());
+        assert!(monitor.observe_row(&["stable"], &[1, 2]).is_err());
+
+        let report = monitor.report();
+        let stable = report.features[0].psi.unwrap();
+        let shifted = report.features[1].psi.unwrap();
+        assert!(stable < 10_000, "stable psi {stable}");
+        assert!(shifted > 250_000, "shifted psi {shifted}");
+        assert!((report.features[1].live_median.unwrap() - 7_500).abs() <= 200);
+
+        let alerts = monitor.alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric_name, "feature_psi.shifted");
+        assert!(matches!(alerts[0].severity, AlertSeverity::Critical));
+    }
+
+    #[test]
+    fn test_monitor_waits_for_min_samples_and_rolls_windows() {
+        let summary = DistributionSummary::from_rows(&["x"], &[vec![1], vec![2], vec![3]], 10);
+        let config = DriftConfig {
+            min_samples: 3,
+            window: 4,
+            ..DriftConfig::default()
+        };
+        let mut monitor = DriftMonitor::new(summary, config);
+
+        monitor.observe("x", 100).unwrap();
+        monitor.observe("x", 100).unwrap();
+        assert_eq!(monitor.report().features[0].psi, None);
+
+        monitor.observe("x", 100).unwrap();
+        monitor.observe("x", 100).unwrap();
+        assert!(monitor.report().features[0].psi.unwrap() > 250_000);
+
+        // A fresh window below min_samples still reports the completed one.
+        monitor.observe("x", 1).unwrap();
+        assert_eq!(monitor.report().features[0].samples, 4);
+        monitor.observe("x", 2).unwrap();
+        monitor.observe("x", 3).unwrap();
+        let drift = &monitor.report().features[0];
+        assert_eq!(drift.samples, 3);
+        assert_eq!(drift.psi, Some(0));
+    }
+}
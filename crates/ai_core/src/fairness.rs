@@ -6,7 +6,7 @@
 //! point operations are used.
 
 use crate::errors::{AiCoreError, Result};
-use crate::gbdt::{Model as DgbdtModel, SCALE as DGBDT_SCALE};
+use crate::gbdt::{Explanation, Model as DgbdtModel, SCALE as DGBDT_SCALE};
 use serde::{Deserialize, Serialize};
 
 /// Fixed-point normalized validator features (0-10_000 scale per field).
@@ -21,6 +21,16 @@ pub struct ValidatorFeatureVector {
 }
 
 impl ValidatorFeatureVector {
+    /// Feature names in [`Self::as_array`] order.
+    pub const FEATURE_NAMES: [&'static str; 6] = [
+        "uptime",
+        "latency_inverse",
+        "honesty",
+        "proposal_rate",
+        "verification_rate",
+        "stake_weight",
+    ];
+
     /// Create a new normalized feature vector.
     pub fn new(
         uptime: i64,
@@ -104,6 +114,15 @@ impl DeterministicFairnessModel {
         Self::quantize(raw)
     }
 
+    /// Attribute the raw model output for `normalized` to its features.
+    ///
+    /// Values are in raw model units; [`Self::score`] divides the raw
+    /// output by [`Self::FEATURE_SCALE`] and clamps it to `[0, MAX_SCORE]`.
+    pub fn explain(&self, normalized: &ValidatorFeatureVector) -> Explanation {
+        self.model
+            .explain(&normalized.scaled(Self::feature_scale()))
+    }
+
     /// Compute the canonical BLAKE3 hash for the wrapped model.
     pub fn hash_hex(&self) -> Result<String> {
         self.model.hash_hex().map_err(AiCoreError::from)
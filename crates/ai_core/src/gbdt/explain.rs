@@ -0,0 +1,241 @@
+//! Per-feature score attributions
+//!
+//! Decomposes [`Model::score`] into a base value plus one contribution per
+//! feature using leaf-path attribution: every split on the decision path is
+//! credited with the change in the expected output of the subtree it leads
+//! into. The model format stores no per-node sample counts, so a subtree's
+//! expected output is the mean of its leaf values, each leaf weighted
+//! equally.
+//!
+//! Expectations are converted to score units with the same
+//! `value * tree.weight / scale` step that inference uses, so the attribution
+//! is exact: `base + contributions.sum() == model.score(features)`.
+
+use super::model::Model;
+use super::tree::Tree;
+use serde::{Deserialize, Serialize};
+
+/// One split on a tree's decision path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    /// Index of the split node
+    pub node: usize,
+    pub feature: usize,
+    pub threshold: i64,
+    /// Whether the feature value was `<= threshold`
+    pub went_left: bool,
+    /// Change in the expected output, in score units
+    pub delta: i64,
+}
+
+/// Decision path through one tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreePath {
+    pub tree: usize,
+    /// Expected output of the whole tree, in score units
+    pub expected: i64,
+    /// Output of the reached leaf, in score units
+    pub value: i64,
+    pub steps: Vec<PathStep>,
+}
+
+/// Exact additive breakdown of a model score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    /// Equal to [`Model::score`] for the explained features
+    pub score: i64,
+    /// Model bias plus the expected output of every tree
+    pub base: i64,
+    /// Contribution of each input feature, indexed like the feature vector
+    pub contributions: Vec<i64>,
+    pub paths: Vec<TreePath>,
+}
+
+impl Model {
+    /// Attribute the score of `features` to individual features.
+    pub fn explain(&self, features: &[i64]) -> Explanation {
+        let mut base = self.bias;
+        let mut contributions = vec![0i64; features.len()];
+        let mut paths = Vec::with_capacity(self.trees.len());
+
+        for (index, tree) in self.trees.iter().enumerate() {
+            let to_score = |value: i64| value.checked_mul(tree.weight).unwrap_or(0) / self.scale;
+            let expected = expected_values(tree);
+            let root = to_score(expected.first().copied().unwrap_or(0));
+
+            // Malformed trees score 0 and are left out of the attribution.
+            let Some(path) = decision_path(tree, features) else {
+                paths.push(TreePath {
+                    tree: index,
+                    expected: 0,
+                    value: 0,
+                    steps: Vec::new(),
+                });
+                continue;
+            };
+
+            let mut steps = Vec::with_capacity(path.len().saturating_sub(1));
+            let mut current = root;
+            for pair in path.windows(2) {
+                let (node, next) = (pair[0], pair[1]);
+                let split = &tree.nodes[node];
+                let feature = split.feature_idx as usize;
+                let reached = to_score(expected[next]);
+                let delta = reached - current;
+                contributions[feature] = contributions[feature].saturating_add(delta);
+                steps.push(PathStep {
+                    node,
+                    feature,
+                    threshold: split.threshold,
+                    went_left: next as i32 == split.left,
+                    delta,
+                });
+                current = reached;
+            }
+
+            base = base.saturating_add(root);
+            paths.push(TreePath {
+                tree: index,
+                expected: root,
+                value: current,
+                steps,
+            });
+        }
+
+        Explanation {
+            score: self.score(features),
+            base,
+            contributions,
+            paths,
+        }
+    }
+}
+
+/// Node indices visited by [`Tree::evaluate`], ending at a leaf; `None` when
+/// evaluation falls back to 0 because the tree is malformed.
+fn decision_path(tree: &Tree, features: &[i64]) -> Option<Vec<usize>> {
+    let mut path = Vec::new();
+    let mut idx = 0usize;
+    loop {
+        let node = tree.nodes.get(idx)?;
+        path.push(idx);
+        if node.is_leaf() {
+            return node.leaf_value().map(|_| path);
+        }
+        if path.len() > tree.nodes.len() {
+            return None;
+        }
+
+        let value = *features.get(node.feature_idx as usize)?;
+        let next = if value <= node.threshold {
+            node.left
+        } else {
+            node.right
+        };
+        if next < 0 || next as usize >= tree.nodes.len() {
+            return None;
+        }
+        idx = next as usize;
+    }
+}
+
+/// Mean leaf value below every node (0 for unreachable or malformed nodes).
+fn expected_values(tree: &Tree) -> Vec<i64> {
+    let mut sums: Vec<Option<(i128, i128)>> = vec![None; tree.nodes.len()];
+    for idx in 0..tree.nodes.len() {
+        subtree_leaves(tree, idx, &mut sums, 0);
+    }
+    sums.into_iter()
+        .map(|entry| match entry {
+            Some((sum, count)) if count > 0 => sum.div_euclid(count) as i64,
+            _ => 0,
+        })
+        .collect()
+}
+
+/// Sum and count of the leaf values below `idx`, memoized in `sums`.
+fn subtree_leaves(
+    tree: &Tree,
+    idx: usize,
+    sums: &mut Vec<Option<(i128, i128)>>,
+    depth: usize,
+) -> (i128, i128) {
+    if let Some(done) = sums[idx] {
+        return done;
+    }
+    let node = &tree.nodes[idx];
+    let result = if node.is_leaf() {
+        (node.leaf_value().unwrap_or(0) as i128, 1)
+    } else if depth > tree.nodes.len() {
+        (0, 0)
+    } else {
+        let mut total = (0, 0);
+        for child in [node.left, node.right] {
+            if child >= 0 && (child as usize) < tree.nodes.len() {
+                let (sum, count) = subtree_leaves(tree, child as usize, sums, depth + 1);
+                total = (total.0 + sum, total.1 + count);
+            }
+        }
+        total
+    };
+    sums[idx] = Some(result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gbdt::tree::Node;
+    use crate::gbdt::SCALE;
+
+    fn two_level_tree() -> Tree {
+        Tree::new(
+            vec![
+                Node::internal(0, 0, 50, 1, 2),
+                Node::internal(1, 1, 10, 3, 4),
+                Node::leaf(2, 900),
+                Node::leaf(3, 100),
+                Node::leaf(4, 200),
+            ],
+            SCALE,
+        )
+    }
+
+    #[test]
+    fn test_path_attribution_is_exact() {
+        let model = Model::new(vec![two_level_tree()], 7);
+        let explanation = model.explain(&[30, 20]);
+
+        // Expected outputs: root (900+100+200)/3 = 400, node 1 = 150, leaf 4 = 200.
+        assert_eq!(explanation.score, 207);
+        assert_eq!(explanation.base, 7 + 400);
+        assert_eq!(explanation.contributions, vec![-250, 50]);
+        assert_eq!(explanation.paths[0].steps.len(), 2);
+        assert!(explanation.paths[0].steps[0].went_left);
+        assert!(!explanation.paths[0].steps[1].went_left);
+    }
+
+    #[test]
+    fn test_contributions_sum_to_score_with_tree_weights() {
+        let mut second = two_level_tree();
+        second.weight = SCALE / 3;
+        let model = Model::new(vec![two_level_tree(), second], -5);
+
+        for features in [[0, 0], [30, 20], [60, 5], [51, 11]] {
+            let explanation = model.explain(&features);
+            let total: i64 = explanation.base + explanation.contributions.iter().sum::<i64>();
+            assert_eq!(total, model.score(&features), "features {features:?}");
+        }
+    }
+
+    #[test]
+    fn test_malformed_tree_contributes_nothing() {
+        let broken = Tree::new(vec![Node::internal(0, 4, 10, 1, 2)], SCALE);
+        let model = Model::new(vec![broken], 3);
+        let explanation = model.explain(&[1, 2]);
+
+        assert_eq!(explanation.score, 3);
+        assert_eq!(explanation.base, 3);
+        assert_eq!(explanation.contributions, vec![0, 0]);
+    }
+}
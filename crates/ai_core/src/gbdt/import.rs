@@ -0,0 +1,1094 @@
+//! Import of LightGBM and XGBoost models
+//!
+//! Converts tree ensembles exported by standard training tooling into the
+//! integer [`Model`] format. Supported inputs:
+//!
+//! - LightGBM text models (`booster.save_model("model.txt")`)
+//! - LightGBM JSON dumps (`booster.dump_model()`)
+//! - XGBoost JSON dumps (`booster.dump_model(path, dump_format="json")`)
+//! - XGBoost JSON models (`booster.save_model("model.json")`)
+//!
+//! Leaf values and the bias are quantized to `SCALE`, thresholds to the
+//! caller's feature scale. LightGBM sends `x <= threshold` left, matching
+//! inference directly; XGBoost sends `x < threshold` left, so its thresholds
+//! become the largest integer strictly below the scaled split value.
+//! Missing-value routing is dropped because inference never sees missing
+//! features.
+//!
+//! This is offline tooling, so it reads the source floats as they are; only
+//! the emitted model is integer-only. [`ImportedModel::precision_report`]
+//! measures how far quantized inference drifts from the source model.
+
+use super::model::{Model, ModelError, ModelMetadata, SCALE};
+use super::tree::{Node, Tree};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Source model layouts understood by [`import_model`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceFormat {
+    LightgbmText,
+    LightgbmJson,
+    XgboostDump,
+    XgboostJson,
+}
+
+impl SourceFormat {
+    /// Guess the layout of `contents`.
+    pub fn detect(contents: &str) -> Option<Self> {
+        let trimmed = contents.trim_start();
+        if trimmed.starts_with('[') {
+            return Some(Self::XgboostDump);
+        }
+        if trimmed.starts_with('{') {
+            let value: Value = serde_json::from_str(trimmed).ok()?;
+            if value.get("tree_info").is_some() {
+                return Some(Self::LightgbmJson);
+            }
+            if value.get("learner").is_some() {
+                return Some(Self::XgboostJson);
+            }
+            return None;
+        }
+        trimmed
+            .lines()
+            .any(|line| line.starts_with("Tree="))
+            .then_some(Self::LightgbmText)
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::LightgbmText => "lightgbm-text",
+            Self::LightgbmJson => "lightgbm-json",
+            Self::XgboostDump => "xgboost-dump",
+            Self::XgboostJson => "xgboost-json",
+        }
+    }
+}
+
+impl fmt::Display for SourceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SourceFormat {
+    type Err = ModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lightgbm-text" => Ok(Self::LightgbmText),
+            "lightgbm-json" => Ok(Self::LightgbmJson),
+            "xgboost-dump" => Ok(Self::XgboostDump),
+            "xgboost-json" => Ok(Self::XgboostJson),
+            other => Err(invalid(format!("unknown source format `{other}`"))),
+        }
+    }
+}
+
+/// Options controlling quantization.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Multiplier applied to raw feature values before inference
+    pub feature_scale: i64,
+    /// Feature order of the emitted model; splits are remapped by name
+    pub feature_names: Option<Vec<String>>,
+    /// Margin added to every prediction. Overrides the base score of XGBoost
+    /// JSON models; required for XGBoost dumps, which do not record it.
+    pub base_score: Option<f64>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            feature_scale: SCALE,
+            feature_names: None,
+            base_score: None,
+        }
+    }
+}
+
+/// How closely the quantized model follows the source model on a sample set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrecisionReport {
+    pub samples: usize,
+    /// Largest `|source - quantized|` over all samples, in output units
+    pub max_abs_error: f64,
+    pub mean_abs_error: f64,
+    /// Samples for which at least one tree reached a different leaf
+    pub path_mismatches: usize,
+}
+
+/// Summary written next to an imported model.
+///
+/// `model_hash`, `sha256` and `size_bytes` are the artifact fields the
+/// registry's `ModelManifestEntry::from_gbdt_model` records for the same file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub source_format: SourceFormat,
+    pub objective: Option<String>,
+    pub feature_names: Vec<String>,
+    pub trees: usize,
+    pub nodes: usize,
+    /// BLAKE3 of the canonical JSON, as computed by `compute_model_hash`
+    pub model_hash: String,
+    /// SHA-256 of the canonical JSON, as recorded in model manifests
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub precision: Option<PrecisionReport>,
+}
+
+/// Result of [`import_model`]: the integer model plus the float source it
+/// was quantized from.
+#[derive(Debug, Clone)]
+pub struct ImportedModel {
+    pub model: Model,
+    pub format: SourceFormat,
+    /// Feature order of `model`; empty when the source named no features
+    pub feature_names: Vec<String>,
+    /// Objective as named by the source tooling
+    pub objective: Option<String>,
+    feature_scale: i64,
+    bias: f64,
+    trees: Vec<Vec<FlatNode>>,
+}
+
+impl ImportedModel {
+    /// Number of input columns the model reads.
+    pub fn num_features(&self) -> usize {
+        self.trees
+            .iter()
+            .flatten()
+            .filter_map(|node| node.split.map(|split| split.feature + 1))
+            .max()
+            .unwrap_or(0)
+            .max(self.feature_names.len())
+    }
+
+    /// Prediction of the source model, in output units.
+    pub fn predict_source(&self, row: &[f64]) -> f64 {
+        self.trees
+            .iter()
+            .map(|tree| tree[source_leaf(tree, row)].leaf)
+            .sum::<f64>()
+            + self.bias
+    }
+
+    /// Quantize raw feature values for [`Model::score`].
+    pub fn quantize_features(&self, row: &[f64]) -> Vec<i64> {
+        row.iter()
+            .map(|value| (value * self.feature_scale as f64).round() as i64)
+            .collect()
+    }
+
+    /// Compare source and quantized predictions over `samples`.
+    pub fn precision_report(&self, samples: &[Vec<f64>]) -> Result<PrecisionReport, ModelError> {
+        let needed = self.num_features();
+        let mut max_abs_error = 0.0f64;
+        let mut total_error = 0.0f64;
+        let mut path_mismatches = 0;
+
+        for (index, row) in samples.iter().enumerate() {
+            if row.len() < needed {
+                return Err(invalid(format!(
+                    "sample {index} has {} features, model reads {needed}",
+                    row.len()
+                )));
+            }
+            let quantized = self.quantize_features(row);
+            let expected = self.predict_source(row);
+            let actual = self.model.score(&quantized) as f64 / self.model.scale as f64;
+            let error = (expected - actual).abs();
+            max_abs_error = max_abs_error.max(error);
+            total_error += error;
+
+            let diverged = self
+                .trees
+                .iter()
+                .zip(&self.model.trees)
+                .any(|(source, tree)| source_leaf(source, row) != quantized_leaf(tree, &quantized));
+            if diverged {
+                path_mismatches += 1;
+            }
+        }
+
+        Ok(PrecisionReport {
+            samples: samples.len(),
+            max_abs_error,
+            mean_abs_error: if samples.is_empty() {
+                0.0
+            } else {
+                total_error / samples.len() as f64
+            },
+            path_mismatches,
+        })
+    }
+
+    /// Summarize the canonical artifact, optionally with a precision report.
+    pub fn report(&self, precision: Option<PrecisionReport>) -> Result<ImportReport, ModelError> {
+        let canonical = self.model.to_canonical_json()?;
+        Ok(ImportReport {
+            source_format: self.format,
+            objective: self.objective.clone(),
+            feature_names: self.feature_names.clone(),
+            trees: self.model.trees.len(),
+            nodes: self.model.trees.iter().map(|tree| tree.nodes.len()).sum(),
+            model_hash: self.model.hash_hex()?,
+            sha256: hex::encode(Sha256::digest(canonical.as_bytes())),
+            size_bytes: canonical.len() as u64,
+            precision,
+        })
+    }
+}
+
+/// Parse `contents` as `format` and quantize it into a [`Model`].
+pub fn import_model(
+    contents: &str,
+    format: SourceFormat,
+    options: &ImportOptions,
+) -> Result<ImportedModel, ModelError> {
+    if options.feature_scale <= 0 {
+        return Err(invalid(format!(
+            "feature scale must be positive, got {}",
+            options.feature_scale
+        )));
+    }
+
+    let mut source = match format {
+        SourceFormat::LightgbmText => parse_lightgbm_text(contents)?,
+        SourceFormat::LightgbmJson => parse_lightgbm_json(contents)?,
+        SourceFormat::XgboostDump => parse_xgboost_dump(contents, options)?,
+        SourceFormat::XgboostJson => parse_xgboost_json(contents)?,
+    };
+    if let Some(base_score) = options.base_score {
+        source.bias = base_score;
+    }
+    if let Some(names) = &options.feature_names {
+        source.remap_features(names)?;
+    }
+
+    let trees: Vec<Vec<FlatNode>> = source.trees.iter().map(flatten).collect();
+    let quantized = trees
+        .iter()
+        .map(|tree| quantize_tree(tree, options.feature_scale))
+        .collect();
+    let mut model = Model::new(quantized, quantize(source.bias, SCALE as f64));
+    if let Some(objective) = &source.objective {
+        model = model.with_metadata(ModelMetadata::new(objective_name(objective)));
+    }
+    model.validate()?;
+
+    Ok(ImportedModel {
+        model,
+        format,
+        feature_names: source.feature_names,
+        objective: source.objective,
+        feature_scale: options.feature_scale,
+        bias: source.bias,
+        trees,
+    })
+}
+
+/// Map a source objective to the name used by the native trainer.
+fn objective_name(objective: &str) -> &str {
+    match objective {
+        "regression" | "regression_l2" | "l2" | "mse" | "reg:squarederror" | "reg:linear" => {
+            "squared_error"
+        }
+        "binary" | "cross_entropy" | "binary:logistic" | "binary:logitraw" | "reg:logistic" => {
+            "logistic"
+        }
+        "lambdarank" | "rank_xendcg" | "rank:pairwise" | "rank:ndcg" | "rank:map" => {
+            "pairwise_ranking"
+        }
+        other => other,
+    }
+}
+
+fn invalid(message: impl Into<String>) -> ModelError {
+    ModelError::InvalidFormat(message.into())
+}
+
+fn quantize(value: f64, scale: f64) -> i64 {
+    (value * scale).round() as i64
+}
+
+#[derive(Debug, Clone)]
+enum SourceNode {
+    Split {
+        feature: usize,
+        threshold: f64,
+        /// `x < threshold` goes left instead of `x <= threshold`
+        strict: bool,
+        left: Box<SourceNode>,
+        right: Box<SourceNode>,
+    },
+    Leaf(f64),
+}
+
+#[derive(Debug)]
+struct SourceModel {
+    trees: Vec<SourceNode>,
+    bias: f64,
+    feature_names: Vec<String>,
+    objective: Option<String>,
+}
+
+impl SourceModel {
+    /// Renumber split features to follow `names`.
+    fn remap_features(&mut self, names: &[String]) -> Result<(), ModelError> {
+        if !self.feature_names.is_empty() {
+            let mapping = self
+                .feature_names
+                .iter()
+                .map(|name| {
+                    names
+                        .iter()
+                        .position(|candidate| candidate == name)
+                        .ok_or_else(|| invalid(format!("feature `{name}` missing from --features")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for tree in &mut self.trees {
+                remap_tree(tree, &mapping)?;
+            }
+        }
+        self.feature_names = names.to_vec();
+        Ok(())
+    }
+}
+
+fn remap_tree(node: &mut SourceNode, mapping: &[usize]) -> Result<(), ModelError> {
+    if let SourceNode::Split {
+        feature,
+        left,
+        right,
+        ..
+    } = node
+    {
+        *feature = *mapping
+            .get(*feature)
+            .ok_or_else(|| invalid(format!("split on unnamed feature {feature}")))?;
+        remap_tree(left, mapping)?;
+        remap_tree(right, mapping)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FlatSplit {
+    feature: usize,
+    threshold: f64,
+    strict: bool,
+    left: usize,
+    right: usize,
+}
+
+/// Source node laid out in the same pre-order as the emitted [`Tree`].
+#[derive(Debug, Clone)]
+struct FlatNode {
+    split: Option<FlatSplit>,
+    leaf: f64,
+}
+
+fn flatten(root: &SourceNode) -> Vec<FlatNode> {
+    fn push(node: &SourceNode, out: &mut Vec<FlatNode>) -> usize {
+        let index = out.len();
+        match node {
+            SourceNode::Leaf(value) => out.push(FlatNode {
+                split: None,
+                leaf: *value,
+            }),
+            SourceNode::Split {
+                feature,
+                threshold,
+                strict,
+                left,
+                right,
+            } => {
+                out.push(FlatNode {
+                    split: None,
+                    leaf: 0.0,
+                });
+                let left = push(left, out);
+                let right = push(right, out);
+                out[index].split = Some(FlatSplit {
+                    feature: *feature,
+                    threshold: *threshold,
+                    strict: *strict,
+                    left,
+                    right,
+                });
+            }
+        }
+        index
+    }
+
+    let mut out = Vec::new();
+    push(root, &mut out);
+    out
+}
+
+fn quantize_tree(nodes: &[FlatNode], feature_scale: i64) -> Tree {
+    let nodes = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| match node.split {
+            None => Node::leaf(index as i32, quantize(node.leaf, SCALE as f64)),
+            Some(split) => {
+                let scaled = split.threshold * feature_scale as f64;
+                let threshold = if split.strict {
+                    (scaled.ceil() as i64).saturating_sub(1)
+                } else {
+                    scaled.floor() as i64
+                };
+                Node::internal(
+                    index as i32,
+                    split.feature as i32,
+                    threshold,
+                    split.left as i32,
+                    split.right as i32,
+                )
+            }
+        })
+        .collect();
+    Tree::new(nodes, SCALE)
+}
+
+fn source_leaf(nodes: &[FlatNode], row: &[f64]) -> usize {
+    let mut index = 0;
+    while let Some(split) = nodes[index].split {
+        let value = row.get(split.feature).copied().unwrap_or(0.0);
+        let left = if split.strict {
+            value < split.threshold
+        } else {
+            value <= split.threshold
+        };
+        index = if left { split.left } else { split.right };
+    }
+    index
+}
+
+fn quantized_leaf(tree: &Tree, features: &[i64]) -> usize {
+    let mut index = 0;
+    while let Some(node) = tree.nodes.get(index).filter(|node| !node.is_leaf()) {
+        let value = features
+            .get(node.feature_idx as usize)
+            .copied()
+            .unwrap_or(0);
+        index = if value <= node.threshold {
+            node.left as usize
+        } else {
+            node.right as usize
+        };
+    }
+    index
+}
+
+fn parse_lightgbm_text(contents: &str) -> Result<SourceModel, ModelError> {
+    let mut header: Vec<(&str, &str)> = Vec::new();
+    let mut blocks: Vec<Vec<(&str, &str)>> = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line == "end of trees" {
+            break;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key == "Tree" {
+            blocks.push(Vec::new());
+        } else if let Some(block) = blocks.last_mut() {
+            block.push((key, value));
+        } else {
+            header.push((key, value));
+        }
+    }
+
+    if let Some(classes) = lookup(&header, "num_class") {
+        if classes.trim() != "1" {
+            return Err(invalid(format!(
+                "multiclass models are not supported ({classes} classes)"
+            )));
+        }
+    }
+
+    let trees = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| {
+            let field = |key: &str| lookup(block, key).unwrap_or_default();
+            LightgbmTextTree::parse(field)
+                .and_then(|tree| tree.root())
+                .map_err(|err| invalid(format!("tree {index}: {err}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SourceModel {
+        trees,
+        bias: 0.0,
+        feature_names: lookup(&header, "feature_names")
+            .map(|names| names.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        objective: lookup(&header, "objective")
+            .and_then(|objective| objective.split_whitespace().next().map(str::to_string)),
+    })
+}
+
+fn lookup(entries: &[(&str, &str)], key: &str) -> Option<String> {
+    entries
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value.to_string())
+}
+
+fn parse_list<T: FromStr>(list: &str) -> Result<Vec<T>, String> {
+    list.split_whitespace()
+        .map(|item| item.parse().map_err(|_| format!("invalid value `{item}`")))
+        .collect()
+}
+
+/// Parallel per-split arrays of one LightGBM text tree.
+struct LightgbmTextTree {
+    features: Vec<usize>,
+    thresholds: Vec<f64>,
+    decisions: Vec<u32>,
+    lefts: Vec<i64>,
+    rights: Vec<i64>,
+    leaves: Vec<f64>,
+}
+
+impl LightgbmTextTree {
+    fn parse(field: impl Fn(&str) -> String) -> Result<Self, String> {
+        Ok(Self {
+            features: parse_list(&field("split_feature"))?,
+            thresholds: parse_list(&field("threshold"))?,
+            decisions: parse_list(&field("decision_type"))?,
+            lefts: parse_list(&field("left_child"))?,
+            rights: parse_list(&field("right_child"))?,
+            leaves: parse_list(&field("leaf_value"))?,
+        })
+    }
+
+    fn root(&self) -> Result<SourceNode, String> {
+        if self.features.is_empty() {
+            return self
+                .leaves
+                .first()
+                .map(|value| SourceNode::Leaf(*value))
+                .ok_or_else(|| "tree has no leaves".to_string());
+        }
+        self.build(0, 0)
+    }
+
+    /// Children >= 0 are split indices, negative children encode leaf `!index`.
+    fn build(&self, child: i64, depth: usize) -> Result<SourceNode, String> {
+        if child < 0 {
+            let leaf = (!child) as usize;
+            return self
+                .leaves
+                .get(leaf)
+                .map(|value| SourceNode::Leaf(*value))
+                .ok_or_else(|| format!("missing leaf {leaf}"));
+        }
+        let split = child as usize;
+        if depth > self.features.len() {
+            return Err("cycle in tree structure".to_string());
+        }
+        let missing = || format!("split {split} is incomplete");
+        if self.decisions.get(split).ok_or_else(missing)? & 1 != 0 {
+            return Err(format!("split {split} is categorical"));
+        }
+        let left = *self.lefts.get(split).ok_or_else(missing)?;
+        let right = *self.rights.get(split).ok_or_else(missing)?;
+        Ok(SourceNode::Split {
+            feature: *self.features.get(split).ok_or_else(missing)?,
+            threshold: *self.thresholds.get(split).ok_or_else(missing)?,
+            strict: false,
+            left: Box::new(self.build(left, depth + 1)?),
+            right: Box::new(self.build(right, depth + 1)?),
+        })
+    }
+}
+
+fn parse_json(contents: &str) -> Result<Value, ModelError> {
+    Ok(serde_json::from_str(contents)?)
+}
+
+fn number(value: &Value, key: &str) -> Result<f64, ModelError> {
+    match value.get(key) {
+        Some(Value::Number(number)) => number
+            .as_f64()
+            .ok_or_else(|| invalid(format!("`{key}` is not a number"))),
+        // Both tools print non-finite values as strings.
+        Some(Value::String(text)) => text
+            .trim_matches(|c| c == '[' || c == ']')
+            .parse()
+            .map_err(|_| invalid(format!("`{key}` is not a number: {text}"))),
+        _ => Err(invalid(format!("missing `{key}`"))),
+    }
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_lightgbm_json(contents: &str) -> Result<SourceModel, ModelError> {
+    let root = parse_json(contents)?;
+    if let Some(classes) = root.get("num_class").and_then(Value::as_u64) {
+        if classes != 1 {
+            return Err(invalid(format!(
+                "multiclass models are not supported ({classes} classes)"
+            )));
+        }
+    }
+
+    let trees = root
+        .get("tree_info")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("missing `tree_info`"))?
+        .iter()
+        .map(|info| {
+            info.get("tree_structure")
+                .ok_or_else(|| invalid("missing `tree_structure`"))
+                .and_then(|node| lightgbm_json_node(node, 0))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SourceModel {
+        trees,
+        bias: 0.0,
+        feature_names: string_list(root.get("feature_names")),
+        objective: root
+            .get("objective")
+            .and_then(Value::as_str)
+            .and_then(|objective| objective.split_whitespace().next())
+            .map(str::to_string),
+    })
+}
+
+fn lightgbm_json_node(node: &Value, depth: usize) -> Result<SourceNode, ModelError> {
+    if node.get("split_feature").is_none() {
+        return Ok(SourceNode::Leaf(number(node, "leaf_value")?));
+    }
+    if depth > 1024 {
+        return Err(invalid("tree is too deep"));
+    }
+    match node.get("decision_type").and_then(Value::as_str) {
+        Some("<=") | None => {}
+        Some(other) => return Err(invalid(format!("unsupported decision type `{other}`"))),
+    }
+    let child = |key: &str| {
+        node.get(key)
+            .ok_or_else(|| invalid(format!("missing `{key}`")))
+            .and_then(|child| lightgbm_json_node(child, depth + 1))
+    };
+    Ok(SourceNode::Split {
+        feature: number(node, "split_feature")? as usize,
+        threshold: number(node, "threshold")?,
+        strict: false,
+        left: Box::new(child("left_child")?),
+        right: Box::new(child("right_child")?),
+    })
+}
+
+fn parse_xgboost_dump(contents: &str, options: &ImportOptions) -> Result<SourceModel, ModelError> {
+    let root = parse_json(contents)?;
+    let names = options.feature_names.clone().unwrap_or_default();
+    let trees = root
+        .as_array()
+        .ok_or_else(|| invalid("XGBoost dump must be a JSON array of trees"))?
+        .iter()
+        .map(|tree| xgboost_dump_node(tree, &names, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Splits are resolved against the requested order directly, so the
+    // names are reported but need no remapping.
+    Ok(SourceModel {
+        trees,
+        bias: 0.0,
+        feature_names: Vec::new(),
+        objective: None,
+    })
+}
+
+fn xgboost_dump_node(
+    node: &Value,
+    names: &[String],
+    depth: usize,
+) -> Result<SourceNode, ModelError> {
+    if node.get("leaf").is_some() {
+        return Ok(SourceNode::Leaf(number(node, "leaf")?));
+    }
+    if depth > 1024 {
+        return Err(invalid("tree is too deep"));
+    }
+
+    let split = node
+        .get("split")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("missing `split`"))?;
+    let feature = names
+        .iter()
+        .position(|name| name == split)
+        .or_else(|| split.strip_prefix('f').and_then(|index| index.parse().ok()))
+        .ok_or_else(|| invalid(format!("unknown feature `{split}`; pass the feature names")))?;
+
+    let children = node
+        .get("children")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("missing `children`"))?;
+    let child = |key: &str| {
+        let id = node
+            .get(key)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid(format!("missing `{key}`")))?;
+        let child = children
+            .iter()
+            .find(|child| child.get("nodeid").and_then(Value::as_u64) == Some(id))
+            .ok_or_else(|| invalid(format!("missing child node {id}")))?;
+        xgboost_dump_node(child, names, depth + 1)
+    };
+
+    Ok(SourceNode::Split {
+        feature,
+        threshold: number(node, "split_condition")?,
+        strict: true,
+        left: Box::new(child("yes")?),
+        right: Box::new(child("no")?),
+    })
+}
+
+fn parse_xgboost_json(contents: &str) -> Result<SourceModel, ModelError> {
+    let root = parse_json(contents)?;
+    let learner = root
+        .get("learner")
+        .ok_or_else(|| invalid("missing `learner`"))?;
+    let booster = learner
+        .get("gradient_booster")
+        .ok_or_else(|| invalid("missing `gradient_booster`"))?;
+    match booster.get("name").and_then(Value::as_str) {
+        Some("gbtree") => {}
+        other => {
+            return Err(invalid(format!(
+                "unsupported booster `{}`",
+                other.unwrap_or("unknown")
+            )))
+        }
+    }
+
+    let params = learner
+        .get("learner_model_param")
+        .ok_or_else(|| invalid("missing `learner_model_param`"))?;
+    let classes = number(params, "num_class").unwrap_or(0.0);
+    if classes > 1.0 {
+        return Err(invalid(format!(
+            "multiclass models are not supported ({classes} classes)"
+        )));
+    }
+
+    let objective = learner
+        .get("objective")
+        .and_then(|objective| objective.get("name"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let base_score = number(params, "base_score").unwrap_or(0.0);
+    // The base score is stored as a probability for logistic objectives.
+    let bias = match objective.as_deref() {
+        Some("binary:logistic" | "reg:logistic") => (base_score / (1.0 - base_score)).ln(),
+        _ => base_score,
+    };
+
+    let trees = booster
+        .get("model")
+        .and_then(|model| model.get("trees"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("missing `model.trees`"))?
+        .iter()
+        .enumerate()
+        .map(|(index, tree)| {
+            xgboost_json_tree(tree).map_err(|err| invalid(format!("tree {index}: {err}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SourceModel {
+        trees,
+        bias,
+        feature_names: string_list(learner.get("feature_names")),
+        objective,
+    })
+}
+
+fn xgboost_json_tree(tree: &Value) -> Result<SourceNode, String> {
+    let array = |key: &str| {
+        tree.get(key)
+            .and_then(Value::as_array)
+            .ok_or_else(|| format!("missing `{key}`"))
+    };
+    let lefts: Vec<i64> = array("left_children")?
+        .iter()
+        .filter_map(Value::as_i64)
+        .collect();
+    let rights: Vec<i64> = array("right_children")?
+        .iter()
+        .filter_map(Value::as_i64)
+        .collect();
+    let features: Vec<u64> = array("split_indices")?
+        .iter()
+        .filter_map(Value::as_u64)
+        .collect();
+    let conditions: Vec<f64> = array("split_conditions")?
+        .iter()
+        .filter_map(Value::as_f64)
+        .collect();
+    if let Ok(types) = array("split_type") {
+        if types.iter().any(|kind| kind.as_u64() != Some(0)) {
+            return Err("categorical splits are not supported".to_string());
+        }
+    }
+
+    // Leaves have no children and keep their value in `split_conditions`.
+    fn build(
+        index: usize,
+        depth: usize,
+        arrays: (&[i64], &[i64], &[u64], &[f64]),
+    ) -> Result<SourceNode, String> {
+        let (lefts, rights, features, conditions) = arrays;
+        let missing = || format!("node {index} is incomplete");
+        let condition = *conditions.get(index).ok_or_else(missing)?;
+        let left = *lefts.get(index).ok_or_else(missing)?;
+        if left < 0 {
+            return Ok(SourceNode::Leaf(condition));
+        }
+        if depth > lefts.len() {
+            return Err("cycle in tree structure".to_string());
+        }
+        let right = *rights.get(index).ok_or_else(missing)?;
+        if right < 0 {
+            return Err(missing());
+        }
+        Ok(SourceNode::Split {
+            feature: *features.get(index).ok_or_else(missing)? as usize,
+            threshold: condition,
+            strict: true,
+            left: Box::new(build(left as usize, depth + 1, arrays)?),
+            right: Box::new(build(right as usize, depth + 1, arrays)?),
+        })
+    }
+
+    build(0, 0, (&lefts, &rights, &features, &conditions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIGHTGBM_TEXT: &str = "tree
+version=v4
+num_class=1
+num_tree_per_iteration=1
+max_feature_idx=1
+objective=regression
+feature_names=uptime latency
+
+Tree=0
+num_leaves=3
+num_cat=0
+split_feature=0 1
+threshold=0.5 0.25
+decision_type=2 2
+left_child=-1 -2
+right_child=1 -3
+leaf_value=0.125 -0.5 0.75
+shrinkage=1
+
+Tree=1
+num_leaves=1
+leaf_value=0.0625
+shrinkage=0.1
+
+end of trees
+";
+
+    const XGBOOST_DUMP: &str = r#"[
+      {"nodeid":0,"depth":0,"split":"f1","split_condition":0.3,"yes":1,"no":2,"missing":1,
+       "children":[{"nodeid":1,"leaf":-0.2},{"nodeid":2,"leaf":0.4}]}
+    ]"#;
+
+    fn samples() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.1, 0.9],
+            vec![0.5, 0.25],
+            vec![0.75, 0.1],
+            vec![0.9, 0.3],
+            vec![0.6, 0.2999],
+        ]
+    }
+
+    #[test]
+    fn test_lightgbm_text_matches_source_predictions() {
+        let imported = import_model(
+            LIGHTGBM_TEXT,
+            SourceFormat::LightgbmText,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(imported.feature_names, vec!["uptime", "latency"]);
+        assert_eq!(
+            imported.model.metadata,
+            Some(ModelMetadata::new("squared_error"))
+        );
+        assert_eq!(imported.model.trees[0].nodes[0].threshold, 500_000);
+
+        // Feature values exactly on a threshold still go left.
+        let report = imported.precision_report(&samples()).unwrap();
+        assert_eq!(report.samples, 5);
+        assert_eq!(report.path_mismatches, 0);
+        assert!(report.max_abs_error < 1e-6, "{report:?}");
+        assert_eq!(
+            imported
+                .model
+                .score(&imported.quantize_features(&[0.5, 0.25])),
+            125_000 + 62_500
+        );
+    }
+
+    #[test]
+    fn test_lightgbm_json_and_text_agree() {
+        let json = r#"{
+          "name":"tree","version":"v4","num_class":1,"objective":"regression",
+          "feature_names":["uptime","latency"],
+          "tree_info":[
+            {"tree_index":0,"shrinkage":1,"tree_structure":{
+              "split_index":0,"split_feature":0,"threshold":0.5,"decision_type":"<=",
+              "left_child":{"leaf_index":0,"leaf_value":0.125},
+              "right_child":{"split_index":1,"split_feature":1,"threshold":0.25,"decision_type":"<=",
+                "left_child":{"leaf_index":1,"leaf_value":-0.5},
+                "right_child":{"leaf_index":2,"leaf_value":0.75}}}},
+            {"tree_index":1,"shrinkage":0.1,"tree_structure":{"leaf_value":0.0625}}
+          ]}"#;
+
+        let options = ImportOptions::default();
+        let from_json = import_model(json, SourceFormat::LightgbmJson, &options).unwrap();
+        let from_text = import_model(LIGHTGBM_TEXT, SourceFormat::LightgbmText, &options).unwrap();
+        assert_eq!(from_json.model, from_text.model);
+        assert_eq!(
+            from_json.report(None).unwrap().model_hash,
+            from_text.model.hash_hex().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_xgboost_strict_split_is_preserved() {
+        let options = ImportOptions {
+            base_score: Some(0.5),
+            ..Default::default()
+        };
+        let imported = import_model(XGBOOST_DUMP, SourceFormat::XgboostDump, &options).unwrap();
+
+        // x < 0.3 goes left, so 0.3 itself must go right.
+        assert_eq!(imported.model.trees[0].nodes[0].threshold, 299_999);
+        assert_eq!(imported.model.bias, 500_000);
+        let at_threshold = imported.quantize_features(&[0.0, 0.3]);
+        assert_eq!(imported.model.score(&at_threshold), 900_000);
+
+        let report = imported.precision_report(&samples()).unwrap();
+        assert_eq!(report.path_mismatches, 0);
+    }
+
+    #[test]
+    fn test_xgboost_json_model_with_named_features() {
+        let json = r#"{"learner":{
+          "feature_names":["latency","uptime"],
+          "learner_model_param":{"base_score":"5E-1","num_class":"0","num_feature":"2"},
+          "objective":{"name":"binary:logistic"},
+          "gradient_booster":{"name":"gbtree","model":{"trees":[{
+            "left_children":[1,-1,-1],"right_children":[2,-1,-1],
+            "split_indices":[0,0,0],"split_conditions":[0.3,-0.2,0.4],
+            "split_type":[0,0,0]}]}}}}"#;
+        let options = ImportOptions {
+            feature_names: Some(vec!["uptime".into(), "latency".into()]),
+            ..Default::default()
+        };
+        let imported = import_model(json, SourceFormat::XgboostJson, &options).unwrap();
+
+        assert_eq!(SourceFormat::detect(json), Some(SourceFormat::XgboostJson));
+        assert_eq!(imported.model.trees[0].nodes[0].feature_idx, 1);
+        assert_eq!(imported.model.bias, 0);
+        assert_eq!(
+            imported.model.metadata,
+            Some(ModelMetadata::new("logistic"))
+        );
+        assert_eq!(
+            imported
+                .precision_report(&samples())
+                .unwrap()
+                .path_mismatches,
+            0
+        );
+    }
+
+    #[test]
+    fn test_precision_report_flags_divergent_paths() {
+        let options = ImportOptions {
+            feature_scale: 10,
+            ..Default::default()
+        };
+        let imported = import_model(LIGHTGBM_TEXT, SourceFormat::LightgbmText, &options).unwrap();
+
+        // 0.54 rounds to 5 at scale 10 and crosses the 0.5 split.
+        let report = imported.precision_report(&[vec![0.54, 0.9]]).unwrap();
+        assert_eq!(report.path_mismatches, 1);
+        assert!((report.max_abs_error - 0.625).abs() < 1e-9);
+
+        assert!(imported.precision_report(&[vec![0.1]]).is_err());
+    }
+
+    #[test]
+    fn test_format_detection_and_rejections() {
+        assert_eq!(
+            SourceFormat::detect(LIGHTGBM_TEXT),
+            Some(SourceFormat::LightgbmText)
+        );
+        assert_eq!(
+            SourceFormat::detect(XGBOOST_DUMP),
+            Some(SourceFormat::XgboostDump)
+        );
+        assert_eq!(
+            "lightgbm-json".parse::<SourceFormat>().unwrap(),
+            SourceFormat::LightgbmJson
+        );
+
+        let categorical = LIGHTGBM_TEXT.replace("decision_type=2 2", "decision_type=1 2");
+        assert!(import_model(
+            &categorical,
+            SourceFormat::LightgbmText,
+            &ImportOptions::default()
+        )
+        .is_err());
+        let multiclass = LIGHTGBM_TEXT.replace("num_class=1", "num_class=3");
+        assert!(import_model(
+            &multiclass,
+            SourceFormat::LightgbmText,
+            &ImportOptions::default()
+        )
+        .is_err());
+        let unnamed = ImportOptions {
+            feature_names: Some(vec!["other".into()]),
+            ..Default::default()
+        };
+        assert!(import_model(LIGHTGBM_TEXT, SourceFormat::LightgbmText, &unnamed).is_err());
+    }
+}
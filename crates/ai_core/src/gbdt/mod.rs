@@ -32,6 +32,10 @@
 //! }
 //! ```
 //!
+//! Models produced by the trainer additionally carry
+//! `"metadata": {"objective": "squared_error"}`; the key is omitted entirely
+//! when no metadata is attached.
+//!
 //! # Usage
 //!
 //! ```rust,no_run
@@ -65,11 +69,17 @@
 //! - Hashing uses blake3 (pure Rust, deterministic)
 //! - No dependency on system locale, timezone, or random state
 
+pub mod explain;
+pub mod import;
 pub mod model;
 pub mod tree;
 
 // Re-export main types for convenience
-pub use model::{model_hash, model_hash_hex, Model, ModelError, SCALE};
+pub use explain::{Explanation, PathStep, TreePath};
+pub use import::{
+    import_model, ImportOptions, ImportReport, ImportedModel, PrecisionReport, SourceFormat,
+};
+pub use model::{model_hash, model_hash_hex, Model, ModelError, ModelMetadata, SCALE};
 pub use tree::{Node, Tree};
 
 #[cfg(test)]
@@ -55,6 +55,27 @@ pub struct Model {
 
     /// Post-processing scale factor (typically same as scale)
     pub post_scale: i64,
+
+    /// Training metadata. Omitted from the canonical JSON when absent so
+    /// models without it keep their original hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ModelMetadata>,
+}
+
+/// Provenance recorded by the trainer that produced a model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelMetadata {
+    /// Loss the trees were fitted against (e.g. `squared_error`, `logistic`)
+    pub objective: String,
+}
+
+impl ModelMetadata {
+    /// Metadata for a model trained against `objective`
+    pub fn new(objective: impl Into<String>) -> Self {
+        Self {
+            objective: objective.into(),
+        }
+    }
 }
 
 impl Model {
@@ -66,6 +87,7 @@ impl Model {
             trees,
             bias,
             post_scale: SCALE,
+            metadata: None,
         }
     }
 
@@ -77,9 +99,16 @@ impl Model {
             trees,
             bias,
             post_scale,
+            metadata: None,
         }
     }
 
+    /// Attach training metadata to the model
+    pub fn with_metadata(mut self, metadata: ModelMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
     /// Validate model structure
     pub fn validate(&self) -> Result<(), ModelError> {
         // Check version
@@ -194,6 +223,7 @@ pub fn model_hash(
         trees: trees.to_vec(),
         bias,
         post_scale,
+        metadata: None,
     };
     model.hash_hex()
 }
@@ -324,6 +354,24 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_metadata_is_hashed_only_when_present() {
+        let plain = create_test_model();
+        let json = plain.to_canonical_json().unwrap();
+        assert!(!json.contains("\"metadata\""));
+
+        let restored: Model = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.metadata, None);
+
+        let tagged = create_test_model().with_metadata(ModelMetadata::new("logistic"));
+        let json = tagged.to_canonical_json().unwrap();
+        assert!(json.contains("\"metadata\":{\"objective\":\"logistic\"}"));
+        assert_ne!(plain.hash_hex().unwrap(), tagged.hash_hex().unwrap());
+
+        let restored: Model = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, tagged);
+    }
+
     #[test]
     fn test_save_load_json() {
         use tempfile::NamedTempFile;
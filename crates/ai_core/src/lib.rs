@@ -8,6 +8,7 @@ pub mod config;
 pub mod deployment;
 pub mod determinism;
 pub mod deterministic_gbdt;
+pub mod drift;
 pub mod errors;
 pub mod execution;
 pub mod fairness;
@@ -38,6 +39,10 @@ pub use crate::deterministic_gbdt::{
     compute_scores, normalize_features as deterministic_normalize_features, DecisionNode,
     DeterministicGBDT, GBDTTree, ValidatorFeatures,
 };
+pub use crate::drift::{
+    DistributionSummary, DriftConfig, DriftMonitor, DriftReport, FeatureDistribution, FeatureDrift,
+    QuantileSketch,
+};
 pub use crate::errors::{AiCoreError, Result as AiCoreResult};
 pub use crate::fairness::{DeterministicFairnessModel, ValidatorFeatureVector};
 pub use crate::features::{extract_features, FeatureConfig, FeatureVector, ValidatorTelemetry};
@@ -1,12 +1,39 @@
-use std::path::PathBuf;
+//! Regenerate `models/canonical_manifest.json`.
+//!
+//! The bundled deterministic reputation model is always included. Every
+//! `--model <path>` adds an integer `gbdt::Model` (as written by
+//! `ai-trainer train` or `import_gbdt`), keyed by the BLAKE3 digest
+//! consensus pins for it. The fairness input distribution `ai-trainer train`
+//! writes next to a model (`<name>.distribution.json`) is attached as the
+//! entry's training distribution:
+//!
+//! ```bash
+//! cargo run -p ippan-ai-registry --bin generate_manifest -- \
+//!     --model models/dlc/dlc_model_example.json
+//! ```
 
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
 use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use ippan_ai_core::drift::DistributionSummary;
+use ippan_ai_core::fairness::ValidatorFeatureVector;
 use ippan_ai_core::types::{ModelId, ModelMetadata};
 use ippan_ai_registry::manifest::{
     write_architecture_hash_files, ModelManifest, ModelManifestEntry, DEFAULT_INFERENCE_SEED,
 };
 
+#[derive(Parser, Debug)]
+#[command(name = "generate_manifest")]
+struct Opt {
+    /// `gbdt::Model` artifact to add, relative to the repository root
+    #[arg(long = "model")]
+    models: Vec<PathBuf>,
+}
+
 fn main() -> anyhow::Result<()> {
+    let opt = Opt::parse();
     let repo_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
     let models_dir = repo_root.join("models");
     let manifest_path = models_dir.join("canonical_manifest.json");
@@ -43,8 +70,22 @@ fn main() -> anyhow::Result<()> {
         DEFAULT_INFERENCE_SEED,
     )?;
 
+    let mut entries = vec![entry.clone()];
+    for model_path in &opt.models {
+        let mut gbdt_entry = ModelManifestEntry::from_gbdt_model(
+            gbdt_metadata(model_path, created_at),
+            model_path.clone(),
+            repo_root.clone(),
+            DEFAULT_INFERENCE_SEED,
+        )?;
+        if let Some(summary) = training_distribution(&repo_root.join(model_path))? {
+            gbdt_entry = gbdt_entry.with_training_distribution(summary);
+        }
+        entries.push(gbdt_entry);
+    }
+
     let generated_at = Utc::now().to_rfc3339();
-    let manifest = ModelManifest::new(1, generated_at, vec![entry.clone()]);
+    let manifest = ModelManifest::new(1, generated_at, entries);
     manifest.write_to_path(&manifest_path)?;
 
     let hash_targets = vec![
@@ -64,6 +105,43 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Fairness input distribution written by `ai-trainer train` next to the
+/// model, which must summarise the live fairness features.
+fn training_distribution(model_path: &Path) -> anyhow::Result<Option<DistributionSummary>> {
+    let path = model_path.with_extension("distribution.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let summary: DistributionSummary = serde_json::from_slice(&std::fs::read(&path)?)?;
+    summary
+        .require_features(&ValidatorFeatureVector::FEATURE_NAMES)
+        .with_context(|| format!("invalid training distribution {}", path.display()))?;
+    Ok(Some(summary))
+}
+
+fn gbdt_metadata(model_path: &Path, created_at: u64) -> ModelMetadata {
+    let name = model_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "gbdt_model".to_string());
+    ModelMetadata {
+        id: ModelId::new(&name, "1.0.0", ""),
+        name: name.clone(),
+        version: "1.0.0".to_string(),
+        description: "Integer gradient boosted decision tree fairness model.".to_string(),
+        author: "IPPAN Core Team".to_string(),
+        license: "Apache-2.0".to_string(),
+        tags: vec!["gbdt".to_string(), "fairness".to_string()],
+        created_at,
+        updated_at: created_at,
+        architecture: "gbdt".to_string(),
+        input_shape: Vec::new(),
+        output_shape: vec![1],
+        size_bytes: 0,
+        parameter_count: 0,
+    }
+}
+
 fn to_unix_micros(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> u64 {
     let naive = NaiveDate::from_ymd_opt(year, month, day)
         .expect("invalid date")
@@ -0,0 +1,443 @@
+//! Model artifact distribution over IPNDHT.
+//!
+//! Approved model artifacts are published as signed [`FileDescriptor`]s whose
+//! content hash is the artifact's canonical BLAKE3 digest, i.e. the `blake3`
+//! recorded in the [`ModelManifestEntry`] and the model hash governance
+//! approves. Artifacts are stored as a single chunk so the Merkle root of the
+//! content equals that plain digest.
+//!
+//! Only canonical `gbdt::Model` artifacts ([`GBDT_MODEL_FORMAT`], see
+//! [`ModelManifestEntry::from_gbdt_model`]) are published: they are what
+//! consensus loads and stages, and their BLAKE3 digest is the model hash the
+//! rollout checks.
+//!
+//! Governance entries point at a published artifact with an
+//! `ipndht://<file id>` model URL. [`ModelFetcher`] resolves such a URL through
+//! an [`ArtifactSource`], checks the bytes against the approved hash (and, for
+//! [`ModelPackage`] artifacts, with [`verify_model_hash`]) and keeps them in a
+//! local [`ModelArtifactCache`], so a model upgrade needs no redeploy.
+
+use crate::manifest::{
+    canonical_blake3, canonical_sha256, ModelArtifact, ModelManifestEntry, GBDT_MODEL_FORMAT,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use ed25519_dalek::SigningKey;
+use ippan_ai_core::gbdt::Model as GbdtModel;
+use ippan_ai_core::model::{verify_model_hash, ModelPackage};
+use ippan_files::content::MAX_CHUNK_SIZE;
+use ippan_files::descriptor::ContentHash;
+use ippan_files::{ContentManifest, ContentStore, FileDescriptor, FileId};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Tag carried by every published model artifact descriptor.
+pub const MODEL_ARTIFACT_TAG: &str = "ai-model";
+
+/// MIME type of published model artifacts.
+pub const MODEL_ARTIFACT_MIME: &str = "application/json";
+
+/// URL scheme used in governance entries for IPNDHT-hosted artifacts.
+pub const IPNDHT_URL_SCHEME: &str = "ipndht://";
+
+/// Model URL referencing the descriptor `id`.
+pub fn artifact_url(id: &FileId) -> String {
+    format!("{IPNDHT_URL_SCHEME}{}", id.to_hex())
+}
+
+/// Extract the descriptor id from an `ipndht://` model URL.
+pub fn parse_artifact_url(url: &str) -> Option<FileId> {
+    let id = url.strip_prefix(IPNDHT_URL_SCHEME)?;
+    FileId::from_hex(id.trim_end_matches('/')).ok()
+}
+
+/// Check that `bytes` hash to the approved model hash `expected` (BLAKE3).
+///
+/// Artifacts that parse as a [`ModelPackage`] must also carry a valid
+/// embedded hash.
+pub fn verify_artifact(expected: &[u8; 32], bytes: &[u8]) -> Result<()> {
+    let actual = canonical_blake3(bytes);
+    if actual != hex::encode(expected) {
+        bail!(
+            "model artifact hash mismatch: expected {}, got {actual}",
+            hex::encode(expected)
+        );
+    }
+    if let Ok(package) = serde_json::from_slice::<ModelPackage>(bytes) {
+        verify_model_hash(&package).context("model package hash check failed")?;
+    }
+    Ok(())
+}
+
+/// Check `bytes` against every digest recorded for `artifact`.
+pub fn verify_manifest_artifact(artifact: &ModelArtifact, bytes: &[u8]) -> Result<()> {
+    if bytes.len() as u64 != artifact.size_bytes {
+        bail!(
+            "artifact {} is {} bytes, manifest records {}",
+            artifact.path,
+            bytes.len(),
+            artifact.size_bytes
+        );
+    }
+    let sha256 = canonical_sha256(bytes);
+    if sha256 != artifact.sha256 {
+        bail!(
+            "artifact {} sha256 mismatch: expected {}, got {sha256}",
+            artifact.path,
+            artifact.sha256
+        );
+    }
+    verify_artifact(&decode_hash(&artifact.blake3)?, bytes)
+}
+
+/// Store `bytes` for `entry` in `store` and return the signed descriptor to
+/// publish over IPNDHT.
+///
+/// The descriptor's content hash equals `entry.artifact.blake3`; its id goes
+/// into the governance proposal via [`artifact_url`]. `bytes` must be the
+/// canonical JSON of a `gbdt::Model`.
+pub fn publish_artifact(
+    entry: &ModelManifestEntry,
+    bytes: &[u8],
+    store: &ContentStore,
+    owner_key: &[u8; 32],
+) -> Result<FileDescriptor> {
+    if entry.artifact.format != GBDT_MODEL_FORMAT {
+        bail!(
+            "artifact {} is {}; only {GBDT_MODEL_FORMAT} artifacts can be staged by consensus",
+            entry.artifact.path,
+            entry.artifact.format
+        );
+    }
+    verify_manifest_artifact(&entry.artifact, bytes)?;
+    let model: GbdtModel = serde_json::from_slice(bytes)
+        .with_context(|| format!("artifact {} is not a gbdt model", entry.artifact.path))?;
+    model.validate()?;
+    if model.to_canonical_json()?.as_bytes() != bytes {
+        bail!(
+            "artifact {} is not canonical gbdt model JSON",
+            entry.artifact.path
+        );
+    }
+    if bytes.len() > MAX_CHUNK_SIZE {
+        bail!(
+            "artifact {} is {} bytes; single-chunk artifacts are limited to {MAX_CHUNK_SIZE}",
+            entry.artifact.path,
+            bytes.len()
+        );
+    }
+
+    let manifest = ContentManifest::from_data(bytes, bytes.len());
+    debug_assert_eq!(manifest.root.to_hex(), entry.artifact.blake3);
+    store.put_chunk(&manifest, 0, bytes)?;
+    store.commit_manifest(&manifest)?;
+
+    let owner = SigningKey::from_bytes(owner_key).verifying_key().to_bytes();
+    let mut descriptor = FileDescriptor::new(
+        manifest.root,
+        owner,
+        manifest.size_bytes,
+        Some(MODEL_ARTIFACT_MIME.to_string()),
+        vec![
+            MODEL_ARTIFACT_TAG.to_string(),
+            format!("model:{}", entry.metadata.id.name),
+            format!("sha256:{}", entry.artifact.sha256),
+        ],
+    );
+    descriptor.sign(owner_key).map_err(|err| anyhow!(err))?;
+    Ok(descriptor)
+}
+
+/// Transport that resolves a descriptor and downloads its content.
+#[async_trait]
+pub trait ArtifactSource: Send + Sync {
+    /// Look up `id` and return its descriptor together with the content bytes.
+    async fn fetch(&self, id: &FileId) -> Result<(FileDescriptor, Vec<u8>)>;
+}
+
+/// Local cache of verified model artifacts, keyed by model hash.
+#[derive(Debug, Clone)]
+pub struct ModelArtifactCache {
+    dir: PathBuf,
+}
+
+impl ModelArtifactCache {
+    /// Open (creating if needed) a cache rooted at `dir`.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create model cache {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Where the artifact for `model_hash` is cached.
+    pub fn path_for(&self, model_hash: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.json", hex::encode(model_hash)))
+    }
+
+    /// Path of the cached artifact, if present and still valid. Corrupted
+    /// entries are removed.
+    pub fn get(&self, model_hash: &[u8; 32]) -> Result<Option<PathBuf>> {
+        let path = self.path_for(model_hash);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path)?;
+        if let Err(err) = verify_artifact(model_hash, &bytes) {
+            tracing::warn!(path = %path.display(), "Dropping corrupted cached model: {err}");
+            fs::remove_file(&path)?;
+            return Ok(None);
+        }
+        Ok(Some(path))
+    }
+
+    /// Verify and cache `bytes` under `model_hash`.
+    pub fn insert(&self, model_hash: &[u8; 32], bytes: &[u8]) -> Result<PathBuf> {
+        verify_artifact(model_hash, bytes)?;
+        let path = self.path_for(model_hash);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+}
+
+/// Fetches governance-approved model artifacts on demand.
+#[derive(Clone)]
+pub struct ModelFetcher {
+    source: Arc<dyn ArtifactSource>,
+    cache: ModelArtifactCache,
+}
+
+impl ModelFetcher {
+    pub fn new(source: Arc<dyn ArtifactSource>, cache: ModelArtifactCache) -> Self {
+        Self { source, cache }
+    }
+
+    pub fn cache(&self) -> &ModelArtifactCache {
+        &self.cache
+    }
+
+    /// Return the local path of the model approved as `model_hash`, fetching
+    /// it from `model_url` when it is not cached yet.
+    pub async fn fetch(&self, model_hash: &[u8; 32], model_url: &str) -> Result<PathBuf> {
+        if let Some(path) = self.cache.get(model_hash)? {
+            return Ok(path);
+        }
+
+        let id = parse_artifact_url(model_url)
+            .ok_or_else(|| anyhow!("model URL {model_url} is not an IPNDHT artifact"))?;
+        let (descriptor, bytes) = self
+            .source
+            .fetch(&id)
+            .await
+            .with_context(|| format!("failed to fetch model artifact {}", id.to_hex()))?;
+
+        if descriptor.content_hash != ContentHash::from_bytes(*model_hash) {
+            bail!(
+                "descriptor {} holds {}, governance approved {}",
+                id.to_hex(),
+                descriptor.content_hash.to_hex(),
+                hex::encode(model_hash)
+            );
+        }
+        let path = self.cache.insert(model_hash, &bytes)?;
+        tracing::info!(
+            model_hash = %hex::encode(model_hash),
+            path = %path.display(),
+            "Cached model artifact from IPNDHT"
+        );
+        Ok(path)
+    }
+}
+
+fn decode_hash(hex_hash: &str) -> Result<[u8; 32]> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(hex_hash, &mut hash)
+        .with_context(|| format!("invalid model hash {hex_hash}"))?;
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{DeterministicInferenceHashes, ModelArtifact, DETERMINISTIC_GBDT_FORMAT};
+    use ippan_ai_core::gbdt::{Node, Tree};
+    use ippan_ai_core::types::{ModelId, ModelMetadata};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    const OWNER_KEY: [u8; 32] = [7u8; 32];
+
+    /// Canonical JSON of a gbdt model with `trees` single-leaf trees.
+    fn model_bytes(trees: usize) -> Vec<u8> {
+        let trees = (0..trees)
+            .map(|i| Tree::new(vec![Node::leaf(0, i as i64)], 1))
+            .collect();
+        GbdtModel::new(trees, 0)
+            .to_canonical_json()
+            .unwrap()
+            .into_bytes()
+    }
+
+    fn manifest_entry(bytes: &[u8]) -> ModelManifestEntry {
+        ModelManifestEntry {
+            metadata: ModelMetadata {
+                id: ModelId {
+                    name: "fairness".into(),
+                    version: "2".into(),
+                    hash: canonical_sha256(bytes),
+                },
+                name: "fairness".into(),
+                version: "2".into(),
+                description: String::new(),
+                author: String::new(),
+                license: String::new(),
+                tags: Vec::new(),
+                created_at: 0,
+                updated_at: 0,
+                architecture: "gbdt".into(),
+                input_shape: vec![6],
+                output_shape: vec![1],
+                size_bytes: bytes.len() as u64,
+                parameter_count: 0,
+            },
+            artifact: ModelArtifact {
+                path: "models/fairness.json".into(),
+                format: GBDT_MODEL_FORMAT.into(),
+                sha256: canonical_sha256(bytes),
+                blake3: canonical_blake3(bytes),
+                size_bytes: bytes.len() as u64,
+            },
+            inference: DeterministicInferenceHashes {
+                seed: "seed".into(),
+                architectures: Default::default(),
+            },
+            training_distribution: None,
+        }
+    }
+
+    /// In-memory stand-in for IPNDHT: serves published descriptors from a
+    /// content store.
+    struct MemorySource {
+        store: ContentStore,
+        descriptors: Mutex<HashMap<FileId, FileDescriptor>>,
+    }
+
+    #[async_trait]
+    impl ArtifactSource for MemorySource {
+        async fn fetch(&self, id: &FileId) -> Result<(FileDescriptor, Vec<u8>)> {
+            let descriptor = self
+                .descriptors
+                .lock()
+                .unwrap()
+                .get(id)
+                .cloned()
+                .ok_or_else(|| anyhow!("descriptor not found"))?;
+            let bytes = self.store.read(&descriptor.content_hash)?;
+            Ok((descriptor, bytes))
+        }
+    }
+
+    fn published(bytes: &[u8]) -> (Arc<MemorySource>, FileDescriptor) {
+        let source = Arc::new(MemorySource {
+            store: ContentStore::in_memory(),
+            descriptors: Mutex::new(HashMap::new()),
+        });
+        let descriptor =
+            publish_artifact(&manifest_entry(bytes), bytes, &source.store, &OWNER_KEY).unwrap();
+        source
+            .descriptors
+            .lock()
+            .unwrap()
+            .insert(descriptor.id, descriptor.clone());
+        (source, descriptor)
+    }
+
+    #[test]
+    fn test_descriptor_content_hash_is_manifest_hash() {
+        // Larger than the default chunk size, still published as one chunk.
+        let bytes = model_bytes(8_000);
+        assert!(bytes.len() > 256 * 1024);
+        let (_, descriptor) = published(&bytes);
+
+        assert_eq!(descriptor.content_hash.to_hex(), canonical_blake3(&bytes));
+        assert!(descriptor.tags.iter().any(|tag| tag == MODEL_ARTIFACT_TAG));
+        descriptor.verify_signature().unwrap();
+        assert_eq!(
+            parse_artifact_url(&artifact_url(&descriptor.id)),
+            Some(descriptor.id)
+        );
+    }
+
+    #[test]
+    fn test_publish_rejects_bytes_not_in_manifest() {
+        let entry = manifest_entry(&model_bytes(1));
+        let store = ContentStore::in_memory();
+        assert!(publish_artifact(&entry, &model_bytes(2), &store, &OWNER_KEY).is_err());
+    }
+
+    #[test]
+    fn test_publish_rejects_artifacts_consensus_cannot_stage() {
+        let store = ContentStore::in_memory();
+        let bytes = model_bytes(1);
+        let mut deterministic = manifest_entry(&bytes);
+        deterministic.artifact.format = DETERMINISTIC_GBDT_FORMAT.into();
+        assert!(publish_artifact(&deterministic, &bytes, &store, &OWNER_KEY).is_err());
+
+        // Same model, but not the canonical JSON its hash is computed over
+        let pretty = serde_json::to_vec_pretty(
+            &serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+        )
+        .unwrap();
+        let entry = manifest_entry(&pretty);
+        assert!(publish_artifact(&entry, &pretty, &store, &OWNER_KEY).is_err());
+
+        let other = br#"{"model":1}"#;
+        assert!(publish_artifact(&manifest_entry(other), other, &store, &OWNER_KEY).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_verifies_and_caches() {
+        let bytes = model_bytes(2);
+        let (source, descriptor) = published(&bytes);
+        let dir = tempfile::tempdir().unwrap();
+        let fetcher = ModelFetcher::new(
+            source.clone(),
+            ModelArtifactCache::open(dir.path()).unwrap(),
+        );
+        let hash = *descriptor.content_hash.as_bytes();
+        let url = artifact_url(&descriptor.id);
+
+        let path = fetcher.fetch(&hash, &url).await.unwrap();
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // Served from the cache once the source forgets the descriptor.
+        source.descriptors.lock().unwrap().clear();
+        assert_eq!(fetcher.fetch(&hash, &url).await.unwrap(), path);
+
+        // A tampered cache entry is dropped and refetched (which now fails).
+        fs::write(&path, b"tampered").unwrap();
+        assert!(fetcher.fetch(&hash, &url).await.is_err());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_descriptor_for_other_model() {
+        let (source, descriptor) = published(&model_bytes(1));
+        let dir = tempfile::tempdir().unwrap();
+        let fetcher = ModelFetcher::new(source, ModelArtifactCache::open(dir.path()).unwrap());
+
+        let approved = *blake3::hash(&model_bytes(2)).as_bytes();
+        let err = fetcher
+            .fetch(&approved, &artifact_url(&descriptor.id))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("governance approved"));
+        assert!(fetcher
+            .fetch(&approved, "https://example.com/model.json")
+            .await
+            .is_err());
+    }
+}
@@ -40,6 +40,7 @@
 
 pub mod activation;
 pub mod d_gbdt;
+pub mod distribution;
 pub mod errors;
 pub mod fees;
 pub mod governance;
@@ -57,13 +58,17 @@ pub use d_gbdt::{
     load_model_from_config, load_model_from_path, DGBDTRegistry, HistoryEntry, StoredModel,
     MAX_HISTORY_VERSIONS,
 };
+pub use distribution::{
+    artifact_url, parse_artifact_url, publish_artifact, verify_artifact, ArtifactSource,
+    ModelArtifactCache, ModelFetcher,
+};
 pub use errors::{RegistryError, Result};
 pub use fees::{FeeCalculation, FeeManager, FeeStats};
 pub use governance::GovernanceManager;
 pub use manifest::{
     canonical_blake3, canonical_sha256, load_manifest, validate_inference_hashes,
     write_architecture_hash_files, DeterministicInferenceHashes, ModelArtifact, ModelManifest,
-    ModelManifestEntry, DEFAULT_INFERENCE_SEED,
+    ModelManifestEntry, DEFAULT_INFERENCE_SEED, DETERMINISTIC_GBDT_FORMAT, GBDT_MODEL_FORMAT,
 };
 pub use proposal::{AiModelProposal, ProposalManager, ProposalStatus};
 pub use registry::ModelRegistry;
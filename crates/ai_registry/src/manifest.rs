@@ -6,6 +6,8 @@
 //! exact same bytes regardless of CPU architecture.
 
 use ippan_ai_core::deterministic_gbdt::{DeterministicGBDT, DeterministicGBDTError};
+use ippan_ai_core::drift::DistributionSummary;
+use ippan_ai_core::gbdt::Model as GbdtModel;
 use ippan_ai_core::serialization::canonical_json_string;
 use ippan_ai_core::types::ModelMetadata;
 use serde::{Deserialize, Serialize};
@@ -17,6 +19,13 @@ use std::path::{Path, PathBuf};
 /// Canonical hash seed used when deriving deterministic inference hashes.
 pub const DEFAULT_INFERENCE_SEED: &str = "ippan::deterministic_inference::v1";
 
+/// Artifact format of [`DeterministicGBDT`] models.
+pub const DETERMINISTIC_GBDT_FORMAT: &str = "deterministic_gbdt.canonical_json";
+
+/// Artifact format of integer `gbdt::Model` ensembles, as written by
+/// `ai-trainer train` and `import_gbdt`.
+pub const GBDT_MODEL_FORMAT: &str = "gbdt.canonical_json";
+
 /// Description of a single model artifact.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ModelArtifact {
@@ -50,6 +59,9 @@ pub struct ModelManifestEntry {
     pub artifact: ModelArtifact,
     /// Deterministic inference hash certificates per architecture.
     pub inference: DeterministicInferenceHashes,
+    /// Feature distribution of the training data, used for drift monitoring.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub training_distribution: Option<DistributionSummary>,
 }
 
 /// Canonical manifest describing all deterministic models bundled with the
@@ -89,7 +101,7 @@ impl ModelManifest {
 impl ModelManifestEntry {
     /// Build a manifest entry from a deterministic GBDT model stored on disk.
     pub fn from_deterministic_gbdt<P: AsRef<Path>>(
-        mut metadata: ModelMetadata,
+        metadata: ModelMetadata,
         model_path: P,
         repository_root: P,
         inference_seed: &str,
@@ -97,18 +109,73 @@ impl ModelManifestEntry {
         let model_path = model_path.as_ref();
         let repo_root = repository_root.as_ref();
 
-        let full_path = if model_path.is_absolute() {
-            model_path.to_path_buf()
-        } else {
-            repo_root.join(model_path)
-        };
+        let model = DeterministicGBDT::from_json_file(resolve_path(model_path, repo_root))
+            .map_err(|err| anyhow::anyhow!("failed to load model: {err}"))?;
+        let canonical_json = model
+            .to_canonical_json()
+            .map_err(|err| anyhow::anyhow!("failed to canonicalize model: {err}"))?;
+        let inference_hash = model
+            .model_hash(inference_seed)
+            .map_err(|err| anyhow::anyhow!("failed to compute deterministic hash: {err}"))?;
 
-        let model = DeterministicGBDT::from_json_file(&full_path)
+        Self::from_canonical_artifact(
+            metadata,
+            model_path,
+            repo_root,
+            DETERMINISTIC_GBDT_FORMAT,
+            &canonical_json,
+            CanonicalModelShape {
+                parameter_count: model.trees.iter().map(|tree| tree.nodes.len() as u64).sum(),
+                feature_count: infer_feature_count(&model) as usize,
+            },
+            uniform_inference_hashes(inference_seed, inference_hash),
+        )
+    }
+
+    /// Build a manifest entry from an integer `gbdt::Model` stored on disk.
+    ///
+    /// The artifact's `blake3` is the model hash consensus pins and
+    /// `compute_model_hash` reports, so the entry can be looked up by the hash
+    /// a rollout activates.
+    pub fn from_gbdt_model<P: AsRef<Path>>(
+        metadata: ModelMetadata,
+        model_path: P,
+        repository_root: P,
+        inference_seed: &str,
+    ) -> anyhow::Result<Self> {
+        let model_path = model_path.as_ref();
+        let repo_root = repository_root.as_ref();
+
+        let model = GbdtModel::load_json(resolve_path(model_path, repo_root))
             .map_err(|err| anyhow::anyhow!("failed to load model: {err}"))?;
         let canonical_json = model
             .to_canonical_json()
             .map_err(|err| anyhow::anyhow!("failed to canonicalize model: {err}"))?;
+        let inference_hash = gbdt_inference_hash(&canonical_json, inference_seed);
+
+        Self::from_canonical_artifact(
+            metadata,
+            model_path,
+            repo_root,
+            GBDT_MODEL_FORMAT,
+            &canonical_json,
+            CanonicalModelShape {
+                parameter_count: model.trees.iter().map(|tree| tree.nodes.len() as u64).sum(),
+                feature_count: gbdt_feature_count(&model),
+            },
+            uniform_inference_hashes(inference_seed, inference_hash),
+        )
+    }
 
+    fn from_canonical_artifact(
+        mut metadata: ModelMetadata,
+        model_path: &Path,
+        repo_root: &Path,
+        format: &str,
+        canonical_json: &str,
+        shape: CanonicalModelShape,
+        inference: DeterministicInferenceHashes,
+    ) -> anyhow::Result<Self> {
         let sha256 = canonical_sha256(canonical_json.as_bytes());
         let blake3 = canonical_blake3(canonical_json.as_bytes());
         let size_bytes = canonical_json.len() as u64;
@@ -116,38 +183,33 @@ impl ModelManifestEntry {
         // Update metadata with canonical measurements.
         metadata.id.hash = sha256.clone();
         metadata.size_bytes = size_bytes;
-        metadata.parameter_count = model.trees.iter().map(|tree| tree.nodes.len() as u64).sum();
-        metadata.input_shape = vec![infer_feature_count(&model) as usize];
+        metadata.parameter_count = shape.parameter_count;
+        metadata.input_shape = vec![shape.feature_count];
         metadata.output_shape = vec![1];
 
-        let mut architectures = BTreeMap::new();
-        let inference_hash = model
-            .model_hash(inference_seed)
-            .map_err(|err| anyhow::anyhow!("failed to compute deterministic hash: {err}"))?;
-        architectures.insert("x86_64".to_string(), inference_hash.clone());
-        architectures.insert("aarch64".to_string(), inference_hash);
-
         let relative_path = normalize_relative_path(model_path, repo_root)?;
         let artifact = ModelArtifact {
             path: relative_path,
-            format: "deterministic_gbdt.canonical_json".to_string(),
+            format: format.to_string(),
             sha256,
             blake3,
             size_bytes,
         };
 
-        let inference = DeterministicInferenceHashes {
-            seed: inference_seed.to_string(),
-            architectures,
-        };
-
         Ok(Self {
             metadata,
             artifact,
             inference,
+            training_distribution: None,
         })
     }
 
+    /// Attach the training feature distribution.
+    pub fn with_training_distribution(mut self, summary: DistributionSummary) -> Self {
+        self.training_distribution = Some(summary);
+        self
+    }
+
     /// Convenience accessor for the canonical SHA-256 hash.
     pub fn canonical_sha256(&self) -> &str {
         &self.artifact.sha256
@@ -166,6 +228,51 @@ pub fn canonical_blake3(data: &[u8]) -> String {
     blake3::hash(data).to_hex().to_string()
 }
 
+/// Parameter and input counts recorded in the entry's metadata.
+struct CanonicalModelShape {
+    parameter_count: u64,
+    feature_count: usize,
+}
+
+/// The same inference hash for every supported architecture; integer
+/// inference does not depend on the CPU.
+fn uniform_inference_hashes(seed: &str, hash: String) -> DeterministicInferenceHashes {
+    let mut architectures = BTreeMap::new();
+    architectures.insert("x86_64".to_string(), hash.clone());
+    architectures.insert("aarch64".to_string(), hash);
+    DeterministicInferenceHashes {
+        seed: seed.to_string(),
+        architectures,
+    }
+}
+
+fn resolve_path(model_path: &Path, repo_root: &Path) -> PathBuf {
+    if model_path.is_absolute() {
+        model_path.to_path_buf()
+    } else {
+        repo_root.join(model_path)
+    }
+}
+
+/// Inference certificate of a `gbdt::Model`: BLAKE3 over the canonical JSON
+/// followed by the seed.
+fn gbdt_inference_hash(canonical_json: &str, inference_seed: &str) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(canonical_json.as_bytes());
+    hasher.update(inference_seed.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn gbdt_feature_count(model: &GbdtModel) -> usize {
+    model
+        .trees
+        .iter()
+        .flat_map(|tree| tree.nodes.iter().map(|node| node.feature_idx))
+        .max()
+        .map(|max_feature| (max_feature + 1).max(0) as usize)
+        .unwrap_or(0)
+}
+
 fn normalize_relative_path(path: &Path, repo_root: &Path) -> anyhow::Result<String> {
     let canonical_root = repo_root
         .canonicalize()
@@ -242,6 +349,12 @@ pub fn recompute_inference_hash(
 ) -> Result<String, DeterministicGBDTError> {
     let repo_root = repo_root.as_ref();
     let path = repo_root.join(&entry.artifact.path);
+    if entry.artifact.format == GBDT_MODEL_FORMAT {
+        let canonical_json = GbdtModel::load_json(&path)
+            .and_then(|model| model.to_canonical_json())
+            .map_err(|err| DeterministicGBDTError::ModelLoadError(err.to_string()))?;
+        return Ok(gbdt_inference_hash(&canonical_json, &entry.inference.seed));
+    }
     let model = DeterministicGBDT::from_json_file(&path)?;
     model.model_hash(&entry.inference.seed)
 }
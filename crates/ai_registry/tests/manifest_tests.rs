@@ -1,6 +1,9 @@
 use ippan_ai_core::deterministic_gbdt::DeterministicGBDT;
+use ippan_ai_core::gbdt::{import_model, ImportOptions, SourceFormat};
+use ippan_ai_core::types::{ModelId, ModelMetadata};
 use ippan_ai_registry::manifest::{
     canonical_sha256, load_manifest, recompute_inference_hash, validate_inference_hashes,
+    ModelManifest, ModelManifestEntry, DEFAULT_INFERENCE_SEED, GBDT_MODEL_FORMAT,
 };
 use std::fs;
 use std::path::PathBuf;
@@ -55,3 +58,90 @@ fn architecture_hash_files_match_manifest() {
         assert_eq!(contents.trim(), entry.artifact.sha256);
     }
 }
+
+const LIGHTGBM_TEXT: &str = "tree
+version=v4
+num_class=1
+num_tree_per_iteration=1
+max_feature_idx=1
+objective=regression
+feature_names=uptime latency
+
+Tree=0
+num_leaves=3
+num_cat=0
+split_feature=0 1
+threshold=0.5 0.25
+decision_type=2 2
+left_child=-1 -2
+right_child=1 -3
+leaf_value=0.125 -0.5 0.75
+shrinkage=1
+
+end of trees
+";
+
+fn metadata(name: &str) -> ModelMetadata {
+    ModelMetadata {
+        id: ModelId::new(name, "1.0.0", ""),
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        description: String::new(),
+        author: "tests".to_string(),
+        license: "Apache-2.0".to_string(),
+        tags: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        architecture: "gbdt".to_string(),
+        input_shape: Vec::new(),
+        output_shape: vec![1],
+        size_bytes: 0,
+        parameter_count: 0,
+    }
+}
+
+#[test]
+fn imported_model_generates_manifest_entry() {
+    let imported = import_model(
+        LIGHTGBM_TEXT,
+        SourceFormat::LightgbmText,
+        &ImportOptions::default(),
+    )
+    .unwrap();
+    let report = imported.report(None).unwrap();
+
+    let root = tempfile::tempdir().unwrap();
+    let model_path = PathBuf::from("imported.json");
+    imported
+        .model
+        .save_json(root.path().join(&model_path))
+        .unwrap();
+
+    let entry = ModelManifestEntry::from_gbdt_model(
+        metadata("imported"),
+        model_path.clone(),
+        root.path().to_path_buf(),
+        DEFAULT_INFERENCE_SEED,
+    )
+    .unwrap();
+    assert_eq!(entry.artifact.format, GBDT_MODEL_FORMAT);
+    assert_eq!(entry.artifact.path, "imported.json");
+    assert_eq!(entry.artifact.blake3, report.model_hash);
+    assert_eq!(entry.artifact.sha256, report.sha256);
+    assert_eq!(entry.artifact.size_bytes, report.size_bytes);
+    assert_eq!(entry.metadata.id.hash, report.sha256);
+    assert_eq!(entry.metadata.parameter_count, report.nodes as u64);
+    assert_eq!(entry.metadata.input_shape, vec![2]);
+
+    let manifest_path = root.path().join("manifest.json");
+    ModelManifest::new(1, "2025-01-01T00:00:00Z".into(), vec![entry])
+        .write_to_path(&manifest_path)
+        .unwrap();
+    let manifest = load_manifest(&manifest_path).unwrap();
+    let entry = &manifest.models[0];
+    validate_inference_hashes(entry).unwrap();
+    let recomputed = recompute_inference_hash(entry, root.path()).unwrap();
+    for hash in entry.inference.architectures.values() {
+        assert_eq!(hash, &recomputed);
+    }
+}
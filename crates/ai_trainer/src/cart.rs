@@ -1,12 +1,16 @@
 //! CART (Classification and Regression Tree) builder
 //!
-//! Implements deterministic exact-greedy decision tree construction
-//! with fixed-point arithmetic only.
+//! Implements deterministic decision tree construction with fixed-point
+//! arithmetic only. Split finding scans per-node gradient histograms over
+//! pre-binned features; each split builds the smaller child's histogram
+//! from its rows and derives the larger one by subtracting from the parent.
 
 use ippan_ai_core::gbdt::tree::Node;
-use std::collections::BTreeMap;
+use rayon::prelude::*;
+use std::borrow::Cow;
 
 use crate::deterministic::SplitTieBreaker;
+use crate::histogram::{BinStats, BinnedFeatures, NodeHistogram, DEFAULT_MAX_BINS};
 
 /// Training parameters for a single tree
 #[derive(Clone, Debug)]
@@ -14,6 +18,8 @@ pub struct TreeConfig {
     pub max_depth: usize,
     pub min_samples_leaf: usize,
     pub quant_step: i64,
+    /// Units of the sample hessians; leaf values are `-G * hessian_scale / H`
+    pub hessian_scale: i64,
 }
 
 impl Default for TreeConfig {
@@ -22,109 +28,147 @@ impl Default for TreeConfig {
             max_depth: 6,
             min_samples_leaf: 32,
             quant_step: 1000,
+            hessian_scale: 1,
         }
     }
 }
 
-/// Sample with features, gradient, and hessian
-#[derive(Clone, Debug)]
-struct Sample {
-    features: Vec<i64>,
-    gradient: i64,
-    hessian: i64,
+/// Feature and gain of one internal node, for importance reporting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitStat {
+    pub feature_idx: usize,
+    pub gain: i64,
 }
 
 /// Split candidate with gain and tie-breaker
 #[derive(Debug, Clone)]
 struct SplitCandidate {
     feature_idx: usize,
+    bin: usize,
     threshold: i64,
     gain: i64,
     tie_breaker: SplitTieBreaker,
 }
 
 impl SplitCandidate {
-    fn new(feature_idx: usize, threshold: i64, gain: i64, node_id: usize) -> Self {
+    fn new(feature_idx: usize, bin: usize, threshold: i64, gain: i64, node_id: usize) -> Self {
         Self {
             feature_idx,
+            bin,
             threshold,
             gain,
             tie_breaker: SplitTieBreaker::new(feature_idx, threshold, node_id),
         }
     }
+
+    /// Higher gain wins; equal gains fall back to the deterministic tie-breaker
+    fn beats(&self, other: &SplitCandidate) -> bool {
+        self.gain > other.gain || (self.gain == other.gain && self.tie_breaker < other.tie_breaker)
+    }
 }
 
-/// Build a regression tree using exact-greedy CART algorithm
-pub struct CartBuilder {
+/// Build a regression tree from gradient histograms
+pub struct CartBuilder<'a> {
     config: TreeConfig,
-    samples: Vec<Sample>,
-    feature_count: usize,
+    bins: Cow<'a, BinnedFeatures>,
+    gradients: &'a [i64],
+    hessians: &'a [i64],
+    rows: Vec<usize>,
+    features: Vec<usize>,
 }
 
-impl CartBuilder {
+impl<'a> CartBuilder<'a> {
+    /// Bin `features` with the configured quantization step and build over all rows.
     pub fn new(
         features: &[Vec<i64>],
-        gradients: &[i64],
-        hessians: &[i64],
+        gradients: &'a [i64],
+        hessians: &'a [i64],
         config: TreeConfig,
     ) -> Self {
-        assert_eq!(features.len(), gradients.len());
-        assert_eq!(features.len(), hessians.len());
+        let bins = BinnedFeatures::from_rows(features, config.quant_step, DEFAULT_MAX_BINS);
+        Self::from_parts(Cow::Owned(bins), gradients, hessians, config)
+    }
 
-        let samples: Vec<Sample> = features
-            .iter()
-            .zip(gradients.iter().zip(hessians.iter()))
-            .map(|(f, (&g, &h))| Sample {
-                features: f.clone(),
-                gradient: g,
-                hessian: h,
-            })
-            .collect();
+    /// Build over features that were binned once for the whole dataset.
+    pub fn with_bins(
+        bins: &'a BinnedFeatures,
+        gradients: &'a [i64],
+        hessians: &'a [i64],
+        config: TreeConfig,
+    ) -> Self {
+        Self::from_parts(Cow::Borrowed(bins), gradients, hessians, config)
+    }
 
-        let feature_count = if samples.is_empty() {
-            0
-        } else {
-            samples[0].features.len()
-        };
+    fn from_parts(
+        bins: Cow<'a, BinnedFeatures>,
+        gradients: &'a [i64],
+        hessians: &'a [i64],
+        config: TreeConfig,
+    ) -> Self {
+        assert_eq!(bins.rows(), gradients.len());
+        assert_eq!(bins.rows(), hessians.len());
 
         Self {
             config,
-            samples,
-            feature_count,
+            rows: (0..bins.rows()).collect(),
+            features: (0..bins.feature_count()).collect(),
+            bins,
+            gradients,
+            hessians,
         }
     }
 
+    /// Fit the tree on a subset of rows (row subsampling)
+    pub fn rows(mut self, rows: Vec<usize>) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// Only consider splits on a subset of features (column subsampling)
+    pub fn features(mut self, features: Vec<usize>) -> Self {
+        self.features = features;
+        self
+    }
+
     /// Build tree and return deterministic nodes
     pub fn build(&self) -> Vec<Node> {
+        self.build_with_splits().0
+    }
+
+    /// Build tree and also return the chosen splits in node order
+    pub fn build_with_splits(&self) -> (Vec<Node>, Vec<SplitStat>) {
         let mut nodes = Vec::new();
-        let indices: Vec<usize> = (0..self.samples.len()).collect();
+        let mut splits = Vec::new();
+        let histogram = self.histogram(&self.rows);
 
-        self.build_node(&indices, 0, &mut nodes, 0);
+        self.build_node(&self.rows, histogram, 0, &mut nodes, &mut splits, 0);
 
-        nodes
+        (nodes, splits)
     }
 
     /// Recursively build tree nodes
     fn build_node(
         &self,
-        indices: &[usize],
+        rows: &[usize],
+        histogram: NodeHistogram,
         depth: usize,
         nodes: &mut Vec<Node>,
+        splits: &mut Vec<SplitStat>,
         node_id: usize,
     ) -> i32 {
         let current_idx = nodes.len() as i32;
 
         // Calculate leaf value
-        let leaf_value = self.calculate_leaf_value(indices);
+        let leaf_value = self.calculate_leaf_value(&histogram.total());
 
         // Check stopping conditions
-        if depth >= self.config.max_depth || indices.len() < 2 * self.config.min_samples_leaf {
+        if depth >= self.config.max_depth || rows.len() < 2 * self.config.min_samples_leaf {
             nodes.push(Node::leaf(current_idx, leaf_value));
             return current_idx;
         }
 
         // Find best split
-        let split = match self.find_best_split(indices, node_id) {
+        let split = match self.find_best_split(&histogram, node_id) {
             Some(s) => s,
             None => {
                 // No valid split, create leaf
@@ -133,17 +177,20 @@ impl CartBuilder {
             }
         };
 
-        // Split samples
-        let (left_indices, right_indices) =
-            self.split_samples(indices, split.feature_idx, split.threshold);
+        // Split rows
+        let (left_rows, right_rows) = self.split_rows(rows, split.feature_idx, split.bin);
 
-        if left_indices.len() < self.config.min_samples_leaf
-            || right_indices.len() < self.config.min_samples_leaf
-        {
-            // Split would violate min_samples_leaf, create leaf
-            nodes.push(Node::leaf(current_idx, leaf_value));
-            return current_idx;
-        }
+        // Histogram subtraction: accumulate the smaller child, derive its sibling
+        let (left_histogram, right_histogram) = if left_rows.len() <= right_rows.len() {
+            let left = self.histogram(&left_rows);
+            let right = histogram.subtract(&left);
+            (left, right)
+        } else {
+            let right = self.histogram(&right_rows);
+            let left = histogram.subtract(&right);
+            (left, right)
+        };
+        drop(histogram);
 
         // Reserve space for current node
         nodes.push(Node::internal(
@@ -153,10 +200,28 @@ impl CartBuilder {
             -1,
             -1,
         ));
+        splits.push(SplitStat {
+            feature_idx: split.feature_idx,
+            gain: split.gain,
+        });
 
         // Build left and right subtrees
-        let left_idx = self.build_node(&left_indices, depth + 1, nodes, node_id * 2 + 1);
-        let right_idx = self.build_node(&right_indices, depth + 1, nodes, node_id * 2 + 2);
+        let left_idx = self.build_node(
+            &left_rows,
+            left_histogram,
+            depth + 1,
+            nodes,
+            splits,
+            node_id * 2 + 1,
+        );
+        let right_idx = self.build_node(
+            &right_rows,
+            right_histogram,
+            depth + 1,
+            nodes,
+            splits,
+            node_id * 2 + 2,
+        );
 
         // Update current node with child indices
         nodes[current_idx as usize].left = left_idx;
@@ -165,136 +230,127 @@ impl CartBuilder {
         current_idx
     }
 
-    /// Find best split using exact-greedy algorithm
-    fn find_best_split(&self, indices: &[usize], node_id: usize) -> Option<SplitCandidate> {
+    fn histogram(&self, rows: &[usize]) -> NodeHistogram {
+        NodeHistogram::build(
+            &self.bins,
+            &self.features,
+            rows,
+            self.gradients,
+            self.hessians,
+        )
+    }
+
+    /// Find the best split by scanning every candidate feature's histogram
+    fn find_best_split(&self, histogram: &NodeHistogram, node_id: usize) -> Option<SplitCandidate> {
+        let per_feature: Vec<Option<SplitCandidate>> = self
+            .features
+            .par_iter()
+            .enumerate()
+            .map(|(position, &feature_idx)| {
+                self.best_feature_split(
+                    feature_idx,
+                    histogram.feature(position),
+                    &histogram.total(),
+                    node_id,
+                )
+            })
+            .collect();
+
+        // Reduce in feature order so the winner never depends on scheduling
+        per_feature
+            .into_iter()
+            .flatten()
+            .fold(None, |best, candidate| match best {
+                Some(current) if !candidate.beats(&current) => Some(current),
+                _ => Some(candidate),
+            })
+    }
+
+    /// Best split of a single feature: bins `0..=b` go left
+    fn best_feature_split(
+        &self,
+        feature_idx: usize,
+        bins: &[BinStats],
+        parent: &BinStats,
+        node_id: usize,
+    ) -> Option<SplitCandidate> {
+        let min_samples_leaf = self.config.min_samples_leaf as u64;
         let mut best_split: Option<SplitCandidate> = None;
+        let mut left = BinStats::default();
 
-        for feature_idx in 0..self.feature_count {
-            // Get quantized thresholds for this feature
-            let thresholds = self.get_quantized_thresholds(indices, feature_idx);
-
-            for threshold in thresholds {
-                let (left_indices, right_indices) =
-                    self.split_samples(indices, feature_idx, threshold);
-
-                if left_indices.len() < self.config.min_samples_leaf
-                    || right_indices.len() < self.config.min_samples_leaf
-                {
-                    continue;
-                }
-
-                let gain = self.calculate_split_gain(&left_indices, &right_indices, indices);
-
-                let candidate = SplitCandidate::new(feature_idx, threshold, gain, node_id);
-
-                best_split = match best_split {
-                    None => Some(candidate),
-                    Some(ref current) => {
-                        // Deterministic tie-breaking
-                        if gain > current.gain
-                            || (gain == current.gain && candidate.tie_breaker < current.tie_breaker)
-                        {
-                            Some(candidate)
-                        } else {
-                            best_split
-                        }
-                    }
-                };
+        for (bin, stats) in bins.iter().enumerate() {
+            left = left.plus(stats);
+            // Empty bins repeat the previous partition with a larger threshold
+            if stats.count == 0 {
+                continue;
             }
-        }
 
-        best_split
-    }
+            let right = parent.minus(&left);
+            if left.count < min_samples_leaf || right.count < min_samples_leaf {
+                continue;
+            }
 
-    /// Get quantized threshold values for a feature
-    fn get_quantized_thresholds(&self, indices: &[usize], feature_idx: usize) -> Vec<i64> {
-        // Collect unique quantized values
-        let mut values = BTreeMap::new();
+            let gain = calculate_split_gain(&left, &right, parent);
+            let threshold = self.bins.threshold(feature_idx, bin);
+            let candidate = SplitCandidate::new(feature_idx, bin, threshold, gain, node_id);
 
-        for &idx in indices {
-            let val = self.samples[idx].features[feature_idx];
-            let quantized = (val / self.config.quant_step) * self.config.quant_step;
-            values.insert(quantized, ());
+            if best_split
+                .as_ref()
+                .is_none_or(|current| candidate.beats(current))
+            {
+                best_split = Some(candidate);
+            }
         }
 
-        values.into_keys().collect()
+        best_split
     }
 
-    /// Split samples based on threshold
-    fn split_samples(
+    /// Split rows based on bin index
+    fn split_rows(
         &self,
-        indices: &[usize],
+        rows: &[usize],
         feature_idx: usize,
-        threshold: i64,
+        bin: usize,
     ) -> (Vec<usize>, Vec<usize>) {
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-
-        for &idx in indices {
-            if self.samples[idx].features[feature_idx] <= threshold {
-                left.push(idx);
-            } else {
-                right.push(idx);
-            }
-        }
-
-        (left, right)
+        rows.iter()
+            .partition(|&&row| self.bins.bin(feature_idx, row) <= bin)
     }
 
-    /// Calculate split gain using fixed-point arithmetic
-    /// Gain = G_left²/H_left + G_right²/H_right - G_parent²/H_parent
-    fn calculate_split_gain(&self, left: &[usize], right: &[usize], parent: &[usize]) -> i64 {
-        let (g_left, h_left) = self.sum_gradients_hessians(left);
-        let (g_right, h_right) = self.sum_gradients_hessians(right);
-        let (g_parent, h_parent) = self.sum_gradients_hessians(parent);
-
-        // Use i128 to avoid overflow
-        let gain_left = if h_left > 0 {
-            ((g_left as i128 * g_left as i128) / h_left as i128) as i64
-        } else {
-            0
-        };
+    /// Calculate optimal leaf value: -G/H
+    fn calculate_leaf_value(&self, stats: &BinStats) -> i64 {
+        if stats.hessian == 0 {
+            return 0;
+        }
 
-        let gain_right = if h_right > 0 {
-            ((g_right as i128 * g_right as i128) / h_right as i128) as i64
-        } else {
-            0
-        };
+        clamp_i64(
+            -(stats
+                .gradient
+                .saturating_mul(self.config.hessian_scale as i128)
+                / stats.hessian),
+        )
+    }
+}
 
-        let gain_parent = if h_parent > 0 {
-            ((g_parent as i128 * g_parent as i128) / h_parent as i128) as i64
+/// Calculate split gain using fixed-point arithmetic
+/// Gain = G_left²/H_left + G_right²/H_right - G_parent²/H_parent
+fn calculate_split_gain(left: &BinStats, right: &BinStats, parent: &BinStats) -> i64 {
+    let score = |stats: &BinStats| {
+        if stats.hessian > 0 {
+            stats.gradient.saturating_mul(stats.gradient) / stats.hessian
         } else {
             0
-        };
-
-        gain_left
-            .saturating_add(gain_right)
-            .saturating_sub(gain_parent)
-    }
-
-    /// Sum gradients and hessians for a set of samples
-    fn sum_gradients_hessians(&self, indices: &[usize]) -> (i64, i64) {
-        let mut sum_g = 0i64;
-        let mut sum_h = 0i64;
-
-        for &idx in indices {
-            sum_g = sum_g.saturating_add(self.samples[idx].gradient);
-            sum_h = sum_h.saturating_add(self.samples[idx].hessian);
         }
+    };
 
-        (sum_g, sum_h)
-    }
-
-    /// Calculate optimal leaf value: -G/H
-    fn calculate_leaf_value(&self, indices: &[usize]) -> i64 {
-        let (sum_g, sum_h) = self.sum_gradients_hessians(indices);
-
-        if sum_h == 0 {
-            return 0;
-        }
+    clamp_i64(
+        score(left)
+            .saturating_add(score(right))
+            .saturating_sub(score(parent)),
+    )
+}
 
-        -((sum_g as i128) / sum_h as i128) as i64
-    }
+fn clamp_i64(value: i128) -> i64 {
+    value.clamp(i64::MIN as i128, i64::MAX as i128) as i64
 }
 
 #[cfg(test)]
@@ -316,6 +372,7 @@ mod tests {
             max_depth: 2,
             min_samples_leaf: 1,
             quant_step: 50_000,
+            hessian_scale: 1,
         };
 
         let builder = CartBuilder::new(&features, &gradients, &hessians, config);
@@ -338,4 +395,54 @@ mod tests {
         assert_eq!(nodes.len(), 1);
         assert!(nodes[0].leaf.is_some());
     }
+
+    #[test]
+    fn test_split_threshold_is_bin_upper_edge() {
+        let features = vec![vec![10], vec![14], vec![25], vec![31]];
+        let gradients = vec![-1000, -1000, 1000, 1000];
+        let hessians = vec![1, 1, 1, 1];
+
+        let config = TreeConfig {
+            max_depth: 1,
+            min_samples_leaf: 1,
+            quant_step: 10,
+            hessian_scale: 1,
+        };
+        let nodes = CartBuilder::new(&features, &gradients, &hessians, config).build();
+
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].threshold, 19);
+        assert_eq!(nodes[1].leaf, Some(1000));
+        assert_eq!(nodes[2].leaf, Some(-1000));
+    }
+
+    #[test]
+    fn test_row_and_feature_subsets() {
+        let features = vec![vec![1, 40], vec![2, 30], vec![3, 20], vec![4, 10]];
+        let gradients = vec![-1000, -1000, 1000, 1000];
+        let hessians = vec![1, 1, 1, 1];
+        let config = TreeConfig {
+            max_depth: 1,
+            min_samples_leaf: 1,
+            quant_step: 1,
+            hessian_scale: 1,
+        };
+        let bins = BinnedFeatures::from_rows(&features, 1, DEFAULT_MAX_BINS);
+
+        // Both features separate the classes; restricting to feature 1 must use it.
+        let nodes = CartBuilder::with_bins(&bins, &gradients, &hessians, config.clone())
+            .features(vec![1])
+            .build();
+        assert_eq!(nodes[0].feature_idx, 1);
+        assert_eq!(nodes[0].threshold, 20);
+
+        // Leaf values only see the sampled rows.
+        let nodes = CartBuilder::with_bins(&bins, &gradients, &hessians, config)
+            .rows(vec![0, 1])
+            .build();
+        assert!(nodes
+            .iter()
+            .filter_map(|node| node.leaf)
+            .all(|leaf| leaf == 1000));
+    }
 }
@@ -2,16 +2,28 @@
 //!
 //! Reads validator telemetry exported as deterministic CSV. All values must be
 //! integers that are already scaled to the runtime SCALE (1_000_000 for micros)
-//! and each row must be sorted by `(validator_id, timestamp)`.
+//! and each row must be sorted by `(validator_id, timestamp)`. The timestamp
+//! doubles as the round key that groups rows for ranking objectives.
+//!
+//! Two layouts are accepted: the legacy telemetry columns in
+//! [`FEATURE_COLUMNS`], or any header with `validator_id`, `timestamp` and
+//! `label` where every other column is a feature in header order (the
+//! layout written by [`crate::export`]).
 
 use anyhow::{Context, Result};
+use ippan_consensus_dlc::dgbdt::FairnessModel;
+use ippan_consensus_dlc::fairness_drift::{validate_reference, DistributionSummary};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::path::Path;
 
 use crate::deterministic::xxhash64_i64;
 
-/// Dataset columns required for training.
+/// Key and target columns every dataset must have.
+pub static KEY_COLUMNS: Lazy<Vec<&'static str>> =
+    Lazy::new(|| vec!["validator_id", "timestamp", "label"]);
+
+/// Columns of the legacy telemetry layout.
 pub static DATASET_COLUMNS: Lazy<Vec<&'static str>> = Lazy::new(|| {
     vec![
         "validator_id",
@@ -41,6 +53,8 @@ pub static FEATURE_COLUMNS: Lazy<Vec<&'static str>> = Lazy::new(|| {
 pub struct Dataset {
     pub features: Vec<Vec<i64>>,
     pub targets: Vec<i64>,
+    /// Round key (the `timestamp` column) of each row
+    pub rounds: Vec<i64>,
     pub feature_count: usize,
     pub feature_names: Vec<String>,
 }
@@ -56,8 +70,11 @@ pub struct FeatureStats {
 impl Dataset {
     /// Load dataset from CSV file with a deterministic header.
     ///
-    /// Expected header columns:
+    /// Legacy header columns:
     /// `validator_id,timestamp,uptime_micros,latency_micros,votes_cast,votes_missed,stake_atomic,label`
+    ///
+    /// Any other header must contain `validator_id,timestamp,label`; the
+    /// remaining columns become features in header order.
     pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path.as_ref()).context("Failed to read CSV file")?;
 
@@ -84,15 +101,30 @@ impl Dataset {
             header_map.insert(name.as_str(), idx);
         }
 
-        for col in DATASET_COLUMNS.iter() {
+        for col in KEY_COLUMNS.iter() {
             if !header_map.contains_key(col) {
                 anyhow::bail!("Missing required column '{col}' in dataset header");
             }
         }
 
-        let feature_indices: Vec<usize> = FEATURE_COLUMNS
+        let feature_names: Vec<String> = if DATASET_COLUMNS
+            .iter()
+            .all(|col| header_map.contains_key(col))
+        {
+            FEATURE_COLUMNS.iter().map(|s| s.to_string()).collect()
+        } else {
+            header
+                .iter()
+                .filter(|name| !KEY_COLUMNS.contains(&name.as_str()))
+                .cloned()
+                .collect()
+        };
+        if feature_names.is_empty() {
+            anyhow::bail!("Dataset header has no feature columns");
+        }
+        let feature_indices: Vec<usize> = feature_names
             .iter()
-            .map(|name| header_map[name])
+            .map(|name| header_map[name.as_str()])
             .collect();
         let label_index = header_map["label"];
         let validator_index = header_map["validator_id"];
@@ -100,6 +132,7 @@ impl Dataset {
 
         let mut features = Vec::new();
         let mut targets = Vec::new();
+        let mut rounds = Vec::new();
         let mut prev_key: Option<(i64, i64)> = None;
 
         for (line_idx, raw_line) in lines {
@@ -149,6 +182,7 @@ impl Dataset {
 
             let label = parse(label_index)?;
             targets.push(label);
+            rounds.push(timestamp);
             features.push(row_features);
         }
 
@@ -158,9 +192,10 @@ impl Dataset {
 
         Ok(Self {
             feature_count: feature_indices.len(),
-            feature_names: FEATURE_COLUMNS.iter().map(|s| s.to_string()).collect(),
+            feature_names,
             features,
             targets,
+            rounds,
         })
     }
 
@@ -179,17 +214,49 @@ impl Dataset {
         // Sort by hash for deterministic ordering
         indices.sort_by_key(|(hash, _)| *hash);
 
-        // Reorder features and targets
+        // Reorder features, targets and rounds
         let mut new_features = Vec::with_capacity(n);
         let mut new_targets = Vec::with_capacity(n);
+        let mut new_rounds = Vec::with_capacity(n);
 
         for (_, idx) in indices {
             new_features.push(self.features[idx].clone());
             new_targets.push(self.targets[idx]);
+            new_rounds.push(self.rounds[idx]);
         }
 
         self.features = new_features;
         self.targets = new_targets;
+        self.rounds = new_rounds;
+    }
+
+    /// Deterministically hold out `validation_micro` (at SCALE) of the rows.
+    ///
+    /// Rows are shuffled with `seed` and the first share becomes the
+    /// validation set; returns `(train, validation)`.
+    pub fn train_validation_split(
+        &self,
+        validation_micro: i64,
+        seed: i64,
+    ) -> Result<(Dataset, Dataset)> {
+        let held_out = (self.len() as i128 * validation_micro as i128 / 1_000_000) as usize;
+        if held_out == 0 || held_out >= self.len() {
+            anyhow::bail!(
+                "validation share {validation_micro} leaves {held_out} of {} rows for validation",
+                self.len()
+            );
+        }
+
+        let mut train = self.clone();
+        train.shuffle(seed);
+        let validation = Dataset {
+            features: train.features.drain(..held_out).collect(),
+            targets: train.targets.drain(..held_out).collect(),
+            rounds: train.rounds.drain(..held_out).collect(),
+            feature_count: self.feature_count,
+            feature_names: self.feature_names.clone(),
+        };
+        Ok((train, validation))
     }
 
     /// Get number of samples
@@ -224,6 +291,30 @@ impl Dataset {
             })
             .collect()
     }
+
+    /// Quantile summary of the fairness model inputs, recorded in the model
+    /// manifest for input drift monitoring. The dataset must have a column
+    /// for every [`FairnessModel::FEATURE_NAMES`] feature on the 0-10000
+    /// scale the live monitor observes; other layouts are an error.
+    pub fn input_distribution(&self, bins: usize) -> Result<DistributionSummary> {
+        let columns = FairnessModel::FEATURE_NAMES
+            .iter()
+            .map(|&name| {
+                self.feature_names
+                    .iter()
+                    .position(|column| column == name)
+                    .with_context(|| format!("dataset has no `{name}` fairness input column"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let rows: Vec<Vec<i64>> = self
+            .features
+            .iter()
+            .map(|row| columns.iter().map(|&column| row[column]).collect())
+            .collect();
+        let summary = DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &rows, bins);
+        validate_reference(&summary)?;
+        Ok(summary)
+    }
 }
 
 #[cfg(test)]
@@ -254,6 +345,33 @@ mod tests {
         assert_eq!(dataset.feature_count, FEATURE_COLUMNS.len());
         assert_eq!(dataset.features[0], vec![100, 20, 10, 0, 500]);
         assert_eq!(dataset.targets[0], 1000);
+        assert_eq!(dataset.rounds, vec![100, 200, 100]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_csv_with_custom_features() -> Result<()> {
+        let mut file = NamedTempFile::new()?;
+        writeln!(
+            file,
+            "validator_id,timestamp,stake_normalized,uptime_ratio_7d,label"
+        )?;
+        writeln!(file, "3,1,250000,1000000,500000")?;
+        writeln!(file, "3,2,250000,900000,400000")?;
+        file.flush()?;
+
+        let dataset = Dataset::from_csv(file.path())?;
+        assert_eq!(
+            dataset.feature_names,
+            vec![
+                "stake_normalized".to_string(),
+                "uptime_ratio_7d".to_string()
+            ]
+        );
+        assert_eq!(dataset.features[1], vec![250_000, 900_000]);
+        assert_eq!(dataset.targets, vec![500_000, 400_000]);
+        assert_eq!(dataset.rounds, vec![1, 2]);
 
         Ok(())
     }
@@ -273,6 +391,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_train_validation_split() -> Result<()> {
+        let file = create_test_csv()?;
+        let dataset = Dataset::from_csv(file.path())?;
+
+        let (train, validation) = dataset.train_validation_split(400_000, 7)?;
+        assert_eq!((train.len(), validation.len()), (2, 1));
+        assert_eq!(train.rounds.len(), 2);
+
+        let (train_again, validation_again) = dataset.train_validation_split(400_000, 7)?;
+        assert_eq!(train.features, train_again.features);
+        assert_eq!(validation.targets, validation_again.targets);
+
+        let mut all_targets: Vec<i64> = train
+            .targets
+            .iter()
+            .chain(&validation.targets)
+            .copied()
+            .collect();
+        all_targets.sort_unstable();
+        assert_eq!(all_targets, vec![1000, 1100, 1200]);
+
+        assert!(dataset.train_validation_split(100_000, 7).is_err());
+        assert!(dataset.train_validation_split(1_000_000, 7).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_feature_stats() -> Result<()> {
         let file = create_test_csv()?;
@@ -291,4 +436,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_input_distribution() -> Result<()> {
+        // Telemetry columns are not the fairness model inputs
+        let file = create_test_csv()?;
+        let dataset = Dataset::from_csv(file.path())?;
+        assert!(dataset.input_distribution(10).is_err());
+
+        let mut file = NamedTempFile::new()?;
+        writeln!(
+            file,
+            "validator_id,timestamp,stake_weight,verification_rate,proposal_rate,honesty,latency_inverse,uptime,label"
+        )?;
+        writeln!(file, "1,1,5000,9000,8000,10000,7000,100,1")?;
+        writeln!(file, "1,2,5000,9000,8000,10000,7000,150,1")?;
+        writeln!(file, "1,3,5000,9000,8000,10000,7000,200,1")?;
+        file.flush()?;
+        let dataset = Dataset::from_csv(file.path())?;
+
+        let summary = dataset.input_distribution(10)?;
+        assert_eq!(summary.features.len(), FairnessModel::FEATURE_NAMES.len());
+        assert_eq!(summary.features[0].feature, "uptime");
+        let uptime = summary.feature("uptime").unwrap();
+        assert_eq!(uptime.count, 3);
+        assert_eq!(uptime.cut_points, vec![99, 100, 150, 200]);
+        assert_eq!(uptime.fractions.iter().sum::<i64>(), 1_000_000);
+
+        // Same names at the 1e6 dataset scale
+        let mut file = NamedTempFile::new()?;
+        writeln!(
+            file,
+            "validator_id,timestamp,uptime,latency_inverse,honesty,proposal_rate,verification_rate,stake_weight,label"
+        )?;
+        writeln!(file, "1,1,990000,700000,1000000,800000,900000,500000,1")?;
+        file.flush()?;
+        assert!(Dataset::from_csv(file.path())?
+            .input_distribution(10)
+            .is_err());
+
+        Ok(())
+    }
 }
@@ -8,4 +8,7 @@ pub enum TrainerError {
 
     #[error("training error: {0}")]
     Training(String),
+
+    #[error("invalid training parameters: {0}")]
+    InvalidParams(String),
 }
@@ -0,0 +1,428 @@
+//! Dataset export from node storage
+//!
+//! Rebuilds per-round validator metrics from finalized rounds, the blocks
+//! their certificates reference and the stored [`ValidatorTelemetry`], and
+//! turns them into labelled feature rows with
+//! [`ippan_consensus_dlc::fairness_features::features_for_validator`], so
+//! training sees exactly the features the consensus engine computes at
+//! inference time.
+//!
+//! For each finalized round `r` a validator has been active in so far, the
+//! row holds the features observed up to and including `r` and the label is
+//! the share (at SCALE) of the next `label_horizon` finalized rounds in which
+//! the validator produced a block. Rounds without a full label horizon are
+//! not exported.
+//!
+//! Block activity comes from [`ippan_storage::round_history`]; stake, latency
+//! and slashing come from the latest telemetry snapshot for every exported
+//! round.
+//!
+//! Each row also carries the fairness model inputs the live drift monitor
+//! observes for the same metrics ([`fairness_features`], 0-10000). Their
+//! quantile summary is written next to the CSV by
+//! [`DatasetExport::write_distribution`] and becomes the model's training
+//! distribution when `ai-trainer train` reads that CSV.
+
+use anyhow::{Context, Result};
+use ippan_consensus_dlc::dgbdt::{FairnessModel, ValidatorMetrics};
+use ippan_consensus_dlc::fairness_drift::{fairness_features, DistributionSummary};
+use ippan_consensus_dlc::fairness_features::{features_for_validator, FEATURE_NAMES, SCALE};
+use ippan_storage::round_history::{FinalizedRound, RoundHistory};
+use ippan_storage::{Storage, ValidatorTelemetry};
+use ippan_types::{Amount, ValidatorId};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Round range and windows used to derive rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportConfig {
+    /// First round to read (default: 0)
+    pub from_round: Option<u64>,
+    /// Last round to read (default: latest finalized round)
+    pub to_round: Option<u64>,
+    /// Finalized rounds used for the uptime ratio
+    pub uptime_window: u64,
+    /// Finalized rounds after each row used for its label
+    pub label_horizon: u64,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            from_round: None,
+            to_round: None,
+            uptime_window: 100,
+            label_horizon: 10,
+        }
+    }
+}
+
+/// One labelled feature row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportRow {
+    /// Validator key folded into a non-negative integer (see [`dataset_validator_id`])
+    pub validator_id: i64,
+    pub round: u64,
+    pub features: [i64; 7],
+    /// Fairness model inputs for the same metrics, in
+    /// [`FairnessModel::FEATURE_NAMES`] order
+    pub inputs: [i64; 6],
+    pub label: i64,
+}
+
+/// Rows derived from storage, sorted by `(validator_id, round)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatasetExport {
+    pub rows: Vec<ExportRow>,
+    /// Finalized rounds read from storage
+    pub rounds: usize,
+    /// Validators with at least one exported row
+    pub validators: usize,
+}
+
+impl DatasetExport {
+    /// Derive feature rows from round finalizations and validator telemetry.
+    pub fn from_storage<S: Storage + ?Sized>(storage: &S, config: &ExportConfig) -> Result<Self> {
+        if config.uptime_window == 0 || config.label_horizon == 0 {
+            anyhow::bail!("uptime window and label horizon must be positive");
+        }
+
+        let history = RoundHistory::load(storage, config.from_round, config.to_round)?;
+        let max_stake = Amount::from_atomic(
+            history
+                .telemetry
+                .values()
+                .map(|entry| entry.stake as u128)
+                .max()
+                .unwrap_or(0),
+        );
+
+        let mut rows = Vec::new();
+        let mut exported = 0;
+        for validator in &history.creators() {
+            let before = rows.len();
+            validator_rows(
+                validator,
+                &history.rounds,
+                history.telemetry.get(validator),
+                max_stake,
+                config,
+                &mut rows,
+            );
+            if rows.len() > before {
+                exported += 1;
+            }
+        }
+        rows.sort_by_key(|row| (row.validator_id, row.round));
+
+        Ok(Self {
+            rows,
+            rounds: history.rounds.len(),
+            validators: exported,
+        })
+    }
+
+    /// Render the rows in the CSV layout read by [`crate::Dataset::from_csv`].
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("validator_id,timestamp,{},label\n", FEATURE_NAMES.join(","));
+        for row in &self.rows {
+            let _ = write!(csv, "{},{}", row.validator_id, row.round);
+            for value in row.features {
+                let _ = write!(csv, ",{value}");
+            }
+            let _ = writeln!(csv, ",{}", row.label);
+        }
+        csv
+    }
+
+    /// Write the CSV to `path`.
+    pub fn write_csv(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_csv())
+            .with_context(|| format!("failed to write dataset to {}", path.display()))
+    }
+
+    /// Quantile summary of the fairness model inputs of every row.
+    pub fn input_distribution(&self, bins: usize) -> DistributionSummary {
+        let rows: Vec<Vec<i64>> = self.rows.iter().map(|row| row.inputs.to_vec()).collect();
+        DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &rows, bins)
+    }
+
+    /// Write [`Self::input_distribution`] to [`distribution_path`] of the
+    /// dataset at `csv_path`.
+    pub fn write_distribution(&self, csv_path: &Path, bins: usize) -> Result<PathBuf> {
+        let path = distribution_path(csv_path);
+        let json = serde_json::to_string_pretty(&self.input_distribution(bins))
+            .context("failed to serialize input distribution")?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("failed to write input distribution to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Path of the input distribution stored next to a dataset or model:
+/// `<name>.distribution.json`.
+pub fn distribution_path(path: &Path) -> PathBuf {
+    path.with_extension("distribution.json")
+}
+
+/// Integer key for a validator in exported datasets: the first eight bytes
+/// of its ID, big-endian, shifted right so the value is non-negative.
+pub fn dataset_validator_id(validator: &ValidatorId) -> i64 {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&validator[..8]);
+    (u64::from_be_bytes(prefix) >> 1) as i64
+}
+
+/// Append the rows of one validator, starting at its first active round.
+fn validator_rows(
+    validator: &ValidatorId,
+    rounds: &[FinalizedRound],
+    telemetry: Option<&ValidatorTelemetry>,
+    max_stake: Amount,
+    config: &ExportConfig,
+    rows: &mut Vec<ExportRow>,
+) {
+    let horizon = config.label_horizon as usize;
+    let window = config.uptime_window as usize;
+    if rounds.len() <= horizon {
+        return;
+    }
+
+    // active_before[i] = finalized rounds among the first `i` with a block
+    let mut active_before = Vec::with_capacity(rounds.len() + 1);
+    active_before.push(0u64);
+    for round in rounds {
+        let active = round.creators.contains_key(validator) as u64;
+        active_before.push(active_before.last().copied().unwrap_or(0) + active);
+    }
+
+    let mut metrics = ValidatorMetrics {
+        latency: telemetry
+            .map(|entry| (entry.avg_latency_us / 100).min(10_000) as i64)
+            .unwrap_or(0),
+        honesty: telemetry
+            .map(|entry| 10_000 / (1 + entry.slash_count as i64))
+            .unwrap_or(10_000),
+        stake: Amount::from_atomic(telemetry.map(|entry| entry.stake as u128).unwrap_or(0)),
+        ..ValidatorMetrics::default()
+    };
+    let mut first_active: Option<usize> = None;
+    let id = dataset_validator_id(validator);
+
+    for (idx, finalized) in rounds.iter().enumerate() {
+        if let Some(activity) = finalized.activity(validator) {
+            first_active.get_or_insert(idx);
+            metrics.blocks_proposed += activity.proposed;
+            metrics.blocks_verified += activity.verified;
+            metrics.rounds_active += 1;
+        }
+        let Some(first) = first_active else {
+            continue;
+        };
+        if idx + horizon >= rounds.len() {
+            break;
+        }
+
+        let window_start = (idx + 1).saturating_sub(window).max(first);
+        let window_len = (idx + 1 - window_start) as i64;
+        let window_active = (active_before[idx + 1] - active_before[window_start]) as i64;
+        metrics.uptime = window_active * 10_000 / window_len;
+
+        let future_active = (active_before[idx + 1 + horizon] - active_before[idx + 1]) as i64;
+        rows.push(ExportRow {
+            validator_id: id,
+            round: finalized.round,
+            features: features_for_validator(&metrics, max_stake),
+            inputs: fairness_features(&metrics),
+            label: future_active * SCALE / horizon as i64,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dataset;
+    use ippan_storage::MemoryStorage;
+    use ippan_types::{
+        Block, IppanTimeMicros, RoundCertificate, RoundFinalizationRecord, RoundWindow,
+    };
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const ALICE: ValidatorId = [2u8; 32];
+    const BOB: ValidatorId = [4u8; 32];
+
+    fn finalize(storage: &MemoryStorage, round: u64, creators: &[ValidatorId]) {
+        let mut block_ids = Vec::new();
+        for creator in creators {
+            let block = Block::new(vec![], vec![], round, *creator);
+            block_ids.push(block.hash());
+            storage.store_block(block).unwrap();
+        }
+        storage
+            .store_round_finalization(RoundFinalizationRecord {
+                round,
+                window: RoundWindow {
+                    id: round,
+                    start_us: IppanTimeMicros(round * 100),
+                    end_us: IppanTimeMicros(round * 100 + 100),
+                },
+                ordered_tx_ids: vec![],
+                fork_drops: vec![],
+                state_root: [0u8; 32],
+                proof: RoundCertificate {
+                    round,
+                    block_ids,
+                    agg_sig: vec![],
+                },
+                total_fees_atomic: None,
+                treasury_fees_atomic: None,
+                applied_payments: None,
+                rejected_payments: None,
+            })
+            .unwrap();
+    }
+
+    fn telemetry(validator_id: ValidatorId, stake: u64, slash_count: u32) -> ValidatorTelemetry {
+        ValidatorTelemetry {
+            validator_id,
+            blocks_proposed: 0,
+            blocks_verified: 0,
+            rounds_active: 0,
+            avg_latency_us: 250_000,
+            slash_count,
+            stake,
+            age_rounds: 0,
+            last_active_round: 0,
+            uptime_percentage_scaled: 10_000,
+            recent_performance_scaled: 10_000,
+            network_contribution_scaled: 10_000,
+        }
+    }
+
+    /// Alice produces in every round; Bob joins at round 2 and skips odd rounds.
+    fn populated_storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for round in 1..=6 {
+            let creators: Vec<ValidatorId> = if round >= 2 && round % 2 == 0 {
+                vec![ALICE, BOB]
+            } else {
+                vec![ALICE]
+            };
+            finalize(&storage, round, &creators);
+        }
+        storage
+            .store_validator_telemetry(&ALICE, &telemetry(ALICE, 1_000, 0))
+            .unwrap();
+        storage
+            .store_validator_telemetry(&BOB, &telemetry(BOB, 500, 1))
+            .unwrap();
+        storage
+    }
+
+    fn config() -> ExportConfig {
+        ExportConfig {
+            uptime_window: 4,
+            label_horizon: 2,
+            ..ExportConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_rows_use_consensus_features_and_future_labels() {
+        let export = DatasetExport::from_storage(&populated_storage(), &config()).unwrap();
+
+        assert_eq!(export.rounds, 6);
+        assert_eq!(export.validators, 2);
+        // Rounds 1..=4 have a two-round horizon; Bob starts at round 2.
+        assert_eq!(export.rows.len(), 4 + 3);
+
+        let bob: Vec<&ExportRow> = export
+            .rows
+            .iter()
+            .filter(|row| row.validator_id == dataset_validator_id(&BOB))
+            .collect();
+        assert_eq!(
+            bob.iter().map(|row| row.round).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        // Round 3: active in 1 of 2 rounds since joining, active in round 4 of 4..=5.
+        let expected = features_for_validator(
+            &ValidatorMetrics {
+                uptime: 5_000,
+                latency: 2_500,
+                honesty: 5_000,
+                blocks_proposed: 1,
+                blocks_verified: 1,
+                stake: Amount::from_atomic(500),
+                rounds_active: 1,
+            },
+            Amount::from_atomic(1_000),
+        );
+        assert_eq!(bob[1].features, expected);
+        assert_eq!(bob[1].inputs[0], 5_000);
+        assert_eq!(bob[1].label, SCALE / 2);
+
+        let alice = export
+            .rows
+            .iter()
+            .find(|row| row.validator_id == dataset_validator_id(&ALICE))
+            .unwrap();
+        assert_eq!(alice.label, SCALE);
+    }
+
+    #[test]
+    fn test_round_range_limits_rows() {
+        let config = ExportConfig {
+            from_round: Some(3),
+            to_round: Some(5),
+            ..config()
+        };
+        let export = DatasetExport::from_storage(&populated_storage(), &config).unwrap();
+
+        assert_eq!(export.rounds, 3);
+        assert!(export.rows.iter().all(|row| row.round == 3));
+    }
+
+    #[test]
+    fn test_csv_round_trips_through_dataset() {
+        let export = DatasetExport::from_storage(&populated_storage(), &config()).unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(export.to_csv().as_bytes()).unwrap();
+        file.flush().unwrap();
+
+        let dataset = Dataset::from_csv(file.path()).unwrap();
+        assert_eq!(dataset.len(), export.rows.len());
+        assert_eq!(
+            dataset.feature_names,
+            FEATURE_NAMES.map(String::from).to_vec()
+        );
+        assert_eq!(dataset.features[0], export.rows[0].features.to_vec());
+        assert_eq!(dataset.targets[0], export.rows[0].label);
+    }
+
+    #[test]
+    fn test_input_distribution_matches_live_monitor() {
+        use ippan_consensus_dlc::fairness_drift::validate_reference;
+
+        let export = DatasetExport::from_storage(&populated_storage(), &config()).unwrap();
+        let summary = export.input_distribution(10);
+        validate_reference(&summary).unwrap();
+        assert_eq!(summary.features[0].feature, "uptime");
+        assert_eq!(summary.features[0].count, export.rows.len() as u64);
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("fairness.csv");
+        let path = export.write_distribution(&csv, 10).unwrap();
+        assert_eq!(path, dir.path().join("fairness.distribution.json"));
+        let written: DistributionSummary =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(written, summary);
+    }
+
+    #[test]
+    fn test_empty_storage_is_an_error() {
+        assert!(DatasetExport::from_storage(&MemoryStorage::default(), &config()).is_err());
+    }
+}
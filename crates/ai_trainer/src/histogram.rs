@@ -0,0 +1,272 @@
+//! Histogram binning for split finding
+//!
+//! Feature values are bucketed once per dataset: values are first grouped by
+//! `quant_step`, and when a feature has more distinct quanta than `max_bins`
+//! neighbouring quanta are merged into bins of roughly equal row counts. Each
+//! bin is identified by its inclusive upper threshold, so a split after bin
+//! `b` is exactly the runtime test `value <= threshold(b)`.
+//!
+//! Gradient and hessian sums are accumulated in `i128`, which keeps
+//! histogram subtraction exact and makes the result independent of the order
+//! (and therefore the thread) in which rows are summed.
+
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// Default upper bound on the number of bins per feature.
+pub const DEFAULT_MAX_BINS: usize = 256;
+
+/// Dataset features mapped to per-feature bin indices.
+#[derive(Clone, Debug)]
+pub struct BinnedFeatures {
+    /// Inclusive upper threshold of every bin, per feature
+    edges: Vec<Vec<i64>>,
+    /// Bin index of every row, per feature (column-major)
+    bins: Vec<Vec<u32>>,
+    rows: usize,
+}
+
+impl BinnedFeatures {
+    /// Bin row-major feature vectors.
+    pub fn from_rows(features: &[Vec<i64>], quant_step: i64, max_bins: usize) -> Self {
+        let quant_step = quant_step.max(1);
+        let max_bins = max_bins.max(2);
+        let feature_count = features.first().map(|row| row.len()).unwrap_or(0);
+
+        let (edges, bins) = (0..feature_count)
+            .into_par_iter()
+            .map(|feature| {
+                let edges = feature_edges(features, feature, quant_step, max_bins);
+                let bins = features
+                    .iter()
+                    .map(|row| edges.partition_point(|&edge| edge < row[feature]) as u32)
+                    .collect();
+                (edges, bins)
+            })
+            .unzip();
+
+        Self {
+            edges,
+            bins,
+            rows: features.len(),
+        }
+    }
+
+    /// Number of binned rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of binned features
+    pub fn feature_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Number of bins for a feature
+    pub fn bin_count(&self, feature: usize) -> usize {
+        self.edges[feature].len()
+    }
+
+    /// Split threshold that sends bins `0..=bin` to the left child
+    pub fn threshold(&self, feature: usize, bin: usize) -> i64 {
+        self.edges[feature][bin]
+    }
+
+    /// Bin index of a row for a feature
+    pub fn bin(&self, feature: usize, row: usize) -> usize {
+        self.bins[feature][row] as usize
+    }
+}
+
+/// Inclusive upper threshold of every bin for one feature.
+fn feature_edges(
+    features: &[Vec<i64>],
+    feature: usize,
+    quant_step: i64,
+    max_bins: usize,
+) -> Vec<i64> {
+    let mut quanta: BTreeMap<i64, u64> = BTreeMap::new();
+    for row in features {
+        *quanta
+            .entry(row[feature].div_euclid(quant_step))
+            .or_default() += 1;
+    }
+
+    let upper = |quantum: i64| {
+        quantum
+            .saturating_mul(quant_step)
+            .saturating_add(quant_step - 1)
+    };
+
+    if quanta.len() <= max_bins {
+        return quanta.into_keys().map(upper).collect();
+    }
+
+    // Merge neighbouring quanta into bins of roughly equal row counts.
+    let per_bin = (features.len() as u64).div_ceil(max_bins as u64);
+    let last = *quanta.keys().next_back().expect("non-empty quanta");
+    let mut edges = Vec::with_capacity(max_bins);
+    let mut filled = 0u64;
+    for (&quantum, &count) in &quanta {
+        filled += count;
+        if (filled >= per_bin && edges.len() + 1 < max_bins) || quantum == last {
+            edges.push(upper(quantum));
+            filled = 0;
+        }
+    }
+    edges
+}
+
+/// Gradient statistics of the rows that fall into one bin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BinStats {
+    pub gradient: i128,
+    pub hessian: i128,
+    pub count: u64,
+}
+
+impl BinStats {
+    fn add_row(&mut self, gradient: i64, hessian: i64) {
+        self.gradient += gradient as i128;
+        self.hessian += hessian as i128;
+        self.count += 1;
+    }
+
+    /// Component-wise sum
+    pub fn plus(&self, other: &BinStats) -> BinStats {
+        BinStats {
+            gradient: self.gradient + other.gradient,
+            hessian: self.hessian + other.hessian,
+            count: self.count + other.count,
+        }
+    }
+
+    /// Component-wise difference
+    pub fn minus(&self, other: &BinStats) -> BinStats {
+        BinStats {
+            gradient: self.gradient - other.gradient,
+            hessian: self.hessian - other.hessian,
+            count: self.count - other.count,
+        }
+    }
+}
+
+/// Per-feature gradient histograms for the rows of one tree node.
+#[derive(Clone, Debug)]
+pub struct NodeHistogram {
+    /// Histogram per candidate feature, in the order they were requested
+    bins: Vec<Vec<BinStats>>,
+    total: BinStats,
+}
+
+impl NodeHistogram {
+    /// Accumulate histograms for `features` over `rows`.
+    pub fn build(
+        binned: &BinnedFeatures,
+        features: &[usize],
+        rows: &[usize],
+        gradients: &[i64],
+        hessians: &[i64],
+    ) -> Self {
+        let bins = features
+            .par_iter()
+            .map(|&feature| {
+                let mut histogram = vec![BinStats::default(); binned.bin_count(feature)];
+                for &row in rows {
+                    histogram[binned.bin(feature, row)].add_row(gradients[row], hessians[row]);
+                }
+                histogram
+            })
+            .collect();
+
+        let mut total = BinStats::default();
+        for &row in rows {
+            total.add_row(gradients[row], hessians[row]);
+        }
+
+        Self { bins, total }
+    }
+
+    /// Histogram of the sibling of `child`, where `self` is their parent.
+    pub fn subtract(&self, child: &NodeHistogram) -> NodeHistogram {
+        let bins = self
+            .bins
+            .iter()
+            .zip(&child.bins)
+            .map(|(parent, child)| parent.iter().zip(child).map(|(p, c)| p.minus(c)).collect())
+            .collect();
+
+        NodeHistogram {
+            bins,
+            total: self.total.minus(&child.total),
+        }
+    }
+
+    /// Totals over every row of the node
+    pub fn total(&self) -> BinStats {
+        self.total
+    }
+
+    /// Histogram of the `position`-th requested feature
+    pub fn feature(&self, position: usize) -> &[BinStats] {
+        &self.bins[position]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bins_follow_quantization_step() {
+        let features = vec![vec![5], vec![12], vec![19], vec![20], vec![-3]];
+        let binned = BinnedFeatures::from_rows(&features, 10, DEFAULT_MAX_BINS);
+
+        assert_eq!(binned.bin_count(0), 4);
+        assert_eq!(
+            (0..4).map(|b| binned.threshold(0, b)).collect::<Vec<_>>(),
+            vec![-1, 9, 19, 29]
+        );
+        let bins: Vec<usize> = (0..features.len()).map(|row| binned.bin(0, row)).collect();
+        assert_eq!(bins, vec![1, 2, 2, 3, 0]);
+
+        // Every row satisfies the threshold of its own bin and not the one below.
+        for (row, values) in features.iter().enumerate() {
+            let bin = binned.bin(0, row);
+            assert!(values[0] <= binned.threshold(0, bin));
+            assert!(bin == 0 || values[0] > binned.threshold(0, bin - 1));
+        }
+    }
+
+    #[test]
+    fn test_bins_are_capped_by_row_quantiles() {
+        let features: Vec<Vec<i64>> = (0..1_000).map(|v| vec![v]).collect();
+        let binned = BinnedFeatures::from_rows(&features, 1, 16);
+
+        assert_eq!(binned.bin_count(0), 16);
+        assert_eq!(binned.threshold(0, 15), 999);
+        assert_eq!(binned.threshold(0, 0), 62);
+        assert_eq!(binned.bin(0, 62), 0);
+        assert_eq!(binned.bin(0, 63), 1);
+    }
+
+    #[test]
+    fn test_sibling_subtraction_matches_direct_build() {
+        let features: Vec<Vec<i64>> = (0..50).map(|v| vec![v * 7 % 23, v % 5]).collect();
+        let gradients: Vec<i64> = (0..50).map(|v| v * 13 - 300).collect();
+        let hessians: Vec<i64> = (0..50).map(|v| 1 + v % 3).collect();
+        let binned = BinnedFeatures::from_rows(&features, 4, DEFAULT_MAX_BINS);
+
+        let all: Vec<usize> = (0..50).collect();
+        let (left, right): (Vec<usize>, Vec<usize>) = all.iter().partition(|&&row| row % 3 == 0);
+
+        let parent = NodeHistogram::build(&binned, &[0, 1], &all, &gradients, &hessians);
+        let left_hist = NodeHistogram::build(&binned, &[0, 1], &left, &gradients, &hessians);
+        let right_hist = NodeHistogram::build(&binned, &[0, 1], &right, &gradients, &hessians);
+        let derived = parent.subtract(&left_hist);
+
+        assert_eq!(derived.total(), right_hist.total());
+        assert_eq!(derived.feature(0), right_hist.feature(0));
+        assert_eq!(derived.feature(1), right_hist.feature(1));
+    }
+}
@@ -7,6 +7,10 @@ pub mod cart;
 pub mod dataset;
 pub mod deterministic;
 pub mod errors;
+pub mod export;
+pub mod histogram;
+pub mod objective;
+pub mod report;
 pub mod trainer;
 
 use ippan_ai_core::gbdt::Model;
@@ -15,6 +19,10 @@ use std::path::Path;
 pub use dataset::{Dataset, FeatureStats, FEATURE_COLUMNS};
 pub use deterministic::{LcgRng, SplitTieBreaker};
 pub use errors::TrainerError;
+pub use export::{distribution_path, DatasetExport, ExportConfig, ExportRow};
+pub use histogram::{BinnedFeatures, DEFAULT_MAX_BINS};
+pub use objective::Objective;
+pub use report::{FeatureImportance, LossPoint, TrainingReport};
 pub use trainer::{GbdtTrainer, TrainingParams};
 
 /// Train a deterministic model directly from a CSV file using the provided parameters.
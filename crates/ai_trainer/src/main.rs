@@ -4,11 +4,20 @@
 
 use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
+use ippan_ai_core::drift::DEFAULT_SUMMARY_BINS;
 use ippan_ai_core::{canonical_model_json, model_hash_hex};
-use ippan_ai_trainer::{train_model_from_csv, TrainingParams};
+use ippan_ai_registry::AiModelProposal;
+use ippan_ai_trainer::{
+    distribution_path, Dataset, DatasetExport, ExportConfig, GbdtTrainer, Objective,
+    TrainingParams, TrainingReport, DEFAULT_MAX_BINS,
+};
+use ippan_consensus::replay::{rounds_from_storage, CounterfactualReplay, ReplayConfig};
+use ippan_consensus_dlc::dgbdt::FairnessModel;
+use ippan_consensus_dlc::fairness_drift::{validate_reference, DistributionSummary};
+use ippan_storage::SledStorage;
 use std::fs;
-use std::path::PathBuf;
-use tracing::{info, Level};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 #[derive(Parser, Debug)]
@@ -25,6 +34,11 @@ struct Cli {
 enum Commands {
     /// Train a deterministic model from a telemetry dataset
     Train(TrainArgs),
+    /// Export a labelled fairness dataset from a node's storage directory
+    Export(ExportArgs),
+    /// Replay stored rounds with a candidate fairness model and compare it
+    /// with the active one
+    Replay(ReplayArgs),
 }
 
 #[derive(Args, Debug)]
@@ -33,7 +47,10 @@ struct TrainArgs {
     #[arg(long)]
     dataset: PathBuf,
 
-    /// Output model JSON path
+    /// Output model JSON path; the training report is written alongside it
+    /// as `<name>.report.json`, and the fairness input distribution as
+    /// `<name>.distribution.json` when the dataset provides one (see
+    /// `export`)
     #[arg(long)]
     out: PathBuf,
 
@@ -56,6 +73,101 @@ struct TrainArgs {
     /// Feature quantization step
     #[arg(long, default_value_t = 10_000)]
     quantization_step: i64,
+
+    /// Training objective: squared_error, logistic or pairwise_ranking
+    #[arg(long, default_value_t = Objective::SquaredError)]
+    objective: Objective,
+
+    /// Maximum histogram bins per feature
+    #[arg(long, default_value_t = DEFAULT_MAX_BINS)]
+    max_bins: usize,
+
+    /// Fraction of rows sampled per tree in micros (1000000 = all)
+    #[arg(long, default_value_t = 1_000_000)]
+    row_subsample_micro: i64,
+
+    /// Fraction of features considered per tree in micros (1000000 = all)
+    #[arg(long, default_value_t = 1_000_000)]
+    feature_subsample_micro: i64,
+
+    /// Seed for row and feature subsampling and the validation split
+    #[arg(long, default_value_t = 42)]
+    seed: i64,
+
+    /// Worker threads (0 = one per core); the model does not depend on it
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Share of rows held out for validation in micros (0 = no split)
+    #[arg(long, default_value_t = 0)]
+    validation_micro: i64,
+
+    /// Stop after this many trees without validation improvement (0 = off)
+    #[arg(long, default_value_t = 0)]
+    early_stopping_rounds: usize,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    /// Node sled database directory (stop the node first)
+    #[arg(long)]
+    db: PathBuf,
+
+    /// Output CSV dataset path; the fairness input distribution is written
+    /// alongside it as `<name>.distribution.json`
+    #[arg(long)]
+    out: PathBuf,
+
+    /// First round to export (default: 0)
+    #[arg(long)]
+    from_round: Option<u64>,
+
+    /// Last round to export (default: latest finalized round)
+    #[arg(long)]
+    to_round: Option<u64>,
+
+    /// Finalized rounds used for the uptime ratio
+    #[arg(long, default_value_t = 100)]
+    uptime_window: u64,
+
+    /// Finalized rounds after each row used for its label
+    #[arg(long, default_value_t = 10)]
+    label_horizon: u64,
+}
+
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    /// Node sled database directory (stop the node first)
+    #[arg(long)]
+    db: PathBuf,
+
+    /// Active D-GBDT model JSON
+    #[arg(long)]
+    active: PathBuf,
+
+    /// Candidate D-GBDT model JSON
+    #[arg(long)]
+    candidate: PathBuf,
+
+    /// Output JSON report path
+    #[arg(long)]
+    out: PathBuf,
+
+    /// First round to replay (default: 0)
+    #[arg(long)]
+    from_round: Option<u64>,
+
+    /// Last round to replay (default: latest finalized round)
+    #[arg(long)]
+    to_round: Option<u64>,
+
+    /// Shadow verifiers selected per round
+    #[arg(long, default_value_t = 3)]
+    shadow_count: usize,
+
+    /// AiModelProposal JSON to attach the report to (rewritten in place)
+    #[arg(long)]
+    proposal: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -69,11 +181,18 @@ fn main() -> Result<()> {
 
     match cli.command {
         Commands::Train(args) => run_train(args),
+        Commands::Export(args) => run_export(args),
+        Commands::Replay(args) => run_replay(args),
     }
 }
 
 fn run_train(args: TrainArgs) -> Result<()> {
-    info!(dataset = %args.dataset.display(), out = %args.out.display(), starting = true);
+    info!(
+        dataset = %args.dataset.display(),
+        out = %args.out.display(),
+        objective = %args.objective,
+        starting = true
+    );
 
     let params = TrainingParams {
         tree_count: args.tree_count,
@@ -81,9 +200,19 @@ fn run_train(args: TrainArgs) -> Result<()> {
         min_samples_leaf: args.min_samples_leaf,
         learning_rate_micro: args.learning_rate_micro,
         quantization_step: args.quantization_step,
+        objective: args.objective,
+        max_bins: args.max_bins,
+        row_subsample_micro: args.row_subsample_micro,
+        feature_subsample_micro: args.feature_subsample_micro,
+        seed: args.seed,
+        threads: args.threads,
+        validation_micro: args.validation_micro,
+        early_stopping_rounds: args.early_stopping_rounds,
     };
 
-    let model = train_model_from_csv(&args.dataset, params)
+    let dataset = Dataset::from_csv(&args.dataset).context("failed to load dataset")?;
+    let (model, report) = GbdtTrainer::new(params)
+        .train_with_report(&dataset)
         .context("failed to train deterministic model")?;
 
     let canonical = canonical_model_json(&model).context("failed to canonicalize model")?;
@@ -100,5 +229,131 @@ fn run_train(args: TrainArgs) -> Result<()> {
     info!(model_hash = %hash);
     println!("model_hash={hash}");
 
+    let report_path = TrainingReport::path_for_model(&args.out);
+    report.write_json(&report_path)?;
+    info!(
+        report = %report_path.display(),
+        trees = report.trees_kept,
+        stopped_early = report.stopped_early
+    );
+    println!("report={}", report_path.display());
+
+    match training_distribution(&args.dataset, &dataset)? {
+        Some(summary) => {
+            let distribution_path = distribution_path(&args.out);
+            let json = serde_json::to_string_pretty(&summary)
+                .context("failed to serialize training distribution")?;
+            fs::write(&distribution_path, json).context("failed to write training distribution")?;
+            println!("distribution={}", distribution_path.display());
+        }
+        None => warn!(
+            "no training distribution written: the dataset has no fairness input \
+             distribution and no fairness input columns; input drift will not be \
+             monitored for this model"
+        ),
+    }
+
+    Ok(())
+}
+
+/// Fairness input distribution for a model trained on `dataset`: the one
+/// written next to the CSV by `export`, or a summary of the dataset's own
+/// fairness input columns. A stored distribution over other features is
+/// an error.
+fn training_distribution(
+    dataset_path: &Path,
+    dataset: &Dataset,
+) -> Result<Option<DistributionSummary>> {
+    let exported = distribution_path(dataset_path);
+    if exported.exists() {
+        let summary: DistributionSummary = serde_json::from_slice(
+            &fs::read(&exported)
+                .with_context(|| format!("failed to read {}", exported.display()))?,
+        )
+        .with_context(|| format!("failed to parse {}", exported.display()))?;
+        validate_reference(&summary)
+            .with_context(|| format!("invalid input distribution {}", exported.display()))?;
+        return Ok(Some(summary));
+    }
+    Ok(dataset.input_distribution(DEFAULT_SUMMARY_BINS).ok())
+}
+
+fn run_export(args: ExportArgs) -> Result<()> {
+    info!(db = %args.db.display(), out = %args.out.display(), starting = true);
+
+    let storage = SledStorage::new(&args.db)
+        .with_context(|| format!("failed to open storage at {}", args.db.display()))?;
+    let config = ExportConfig {
+        from_round: args.from_round,
+        to_round: args.to_round,
+        uptime_window: args.uptime_window,
+        label_horizon: args.label_horizon,
+    };
+    let export = DatasetExport::from_storage(&storage, &config)
+        .context("failed to derive dataset from storage")?;
+
+    if let Some(parent) = args.out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).context("failed to create output directory")?;
+    }
+    export.write_csv(&args.out)?;
+    let distribution = export.write_distribution(&args.out, DEFAULT_SUMMARY_BINS)?;
+
+    info!(
+        rounds = export.rounds,
+        validators = export.validators,
+        rows = export.rows.len()
+    );
+    println!("rows={}", export.rows.len());
+    println!("distribution={}", distribution.display());
+
+    Ok(())
+}
+
+fn run_replay(args: ReplayArgs) -> Result<()> {
+    info!(
+        db = %args.db.display(),
+        candidate = %args.candidate.display(),
+        starting = true
+    );
+
+    let active = FairnessModel::from_d_gbdt_file(&args.active)
+        .with_context(|| format!("failed to load active model {}", args.active.display()))?;
+    let candidate = FairnessModel::from_d_gbdt_file(&args.candidate).with_context(|| {
+        format!(
+            "failed to load candidate model {}",
+            args.candidate.display()
+        )
+    })?;
+
+    let storage = SledStorage::new(&args.db)
+        .with_context(|| format!("failed to open storage at {}", args.db.display()))?;
+    let rounds = rounds_from_storage(&storage, args.from_round, args.to_round)?;
+    let config = ReplayConfig {
+        shadow_count: args.shadow_count,
+        ..ReplayConfig::default()
+    };
+    let report = CounterfactualReplay::new(active, candidate, config).run(&rounds)?;
+
+    fs::write(&args.out, report.to_json()?)
+        .with_context(|| format!("failed to write report to {}", args.out.display()))?;
+    info!(
+        rounds = report.rounds,
+        weighted_primary_changes = report.weighted_primary_changes,
+        payout_gini_delta = report.payout_gini_delta,
+        stake_fairness_delta = report.stake_fairness_delta
+    );
+    println!("report={}", args.out.display());
+
+    if let Some(path) = &args.proposal {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read proposal {}", path.display()))?;
+        let mut proposal: AiModelProposal =
+            serde_json::from_str(&raw).context("failed to parse proposal")?;
+        report.attach_to(&mut proposal)?;
+        fs::write(path, serde_json::to_string_pretty(&proposal)?)
+            .with_context(|| format!("failed to write proposal {}", path.display()))?;
+        println!("proposal={}", path.display());
+    }
+
     Ok(())
 }
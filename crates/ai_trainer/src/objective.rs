@@ -0,0 +1,425 @@
+//! Fixed-point training objectives
+//!
+//! An objective turns the current raw predictions into per-sample gradients
+//! and hessians for the CART builder. Every quantity is an integer at
+//! `SCALE` (1_000_000); the logistic link uses a fixed sigmoid lookup table
+//! with linear interpolation so results are bit-identical on every platform.
+
+use ippan_ai_core::gbdt::SCALE;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::TrainerError;
+
+/// Distance between sigmoid table entries (1/16 at SCALE).
+const SIGMOID_STEP: i64 = SCALE / 16;
+
+/// `sigmoid(k / 16)` at SCALE for `k = 0..=128`, i.e. `x` in `[0, 8]`.
+///
+/// Negative inputs use `sigmoid(-x) = 1 - sigmoid(x)`; inputs beyond ±8
+/// saturate at the last entry.
+const SIGMOID_TABLE: [i64; 129] = [
+    500000, 515620, 531209, 546738, 562177, 577495, 592667, 607663, 622459, 637031, 651355, 665411,
+    679179, 692642, 705785, 718594, 731059, 743168, 754915, 766294, 777300, 787931, 798187, 808067,
+    817574, 826712, 835484, 843895, 851953, 859664, 867036, 874077, 880797, 887205, 893309, 899121,
+    904651, 909907, 914901, 919643, 924142, 928409, 932453, 936285, 939913, 943348, 946597, 949669,
+    952574, 955319, 957912, 960361, 962673, 964855, 966914, 968856, 970688, 972415, 974043, 975577,
+    977023, 978385, 979668, 980876, 982014, 983085, 984094, 985043, 985936, 986777, 987568, 988313,
+    989013, 989672, 990292, 990874, 991423, 991938, 992423, 992879, 993307, 993710, 994089, 994445,
+    994780, 995095, 995390, 995668, 995930, 996176, 996406, 996623, 996827, 997019, 997199, 997368,
+    997527, 997677, 997817, 997949, 998073, 998190, 998299, 998402, 998499, 998590, 998675, 998755,
+    998830, 998901, 998968, 999030, 999089, 999144, 999196, 999245, 999290, 999333, 999374, 999412,
+    999447, 999481, 999512, 999542, 999569, 999596, 999620, 999643, 999665,
+];
+
+/// Largest input magnitude covered by the table (8.0 at SCALE).
+const SIGMOID_LIMIT: i64 = (SIGMOID_TABLE.len() as i64 - 1) * SIGMOID_STEP;
+
+/// Deterministic integer sigmoid. Input and output are at SCALE.
+pub fn sigmoid_micro(x: i64) -> i64 {
+    let magnitude = (x.unsigned_abs().min(SIGMOID_LIMIT as u64)) as i64;
+    let idx = (magnitude / SIGMOID_STEP) as usize;
+    let value = match SIGMOID_TABLE.get(idx + 1) {
+        Some(&hi) => {
+            let lo = SIGMOID_TABLE[idx];
+            lo + (hi - lo) * (magnitude % SIGMOID_STEP) / SIGMOID_STEP
+        }
+        None => SIGMOID_TABLE[idx],
+    };
+
+    if x < 0 {
+        SCALE - value
+    } else {
+        value
+    }
+}
+
+/// Inverse of [`sigmoid_micro`]: log-odds of a probability at SCALE,
+/// saturating at ±8.
+pub fn logit_micro(p: i64) -> i64 {
+    let max = SIGMOID_TABLE[SIGMOID_TABLE.len() - 1];
+    let p = p.clamp(SCALE - max, max);
+    let upper = p.max(SCALE - p);
+
+    let idx = SIGMOID_TABLE
+        .partition_point(|&v| v <= upper)
+        .saturating_sub(1)
+        .min(SIGMOID_TABLE.len() - 2);
+    let lo = SIGMOID_TABLE[idx];
+    let hi = SIGMOID_TABLE[idx + 1];
+    let magnitude = idx as i64 * SIGMOID_STEP + (upper - lo) * SIGMOID_STEP / (hi - lo);
+
+    if p < SCALE / 2 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// `ln 2` at 1e12.
+const LN2_PICO: i128 = 693_147_180_560;
+
+/// Deterministic natural logarithm of a positive value at SCALE, returned
+/// at SCALE. Non-positive inputs are treated as the smallest representable
+/// value.
+pub fn ln_micro(x: i64) -> i64 {
+    const PICO: i128 = 1_000_000_000_000;
+    let scale = SCALE as i128;
+
+    // Normalise to m in [1, 2) so that x = m * 2^k.
+    let mut m = x.max(1) as i128;
+    let mut k = 0i128;
+    while m >= 2 * scale {
+        m /= 2;
+        k += 1;
+    }
+    while m < scale {
+        m *= 2;
+        k -= 1;
+    }
+
+    // ln m = 2 * atanh(u) = 2 * (u + u^3/3 + u^5/5 + ...), u = (m-1)/(m+1) <= 1/3
+    let u = (m - scale) * PICO / (m + scale);
+    let u_squared = u * u / PICO;
+    let mut term = u;
+    let mut sum = 0i128;
+    let mut denominator = 1i128;
+    while term != 0 {
+        sum += term / denominator;
+        term = term * u_squared / PICO;
+        denominator += 2;
+    }
+
+    ((k * LN2_PICO + 2 * sum) / (PICO / scale)) as i64
+}
+
+/// Loss function minimised by the boosting loop.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Objective {
+    /// Regression on the raw label with a unit hessian.
+    #[default]
+    SquaredError,
+    /// Binary cross-entropy on log-odds. Labels are probabilities at SCALE
+    /// (`0` or `1_000_000` for hard labels) and are clamped into that range.
+    Logistic,
+    /// RankNet-style pairwise loss: within each round, every pair of rows
+    /// with different labels pushes the higher-labelled row above the other.
+    PairwiseRanking,
+}
+
+impl Objective {
+    /// Stable name used on the CLI and in model metadata.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::SquaredError => "squared_error",
+            Objective::Logistic => "logistic",
+            Objective::PairwiseRanking => "pairwise_ranking",
+        }
+    }
+
+    /// Units of the hessians returned by [`Objective::gradients_hessians`].
+    ///
+    /// Squared error counts samples (1 each); the second-order objectives
+    /// report hessians at SCALE so leaf values `-G * scale / H` stay at SCALE.
+    pub fn hessian_scale(&self) -> i64 {
+        match self {
+            Objective::SquaredError => 1,
+            Objective::Logistic | Objective::PairwiseRanking => SCALE,
+        }
+    }
+
+    /// Whether the objective needs the per-row round keys.
+    pub fn is_grouped(&self) -> bool {
+        matches!(self, Objective::PairwiseRanking)
+    }
+
+    /// Constant starting prediction (the model bias).
+    pub fn initial_score(&self, targets: &[i64]) -> i64 {
+        match self {
+            Objective::SquaredError => mean(targets.iter().copied()),
+            Objective::Logistic => {
+                if targets.is_empty() {
+                    return 0;
+                }
+                logit_micro(mean(targets.iter().map(|&t| t.clamp(0, SCALE))))
+            }
+            // Rankings are invariant to a constant shift.
+            Objective::PairwiseRanking => 0,
+        }
+    }
+
+    /// First and second derivatives of the loss at the current predictions.
+    ///
+    /// `rounds` groups rows for [`Objective::PairwiseRanking`] and is ignored
+    /// by the pointwise objectives.
+    pub fn gradients_hessians(
+        &self,
+        targets: &[i64],
+        predictions: &[i64],
+        rounds: &[i64],
+    ) -> (Vec<i64>, Vec<i64>) {
+        match self {
+            Objective::SquaredError => {
+                let gradients = targets
+                    .iter()
+                    .zip(predictions)
+                    .map(|(&target, &pred)| pred.saturating_sub(target))
+                    .collect();
+                (gradients, vec![1; targets.len()])
+            }
+            Objective::Logistic => targets
+                .iter()
+                .zip(predictions)
+                .map(|(&target, &pred)| {
+                    let p = sigmoid_micro(pred);
+                    (p - target.clamp(0, SCALE), bernoulli_variance(p).max(1))
+                })
+                .unzip(),
+            Objective::PairwiseRanking => pairwise_gradients(targets, predictions, rounds),
+        }
+    }
+
+    /// Mean loss at SCALE, used for training curves and early stopping.
+    ///
+    /// Squared error reports `(pred - target)^2 / SCALE`, logistic the
+    /// cross-entropy in nats, and pairwise ranking the mean RankNet loss over
+    /// ordered pairs within each round.
+    pub fn loss(&self, targets: &[i64], predictions: &[i64], rounds: &[i64]) -> i64 {
+        match self {
+            Objective::SquaredError => mean(targets.iter().zip(predictions).map(|(&t, &p)| {
+                let err = p as i128 - t as i128;
+                (err * err / SCALE as i128).min(i64::MAX as i128) as i64
+            })),
+            Objective::Logistic => mean(targets.iter().zip(predictions).map(|(&t, &pred)| {
+                let y = t.clamp(0, SCALE) as i128;
+                let p = sigmoid_micro(pred).clamp(1, SCALE - 1);
+                let nats =
+                    y * ln_micro(p) as i128 + (SCALE as i128 - y) * ln_micro(SCALE - p) as i128;
+                (-nats / SCALE as i128) as i64
+            })),
+            Objective::PairwiseRanking => {
+                let mut total = 0i128;
+                let mut pairs = 0i128;
+                for_each_ordered_pair(targets, rounds, |better, worse| {
+                    let p = sigmoid_micro(predictions[better].saturating_sub(predictions[worse]));
+                    total -= ln_micro(p) as i128;
+                    pairs += 1;
+                });
+                if pairs == 0 {
+                    0
+                } else {
+                    (total / pairs) as i64
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Objective {
+    type Err = TrainerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "squared_error" => Ok(Objective::SquaredError),
+            "logistic" => Ok(Objective::Logistic),
+            "pairwise_ranking" => Ok(Objective::PairwiseRanking),
+            other => Err(TrainerError::InvalidParams(format!(
+                "unknown objective '{other}' (expected squared_error, logistic or pairwise_ranking)"
+            ))),
+        }
+    }
+}
+
+fn mean(values: impl ExactSizeIterator<Item = i64>) -> i64 {
+    let count = values.len();
+    if count == 0 {
+        return 0;
+    }
+
+    let sum: i128 = values.map(|v| v as i128).sum();
+    (sum / count as i128) as i64
+}
+
+/// `p * (1 - p)` at SCALE.
+fn bernoulli_variance(p: i64) -> i64 {
+    ((p as i128 * (SCALE - p) as i128) / SCALE as i128) as i64
+}
+
+fn pairwise_gradients(
+    targets: &[i64],
+    predictions: &[i64],
+    rounds: &[i64],
+) -> (Vec<i64>, Vec<i64>) {
+    let mut gradients = vec![0i64; targets.len()];
+    let mut hessians = vec![0i64; targets.len()];
+
+    for_each_ordered_pair(targets, rounds, |better, worse| {
+        // Probability the pair is currently ranked the wrong way round.
+        let rho = sigmoid_micro(predictions[worse].saturating_sub(predictions[better]));
+        let weight = bernoulli_variance(rho);
+
+        gradients[better] = gradients[better].saturating_sub(rho);
+        gradients[worse] = gradients[worse].saturating_add(rho);
+        hessians[better] = hessians[better].saturating_add(weight);
+        hessians[worse] = hessians[worse].saturating_add(weight);
+    });
+
+    (gradients, hessians)
+}
+
+/// Visit every `(better, worse)` pair of rows sharing a round, in round
+/// order and then row order.
+fn for_each_ordered_pair(targets: &[i64], rounds: &[i64], mut visit: impl FnMut(usize, usize)) {
+    let mut groups: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (idx, &round) in rounds.iter().enumerate().take(targets.len()) {
+        groups.entry(round).or_default().push(idx);
+    }
+
+    for rows in groups.values() {
+        for (pos, &a) in rows.iter().enumerate() {
+            for &b in &rows[pos + 1..] {
+                match targets[a].cmp(&targets[b]) {
+                    std::cmp::Ordering::Greater => visit(a, b),
+                    std::cmp::Ordering::Less => visit(b, a),
+                    std::cmp::Ordering::Equal => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigmoid_table_is_symmetric_and_monotonic() {
+        assert_eq!(sigmoid_micro(0), SCALE / 2);
+        assert_eq!(sigmoid_micro(8 * SCALE), 999_665);
+        assert_eq!(sigmoid_micro(i64::MAX), 999_665);
+        assert_eq!(sigmoid_micro(i64::MIN), SCALE - 999_665);
+
+        let mut prev = 0;
+        for x in (-9 * SCALE..=9 * SCALE).step_by(12_345) {
+            let y = sigmoid_micro(x);
+            assert!(y >= prev);
+            assert_eq!(y + sigmoid_micro(-x), SCALE);
+            prev = y;
+        }
+
+        // Interpolates between table entries: sigmoid(1.03125) ~ 0.737
+        assert_eq!(sigmoid_micro(SCALE + SIGMOID_STEP / 2), 737_113);
+    }
+
+    #[test]
+    fn test_logit_inverts_sigmoid() {
+        assert_eq!(logit_micro(SCALE / 2), 0);
+        for x in [-7 * SCALE, -1_234_567, -SIGMOID_STEP, 250_000, 3 * SCALE] {
+            let roundtrip = logit_micro(sigmoid_micro(x));
+            assert!(
+                (roundtrip - x).abs() <= SIGMOID_STEP / 64,
+                "{x} -> {roundtrip}"
+            );
+        }
+        assert_eq!(logit_micro(0), -8 * SCALE);
+        assert_eq!(logit_micro(SCALE), 8 * SCALE);
+    }
+
+    #[test]
+    fn test_ln_micro() {
+        assert_eq!(ln_micro(SCALE), 0);
+        assert_eq!(ln_micro(2 * SCALE), 693_147);
+        assert_eq!(ln_micro(SCALE / 2), -693_147);
+        assert!((ln_micro(2_718_282) - SCALE).abs() <= 1);
+        assert!((ln_micro(1) + 13_815_511).abs() <= 1);
+    }
+
+    #[test]
+    fn test_losses() {
+        assert_eq!(
+            Objective::SquaredError.loss(&[SCALE, 0], &[0, 2 * SCALE], &[]),
+            (SCALE + 4 * SCALE) / 2
+        );
+
+        // Predicting 0.5 for hard labels costs ln 2 per row.
+        let logistic = Objective::Logistic.loss(&[SCALE, 0], &[0, 0], &[]);
+        assert!((logistic - 693_147).abs() <= 1);
+        let confident = Objective::Logistic.loss(&[SCALE, 0], &[4 * SCALE, -4 * SCALE], &[]);
+        assert!(confident < 20_000);
+
+        let tied = Objective::PairwiseRanking.loss(&[2, 1, 5], &[0, 0, 0], &[1, 1, 2]);
+        assert!((tied - 693_147).abs() <= 1);
+        let ordered = Objective::PairwiseRanking.loss(&[2, 1], &[3 * SCALE, 0], &[1, 1]);
+        assert!(ordered < tied);
+    }
+
+    #[test]
+    fn test_objective_names_roundtrip() {
+        for objective in [
+            Objective::SquaredError,
+            Objective::Logistic,
+            Objective::PairwiseRanking,
+        ] {
+            assert_eq!(objective.name().parse::<Objective>().unwrap(), objective);
+        }
+        assert!("hinge".parse::<Objective>().is_err());
+    }
+
+    #[test]
+    fn test_logistic_gradients() {
+        let (gradients, hessians) =
+            Objective::Logistic.gradients_hessians(&[SCALE, 0, 2 * SCALE], &[0, 0, 0], &[]);
+        assert_eq!(gradients, vec![-SCALE / 2, SCALE / 2, -SCALE / 2]);
+        assert_eq!(hessians, vec![SCALE / 4; 3]);
+
+        // A quarter of the labels positive -> log-odds of 0.25
+        let bias = Objective::Logistic.initial_score(&[SCALE, 0, 0, 0]);
+        assert!((bias - logit_micro(SCALE / 4)).abs() <= 1);
+        assert!(bias < -1_090_000 && bias > -1_110_000);
+    }
+
+    #[test]
+    fn test_pairwise_gradients_stay_within_round() {
+        let targets = [3, 1, 2, 5];
+        let predictions = [0, 0, 0, 0];
+        let rounds = [10, 10, 20, 20];
+
+        let (gradients, hessians) =
+            Objective::PairwiseRanking.gradients_hessians(&targets, &predictions, &rounds);
+
+        // One pair per round, each mis-ordered with probability 1/2.
+        assert_eq!(
+            gradients,
+            vec![-SCALE / 2, SCALE / 2, SCALE / 2, -SCALE / 2]
+        );
+        assert_eq!(hessians, vec![SCALE / 4; 4]);
+        assert_eq!(gradients.iter().sum::<i64>(), 0);
+    }
+}
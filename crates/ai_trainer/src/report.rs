@@ -0,0 +1,77 @@
+//! Training reports
+//!
+//! A [`TrainingReport`] summarises one training run: the loss curve, how
+//! often and how profitably each feature was split on, and the hash of the
+//! resulting model. The CLI writes it next to the model file so governance
+//! proposals can reference both.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Mean losses (at SCALE) after a given number of trees.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LossPoint {
+    /// Number of trees in the ensemble (0 = bias only)
+    pub iteration: usize,
+    pub train_loss: i64,
+    /// Absent when training without a validation split
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_loss: Option<i64>,
+}
+
+/// Split usage of one feature across the trees kept in the model.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureImportance {
+    pub feature: String,
+    pub split_count: u64,
+    /// Sum of split gains, in the objective's gain units
+    pub total_gain: i64,
+}
+
+/// Summary of a training run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrainingReport {
+    pub objective: String,
+    pub train_rows: usize,
+    pub validation_rows: usize,
+    pub trees_built: usize,
+    /// Trees in the final model; fewer than `trees_built` after early stopping
+    pub trees_kept: usize,
+    pub stopped_early: bool,
+    pub loss_curve: Vec<LossPoint>,
+    pub feature_importance: Vec<FeatureImportance>,
+    pub model_hash: String,
+}
+
+impl TrainingReport {
+    /// Report location for a model file: `model.json` -> `model.report.json`.
+    pub fn path_for_model(model_path: &Path) -> PathBuf {
+        model_path.with_extension("report.json")
+    }
+
+    /// Write the report as pretty-printed JSON.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("failed to serialize report")?;
+        fs::write(path, json)
+            .with_context(|| format!("failed to write report to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_path_sits_next_to_model() {
+        assert_eq!(
+            TrainingReport::path_for_model(Path::new("models/validator.json")),
+            PathBuf::from("models/validator.report.json")
+        );
+        assert_eq!(
+            TrainingReport::path_for_model(Path::new("model")),
+            PathBuf::from("model.report.json")
+        );
+    }
+}
@@ -1,14 +1,29 @@
 //! Gradient Boosted Decision Tree (GBDT) trainer
 //!
 //! Implements deterministic GBDT training with fixed-point arithmetic
-//! and exact-greedy CART splits that target the runtime `ippan-ai-core`
-//! integer model format.
+//! and histogram-based CART splits that target the runtime `ippan-ai-core`
+//! integer model format. The loss is pluggable via [`Objective`] and is
+//! recorded in the model metadata.
+//!
+//! Row and feature subsampling draw from a single [`LcgRng`] on the boosting
+//! thread, and all parallel work sums integers, so a given seed yields the
+//! same model bytes for any thread count.
+//!
+//! With a validation share the dataset is split deterministically by the
+//! same seed; validation loss is tracked per tree and, with early stopping,
+//! the model is cut back to the best iteration. Every run produces a
+//! [`TrainingReport`].
 
-use anyhow::Result;
-use ippan_ai_core::gbdt::{Model, Tree, SCALE};
+use anyhow::{Context, Result};
+use ippan_ai_core::gbdt::{Model, ModelMetadata, Tree, SCALE};
+use rayon::prelude::*;
 
-use crate::cart::{CartBuilder, TreeConfig};
+use crate::cart::{CartBuilder, SplitStat, TreeConfig};
 use crate::dataset::Dataset;
+use crate::deterministic::LcgRng;
+use crate::histogram::{BinnedFeatures, DEFAULT_MAX_BINS};
+use crate::objective::Objective;
+use crate::report::{FeatureImportance, LossPoint, TrainingReport};
 
 /// Training configuration for deterministic GBDT.
 #[derive(Clone, Debug)]
@@ -18,6 +33,21 @@ pub struct TrainingParams {
     pub min_samples_leaf: usize,
     pub learning_rate_micro: i64,
     pub quantization_step: i64,
+    pub objective: Objective,
+    /// Upper bound on histogram bins per feature
+    pub max_bins: usize,
+    /// Fraction of rows sampled for each tree, at SCALE
+    pub row_subsample_micro: i64,
+    /// Fraction of features considered by each tree, at SCALE
+    pub feature_subsample_micro: i64,
+    /// Seed for row and feature subsampling and the validation split
+    pub seed: i64,
+    /// Worker threads (0 = one per core); does not affect the model
+    pub threads: usize,
+    /// Share of rows held out for validation, at SCALE (0 = no split)
+    pub validation_micro: i64,
+    /// Stop after this many trees without a validation improvement (0 = off)
+    pub early_stopping_rounds: usize,
 }
 
 impl Default for TrainingParams {
@@ -28,6 +58,14 @@ impl Default for TrainingParams {
             min_samples_leaf: 8,
             learning_rate_micro: 100_000, // 0.1
             quantization_step: 10_000,
+            objective: Objective::SquaredError,
+            max_bins: DEFAULT_MAX_BINS,
+            row_subsample_micro: SCALE,
+            feature_subsample_micro: SCALE,
+            seed: 42,
+            threads: 0,
+            validation_micro: 0,
+            early_stopping_rounds: 0,
         }
     }
 }
@@ -44,60 +82,233 @@ impl GbdtTrainer {
 
     /// Train a deterministic model from a dataset.
     pub fn train(&self, dataset: &Dataset) -> Result<Model> {
+        Ok(self.train_with_report(dataset)?.0)
+    }
+
+    /// Train a deterministic model and summarise the run.
+    pub fn train_with_report(&self, dataset: &Dataset) -> Result<(Model, TrainingReport)> {
+        self.validate(dataset)?;
+
+        let split;
+        let (train, validation) = if self.config.validation_micro > 0 {
+            split =
+                dataset.train_validation_split(self.config.validation_micro, self.config.seed)?;
+            (&split.0, Some(&split.1))
+        } else {
+            (dataset, None)
+        };
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.threads)
+            .build()
+            .context("failed to start training thread pool")?;
+        let (model, mut report) = pool.install(|| self.boost(train, validation));
+
+        report.model_hash = model.hash_hex().context("failed to hash model")?;
+        Ok((model, report))
+    }
+
+    fn validate(&self, dataset: &Dataset) -> Result<()> {
+        let objective = self.config.objective;
+        if objective.is_grouped() && dataset.rounds.len() != dataset.len() {
+            anyhow::bail!(
+                "{objective} objective needs a round key for every row ({} of {})",
+                dataset.rounds.len(),
+                dataset.len()
+            );
+        }
+        for (name, rate) in [
+            ("row_subsample_micro", self.config.row_subsample_micro),
+            (
+                "feature_subsample_micro",
+                self.config.feature_subsample_micro,
+            ),
+        ] {
+            if rate <= 0 || rate > SCALE {
+                anyhow::bail!("{name} must be in (0, {SCALE}], got {rate}");
+            }
+        }
+        if !(0..SCALE).contains(&self.config.validation_micro) {
+            anyhow::bail!(
+                "validation_micro must be in [0, {SCALE}), got {}",
+                self.config.validation_micro
+            );
+        }
+        if self.config.early_stopping_rounds > 0 && self.config.validation_micro == 0 {
+            anyhow::bail!("early stopping needs a validation split (validation_micro > 0)");
+        }
+        Ok(())
+    }
+
+    fn boost(&self, dataset: &Dataset, validation: Option<&Dataset>) -> (Model, TrainingReport) {
         let n_samples = dataset.len();
+        let objective = self.config.objective;
+        let bins = BinnedFeatures::from_rows(
+            &dataset.features,
+            self.config.quantization_step,
+            self.config.max_bins,
+        );
+        let mut rng = LcgRng::new(self.config.seed);
         let mut predictions = vec![0i64; n_samples];
 
         let bias = self.calculate_bias(&dataset.targets);
         predictions.fill(bias);
+        let mut validation_predictions = validation.map(|v| vec![bias; v.len()]);
 
         let mut trees = Vec::with_capacity(self.config.tree_count);
+        let mut tree_splits: Vec<Vec<SplitStat>> = Vec::with_capacity(self.config.tree_count);
+        let mut loss_curve = vec![self.loss_point(
+            0,
+            dataset,
+            &predictions,
+            validation.zip(validation_predictions.as_deref()),
+        )];
+        // (trees, validation loss) of the best iteration so far
+        let mut best: Option<(usize, i64)> = None;
+        let mut stopped_early = false;
 
         for tree_idx in 0..self.config.tree_count {
             tracing::info!(training_tree = tree_idx + 1, total = self.config.tree_count);
 
-            let (gradients, hessians) =
-                self.calculate_gradients_hessians(&dataset.targets, &predictions);
+            let (gradients, hessians) = self.calculate_gradients_hessians(dataset, &predictions);
 
             let tree_config = TreeConfig {
                 max_depth: self.config.max_depth,
                 min_samples_leaf: self.config.min_samples_leaf,
                 quant_step: self.config.quantization_step,
+                hessian_scale: objective.hessian_scale(),
             };
 
-            let builder = CartBuilder::new(&dataset.features, &gradients, &hessians, tree_config);
-            let nodes = builder.build();
+            let rows = self.sample_rows(n_samples, &mut rng);
+            let features = self.sample_features(bins.feature_count(), &mut rng);
+            let (nodes, splits) = CartBuilder::with_bins(&bins, &gradients, &hessians, tree_config)
+                .rows(rows)
+                .features(features)
+                .build_with_splits();
 
             self.update_predictions(&nodes, &dataset.features, &mut predictions);
+            if let (Some(validation), Some(validation_predictions)) =
+                (validation, validation_predictions.as_mut())
+            {
+                self.update_predictions(&nodes, &validation.features, validation_predictions);
+            }
 
             trees.push(Tree::new(nodes, self.config.learning_rate_micro));
+            tree_splits.push(splits);
+
+            let point = self.loss_point(
+                trees.len(),
+                dataset,
+                &predictions,
+                validation.zip(validation_predictions.as_deref()),
+            );
+            tracing::info!(
+                iteration = point.iteration,
+                train_loss = point.train_loss,
+                validation_loss = ?point.validation_loss
+            );
+            let validation_loss = point.validation_loss;
+            loss_curve.push(point);
+
+            if let Some(loss) = validation_loss {
+                match best {
+                    Some((_, best_loss)) if loss >= best_loss => {}
+                    _ => best = Some((trees.len(), loss)),
+                }
+            }
+            if let Some((best_trees, _)) = best {
+                let patience = self.config.early_stopping_rounds;
+                if patience > 0 && trees.len() - best_trees >= patience {
+                    tracing::info!(best_iteration = best_trees, "early stopping");
+                    stopped_early = true;
+                    break;
+                }
+            }
         }
 
-        Ok(Model::new(trees, bias))
+        let trees_built = trees.len();
+        if self.config.early_stopping_rounds > 0 {
+            if let Some((best_trees, _)) = best {
+                trees.truncate(best_trees);
+            }
+        }
+
+        let report = TrainingReport {
+            objective: objective.name().to_string(),
+            train_rows: n_samples,
+            validation_rows: validation.map(Dataset::len).unwrap_or(0),
+            trees_built,
+            trees_kept: trees.len(),
+            stopped_early,
+            loss_curve,
+            feature_importance: feature_importance(dataset, &tree_splits[..trees.len()]),
+            model_hash: String::new(),
+        };
+        let model = Model::new(trees, bias).with_metadata(ModelMetadata::new(objective.name()));
+        (model, report)
+    }
+
+    fn loss_point(
+        &self,
+        iteration: usize,
+        dataset: &Dataset,
+        predictions: &[i64],
+        validation: Option<(&Dataset, &[i64])>,
+    ) -> LossPoint {
+        let objective = self.config.objective;
+        LossPoint {
+            iteration,
+            train_loss: objective.loss(&dataset.targets, predictions, &dataset.rounds),
+            validation_loss: validation.map(|(validation, predictions)| {
+                objective.loss(&validation.targets, predictions, &validation.rounds)
+            }),
+        }
     }
 
-    fn calculate_bias(&self, targets: &[i64]) -> i64 {
-        if targets.is_empty() {
-            return 0;
+    /// Bernoulli row sample; every row when subsampling is off.
+    fn sample_rows(&self, n_samples: usize, rng: &mut LcgRng) -> Vec<usize> {
+        if self.config.row_subsample_micro >= SCALE {
+            return (0..n_samples).collect();
         }
 
-        let sum: i128 = targets.iter().map(|&t| t as i128).sum();
-        (sum / targets.len() as i128) as i64
+        (0..n_samples)
+            .filter(|_| rng.next_unit_micro() < self.config.row_subsample_micro)
+            .collect()
+    }
+
+    /// Sorted feature sample of `ceil(rate * count)` features (at least one).
+    fn sample_features(&self, feature_count: usize, rng: &mut LcgRng) -> Vec<usize> {
+        let mut features: Vec<usize> = (0..feature_count).collect();
+        if self.config.feature_subsample_micro >= SCALE || feature_count == 0 {
+            return features;
+        }
+
+        let wanted =
+            (feature_count as i64 * self.config.feature_subsample_micro + SCALE - 1) / SCALE;
+        let wanted = (wanted as usize).clamp(1, feature_count);
+
+        // Partial Fisher-Yates shuffle
+        for i in 0..wanted {
+            let j = i + rng.next_range((feature_count - i) as i64) as usize;
+            features.swap(i, j);
+        }
+        features.truncate(wanted);
+        features.sort_unstable();
+        features
+    }
+
+    fn calculate_bias(&self, targets: &[i64]) -> i64 {
+        self.config.objective.initial_score(targets)
     }
 
     fn calculate_gradients_hessians(
         &self,
-        targets: &[i64],
+        dataset: &Dataset,
         predictions: &[i64],
     ) -> (Vec<i64>, Vec<i64>) {
-        let mut gradients = Vec::with_capacity(targets.len());
-        let mut hessians = Vec::with_capacity(targets.len());
-
-        for i in 0..targets.len() {
-            gradients.push(predictions[i].saturating_sub(targets[i]));
-            hessians.push(1); // unit hessian for MSE
-        }
-
-        (gradients, hessians)
+        self.config
+            .objective
+            .gradients_hessians(&dataset.targets, predictions, &dataset.rounds)
     }
 
     fn update_predictions(
@@ -106,12 +317,15 @@ impl GbdtTrainer {
         features: &[Vec<i64>],
         predictions: &mut [i64],
     ) {
-        for (idx, feature_vec) in features.iter().enumerate() {
-            let value = self.evaluate_nodes(nodes, feature_vec);
-            let scaled =
-                ((value as i128 * self.config.learning_rate_micro as i128) / SCALE as i128) as i64;
-            predictions[idx] = predictions[idx].saturating_add(scaled);
-        }
+        predictions
+            .par_iter_mut()
+            .zip(features.par_iter())
+            .for_each(|(prediction, feature_vec)| {
+                let value = self.evaluate_nodes(nodes, feature_vec);
+                let scaled = ((value as i128 * self.config.learning_rate_micro as i128)
+                    / SCALE as i128) as i64;
+                *prediction = prediction.saturating_add(scaled);
+            });
     }
 
     fn evaluate_nodes(&self, nodes: &[ippan_ai_core::gbdt::tree::Node], features: &[i64]) -> i64 {
@@ -144,6 +358,30 @@ impl GbdtTrainer {
     }
 }
 
+/// Split counts and summed gains per feature, in dataset column order.
+fn feature_importance(dataset: &Dataset, tree_splits: &[Vec<SplitStat>]) -> Vec<FeatureImportance> {
+    let mut importance: Vec<FeatureImportance> = (0..dataset.feature_count)
+        .map(|idx| FeatureImportance {
+            feature: dataset
+                .feature_names
+                .get(idx)
+                .cloned()
+                .unwrap_or_else(|| format!("feature_{idx}")),
+            split_count: 0,
+            total_gain: 0,
+        })
+        .collect();
+
+    for split in tree_splits.iter().flatten() {
+        if let Some(entry) = importance.get_mut(split.feature_idx) {
+            entry.split_count += 1;
+            entry.total_gain = entry.total_gain.saturating_add(split.gain);
+        }
+    }
+
+    importance
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +395,7 @@ mod tests {
                 vec![400, 25, 8, 2, 600],
             ],
             targets: vec![1_000, 1_500, 2_000, 2_500],
+            rounds: vec![1, 1, 2, 2],
             feature_count: 5,
             feature_names: FEATURE_COLUMNS.iter().map(|s| s.to_string()).collect(),
         }
@@ -181,6 +420,8 @@ mod tests {
             min_samples_leaf: 1,
             learning_rate_micro: 200_000,
             quantization_step: 10,
+            objective: Objective::SquaredError,
+            ..TrainingParams::default()
         };
 
         let trainer = GbdtTrainer::new(params.clone());
@@ -191,4 +432,234 @@ mod tests {
         assert_eq!(model_a, model_b);
         Ok(())
     }
+
+    #[test]
+    fn test_objective_recorded_in_metadata() -> Result<()> {
+        let dataset = sample_dataset();
+        for objective in [
+            Objective::SquaredError,
+            Objective::Logistic,
+            Objective::PairwiseRanking,
+        ] {
+            let params = TrainingParams {
+                tree_count: 2,
+                min_samples_leaf: 1,
+                objective,
+                ..TrainingParams::default()
+            };
+            let model = GbdtTrainer::new(params).train(&dataset)?;
+            assert_eq!(model.metadata, Some(ModelMetadata::new(objective.name())));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_logistic_separates_classes() -> Result<()> {
+        let mut dataset = sample_dataset();
+        dataset.targets = vec![0, 0, SCALE, SCALE];
+        let params = TrainingParams {
+            tree_count: 10,
+            max_depth: 1,
+            min_samples_leaf: 1,
+            learning_rate_micro: 500_000,
+            quantization_step: 10,
+            objective: Objective::Logistic,
+            ..TrainingParams::default()
+        };
+
+        let model = GbdtTrainer::new(params).train(&dataset)?;
+        assert_eq!(model.bias, 0);
+        assert!(model.score(&dataset.features[0]) < -SCALE);
+        assert!(model.score(&dataset.features[3]) > SCALE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pairwise_ranking_orders_within_round() -> Result<()> {
+        let mut dataset = sample_dataset();
+        // Labels only carry meaning relative to the other rows of a round.
+        dataset.targets = vec![1, 2, 1, 2];
+        let params = TrainingParams {
+            tree_count: 8,
+            max_depth: 2,
+            min_samples_leaf: 1,
+            learning_rate_micro: 500_000,
+            quantization_step: 10,
+            objective: Objective::PairwiseRanking,
+            ..TrainingParams::default()
+        };
+
+        let model = GbdtTrainer::new(params).train(&dataset)?;
+        let scores: Vec<i64> = dataset.features.iter().map(|f| model.score(f)).collect();
+        assert!(scores[1] > scores[0]);
+        assert!(scores[3] > scores[2]);
+        Ok(())
+    }
+
+    fn synthetic_dataset(rows: usize) -> Dataset {
+        let mut rng = LcgRng::new(7);
+        let features: Vec<Vec<i64>> = (0..rows)
+            .map(|_| (0..5).map(|_| rng.next_range(1_000_000)).collect())
+            .collect();
+        let targets = features
+            .iter()
+            .map(|f| f[0] / 2 - f[3] / 3 + f[4] % 1_000)
+            .collect();
+        Dataset {
+            features,
+            targets,
+            rounds: (0..rows as i64).map(|row| row / 10).collect(),
+            feature_count: 5,
+            feature_names: FEATURE_COLUMNS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_subsampled_model_independent_of_thread_count() -> Result<()> {
+        let dataset = synthetic_dataset(400);
+        let params = TrainingParams {
+            tree_count: 6,
+            max_depth: 3,
+            min_samples_leaf: 4,
+            quantization_step: 1_000,
+            max_bins: 32,
+            row_subsample_micro: 700_000,
+            feature_subsample_micro: 600_000,
+            seed: 11,
+            threads: 1,
+            ..TrainingParams::default()
+        };
+
+        let single = GbdtTrainer::new(params.clone()).train(&dataset)?;
+        let multi = GbdtTrainer::new(TrainingParams {
+            threads: 4,
+            ..params.clone()
+        })
+        .train(&dataset)?;
+        assert_eq!(single.to_canonical_json()?, multi.to_canonical_json()?);
+
+        let reseeded = GbdtTrainer::new(TrainingParams { seed: 12, ..params }).train(&dataset)?;
+        assert_ne!(single, reseeded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_feature_sample_size() {
+        let trainer = GbdtTrainer::new(TrainingParams {
+            feature_subsample_micro: 400_000,
+            ..TrainingParams::default()
+        });
+        let mut rng = LcgRng::new(3);
+        for _ in 0..20 {
+            let features = trainer.sample_features(5, &mut rng);
+            assert_eq!(features.len(), 2);
+            assert!(features.windows(2).all(|w| w[0] < w[1]));
+            assert!(features.iter().all(|&f| f < 5));
+        }
+    }
+
+    #[test]
+    fn test_report_tracks_losses_and_importance() -> Result<()> {
+        let dataset = synthetic_dataset(300);
+        let params = TrainingParams {
+            tree_count: 5,
+            max_depth: 3,
+            min_samples_leaf: 4,
+            quantization_step: 1_000,
+            validation_micro: 200_000,
+            ..TrainingParams::default()
+        };
+
+        let (model, report) = GbdtTrainer::new(params).train_with_report(&dataset)?;
+        assert_eq!((report.train_rows, report.validation_rows), (240, 60));
+        assert_eq!(report.loss_curve.len(), 6);
+        assert!(report
+            .loss_curve
+            .iter()
+            .all(|p| p.validation_loss.is_some()));
+        assert!(report.loss_curve[5].train_loss < report.loss_curve[0].train_loss);
+        assert_eq!((report.trees_built, report.trees_kept), (5, 5));
+        assert!(!report.stopped_early);
+        assert_eq!(report.model_hash, model.hash_hex()?);
+
+        let splits: u64 = report
+            .feature_importance
+            .iter()
+            .map(|f| f.split_count)
+            .sum();
+        let internal_nodes = model
+            .trees
+            .iter()
+            .flat_map(|tree| &tree.nodes)
+            .filter(|node| node.leaf.is_none())
+            .count();
+        assert_eq!(splits, internal_nodes as u64);
+        assert_eq!(report.feature_importance[0].feature, "uptime_micros");
+        // The target is driven by features 0, 3 and 4 only.
+        assert!(report.feature_importance[0].total_gain > report.feature_importance[1].total_gain);
+        Ok(())
+    }
+
+    #[test]
+    fn test_early_stopping_keeps_best_iteration() -> Result<()> {
+        let mut dataset = synthetic_dataset(200);
+        // Pure noise: validation loss stops improving almost immediately.
+        let mut rng = LcgRng::new(99);
+        dataset.targets = dataset
+            .targets
+            .iter()
+            .map(|_| rng.next_range(SCALE))
+            .collect();
+        let params = TrainingParams {
+            tree_count: 50,
+            max_depth: 4,
+            min_samples_leaf: 1,
+            learning_rate_micro: 500_000,
+            quantization_step: 1_000,
+            validation_micro: 300_000,
+            early_stopping_rounds: 3,
+            ..TrainingParams::default()
+        };
+
+        let (model, report) = GbdtTrainer::new(params).train_with_report(&dataset)?;
+        assert!(report.stopped_early);
+        assert_eq!(report.trees_built, report.trees_kept + 3);
+        assert_eq!(model.trees.len(), report.trees_kept);
+
+        let best = report.loss_curve[1..]
+            .iter()
+            .min_by_key(|p| (p.validation_loss, p.iteration))
+            .unwrap();
+        assert_eq!(best.iteration, report.trees_kept);
+        Ok(())
+    }
+
+    #[test]
+    fn test_early_stopping_requires_validation() {
+        let params = TrainingParams {
+            early_stopping_rounds: 2,
+            ..TrainingParams::default()
+        };
+        assert!(GbdtTrainer::new(params).train(&sample_dataset()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_subsample_rate_rejected() {
+        let params = TrainingParams {
+            row_subsample_micro: 0,
+            ..TrainingParams::default()
+        };
+        assert!(GbdtTrainer::new(params).train(&sample_dataset()).is_err());
+    }
+
+    #[test]
+    fn test_pairwise_ranking_requires_rounds() {
+        let mut dataset = sample_dataset();
+        dataset.rounds.clear();
+        let params = TrainingParams {
+            objective: Objective::PairwiseRanking,
+            ..TrainingParams::default()
+        };
+        assert!(GbdtTrainer::new(params).train(&dataset).is_err());
+    }
 }
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use ippan_ai_trainer::{train_model_from_csv, TrainingParams};
+use ippan_ai_trainer::{train_model_from_csv, Objective, TrainingParams};
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -26,6 +26,8 @@ fn deterministic_training() -> Result<()> {
         min_samples_leaf: 1,
         learning_rate_micro: 100_000,
         quantization_step: 1_000,
+        objective: Objective::SquaredError,
+        ..TrainingParams::default()
     };
 
     let model_a = train_model_from_csv(dataset.path(), params.clone())?;
@@ -33,3 +35,20 @@ fn deterministic_training() -> Result<()> {
     assert_eq!(model_a, model_b);
     Ok(())
 }
+
+#[test]
+fn ranking_objective_recorded_in_model_json() -> Result<()> {
+    let dataset = write_dataset()?;
+    let params = TrainingParams {
+        tree_count: 2,
+        min_samples_leaf: 1,
+        quantization_step: 1_000,
+        objective: Objective::PairwiseRanking,
+        ..TrainingParams::default()
+    };
+
+    let model = train_model_from_csv(dataset.path(), params)?;
+    let json = model.to_canonical_json()?;
+    assert!(json.contains("\"metadata\":{\"objective\":\"pairwise_ranking\"}"));
+    Ok(())
+}
@@ -8,7 +8,8 @@ use blake3::Hasher as Blake3;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use ippan_types::{RoundId, ValidatorId};
+use ippan_consensus_dlc::dgbdt::{FairnessModel, ValidatorMetrics as FairnessMetrics};
+use ippan_types::{Amount, RoundId, ValidatorId};
 
 /// Validator metrics used for D-GBDT scoring
 /// All scores are in fixed-point format (scaled by 1_000_000) for determinism
@@ -28,6 +29,24 @@ pub struct ValidatorMetrics {
     pub stake_amount: u64,
 }
 
+impl ValidatorMetrics {
+    /// Convert to the 0-10000 metrics consumed by a D-GBDT [`FairnessModel`].
+    ///
+    /// Stake is read as µIPN, latency maps 1000ms to 10000 and honesty is
+    /// divided by one plus the slash count.
+    pub fn to_fairness_metrics(&self) -> FairnessMetrics {
+        FairnessMetrics {
+            uptime: (self.uptime_percentage / 100).clamp(0, 10_000),
+            latency: (self.avg_latency_us / 100).min(10_000) as i64,
+            honesty: 10_000 / (1 + self.slash_count as i64),
+            blocks_proposed: self.blocks_proposed,
+            blocks_verified: self.blocks_verified,
+            stake: Amount::from_micro_ipn(self.stake_amount),
+            rounds_active: self.rounds_active,
+        }
+    }
+}
+
 impl Default for ValidatorMetrics {
     fn default() -> Self {
         Self {
@@ -53,6 +72,35 @@ pub struct VerifierSelection {
     pub selection_seed: [u8; 32],
 }
 
+/// One feature's share of a reputation score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureContribution {
+    pub feature: String,
+    pub value: i64,
+}
+
+/// Exact integer breakdown of a reputation score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReputationExplanation {
+    /// Reputation (0-10000) as returned by [`DGBDTEngine::calculate_reputation`]
+    pub score: i32,
+    pub base: i64,
+    /// Divisor applied to `base + Σ contributions` before clamping
+    pub scale: i64,
+    pub contributions: Vec<FeatureContribution>,
+    /// Hash of the fairness model, `None` for the weighted reputation
+    pub model_hash: Option<String>,
+}
+
+impl ReputationExplanation {
+    /// `base + Σ contributions`, before scaling and clamping
+    pub fn raw_score(&self) -> i64 {
+        self.contributions
+            .iter()
+            .fold(self.base, |acc, c| acc.saturating_add(c.value))
+    }
+}
+
 /// D-GBDT Engine for deterministic validator selection
 /// All weights are stored as fixed-point integers (scaled by 1_000_000)
 pub struct DGBDTEngine {
@@ -61,6 +109,9 @@ pub struct DGBDTEngine {
 
     /// Historical performance data
     history: Vec<SelectionHistory>,
+
+    /// D-GBDT model that replaces the weighted reputation when set
+    fairness_model: Option<FairnessModel>,
 }
 
 #[derive(Debug, Clone)]
@@ -89,12 +140,91 @@ impl DGBDTEngine {
         Self {
             weights,
             history: Vec::new(),
+            fairness_model: None,
         }
     }
 
+    /// Score validators with a D-GBDT fairness model instead of the weights
+    pub fn with_fairness_model(mut self, model: FairnessModel) -> Self {
+        self.fairness_model = Some(model);
+        self
+    }
+
+    /// Swap the fairness model in place (`None` restores the weighted reputation)
+    pub fn set_fairness_model(&mut self, model: Option<FairnessModel>) {
+        self.fairness_model = model;
+    }
+
+    /// Fairness model used for scoring, if any
+    pub fn fairness_model(&self) -> Option<&FairnessModel> {
+        self.fairness_model.as_ref()
+    }
+
     /// Calculate reputation score (0-10000) for a validator
     /// Uses only integer arithmetic for deterministic results across architectures
     pub fn calculate_reputation(&self, metrics: &ValidatorMetrics) -> i32 {
+        if let Some(model) = &self.fairness_model {
+            let score = model.score_deterministic(&metrics.to_fairness_metrics());
+            return score.clamp(0, 10_000) as i32;
+        }
+
+        let weighted_score: i64 = self.weighted_terms(metrics).iter().map(|(_, v)| v).sum();
+        (weighted_score as i32).clamp(0, 10000)
+    }
+
+    /// Break the reputation of `metrics` into one integer term per feature.
+    ///
+    /// The breakdown is exact: `score == clamp((base + Σ contributions) / scale, 0, 10000)`.
+    /// With a fairness model the terms are its tree-path attributions in raw
+    /// model units; otherwise they are the weighted factors, with a base of 0
+    /// and a scale of 1.
+    pub fn explain_reputation(&self, metrics: &ValidatorMetrics) -> ReputationExplanation {
+        let (base, scale, contributions, model_hash) = match &self.fairness_model {
+            Some(model) => {
+                let explanation = model.explain_deterministic(&metrics.to_fairness_metrics());
+                let contributions = FairnessModel::FEATURE_NAMES
+                    .iter()
+                    .zip(explanation.contributions)
+                    .map(|(feature, value)| FeatureContribution {
+                        feature: feature.to_string(),
+                        value,
+                    })
+                    .collect();
+                let hash = model
+                    .active_hash()
+                    .map(str::to_string)
+                    .or_else(|| model.raw_model().hash_hex().ok());
+                (
+                    explanation.base,
+                    FairnessModel::SCORE_SCALE,
+                    contributions,
+                    hash,
+                )
+            }
+            None => {
+                let contributions = self
+                    .weighted_terms(metrics)
+                    .into_iter()
+                    .map(|(feature, value)| FeatureContribution {
+                        feature: feature.to_string(),
+                        value,
+                    })
+                    .collect();
+                (0, 1, contributions, None)
+            }
+        };
+
+        ReputationExplanation {
+            score: self.calculate_reputation(metrics),
+            base,
+            scale,
+            contributions,
+            model_hash,
+        }
+    }
+
+    /// Weighted factor terms summed by [`Self::calculate_reputation`]
+    fn weighted_terms(&self, metrics: &ValidatorMetrics) -> [(&'static str, i64); 7] {
         // All calculations use fixed-point arithmetic (scaled by 1_000_000)
         let proposal_score = if metrics.rounds_active > 0 {
             ((metrics.blocks_proposed * 10000 / metrics.rounds_active).min(10000)) as i64
@@ -128,20 +258,20 @@ impl DGBDTEngine {
             normalized as i64
         };
 
-        // Weighted sum using fixed-point weights (scaled by 1_000_000)
         // Each score is 0-10000, weight is 0-1_000_000, so we divide by 1_000_000
-        let weighted_score = (proposal_score
-            * self.weights.get("blocks_proposed").unwrap_or(&250_000))
-            / 1_000_000
-            + (verification_score * self.weights.get("blocks_verified").unwrap_or(&200_000))
-                / 1_000_000
-            + (uptime_score * self.weights.get("uptime").unwrap_or(&150_000)) / 1_000_000
-            + (latency_score * self.weights.get("latency").unwrap_or(&150_000)) / 1_000_000
-            + (slash_penalty * self.weights.get("slash_penalty").unwrap_or(&100_000)) / 1_000_000
-            + (performance_score * self.weights.get("performance").unwrap_or(&100_000)) / 1_000_000
-            + (stake_score * self.weights.get("stake").unwrap_or(&50_000)) / 1_000_000;
-
-        (weighted_score as i32).clamp(0, 10000)
+        let term = |factor: &'static str, score: i64, default: i64| {
+            let weight = self.weights.get(factor).copied().unwrap_or(default);
+            (factor, score * weight / 1_000_000)
+        };
+        [
+            term("blocks_proposed", proposal_score, 250_000),
+            term("blocks_verified", verification_score, 200_000),
+            term("uptime", uptime_score, 150_000),
+            term("latency", latency_score, 150_000),
+            term("slash_penalty", slash_penalty, 100_000),
+            term("performance", performance_score, 100_000),
+            term("stake", stake_score, 50_000),
+        ]
     }
 
     /// Select verifiers deterministically using round seed and metrics
@@ -341,6 +471,53 @@ mod tests {
         assert!(score > 8000); // Should be high for good metrics
     }
 
+    #[test]
+    fn test_fairness_model_replaces_weighted_reputation() {
+        let engine = DGBDTEngine::new().with_fairness_model(FairnessModel::testing_stub());
+        let metrics = ValidatorMetrics::default();
+
+        let expected = FairnessModel::testing_stub()
+            .score_deterministic(&metrics.to_fairness_metrics())
+            .clamp(0, 10_000) as i32;
+        assert_eq!(engine.calculate_reputation(&metrics), expected);
+        assert!(engine.fairness_model().is_some());
+        assert!(DGBDTEngine::new().fairness_model().is_none());
+    }
+
+    #[test]
+    fn test_explanation_reconstructs_reputation() {
+        let mut metrics = ValidatorMetrics {
+            blocks_proposed: 40,
+            blocks_verified: 90,
+            rounds_active: 100,
+            slash_count: 2,
+            stake_amount: 30_000_000,
+            ..Default::default()
+        };
+
+        let weighted = DGBDTEngine::new();
+        let modelled = DGBDTEngine::new().with_fairness_model(FairnessModel::testing_stub());
+        for uptime in [0, 450_000, 990_000] {
+            metrics.uptime_percentage = uptime;
+            for engine in [&weighted, &modelled] {
+                let explanation = engine.explain_reputation(&metrics);
+                let rebuilt = (explanation.raw_score() / explanation.scale).clamp(0, 10_000);
+                assert_eq!(rebuilt as i32, engine.calculate_reputation(&metrics));
+                assert_eq!(explanation.score, engine.calculate_reputation(&metrics));
+            }
+        }
+
+        let explanation = weighted.explain_reputation(&metrics);
+        assert_eq!(explanation.contributions.len(), 7);
+        assert_eq!(explanation.contributions[0].feature, "blocks_proposed");
+        assert!(explanation.model_hash.is_none());
+
+        let explanation = modelled.explain_reputation(&metrics);
+        assert_eq!(explanation.contributions.len(), 6);
+        assert_eq!(explanation.contributions[0].feature, "uptime");
+        assert!(explanation.model_hash.is_some());
+    }
+
     #[test]
     fn test_deterministic_selection() {
         let engine = DGBDTEngine::new();
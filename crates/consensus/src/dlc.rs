@@ -17,11 +17,14 @@ use tracing::{info, warn};
 use ippan_types::{Block, BlockId, IppanTimeMicros, RoundId, ValidatorId};
 
 use crate::bonding::BondingManager;
-use crate::dgbdt::{DGBDTEngine, ValidatorMetrics};
+use crate::dgbdt::{DGBDTEngine, ReputationExplanation, ValidatorMetrics};
 use crate::hashtimer_integration::should_close_round;
 use crate::parallel_dag::{ParallelDag, ParallelDagConfig};
 use crate::shadow_verifier::ShadowVerifierSet;
 
+/// Number of past verifier selections whose metrics are kept for explanations
+pub const SELECTION_HISTORY_ROUNDS: usize = 256;
+
 /// DLC Consensus configuration
 #[derive(Debug, Clone)]
 pub struct DLCConfig {
@@ -106,6 +109,9 @@ pub struct DLCConsensus {
 
     /// Validator metrics for D-GBDT
     pub validator_metrics: Arc<RwLock<HashMap<ValidatorId, ValidatorMetrics>>>,
+
+    /// Metrics each recent verifier selection was scored with, keyed by round
+    pub selection_metrics: Arc<RwLock<BTreeMap<RoundId, HashMap<ValidatorId, ValidatorMetrics>>>>,
 }
 
 impl DLCConsensus {
@@ -129,6 +135,7 @@ impl DLCConsensus {
             ))),
             bonding_manager: Arc::new(RwLock::new(BondingManager::new())),
             validator_metrics: Arc::new(RwLock::new(HashMap::new())),
+            selection_metrics: Arc::new(RwLock::new(BTreeMap::new())),
             current_round: Arc::new(RwLock::new(current_round)),
             validator_id,
             config,
@@ -201,6 +208,7 @@ impl DLCConsensus {
         if metrics.is_empty() {
             return Ok((self.validator_id, Vec::new()));
         }
+        self.record_selection_metrics(round_seed, &metrics);
 
         // Use D-GBDT to select based on reputation and fairness
         let selection = match dgbdt.select_verifiers(
@@ -218,6 +226,46 @@ impl DLCConsensus {
         Ok((selection.primary, selection.shadows))
     }
 
+    /// Keep the metrics a selection was scored with, dropping the oldest rounds
+    fn record_selection_metrics(
+        &self,
+        round: RoundId,
+        metrics: &HashMap<ValidatorId, ValidatorMetrics>,
+    ) {
+        let mut history = self.selection_metrics.write();
+        history.insert(round, metrics.clone());
+        while history.len() > SELECTION_HISTORY_ROUNDS {
+            history.pop_first();
+        }
+    }
+
+    /// Explain a validator's selection score.
+    ///
+    /// With a round, uses the metrics recorded when that round's verifiers
+    /// were selected; without one, scores the live metrics for the current
+    /// round. Returns `None` when the round or validator is unknown.
+    pub fn explain_validator(
+        &self,
+        validator_id: &ValidatorId,
+        round: Option<RoundId>,
+    ) -> Option<(RoundId, ReputationExplanation)> {
+        let (round, metrics) = match round {
+            Some(round) => {
+                let history = self.selection_metrics.read();
+                (round, history.get(&round)?.get(validator_id)?.clone())
+            }
+            None => {
+                let round = self.current_round.read().round_id;
+                (
+                    round,
+                    self.validator_metrics.read().get(validator_id)?.clone(),
+                )
+            }
+        };
+        let explanation = self.dgbdt_engine.read().explain_reputation(&metrics);
+        Some((round, explanation))
+    }
+
     /// Verify a block using primary + shadow verifiers
     #[allow(clippy::await_holding_lock)]
     pub async fn verify_block(&self, block: &Block) -> Result<bool> {
@@ -365,4 +413,44 @@ mod tests {
         let state = dlc.get_state();
         assert_eq!(state.round_id, 2);
     }
+
+    #[test]
+    fn test_explain_validator_uses_recorded_round_metrics() {
+        let dlc = DLCConsensus::new(DLCConfig::default(), [1u8; 32]);
+        let validator = [2u8; 32];
+        let recorded = ValidatorMetrics {
+            uptime_percentage: 500_000,
+            ..Default::default()
+        };
+        dlc.update_validator_metrics(validator, recorded.clone());
+        dlc.select_verifiers_deterministic(7).unwrap();
+        dlc.update_validator_metrics(validator, ValidatorMetrics::default());
+
+        let (round, explanation) = dlc.explain_validator(&validator, Some(7)).unwrap();
+        assert_eq!(round, 7);
+        assert_eq!(
+            explanation.score,
+            dlc.dgbdt_engine.read().calculate_reputation(&recorded)
+        );
+
+        let (round, live) = dlc.explain_validator(&validator, None).unwrap();
+        assert_eq!(round, 1);
+        assert!(live.score > explanation.score);
+
+        assert!(dlc.explain_validator(&validator, Some(8)).is_none());
+        assert!(dlc.explain_validator(&[9u8; 32], None).is_none());
+    }
+
+    #[test]
+    fn test_selection_metrics_are_bounded() {
+        let dlc = DLCConsensus::new(DLCConfig::default(), [1u8; 32]);
+        dlc.update_validator_metrics([2u8; 32], ValidatorMetrics::default());
+        for round in 0..(SELECTION_HISTORY_ROUNDS as RoundId + 10) {
+            dlc.select_verifiers_deterministic(round).unwrap();
+        }
+
+        let history = dlc.selection_metrics.read();
+        assert_eq!(history.len(), SELECTION_HISTORY_ROUNDS);
+        assert_eq!(history.keys().next(), Some(&10));
+    }
 }
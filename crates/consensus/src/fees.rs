@@ -27,6 +27,8 @@ pub enum TxKind {
     Validator,
     /// Handle registration or maintenance transaction
     Handle,
+    /// File descriptor ownership anchor
+    FileAnchor,
 }
 
 /// L1 Fee cap configuration (values in atomic IPN units)
@@ -40,6 +42,8 @@ pub struct FeeCapConfig {
     pub cap_governance: Amount,
     pub cap_validator: Amount,
     pub cap_handle: Amount,
+    #[serde(default = "default_cap_file_anchor")]
+    pub cap_file_anchor: Amount,
 }
 
 pub const DEFAULT_FEE_CAP_TRANSFER_MICRO_IPN: u64 = 100;
@@ -48,6 +52,7 @@ pub const DEFAULT_FEE_CAP_L2_EXIT_MICRO_IPN: u64 = 2_000;
 pub const DEFAULT_FEE_CAP_GOVERNANCE_MICRO_IPN: u64 = 10_000;
 pub const DEFAULT_FEE_CAP_VALIDATOR_MICRO_IPN: u64 = 10_000;
 pub const DEFAULT_FEE_CAP_HANDLE_MICRO_IPN: u64 = 5_000;
+pub const DEFAULT_FEE_CAP_FILE_ANCHOR_MICRO_IPN: u64 = 1_000;
 
 pub const DEFAULT_FEE_CAP_TRANSFER: Amount =
     Amount::from_micro_ipn(DEFAULT_FEE_CAP_TRANSFER_MICRO_IPN);
@@ -60,6 +65,12 @@ pub const DEFAULT_FEE_CAP_GOVERNANCE: Amount =
 pub const DEFAULT_FEE_CAP_VALIDATOR: Amount =
     Amount::from_micro_ipn(DEFAULT_FEE_CAP_VALIDATOR_MICRO_IPN);
 pub const DEFAULT_FEE_CAP_HANDLE: Amount = Amount::from_micro_ipn(DEFAULT_FEE_CAP_HANDLE_MICRO_IPN);
+pub const DEFAULT_FEE_CAP_FILE_ANCHOR: Amount =
+    Amount::from_micro_ipn(DEFAULT_FEE_CAP_FILE_ANCHOR_MICRO_IPN);
+
+fn default_cap_file_anchor() -> Amount {
+    DEFAULT_FEE_CAP_FILE_ANCHOR
+}
 
 impl Default for FeeCapConfig {
     fn default() -> Self {
@@ -70,6 +81,7 @@ impl Default for FeeCapConfig {
             cap_governance: DEFAULT_FEE_CAP_GOVERNANCE, // 0.01 IPN
             cap_validator: DEFAULT_FEE_CAP_VALIDATOR, // 0.01 IPN
             cap_handle: DEFAULT_FEE_CAP_HANDLE,     // 0.005 IPN handle ops
+            cap_file_anchor: DEFAULT_FEE_CAP_FILE_ANCHOR, // 0.001 IPN file anchors
         }
     }
 }
@@ -84,6 +96,7 @@ impl FeeCapConfig {
             TxKind::Governance => self.cap_governance,
             TxKind::Validator => self.cap_validator,
             TxKind::Handle => self.cap_handle,
+            TxKind::FileAnchor => self.cap_file_anchor,
         }
     }
 }
@@ -108,6 +121,12 @@ pub fn classify_transaction(tx: &Transaction) -> TxKind {
     if tx.handle_operation().is_some() {
         return TxKind::Handle;
     }
+    if tx.file_anchor().is_some() {
+        return TxKind::FileAnchor;
+    }
+    if tx.model_governance().is_some() {
+        return TxKind::Governance;
+    }
     if let Some(topic) = tx.topics.first() {
         match topic.as_str() {
             "l2_anchor" | "l2_commit" => TxKind::L2Anchor,
@@ -250,6 +269,36 @@ mod tests {
         assert_eq!(classify_transaction(&tx), TxKind::Handle);
     }
 
+    #[test]
+    fn classify_file_anchor() {
+        let mut tx = Transaction::new([1u8; 32], [0u8; 32], Amount::zero(), 1);
+        tx.set_file_anchor(ippan_types::FileAnchorOp {
+            file_id: ippan_types::FileDescriptorId::from_bytes([2u8; 32]),
+            content_hash: [3u8; 32],
+            owner: [1u8; 32],
+            created_at_us: 0,
+            descriptor_digest: [4u8; 32],
+            signature: vec![0u8; 64],
+        });
+        assert_eq!(classify_transaction(&tx), TxKind::FileAnchor);
+        assert_eq!(
+            FeeCapConfig::default().get_cap(TxKind::FileAnchor),
+            DEFAULT_FEE_CAP_FILE_ANCHOR
+        );
+    }
+
+    #[test]
+    fn classify_model_governance() {
+        let mut tx = Transaction::new([1u8; 32], [0u8; 32], Amount::zero(), 1);
+        tx.set_model_governance(ippan_types::ModelGovernanceOp::Vote(
+            ippan_types::ModelVoteOp {
+                model_id: "fairness".into(),
+                approve: true,
+            },
+        ));
+        assert_eq!(classify_transaction(&tx), TxKind::Governance);
+    }
+
     #[test]
     fn fee_validation_caps() {
         let cfg = FeeCapConfig::default();
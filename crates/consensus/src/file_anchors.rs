@@ -0,0 +1,151 @@
+use ippan_files::{
+    descriptor::ContentHash, FileAnchorError, FileId, FileOwnershipAnchor, L1FileAnchorStorage,
+};
+use ippan_types::{FileAnchorOpError, Transaction};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Applies file ownership anchor transactions during round finalization.
+pub struct FileAnchorPipeline {
+    anchors: Arc<L1FileAnchorStorage>,
+}
+
+impl FileAnchorPipeline {
+    pub fn new(anchors: Arc<L1FileAnchorStorage>) -> Self {
+        Self { anchors }
+    }
+
+    pub fn anchors(&self) -> Arc<L1FileAnchorStorage> {
+        self.anchors.clone()
+    }
+
+    /// Record the anchor carried by `tx`.
+    ///
+    /// The sender must be the descriptor owner, the file id must be the one
+    /// derived from the content hash, owner and creation time, and the owner's
+    /// descriptor signature must verify. The first anchor for a file id wins.
+    pub fn apply(
+        &self,
+        tx: &Transaction,
+        block_height: u64,
+        round: u64,
+    ) -> Result<(), FileAnchorApplyError> {
+        let op = tx
+            .file_anchor()
+            .ok_or(FileAnchorApplyError::MissingAnchor)?;
+        op.validate_for_sender(&tx.from)?;
+
+        let file_id = FileId::from_bytes(op.file_id.to_bytes());
+        let content_hash = ContentHash::from_bytes(op.content_hash);
+        if file_id != FileId::derive(&content_hash, &op.owner, op.created_at_us) {
+            return Err(FileAnchorApplyError::IdMismatch(file_id.to_hex()));
+        }
+
+        self.anchors.store_anchor(FileOwnershipAnchor {
+            file_id,
+            content_hash,
+            owner: op.owner,
+            descriptor_digest: op.descriptor_digest,
+            block_height,
+            round,
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FileAnchorApplyError {
+    #[error("transaction does not carry a file anchor")]
+    MissingAnchor,
+    #[error("file id {0} is not derived from the anchored content hash, owner and creation time")]
+    IdMismatch(String),
+    #[error(transparent)]
+    Invalid(#[from] FileAnchorOpError),
+    #[error(transparent)]
+    Anchor(#[from] FileAnchorError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_crypto::KeyPair;
+    use ippan_files::FileDescriptor;
+    use ippan_types::{Amount, FileAnchorOp, FileDescriptorId};
+
+    fn anchor_tx(keypair: &KeyPair, descriptor: &FileDescriptor) -> Transaction {
+        let mut tx = Transaction::new(keypair.public_key(), [0u8; 32], Amount::zero(), 1);
+        tx.set_file_anchor(FileAnchorOp {
+            file_id: FileDescriptorId::from_bytes(*descriptor.id.as_bytes()),
+            content_hash: *descriptor.content_hash.as_bytes(),
+            owner: descriptor.owner,
+            created_at_us: descriptor.created_at_us,
+            descriptor_digest: descriptor.signing_digest(),
+            signature: descriptor.signature.clone(),
+        });
+        tx.sign(&keypair.private_key()).unwrap();
+        tx
+    }
+
+    #[test]
+    fn anchors_signed_descriptor_once() {
+        let keypair = KeyPair::generate();
+        let mut descriptor = FileDescriptor::new(
+            ContentHash::from_data(b"anchored"),
+            keypair.public_key(),
+            8,
+            None,
+            vec![],
+        );
+        descriptor.sign(&keypair.private_key()).unwrap();
+
+        let pipeline = FileAnchorPipeline::new(Arc::new(L1FileAnchorStorage::new()));
+        pipeline
+            .apply(&anchor_tx(&keypair, &descriptor), 3, 4)
+            .unwrap();
+        let anchor = pipeline.anchors().get_anchor(&descriptor.id).unwrap();
+        assert_eq!((anchor.block_height, anchor.round), (3, 4));
+        assert!(anchor.matches(&descriptor));
+
+        let mut edited = descriptor.clone();
+        edited.tags.push("edited".to_string());
+        edited.sign(&keypair.private_key()).unwrap();
+        assert!(matches!(
+            pipeline.apply(&anchor_tx(&keypair, &edited), 5, 6),
+            Err(FileAnchorApplyError::Anchor(
+                FileAnchorError::DescriptorConflict(_)
+            ))
+        ));
+
+        let mut unsigned = descriptor.clone();
+        unsigned.signature = vec![0u8; 64];
+        assert!(matches!(
+            pipeline.apply(&anchor_tx(&keypair, &unsigned), 5, 6),
+            Err(FileAnchorApplyError::Invalid(_))
+        ));
+
+        // A squatter signs a descriptor claiming the victim's file id for
+        // their own content; the id does not derive from the squatter's key.
+        let squatter = KeyPair::generate();
+        let victim = FileDescriptor::new(
+            ContentHash::from_data(b"victim"),
+            KeyPair::generate().public_key(),
+            6,
+            None,
+            vec![],
+        );
+        let mut claimed = FileDescriptor::new(
+            ContentHash::from_data(b"victim"),
+            squatter.public_key(),
+            6,
+            None,
+            vec![],
+        );
+        claimed.id = victim.id;
+        claimed.sign(&squatter.private_key()).unwrap();
+        assert!(matches!(
+            pipeline.apply(&anchor_tx(&squatter, &claimed), 5, 6),
+            Err(FileAnchorApplyError::IdMismatch(_))
+        ));
+        assert!(pipeline.anchors().get_anchor(&victim.id).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! Input drift monitoring for the fairness model
+//!
+//! Every AI-driven selection feeds the validators' fairness features into a
+//! [`FairnessDriftMonitor`] built from the training distribution recorded in
+//! the model manifest. The tracker keeps the manifest's distributions by
+//! model hash and switches reference whenever another model becomes active.
+//! Distributions that do not describe the scored feature vector are rejected
+//! when they are loaded.
+//! Per-feature PSI is published through [`ConsensusMetrics`]; an alert is
+//! counted when a feature first crosses the warning or critical threshold,
+//! not on every round it stays there.
+
+use crate::dgbdt::ValidatorMetrics;
+use crate::metrics::ConsensusMetrics;
+use anyhow::{Context, Result};
+use ippan_consensus_dlc::fairness_drift::{
+    validate_reference, Alert, AlertSeverity, DistributionSummary, DriftConfig, DriftReport,
+    FairnessDriftMonitor,
+};
+use ippan_types::ValidatorId;
+use std::collections::{BTreeMap, HashMap};
+
+/// Tracks live fairness inputs against the training distribution.
+#[derive(Debug, Clone)]
+pub struct InputDriftTracker {
+    config: DriftConfig,
+    monitor: Option<FairnessDriftMonitor>,
+    /// Training distributions from the model manifest, by model hash
+    references: HashMap<String, DistributionSummary>,
+    /// Severity level currently raised per alert metric
+    raised: BTreeMap<String, u8>,
+    last_observed: Option<u64>,
+}
+
+impl Default for InputDriftTracker {
+    fn default() -> Self {
+        Self::new(DriftConfig::default())
+    }
+}
+
+impl InputDriftTracker {
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            monitor: None,
+            references: HashMap::new(),
+            raised: BTreeMap::new(),
+            last_observed: None,
+        }
+    }
+
+    /// Replace the training reference; `None` stops monitoring.
+    pub fn set_reference(&mut self, reference: Option<DistributionSummary>) -> Result<()> {
+        self.monitor = reference
+            .map(|summary| FairnessDriftMonitor::new(summary, self.config.clone()))
+            .transpose()?;
+        self.raised.clear();
+        self.last_observed = None;
+        Ok(())
+    }
+
+    /// Record the training distributions of the manifest models, keyed by
+    /// canonical model hash. Fails, keeping the previous set, if any of them
+    /// does not summarise the fairness features.
+    pub fn set_manifest_references(
+        &mut self,
+        references: HashMap<String, DistributionSummary>,
+    ) -> Result<()> {
+        for (hash, summary) in &references {
+            validate_reference(summary)
+                .with_context(|| format!("training distribution of model {hash}"))?;
+        }
+        self.references = references;
+        Ok(())
+    }
+
+    /// Monitor against the training distribution of the model `hash`, or
+    /// stop monitoring if the manifest records none. Returns whether
+    /// monitoring is active.
+    pub fn activate_model(&mut self, hash: Option<&str>) -> bool {
+        let reference = hash.and_then(|hash| self.references.get(hash)).cloned();
+        // Manifest references were validated when they were recorded
+        if self.set_reference(reference).is_err() {
+            self.monitor = None;
+        }
+        self.is_active()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.monitor.is_some()
+    }
+
+    pub fn report(&self) -> Option<DriftReport> {
+        self.monitor.as_ref().map(FairnessDriftMonitor::report)
+    }
+
+    /// Feed the validator metrics scored in `round` and publish the drift
+    /// gauges. Returns the alerts raised or escalated by this round.
+    pub fn observe_round(
+        &mut self,
+        round: u64,
+        validators: &HashMap<ValidatorId, ValidatorMetrics>,
+        metrics: &ConsensusMetrics,
+    ) -> Vec<Alert> {
+        let Some(monitor) = self.monitor.as_mut() else {
+            return Vec::new();
+        };
+        // `get_state` re-runs selection for the current slot
+        if self.last_observed.is_some_and(|last| last >= round) {
+            return Vec::new();
+        }
+        self.last_observed = Some(round);
+
+        // Window rollover depends on insertion order, so keep it fixed
+        let mut ids: Vec<&ValidatorId> = validators.keys().collect();
+        ids.sort();
+        for id in ids {
+            monitor.observe(&validators[id].to_fairness_metrics());
+        }
+
+        for drift in monitor.report().features {
+            metrics.record_feature_drift(&drift.feature, drift.samples, drift.psi);
+        }
+
+        let mut raised = BTreeMap::new();
+        let mut new_alerts = Vec::new();
+        for alert in monitor.alerts() {
+            let level = severity_level(&alert.severity);
+            if self
+                .raised
+                .get(&alert.metric_name)
+                .is_none_or(|&previous| previous < level)
+            {
+                metrics.record_feature_drift_alert(severity_label(&alert.severity));
+                new_alerts.push(alert.clone());
+            }
+            raised.insert(alert.metric_name, level);
+        }
+        self.raised = raised;
+        new_alerts
+    }
+}
+
+fn severity_level(severity: &AlertSeverity) -> u8 {
+    match severity {
+        AlertSeverity::Info => 0,
+        AlertSeverity::Warning => 1,
+        AlertSeverity::Critical => 2,
+    }
+}
+
+fn severity_label(severity: &AlertSeverity) -> &'static str {
+    match severity {
+        AlertSeverity::Info => "info",
+        AlertSeverity::Warning => "warning",
+        AlertSeverity::Critical => "critical",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_consensus_dlc::dgbdt::FairnessModel;
+    use ippan_consensus_dlc::fairness_drift::fairness_features;
+
+    fn validators(uptime_percentage: i64, count: u8) -> HashMap<ValidatorId, ValidatorMetrics> {
+        (0..count)
+            .map(|i| {
+                let metrics = ValidatorMetrics {
+                    uptime_percentage: uptime_percentage + i as i64 * 1_000,
+                    blocks_proposed: 10,
+                    blocks_verified: 20,
+                    rounds_active: 20,
+                    ..ValidatorMetrics::default()
+                };
+                ([i; 32], metrics)
+            })
+            .collect()
+    }
+
+    fn reference() -> DistributionSummary {
+        let rows: Vec<Vec<i64>> = validators(950_000, 50)
+            .values()
+            .map(|metrics| fairness_features(&metrics.to_fairness_metrics()).to_vec())
+            .collect();
+        DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &rows, 10)
+    }
+
+    #[test]
+    fn test_inactive_without_reference() {
+        let mut tracker = InputDriftTracker::default();
+        let metrics = ConsensusMetrics::new();
+
+        assert!(tracker
+            .observe_round(1, &validators(950_000, 4), &metrics)
+            .is_empty());
+        assert!(tracker.report().is_none());
+        assert!(metrics.get_feature_drift_psi().is_empty());
+    }
+
+    #[test]
+    fn test_activated_model_selects_reference() {
+        let mut tracker = InputDriftTracker::default();
+        tracker
+            .set_manifest_references(HashMap::from([("trained".to_string(), reference())]))
+            .unwrap();
+
+        assert!(tracker.activate_model(Some("trained")));
+        assert!(!tracker.activate_model(Some("untracked")));
+        assert!(!tracker.activate_model(None));
+    }
+
+    #[test]
+    fn test_rejects_dataset_column_references() {
+        let mut tracker = InputDriftTracker::default();
+        tracker
+            .set_manifest_references(HashMap::from([("trained".to_string(), reference())]))
+            .unwrap();
+        let columns = DistributionSummary::from_rows(
+            &["uptime_micros", "latency_micros"],
+            &[vec![990_000, 1_500]],
+            10,
+        );
+
+        assert!(tracker
+            .set_manifest_references(HashMap::from([("trained".to_string(), columns.clone())]))
+            .is_err());
+        assert!(tracker.set_reference(Some(columns)).is_err());
+        assert!(tracker.activate_model(Some("trained")));
+    }
+
+    #[test]
+    fn test_alerts_once_per_escalation() {
+        let mut tracker = InputDriftTracker::new(DriftConfig {
+            min_samples: 8,
+            ..DriftConfig::default()
+        });
+        tracker.set_reference(Some(reference())).unwrap();
+        let metrics = ConsensusMetrics::new();
+        let degraded = validators(400_000, 4);
+
+        assert!(tracker.observe_round(1, &degraded, &metrics).is_empty());
+        let alerts = tracker.observe_round(2, &degraded, &metrics);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric_name, "feature_psi.uptime");
+        assert!(matches!(alerts[0].severity, AlertSeverity::Critical));
+
+        // Same round again and a further degraded round add nothing new
+        assert!(tracker.observe_round(2, &degraded, &metrics).is_empty());
+        assert!(tracker.observe_round(3, &degraded, &metrics).is_empty());
+
+        let report = tracker.report().unwrap();
+        assert_eq!(report.features[0].samples, 12);
+        assert!(metrics.get_feature_drift_psi()["uptime"] > 250_000);
+        assert_eq!(metrics.get_feature_drift_psi()["honesty"], 0);
+        assert_eq!(metrics.get_feature_drift_alerts()["critical"], 1);
+    }
+}
@@ -11,9 +11,12 @@
 use anyhow::Result;
 use blake3::Hasher as Blake3;
 use ippan_crypto::{validate_confidential_block, validate_confidential_transaction};
+use ippan_files::{
+    L1FileAnchorStorage, PinDeal, PinDealRequest, PinProof, PinningConfig, PinningMarket,
+};
 use ippan_l1_fees::FeePolicy;
 use ippan_l1_handle_anchors::L1HandleAnchorStorage;
-use ippan_l2_handle_registry::{dht::HandleDhtService, L2HandleRegistry};
+use ippan_l2_handle_registry::{dht::HandleDhtService, HandleAuctionBook, L2HandleRegistry};
 use ippan_mempool::Mempool;
 use ippan_storage::Storage;
 use ippan_types::{
@@ -23,7 +26,7 @@ use ippan_types::{
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::Arc,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
@@ -42,9 +45,12 @@ pub mod bonding;
 pub mod dgbdt;
 pub mod dlc;
 pub mod dlc_integration;
+pub mod file_anchors;
 pub mod handles;
 pub mod hashtimer_integration;
 pub mod payments;
+pub mod pinning;
+pub mod replay;
 pub mod shadow_verifier;
 
 // Economic and emission modules
@@ -54,10 +60,13 @@ pub mod fees;
 
 // AI and selection modules
 // Legacy l1_ai_consensus removed - using DLC fairness model instead
+pub mod model_governance;
 
 // Telemetry and metrics
+pub mod input_drift;
 pub mod metrics;
 pub mod model_reload;
+pub mod model_rollout;
 pub mod reputation;
 pub mod telemetry;
 
@@ -75,7 +84,9 @@ pub mod round_executor;
 
 // DLC Core
 pub use bonding::{BondingManager, ValidatorBond, MIN_BOND_AMOUNT, VALIDATOR_BOND_AMOUNT};
-pub use dgbdt::{DGBDTEngine, ValidatorMetrics, VerifierSelection};
+pub use dgbdt::{
+    DGBDTEngine, FeatureContribution, ReputationExplanation, ValidatorMetrics, VerifierSelection,
+};
 pub use dlc::{DLCConfig, DLCConsensus, DLCRoundState};
 pub use dlc_integration::{dlc_config_from_poa, DLCIntegratedConsensus};
 pub use hashtimer_integration::{
@@ -95,7 +106,12 @@ pub use emission_tracker::{
     EmissionStatistics, EmissionTracker, ValidatorContribution as TrackerValidatorContribution,
 };
 pub use fees::{classify_transaction, validate_fee, FeeCapConfig, FeeCollector, FeeError, TxKind};
+pub use input_drift::InputDriftTracker;
 pub use ippan_economics::{EmissionEngine, EmissionParams, RewardAmount, RoundIndex, RoundRewards};
+pub use model_rollout::{
+    FinalizedSample, ModelRollout, RolloutActivation, RolloutConfig, RolloutDecision,
+    RolloutStatus, ShadowStats,
+};
 pub use ordering::order_round;
 pub use parallel_dag::{
     DagError, DagSnapshot, InsertionOutcome, ParallelDag, ParallelDagConfig, ParallelDagEngine,
@@ -205,9 +221,18 @@ pub struct PoAConsensus {
     pub emission_tracker: Arc<RwLock<EmissionTracker>>,
     pub telemetry_manager: Arc<telemetry::TelemetryManager>,
     pub model_reloader: Option<Arc<model_reload::ModelReloader>>,
+    pub model_rollout: Arc<RwLock<model_rollout::ModelRollout>>,
+    pub input_drift: Arc<RwLock<input_drift::InputDriftTracker>>,
+    /// Fetches approved model artifacts over IPNDHT
+    #[cfg(feature = "ai_l1")]
+    pub model_fetcher: Option<Arc<ippan_ai_registry::ModelFetcher>>,
+    /// Governance-approved models waiting to be staged
+    pub model_approvals: Arc<model_governance::ModelApprovals>,
     pub metrics: Arc<metrics::ConsensusMetrics>,
     pub payment_engine: Arc<payments::PaymentApplier>,
     pub handle_pipeline: Arc<handles::HandlePipeline>,
+    pub file_anchor_pipeline: Arc<file_anchors::FileAnchorPipeline>,
+    pub pinning_pipeline: Arc<pinning::PinningPipeline>,
 }
 
 impl PoAConsensus {
@@ -224,6 +249,7 @@ impl PoAConsensus {
             Arc::new(L2HandleRegistry::new()),
             Arc::new(L1HandleAnchorStorage::new()),
             None,
+            Arc::new(HandleAuctionBook::new()),
         )
     }
 
@@ -234,6 +260,7 @@ impl PoAConsensus {
         handle_registry: Arc<L2HandleRegistry>,
         handle_anchors: Arc<L1HandleAnchorStorage>,
         handle_dht: Option<Arc<dyn HandleDhtService>>,
+        handle_auctions: Arc<HandleAuctionBook>,
     ) -> Self {
         let (tx_sender, submit_rx) = mpsc::unbounded_channel();
         let latest_height = storage.get_latest_height().unwrap_or(0);
@@ -265,6 +292,36 @@ impl PoAConsensus {
 
         let metrics = Arc::new(metrics::ConsensusMetrics::new());
 
+        let handle_pipeline = Arc::new(handles::HandlePipeline::with_services(
+            handle_registry,
+            handle_anchors,
+            handle_dht,
+            handle_auctions,
+        ));
+        match handle_pipeline.restore_auctions(&storage) {
+            Ok(true) => info!(
+                "Restored {} running handle auctions",
+                handle_pipeline.auctions().active_count()
+            ),
+            Ok(false) => {}
+            Err(err) => error!("Failed to restore handle auctions: {}", err),
+        }
+
+        let pinning_pipeline = Arc::new(pinning::PinningPipeline::new(Arc::new(
+            PinningMarket::new(PinningConfig::default()),
+        )));
+        Self::restore_pinning(&pinning_pipeline, &storage);
+
+        let model_approvals = Arc::new(model_governance::ModelApprovals::new(storage.clone()));
+        match model_approvals.restore() {
+            Ok(true) => info!(
+                "Restored {} approved AI models",
+                model_approvals.governance().registered_model_count()
+            ),
+            Ok(false) => {}
+            Err(err) => error!("Failed to restore AI model approvals: {}", err),
+        }
+
         Self {
             config: config.clone(),
             storage: storage.clone(),
@@ -284,19 +341,70 @@ impl PoAConsensus {
             emission_tracker: Arc::new(RwLock::new(emission_tracker)),
             telemetry_manager,
             model_reloader: None,
+            model_rollout: Arc::new(RwLock::new(model_rollout::ModelRollout::default())),
+            input_drift: Arc::new(RwLock::new(input_drift::InputDriftTracker::default())),
+            #[cfg(feature = "ai_l1")]
+            model_fetcher: None,
+            #[cfg(feature = "ai_l1")]
+            model_approvals,
             metrics,
             payment_engine: Arc::new(payments::PaymentApplier::new(
                 FeePolicy::default(),
                 payments::TREASURY_ACCOUNT,
             )),
-            handle_pipeline: Arc::new(handles::HandlePipeline::with_dht(
-                handle_registry,
-                handle_anchors,
-                handle_dht,
-            )),
+            handle_pipeline,
+            file_anchor_pipeline: Arc::new(file_anchors::FileAnchorPipeline::new(Arc::new(
+                L1FileAnchorStorage::new(),
+            ))),
+            pinning_pipeline,
+        }
+    }
+
+    /// Use a shared store for file ownership anchors.
+    pub fn with_file_anchors(mut self, anchors: Arc<L1FileAnchorStorage>) -> Self {
+        self.file_anchor_pipeline = Arc::new(file_anchors::FileAnchorPipeline::new(anchors));
+        self
+    }
+
+    /// Use a pinning market with its own configuration. Deals persisted by a
+    /// previous run are restored into it.
+    pub fn with_pinning_market(mut self, market: Arc<PinningMarket>) -> Self {
+        self.pinning_pipeline = Arc::new(pinning::PinningPipeline::new(market));
+        Self::restore_pinning(&self.pinning_pipeline, &self.storage);
+        self
+    }
+
+    fn restore_pinning(
+        pipeline: &Arc<pinning::PinningPipeline>,
+        storage: &Arc<dyn Storage + Send + Sync>,
+    ) {
+        match pipeline.restore(storage) {
+            Ok(true) => info!(
+                "Restored {} active pinning deals",
+                pipeline.market().active_count()
+            ),
+            Ok(false) => {}
+            Err(err) => error!("Failed to restore pinning deals: {}", err),
         }
     }
 
+    /// Open a pinning deal in the current round, escrowing the client's
+    /// payment and the provider's collateral.
+    pub fn open_pinning_deal(&self, request: PinDealRequest) -> Result<PinDeal> {
+        let round = self.round_tracker.read().current_round;
+        let deal = self
+            .pinning_pipeline
+            .open_deal(&self.storage, request, round)?;
+        self.pinning_pipeline.persist(&self.storage)?;
+        Ok(deal)
+    }
+
+    /// Record a provider's answer to its pinning challenge for the current epoch.
+    pub fn submit_pinning_proof(&self, proof: &PinProof) -> Result<()> {
+        self.pinning_pipeline.submit_proof(proof)?;
+        self.pinning_pipeline.persist(&self.storage)
+    }
+
     pub fn get_tx_sender(&self) -> mpsc::UnboundedSender<Transaction> {
         self.tx_sender.clone()
     }
@@ -360,8 +468,13 @@ impl PoAConsensus {
             telemetry_manager,
             metrics,
             dgbdt_engine,
+            model_rollout,
+            input_drift,
             payment_engine,
             handle_pipeline,
+            file_anchor_pipeline,
+            pinning_pipeline,
+            model_approvals,
         ) = (
             self.is_running.clone(),
             self.current_slot.clone(),
@@ -376,8 +489,13 @@ impl PoAConsensus {
             self.telemetry_manager.clone(),
             self.metrics.clone(),
             self.dgbdt_engine.clone(),
+            self.model_rollout.clone(),
+            self.input_drift.clone(),
             self.payment_engine.clone(),
             self.handle_pipeline.clone(),
+            self.file_anchor_pipeline.clone(),
+            self.pinning_pipeline.clone(),
+            self.model_approvals.clone(),
         );
 
         let mut ticker = interval(Duration::from_millis(config.slot_duration_ms));
@@ -395,6 +513,9 @@ impl PoAConsensus {
                     &fee_collector,
                     &payment_engine,
                     &handle_pipeline,
+                    &file_anchor_pipeline,
+                    &pinning_pipeline,
+                    &model_approvals,
                     &metrics,
                 ) {
                     error!("Round finalization error: {e}");
@@ -404,8 +525,11 @@ impl PoAConsensus {
                 let maybe_proposer = Self::select_proposer(
                     &config,
                     &round_consensus,
+                    &storage,
                     slot,
                     &dgbdt_engine,
+                    &model_rollout,
+                    &input_drift,
                     &telemetry_manager,
                     &metrics,
                 );
@@ -449,11 +573,15 @@ impl PoAConsensus {
     // Proposer selection
     // -----------------------------------------------------------------
 
+    #[allow(clippy::too_many_arguments)]
     fn select_proposer(
         config: &PoAConfig,
         _round_consensus: &Arc<RwLock<RoundConsensus>>,
+        storage: &Arc<dyn Storage + Send + Sync>,
         slot: u64,
         dgbdt_engine: &Arc<RwLock<DGBDTEngine>>,
+        model_rollout: &Arc<RwLock<model_rollout::ModelRollout>>,
+        input_drift: &Arc<RwLock<input_drift::InputDriftTracker>>,
         telemetry_manager: &Arc<telemetry::TelemetryManager>,
         metrics: &Arc<metrics::ConsensusMetrics>,
     ) -> Option<[u8; 32]> {
@@ -506,6 +634,18 @@ impl PoAConsensus {
                 );
             }
 
+            // A staged model takes over at its approved activation round if it
+            // passes the checks over the finalized rounds before it
+            Self::decide_staged_model(
+                config,
+                storage,
+                slot,
+                dgbdt_engine,
+                model_rollout,
+                input_drift,
+                metrics,
+            );
+
             // Use DGBDT engine for fair selection
             let engine = dgbdt_engine.read();
             match engine.select_verifiers(slot, &validator_metrics, 0, 0) {
@@ -527,6 +667,26 @@ impl PoAConsensus {
                         hex::encode(selection.primary),
                         latency_us
                     );
+
+                    let drift_alerts =
+                        input_drift
+                            .write()
+                            .observe_round(slot, &validator_metrics, metrics);
+                    for alert in drift_alerts {
+                        warn!(
+                            "Fairness input drift {:?}: {} = {} (threshold {})",
+                            alert.severity, alert.metric_name, alert.value, alert.threshold
+                        );
+                    }
+
+                    // Shadow-score any staged model for the metrics only
+                    model_rollout.write().observe_round(
+                        slot,
+                        &validator_metrics,
+                        &engine,
+                        &selection,
+                        metrics,
+                    );
                     Some(selection.primary)
                 }
                 Err(e) => {
@@ -540,6 +700,68 @@ impl PoAConsensus {
         }
     }
 
+    /// Activate or reject the staged model once `slot` reaches its activation
+    /// round. Waits while the rounds the checks need are not finalized yet.
+    fn decide_staged_model(
+        config: &PoAConfig,
+        storage: &Arc<dyn Storage + Send + Sync>,
+        slot: u64,
+        dgbdt_engine: &Arc<RwLock<DGBDTEngine>>,
+        model_rollout: &Arc<RwLock<model_rollout::ModelRollout>>,
+        input_drift: &Arc<RwLock<input_drift::InputDriftTracker>>,
+        metrics: &Arc<metrics::ConsensusMetrics>,
+    ) {
+        let Some(window) = model_rollout.read().due_check_window(slot) else {
+            return;
+        };
+        let stakes: BTreeMap<[u8; 32], u64> = config
+            .validators
+            .iter()
+            .filter(|v| v.is_active)
+            .map(|v| (v.id, v.stake))
+            .collect();
+        let samples = match model_rollout::finalized_samples(storage.as_ref(), window, &stakes) {
+            Ok(Some(samples)) => samples,
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Cannot check staged fairness model against finalized rounds: {err}");
+                return;
+            }
+        };
+
+        let decision =
+            model_rollout
+                .write()
+                .activate_if_due(slot, &dgbdt_engine.read(), &samples, metrics);
+        match decision {
+            Some(model_rollout::RolloutDecision::Activated(activation)) => {
+                for warning in &activation.warnings {
+                    warn!("Staged fairness model shadow check (advisory): {warning}");
+                }
+                info!(
+                    "Activating staged fairness model {} at round {slot}",
+                    activation.hash
+                );
+                dgbdt_engine
+                    .write()
+                    .set_fairness_model(Some(activation.model));
+                if !input_drift.write().activate_model(Some(&activation.hash)) {
+                    warn!(
+                        "No training distribution for fairness model {}; input drift is not monitored",
+                        activation.hash
+                    );
+                }
+            }
+            Some(model_rollout::RolloutDecision::Rejected { hash, reasons }) => {
+                error!(
+                    "Rejected staged fairness model {hash} at round {slot}, keeping the active model: {}",
+                    reasons.join("; ")
+                );
+            }
+            None => {}
+        }
+    }
+
     fn fallback_proposer(validators: &[[u8; 32]], slot: u64) -> Option<[u8; 32]> {
         if validators.is_empty() {
             None
@@ -620,6 +842,9 @@ impl PoAConsensus {
         fee_collector: &Arc<RwLock<FeeCollector>>,
         payment_engine: &Arc<payments::PaymentApplier>,
         handle_pipeline: &Arc<handles::HandlePipeline>,
+        file_anchor_pipeline: &Arc<file_anchors::FileAnchorPipeline>,
+        pinning_pipeline: &Arc<pinning::PinningPipeline>,
+        model_approvals: &Arc<model_governance::ModelApprovals>,
         metrics: &Arc<metrics::ConsensusMetrics>,
     ) -> Result<()> {
         let (round_id, block_ids, start, end) = {
@@ -688,29 +913,75 @@ impl PoAConsensus {
         for tx_id in &ordered {
             if let Some((tx, proposer, block_round)) = tx_lookup.get(tx_id) {
                 let tx_kind = fees::classify_transaction(tx);
-                let handle_ok = if matches!(tx_kind, TxKind::Handle) {
-                    match handle_pipeline.apply(tx, *block_round, round_id) {
-                        Ok(()) => true,
+                // Sealed bids escrow their deposit through the payment, so they are
+                // only validated here and recorded once the deposit has moved.
+                let escrowed_bid = matches!(tx_kind, TxKind::Handle)
+                    && handles::HandlePipeline::requires_escrow(tx);
+                let handle_result = if !matches!(tx_kind, TxKind::Handle) {
+                    Ok(())
+                } else if escrowed_bid {
+                    handle_pipeline.validate_bid(tx, round_id)
+                } else {
+                    handle_pipeline.apply(tx, *block_round, round_id)
+                };
+
+                if let Err(err) = handle_result {
+                    warn!(
+                        "Round {}: handle tx {} rejected: {}",
+                        round_id,
+                        hex::encode(tx_id),
+                        err
+                    );
+                    continue;
+                }
+
+                if matches!(tx_kind, TxKind::FileAnchor) {
+                    if let Err(err) = file_anchor_pipeline.apply(tx, *block_round, round_id) {
+                        warn!(
+                            "Round {}: file anchor tx {} rejected: {}",
+                            round_id,
+                            hex::encode(tx_id),
+                            err
+                        );
+                        continue;
+                    }
+                }
+
+                if tx.model_governance().is_some() {
+                    match model_approvals.apply(tx, round_id, &config.validators) {
+                        Ok(Some(entry)) => info!(
+                            "Round {}: approved fairness model {} for activation at round {}",
+                            round_id,
+                            hex::encode(entry.model_hash),
+                            entry.activation_round
+                        ),
+                        Ok(None) => {}
                         Err(err) => {
                             warn!(
-                                "Round {}: handle tx {} rejected: {}",
+                                "Round {}: model governance tx {} rejected: {}",
                                 round_id,
                                 hex::encode(tx_id),
                                 err
                             );
-                            false
+                            continue;
                         }
                     }
-                } else {
-                    true
-                };
-
-                if !handle_ok {
-                    continue;
                 }
 
                 match payment_engine.apply(storage, tx, proposer) {
-                    Ok(split) => payment_stats.record_success(tx, *proposer, split),
+                    Ok(split) => {
+                        payment_stats.record_success(tx, *proposer, split);
+                        if escrowed_bid {
+                            if let Err(err) = handle_pipeline.apply(tx, *block_round, round_id) {
+                                warn!(
+                                    "Round {}: escrowed handle bid {} not recorded: {}",
+                                    round_id,
+                                    hex::encode(tx_id),
+                                    err
+                                );
+                            }
+                        }
+                    }
                     Err(err) => {
                         payment_stats.record_failure(&err);
                         warn!(
@@ -724,16 +995,61 @@ impl PoAConsensus {
             }
         }
 
+        let settlements = handle_pipeline.settle_auctions(
+            storage,
+            &payment_engine.treasury_account(),
+            round_id,
+            round_id,
+        );
+        for settlement in &settlements {
+            info!(
+                target: "handles",
+                round = round_id,
+                handle = settlement.handle.as_str(),
+                won = settlement.winner.is_some(),
+                proceeds = settlement.proceeds() as u64,
+                "Settled premium handle auction"
+            );
+        }
+        // Bids, reveals and settlements all land above; deposits already moved.
+        handle_pipeline.persist_auctions(storage)?;
+
+        for model_id in model_approvals.expire_proposals(round_id)? {
+            info!(
+                "Round {}: proposal for model {} expired at its activation round",
+                round_id, model_id
+            );
+        }
+
+        // Pinning epochs turn over on finalized rounds. The challenge seed comes
+        // from the HashTimer of the round's lowest block id, which every node
+        // holding the same round agrees on.
+        if pinning_pipeline.market().is_epoch_start(round_id) {
+            if let Some(block) = blocks.iter().min_by_key(|block| block.header.id) {
+                let outcome = pinning_pipeline.on_round(storage, round_id, &block.header.hashtimer);
+                for settlement in &outcome.settlements {
+                    info!(
+                        target: "pinning",
+                        round = round_id,
+                        deal = settlement.deal_id.to_hex(),
+                        epoch = settlement.epoch,
+                        outcome = ?settlement.outcome,
+                        to_provider = settlement.to_provider as u64,
+                        to_client = settlement.to_client as u64,
+                        slashed = settlement.slashed as u64,
+                        "Settled pinning epoch"
+                    );
+                }
+                pinning_pipeline.persist(storage)?;
+            }
+        }
+
         if payment_stats.total_fees > 0 {
             let mut collector = fee_collector.write();
             collector.collect(ippan_types::Amount::from_atomic(payment_stats.total_fees));
         }
 
-        let cert = RoundCertificate {
-            round: round_id,
-            block_ids: block_ids.clone(),
-            agg_sig: Self::aggregate_round_signature(round_id, &block_ids),
-        };
+        let cert = RoundCertificate::new(round_id, block_ids.clone());
 
         let prev_root = storage
             .get_latest_round_finalization()?
@@ -804,22 +1120,16 @@ impl PoAConsensus {
         Ok(())
     }
 
-    fn aggregate_round_signature(round: RoundId, blocks: &[BlockId]) -> Vec<u8> {
-        let mut h = Blake3::new();
-        h.update(&round.to_be_bytes());
-        for id in blocks {
-            h.update(id);
-        }
-        h.finalize().as_bytes()[..32].to_vec()
-    }
-
     pub fn get_state(&self) -> ConsensusState {
         let slot = *self.current_slot.read();
         let proposer = Self::select_proposer(
             &self.config,
             &self.round_consensus,
+            &self.storage,
             slot,
             &self.dgbdt_engine,
+            &self.model_rollout,
+            &self.input_drift,
             &self.telemetry_manager,
             &self.metrics,
         );
@@ -854,6 +1164,115 @@ impl PoAConsensus {
         info!("Removed validator {}", hex::encode(id));
     }
 
+    /// Stage a governance-approved fairness model for shadow scoring until
+    /// its activation round
+    pub fn stage_fairness_model(
+        &self,
+        entry: &ippan_governance::ai_models::ModelRegistryEntry,
+        model: ippan_consensus_dlc::dgbdt::FairnessModel,
+    ) -> Result<()> {
+        Self::stage_model(&self.model_rollout, &self.current_slot, entry, model)
+    }
+
+    /// Fetch the artifact of a governance-approved model through
+    /// `model_fetcher` (or its local cache) and stage it for shadow scoring
+    #[cfg(feature = "ai_l1")]
+    pub async fn stage_approved_model(
+        &self,
+        entry: &ippan_governance::ai_models::ModelRegistryEntry,
+    ) -> Result<()> {
+        let fetcher = self
+            .model_fetcher
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no model fetcher configured"))?;
+        Self::fetch_and_stage(fetcher, &self.model_rollout, &self.current_slot, entry).await
+    }
+
+    /// Stage approved models as governance approves them, starting with the
+    /// latest approval restored from storage. Call once `model_fetcher` is set.
+    #[cfg(feature = "ai_l1")]
+    pub fn spawn_model_staging(&self) -> Result<()> {
+        let fetcher = self
+            .model_fetcher
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("no model fetcher configured"))?;
+        let mut approvals = self
+            .model_approvals
+            .take_queue()
+            .ok_or_else(|| anyhow::anyhow!("model staging is already running"))?;
+        let model_rollout = self.model_rollout.clone();
+        let current_slot = self.current_slot.clone();
+
+        tokio::spawn(async move {
+            while let Some(entry) = approvals.recv().await {
+                if let Err(err) =
+                    Self::fetch_and_stage(&fetcher, &model_rollout, &current_slot, &entry).await
+                {
+                    warn!(
+                        "Failed to stage approved model {}: {}",
+                        hex::encode(entry.model_hash),
+                        err
+                    );
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "ai_l1")]
+    async fn fetch_and_stage(
+        fetcher: &ippan_ai_registry::ModelFetcher,
+        model_rollout: &RwLock<model_rollout::ModelRollout>,
+        current_slot: &RwLock<u64>,
+        entry: &ippan_governance::ai_models::ModelRegistryEntry,
+    ) -> Result<()> {
+        let path = fetcher.fetch(&entry.model_hash, &entry.model_url).await?;
+        let model = ippan_consensus_dlc::dgbdt::FairnessModel::from_d_gbdt_file(&path)?;
+        Self::stage_model(model_rollout, current_slot, entry, model)
+    }
+
+    fn stage_model(
+        model_rollout: &RwLock<model_rollout::ModelRollout>,
+        current_slot: &RwLock<u64>,
+        entry: &ippan_governance::ai_models::ModelRegistryEntry,
+        model: ippan_consensus_dlc::dgbdt::FairnessModel,
+    ) -> Result<()> {
+        let slot = *current_slot.read();
+        model_rollout.write().stage(entry, model, slot)?;
+        info!(
+            "Staged fairness model {} for activation at round {}",
+            hex::encode(entry.model_hash),
+            entry.activation_round
+        );
+        Ok(())
+    }
+
+    /// Keep the training distributions recorded in `manifest` and monitor
+    /// the active fairness model's inputs against its own. Models activated
+    /// later switch to theirs. Fails if a distribution does not cover the
+    /// fairness features. Returns whether monitoring is active.
+    #[cfg(feature = "ai_l1")]
+    pub fn load_model_manifest(&self, manifest: &ippan_ai_registry::ModelManifest) -> Result<bool> {
+        let references = manifest
+            .models
+            .iter()
+            .filter_map(|entry| {
+                let summary = entry.training_distribution.clone()?;
+                Some((entry.artifact.blake3.clone(), summary))
+            })
+            .collect();
+        let active = self
+            .dgbdt_engine
+            .read()
+            .fairness_model()
+            .map(|model| model.raw_model().hash_hex())
+            .transpose()?;
+
+        let mut input_drift = self.input_drift.write();
+        input_drift.set_manifest_references(references)?;
+        Ok(input_drift.activate_model(active.as_deref()))
+    }
+
     /// Update DGBDT model weights (for adaptive learning)
     pub fn update_dgbdt_weights(&self, factor: &str, new_weight: i64) {
         self.dgbdt_engine.write().update_weights(factor, new_weight);
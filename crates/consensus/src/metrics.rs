@@ -2,7 +2,7 @@
 
 use crate::payments::{PaymentApplyErrorKind, PaymentRoundStats};
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 /// Consensus metrics collector (fully deterministic, integer-only)
@@ -27,6 +27,21 @@ pub struct ConsensusMetrics {
     model_reload_failures: Arc<Mutex<u64>>,
     model_validation_errors: Arc<Mutex<u64>>,
 
+    // Staged rollout metrics (divergence scaled 0-10000)
+    shadow_rounds_total: Arc<Mutex<u64>>,
+    shadow_divergence_last: Arc<Mutex<i64>>,
+    shadow_divergence_max: Arc<Mutex<i64>>,
+    shadow_primary_mismatches: Arc<Mutex<u64>>,
+    shadow_determinism_failures: Arc<Mutex<u64>>,
+    model_rollout_activations: Arc<Mutex<u64>>,
+    model_rollout_check_failures: Arc<Mutex<u64>>,
+    model_rollout_rejections: Arc<Mutex<u64>>,
+
+    // Fairness input drift (PSI scaled 1_000_000 = 1.0)
+    feature_drift_psi: Arc<Mutex<BTreeMap<String, i64>>>,
+    feature_drift_samples: Arc<Mutex<BTreeMap<String, u64>>>,
+    feature_drift_alerts: Arc<Mutex<BTreeMap<String, u64>>>,
+
     // Round metrics
     rounds_finalized: Arc<Mutex<u64>>,
     blocks_proposed: Arc<Mutex<u64>>,
@@ -59,6 +74,17 @@ impl ConsensusMetrics {
             model_reload_success: Arc::new(Mutex::new(0)),
             model_reload_failures: Arc::new(Mutex::new(0)),
             model_validation_errors: Arc::new(Mutex::new(0)),
+            shadow_rounds_total: Arc::new(Mutex::new(0)),
+            shadow_divergence_last: Arc::new(Mutex::new(0)),
+            shadow_divergence_max: Arc::new(Mutex::new(0)),
+            shadow_primary_mismatches: Arc::new(Mutex::new(0)),
+            shadow_determinism_failures: Arc::new(Mutex::new(0)),
+            model_rollout_activations: Arc::new(Mutex::new(0)),
+            model_rollout_check_failures: Arc::new(Mutex::new(0)),
+            model_rollout_rejections: Arc::new(Mutex::new(0)),
+            feature_drift_psi: Arc::new(Mutex::new(BTreeMap::new())),
+            feature_drift_samples: Arc::new(Mutex::new(BTreeMap::new())),
+            feature_drift_alerts: Arc::new(Mutex::new(BTreeMap::new())),
             rounds_finalized: Arc::new(Mutex::new(0)),
             blocks_proposed: Arc::new(Mutex::new(0)),
             blocks_validated: Arc::new(Mutex::new(0)),
@@ -134,6 +160,63 @@ impl ConsensusMetrics {
         *self.model_validation_errors.lock() += 1;
     }
 
+    // Staged rollout metrics
+
+    /// Record one shadow-scored round: mean |candidate - active| reputation
+    /// delta (0-10000) and whether both models picked the same primary
+    pub fn record_shadow_round(&self, mean_divergence: i64, primary_matches: bool) {
+        *self.shadow_rounds_total.lock() += 1;
+        *self.shadow_divergence_last.lock() = mean_divergence;
+        let mut max = self.shadow_divergence_max.lock();
+        *max = (*max).max(mean_divergence);
+        if !primary_matches {
+            *self.shadow_primary_mismatches.lock() += 1;
+        }
+    }
+
+    pub fn record_shadow_determinism_failure(&self) {
+        *self.shadow_determinism_failures.lock() += 1;
+    }
+
+    pub fn record_model_rollout_activation(&self) {
+        *self.model_rollout_activations.lock() += 1;
+    }
+
+    pub fn record_model_rollout_check_failure(&self) {
+        *self.model_rollout_check_failures.lock() += 1;
+    }
+
+    pub fn record_model_rollout_rejection(&self) {
+        *self.model_rollout_rejections.lock() += 1;
+    }
+
+    // Input drift metrics
+
+    /// Record the live sample count and PSI (`None` until enough samples)
+    /// of one fairness model feature
+    pub fn record_feature_drift(&self, feature: &str, samples: u64, psi: Option<i64>) {
+        self.feature_drift_samples
+            .lock()
+            .insert(feature.to_string(), samples);
+        let mut psis = self.feature_drift_psi.lock();
+        match psi {
+            Some(psi) => {
+                psis.insert(feature.to_string(), psi);
+            }
+            None => {
+                psis.remove(feature);
+            }
+        }
+    }
+
+    pub fn record_feature_drift_alert(&self, severity: &str) {
+        *self
+            .feature_drift_alerts
+            .lock()
+            .entry(severity.to_string())
+            .or_insert(0) += 1;
+    }
+
     // Round metrics
 
     pub fn record_round_finalized(&self) {
@@ -276,6 +359,50 @@ impl ConsensusMetrics {
         *self.model_validation_errors.lock()
     }
 
+    pub fn get_shadow_rounds_total(&self) -> u64 {
+        *self.shadow_rounds_total.lock()
+    }
+
+    pub fn get_shadow_divergence_last(&self) -> i64 {
+        *self.shadow_divergence_last.lock()
+    }
+
+    pub fn get_shadow_divergence_max(&self) -> i64 {
+        *self.shadow_divergence_max.lock()
+    }
+
+    pub fn get_shadow_primary_mismatches(&self) -> u64 {
+        *self.shadow_primary_mismatches.lock()
+    }
+
+    pub fn get_shadow_determinism_failures(&self) -> u64 {
+        *self.shadow_determinism_failures.lock()
+    }
+
+    pub fn get_model_rollout_activations(&self) -> u64 {
+        *self.model_rollout_activations.lock()
+    }
+
+    pub fn get_model_rollout_check_failures(&self) -> u64 {
+        *self.model_rollout_check_failures.lock()
+    }
+
+    pub fn get_model_rollout_rejections(&self) -> u64 {
+        *self.model_rollout_rejections.lock()
+    }
+
+    pub fn get_feature_drift_psi(&self) -> BTreeMap<String, i64> {
+        self.feature_drift_psi.lock().clone()
+    }
+
+    pub fn get_feature_drift_samples(&self) -> BTreeMap<String, u64> {
+        self.feature_drift_samples.lock().clone()
+    }
+
+    pub fn get_feature_drift_alerts(&self) -> BTreeMap<String, u64> {
+        self.feature_drift_alerts.lock().clone()
+    }
+
     pub fn get_rounds_finalized(&self) -> u64 {
         *self.rounds_finalized.lock()
     }
@@ -432,6 +559,112 @@ impl ConsensusMetrics {
             self.get_model_validation_errors()
         ));
 
+        // Staged rollout metrics
+        output.push_str(
+            "# HELP ippan_model_shadow_rounds_total Rounds scored by a staged candidate model\n",
+        );
+        output.push_str("# TYPE ippan_model_shadow_rounds_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_shadow_rounds_total {}\n",
+            self.get_shadow_rounds_total()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_shadow_divergence Mean reputation delta of the candidate in the last shadow round (0-10000)\n",
+        );
+        output.push_str("# TYPE ippan_model_shadow_divergence gauge\n");
+        output.push_str(&format!(
+            "ippan_model_shadow_divergence {}\n",
+            self.get_shadow_divergence_last()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_shadow_divergence_max Largest per-round shadow divergence (0-10000)\n",
+        );
+        output.push_str("# TYPE ippan_model_shadow_divergence_max gauge\n");
+        output.push_str(&format!(
+            "ippan_model_shadow_divergence_max {}\n",
+            self.get_shadow_divergence_max()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_shadow_primary_mismatches_total Shadow rounds where the candidate picked a different primary\n",
+        );
+        output.push_str("# TYPE ippan_model_shadow_primary_mismatches_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_shadow_primary_mismatches_total {}\n",
+            self.get_shadow_primary_mismatches()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_shadow_determinism_failures_total Shadow rounds where re-scoring the candidate disagreed\n",
+        );
+        output.push_str("# TYPE ippan_model_shadow_determinism_failures_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_shadow_determinism_failures_total {}\n",
+            self.get_shadow_determinism_failures()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_rollout_activations_total Staged models activated at their approved round\n",
+        );
+        output.push_str("# TYPE ippan_model_rollout_activations_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_rollout_activations_total {}\n",
+            self.get_model_rollout_activations()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_rollout_check_failures_total Activation checks failed by staged models\n",
+        );
+        output.push_str("# TYPE ippan_model_rollout_check_failures_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_rollout_check_failures_total {}\n",
+            self.get_model_rollout_check_failures()
+        ));
+
+        output.push_str(
+            "# HELP ippan_model_rollout_rejections_total Staged models dropped at their approved round\n",
+        );
+        output.push_str("# TYPE ippan_model_rollout_rejections_total counter\n");
+        output.push_str(&format!(
+            "ippan_model_rollout_rejections_total {}\n",
+            self.get_model_rollout_rejections()
+        ));
+
+        // Input drift metrics
+        output.push_str(
+            "# HELP ippan_model_feature_psi Population stability index of a fairness model input against its training distribution\n",
+        );
+        output.push_str("# TYPE ippan_model_feature_psi gauge\n");
+        for (feature, psi) in self.get_feature_drift_psi() {
+            output.push_str(&format!(
+                "ippan_model_feature_psi{{feature=\"{feature}\"}} {}.{:06}\n",
+                psi / 1_000_000,
+                psi % 1_000_000
+            ));
+        }
+
+        output.push_str(
+            "# HELP ippan_model_feature_samples Live samples behind each feature's PSI\n",
+        );
+        output.push_str("# TYPE ippan_model_feature_samples gauge\n");
+        for (feature, samples) in self.get_feature_drift_samples() {
+            output.push_str(&format!(
+                "ippan_model_feature_samples{{feature=\"{feature}\"}} {samples}\n"
+            ));
+        }
+
+        output.push_str(
+            "# HELP ippan_model_drift_alerts_total Fairness input drift alerts raised, by severity\n",
+        );
+        output.push_str("# TYPE ippan_model_drift_alerts_total counter\n");
+        for (severity, count) in self.get_feature_drift_alerts() {
+            output.push_str(&format!(
+                "ippan_model_drift_alerts_total{{severity=\"{severity}\"}} {count}\n"
+            ));
+        }
+
         // Round metrics
         output.push_str("# HELP ippan_rounds_finalized_total Total number of rounds finalized\n");
         output.push_str("# TYPE ippan_rounds_finalized_total counter\n");
@@ -610,4 +843,47 @@ mod tests {
         assert!(output.contains("ippan_blocks_proposed_total 1"));
         assert!(output.contains("ippan_rounds_finalized_total 1"));
     }
+
+    #[test]
+    fn test_shadow_rollout_metrics() {
+        let metrics = ConsensusMetrics::new();
+
+        metrics.record_shadow_round(120, true);
+        metrics.record_shadow_round(450, false);
+        metrics.record_shadow_round(80, true);
+        metrics.record_model_rollout_check_failure();
+        metrics.record_model_rollout_rejection();
+
+        assert_eq!(metrics.get_shadow_rounds_total(), 3);
+        assert_eq!(metrics.get_shadow_divergence_last(), 80);
+        assert_eq!(metrics.get_shadow_divergence_max(), 450);
+        assert_eq!(metrics.get_shadow_primary_mismatches(), 1);
+
+        let output = metrics.export_prometheus();
+        assert!(output.contains("ippan_model_shadow_rounds_total 3"));
+        assert!(output.contains("ippan_model_shadow_divergence_max 450"));
+        assert!(output.contains("ippan_model_rollout_check_failures_total 1"));
+        assert!(output.contains("ippan_model_rollout_activations_total 0"));
+        assert!(output.contains("ippan_model_rollout_rejections_total 1"));
+    }
+
+    #[test]
+    fn test_feature_drift_metrics() {
+        let metrics = ConsensusMetrics::new();
+
+        metrics.record_feature_drift("uptime", 120, Some(312_500));
+        metrics.record_feature_drift("honesty", 120, Some(0));
+        metrics.record_feature_drift("stake_weight", 40, None);
+        metrics.record_feature_drift_alert("critical");
+
+        assert_eq!(metrics.get_feature_drift_psi().len(), 2);
+        assert_eq!(metrics.get_feature_drift_samples()["stake_weight"], 40);
+
+        let output = metrics.export_prometheus();
+        assert!(output.contains("ippan_model_feature_psi{feature=\"uptime\"} 0.312500"));
+        assert!(output.contains("ippan_model_feature_psi{feature=\"honesty\"} 0.000000"));
+        assert!(!output.contains("ippan_model_feature_psi{feature=\"stake_weight\"}"));
+        assert!(output.contains("ippan_model_feature_samples{feature=\"stake_weight\"} 40"));
+        assert!(output.contains("ippan_model_drift_alerts_total{severity=\"critical\"} 1"));
+    }
 }
@@ -0,0 +1,397 @@
+//! Governance approvals of fairness models
+//!
+//! Model proposals and validator votes arrive as [`ModelGovernanceOp`]
+//! transactions and are applied while a round is finalized, so every node
+//! records an approval at the same round. A proposal is approved once active
+//! validators holding [`MODEL_APPROVAL_THRESHOLD`] of the active stake vote
+//! for it. It is rejected once that can no longer happen, and it expires if
+//! its activation round is reached first.
+//!
+//! The [`AiModelGovernance`] registry and the open votes are kept as module
+//! state. Approving a proposal queues its [`ModelRegistryEntry`] for staging;
+//! after a restart the latest approved entry is queued again, so a node that
+//! was down across the activation round still switches to the approved
+//! model. `PoAConsensus::spawn_model_staging` drains the queue, fetching every
+//! artifact and staging it in the model rollout.
+
+use crate::Validator;
+use anyhow::Result;
+use ippan_ai_registry::AiModelProposal;
+use ippan_governance::ai_models::{AiModelGovernance, ModelRegistryEntry};
+use ippan_storage::Storage;
+use ippan_types::{
+    ratio_from_parts, ModelGovernanceOp, ModelGovernanceOpError, RatioMicros, RoundId, Transaction,
+    RATIO_SCALE,
+};
+use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+/// Module state key under which the model registry is persisted.
+const MODEL_GOVERNANCE_STATE_KEY: &str = "ai_model_governance";
+
+/// Share of the active validator stake that must vote for a proposal.
+pub const MODEL_APPROVAL_THRESHOLD: RatioMicros = 666_667;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelGovernanceState {
+    #[serde(flatten)]
+    governance: AiModelGovernance,
+    /// Votes on pending proposals: model id -> hex voter address -> approve
+    #[serde(default)]
+    votes: BTreeMap<String, BTreeMap<String, bool>>,
+}
+
+impl Default for ModelGovernanceState {
+    fn default() -> Self {
+        Self {
+            governance: AiModelGovernance::new(),
+            votes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Approved fairness models and the entries waiting to be staged.
+pub struct ModelApprovals {
+    storage: Arc<dyn Storage + Send + Sync>,
+    state: RwLock<ModelGovernanceState>,
+    queue: mpsc::UnboundedSender<ModelRegistryEntry>,
+    pending: Mutex<Option<mpsc::UnboundedReceiver<ModelRegistryEntry>>>,
+}
+
+impl ModelApprovals {
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
+        let (queue, pending) = mpsc::unbounded_channel();
+        Self {
+            storage,
+            state: RwLock::new(ModelGovernanceState::default()),
+            queue,
+            pending: Mutex::new(Some(pending)),
+        }
+    }
+
+    /// Reload the registry last persisted and queue its latest approved
+    /// entry. Returns `false` when storage holds no registry.
+    pub fn restore(&self) -> Result<bool> {
+        let Some(state) = self.storage.get_module_state(MODEL_GOVERNANCE_STATE_KEY)? else {
+            return Ok(false);
+        };
+        let state: ModelGovernanceState = serde_json::from_slice(&state)?;
+        if let Some(entry) = state.governance.latest_approved() {
+            let _ = self.queue.send(entry.clone());
+        }
+        *self.state.write() = state;
+        Ok(true)
+    }
+
+    pub fn governance(&self) -> MappedRwLockReadGuard<'_, AiModelGovernance> {
+        RwLockReadGuard::map(self.state.read(), |state| &state.governance)
+    }
+
+    /// Apply the model governance operation carried by `tx`, finalized in
+    /// `round` with `validators` as the validator set. Returns the registry
+    /// entry when the operation approves a proposal.
+    pub fn apply(
+        &self,
+        tx: &Transaction,
+        round: RoundId,
+        validators: &[Validator],
+    ) -> Result<Option<ModelRegistryEntry>, ModelGovernanceApplyError> {
+        let op = tx
+            .model_governance()
+            .ok_or(ModelGovernanceApplyError::MissingOperation)?;
+        op.validate()?;
+
+        let mut state = self.state.write();
+        let approved = match op {
+            ModelGovernanceOp::Propose(op) => {
+                if op.activation_round <= round {
+                    return Err(ModelGovernanceApplyError::ActivationPassed(
+                        op.model_id.clone(),
+                    ));
+                }
+                if state.governance.get_proposal(&op.model_id).is_some() {
+                    return Err(ModelGovernanceApplyError::AlreadyPending(
+                        op.model_id.clone(),
+                    ));
+                }
+                state.governance.submit_proposal(AiModelProposal {
+                    proposal_id: hex::encode(tx.hash()),
+                    model_id: op.model_id.clone(),
+                    version: op.version,
+                    model_url: op.model_url.clone(),
+                    model_hash: op.model_hash,
+                    signature: op.signature_bytes()?,
+                    signer_pubkey: op.signer_pubkey,
+                    activation_round: op.activation_round,
+                    description: op.description.clone(),
+                    proposer: tx.from,
+                    created_at: tx.timestamp.0,
+                    metadata: Default::default(),
+                })?;
+                None
+            }
+            ModelGovernanceOp::Vote(op) => {
+                if !validators
+                    .iter()
+                    .any(|validator| validator.is_active && validator.address == tx.from)
+                {
+                    return Err(ModelGovernanceApplyError::NotAValidator);
+                }
+                let Some(proposal) = state.governance.get_proposal(&op.model_id) else {
+                    return Err(ModelGovernanceApplyError::NoProposal(op.model_id.clone()));
+                };
+                if proposal.activation_round <= round {
+                    return Err(ModelGovernanceApplyError::ActivationPassed(
+                        op.model_id.clone(),
+                    ));
+                }
+                let votes = state.votes.entry(op.model_id.clone()).or_default();
+                if votes.insert(hex::encode(tx.from), op.approve).is_some() {
+                    return Err(ModelGovernanceApplyError::AlreadyVoted);
+                }
+
+                match tally(votes, validators) {
+                    Tally::Approved => {
+                        state.votes.remove(&op.model_id);
+                        state.governance.approve_proposal(&op.model_id, round)?
+                    }
+                    Tally::Rejected => {
+                        state.votes.remove(&op.model_id);
+                        state.governance.reject_proposal(&op.model_id)?;
+                        None
+                    }
+                    Tally::Open => None,
+                }
+            }
+        };
+
+        self.persist(&state)?;
+        if let Some(entry) = &approved {
+            let _ = self.queue.send(entry.clone());
+        }
+        Ok(approved)
+    }
+
+    /// Drop the proposals still pending when `round` reaches their
+    /// activation round. Returns their model ids.
+    pub fn expire_proposals(&self, round: RoundId) -> Result<Vec<String>> {
+        let mut state = self.state.write();
+        let mut expired: Vec<String> = state
+            .governance
+            .get_active_proposals()
+            .values()
+            .filter(|proposal| proposal.activation_round <= round)
+            .map(|proposal| proposal.model_id.clone())
+            .collect();
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+        expired.sort();
+        for model_id in &expired {
+            state.votes.remove(model_id);
+            state.governance.reject_proposal(model_id)?;
+        }
+        self.persist(&state)?;
+        Ok(expired)
+    }
+
+    /// Take the queue of approved entries; only the first caller gets it.
+    pub fn take_queue(&self) -> Option<mpsc::UnboundedReceiver<ModelRegistryEntry>> {
+        self.pending.lock().unwrap().take()
+    }
+
+    fn persist(&self, state: &ModelGovernanceState) -> Result<()> {
+        let state = serde_json::to_vec(state)?;
+        self.storage
+            .put_module_state(MODEL_GOVERNANCE_STATE_KEY, &state)
+    }
+}
+
+enum Tally {
+    Approved,
+    Rejected,
+    Open,
+}
+
+/// Weigh `votes` by the stake of the active validators casting them.
+fn tally(votes: &BTreeMap<String, bool>, validators: &[Validator]) -> Tally {
+    let mut total = 0u128;
+    let mut approve = 0u128;
+    let mut reject = 0u128;
+    for validator in validators.iter().filter(|validator| validator.is_active) {
+        let stake = validator.stake as u128;
+        total += stake;
+        match votes.get(&hex::encode(validator.address)) {
+            Some(true) => approve += stake,
+            Some(false) => reject += stake,
+            None => {}
+        }
+    }
+    if total == 0 {
+        return Tally::Open;
+    }
+    if ratio_from_parts(approve, total) >= MODEL_APPROVAL_THRESHOLD {
+        Tally::Approved
+    } else if ratio_from_parts(reject, total) > RATIO_SCALE - MODEL_APPROVAL_THRESHOLD {
+        Tally::Rejected
+    } else {
+        Tally::Open
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ModelGovernanceApplyError {
+    #[error("transaction does not carry a model governance operation")]
+    MissingOperation,
+    #[error("activation round of model {0} has already been reached")]
+    ActivationPassed(String),
+    #[error("a proposal for model {0} is already pending")]
+    AlreadyPending(String),
+    #[error("no pending proposal for model {0}")]
+    NoProposal(String),
+    #[error("only active validators can vote on model proposals")]
+    NotAValidator,
+    #[error("validator has already voted on this proposal")]
+    AlreadyVoted,
+    #[error(transparent)]
+    Invalid(#[from] ModelGovernanceOpError),
+    #[error(transparent)]
+    State(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use ippan_storage::MemoryStorage;
+    use ippan_types::{Amount, ModelProposalOp, ModelVoteOp};
+
+    fn validators() -> Vec<Validator> {
+        [(1u8, 40), (2, 30), (3, 30)]
+            .into_iter()
+            .map(|(i, stake)| Validator {
+                id: [i; 32],
+                address: [i; 32],
+                stake,
+                is_active: true,
+            })
+            .collect()
+    }
+
+    fn propose(activation_round: u64) -> Transaction {
+        let signer = SigningKey::from_bytes(&[9u8; 32]);
+        let model_hash = [7u8; 32];
+        let mut tx = Transaction::new([5u8; 32], [0u8; 32], Amount::zero(), 1);
+        tx.set_model_governance(ModelGovernanceOp::Propose(ModelProposalOp {
+            model_id: "fairness".into(),
+            version: 2,
+            model_url: "ipndht://00".into(),
+            model_hash,
+            signature: signer.sign(&model_hash).to_bytes().to_vec(),
+            signer_pubkey: signer.verifying_key().to_bytes(),
+            activation_round,
+            description: "retrained fairness model".into(),
+        }));
+        tx
+    }
+
+    fn vote(voter: u8, approve: bool) -> Transaction {
+        let mut tx = Transaction::new([voter; 32], [0u8; 32], Amount::zero(), 1);
+        tx.set_model_governance(ModelGovernanceOp::Vote(ModelVoteOp {
+            model_id: "fairness".into(),
+            approve,
+        }));
+        tx
+    }
+
+    #[test]
+    fn test_stake_weighted_votes_approve_at_finalized_round() {
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::default());
+        let approvals = ModelApprovals::new(storage.clone());
+        let validators = validators();
+        let mut queue = approvals.take_queue().unwrap();
+
+        assert!(approvals
+            .apply(&propose(50), 3, &validators)
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            approvals.apply(&propose(50), 3, &validators),
+            Err(ModelGovernanceApplyError::AlreadyPending(_))
+        ));
+        assert!(matches!(
+            approvals.apply(&vote(8, true), 4, &validators),
+            Err(ModelGovernanceApplyError::NotAValidator)
+        ));
+
+        // 40% + 30% of the stake crosses two thirds
+        assert!(approvals
+            .apply(&vote(1, true), 4, &validators)
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            approvals.apply(&vote(1, true), 5, &validators),
+            Err(ModelGovernanceApplyError::AlreadyVoted)
+        ));
+        let entry = approvals
+            .apply(&vote(2, true), 6, &validators)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.registered_at_round, 6);
+        assert_eq!(entry.activation_round, 50);
+        assert_eq!(queue.try_recv().unwrap().model_hash, [7u8; 32]);
+
+        // The approval survives a restart
+        let restored = ModelApprovals::new(storage);
+        assert!(restored.restore().unwrap());
+        assert!(restored.governance().is_model_registered("fairness"));
+        assert_eq!(
+            restored
+                .take_queue()
+                .unwrap()
+                .try_recv()
+                .unwrap()
+                .registered_at_round,
+            6
+        );
+    }
+
+    #[test]
+    fn test_rejected_and_expired_proposals_are_dropped() {
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::default());
+        let approvals = ModelApprovals::new(storage);
+        let validators = validators();
+
+        assert!(matches!(
+            approvals.apply(&propose(3), 3, &validators),
+            Err(ModelGovernanceApplyError::ActivationPassed(_))
+        ));
+
+        approvals.apply(&propose(50), 3, &validators).unwrap();
+        approvals.apply(&vote(2, true), 4, &validators).unwrap();
+        approvals.apply(&vote(3, false), 4, &validators).unwrap();
+        assert_eq!(approvals.governance().active_proposal_count(), 1);
+        // With 70% against, two thirds can no longer be reached
+        assert!(approvals
+            .apply(&vote(1, false), 5, &validators)
+            .unwrap()
+            .is_none());
+        assert_eq!(approvals.governance().active_proposal_count(), 0);
+        assert!(!approvals.governance().is_model_registered("fairness"));
+
+        approvals.apply(&propose(10), 5, &validators).unwrap();
+        assert!(approvals.expire_proposals(9).unwrap().is_empty());
+        assert_eq!(
+            approvals.expire_proposals(10).unwrap(),
+            vec!["fairness".to_string()]
+        );
+        assert!(matches!(
+            approvals.apply(&vote(1, true), 11, &validators),
+            Err(ModelGovernanceApplyError::NoProposal(_))
+        ));
+    }
+}
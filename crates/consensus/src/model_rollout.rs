@@ -0,0 +1,729 @@
+//! Staged rollout of governance-approved fairness models
+//!
+//! An approved [`ModelRegistryEntry`] is staged together with the model it
+//! pins. Until the entry's activation round the candidate scores every round
+//! in shadow next to the active engine without influencing selection; the
+//! divergence from the active reputations, primary mismatches and a
+//! re-scoring determinism check are recorded in [`ConsensusMetrics`].
+//!
+//! Those shadow statistics come from node-local telemetry, so they are only
+//! reported. Whether the candidate takes over is decided at its activation
+//! round by [`ModelRollout::activate_if_due`] from data every node holds:
+//! validator metrics rebuilt from the finalized rounds before the activation
+//! round ([`finalized_samples`]) and the stake in the validator set. Over
+//! those the candidate must re-score deterministically and stay within
+//! [`RolloutConfig::max_mean_divergence`] of the active model. A candidate
+//! that fails is dropped and the previous model stays in force.
+
+use crate::dgbdt::{DGBDTEngine, ValidatorMetrics, VerifierSelection};
+use crate::metrics::ConsensusMetrics;
+use anyhow::{bail, Result};
+use blake3::Hasher as Blake3;
+use ippan_consensus_dlc::dgbdt::FairnessModel;
+use ippan_governance::ai_models::ModelRegistryEntry;
+use ippan_storage::round_history::RoundHistory;
+use ippan_storage::Storage;
+use ippan_types::{RoundId, ValidatorId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use tracing::warn;
+
+/// Checks a staged candidate has to pass to be activated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RolloutConfig {
+    /// Shadow rounds expected before the activation round; the activation
+    /// checks run over this many finalized rounds
+    pub min_shadow_rounds: u64,
+    /// Largest mean |candidate - active| reputation delta (0-10000) over the
+    /// finalized rounds checked at activation
+    pub max_mean_divergence: i64,
+}
+
+impl Default for RolloutConfig {
+    fn default() -> Self {
+        Self {
+            min_shadow_rounds: 100,
+            max_mean_divergence: 2_000,
+        }
+    }
+}
+
+/// Accumulated results of shadow scoring.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowStats {
+    pub rounds: u64,
+    /// Sum of the per-round mean divergences (0-10000 each)
+    pub total_divergence: i64,
+    pub max_divergence: i64,
+    pub primary_mismatches: u64,
+    pub determinism_failures: u64,
+}
+
+impl ShadowStats {
+    /// Mean per-round divergence (0-10000)
+    pub fn mean_divergence(&self) -> i64 {
+        if self.rounds == 0 {
+            return 0;
+        }
+        self.total_divergence / self.rounds as i64
+    }
+}
+
+/// Where the rollout currently stands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum RolloutStatus {
+    Idle,
+    Shadow {
+        candidate_hash: String,
+        activation_round: RoundId,
+    },
+    Activated {
+        model_hash: String,
+        round: RoundId,
+        /// Node-local shadow checks the model failed; they are advisory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+    },
+    /// The candidate failed its activation checks and was dropped.
+    Rejected {
+        candidate_hash: String,
+        round: RoundId,
+        /// Model left in force (`None` for the weighted reputation)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        active_hash: Option<String>,
+        reasons: Vec<String>,
+    },
+}
+
+/// Validator metrics as of one finalized round, identical on every node.
+#[derive(Debug, Clone)]
+pub struct FinalizedSample {
+    pub round: RoundId,
+    pub metrics: HashMap<ValidatorId, ValidatorMetrics>,
+}
+
+/// A candidate that reached its activation round.
+#[derive(Debug)]
+pub struct RolloutActivation {
+    /// Canonical hash of the model
+    pub hash: String,
+    /// Model to install in the active engine
+    pub model: FairnessModel,
+    /// Node-local shadow checks the model failed
+    pub warnings: Vec<String>,
+}
+
+/// What happened to a candidate at its activation round.
+#[derive(Debug)]
+pub enum RolloutDecision {
+    Activated(RolloutActivation),
+    /// The candidate was dropped; the active model is unchanged.
+    Rejected {
+        hash: String,
+        reasons: Vec<String>,
+    },
+}
+
+struct Candidate {
+    hash: String,
+    activation_round: RoundId,
+    engine: DGBDTEngine,
+    stats: ShadowStats,
+}
+
+/// Stages one candidate model at a time and decides its activation.
+pub struct ModelRollout {
+    config: RolloutConfig,
+    active_hash: Option<String>,
+    candidate: Option<Candidate>,
+    status: RolloutStatus,
+    /// Last round passed to `observe_round`, so repeated queries for the same
+    /// round are not counted twice
+    last_observed: Option<RoundId>,
+}
+
+impl ModelRollout {
+    pub fn new(config: RolloutConfig) -> Self {
+        Self {
+            config,
+            active_hash: None,
+            candidate: None,
+            status: RolloutStatus::Idle,
+            last_observed: None,
+        }
+    }
+
+    /// Record the hash of the model the active engine scores with.
+    pub fn with_active_hash(mut self, hash: Option<String>) -> Self {
+        self.active_hash = hash;
+        self
+    }
+
+    pub fn config(&self) -> &RolloutConfig {
+        &self.config
+    }
+
+    pub fn status(&self) -> &RolloutStatus {
+        &self.status
+    }
+
+    /// Hash of the model in force (`None` for the weighted reputation)
+    pub fn active_hash(&self) -> Option<&str> {
+        self.active_hash.as_deref()
+    }
+
+    /// Shadow statistics of the staged candidate, if any
+    pub fn shadow_stats(&self) -> Option<&ShadowStats> {
+        self.candidate.as_ref().map(|c| &c.stats)
+    }
+
+    /// Stage `model` for the approved `entry`.
+    ///
+    /// The model's canonical hash must equal the registry hash. A node that
+    /// learns of the entry late still stages it, even past its activation
+    /// round; the short shadow period only shows up as a warning.
+    pub fn stage(
+        &mut self,
+        entry: &ModelRegistryEntry,
+        model: FairnessModel,
+        current_round: RoundId,
+    ) -> Result<()> {
+        if let Some(candidate) = &self.candidate {
+            bail!("model {} is already in its shadow period", candidate.hash);
+        }
+
+        let approved = hex::encode(entry.model_hash);
+        let hash = model.raw_model().hash_hex()?;
+        if hash != approved {
+            bail!("model hash {hash} does not match approved hash {approved}");
+        }
+        if self.active_hash.as_deref() == Some(approved.as_str()) {
+            bail!("model {approved} is already active");
+        }
+
+        let shadow_rounds = entry.activation_round.saturating_sub(current_round);
+        if shadow_rounds < self.config.min_shadow_rounds {
+            warn!(
+                "Model {hash} staged {shadow_rounds} rounds before activation, expected {}",
+                self.config.min_shadow_rounds
+            );
+        }
+
+        self.status = RolloutStatus::Shadow {
+            candidate_hash: hash.clone(),
+            activation_round: entry.activation_round,
+        };
+        self.candidate = Some(Candidate {
+            hash,
+            activation_round: entry.activation_round,
+            engine: DGBDTEngine::new().with_fairness_model(model),
+            stats: ShadowStats::default(),
+        });
+        Ok(())
+    }
+
+    /// Finalized rounds the activation checks of the staged candidate run
+    /// over, once `round` has reached its activation round.
+    pub fn due_check_window(&self, round: RoundId) -> Option<RangeInclusive<RoundId>> {
+        let candidate = self.candidate.as_ref()?;
+        if round < candidate.activation_round {
+            return None;
+        }
+        let end = candidate.activation_round.saturating_sub(1);
+        let start = candidate
+            .activation_round
+            .saturating_sub(self.config.min_shadow_rounds.max(1));
+        Some(start..=end)
+    }
+
+    /// Decide the staged candidate if `round` has reached its activation
+    /// round. Call this before selecting for `round`, with the
+    /// [`finalized_samples`] of [`ModelRollout::due_check_window`].
+    ///
+    /// The decision depends only on the approved entry, the active model and
+    /// `finalized`, so every node holding the same finalized rounds reaches
+    /// the same one. A rejected candidate is dropped and the previous model
+    /// stays active.
+    pub fn activate_if_due(
+        &mut self,
+        round: RoundId,
+        active: &DGBDTEngine,
+        finalized: &[FinalizedSample],
+        metrics: &ConsensusMetrics,
+    ) -> Option<RolloutDecision> {
+        if self
+            .candidate
+            .as_ref()
+            .is_none_or(|candidate| round < candidate.activation_round)
+        {
+            return None;
+        }
+        let candidate = self.candidate.take()?;
+
+        let reasons = self.activation_failures(&candidate.engine, active, finalized);
+        if !reasons.is_empty() {
+            for _ in &reasons {
+                metrics.record_model_rollout_check_failure();
+            }
+            metrics.record_model_rollout_rejection();
+            self.status = RolloutStatus::Rejected {
+                candidate_hash: candidate.hash.clone(),
+                round,
+                active_hash: self.active_hash.clone(),
+                reasons: reasons.clone(),
+            };
+            return Some(RolloutDecision::Rejected {
+                hash: candidate.hash,
+                reasons,
+            });
+        }
+
+        let warnings = self.shadow_warnings(&candidate.stats);
+        metrics.record_model_rollout_activation();
+        let model = candidate
+            .engine
+            .fairness_model()
+            .cloned()
+            .expect("staged engines always carry a model");
+        self.active_hash = Some(candidate.hash.clone());
+        self.status = RolloutStatus::Activated {
+            model_hash: candidate.hash.clone(),
+            round,
+            warnings: warnings.clone(),
+        };
+        Some(RolloutDecision::Activated(RolloutActivation {
+            hash: candidate.hash,
+            model,
+            warnings,
+        }))
+    }
+
+    /// Activation checks `candidate` fails over the finalized rounds.
+    fn activation_failures(
+        &self,
+        candidate: &DGBDTEngine,
+        active: &DGBDTEngine,
+        finalized: &[FinalizedSample],
+    ) -> Vec<String> {
+        if finalized.is_empty() {
+            return vec!["no finalized rounds to check the model against".into()];
+        }
+        let mut reasons = Vec::new();
+        let nondeterministic = finalized
+            .iter()
+            .filter(|sample| !shadow_is_deterministic(candidate, &sample.metrics))
+            .count();
+        if nondeterministic > 0 {
+            reasons.push(format!(
+                "scores were not deterministic in {nondeterministic} finalized rounds"
+            ));
+        }
+        let total: i64 = finalized
+            .iter()
+            .map(|sample| mean_divergence(candidate, active, &sample.metrics))
+            .sum();
+        let mean = total / finalized.len() as i64;
+        if mean > self.config.max_mean_divergence {
+            reasons.push(format!(
+                "mean divergence {mean} over {} finalized rounds exceeds {}",
+                finalized.len(),
+                self.config.max_mean_divergence
+            ));
+        }
+        reasons
+    }
+
+    /// Shadow-score `round` with the staged candidate, if any.
+    ///
+    /// `selection` is the active engine's choice for the round; the
+    /// candidate never influences it. Results only feed metrics.
+    pub fn observe_round(
+        &mut self,
+        round: RoundId,
+        all_metrics: &HashMap<ValidatorId, ValidatorMetrics>,
+        active: &DGBDTEngine,
+        selection: &VerifierSelection,
+        metrics: &ConsensusMetrics,
+    ) {
+        if self.last_observed.is_some_and(|last| round <= last) {
+            return;
+        }
+        let Some(candidate) = self.candidate.as_mut() else {
+            return;
+        };
+        if round >= candidate.activation_round {
+            return;
+        }
+        self.last_observed = Some(round);
+
+        if !shadow_is_deterministic(&candidate.engine, all_metrics) {
+            candidate.stats.determinism_failures += 1;
+            metrics.record_shadow_determinism_failure();
+            warn!(
+                "Shadow scores of model {} changed with validator order in round {round}",
+                candidate.hash
+            );
+        }
+
+        let divergence = mean_divergence(&candidate.engine, active, all_metrics);
+        let primary_matches = candidate
+            .engine
+            .select_verifiers(round, all_metrics, 0, 0)
+            .map(|shadow| shadow.primary == selection.primary)
+            .unwrap_or(false);
+
+        let stats = &mut candidate.stats;
+        stats.rounds += 1;
+        stats.total_divergence = stats.total_divergence.saturating_add(divergence);
+        stats.max_divergence = stats.max_divergence.max(divergence);
+        if !primary_matches {
+            stats.primary_mismatches += 1;
+        }
+        metrics.record_shadow_round(divergence, primary_matches);
+    }
+
+    /// Node-local shadow checks `stats` failed.
+    fn shadow_warnings(&self, stats: &ShadowStats) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if stats.determinism_failures > 0 {
+            warnings.push(format!(
+                "shadow scores were not deterministic in {} rounds",
+                stats.determinism_failures
+            ));
+        }
+        if stats.rounds < self.config.min_shadow_rounds {
+            warnings.push(format!(
+                "only {} of {} shadow rounds observed",
+                stats.rounds, self.config.min_shadow_rounds
+            ));
+        }
+        if stats.mean_divergence() > self.config.max_mean_divergence {
+            warnings.push(format!(
+                "mean divergence {} exceeds {}",
+                stats.mean_divergence(),
+                self.config.max_mean_divergence
+            ));
+        }
+        warnings
+    }
+}
+
+impl Default for ModelRollout {
+    fn default() -> Self {
+        Self::new(RolloutConfig::default())
+    }
+}
+
+/// Validator metrics rebuilt from the finalized rounds in `window`.
+///
+/// Block counts and active rounds accumulate over the window; stake comes
+/// from `stakes`, the validator set every node shares. Telemetry is left
+/// out because each node only holds its own snapshot. Returns `None` until
+/// the last round of the window is finalized.
+pub fn finalized_samples<S: Storage + ?Sized>(
+    storage: &S,
+    window: RangeInclusive<RoundId>,
+    stakes: &BTreeMap<ValidatorId, u64>,
+) -> Result<Option<Vec<FinalizedSample>>> {
+    let finalized_through = storage
+        .get_latest_round_finalization()?
+        .map(|record| record.round);
+    if finalized_through.is_none_or(|latest| latest < *window.end()) {
+        return Ok(None);
+    }
+    let history = RoundHistory::load(storage, Some(*window.start()), Some(*window.end()))?;
+
+    let mut metrics: HashMap<ValidatorId, ValidatorMetrics> = stakes
+        .iter()
+        .map(|(id, stake)| {
+            let metrics = ValidatorMetrics {
+                stake_amount: *stake,
+                ..Default::default()
+            };
+            (*id, metrics)
+        })
+        .collect();
+    let mut samples = Vec::with_capacity(history.rounds.len());
+    for finalized in &history.rounds {
+        for (creator, activity) in finalized.activities() {
+            if let Some(entry) = metrics.get_mut(&creator) {
+                entry.blocks_proposed += activity.proposed;
+                entry.blocks_verified += activity.verified;
+                entry.rounds_active += 1;
+            }
+        }
+        samples.push(FinalizedSample {
+            round: finalized.round,
+            metrics: metrics.clone(),
+        });
+    }
+    Ok(Some(samples))
+}
+
+/// Mean |candidate - active| reputation over all validators (0-10000).
+fn mean_divergence(
+    candidate: &DGBDTEngine,
+    active: &DGBDTEngine,
+    all_metrics: &HashMap<ValidatorId, ValidatorMetrics>,
+) -> i64 {
+    if all_metrics.is_empty() {
+        return 0;
+    }
+    let total: i64 = all_metrics
+        .values()
+        .map(|m| {
+            (candidate.calculate_reputation(m) as i64 - active.calculate_reputation(m) as i64).abs()
+        })
+        .sum();
+    total / all_metrics.len() as i64
+}
+
+/// Score the validators in ascending and descending id order and compare the
+/// digests; any order or state dependence in the candidate shows up here.
+fn shadow_is_deterministic(
+    engine: &DGBDTEngine,
+    all_metrics: &HashMap<ValidatorId, ValidatorMetrics>,
+) -> bool {
+    let ordered: BTreeMap<&ValidatorId, &ValidatorMetrics> = all_metrics.iter().collect();
+    let forward: Vec<_> = ordered
+        .iter()
+        .map(|(id, m)| (**id, engine.calculate_reputation(m)))
+        .collect();
+    let mut backward: Vec<_> = ordered
+        .iter()
+        .rev()
+        .map(|(id, m)| (**id, engine.calculate_reputation(m)))
+        .collect();
+    backward.reverse();
+    score_digest(&forward) == score_digest(&backward)
+}
+
+fn score_digest(scores: &[(ValidatorId, i32)]) -> [u8; 32] {
+    let mut hasher = Blake3::new();
+    hasher.update(b"DLC_SHADOW_SCORES");
+    for (id, score) in scores {
+        hasher.update(id);
+        hasher.update(&score.to_be_bytes());
+    }
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metrics() -> HashMap<ValidatorId, ValidatorMetrics> {
+        (1u8..=4)
+            .map(|i| {
+                let metrics = ValidatorMetrics {
+                    blocks_proposed: i as u64 * 10,
+                    blocks_verified: i as u64 * 20,
+                    rounds_active: 100,
+                    ..Default::default()
+                };
+                ([i; 32], metrics)
+            })
+            .collect()
+    }
+
+    fn approved_entry(model: &FairnessModel, activation_round: RoundId) -> ModelRegistryEntry {
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(model.raw_model().hash_hex().unwrap(), &mut hash).unwrap();
+        ModelRegistryEntry::new(
+            "fairness".into(),
+            hash,
+            2,
+            activation_round,
+            [0u8; 64],
+            0,
+            String::new(),
+        )
+    }
+
+    fn finalized(window: RangeInclusive<RoundId>) -> Vec<FinalizedSample> {
+        window
+            .map(|round| FinalizedSample {
+                round,
+                metrics: sample_metrics(),
+            })
+            .collect()
+    }
+
+    /// Shadow-score `rounds`, deciding first when due as the engine does.
+    fn run_until(
+        rollout: &mut ModelRollout,
+        engine: &DGBDTEngine,
+        metrics: &ConsensusMetrics,
+        rounds: std::ops::Range<RoundId>,
+    ) -> Option<(RoundId, RolloutDecision)> {
+        let validators = sample_metrics();
+        for round in rounds {
+            if let Some(window) = rollout.due_check_window(round) {
+                let samples = finalized(window);
+                let decision = rollout.activate_if_due(round, engine, &samples, metrics);
+                return decision.map(|decision| (round, decision));
+            }
+            let selection = engine.select_verifiers(round, &validators, 0, 0).unwrap();
+            rollout.observe_round(round, &validators, engine, &selection, metrics);
+        }
+        None
+    }
+
+    #[test]
+    fn test_stage_checks_approved_hash() {
+        let model = FairnessModel::testing_stub();
+        let config = RolloutConfig {
+            min_shadow_rounds: 10,
+            ..Default::default()
+        };
+
+        let mut wrong_hash = approved_entry(&model, 50);
+        wrong_hash.model_hash[0] ^= 1;
+        let mut rollout = ModelRollout::new(config.clone());
+        assert!(rollout.stage(&wrong_hash, model.clone(), 0).is_err());
+        assert_eq!(rollout.status(), &RolloutStatus::Idle);
+
+        // A short shadow period is not a reason to refuse the approved model
+        rollout
+            .stage(&approved_entry(&model, 5), model.clone(), 0)
+            .unwrap();
+        assert!(rollout
+            .stage(&approved_entry(&model, 20), model, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_candidate_activates_at_its_round() {
+        let model = FairnessModel::testing_stub();
+        let hash = model.raw_model().hash_hex().unwrap();
+        let engine = DGBDTEngine::new();
+        let metrics = ConsensusMetrics::new();
+        let mut rollout = ModelRollout::new(RolloutConfig {
+            min_shadow_rounds: 5,
+            max_mean_divergence: 10_000,
+        });
+        rollout.stage(&approved_entry(&model, 5), model, 0).unwrap();
+        assert_eq!(rollout.due_check_window(4), None);
+        assert_eq!(rollout.due_check_window(5), Some(0..=4));
+
+        let (round, decision) = run_until(&mut rollout, &engine, &metrics, 0..10).unwrap();
+        assert_eq!(round, 5);
+        let RolloutDecision::Activated(activation) = decision else {
+            panic!("expected activation, got {decision:?}");
+        };
+        assert!(activation.warnings.is_empty());
+        assert_eq!(
+            rollout.status(),
+            &RolloutStatus::Activated {
+                model_hash: hash.clone(),
+                round: 5,
+                warnings: Vec::new(),
+            }
+        );
+        assert_eq!(rollout.active_hash(), Some(hash.as_str()));
+        assert_eq!(metrics.get_shadow_rounds_total(), 5);
+        assert!(metrics.get_shadow_divergence_max() > 0);
+        assert_eq!(metrics.get_model_rollout_activations(), 1);
+        assert_eq!(metrics.get_model_rollout_check_failures(), 0);
+    }
+
+    #[test]
+    fn test_failed_checks_keep_previous_model() {
+        let model = FairnessModel::testing_stub();
+        let hash = model.raw_model().hash_hex().unwrap();
+        let engine = DGBDTEngine::new();
+        let metrics = ConsensusMetrics::new();
+        let mut rollout = ModelRollout::new(RolloutConfig {
+            min_shadow_rounds: 3,
+            max_mean_divergence: 100,
+        })
+        .with_active_hash(Some("previous".into()));
+        rollout
+            .stage(&approved_entry(&model, 3), model.clone(), 0)
+            .unwrap();
+
+        let (round, decision) = run_until(&mut rollout, &engine, &metrics, 0..10).unwrap();
+        assert_eq!(round, 3);
+        let RolloutDecision::Rejected {
+            hash: rejected,
+            reasons,
+        } = decision
+        else {
+            panic!("expected rejection, got {decision:?}");
+        };
+        assert_eq!(rejected, hash);
+        assert_eq!(reasons.len(), 1);
+        assert!(reasons[0].contains("divergence"));
+        assert_eq!(rollout.active_hash(), Some("previous"));
+        assert!(matches!(
+            rollout.status(),
+            RolloutStatus::Rejected { active_hash: Some(active), .. } if active == "previous"
+        ));
+        assert_eq!(metrics.get_model_rollout_rejections(), 1);
+        assert_eq!(metrics.get_model_rollout_check_failures(), 1);
+        assert_eq!(metrics.get_model_rollout_activations(), 0);
+
+        // The candidate is gone; a later approval can be staged again
+        assert!(rollout.shadow_stats().is_none());
+        assert!(rollout.activate_if_due(4, &engine, &[], &metrics).is_none());
+        rollout
+            .stage(&approved_entry(&model, 20), model, 4)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_activation_requires_finalized_rounds() {
+        let model = FairnessModel::testing_stub();
+        let engine = DGBDTEngine::new();
+        let metrics = ConsensusMetrics::new();
+        let mut rollout = ModelRollout::default();
+        rollout.stage(&approved_entry(&model, 5), model, 0).unwrap();
+
+        let decision = rollout.activate_if_due(5, &engine, &[], &metrics);
+        assert!(matches!(
+            decision,
+            Some(RolloutDecision::Rejected { ref reasons, .. }) if reasons[0].contains("finalized")
+        ));
+        assert_eq!(rollout.active_hash(), None);
+    }
+
+    #[test]
+    fn test_late_staging_activates_on_next_round() {
+        let model = FairnessModel::testing_stub();
+        let engine = DGBDTEngine::new();
+        let metrics = ConsensusMetrics::new();
+        let mut rollout = ModelRollout::new(RolloutConfig {
+            max_mean_divergence: 10_000,
+            ..Default::default()
+        });
+        rollout.stage(&approved_entry(&model, 5), model, 8).unwrap();
+
+        let samples = finalized(rollout.due_check_window(8).unwrap());
+        let Some(RolloutDecision::Activated(activation)) =
+            rollout.activate_if_due(8, &engine, &samples, &metrics)
+        else {
+            panic!("expected activation");
+        };
+        // The missed shadow period is only reported
+        assert_eq!(
+            activation.warnings,
+            vec!["only 0 of 100 shadow rounds observed"]
+        );
+        assert!(rollout
+            .activate_if_due(9, &engine, &samples, &metrics)
+            .is_none());
+    }
+
+    #[test]
+    fn test_shadow_scoring_is_deterministic() {
+        let engine = DGBDTEngine::new().with_fairness_model(FairnessModel::testing_stub());
+        assert!(shadow_is_deterministic(&engine, &sample_metrics()));
+        assert_eq!(
+            mean_divergence(&engine, &engine, &sample_metrics()),
+            0,
+            "identical engines never diverge"
+        );
+    }
+}
@@ -29,6 +29,10 @@ impl PaymentApplier {
         &self.policy
     }
 
+    pub fn treasury_account(&self) -> [u8; 32] {
+        self.treasury_account
+    }
+
     pub fn apply(
         &self,
         storage: &Arc<dyn Storage + Send + Sync>,
@@ -81,6 +85,40 @@ impl PaymentApplier {
     }
 }
 
+/// Move `amount_atomic` between two accounts without charging fees.
+///
+/// Used to release funds held in protocol escrow accounts (e.g. handle auction deposits).
+pub fn move_funds(
+    storage: &Arc<dyn Storage + Send + Sync>,
+    from: &[u8; 32],
+    to: &[u8; 32],
+    amount_atomic: u128,
+) -> Result<(), PaymentApplyError> {
+    if amount_atomic == 0 || from == to {
+        return Ok(());
+    }
+
+    let mut source = storage
+        .get_account(from)
+        .map_err(PaymentApplyError::Storage)?
+        .ok_or(PaymentApplyError::MissingAccount(*from))?;
+    let available = source.balance as u128;
+    if available < amount_atomic {
+        return Err(PaymentApplyError::InsufficientBalance {
+            available,
+            required: amount_atomic,
+        });
+    }
+    source.balance = (available - amount_atomic)
+        .try_into()
+        .map_err(|_| PaymentApplyError::BalanceOverflow)?;
+    storage
+        .update_account(source)
+        .map_err(PaymentApplyError::Storage)?;
+
+    credit_account(storage, to, amount_atomic)
+}
+
 fn credit_account(
     storage: &Arc<dyn Storage + Send + Sync>,
     address: &[u8; 32],
@@ -0,0 +1,270 @@
+use crate::hashtimer_integration::derive_selection_seed;
+use crate::payments::{move_funds, PaymentApplyError};
+use ippan_files::{
+    PinChallenge, PinDeal, PinDealId, PinDealRequest, PinProof, PinSettlement, PinningError,
+    PinningMarket,
+};
+use ippan_storage::Storage;
+use ippan_types::HashTimer;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::warn;
+
+/// Protocol account holding pinning payments and provider collateral.
+pub const PINNING_ESCROW_ACCOUNT: [u8; 32] = *b"IPPAN_PINNING_MARKET_ESCROW_0000";
+
+/// Module state key under which the deal book is persisted.
+const PINNING_MARKET_STATE_KEY: &str = "pinning_market";
+
+/// Drives the storage pinning market from finalized rounds.
+///
+/// Funds are moved into [`PINNING_ESCROW_ACCOUNT`] when a deal opens and
+/// released as epochs settle during round finalization. Challenges are seeded
+/// with [`derive_selection_seed`] over the HashTimer of the round that starts
+/// the epoch, so every node derives the same chunk indices.
+pub struct PinningPipeline {
+    market: Arc<PinningMarket>,
+}
+
+/// Result of advancing the market at an epoch boundary.
+#[derive(Debug, Default)]
+pub struct PinningRoundOutcome {
+    /// Settlements for the epoch that just ended.
+    pub settlements: Vec<PinSettlement>,
+    /// Challenges for the epoch that just started.
+    pub challenges: Vec<PinChallenge>,
+}
+
+impl PinningPipeline {
+    pub fn new(market: Arc<PinningMarket>) -> Self {
+        Self { market }
+    }
+
+    pub fn market(&self) -> Arc<PinningMarket> {
+        self.market.clone()
+    }
+
+    /// Write the deal book to storage so escrowed deals survive a restart.
+    pub fn persist(&self, storage: &Arc<dyn Storage + Send + Sync>) -> anyhow::Result<()> {
+        let state = serde_json::to_vec(&self.market.snapshot())?;
+        storage.put_module_state(PINNING_MARKET_STATE_KEY, &state)
+    }
+
+    /// Reload the deal book last written by [`PinningPipeline::persist`].
+    ///
+    /// Returns `false` when storage holds no pinning state.
+    pub fn restore(&self, storage: &Arc<dyn Storage + Send + Sync>) -> anyhow::Result<bool> {
+        let Some(state) = storage.get_module_state(PINNING_MARKET_STATE_KEY)? else {
+            return Ok(false);
+        };
+        self.market.restore(serde_json::from_slice(&state)?);
+        Ok(true)
+    }
+
+    /// Open a deal, escrowing the client's payment and the provider's collateral.
+    pub fn open_deal(
+        &self,
+        storage: &Arc<dyn Storage + Send + Sync>,
+        request: PinDealRequest,
+        round: u64,
+    ) -> Result<PinDeal, PinningApplyError> {
+        self.market.validate_deal(&request)?;
+        let payment = request.total_payment().unwrap_or_default();
+        move_funds(storage, &request.client, &PINNING_ESCROW_ACCOUNT, payment)?;
+        if let Err(error) = move_funds(
+            storage,
+            &request.provider,
+            &PINNING_ESCROW_ACCOUNT,
+            request.collateral,
+        ) {
+            self.release(storage, &request.client, payment, None);
+            return Err(error.into());
+        }
+
+        let (client, provider, collateral) = (request.client, request.provider, request.collateral);
+        self.market
+            .open_deal(request, round)
+            .inspect_err(|_| {
+                self.release(storage, &client, payment, None);
+                self.release(storage, &provider, collateral, None);
+            })
+            .map_err(PinningApplyError::from)
+    }
+
+    /// Record a provider's answer to its open challenge.
+    pub fn submit_proof(&self, proof: &PinProof) -> Result<(), PinningError> {
+        self.market.submit_proof(proof)
+    }
+
+    /// Advance the market at `round`.
+    ///
+    /// On the first round of an epoch the previous epoch is settled (releasing
+    /// payments, slashed collateral and refunds from escrow) and active deals are
+    /// challenged for the new epoch. Other rounds are a no-op.
+    pub fn on_round(
+        &self,
+        storage: &Arc<dyn Storage + Send + Sync>,
+        round: u64,
+        round_hashtimer: &HashTimer,
+    ) -> PinningRoundOutcome {
+        if !self.market.is_epoch_start(round) {
+            return PinningRoundOutcome::default();
+        }
+        let epoch = self.market.epoch_for_round(round);
+        let settlements = match epoch.checked_sub(1) {
+            Some(previous) => self.market.settle_epoch(previous),
+            None => Vec::new(),
+        };
+        for settlement in &settlements {
+            let deal = Some(&settlement.deal_id);
+            self.release(storage, &settlement.provider, settlement.to_provider, deal);
+            self.release(storage, &settlement.client, settlement.to_client, deal);
+        }
+        let challenges = self
+            .market
+            .issue_challenges(epoch, &derive_selection_seed(round_hashtimer));
+        PinningRoundOutcome {
+            settlements,
+            challenges,
+        }
+    }
+
+    fn release(
+        &self,
+        storage: &Arc<dyn Storage + Send + Sync>,
+        to: &[u8; 32],
+        amount: u128,
+        deal: Option<&PinDealId>,
+    ) {
+        if let Err(error) = move_funds(storage, &PINNING_ESCROW_ACCOUNT, to, amount) {
+            warn!(
+                deal = deal.map(PinDealId::to_hex),
+                recipient = hex::encode(to),
+                amount = amount as u64,
+                error = %error,
+                "failed to release pinning escrow"
+            );
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PinningApplyError {
+    #[error(transparent)]
+    Market(#[from] PinningError),
+    #[error("failed to escrow deal funds: {0}")]
+    Payment(#[from] PaymentApplyError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashtimer_integration::generate_round_hashtimer;
+    use ippan_files::{ContentStore, FileId, LocalProviderSet, PinDealStatus, PinningConfig};
+    use ippan_storage::{Account, MemoryStorage};
+
+    const CLIENT: [u8; 32] = [1u8; 32];
+    const HONEST: [u8; 32] = [2u8; 32];
+    const FLAKY: [u8; 32] = [3u8; 32];
+
+    fn balance(storage: &Arc<dyn Storage + Send + Sync>, address: &[u8; 32]) -> u64 {
+        storage
+            .get_account(address)
+            .unwrap()
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn settles_in_process_providers_across_epochs() {
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        for address in [CLIENT, HONEST, FLAKY] {
+            storage
+                .update_account(Account {
+                    address,
+                    balance: 10_000,
+                    nonce: 0,
+                })
+                .unwrap();
+        }
+        let pipeline = PinningPipeline::new(Arc::new(PinningMarket::new(PinningConfig {
+            epoch_rounds: 5,
+            challenges_per_epoch: 2,
+            slash_bps: 2_500,
+            max_missed_epochs: 2,
+        })));
+
+        let data: Vec<u8> = (0..300u32).map(|i| (i * 7 % 256) as u8).collect();
+        let mut providers = LocalProviderSet::new();
+        let mut manifest = None;
+        for provider in [HONEST, FLAKY] {
+            let store = ContentStore::in_memory().with_chunk_size(32);
+            manifest = Some(store.put(&data).unwrap());
+            providers.add_provider(provider, store);
+        }
+        let manifest = manifest.unwrap();
+        let request = |provider| PinDealRequest {
+            file_id: FileId::from_bytes([8u8; 32]),
+            content_root: manifest.root,
+            chunk_count: manifest.chunk_count() as u64,
+            client: CLIENT,
+            provider,
+            price_per_epoch: 200,
+            epochs: 2,
+            collateral: 1_000,
+        };
+        let honest = pipeline.open_deal(&storage, request(HONEST), 1).unwrap();
+        let flaky = pipeline.open_deal(&storage, request(FLAKY), 1).unwrap();
+        assert_eq!(balance(&storage, &CLIENT), 10_000 - 800);
+        assert_eq!(balance(&storage, &PINNING_ESCROW_ACCOUNT), 2_800);
+
+        let mut poor = request([4u8; 32]);
+        poor.collateral = 1;
+        assert!(matches!(
+            pipeline.open_deal(&storage, poor, 1),
+            Err(PinningApplyError::Payment(_))
+        ));
+        assert_eq!(balance(&storage, &CLIENT), 10_000 - 800);
+
+        let mut previous = [0u8; 32];
+        for round in 2..=15u64 {
+            let hashtimer = generate_round_hashtimer(round, &previous, &[7u8; 32]);
+            previous = hashtimer.digest();
+            let outcome = pipeline.on_round(&storage, round, &hashtimer);
+            for challenge in &outcome.challenges {
+                let expected = derive_selection_seed(&hashtimer);
+                assert_eq!(
+                    challenge.chunk_indices,
+                    ippan_files::pinning::derive_challenge_indices(
+                        &expected,
+                        &challenge.deal_id,
+                        challenge.epoch,
+                        manifest.chunk_count() as u64,
+                        2,
+                    )
+                );
+                // The flaky provider only answers its first challenge.
+                if challenge.provider == FLAKY && challenge.epoch > 1 {
+                    continue;
+                }
+                let proof = providers.respond(challenge).unwrap();
+                pipeline.submit_proof(&proof).unwrap();
+            }
+        }
+
+        let market = pipeline.market();
+        assert_eq!(
+            market.deal(&honest.id).unwrap().status,
+            PinDealStatus::Completed
+        );
+        let flaky = market.deal(&flaky.id).unwrap();
+        assert_eq!(flaky.status, PinDealStatus::Completed);
+        assert_eq!((flaky.epochs_proven, flaky.epochs_missed), (1, 1));
+
+        assert_eq!(balance(&storage, &HONEST), 10_000 + 400);
+        // One epoch paid, a quarter of the collateral slashed to the client.
+        assert_eq!(balance(&storage, &FLAKY), 10_000 + 200 - 250);
+        assert_eq!(balance(&storage, &CLIENT), 10_000 - 600 + 250);
+        assert_eq!(balance(&storage, &PINNING_ESCROW_ACCOUNT), 0);
+    }
+}
@@ -0,0 +1,569 @@
+//! Counterfactual replay of a candidate fairness model
+//!
+//! Replays historical rounds through the two selection paths, once with the
+//! active D-GBDT model and once with a candidate:
+//! - [`VerifierSet::select`], the DLC verifier-set ranking, and
+//! - [`DGBDTEngine::select_verifiers`], the seeded weighted selection that
+//!   drives block rewards, whose choices are fed into an [`EmissionTracker`].
+//!
+//! The [`ReplayReport`] compares selection counts, reward concentration (Gini
+//! over tracker payouts) and stake-weighted fairness between the two models,
+//! and serializes to JSON for attachment to a governance proposal.
+
+use crate::dgbdt::{DGBDTEngine, ValidatorMetrics};
+use crate::emission_tracker::{EmissionTracker, ValidatorContribution};
+use anyhow::{anyhow, Context, Result};
+use ippan_consensus_dlc::dgbdt::FairnessModel;
+use ippan_consensus_dlc::verifier::VerifierSet;
+use ippan_economics::EmissionParams;
+use ippan_storage::round_history::RoundHistory;
+use ippan_storage::Storage;
+use ippan_types::{RoundId, ValidatorId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Fixed-point scale of Gini and fairness values (1_000_000 = 1.0)
+pub const REPLAY_SCALE: i64 = 1_000_000;
+
+/// Proposal metadata key under which [`ReplayReport::to_json`] is stored
+pub const REPLAY_METADATA_KEY: &str = "counterfactual_replay";
+
+/// Validator metrics as they were known when a round's verifiers were chosen.
+#[derive(Debug, Clone)]
+pub struct ReplayRound {
+    pub round: RoundId,
+    /// Selection seed passed to [`VerifierSet::select`]
+    pub seed: String,
+    pub metrics: HashMap<ValidatorId, ValidatorMetrics>,
+}
+
+/// Rebuild replay rounds from finalized rounds and stored telemetry.
+///
+/// Candidates in each round are the validators with telemetry plus every
+/// block creator seen so far. Block counts and active rounds are counted from
+/// the finalized rounds before the replayed one; everything else comes from
+/// the latest telemetry snapshot (see [`ippan_storage::round_history`]).
+pub fn rounds_from_storage<S: Storage + ?Sized>(
+    storage: &S,
+    from_round: Option<RoundId>,
+    to_round: Option<RoundId>,
+) -> Result<Vec<ReplayRound>> {
+    let history = RoundHistory::load(storage, from_round, to_round)?;
+    let mut metrics: HashMap<ValidatorId, ValidatorMetrics> = history
+        .telemetry
+        .iter()
+        .map(|(id, entry)| {
+            let metrics = ValidatorMetrics {
+                blocks_proposed: 0,
+                blocks_verified: 0,
+                rounds_active: 0,
+                avg_latency_us: entry.avg_latency_us,
+                uptime_percentage: (entry.uptime_percentage_scaled * 1_000_000) / 10000,
+                slash_count: entry.slash_count,
+                recent_performance: (entry.recent_performance_scaled * 1_000_000) / 10000,
+                network_contribution: (entry.network_contribution_scaled * 1_000_000) / 10000,
+                stake_amount: entry.stake,
+            };
+            (*id, metrics)
+        })
+        .collect();
+
+    let mut rounds = Vec::new();
+    for finalized in &history.rounds {
+        if !metrics.is_empty() {
+            rounds.push(ReplayRound {
+                round: finalized.round,
+                seed: hex::encode(finalized.state_root),
+                metrics: metrics.clone(),
+            });
+        }
+        for (creator, activity) in finalized.activities() {
+            let entry = metrics.entry(creator).or_default();
+            entry.blocks_proposed += activity.proposed;
+            entry.blocks_verified += activity.verified;
+            entry.rounds_active += 1;
+        }
+    }
+
+    if rounds.is_empty() {
+        return Err(anyhow!("no finalized rounds with validators in range"));
+    }
+    Ok(rounds)
+}
+
+/// Selection parameters shared by both models.
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    /// Shadow verifiers per round; verifier sets hold `shadow_count + 1`
+    pub shadow_count: usize,
+    /// Minimum reputation for the weighted selection
+    pub min_reputation: i32,
+    pub emission: EmissionParams,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            shadow_count: 3,
+            min_reputation: 0,
+            emission: EmissionParams::default(),
+        }
+    }
+}
+
+/// Per-validator outcome of replaying one model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorReplay {
+    pub validator_id: String,
+    pub stake: u64,
+    /// Rounds as primary in [`VerifierSet::select`]
+    pub verifier_set_primary: u64,
+    /// Rounds in the verifier set, primary included
+    pub verifier_set_member: u64,
+    /// Rounds as primary in [`DGBDTEngine::select_verifiers`]
+    pub weighted_primary: u64,
+    /// Rounds as a weighted shadow
+    pub weighted_shadow: u64,
+    /// Total [`EmissionTracker`] earnings in µIPN
+    pub payout: u128,
+}
+
+/// Outcome of replaying one model over all rounds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelReplay {
+    pub model_hash: String,
+    /// Sorted by validator ID
+    pub validators: Vec<ValidatorReplay>,
+    /// Gini coefficient of payouts at [`REPLAY_SCALE`] (0 = equal)
+    pub payout_gini: i64,
+    /// One minus the total variation distance between weighted primary
+    /// shares and stake shares, at [`REPLAY_SCALE`] (1 = proportional)
+    pub stake_fairness: i64,
+    #[serde(skip)]
+    primaries: Vec<(ValidatorId, ValidatorId)>,
+}
+
+/// Side-by-side replay of the active and candidate models.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub from_round: RoundId,
+    pub to_round: RoundId,
+    pub rounds: usize,
+    pub active: ModelReplay,
+    pub candidate: ModelReplay,
+    /// Rounds whose verifier-set primary differs between the models
+    pub verifier_set_primary_changes: u64,
+    /// Rounds whose weighted primary differs between the models
+    pub weighted_primary_changes: u64,
+    /// `candidate.payout_gini - active.payout_gini`
+    pub payout_gini_delta: i64,
+    /// `candidate.stake_fairness - active.stake_fairness`
+    pub stake_fairness_delta: i64,
+}
+
+impl ReplayReport {
+    /// Pretty-printed JSON report
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize replay report")
+    }
+
+    /// Store the report in a proposal's metadata under [`REPLAY_METADATA_KEY`]
+    #[cfg(feature = "ai_l1")]
+    pub fn attach_to(&self, proposal: &mut ippan_ai_registry::AiModelProposal) -> Result<()> {
+        let json = serde_json::to_string(self).context("failed to serialize replay report")?;
+        proposal
+            .metadata
+            .insert(REPLAY_METADATA_KEY.to_string(), json);
+        Ok(())
+    }
+}
+
+/// Replays rounds for an active and a candidate fairness model.
+pub struct CounterfactualReplay {
+    active: FairnessModel,
+    candidate: FairnessModel,
+    config: ReplayConfig,
+}
+
+impl CounterfactualReplay {
+    pub fn new(active: FairnessModel, candidate: FairnessModel, config: ReplayConfig) -> Self {
+        Self {
+            active,
+            candidate,
+            config,
+        }
+    }
+
+    /// Replay `rounds` (in order) under both models and compare the outcomes.
+    pub fn run(&self, rounds: &[ReplayRound]) -> Result<ReplayReport> {
+        let (first, last) = match (rounds.first(), rounds.last()) {
+            (Some(first), Some(last)) => (first.round, last.round),
+            _ => return Err(anyhow!("no rounds to replay")),
+        };
+
+        let active = self.replay_model(&self.active, rounds)?;
+        let candidate = self.replay_model(&self.candidate, rounds)?;
+
+        let changes = |pick: fn(&(ValidatorId, ValidatorId)) -> ValidatorId| {
+            active
+                .primaries
+                .iter()
+                .zip(&candidate.primaries)
+                .filter(|(a, c)| pick(a) != pick(c))
+                .count() as u64
+        };
+
+        Ok(ReplayReport {
+            from_round: first,
+            to_round: last,
+            rounds: rounds.len(),
+            verifier_set_primary_changes: changes(|p| p.0),
+            weighted_primary_changes: changes(|p| p.1),
+            payout_gini_delta: candidate.payout_gini - active.payout_gini,
+            stake_fairness_delta: candidate.stake_fairness - active.stake_fairness,
+            active,
+            candidate,
+        })
+    }
+
+    fn replay_model(&self, model: &FairnessModel, rounds: &[ReplayRound]) -> Result<ModelReplay> {
+        let engine = DGBDTEngine::new().with_fairness_model(model.clone());
+        // Replayed rounds are numbered consecutively, as the tracker requires.
+        let mut tracker = EmissionTracker::new(self.config.emission.clone(), u64::MAX);
+        let first_round = rounds[0].round.max(1);
+
+        let mut stats: BTreeMap<ValidatorId, ValidatorReplay> = BTreeMap::new();
+        let mut primaries = Vec::with_capacity(rounds.len());
+
+        for (offset, replay) in rounds.iter().enumerate() {
+            for (id, metrics) in &replay.metrics {
+                let entry = stats.entry(*id).or_insert_with(|| ValidatorReplay {
+                    validator_id: hex::encode(id),
+                    stake: 0,
+                    verifier_set_primary: 0,
+                    verifier_set_member: 0,
+                    weighted_primary: 0,
+                    weighted_shadow: 0,
+                    payout: 0,
+                });
+                entry.stake = metrics.stake_amount;
+            }
+
+            let by_hex: HashMap<String, ValidatorId> = replay
+                .metrics
+                .keys()
+                .map(|id| (hex::encode(id), *id))
+                .collect();
+            let fairness_metrics = replay
+                .metrics
+                .iter()
+                .map(|(id, metrics)| (hex::encode(id), metrics.to_fairness_metrics()))
+                .collect();
+            let set = VerifierSet::select(
+                model,
+                &fairness_metrics,
+                replay.seed.clone(),
+                replay.round,
+                self.config.shadow_count + 1,
+            )
+            .map_err(|e| {
+                anyhow!(
+                    "verifier set selection failed in round {}: {e}",
+                    replay.round
+                )
+            })?;
+            let set_primary = by_hex[&set.primary];
+            for member in std::iter::once(&set.primary).chain(&set.shadows) {
+                let entry = stats.get_mut(&by_hex[member]).expect("known validator");
+                entry.verifier_set_member += 1;
+                if member == &set.primary {
+                    entry.verifier_set_primary += 1;
+                }
+            }
+
+            let selection = engine
+                .select_verifiers(
+                    replay.round,
+                    &replay.metrics,
+                    self.config.shadow_count,
+                    self.config.min_reputation,
+                )
+                .with_context(|| format!("weighted selection failed in round {}", replay.round))?;
+            let mut contributions = Vec::with_capacity(selection.shadows.len() + 1);
+            for (id, is_primary) in std::iter::once((selection.primary, true))
+                .chain(selection.shadows.iter().map(|id| (*id, false)))
+            {
+                let entry = stats.get_mut(&id).expect("known validator");
+                if is_primary {
+                    entry.weighted_primary += 1;
+                } else {
+                    entry.weighted_shadow += 1;
+                }
+                contributions.push(ValidatorContribution {
+                    validator_id: id,
+                    blocks_proposed: is_primary as u32,
+                    blocks_verified: !is_primary as u32,
+                    reputation_score: engine.calculate_reputation(&replay.metrics[&id]) as i64,
+                });
+            }
+            tracker
+                .process_round(first_round + offset as u64, &contributions, 0, 0)
+                .map_err(|e| anyhow!("emission replay failed in round {}: {e}", replay.round))?;
+
+            primaries.push((set_primary, selection.primary));
+        }
+
+        for (id, entry) in stats.iter_mut() {
+            entry.payout = tracker.validator_earnings.get(id).copied().unwrap_or(0);
+        }
+        let validators: Vec<ValidatorReplay> = stats.into_values().collect();
+        let payouts: Vec<u128> = validators.iter().map(|v| v.payout).collect();
+
+        Ok(ModelReplay {
+            model_hash: model
+                .raw_model()
+                .hash_hex()
+                .map_err(|e| anyhow!("failed to hash model: {e}"))?,
+            payout_gini: gini(&payouts),
+            stake_fairness: stake_fairness(&validators),
+            validators,
+            primaries,
+        })
+    }
+}
+
+/// Gini coefficient of `values` at [`REPLAY_SCALE`].
+pub fn gini(values: &[u128]) -> i64 {
+    let total: u128 = values.iter().sum();
+    if values.len() < 2 || total == 0 {
+        return 0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as i128;
+    // G = sum_i (2i - n - 1) * x_i / (n * sum x), with i 1-based over ascending x.
+    let weighted: i128 = sorted
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| (2 * (idx as i128 + 1) - n - 1) * value as i128)
+        .sum();
+    (weighted * REPLAY_SCALE as i128 / (n * total as i128)) as i64
+}
+
+/// How closely weighted primary selections follow stake shares.
+fn stake_fairness(validators: &[ValidatorReplay]) -> i64 {
+    let total_stake: u128 = validators.iter().map(|v| v.stake as u128).sum();
+    let total_primary: u128 = validators.iter().map(|v| v.weighted_primary as u128).sum();
+    if total_stake == 0 || total_primary == 0 {
+        return 0;
+    }
+
+    let scale = REPLAY_SCALE as i128;
+    let distance: i128 = validators
+        .iter()
+        .map(|v| {
+            let selected = v.weighted_primary as i128 * scale / total_primary as i128;
+            let staked = v.stake as i128 * scale / total_stake as i128;
+            (selected - staked).abs()
+        })
+        .sum();
+    (scale - distance / 2).max(0) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_ai_core::gbdt::{Model, Node, Tree};
+    use ippan_storage::{MemoryStorage, ValidatorTelemetry};
+    use ippan_types::{
+        Block, IppanTimeMicros, RoundCertificate, RoundFinalizationRecord, RoundWindow,
+    };
+
+    const SCALE: i64 = 1_000_000;
+
+    /// Scores every validator the same, so selection reduces to ID order.
+    fn flat_model() -> FairnessModel {
+        let tree = Tree::new(vec![Node::leaf(0, 5_000 * 100)], SCALE);
+        FairnessModel::from_d_gbdt_model(Model::new(vec![tree], 0))
+    }
+
+    /// Rewards stake: feature 5 is the stake weight (0-10000).
+    fn stake_model() -> FairnessModel {
+        let tree = Tree::new(
+            vec![
+                Node::internal(0, 5, 5 * 100, 1, 2),
+                Node::leaf(1, 1_000 * 100),
+                Node::leaf(2, 9_000 * 100),
+            ],
+            SCALE,
+        );
+        FairnessModel::from_d_gbdt_model(Model::new(vec![tree], 0))
+    }
+
+    fn rounds(count: u64) -> Vec<ReplayRound> {
+        let mut metrics = HashMap::new();
+        for (idx, stake) in [1_000_000u64, 20_000_000, 1_000_000]
+            .into_iter()
+            .enumerate()
+        {
+            let mut id = [0u8; 32];
+            id[0] = idx as u8 + 1;
+            metrics.insert(
+                id,
+                ValidatorMetrics {
+                    stake_amount: stake,
+                    rounds_active: 10,
+                    blocks_proposed: 10,
+                    ..ValidatorMetrics::default()
+                },
+            );
+        }
+        (1..=count)
+            .map(|round| ReplayRound {
+                round,
+                seed: format!("seed-{round}"),
+                metrics: metrics.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gini_bounds() {
+        assert_eq!(gini(&[5, 5, 5, 5]), 0);
+        assert_eq!(gini(&[0, 0, 0, 100]), 750_000);
+        assert_eq!(gini(&[]), 0);
+        assert_eq!(gini(&[0, 0]), 0);
+    }
+
+    #[test]
+    fn test_identical_models_report_no_change() {
+        let replay = CounterfactualReplay::new(flat_model(), flat_model(), ReplayConfig::default());
+        let report = replay.run(&rounds(20)).unwrap();
+
+        assert_eq!(report.rounds, 20);
+        assert_eq!((report.from_round, report.to_round), (1, 20));
+        assert_eq!(report.active, report.candidate);
+        assert_eq!(report.verifier_set_primary_changes, 0);
+        assert_eq!(report.weighted_primary_changes, 0);
+        assert_eq!(report.payout_gini_delta, 0);
+
+        let selected: u64 = report
+            .active
+            .validators
+            .iter()
+            .map(|v| v.weighted_primary)
+            .sum();
+        assert_eq!(selected, 20);
+    }
+
+    #[test]
+    fn test_candidate_favouring_stake_moves_selection() {
+        let replay =
+            CounterfactualReplay::new(flat_model(), stake_model(), ReplayConfig::default());
+        let report = replay.run(&rounds(50)).unwrap();
+
+        let big = |replay: &ModelReplay| {
+            replay
+                .validators
+                .iter()
+                .find(|v| v.validator_id.starts_with("02"))
+                .unwrap()
+                .clone()
+        };
+        assert!(big(&report.candidate).weighted_primary > big(&report.active).weighted_primary);
+        assert!(report.weighted_primary_changes > 0);
+        assert!(report.stake_fairness_delta > 0);
+        assert_ne!(report.active.model_hash, report.candidate.model_hash);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["rounds"], 50);
+        assert!(json["candidate"]["validators"].is_array());
+    }
+
+    #[test]
+    fn test_attach_to_proposal_metadata() {
+        let replay =
+            CounterfactualReplay::new(flat_model(), stake_model(), ReplayConfig::default());
+        let report = replay.run(&rounds(5)).unwrap();
+
+        let mut proposal = ippan_ai_registry::AiModelProposal {
+            proposal_id: "p1".to_string(),
+            model_id: "fairness".to_string(),
+            version: 2,
+            model_url: String::new(),
+            model_hash: [0u8; 32],
+            signature: [0u8; 64],
+            signer_pubkey: [0u8; 32],
+            activation_round: 100,
+            description: String::new(),
+            proposer: [0u8; 32],
+            created_at: 0,
+            metadata: HashMap::new(),
+        };
+        report.attach_to(&mut proposal).unwrap();
+
+        let stored: ReplayReport =
+            serde_json::from_str(&proposal.metadata[REPLAY_METADATA_KEY]).unwrap();
+        assert_eq!(stored.candidate.validators, report.candidate.validators);
+    }
+
+    #[test]
+    fn test_rounds_from_storage_use_prior_history() {
+        let storage = MemoryStorage::default();
+        let (alice, bob) = ([1u8; 32], [2u8; 32]);
+        for round in 1..=3u64 {
+            let block = Block::new(vec![], vec![], round, alice);
+            let block_ids = vec![block.hash()];
+            storage.store_block(block).unwrap();
+            storage
+                .store_round_finalization(RoundFinalizationRecord {
+                    round,
+                    window: RoundWindow {
+                        id: round,
+                        start_us: IppanTimeMicros(round * 100),
+                        end_us: IppanTimeMicros(round * 100 + 100),
+                    },
+                    ordered_tx_ids: vec![],
+                    fork_drops: vec![],
+                    state_root: [round as u8; 32],
+                    proof: RoundCertificate {
+                        round,
+                        block_ids,
+                        agg_sig: vec![],
+                    },
+                    total_fees_atomic: None,
+                    treasury_fees_atomic: None,
+                    applied_payments: None,
+                    rejected_payments: None,
+                })
+                .unwrap();
+        }
+        let telemetry = ValidatorTelemetry {
+            validator_id: bob,
+            blocks_proposed: 0,
+            blocks_verified: 0,
+            rounds_active: 0,
+            avg_latency_us: 20_000,
+            slash_count: 1,
+            stake: 7,
+            age_rounds: 0,
+            last_active_round: 0,
+            uptime_percentage_scaled: 9_000,
+            recent_performance_scaled: 10_000,
+            network_contribution_scaled: 10_000,
+        };
+        storage.store_validator_telemetry(&bob, &telemetry).unwrap();
+
+        let rounds = rounds_from_storage(&storage, None, None).unwrap();
+        assert_eq!(
+            rounds.iter().map(|r| r.round).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(!rounds[0].metrics.contains_key(&alice));
+        assert_eq!(rounds[2].metrics[&alice].blocks_proposed, 2);
+        assert_eq!(rounds[2].metrics[&bob].stake_amount, 7);
+        assert_eq!(rounds[2].metrics[&bob].uptime_percentage, 900_000);
+        assert_eq!(rounds[2].metrics.len(), 2);
+    }
+}
@@ -49,6 +49,44 @@ fn signed_transaction(from_seed: u8, to_seed: u8, amount_micro: u64, nonce: u64)
     tx
 }
 
+/// Finalize the tracker's current round with one empty block by `creator`.
+fn finalize_round_with_block(consensus: &PoAConsensus, creator: [u8; 32]) -> RoundId {
+    finalize_round_with_transactions(consensus, creator, Vec::new())
+}
+
+/// Finalize the tracker's current round with one block by `creator` carrying
+/// `transactions`.
+fn finalize_round_with_transactions(
+    consensus: &PoAConsensus,
+    creator: [u8; 32],
+    transactions: Vec<Transaction>,
+) -> RoundId {
+    let round = consensus.round_tracker.read().current_round;
+    let parents = consensus.round_tracker.read().previous_round_blocks.clone();
+    let block = Block::new(parents, transactions, round, creator);
+    consensus.storage.store_block(block.clone()).unwrap();
+    {
+        let mut tracker = consensus.round_tracker.write();
+        tracker.current_round_blocks.push(block.header.id);
+        tracker.round_start = Instant::now() - consensus.finalization_interval;
+    }
+    PoAConsensus::finalize_round_if_ready(
+        &consensus.storage,
+        &consensus.round_tracker,
+        consensus.finalization_interval,
+        &consensus.config,
+        &consensus.fee_collector,
+        &consensus.payment_engine,
+        &consensus.handle_pipeline,
+        &consensus.file_anchor_pipeline,
+        &consensus.pinning_pipeline,
+        &consensus.model_approvals,
+        &consensus.metrics,
+    )
+    .unwrap();
+    round
+}
+
 #[tokio::test]
 async fn test_consensus_creation() {
     let config = create_test_config();
@@ -221,6 +259,114 @@ async fn test_consensus_with_ai_reputation() {
     assert_eq!(state.validator_count, 2);
 }
 
+#[tokio::test]
+async fn test_activation_switches_drift_reference() {
+    use ippan_consensus_dlc::dgbdt::FairnessModel;
+    use ippan_consensus_dlc::fairness_drift::DistributionSummary;
+    use ippan_governance::ai_models::ModelRegistryEntry;
+
+    let mut config = create_test_config();
+    config.enable_ai_reputation = true;
+    let consensus = PoAConsensus::new(config, Arc::new(MemoryStorage::default()), [1u8; 32]);
+    *consensus.model_rollout.write() = ModelRollout::new(RolloutConfig {
+        min_shadow_rounds: 4,
+        max_mean_divergence: 10_000,
+    });
+
+    let model = FairnessModel::testing_stub();
+    let hash = model.raw_model().hash_hex().unwrap();
+    let rows = vec![vec![5_000; FairnessModel::FEATURE_NAMES.len()]; 20];
+    consensus
+        .input_drift
+        .write()
+        .set_manifest_references(HashMap::from([(
+            hash.clone(),
+            DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &rows, 10),
+        )]))
+        .unwrap();
+
+    let mut model_hash = [0u8; 32];
+    hex::decode_to_slice(&hash, &mut model_hash).unwrap();
+    let entry = ModelRegistryEntry::new(
+        "fairness".into(),
+        model_hash,
+        1,
+        5,
+        [0u8; 64],
+        0,
+        String::new(),
+    );
+    consensus.stage_fairness_model(&entry, model).unwrap();
+
+    *consensus.current_slot.write() = 4;
+    consensus.get_state();
+    assert!(!consensus.input_drift.read().is_active());
+
+    // Activation waits until the rounds before it are finalized
+    for creator in [[1u8; 32], [2u8; 32], [1u8; 32]] {
+        finalize_round_with_block(&consensus, creator);
+    }
+    *consensus.current_slot.write() = 5;
+    consensus.get_state();
+    assert!(!consensus.input_drift.read().is_active());
+    assert_eq!(finalize_round_with_block(&consensus, [2u8; 32]), 4);
+
+    consensus.get_state();
+    assert!(consensus.input_drift.read().is_active());
+    assert_eq!(
+        consensus.model_rollout.read().active_hash(),
+        Some(hash.as_str())
+    );
+    assert_eq!(
+        consensus.get_metrics().get_feature_drift_samples()["uptime"],
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_model_keeps_active_engine() {
+    use ippan_consensus_dlc::dgbdt::FairnessModel;
+    use ippan_governance::ai_models::ModelRegistryEntry;
+
+    let mut config = create_test_config();
+    config.enable_ai_reputation = true;
+    let consensus = PoAConsensus::new(config, Arc::new(MemoryStorage::default()), [1u8; 32]);
+    *consensus.model_rollout.write() = ModelRollout::new(RolloutConfig {
+        min_shadow_rounds: 3,
+        max_mean_divergence: 0,
+    });
+
+    let model = FairnessModel::testing_stub();
+    let mut model_hash = [0u8; 32];
+    hex::decode_to_slice(model.raw_model().hash_hex().unwrap(), &mut model_hash).unwrap();
+    let entry = ModelRegistryEntry::new(
+        "fairness".into(),
+        model_hash,
+        1,
+        4,
+        [0u8; 64],
+        0,
+        String::new(),
+    );
+    consensus.stage_fairness_model(&entry, model).unwrap();
+    for creator in [[1u8; 32], [2u8; 32], [1u8; 32]] {
+        finalize_round_with_block(&consensus, creator);
+    }
+
+    *consensus.current_slot.write() = 4;
+    consensus.get_state();
+    assert!(matches!(
+        consensus.model_rollout.read().status(),
+        RolloutStatus::Rejected {
+            active_hash: None,
+            ..
+        }
+    ));
+    assert!(consensus.dgbdt_engine.read().fairness_model().is_none());
+    assert_eq!(consensus.model_rollout.read().active_hash(), None);
+    assert_eq!(consensus.get_metrics().get_model_rollout_rejections(), 1);
+}
+
 #[tokio::test]
 async fn test_fee_validation() {
     let config = create_test_config();
@@ -294,6 +440,9 @@ async fn test_round_finalization() {
         &consensus.fee_collector,
         &consensus.payment_engine,
         &consensus.handle_pipeline,
+        &consensus.file_anchor_pipeline,
+        &consensus.pinning_pipeline,
+        &consensus.model_approvals,
         &consensus.metrics,
     )
     .unwrap();
@@ -387,3 +536,279 @@ async fn submit_channel_stays_open_while_consensus_runs() {
 
     consensus.stop().await.expect("stop consensus");
 }
+
+#[cfg(feature = "ai_l1")]
+#[tokio::test]
+async fn test_published_model_is_approved_fetched_and_staged() {
+    use ed25519_dalek::Signer;
+    use ippan_ai_core::types::{ModelId, ModelMetadata};
+    use ippan_ai_registry::{
+        artifact_url, publish_artifact, ArtifactSource, ModelArtifactCache, ModelFetcher,
+        ModelManifestEntry,
+    };
+    use ippan_consensus_dlc::dgbdt::FairnessModel;
+    use ippan_files::{ContentStore, FileDescriptor, FileId};
+    use ippan_types::{ModelGovernanceOp, ModelProposalOp, ModelVoteOp};
+
+    struct PublishedArtifact {
+        store: ContentStore,
+        descriptor: FileDescriptor,
+    }
+
+    #[async_trait::async_trait]
+    impl ArtifactSource for PublishedArtifact {
+        async fn fetch(&self, id: &FileId) -> anyhow::Result<(FileDescriptor, Vec<u8>)> {
+            anyhow::ensure!(*id == self.descriptor.id, "unknown descriptor");
+            let bytes = self.store.read(&self.descriptor.content_hash)?;
+            Ok((self.descriptor.clone(), bytes))
+        }
+    }
+
+    async fn wait_for_shadow(consensus: &PoAConsensus, activation_round: RoundId) {
+        for _ in 0..100 {
+            let status = consensus.model_rollout.read().status().clone();
+            if let model_rollout::RolloutStatus::Shadow {
+                activation_round: round,
+                ..
+            } = status
+            {
+                assert_eq!(round, activation_round);
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("approved model was not staged");
+    }
+
+    fn governance_tx(from: [u8; 32], nonce: u64, op: ModelGovernanceOp) -> Transaction {
+        let mut tx = Transaction::new(from, [0u8; 32], ippan_types::Amount::zero(), nonce);
+        tx.set_model_governance(op);
+        tx
+    }
+
+    // Publish the model the way the trainer output is published
+    let model_dir = tempfile::tempdir().unwrap();
+    let model_path = model_dir.path().join("fairness.json");
+    let model = FairnessModel::testing_stub();
+    std::fs::write(&model_path, model.raw_model().to_canonical_json().unwrap()).unwrap();
+    let metadata = ModelMetadata {
+        id: ModelId::new("fairness", "2", ""),
+        name: "fairness".into(),
+        version: "2".into(),
+        description: String::new(),
+        author: String::new(),
+        license: String::new(),
+        tags: Vec::new(),
+        created_at: 0,
+        updated_at: 0,
+        architecture: "gbdt".into(),
+        input_shape: Vec::new(),
+        output_shape: Vec::new(),
+        size_bytes: 0,
+        parameter_count: 0,
+    };
+    let entry = ModelManifestEntry::from_gbdt_model(
+        metadata,
+        model_path.clone(),
+        model_dir.path().to_path_buf(),
+        "seed",
+    )
+    .unwrap();
+    let bytes = std::fs::read(&model_path).unwrap();
+    let store = ContentStore::in_memory();
+    let descriptor = publish_artifact(&entry, &bytes, &store, &[9u8; 32]).unwrap();
+    let model_hash = *descriptor.content_hash.as_bytes();
+    assert_eq!(
+        hex::encode(model_hash),
+        model.raw_model().hash_hex().unwrap()
+    );
+    let source = Arc::new(PublishedArtifact {
+        store,
+        descriptor: descriptor.clone(),
+    });
+
+    let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::default());
+    let cache_dir = tempfile::tempdir().unwrap();
+    let node = |storage: Arc<dyn Storage + Send + Sync>| {
+        let mut consensus = PoAConsensus::new(create_test_config(), storage, [1u8; 32]);
+        consensus.model_fetcher = Some(Arc::new(ModelFetcher::new(
+            source.clone(),
+            ModelArtifactCache::open(cache_dir.path()).unwrap(),
+        )));
+        consensus
+    };
+
+    let consensus = node(storage.clone());
+    consensus.spawn_model_staging().unwrap();
+    assert!(consensus.spawn_model_staging().is_err());
+
+    let signer = SigningKey::from_bytes(&[7u8; 32]);
+    let propose = governance_tx(
+        [5u8; 32],
+        1,
+        ModelGovernanceOp::Propose(ModelProposalOp {
+            model_id: "fairness".into(),
+            version: 2,
+            model_url: artifact_url(&descriptor.id),
+            model_hash,
+            signature: signer.sign(&model_hash).to_bytes().to_vec(),
+            signer_pubkey: signer.verifying_key().to_bytes(),
+            activation_round: 1_000,
+            description: "Fairness model".into(),
+        }),
+    );
+    finalize_round_with_transactions(&consensus, [1u8; 32], vec![propose]);
+    assert_eq!(
+        consensus
+            .model_approvals
+            .governance()
+            .active_proposal_count(),
+        1
+    );
+
+    let vote = |voter: u8| {
+        governance_tx(
+            [voter; 32],
+            1,
+            ModelGovernanceOp::Vote(ModelVoteOp {
+                model_id: "fairness".into(),
+                approve: true,
+            }),
+        )
+    };
+    // One validator holds two thirds of the stake minus rounding: not enough
+    finalize_round_with_transactions(&consensus, [1u8; 32], vec![vote(2)]);
+    assert!(!consensus
+        .model_approvals
+        .governance()
+        .is_model_registered("fairness"));
+    let approval_round = finalize_round_with_transactions(&consensus, [1u8; 32], vec![vote(1)]);
+
+    let approved = consensus
+        .model_approvals
+        .governance()
+        .get_registered_model("fairness")
+        .cloned()
+        .unwrap();
+    assert_eq!(approved.registered_at_round, approval_round);
+    assert_eq!(approved.model_hash, model_hash);
+    wait_for_shadow(&consensus, 1_000).await;
+    assert!(cache_dir
+        .path()
+        .join(format!("{}.json", descriptor.content_hash.to_hex()))
+        .exists());
+
+    // A restarted node stages the approval it restores from storage
+    let restarted = node(storage);
+    assert!(restarted
+        .model_approvals
+        .governance()
+        .is_model_registered("fairness"));
+    restarted.spawn_model_staging().unwrap();
+    wait_for_shadow(&restarted, 1_000).await;
+}
+
+#[tokio::test]
+async fn test_pinning_epochs_settle_into_account_balances() {
+    use ippan_files::{
+        ContentStore, FileId, LocalProviderSet, PinDealRequest, PinDealStatus, PinningConfig,
+        PinningMarket,
+    };
+    use ippan_storage::Account;
+
+    const CLIENT: [u8; 32] = [11u8; 32];
+    const HONEST: [u8; 32] = [12u8; 32];
+    const FLAKY: [u8; 32] = [13u8; 32];
+
+    let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+    for address in [CLIENT, HONEST, FLAKY] {
+        storage
+            .update_account(Account {
+                address,
+                balance: 10_000,
+                nonce: 0,
+            })
+            .unwrap();
+    }
+    let balance = |address: &[u8; 32]| {
+        storage
+            .get_account(address)
+            .unwrap()
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    };
+    let market_config = PinningConfig {
+        epoch_rounds: 5,
+        challenges_per_epoch: 2,
+        slash_bps: 2_500,
+        max_missed_epochs: 2,
+    };
+    let consensus = PoAConsensus::new(create_test_config(), storage.clone(), [1u8; 32])
+        .with_pinning_market(Arc::new(PinningMarket::new(market_config.clone())));
+
+    let data: Vec<u8> = (0..300u32).map(|i| (i * 5 % 256) as u8).collect();
+    let mut providers = LocalProviderSet::new();
+    let mut manifest = None;
+    for provider in [HONEST, FLAKY] {
+        let store = ContentStore::in_memory().with_chunk_size(32);
+        manifest = Some(store.put(&data).unwrap());
+        providers.add_provider(provider, store);
+    }
+    let manifest = manifest.unwrap();
+    let request = |provider| PinDealRequest {
+        file_id: FileId::from_bytes([9u8; 32]),
+        content_root: manifest.root,
+        chunk_count: manifest.chunk_count() as u64,
+        client: CLIENT,
+        provider,
+        price_per_epoch: 200,
+        epochs: 2,
+        collateral: 1_000,
+    };
+    let honest = consensus.open_pinning_deal(request(HONEST)).unwrap();
+    let flaky = consensus.open_pinning_deal(request(FLAKY)).unwrap();
+    assert_eq!(balance(&CLIENT), 10_000 - 800);
+    assert_eq!(balance(&pinning::PINNING_ESCROW_ACCOUNT), 2_800);
+
+    // Deals survive a restart before their first epoch is settled.
+    let restarted = PoAConsensus::new(create_test_config(), storage.clone(), [1u8; 32])
+        .with_pinning_market(Arc::new(PinningMarket::new(market_config)));
+    assert_eq!(restarted.pinning_pipeline.market().active_count(), 2);
+
+    let market = consensus.pinning_pipeline.market();
+    let mut answered = Vec::new();
+    for _ in 0..20 {
+        finalize_round_with_block(&consensus, [1u8; 32]);
+
+        for deal in [&honest, &flaky] {
+            let Some(challenge) = market.challenge(&deal.id) else {
+                continue;
+            };
+            if answered.contains(&(challenge.deal_id, challenge.epoch)) {
+                continue;
+            }
+            answered.push((challenge.deal_id, challenge.epoch));
+            // The flaky provider only answers its first challenge.
+            if challenge.provider == FLAKY && challenge.epoch > flaky.first_epoch {
+                continue;
+            }
+            let proof = providers.respond(&challenge).unwrap();
+            consensus.submit_pinning_proof(&proof).unwrap();
+        }
+    }
+
+    assert_eq!(
+        market.deal(&honest.id).unwrap().status,
+        PinDealStatus::Completed
+    );
+    let flaky = market.deal(&flaky.id).unwrap();
+    assert_eq!(flaky.status, PinDealStatus::Completed);
+    assert_eq!((flaky.epochs_proven, flaky.epochs_missed), (1, 1));
+
+    // Two epochs paid to the honest provider; one epoch paid to the flaky one
+    // and a quarter of its collateral slashed to the client.
+    assert_eq!(balance(&HONEST), 10_000 + 400);
+    assert_eq!(balance(&FLAKY), 10_000 + 200 - 250);
+    assert_eq!(balance(&CLIENT), 10_000 - 600 + 250);
+    assert_eq!(balance(&pinning::PINNING_ESCROW_ACCOUNT), 0);
+}
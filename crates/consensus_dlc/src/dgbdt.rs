@@ -5,7 +5,7 @@
 
 use crate::error::{DlcError, Result};
 use ippan_ai_core::fairness::{DeterministicFairnessModel, ValidatorFeatureVector};
-use ippan_ai_core::gbdt::{Model as DgbdtModel, SCALE as DGBDT_SCALE};
+use ippan_ai_core::gbdt::{Explanation, Model as DgbdtModel, SCALE as DGBDT_SCALE};
 use ippan_ai_registry::d_gbdt::DGBDTRegistry;
 use ippan_types::currency::denominations;
 use ippan_types::Amount;
@@ -150,6 +150,12 @@ pub struct FairnessModel {
 }
 
 impl FairnessModel {
+    /// Feature names, in the order used by [`Explanation::contributions`]
+    pub const FEATURE_NAMES: [&'static str; 6] = ValidatorFeatureVector::FEATURE_NAMES;
+
+    /// Divisor from raw model output to [`Self::score_deterministic`] units
+    pub const SCORE_SCALE: i64 = DeterministicFairnessModel::FEATURE_SCALE;
+
     /// Load a D-GBDT model from a file path
     pub fn from_d_gbdt_file(path: &Path) -> Result<Self> {
         use ippan_ai_registry::d_gbdt::load_model_from_path;
@@ -244,6 +250,13 @@ impl FairnessModel {
         self.model.score(&normalized)
     }
 
+    /// Per-feature breakdown of the raw model output behind
+    /// [`Self::score_deterministic`], in raw model units.
+    pub fn explain_deterministic(&self, metrics: &ValidatorMetrics) -> Explanation {
+        let normalized = ValidatorFeatureVector::from(metrics.to_normalized());
+        self.model.explain(&normalized)
+    }
+
     /// Validate model integrity
     pub fn validate(&self) -> Result<()> {
         self.model
@@ -435,6 +448,30 @@ mod tests {
         assert_eq!(score1, score2);
     }
 
+    #[test]
+    fn test_explanation_matches_deterministic_score() {
+        let model = FairnessModel::testing_stub();
+        for uptime in [0, 4000, 9900] {
+            let metrics = ValidatorMetrics::new(
+                uptime,
+                1000,
+                10000,
+                100,
+                500,
+                Amount::from_micro_ipn(10_000_000),
+                100,
+            );
+            let explanation = model.explain_deterministic(&metrics);
+            let raw = explanation.base + explanation.contributions.iter().sum::<i64>();
+
+            assert_eq!(raw, explanation.score);
+            assert_eq!(
+                (raw / FairnessModel::SCORE_SCALE).clamp(0, 10_000),
+                model.score_deterministic(&metrics)
+            );
+        }
+    }
+
     #[test]
     fn test_load_from_registry_path() {
         use ippan_ai_core::gbdt::{Node as DNode, Tree as DTree, SCALE};
@@ -0,0 +1,150 @@
+//! Input drift monitoring for the fairness model
+//!
+//! Binds a [`DriftMonitor`] to the normalized features a [`FairnessModel`]
+//! actually scores, so live validator telemetry can be compared with the
+//! training distribution recorded in the model manifest. The reference
+//! summary must cover exactly [`FairnessModel::FEATURE_NAMES`], on the same
+//! 0-10000 scale; a summary over other features is rejected rather than
+//! silently never reporting drift.
+
+use crate::dgbdt::{FairnessModel, ValidatorMetrics};
+use ippan_ai_core::fairness::ValidatorFeatureVector;
+use ippan_ai_core::{AiCoreError, AiCoreResult};
+
+pub use ippan_ai_core::drift::{
+    DistributionSummary, DriftConfig, DriftMonitor, DriftReport, FeatureDrift,
+};
+pub use ippan_ai_core::monitoring::{Alert, AlertSeverity};
+
+/// Features scored for `metrics` (0-10000), in [`FairnessModel::FEATURE_NAMES`] order.
+pub fn fairness_features(metrics: &ValidatorMetrics) -> [i64; 6] {
+    ValidatorFeatureVector::from(metrics.to_normalized())
+        .clamped()
+        .as_array()
+}
+
+/// Check that `reference` summarises exactly the fairness features, by
+/// [`FairnessModel::FEATURE_NAMES`] and on the 0-10000 scale.
+pub fn validate_reference(reference: &DistributionSummary) -> AiCoreResult<()> {
+    reference.require_features(&FairnessModel::FEATURE_NAMES)?;
+    if let Some(dist) = reference
+        .features
+        .iter()
+        .find(|dist| dist.min < 0 || dist.max > 10_000)
+    {
+        return Err(AiCoreError::InvalidParameters(format!(
+            "training distribution of `{}` spans {}..={}, outside the 0-10000 feature scale",
+            dist.feature, dist.min, dist.max
+        )));
+    }
+    Ok(())
+}
+
+/// Drift monitor over fairness model inputs.
+#[derive(Debug, Clone)]
+pub struct FairnessDriftMonitor {
+    monitor: DriftMonitor,
+}
+
+impl FairnessDriftMonitor {
+    /// Monitor against `reference`, which must pass [`validate_reference`].
+    pub fn new(reference: DistributionSummary, config: DriftConfig) -> AiCoreResult<Self> {
+        validate_reference(&reference)?;
+        Ok(Self {
+            monitor: DriftMonitor::new(reference, config),
+        })
+    }
+
+    /// Record the features of one validator.
+    pub fn observe(&mut self, metrics: &ValidatorMetrics) {
+        self.monitor
+            .observe_row(&FairnessModel::FEATURE_NAMES, &fairness_features(metrics))
+            .expect("reference covers every fairness feature");
+    }
+
+    pub fn report(&self) -> DriftReport {
+        self.monitor.report()
+    }
+
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.monitor.alerts()
+    }
+
+    pub fn monitor(&self) -> &DriftMonitor {
+        &self.monitor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_types::Amount;
+
+    fn metrics(uptime: i64, latency: i64) -> ValidatorMetrics {
+        ValidatorMetrics {
+            uptime,
+            latency,
+            honesty: 10_000,
+            blocks_proposed: 50,
+            blocks_verified: 100,
+            stake: Amount::from_micro_ipn(5_000_000_000),
+            rounds_active: 100,
+        }
+    }
+
+    #[test]
+    fn test_monitor_tracks_scored_features() {
+        let training: Vec<Vec<i64>> = (0..200)
+            .map(|i| fairness_features(&metrics(9_000 + i * 5, 500 + i)).to_vec())
+            .collect();
+        let reference =
+            DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &training, 10);
+        let config = DriftConfig {
+            min_samples: 50,
+            ..DriftConfig::default()
+        };
+        let mut monitor = FairnessDriftMonitor::new(reference, config).unwrap();
+
+        // Uptime collapses while latency stays in its training range.
+        for i in 0..100 {
+            monitor.observe(&metrics(4_000 + i * 10, 500 + i * 2));
+        }
+
+        let report = monitor.report();
+        assert_eq!(report.features.len(), FairnessModel::FEATURE_NAMES.len());
+        assert_eq!(report.features[0].feature, "uptime");
+        assert!(report.features[0].psi.unwrap() > 250_000);
+        assert!(report.features[1].psi.unwrap() < 100_000);
+
+        let alerts = monitor.alerts();
+        assert!(alerts
+            .iter()
+            .any(|alert| alert.metric_name == "feature_psi.uptime"));
+        assert!(!alerts
+            .iter()
+            .any(|alert| alert.metric_name == "feature_psi.latency_inverse"));
+    }
+
+    #[test]
+    fn test_rejects_reference_over_other_features() {
+        // Trainer dataset columns are not the scored feature vector
+        let rows = vec![vec![990_000, 1_500, 1_000, 0, 5_000_000_000]; 20];
+        let reference = DistributionSummary::from_rows(
+            &[
+                "uptime_micros",
+                "latency_micros",
+                "votes_cast",
+                "votes_missed",
+                "stake_atomic",
+            ],
+            &rows,
+            10,
+        );
+        assert!(FairnessDriftMonitor::new(reference, DriftConfig::default()).is_err());
+
+        // Right names, but exported at the 1e6 dataset scale
+        let rows = vec![vec![990_000; FairnessModel::FEATURE_NAMES.len()]; 20];
+        let reference = DistributionSummary::from_rows(&FairnessModel::FEATURE_NAMES, &rows, 10);
+        assert!(FairnessDriftMonitor::new(reference, DriftConfig::default()).is_err());
+    }
+}
@@ -17,6 +17,17 @@ use ippan_types::Amount;
 /// Fixed-point scale factor (1e6)
 pub const SCALE: i64 = 1_000_000;
 
+/// Feature names in the order returned by [`features_for_validator`]
+pub const FEATURE_NAMES: [&str; 7] = [
+    "uptime_ratio_7d",
+    "validated_blocks_7d",
+    "missed_blocks_7d",
+    "avg_latency_ms",
+    "slashing_events_90d",
+    "stake_normalized",
+    "peer_reports_quality",
+];
+
 /// Extract 7 features for the fairness model v1
 ///
 /// Features are in the order expected by the model:
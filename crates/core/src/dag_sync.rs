@@ -5,20 +5,30 @@
 //! republishes full blocks so freshly discovered peers can request the data
 //! immediately after joining. All block payloads are accompanied by zk-STARK
 //! proof metadata verifying their authenticity, as required by the IPPAN PRD.
-
-use std::collections::HashSet;
+//!
+//! Tips announced by peers that are not yet in the local DAG are fetched by
+//! hash over the `/ippan/dag-sync/1.0.0` request-response protocol, walking
+//! back through missing parents. The same protocol backs [`DagSyncHandle`],
+//! the [`BlockFetcher`] that lets [`SyncManager`](crate::SyncManager) pull
+//! blocks over libp2p.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
 use ed25519_dalek::SigningKey;
 use either::Either;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use futures::StreamExt;
 use libp2p::core::transport::{upgrade, PortUse};
 use libp2p::core::Endpoint;
 use libp2p::gossipsub;
 use libp2p::identity;
 use libp2p::noise;
+use libp2p::request_response::{self, OutboundRequestId, ProtocolSupport};
 use libp2p::swarm::ConnectionHandler;
 use libp2p::swarm::{
     self, ConnectionDenied, ConnectionHandlerSelect, ConnectionId, FromSwarm, NetworkBehaviour,
@@ -30,10 +40,12 @@ use libp2p::{gossipsub as gsub, mdns};
 use libp2p::{Multiaddr, PeerId, Swarm, Transport};
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
 use tokio::time::interval;
 
 use crate::block::Block;
 use crate::dag::BlockDAG;
+use crate::sync_manager::BlockFetcher;
 use crate::zk_stark::{
     deserialize_proof, generate_stark_proof, serialize_proof, verify_stark_proof,
 };
@@ -42,6 +54,12 @@ use crate::zk_stark::{
 const DAG_TOPIC: &str = "ippan-dag";
 /// Interval between periodic tip advertisements.
 const TIP_INTERVAL: Duration = Duration::from_secs(8);
+/// Request-response protocol used to fetch blocks by hash.
+pub const DAG_SYNC_PROTOCOL_NAME: &str = "/ippan/dag-sync/1.0.0";
+/// Upper bound on an encoded request or response.
+const MAX_SYNC_MESSAGE_BYTES: u64 = 8 * 1024 * 1024;
+/// Timeout for a single block fetch.
+const SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
 
 /// Messages distributed across the DAG gossip topic.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -57,12 +75,190 @@ pub enum GossipMsg {
     },
 }
 
+/// Requests served over [`DAG_SYNC_PROTOCOL_NAME`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DagSyncRequest {
+    /// Ask for the peer's current DAG tips.
+    Tips,
+    /// Ask for a single block by hash.
+    Block([u8; 32]),
+}
+
+/// Responses served over [`DAG_SYNC_PROTOCOL_NAME`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DagSyncResponse {
+    Tips(Vec<[u8; 32]>),
+    /// `None` when the peer does not hold the block.
+    Block(Option<Box<Block>>),
+}
+
+/// Protocol marker for the DAG sync request-response behaviour.
+#[derive(Debug, Clone, Default)]
+pub struct DagSyncProtocol;
+
+impl AsRef<str> for DagSyncProtocol {
+    fn as_ref(&self) -> &str {
+        DAG_SYNC_PROTOCOL_NAME
+    }
+}
+
+/// JSON codec for [`DagSyncRequest`] / [`DagSyncResponse`].
+#[derive(Debug, Clone, Default)]
+pub struct DagSyncCodec;
+
+async fn read_json<T, M>(io: &mut T) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: for<'de> Deserialize<'de>,
+{
+    let mut buf = Vec::new();
+    io.take(MAX_SYNC_MESSAGE_BYTES + 1)
+        .read_to_end(&mut buf)
+        .await?;
+    if buf.len() as u64 > MAX_SYNC_MESSAGE_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "DAG sync message too large",
+        ));
+    }
+    serde_json::from_slice(&buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+async fn write_json<T, M>(io: &mut T, message: &M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Serialize,
+{
+    let payload = serde_json::to_vec(message)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    io.write_all(&payload).await?;
+    io.close().await
+}
+
+#[async_trait]
+impl request_response::Codec for DagSyncCodec {
+    type Protocol = DagSyncProtocol;
+    type Request = DagSyncRequest;
+    type Response = DagSyncResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io).await
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        request: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &request).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        response: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &response).await
+    }
+}
+
+/// Answer a DAG sync request from the local DAG.
+pub fn serve_dag_sync_request(dag: &BlockDAG, request: &DagSyncRequest) -> Result<DagSyncResponse> {
+    Ok(match request {
+        DagSyncRequest::Tips => DagSyncResponse::Tips(dag.get_tips()?),
+        DagSyncRequest::Block(hash) => DagSyncResponse::Block(dag.get_block(hash)?.map(Box::new)),
+    })
+}
+
+type SyncReply = oneshot::Sender<Result<DagSyncResponse>>;
+
+/// Request issued through a [`DagSyncHandle`].
+struct SyncCommand {
+    peer: PeerId,
+    request: DagSyncRequest,
+    respond_to: SyncReply,
+}
+
+/// Handle to a running [`DagSyncService`], used to fetch from its peers.
+#[derive(Clone)]
+pub struct DagSyncHandle {
+    commands: mpsc::UnboundedSender<SyncCommand>,
+}
+
+impl DagSyncHandle {
+    /// Send `request` to `peer` and await the response.
+    pub async fn request(&self, peer: &str, request: DagSyncRequest) -> Result<DagSyncResponse> {
+        let peer: PeerId = peer
+            .parse()
+            .map_err(|err| anyhow!("invalid peer id {peer}: {err}"))?;
+        let (respond_to, response) = oneshot::channel();
+        self.commands
+            .send(SyncCommand {
+                peer,
+                request,
+                respond_to,
+            })
+            .map_err(|_| anyhow!("DAG sync service stopped"))?;
+        response
+            .await
+            .map_err(|_| anyhow!("DAG sync service dropped the request"))?
+    }
+}
+
+#[async_trait]
+impl BlockFetcher for DagSyncHandle {
+    async fn fetch_tips(&self, peer_id: &str) -> Result<Vec<[u8; 32]>> {
+        match self.request(peer_id, DagSyncRequest::Tips).await? {
+            DagSyncResponse::Tips(tips) => Ok(tips),
+            other => Err(anyhow!("unexpected response to tips request: {other:?}")),
+        }
+    }
+
+    async fn fetch_block(&self, peer_id: &str, hash: [u8; 32]) -> Result<Option<Block>> {
+        match self.request(peer_id, DagSyncRequest::Block(hash)).await? {
+            DagSyncResponse::Block(block) => Ok(block.map(|block| *block)),
+            other => Err(anyhow!("unexpected response to block request: {other:?}")),
+        }
+    }
+}
+
+/// Who is waiting on an outbound DAG sync request.
+enum PendingFetch {
+    /// A tip or parent fetched on behalf of the gossip loop.
+    Gossip,
+    /// A request issued through a [`DagSyncHandle`].
+    Handle(SyncReply),
+}
+
 /// Events emitted by the combined DAG sync behaviour.
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 enum DagEvent {
     Gossip(Box<gsub::Event>),
     Mdns(mdns::Event),
+    Sync(request_response::Event<DagSyncRequest, DagSyncResponse>),
 }
 
 impl From<gsub::Event> for DagEvent {
@@ -77,15 +273,25 @@ impl From<mdns::Event> for DagEvent {
     }
 }
 
-/// Combined network behaviour for DAG gossip + mDNS peer discovery.
+impl From<request_response::Event<DagSyncRequest, DagSyncResponse>> for DagEvent {
+    fn from(event: request_response::Event<DagSyncRequest, DagSyncResponse>) -> Self {
+        DagEvent::Sync(event)
+    }
+}
+
+/// Combined network behaviour for DAG gossip, mDNS peer discovery and
+/// block fetching.
 struct DagBehaviour {
     pub gossip: gsub::Behaviour,
     pub mdns: mdns::tokio::Behaviour,
+    pub sync: request_response::Behaviour<DagSyncCodec>,
 }
 
 impl NetworkBehaviour for DagBehaviour {
-    type ConnectionHandler =
-        ConnectionHandlerSelect<THandler<gsub::Behaviour>, THandler<mdns::tokio::Behaviour>>;
+    type ConnectionHandler = ConnectionHandlerSelect<
+        ConnectionHandlerSelect<THandler<gsub::Behaviour>, THandler<mdns::tokio::Behaviour>>,
+        THandler<request_response::Behaviour<DagSyncCodec>>,
+    >;
     type ToSwarm = DagEvent;
 
     fn handle_pending_inbound_connection(
@@ -98,6 +304,8 @@ impl NetworkBehaviour for DagBehaviour {
             .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
         self.mdns
             .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
+        self.sync
+            .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
         Ok(())
     }
 
@@ -120,7 +328,13 @@ impl NetworkBehaviour for DagBehaviour {
             local_addr,
             remote_addr,
         )?;
-        Ok(gossip_handler.select(mdns_handler))
+        let sync_handler = self.sync.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )?;
+        Ok(gossip_handler.select(mdns_handler).select(sync_handler))
     }
 
     fn handle_pending_outbound_connection(
@@ -142,6 +356,12 @@ impl NetworkBehaviour for DagBehaviour {
             addresses,
             effective_role,
         )?);
+        combined.extend(self.sync.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )?);
         Ok(combined)
     }
 
@@ -167,12 +387,20 @@ impl NetworkBehaviour for DagBehaviour {
             role_override,
             port_use,
         )?;
-        Ok(gossip_handler.select(mdns_handler))
+        let sync_handler = self.sync.handle_established_outbound_connection(
+            connection_id,
+            peer,
+            addr,
+            role_override,
+            port_use,
+        )?;
+        Ok(gossip_handler.select(mdns_handler).select(sync_handler))
     }
 
     fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
         self.gossip.on_swarm_event(event);
         self.mdns.on_swarm_event(event);
+        self.sync.on_swarm_event(event);
     }
 
     fn on_connection_handler_event(
@@ -182,14 +410,18 @@ impl NetworkBehaviour for DagBehaviour {
         event: THandlerOutEvent<Self>,
     ) {
         match event {
-            Either::Left(event) => {
+            Either::Left(Either::Left(event)) => {
                 self.gossip
                     .on_connection_handler_event(peer_id, connection_id, event)
             }
-            Either::Right(event) => {
+            Either::Left(Either::Right(event)) => {
                 self.mdns
                     .on_connection_handler_event(peer_id, connection_id, event)
             }
+            Either::Right(event) => {
+                self.sync
+                    .on_connection_handler_event(peer_id, connection_id, event)
+            }
         }
     }
 
@@ -198,10 +430,22 @@ impl NetworkBehaviour for DagBehaviour {
         cx: &mut Context<'_>,
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
         if let Poll::Ready(action) = self.gossip.poll(cx) {
-            return Poll::Ready(action.map_out(DagEvent::from).map_in(Either::Left));
+            return Poll::Ready(
+                action
+                    .map_out(DagEvent::from)
+                    .map_in(|event| Either::Left(Either::Left(event))),
+            );
         }
 
         if let Poll::Ready(action) = self.mdns.poll(cx) {
+            return Poll::Ready(
+                action
+                    .map_out(DagEvent::from)
+                    .map_in(|event| Either::Left(Either::Right(event))),
+            );
+        }
+
+        if let Poll::Ready(action) = self.sync.poll(cx) {
             return Poll::Ready(action.map_out(DagEvent::from).map_in(Either::Right));
         }
 
@@ -215,6 +459,28 @@ pub struct DagSyncService;
 impl DagSyncService {
     /// Start the DAG synchronization service and run until the task is cancelled.
     pub async fn start(listen_addr: &str, signing_key: SigningKey, dag: BlockDAG) -> Result<()> {
+        let (_commands, receiver) = mpsc::unbounded_channel();
+        Self::run(listen_addr, signing_key, dag, receiver).await
+    }
+
+    /// Spawn the service and return a handle for fetching from its peers.
+    pub fn spawn(listen_addr: &str, signing_key: SigningKey, dag: BlockDAG) -> DagSyncHandle {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let addr = listen_addr.to_owned();
+        tokio::spawn(async move {
+            if let Err(err) = Self::run(&addr, signing_key, dag, receiver).await {
+                warn!("DAG sync service stopped: {err:?}");
+            }
+        });
+        DagSyncHandle { commands }
+    }
+
+    async fn run(
+        listen_addr: &str,
+        signing_key: SigningKey,
+        dag: BlockDAG,
+        mut commands: mpsc::UnboundedReceiver<SyncCommand>,
+    ) -> Result<()> {
         let local_key = identity::Keypair::generate_ed25519();
         let local_peer_id = PeerId::from(local_key.public());
         let verifying_key = signing_key.verifying_key();
@@ -257,7 +523,12 @@ impl DagSyncService {
         let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), local_peer_id)
             .context("failed to initialise mDNS discovery")?;
 
-        let behaviour = DagBehaviour { gossip, mdns };
+        let sync = request_response::Behaviour::new(
+            std::iter::once((DagSyncProtocol, ProtocolSupport::Full)),
+            request_response::Config::default().with_request_timeout(SYNC_REQUEST_TIMEOUT),
+        );
+
+        let behaviour = DagBehaviour { gossip, mdns, sync };
         let swarm_config = swarm::Config::with_tokio_executor();
         let mut swarm = Swarm::new(transport, behaviour, local_peer_id, swarm_config);
 
@@ -267,6 +538,7 @@ impl DagSyncService {
         Swarm::listen_on(&mut swarm, addr.clone()).context("unable to listen for DAG sync")?;
 
         let mut seen: HashSet<[u8; 32]> = HashSet::new();
+        let mut pending: HashMap<OutboundRequestId, PendingFetch> = HashMap::new();
         let mut ticker = interval(TIP_INTERVAL);
 
         loop {
@@ -277,10 +549,21 @@ impl DagSyncService {
                             info!("DAG sync listening on {address}");
                         }
                         SwarmEvent::Behaviour(DagEvent::Gossip(event)) => {
-                            if let Err(err) = handle_gossip_event(*event, &dag, &mut seen) {
-                                warn!("error handling gossip event: {err:?}");
+                            match handle_gossip_event(*event, &dag, &mut seen) {
+                                Ok(Some((peer, hash))) => {
+                                    let id = swarm
+                                        .behaviour_mut()
+                                        .sync
+                                        .send_request(&peer, DagSyncRequest::Block(hash));
+                                    pending.insert(id, PendingFetch::Gossip);
+                                }
+                                Ok(None) => {}
+                                Err(err) => warn!("error handling gossip event: {err:?}"),
                             }
                         }
+                        SwarmEvent::Behaviour(DagEvent::Sync(event)) => {
+                            handle_sync_event(event, &mut swarm, &dag, &mut seen, &mut pending);
+                        }
                         SwarmEvent::Behaviour(DagEvent::Mdns(event)) => {
                             match event {
                                 mdns::Event::Discovered(peers) => {
@@ -298,6 +581,13 @@ impl DagSyncService {
                         _ => {}
                     }
                 }
+                Some(command) = commands.recv() => {
+                    let id = swarm
+                        .behaviour_mut()
+                        .sync
+                        .send_request(&command.peer, command.request);
+                    pending.insert(id, PendingFetch::Handle(command.respond_to));
+                }
                 _ = ticker.tick() => {
                     if let Err(err) =
                         broadcast_tips(&dag, &mut swarm.behaviour_mut().gossip, &topic, &mut seen)
@@ -310,13 +600,19 @@ impl DagSyncService {
     }
 }
 
+/// Handle a gossip event, returning an unknown tip to fetch from the peer
+/// that relayed it.
 fn handle_gossip_event(
     event: gossipsub::Event,
     dag: &BlockDAG,
     seen: &mut HashSet<[u8; 32]>,
-) -> Result<()> {
+) -> Result<Option<(PeerId, [u8; 32])>> {
     match event {
-        gossipsub::Event::Message { message, .. } => {
+        gossipsub::Event::Message {
+            propagation_source,
+            message,
+            ..
+        } => {
             let msg: GossipMsg = serde_json::from_slice(&message.data)
                 .context("failed to decode DAG gossip payload")?;
             match msg {
@@ -325,6 +621,7 @@ fn handle_gossip_event(
                         debug!("received tip {hash:?} from gossip");
                         if !dag.contains(&hash)? {
                             seen.insert(hash);
+                            return Ok(Some((propagation_source, hash)));
                         }
                     }
                 }
@@ -340,7 +637,7 @@ fn handle_gossip_event(
                                 ),
                                 Ok(false) => {
                                     warn!("zk-STARK proof invalid for block {}", hex::encode(hash));
-                                    return Ok(());
+                                    return Ok(None);
                                 }
                                 Err(e) => {
                                     warn!(
@@ -348,7 +645,7 @@ fn handle_gossip_event(
                                         hex::encode(hash),
                                         e
                                     );
-                                    return Ok(());
+                                    return Ok(None);
                                 }
                             }
                         } else {
@@ -356,7 +653,7 @@ fn handle_gossip_event(
                                 "Failed to deserialize zk-STARK proof for block {}",
                                 hex::encode(hash)
                             );
-                            return Ok(());
+                            return Ok(None);
                         }
                     }
 
@@ -380,7 +677,113 @@ fn handle_gossip_event(
             warn!("peer {peer_id} does not support gossipsub");
         }
     }
-    Ok(())
+    Ok(None)
+}
+
+/// Serve inbound block requests and import blocks fetched for gossip.
+fn handle_sync_event(
+    event: request_response::Event<DagSyncRequest, DagSyncResponse>,
+    swarm: &mut Swarm<DagBehaviour>,
+    dag: &BlockDAG,
+    seen: &mut HashSet<[u8; 32]>,
+    pending: &mut HashMap<OutboundRequestId, PendingFetch>,
+) {
+    match event {
+        request_response::Event::Message {
+            message:
+                request_response::Message::Request {
+                    request, channel, ..
+                },
+            ..
+        } => match serve_dag_sync_request(dag, &request) {
+            Ok(response) => {
+                if swarm
+                    .behaviour_mut()
+                    .sync
+                    .send_response(channel, response)
+                    .is_err()
+                {
+                    debug!("DAG sync requester went away before the response");
+                }
+            }
+            Err(err) => warn!("failed to serve DAG sync request: {err:?}"),
+        },
+        request_response::Event::Message {
+            peer,
+            message:
+                request_response::Message::Response {
+                    request_id,
+                    response,
+                },
+            ..
+        } => match pending.remove(&request_id) {
+            Some(PendingFetch::Handle(reply)) => {
+                let _ = reply.send(Ok(response));
+            }
+            Some(PendingFetch::Gossip) => {
+                let DagSyncResponse::Block(Some(block)) = response else {
+                    return;
+                };
+                for parent in import_fetched_block(dag, &block, seen) {
+                    let id = swarm
+                        .behaviour_mut()
+                        .sync
+                        .send_request(&peer, DagSyncRequest::Block(parent));
+                    pending.insert(id, PendingFetch::Gossip);
+                }
+            }
+            None => {}
+        },
+        request_response::Event::OutboundFailure {
+            request_id, error, ..
+        } => {
+            if let Some(PendingFetch::Handle(reply)) = pending.remove(&request_id) {
+                let _ = reply.send(Err(anyhow!("DAG sync request failed: {error}")));
+            }
+        }
+        request_response::Event::InboundFailure { .. }
+        | request_response::Event::ResponseSent { .. } => {}
+    }
+}
+
+/// Insert a block fetched by hash and return the parents that still need to
+/// be fetched. A block whose parents are missing is inserted once they are.
+fn import_fetched_block(
+    dag: &BlockDAG,
+    block: &Block,
+    seen: &mut HashSet<[u8; 32]>,
+) -> Vec<[u8; 32]> {
+    let hash = block.hash();
+    if !block.verify() {
+        warn!("peer served an invalid block for {}", hex::encode(hash));
+        return Vec::new();
+    }
+
+    let missing: Vec<[u8; 32]> = block
+        .header
+        .parent_hashes
+        .iter()
+        .copied()
+        .filter(|parent| !dag.contains(parent).unwrap_or(false))
+        .collect();
+    if missing.is_empty() {
+        match dag.insert_block(block) {
+            Ok(true) => {
+                info!("🧱  Fetched missing block {}", hex::encode(hash));
+                dag.flush().ok();
+            }
+            Ok(false) => {}
+            Err(err) => warn!("rejected fetched block: {err:?}"),
+        }
+        return Vec::new();
+    }
+
+    // Re-fetch this block after its parents so the tip set stays correct.
+    seen.remove(&hash);
+    missing
+        .into_iter()
+        .filter(|parent| seen.insert(*parent))
+        .collect()
 }
 
 fn broadcast_tips(
@@ -442,4 +845,35 @@ mod tests {
             serde_json::from_slice(&tip_bytes).unwrap()
         );
     }
+
+    #[test]
+    fn fetched_blocks_wait_for_their_parents() {
+        let remote_dir = tempfile::tempdir().unwrap();
+        let remote = BlockDAG::open(remote_dir.path()).unwrap();
+        let local_dir = tempfile::tempdir().unwrap();
+        let local = BlockDAG::open(local_dir.path()).unwrap();
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let parent = Block::new(&key, vec![], vec![b"parent".to_vec()]);
+        let child = Block::new(&key, vec![parent.hash()], vec![b"child".to_vec()]);
+        remote.insert_block(&parent).unwrap();
+        remote.insert_block(&child).unwrap();
+
+        let fetch = |hash| match serve_dag_sync_request(&remote, &DagSyncRequest::Block(hash)) {
+            Ok(DagSyncResponse::Block(Some(block))) => *block,
+            other => panic!("unexpected response {other:?}"),
+        };
+        assert_eq!(
+            serve_dag_sync_request(&remote, &DagSyncRequest::Tips).unwrap(),
+            DagSyncResponse::Tips(vec![child.hash()])
+        );
+
+        let mut seen = HashSet::from([child.hash()]);
+        let missing = import_fetched_block(&local, &fetch(child.hash()), &mut seen);
+        assert_eq!(missing, vec![parent.hash()]);
+        assert!(!local.contains(&child.hash()).unwrap());
+
+        assert!(import_fetched_block(&local, &fetch(parent.hash()), &mut seen).is_empty());
+        assert!(import_fetched_block(&local, &fetch(child.hash()), &mut seen).is_empty());
+        assert_eq!(local.get_tips().unwrap(), vec![child.hash()]);
+    }
 }
@@ -17,10 +17,14 @@ pub use dag::BlockDAG;
 pub use dag_operations::{
     DAGAnalysis, DAGOperations, DAGOptimizationConfig, DAGPath, DAGStatistics,
 };
-pub use dag_sync::{start_dag_sync, DagSyncService, GossipMsg};
+pub use dag_sync::{
+    start_dag_sync, DagSyncHandle, DagSyncRequest, DagSyncResponse, DagSyncService, GossipMsg,
+    DAG_SYNC_PROTOCOL_NAME,
+};
 pub use order::order_blocks;
 pub use sync_manager::{
-    ConflictResolutionStrategy, SyncConfig, SyncEvent, SyncManager, SyncPerformance, SyncState,
+    BlockFetcher, ConflictResolutionStrategy, SyncConfig, SyncEvent, SyncManager, SyncPerformance,
+    SyncState,
 };
 pub use zk_stark::{
     batch_verify_proofs, generate_stark_proof, verify_stark_proof, StarkConfig, StarkGenerator,
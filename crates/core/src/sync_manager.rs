@@ -4,9 +4,10 @@
 //! conflict resolution, state reconciliation, and performance optimization.
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use ippan_types::{format_ratio, RatioMicros, RATIO_SCALE};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, RwLock};
@@ -76,6 +77,16 @@ pub trait ConflictResolver: Send + Sync {
     fn resolve_conflict(&self, blocks: &[Block]) -> Result<Block>;
 }
 
+/// Transport used to pull blocks from peers, e.g. the libp2p
+/// [`DagSyncHandle`](crate::dag_sync::DagSyncHandle).
+#[async_trait]
+pub trait BlockFetcher: Send + Sync {
+    /// Current DAG tips advertised by `peer_id`.
+    async fn fetch_tips(&self, peer_id: &str) -> Result<Vec<[u8; 32]>>;
+    /// A single block by hash, or `None` when the peer does not hold it.
+    async fn fetch_block(&self, peer_id: &str, hash: [u8; 32]) -> Result<Option<Block>>;
+}
+
 /// Synchronization event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SyncEvent {
@@ -110,6 +121,7 @@ pub struct SyncManager {
     sync_queue: Arc<RwLock<VecDeque<SyncTask>>>,
     last_sync_time: Arc<RwLock<Option<Instant>>>,
     is_running: Arc<RwLock<bool>>,
+    fetcher: Option<Arc<dyn BlockFetcher>>,
 }
 
 /// Peer connection information
@@ -177,11 +189,18 @@ impl SyncManager {
             sync_queue: Arc::new(RwLock::new(VecDeque::new())),
             last_sync_time: Arc::new(RwLock::new(None)),
             is_running: Arc::new(RwLock::new(false)),
+            fetcher: None,
         };
 
         Ok((manager, event_receiver))
     }
 
+    /// Pull missing blocks from peers through `fetcher`.
+    pub fn with_block_fetcher(mut self, fetcher: Arc<dyn BlockFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
     /// Start the synchronization manager
     pub async fn start(&self) -> Result<()> {
         let mut is_running = self.is_running.write().await;
@@ -449,16 +468,21 @@ impl SyncManager {
                 .max_batch_size
                 .min(self.config.batch_size);
 
-            self.request_blocks_from_peer(
-                &peer.peer_id,
-                &our_tips,
-                batch_limit,
-                peer.sync_capability.supported_protocols.clone(),
-                peer.performance_score,
-            )
-            .await?;
+            let result = self
+                .request_blocks_from_peer(
+                    &peer.peer_id,
+                    &our_tips,
+                    batch_limit,
+                    peer.sync_capability.supported_protocols.clone(),
+                    peer.performance_score,
+                )
+                .await;
+            if let Err(err) = &result {
+                warn!("Block sync with peer {} failed: {}", peer.peer_id, err);
+            }
 
-            self.update_peer_activity(&peer.peer_id, true).await;
+            self.update_peer_activity(&peer.peer_id, result.is_ok())
+                .await;
         }
 
         Ok(())
@@ -544,10 +568,81 @@ impl SyncManager {
             our_tips.len()
         );
 
-        // Implement peer communication logic
+        let Some(fetcher) = &self.fetcher else {
+            return Ok(());
+        };
+
+        // Walk back from the peer's tips to blocks we already hold.
+        let mut wanted: VecDeque<[u8; 32]> = fetcher.fetch_tips(peer_id).await?.into();
+        let mut requested = HashSet::new();
+        let mut fetched = Vec::new();
+        while let Some(hash) = wanted.pop_front() {
+            if fetched.len() >= batch_limit {
+                break;
+            }
+            if !requested.insert(hash) || self.dag.read().await.contains(&hash)? {
+                continue;
+            }
+            let Some(block) = fetcher.fetch_block(peer_id, hash).await? else {
+                continue;
+            };
+            if block.hash() != hash || !block.verify() {
+                return Err(anyhow!(
+                    "peer {} served an invalid block for {}",
+                    peer_id,
+                    hex::encode(hash)
+                ));
+            }
+            wanted.extend(block.header.parent_hashes.iter().copied());
+            fetched.push(block);
+        }
+
+        let inserted = self.insert_in_dependency_order(fetched).await?;
+        if inserted > 0 {
+            self.performance.write().await.total_blocks_synced += inserted;
+            info!("Synced {} blocks from peer {}", inserted, peer_id);
+        }
         Ok(())
     }
 
+    /// Insert fetched blocks parents-first so the tip set stays correct.
+    /// Blocks whose parents are still missing wait for a later cycle.
+    async fn insert_in_dependency_order(&self, mut pending: Vec<Block>) -> Result<usize> {
+        let dag = self.dag.read().await;
+        let mut inserted = 0;
+        loop {
+            let mut ready = Vec::new();
+            let mut waiting = Vec::new();
+            for block in pending {
+                let parents_known = block
+                    .header
+                    .parent_hashes
+                    .iter()
+                    .map(|parent| dag.contains(parent))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .all(|known| known);
+                if parents_known {
+                    ready.push(block);
+                } else {
+                    waiting.push(block);
+                }
+            }
+            if ready.is_empty() {
+                break;
+            }
+            for block in ready {
+                if dag.insert_block(&block)? {
+                    inserted += 1;
+                    self.send_event(SyncEvent::BlockReceived(block)).await?;
+                }
+            }
+            pending = waiting;
+        }
+        dag.flush()?;
+        Ok(inserted)
+    }
+
     async fn update_peer_activity(&self, peer_id: &str, success: bool) {
         let mut connections = self.peer_connections.write().await;
         if let Some(connection) = connections.get_mut(peer_id) {
@@ -694,6 +789,63 @@ mod tests {
         manager.remove_peer("test_peer").await.unwrap();
     }
 
+    struct DagFetcher(BlockDAG);
+
+    #[async_trait]
+    impl BlockFetcher for DagFetcher {
+        async fn fetch_tips(&self, _peer_id: &str) -> Result<Vec<[u8; 32]>> {
+            self.0.get_tips()
+        }
+
+        async fn fetch_block(&self, _peer_id: &str, hash: [u8; 32]) -> Result<Option<Block>> {
+            self.0.get_block(&hash)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_blocks_through_fetcher() {
+        let remote_dir = tempdir().unwrap();
+        let remote = BlockDAG::open(remote_dir.path()).unwrap();
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let genesis = Block::new(&key, vec![], vec![b"genesis".to_vec()]);
+        let left = Block::new(&key, vec![genesis.hash()], vec![b"left".to_vec()]);
+        let right = Block::new(&key, vec![genesis.hash()], vec![b"right".to_vec()]);
+        let merge = Block::new(&key, vec![left.hash(), right.hash()], vec![]);
+        for block in [&genesis, &left, &right, &merge] {
+            remote.insert_block(block).unwrap();
+        }
+
+        let (manager, mut events) = create_test_sync_manager().await;
+        let manager = manager.with_block_fetcher(Arc::new(DagFetcher(remote)));
+        manager
+            .add_peer(
+                "remote".to_string(),
+                SyncCapability {
+                    max_batch_size: 100,
+                    supported_protocols: vec!["/ippan/dag-sync/1.0.0".to_string()],
+                    compression_enabled: false,
+                    encryption_enabled: false,
+                },
+            )
+            .await
+            .unwrap();
+        manager.sync_blocks().await.unwrap();
+
+        let dag = manager.dag.read().await;
+        for block in [&genesis, &left, &right, &merge] {
+            assert!(dag.contains(&block.hash()).unwrap());
+        }
+        assert_eq!(dag.get_tips().unwrap(), vec![merge.hash()]);
+        assert_eq!(manager.get_performance().await.total_blocks_synced, 4);
+        let mut received = 0;
+        while let Ok(event) = events.try_recv() {
+            if matches!(event, SyncEvent::BlockReceived(_)) {
+                received += 1;
+            }
+        }
+        assert_eq!(received, 4);
+    }
+
     #[tokio::test]
     async fn test_manual_sync_trigger() {
         let (manager, _) = create_test_sync_manager().await;
@@ -0,0 +1,156 @@
+//! ECIES over IPPAN Ed25519 identity keys
+//!
+//! Seals short secrets (e.g. symmetric content keys) to a recipient's Ed25519
+//! public key. Both sides convert their Ed25519 keys to X25519: the sender
+//! performs ECDH from a fresh ephemeral key, the shared secret is expanded
+//! with HKDF-SHA256, and the payload is encrypted with AES-256-GCM.
+//!
+//! Sealed format: `ephemeral_public (32) || nonce (12) || ciphertext + tag`.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hkdf::Hkdf;
+use rand_core::{OsRng, RngCore};
+use sha2::Sha256;
+use thiserror::Error;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret};
+
+/// Bytes added to the plaintext by [`seal`].
+pub const SEAL_OVERHEAD: usize = 32 + 12 + 16;
+
+/// ECIES error types
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum EciesError {
+    #[error("invalid recipient public key")]
+    InvalidPublicKey,
+    #[error("sealed payload is too short")]
+    Truncated,
+    #[error("key agreement produced a degenerate shared secret")]
+    WeakKey,
+    #[error("encryption failed")]
+    EncryptionFailed,
+    #[error("decryption failed")]
+    DecryptionFailed,
+}
+
+fn to_x25519_public(public_key: &[u8; 32]) -> Result<X25519PublicKey, EciesError> {
+    let verifying_key =
+        VerifyingKey::from_bytes(public_key).map_err(|_| EciesError::InvalidPublicKey)?;
+    Ok(X25519PublicKey::from(
+        verifying_key.to_montgomery().to_bytes(),
+    ))
+}
+
+fn derive_key(
+    shared: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    recipient_public: &[u8; 32],
+    info: &[u8],
+) -> [u8; 32] {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public);
+    salt[32..].copy_from_slice(recipient_public);
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Encrypt `plaintext` to the holder of the Ed25519 key `recipient_public`.
+///
+/// `info` is a context string; the same value must be passed to [`open`].
+pub fn seal(
+    recipient_public: &[u8; 32],
+    plaintext: &[u8],
+    info: &[u8],
+) -> Result<Vec<u8>, EciesError> {
+    let recipient = to_x25519_public(recipient_public)?;
+    let ephemeral = StaticSecret::random_from_rng(OsRng);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral);
+    let shared = ephemeral.diffie_hellman(&recipient);
+    if !shared.was_contributory() {
+        return Err(EciesError::WeakKey);
+    }
+
+    let key = derive_key(
+        shared.as_bytes(),
+        ephemeral_public.as_bytes(),
+        recipient.as_bytes(),
+        info,
+    );
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| EciesError::EncryptionFailed)?;
+
+    let mut sealed = Vec::with_capacity(SEAL_OVERHEAD + plaintext.len());
+    sealed.extend_from_slice(ephemeral_public.as_bytes());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a payload produced by [`seal`] with the recipient's Ed25519 private key.
+pub fn open(
+    recipient_private: &[u8; 32],
+    sealed: &[u8],
+    info: &[u8],
+) -> Result<Vec<u8>, EciesError> {
+    if sealed.len() < SEAL_OVERHEAD {
+        return Err(EciesError::Truncated);
+    }
+    let mut ephemeral_public = [0u8; 32];
+    ephemeral_public.copy_from_slice(&sealed[..32]);
+    let nonce = &sealed[32..44];
+
+    let secret = StaticSecret::from(SigningKey::from_bytes(recipient_private).to_scalar_bytes());
+    let recipient_public = X25519PublicKey::from(&secret);
+    let shared = secret.diffie_hellman(&X25519PublicKey::from(ephemeral_public));
+    if !shared.was_contributory() {
+        return Err(EciesError::WeakKey);
+    }
+
+    let key = derive_key(
+        shared.as_bytes(),
+        &ephemeral_public,
+        recipient_public.as_bytes(),
+        info,
+    );
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+        .decrypt(Nonce::from_slice(nonce), &sealed[44..])
+        .map_err(|_| EciesError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyPair;
+
+    #[test]
+    fn test_seal_and_open_roundtrip() {
+        let recipient = KeyPair::generate();
+        let sealed = seal(&recipient.public_key(), b"content key", b"test").unwrap();
+        assert_eq!(sealed.len(), SEAL_OVERHEAD + 11);
+        assert_eq!(
+            open(&recipient.private_key(), &sealed, b"test").unwrap(),
+            b"content key"
+        );
+
+        let other = KeyPair::generate();
+        assert_eq!(
+            open(&other.private_key(), &sealed, b"test"),
+            Err(EciesError::DecryptionFailed)
+        );
+        assert_eq!(
+            open(&recipient.private_key(), &sealed, b"other context"),
+            Err(EciesError::DecryptionFailed)
+        );
+        assert_eq!(
+            open(&recipient.private_key(), &sealed[..40], b"test"),
+            Err(EciesError::Truncated)
+        );
+    }
+}
@@ -7,6 +7,7 @@
 //! - `hash_functions`: Implements multiple hashing algorithms (Blake3, SHA2, Keccak, etc.)
 //! - `merkle_trees`: Provides Merkle tree construction and proof verification
 //! - `commitment_schemes`: Contains Pedersen and other zero-knowledge commitments
+//! - `ecies`: Seals secrets to Ed25519 identity keys (X25519 + HKDF + AES-256-GCM)
 //! - `validators`: Cryptographic validation helpers for confidential blocks and transactions
 
 use anyhow::Result;
@@ -50,6 +51,7 @@ pub enum CryptoError {
 // Module exports
 // -----------------------------------------------------------------------------
 pub mod commitment_schemes;
+pub mod ecies;
 pub mod hash_functions;
 pub mod merkle_trees;
 pub mod validators;
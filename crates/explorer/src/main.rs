@@ -60,6 +60,7 @@ async fn main() -> Result<()> {
         .route("/api/transaction/:hash", get(get_transaction))
         .route("/api/validators", get(get_validators))
         .route("/api/validator/:id", get(get_validator))
+        .route("/api/validator/:id/explain", get(get_validator_explain))
         .route("/api/stats", get(get_stats))
         .route("/api/node/status", get(get_node_status))
         .route("/api/node/peers", get(get_node_peers))
@@ -137,6 +138,7 @@ async fn index() -> Html<&'static str> {
             <li><code>GET /api/transaction/:hash</code> - Get transaction by hash</li>
             <li><code>GET /api/validators</code> - List validators</li>
             <li><code>GET /api/validator/:id</code> - Get validator info</li>
+            <li><code>GET /api/validator/:id/explain?round=N</code> - Per-feature breakdown of a validator's selection score</li>
             <li><code>GET /api/stats</code> - Blockchain statistics</li>
             <li><code>GET /api/node/status</code> - Node status</li>
             <li><code>GET /api/node/peers</code> - Node peers</li>
@@ -343,6 +345,44 @@ async fn get_validator(
     Ok(Json(resp))
 }
 
+#[derive(Deserialize)]
+struct ExplainParams {
+    round: Option<u64>,
+}
+
+async fn get_validator_explain(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<ExplainParams>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let mut url = format!("{}/ai/explain/{}", state.node_rpc, id);
+    if let Some(round) = params.round {
+        url.push_str(&format!("?round={round}"));
+    }
+
+    let resp = state
+        .client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Failed to connect to node: {e}"),
+            )
+        })?
+        .json()
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to parse response: {e}"),
+            )
+        })?;
+
+    Ok(Json(resp))
+}
+
 async fn get_stats(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
@@ -0,0 +1,179 @@
+//! L1 ownership anchors for file descriptors.
+//!
+//! Consensus records which key owns a descriptor and the digest it signed,
+//! mirroring how `ippan-l1-handle-anchors` anchors handle ownership. Peers can
+//! then check a descriptor against chain state instead of trusting whoever
+//! published it.
+
+use crate::descriptor::{ContentHash, FileDescriptor, FileId};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Ownership record for a file descriptor anchored on L1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOwnershipAnchor {
+    /// Anchored descriptor id.
+    pub file_id: FileId,
+    /// Content hash of the anchored descriptor.
+    pub content_hash: ContentHash,
+    /// Owner public key.
+    pub owner: [u8; 32],
+    /// [`FileDescriptor::signing_digest`] of the anchored descriptor.
+    pub descriptor_digest: [u8; 32],
+    /// Block height at which the anchor was created.
+    pub block_height: u64,
+    /// Round at which the anchor was created.
+    pub round: u64,
+}
+
+impl FileOwnershipAnchor {
+    /// Whether `descriptor` is the exact descriptor this anchor commits to.
+    pub fn matches(&self, descriptor: &FileDescriptor) -> bool {
+        self.file_id == descriptor.id
+            && self.content_hash == descriptor.content_hash
+            && self.owner == descriptor.owner
+            && self.descriptor_digest == descriptor.signing_digest()
+    }
+}
+
+/// Errors raised by [`L1FileAnchorStorage`].
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum FileAnchorError {
+    #[error("file {0} is already anchored by another owner")]
+    OwnerConflict(String),
+    #[error("file {0} is already anchored with a different descriptor")]
+    DescriptorConflict(String),
+    #[error("descriptor {0} does not match its L1 anchor")]
+    Mismatch(String),
+}
+
+/// In-memory store of file ownership anchors.
+#[derive(Debug, Default)]
+pub struct L1FileAnchorStorage {
+    anchors: RwLock<HashMap<FileId, FileOwnershipAnchor>>,
+}
+
+impl L1FileAnchorStorage {
+    /// Create an empty anchor store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an anchor.
+    ///
+    /// Anchors are immutable: re-anchoring the same descriptor is a no-op and
+    /// anything else for an anchored id is rejected.
+    pub fn store_anchor(&self, anchor: FileOwnershipAnchor) -> Result<(), FileAnchorError> {
+        let mut anchors = self.anchors.write();
+        if let Some(existing) = anchors.get(&anchor.file_id) {
+            if existing.owner != anchor.owner {
+                return Err(FileAnchorError::OwnerConflict(anchor.file_id.to_hex()));
+            }
+            if existing.descriptor_digest != anchor.descriptor_digest
+                || existing.content_hash != anchor.content_hash
+            {
+                return Err(FileAnchorError::DescriptorConflict(anchor.file_id.to_hex()));
+            }
+            return Ok(());
+        }
+        anchors.insert(anchor.file_id, anchor);
+        Ok(())
+    }
+
+    /// Look up the anchor for a file id.
+    pub fn get_anchor(&self, id: &FileId) -> Option<FileOwnershipAnchor> {
+        self.anchors.read().get(id).cloned()
+    }
+
+    /// Ids of all files anchored by `owner`.
+    pub fn list_owner_files(&self, owner: &[u8; 32]) -> Vec<FileId> {
+        let mut ids: Vec<FileId> = self
+            .anchors
+            .read()
+            .values()
+            .filter(|anchor| anchor.owner == *owner)
+            .map(|anchor| anchor.file_id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Check a descriptor against its anchor.
+    ///
+    /// Returns `Ok(None)` if the file is not anchored, and the anchor if the
+    /// descriptor matches it.
+    pub fn check_descriptor(
+        &self,
+        descriptor: &FileDescriptor,
+    ) -> Result<Option<FileOwnershipAnchor>, FileAnchorError> {
+        match self.get_anchor(&descriptor.id) {
+            Some(anchor) if anchor.matches(descriptor) => Ok(Some(anchor)),
+            Some(_) => Err(FileAnchorError::Mismatch(descriptor.id.to_hex())),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of stored anchors.
+    pub fn len(&self) -> usize {
+        self.anchors.read().len()
+    }
+
+    /// Whether no anchors are stored.
+    pub fn is_empty(&self) -> bool {
+        self.anchors.read().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor_for(descriptor: &FileDescriptor) -> FileOwnershipAnchor {
+        FileOwnershipAnchor {
+            file_id: descriptor.id,
+            content_hash: descriptor.content_hash,
+            owner: descriptor.owner,
+            descriptor_digest: descriptor.signing_digest(),
+            block_height: 1,
+            round: 1,
+        }
+    }
+
+    #[test]
+    fn test_anchor_lifecycle() {
+        let storage = L1FileAnchorStorage::new();
+        let descriptor =
+            FileDescriptor::new(ContentHash::from_data(b"file"), [3u8; 32], 4, None, vec![]);
+        assert_eq!(storage.check_descriptor(&descriptor), Ok(None));
+
+        let anchor = anchor_for(&descriptor);
+        storage.store_anchor(anchor.clone()).unwrap();
+        storage.store_anchor(anchor.clone()).unwrap();
+        assert_eq!(storage.len(), 1);
+        assert_eq!(
+            storage.check_descriptor(&descriptor),
+            Ok(Some(anchor.clone()))
+        );
+        assert_eq!(storage.list_owner_files(&[3u8; 32]), vec![descriptor.id]);
+
+        let mut retagged = descriptor.clone();
+        retagged.tags.push("changed".to_string());
+        assert!(matches!(
+            storage.check_descriptor(&retagged),
+            Err(FileAnchorError::Mismatch(_))
+        ));
+        assert!(matches!(
+            storage.store_anchor(anchor_for(&retagged)),
+            Err(FileAnchorError::DescriptorConflict(_))
+        ));
+
+        let mut hijack = anchor;
+        hijack.owner = [4u8; 32];
+        assert!(matches!(
+            storage.store_anchor(hijack),
+            Err(FileAnchorError::OwnerConflict(_))
+        ));
+    }
+}
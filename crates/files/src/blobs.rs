@@ -0,0 +1,169 @@
+//! Local blob stores for content-addressed file chunks.
+//!
+//! Blob stores are dumb key/value backends keyed by [`ContentHash`]. Integrity
+//! checks (hash verification, Merkle roots) live in [`crate::content`], so the
+//! same backend can hold both raw chunks and serialized manifests.
+
+use crate::descriptor::ContentHash;
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Trait for blob storage backends.
+pub trait BlobStore: Send + Sync {
+    /// Store a blob under the given key, replacing any previous value.
+    fn put(&self, key: &ContentHash, bytes: &[u8]) -> Result<()>;
+
+    /// Retrieve a blob by key.
+    fn get(&self, key: &ContentHash) -> Result<Option<Vec<u8>>>;
+
+    /// Check whether a blob exists.
+    fn contains(&self, key: &ContentHash) -> Result<bool>;
+
+    /// Remove a blob. Returns `true` if it existed.
+    fn remove(&self, key: &ContentHash) -> Result<bool>;
+}
+
+/// In-memory blob store (for testing and ephemeral nodes).
+#[derive(Clone, Default)]
+pub struct MemoryBlobStore {
+    blobs: Arc<RwLock<HashMap<ContentHash, Vec<u8>>>>,
+}
+
+impl MemoryBlobStore {
+    /// Create a new, empty in-memory blob store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of blobs currently held.
+    pub fn len(&self) -> usize {
+        self.blobs.read().len()
+    }
+
+    /// Whether the store holds no blobs.
+    pub fn is_empty(&self) -> bool {
+        self.blobs.read().is_empty()
+    }
+}
+
+impl BlobStore for MemoryBlobStore {
+    fn put(&self, key: &ContentHash, bytes: &[u8]) -> Result<()> {
+        self.blobs.write().insert(*key, bytes.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &ContentHash) -> Result<Option<Vec<u8>>> {
+        Ok(self.blobs.read().get(key).cloned())
+    }
+
+    fn contains(&self, key: &ContentHash) -> Result<bool> {
+        Ok(self.blobs.read().contains_key(key))
+    }
+
+    fn remove(&self, key: &ContentHash) -> Result<bool> {
+        Ok(self.blobs.write().remove(key).is_some())
+    }
+}
+
+/// Filesystem blob store.
+///
+/// Blobs are written to `<root>/<first byte hex>/<full hex>` so no single
+/// directory grows unbounded. Writes go through a temporary file and a rename,
+/// so readers never observe a partially written blob.
+#[derive(Debug, Clone)]
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+impl FsBlobStore {
+    /// Open (creating if needed) a blob store rooted at `root`.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)
+            .with_context(|| format!("failed to create blob directory {}", root.display()))?;
+        Ok(Self { root })
+    }
+
+    /// Root directory of this store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn blob_path(&self, key: &ContentHash) -> PathBuf {
+        let hex = key.to_hex();
+        self.root.join(&hex[..2]).join(hex)
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    fn put(&self, key: &ContentHash, bytes: &[u8]) -> Result<()> {
+        let path = self.blob_path(key);
+        let dir = path
+            .parent()
+            .expect("blob path always has a shard directory");
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create shard directory {}", dir.display()))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("failed to commit {}", path.display()))?;
+        Ok(())
+    }
+
+    fn get(&self, key: &ContentHash) -> Result<Option<Vec<u8>>> {
+        let path = self.blob_path(key);
+        match fs::read(&path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    fn contains(&self, key: &ContentHash) -> Result<bool> {
+        Ok(self.blob_path(key).is_file())
+    }
+
+    fn remove(&self, key: &ContentHash) -> Result<bool> {
+        let path = self.blob_path(key);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err).with_context(|| format!("failed to remove {}", path.display())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(store: &dyn BlobStore) {
+        let key = ContentHash::from_data(b"blob");
+        assert!(!store.contains(&key).unwrap());
+        assert!(store.get(&key).unwrap().is_none());
+
+        store.put(&key, b"blob").unwrap();
+        assert!(store.contains(&key).unwrap());
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some(&b"blob"[..]));
+
+        assert!(store.remove(&key).unwrap());
+        assert!(!store.remove(&key).unwrap());
+        assert!(store.get(&key).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_blob_store_roundtrip() {
+        exercise(&MemoryBlobStore::new());
+    }
+
+    #[test]
+    fn test_fs_blob_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsBlobStore::open(dir.path().join("blobs")).unwrap();
+        exercise(&store);
+    }
+}
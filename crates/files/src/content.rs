@@ -0,0 +1,724 @@
+//! Chunked, content-addressed file storage.
+//!
+//! File content is split into fixed-size chunks. Each chunk is addressed by its
+//! BLAKE3 hash, and the chunk hashes form a binary Merkle tree whose root is the
+//! file's [`ContentHash`]. A file that fits in a single chunk therefore has the
+//! same root as [`ContentHash::from_data`], keeping descriptors published before
+//! chunking was introduced valid. Interior nodes are hashed in BLAKE3's
+//! key-derivation mode, so no chunk's plain hash can equal an interior node and
+//! a single-chunk file can never claim the root of a multi-chunk one.
+//!
+//! The ordered list of chunk hashes is kept in a [`ContentManifest`], stored
+//! alongside the chunks. Every read re-derives the root from the manifest and
+//! checks each chunk against its hash, so a corrupted blob store can never
+//! serve bytes that do not match the advertised content hash.
+
+use crate::blobs::{BlobStore, FsBlobStore, MemoryBlobStore};
+use crate::descriptor::ContentHash;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Default chunk size (256 KiB).
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Largest chunk size accepted in a manifest (4 MiB).
+pub const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// BLAKE3 key-derivation context for interior Merkle nodes.
+const MERKLE_NODE_CONTEXT: &str = "ippan-files 2025 content merkle node";
+
+/// Errors raised while storing or reading file content.
+#[derive(Debug, Error)]
+pub enum ContentError {
+    #[error("content {0} not found")]
+    NotFound(String),
+    #[error("content hash mismatch: expected {expected}, computed {actual}")]
+    HashMismatch { expected: String, actual: String },
+    #[error("chunk {index} ({hash}) is missing from the blob store")]
+    MissingChunk { index: usize, hash: String },
+    #[error("chunk {index} failed verification against {hash}")]
+    CorruptChunk { index: usize, hash: String },
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("range {start}..{end} is not satisfiable for {size} bytes")]
+    InvalidRange { start: u64, end: u64, size: u64 },
+    #[error("storage error: {0}")]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Hash an interior Merkle node.
+///
+/// Leaves are plain BLAKE3 hashes of chunk bytes; nodes use a derived key so
+/// the two domains cannot collide.
+fn hash_node(left: &ContentHash, right: &ContentHash) -> ContentHash {
+    let mut hasher = blake3::Hasher::new_derive_key(MERKLE_NODE_CONTEXT);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    ContentHash::from_bytes(*hasher.finalize().as_bytes())
+}
+
+/// Compute the Merkle root over ordered chunk hashes.
+///
+/// Odd nodes are promoted unchanged to the next level. An empty file has the
+/// root `ContentHash::from_data(&[])`.
+pub fn merkle_root(chunks: &[ContentHash]) -> ContentHash {
+    if chunks.is_empty() {
+        return ContentHash::from_data(&[]);
+    }
+    let mut level = chunks.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+fn next_level(level: &[ContentHash]) -> Vec<ContentHash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_node(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks(2) yields one or two items"),
+        })
+        .collect()
+}
+
+/// Inclusion proof for one chunk hash under a Merkle root.
+///
+/// `siblings` holds the sibling hash for every level at which the node has one,
+/// bottom-up; levels where the node is promoted unpaired contribute nothing.
+/// The shape of the path is fixed by `index` and `leaf_count`, so a proof
+/// cannot be replayed for another position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub index: u64,
+    pub leaf_count: u64,
+    pub siblings: Vec<ContentHash>,
+}
+
+impl MerkleProof {
+    /// Build the proof for leaf `index` of `chunks`.
+    pub fn build(chunks: &[ContentHash], index: usize) -> Option<Self> {
+        if index >= chunks.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut level = chunks.to_vec();
+        let mut position = index;
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = next_level(&level);
+            position /= 2;
+        }
+        Some(Self {
+            index: index as u64,
+            leaf_count: chunks.len() as u64,
+            siblings,
+        })
+    }
+
+    /// Whether `leaf` sits at `index` of a tree with root `root`.
+    pub fn verify(&self, root: &ContentHash, leaf: &ContentHash) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
+        let mut hash = *leaf;
+        let mut position = self.index;
+        let mut width = self.leaf_count;
+        let mut siblings = self.siblings.iter();
+        while width > 1 {
+            if (position ^ 1) < width {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                hash = if position.is_multiple_of(2) {
+                    hash_node(&hash, sibling)
+                } else {
+                    hash_node(sibling, &hash)
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == *root
+    }
+}
+
+/// A chunk together with its inclusion proof, as served to storage challenges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkProof {
+    #[serde(with = "hex_bytes")]
+    pub chunk: Vec<u8>,
+    pub proof: MerkleProof,
+}
+
+impl ChunkProof {
+    /// Whether the chunk is leaf `proof.index` of the content with `root`.
+    pub fn verify(&self, root: &ContentHash) -> bool {
+        self.proof
+            .verify(root, &ContentHash::from_data(&self.chunk))
+    }
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        hex::decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Compute the content hash of `data` when chunked at `chunk_size`.
+pub fn content_root(data: &[u8], chunk_size: usize) -> ContentHash {
+    let chunks: Vec<ContentHash> = data
+        .chunks(chunk_size)
+        .map(ContentHash::from_data)
+        .collect();
+    merkle_root(&chunks)
+}
+
+/// Ordered chunk list for a stored file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentManifest {
+    /// Merkle root over `chunks`; equals the file's content hash.
+    pub root: ContentHash,
+    /// Total content length in bytes.
+    pub size_bytes: u64,
+    /// Chunk size used when splitting the content.
+    pub chunk_size: u32,
+    /// BLAKE3 hash of each chunk, in order.
+    pub chunks: Vec<ContentHash>,
+}
+
+impl ContentManifest {
+    /// Build a manifest for `data` split at `chunk_size`.
+    pub fn from_data(data: &[u8], chunk_size: usize) -> Self {
+        let chunks: Vec<ContentHash> = data
+            .chunks(chunk_size)
+            .map(ContentHash::from_data)
+            .collect();
+        Self {
+            root: merkle_root(&chunks),
+            size_bytes: data.len() as u64,
+            chunk_size: chunk_size as u32,
+            chunks,
+        }
+    }
+
+    /// Number of chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Inclusion proof for chunk `index` against `root`.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        MerkleProof::build(&self.chunks, index)
+    }
+
+    /// Byte span covered by chunk `index`.
+    pub fn chunk_span(&self, index: usize) -> Range<u64> {
+        let start = index as u64 * self.chunk_size as u64;
+        let end = (start + self.chunk_size as u64).min(self.size_bytes);
+        start..end
+    }
+
+    /// Indices of the chunks covering the byte range `range`.
+    pub fn chunks_for_range(&self, range: &Range<u64>) -> Range<usize> {
+        if range.start >= range.end {
+            return 0..0;
+        }
+        let chunk_size = self.chunk_size as u64;
+        let first = (range.start / chunk_size) as usize;
+        let last = range.end.div_ceil(chunk_size) as usize;
+        first..last
+    }
+
+    /// Check internal consistency: chunk count matches the size and the chunk
+    /// hashes reproduce the advertised root.
+    pub fn verify(&self) -> Result<(), ContentError> {
+        let chunk_size = self.chunk_size as usize;
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(ContentError::InvalidManifest(format!(
+                "chunk size {chunk_size} outside 1..={MAX_CHUNK_SIZE}"
+            )));
+        }
+        let expected_chunks = self.size_bytes.div_ceil(self.chunk_size as u64);
+        if expected_chunks != self.chunks.len() as u64 {
+            return Err(ContentError::InvalidManifest(format!(
+                "{} bytes require {expected_chunks} chunks, manifest lists {}",
+                self.size_bytes,
+                self.chunks.len()
+            )));
+        }
+        let actual = merkle_root(&self.chunks);
+        if actual != self.root {
+            return Err(ContentError::HashMismatch {
+                expected: self.root.to_hex(),
+                actual: actual.to_hex(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Content-addressed file store backed by two blob stores: one for chunks and
+/// one for manifests (keyed by root).
+#[derive(Clone)]
+pub struct ContentStore {
+    chunks: Arc<dyn BlobStore>,
+    manifests: Arc<dyn BlobStore>,
+    chunk_size: usize,
+}
+
+impl ContentStore {
+    /// Create a store over explicit chunk and manifest backends.
+    pub fn new(chunks: Arc<dyn BlobStore>, manifests: Arc<dyn BlobStore>) -> Self {
+        Self {
+            chunks,
+            manifests,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Create an ephemeral in-memory store.
+    pub fn in_memory() -> Self {
+        Self::new(
+            Arc::new(MemoryBlobStore::new()),
+            Arc::new(MemoryBlobStore::new()),
+        )
+    }
+
+    /// Open a filesystem store under `dir` (`dir/chunks`, `dir/manifests`).
+    pub fn open(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref();
+        Ok(Self::new(
+            Arc::new(FsBlobStore::open(dir.join("chunks"))?),
+            Arc::new(FsBlobStore::open(dir.join("manifests"))?),
+        ))
+    }
+
+    /// Override the chunk size used for new uploads.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero or exceeds [`MAX_CHUNK_SIZE`].
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(
+            chunk_size > 0 && chunk_size <= MAX_CHUNK_SIZE,
+            "chunk size must be within 1..={MAX_CHUNK_SIZE}"
+        );
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Chunk size used for new uploads.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Chunk and store `data`, returning its manifest.
+    pub fn put(&self, data: &[u8]) -> Result<ContentManifest, ContentError> {
+        let manifest = ContentManifest::from_data(data, self.chunk_size);
+        self.write(&manifest, data)?;
+        Ok(manifest)
+    }
+
+    /// Chunk and store `data` only if its root equals `expected`.
+    pub fn put_verified(
+        &self,
+        expected: &ContentHash,
+        data: &[u8],
+    ) -> Result<ContentManifest, ContentError> {
+        let manifest = ContentManifest::from_data(data, self.chunk_size);
+        if manifest.root != *expected {
+            return Err(ContentError::HashMismatch {
+                expected: expected.to_hex(),
+                actual: manifest.root.to_hex(),
+            });
+        }
+        self.write(&manifest, data)?;
+        Ok(manifest)
+    }
+
+    fn write(&self, manifest: &ContentManifest, data: &[u8]) -> Result<(), ContentError> {
+        for (hash, chunk) in manifest
+            .chunks
+            .iter()
+            .zip(data.chunks(manifest.chunk_size as usize))
+        {
+            if !self.chunks.contains(hash)? {
+                self.chunks.put(hash, chunk)?;
+            }
+        }
+        self.store_manifest(manifest)
+    }
+
+    fn store_manifest(&self, manifest: &ContentManifest) -> Result<(), ContentError> {
+        let encoded = serde_json::to_vec(manifest).map_err(anyhow::Error::from)?;
+        self.manifests.put(&manifest.root, &encoded)?;
+        Ok(())
+    }
+
+    /// Indices of the chunks of `manifest` that are not yet stored locally.
+    pub fn missing_chunks(&self, manifest: &ContentManifest) -> Result<Vec<usize>, ContentError> {
+        let mut missing = Vec::new();
+        for (index, hash) in manifest.chunks.iter().enumerate() {
+            if !self.chunks.contains(hash)? {
+                missing.push(index);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Store a single chunk fetched from elsewhere, verifying it against
+    /// `manifest` first.
+    pub fn put_chunk(
+        &self,
+        manifest: &ContentManifest,
+        index: usize,
+        bytes: &[u8],
+    ) -> Result<(), ContentError> {
+        let hash = manifest
+            .chunks
+            .get(index)
+            .ok_or_else(|| ContentError::InvalidManifest(format!("no chunk at index {index}")))?;
+        let span = manifest.chunk_span(index);
+        if bytes.len() as u64 != span.end - span.start || ContentHash::from_data(bytes) != *hash {
+            return Err(ContentError::CorruptChunk {
+                index,
+                hash: hash.to_hex(),
+            });
+        }
+        self.chunks.put(hash, bytes)?;
+        Ok(())
+    }
+
+    /// Record `manifest` as complete once every chunk is stored. Until then
+    /// the content is not reported by [`ContentStore::contains`], so partial
+    /// downloads can be resumed with [`ContentStore::missing_chunks`].
+    pub fn commit_manifest(&self, manifest: &ContentManifest) -> Result<(), ContentError> {
+        manifest.verify()?;
+        if let Some(index) = self.missing_chunks(manifest)?.first().copied() {
+            return Err(ContentError::MissingChunk {
+                index,
+                hash: manifest.chunks[index].to_hex(),
+            });
+        }
+        self.store_manifest(manifest)
+    }
+
+    /// Fetch a chunk by hash, returning `None` if it is absent or fails
+    /// verification.
+    pub fn chunk(&self, hash: &ContentHash) -> Result<Option<Vec<u8>>, ContentError> {
+        Ok(self
+            .chunks
+            .get(hash)?
+            .filter(|bytes| ContentHash::from_data(bytes) == *hash))
+    }
+
+    /// Whether content with the given root is stored locally.
+    pub fn contains(&self, root: &ContentHash) -> Result<bool, ContentError> {
+        Ok(self.manifests.contains(root)?)
+    }
+
+    /// Load and verify the manifest for `root`.
+    pub fn manifest(&self, root: &ContentHash) -> Result<Option<ContentManifest>, ContentError> {
+        let Some(encoded) = self.manifests.get(root)? else {
+            return Ok(None);
+        };
+        let manifest: ContentManifest = serde_json::from_slice(&encoded)
+            .map_err(|e| ContentError::InvalidManifest(e.to_string()))?;
+        if manifest.root != *root {
+            return Err(ContentError::HashMismatch {
+                expected: root.to_hex(),
+                actual: manifest.root.to_hex(),
+            });
+        }
+        manifest.verify()?;
+        Ok(Some(manifest))
+    }
+
+    /// Read chunk `index` of `manifest`, verifying it against its hash.
+    pub fn read_chunk(
+        &self,
+        manifest: &ContentManifest,
+        index: usize,
+    ) -> Result<Vec<u8>, ContentError> {
+        let hash = manifest
+            .chunks
+            .get(index)
+            .ok_or_else(|| ContentError::InvalidManifest(format!("no chunk at index {index}")))?;
+        let bytes = self
+            .chunks
+            .get(hash)?
+            .ok_or_else(|| ContentError::MissingChunk {
+                index,
+                hash: hash.to_hex(),
+            })?;
+        let span = manifest.chunk_span(index);
+        if bytes.len() as u64 != span.end - span.start || ContentHash::from_data(&bytes) != *hash {
+            return Err(ContentError::CorruptChunk {
+                index,
+                hash: hash.to_hex(),
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Read chunk `index` of `manifest` together with its Merkle proof.
+    pub fn prove_chunk(
+        &self,
+        manifest: &ContentManifest,
+        index: usize,
+    ) -> Result<ChunkProof, ContentError> {
+        let chunk = self.read_chunk(manifest, index)?;
+        let proof = manifest
+            .proof(index)
+            .ok_or_else(|| ContentError::InvalidManifest(format!("no chunk at index {index}")))?;
+        Ok(ChunkProof { chunk, proof })
+    }
+
+    /// Read the byte range `range` of the content with `root`, verifying every
+    /// chunk it touches.
+    pub fn read_range(
+        &self,
+        root: &ContentHash,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ContentError> {
+        let manifest = self
+            .manifest(root)?
+            .ok_or_else(|| ContentError::NotFound(root.to_hex()))?;
+        self.read_manifest_range(&manifest, range)
+    }
+
+    /// Read a byte range from an already loaded manifest.
+    pub fn read_manifest_range(
+        &self,
+        manifest: &ContentManifest,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ContentError> {
+        if range.start > range.end || range.end > manifest.size_bytes {
+            return Err(ContentError::InvalidRange {
+                start: range.start,
+                end: range.end,
+                size: manifest.size_bytes,
+            });
+        }
+        let mut out = Vec::with_capacity((range.end - range.start) as usize);
+        for index in manifest.chunks_for_range(&range) {
+            let chunk = self.read_chunk(manifest, index)?;
+            let span = manifest.chunk_span(index);
+            let from = range.start.max(span.start) - span.start;
+            let to = range.end.min(span.end) - span.start;
+            out.extend_from_slice(&chunk[from as usize..to as usize]);
+        }
+        Ok(out)
+    }
+
+    /// Read the full content with `root`.
+    pub fn read(&self, root: &ContentHash) -> Result<Vec<u8>, ContentError> {
+        let manifest = self
+            .manifest(root)?
+            .ok_or_else(|| ContentError::NotFound(root.to_hex()))?;
+        self.read_manifest_range(&manifest, 0..manifest.size_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_single_chunk_root_matches_plain_hash() {
+        let data = b"small file";
+        assert_eq!(
+            content_root(data, DEFAULT_CHUNK_SIZE),
+            ContentHash::from_data(data)
+        );
+        assert_eq!(content_root(&[], 16), ContentHash::from_data(&[]));
+    }
+
+    #[test]
+    fn test_manifest_chunks_and_verifies() {
+        let data = sample(100);
+        let manifest = ContentManifest::from_data(&data, 32);
+        assert_eq!(manifest.chunk_count(), 4);
+        assert_eq!(manifest.chunk_span(3), 96..100);
+        assert_eq!(manifest.chunks_for_range(&(30..70)), 0..3);
+        assert_eq!(manifest.root, content_root(&data, 32));
+        manifest.verify().unwrap();
+
+        let mut tampered = manifest.clone();
+        tampered.chunks.swap(0, 1);
+        assert!(matches!(
+            tampered.verify(),
+            Err(ContentError::HashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        for chunk_count in 1..=9 {
+            let data = sample(chunk_count * 8);
+            let manifest = ContentManifest::from_data(&data, 8);
+            for index in 0..chunk_count {
+                let proof = manifest.proof(index).unwrap();
+                assert!(proof.verify(&manifest.root, &manifest.chunks[index]));
+                let other = manifest.chunks[(index + 1) % chunk_count];
+                if other != manifest.chunks[index] {
+                    assert!(!proof.verify(&manifest.root, &other));
+                }
+
+                let mut moved = proof.clone();
+                moved.index = (index as u64 + 1) % chunk_count as u64;
+                if chunk_count > 1 {
+                    assert!(!moved.verify(&manifest.root, &manifest.chunks[index]));
+                }
+            }
+            assert!(manifest.proof(chunk_count).is_none());
+        }
+
+        let store = ContentStore::in_memory().with_chunk_size(16);
+        let manifest = store.put(&sample(70)).unwrap();
+        let chunk_proof = store.prove_chunk(&manifest, 2).unwrap();
+        assert!(chunk_proof.verify(&manifest.root));
+        let mut forged = chunk_proof;
+        forged.chunk[0] ^= 1;
+        assert!(!forged.verify(&manifest.root));
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_ranges() {
+        let store = ContentStore::in_memory().with_chunk_size(16);
+        let data = sample(70);
+        let manifest = store.put(&data).unwrap();
+
+        assert!(store.contains(&manifest.root).unwrap());
+        assert_eq!(store.read(&manifest.root).unwrap(), data);
+        assert_eq!(
+            store.read_range(&manifest.root, 10..40).unwrap(),
+            &data[10..40]
+        );
+        assert!(store.read_range(&manifest.root, 5..5).unwrap().is_empty());
+        assert!(matches!(
+            store.read_range(&manifest.root, 60..71),
+            Err(ContentError::InvalidRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_put_verified_rejects_wrong_hash() {
+        let store = ContentStore::in_memory();
+        let wrong = ContentHash::from_data(b"other");
+        assert!(matches!(
+            store.put_verified(&wrong, b"payload"),
+            Err(ContentError::HashMismatch { .. })
+        ));
+        assert!(!store.contains(&wrong).unwrap());
+
+        let root = ContentHash::from_data(b"payload");
+        store.put_verified(&root, b"payload").unwrap();
+        assert_eq!(store.read(&root).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_node_preimage_is_not_a_leaf() {
+        let data = sample(64);
+        let manifest = ContentManifest::from_data(&data, 32);
+        let (left, right) = (manifest.chunks[0], manifest.chunks[1]);
+
+        // The bytes an interior node used to hash, uploaded as one chunk
+        let mut forged = vec![0x01];
+        forged.extend_from_slice(left.as_bytes());
+        forged.extend_from_slice(right.as_bytes());
+        assert_ne!(content_root(&forged, DEFAULT_CHUNK_SIZE), manifest.root);
+
+        let store = ContentStore::in_memory();
+        assert!(matches!(
+            store.put_verified(&manifest.root, &forged),
+            Err(ContentError::HashMismatch { .. })
+        ));
+        assert!(!store.contains(&manifest.root).unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_chunk_is_detected() {
+        let chunks = MemoryBlobStore::new();
+        let store = ContentStore::new(Arc::new(chunks.clone()), Arc::new(MemoryBlobStore::new()))
+            .with_chunk_size(8);
+        let data = sample(24);
+        let manifest = store.put(&data).unwrap();
+
+        chunks.put(&manifest.chunks[1], b"garbage!").unwrap();
+        assert!(store.read_range(&manifest.root, 0..8).is_ok());
+        assert!(matches!(
+            store.read_range(&manifest.root, 4..12),
+            Err(ContentError::CorruptChunk { index: 1, .. })
+        ));
+
+        chunks.remove(&manifest.chunks[2]).unwrap();
+        assert!(matches!(
+            store.read(&manifest.root),
+            Err(ContentError::CorruptChunk { index: 1, .. })
+        ));
+        assert!(matches!(
+            store.read_range(&manifest.root, 16..24),
+            Err(ContentError::MissingChunk { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_chunks_can_be_filled_in_incrementally() {
+        let source = ContentStore::in_memory().with_chunk_size(8);
+        let data = sample(30);
+        let manifest = source.put(&data).unwrap();
+
+        let target = ContentStore::in_memory();
+        assert_eq!(target.missing_chunks(&manifest).unwrap(), vec![0, 1, 2, 3]);
+
+        let chunk = source.chunk(&manifest.chunks[2]).unwrap().unwrap();
+        assert!(matches!(
+            target.put_chunk(&manifest, 1, &chunk),
+            Err(ContentError::CorruptChunk { index: 1, .. })
+        ));
+        target.put_chunk(&manifest, 2, &chunk).unwrap();
+        assert_eq!(target.missing_chunks(&manifest).unwrap(), vec![0, 1, 3]);
+        assert!(matches!(
+            target.commit_manifest(&manifest),
+            Err(ContentError::MissingChunk { index: 0, .. })
+        ));
+        assert!(!target.contains(&manifest.root).unwrap());
+
+        for index in [0, 1, 3] {
+            let chunk = source.chunk(&manifest.chunks[index]).unwrap().unwrap();
+            target.put_chunk(&manifest, index, &chunk).unwrap();
+        }
+        target.commit_manifest(&manifest).unwrap();
+        assert_eq!(target.read(&manifest.root).unwrap(), data);
+    }
+
+    #[test]
+    fn test_fs_store_persists_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = sample(1000);
+        let root = {
+            let store = ContentStore::open(dir.path()).unwrap().with_chunk_size(128);
+            store.put(&data).unwrap().root
+        };
+        let reopened = ContentStore::open(dir.path()).unwrap();
+        assert_eq!(reopened.read(&root).unwrap(), data);
+    }
+}
@@ -1,9 +1,17 @@
 //! File descriptor data model for IPNDHT file tracking.
 
+use crate::encryption::FileEncryption;
 use ippan_crypto::hash_functions::{Blake3, HashFunction};
+use ippan_crypto::KeyPair;
 use ippan_time::{HashTimer, IppanTimeMicros};
 use serde::{Deserialize, Serialize};
 
+/// Domain separator for descriptor signatures.
+const DESCRIPTOR_SIGNING_DOMAIN: &[u8] = b"ippan-file-descriptor-v1";
+
+/// Domain separator for recipient envelope signatures.
+const RECIPIENTS_SIGNING_DOMAIN: &[u8] = b"ippan-file-recipients-v1";
+
 /// Unique identifier for a file descriptor, derived from HashTimer.
 /// This provides deterministic, time-ordered IDs for file metadata.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
@@ -15,6 +23,20 @@ impl FileId {
         Self(timer.digest())
     }
 
+    /// Derive the id of a descriptor created by `owner` at `created_at_us`.
+    pub fn derive(content_hash: &ContentHash, owner: &[u8; 32], created_at_us: u64) -> Self {
+        // context="file", domain=content_hash, payload=owner
+        let timer = HashTimer::derive(
+            "file",
+            IppanTimeMicros(created_at_us),
+            content_hash.as_bytes(), // domain
+            owner,                   // payload
+            &[0u8; 32],              // nonce (deterministic, use zero)
+            owner,                   // node_id (use owner for consistency)
+        );
+        Self::from_hashtimer(&timer)
+    }
+
     /// Create from raw bytes.
     pub fn from_bytes(bytes: [u8; 32]) -> Self {
         Self(bytes)
@@ -120,6 +142,14 @@ pub struct FileDescriptor {
     /// Optional tags for categorization.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+
+    /// Encryption metadata when the content is encrypted client-side.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<FileEncryption>,
+
+    /// Owner's Ed25519 signature over [`FileDescriptor::signing_digest`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signature: Vec<u8>,
 }
 
 impl FileDescriptor {
@@ -147,31 +177,143 @@ impl FileDescriptor {
         mime_type: Option<String>,
         tags: Vec<String>,
     ) -> Self {
-        // Derive deterministic ID: context="file", domain=content_hash, payload=owner
-        let timer = HashTimer::derive(
-            "file",
-            time,
-            content_hash.as_bytes(), // domain
-            &owner,                  // payload
-            &[0u8; 32],              // nonce (deterministic, use zero)
-            &owner,                  // node_id (use owner for consistency)
-        );
-
-        let id = FileId::from_hashtimer(&timer);
-
         Self {
-            id,
+            id: FileId::derive(&content_hash, &owner, time.0),
             content_hash,
             owner,
             size_bytes,
             created_at_us: time.0,
             mime_type,
             tags,
+            encryption: None,
+            signature: Vec::new(),
         }
     }
 
+    /// Mark the descriptor as describing encrypted content.
+    pub fn with_encryption(mut self, encryption: FileEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Digest signed by the owner.
+    ///
+    /// Covers the id (which already binds owner and creation time), content
+    /// hash, size, MIME type, tags and, for encrypted files, the cipher and
+    /// nonce. Recipient envelopes are covered by [`Self::recipients_digest`]
+    /// instead, so readers can change without touching the L1 anchor.
+    pub fn signing_digest(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(DESCRIPTOR_SIGNING_DOMAIN);
+        hasher.update(self.id.as_bytes());
+        hasher.update(self.content_hash.as_bytes());
+        hasher.update(&self.size_bytes.to_be_bytes());
+        match &self.mime_type {
+            Some(mime) => {
+                hasher.update(&[1]);
+                hasher.update(&(mime.len() as u32).to_be_bytes());
+                hasher.update(mime.as_bytes());
+            }
+            None => {
+                hasher.update(&[0]);
+            }
+        }
+        hasher.update(&(self.tags.len() as u32).to_be_bytes());
+        for tag in &self.tags {
+            hasher.update(&(tag.len() as u32).to_be_bytes());
+            hasher.update(tag.as_bytes());
+        }
+        if let Some(encryption) = &self.encryption {
+            encryption.update_digest(&mut hasher);
+        }
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Digest of an encrypted file's versioned envelope set, bound to the
+    /// descriptor it belongs to. `None` for unencrypted files.
+    pub fn recipients_digest(&self) -> Option<[u8; 32]> {
+        let encryption = self.encryption.as_ref()?;
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(RECIPIENTS_SIGNING_DOMAIN);
+        hasher.update(&self.signing_digest());
+        encryption.update_recipients_digest(&mut hasher);
+        Some(*hasher.finalize().as_bytes())
+    }
+
+    /// Version of the recipient envelope set, if the file is encrypted.
+    pub fn recipients_version(&self) -> Option<u64> {
+        self.encryption
+            .as_ref()
+            .map(|encryption| encryption.version)
+    }
+
+    /// Sign the descriptor, and the envelope set of encrypted files, with the
+    /// owner's Ed25519 private key.
+    pub fn sign(&mut self, private_key: &[u8; 32]) -> Result<(), String> {
+        let keypair = self.owner_keypair(private_key)?;
+        self.signature = keypair.sign(&self.signing_digest()).to_vec();
+        if self.encryption.is_some() {
+            self.sign_recipients(private_key)?;
+        }
+        Ok(())
+    }
+
+    /// Re-sign only the envelope set, e.g. after changing recipients and
+    /// raising the encryption `version`.
+    pub fn sign_recipients(&mut self, private_key: &[u8; 32]) -> Result<(), String> {
+        let keypair = self.owner_keypair(private_key)?;
+        let digest = self
+            .recipients_digest()
+            .ok_or_else(|| "Descriptor is not encrypted".to_string())?;
+        if let Some(encryption) = self.encryption.as_mut() {
+            encryption.recipients_signature = keypair.sign(&digest).to_vec();
+        }
+        Ok(())
+    }
+
+    fn owner_keypair(&self, private_key: &[u8; 32]) -> Result<KeyPair, String> {
+        let keypair = KeyPair::from_private_key(private_key).map_err(|e| e.to_string())?;
+        if keypair.public_key() != self.owner {
+            return Err("Signing key does not match descriptor owner".to_string());
+        }
+        Ok(keypair)
+    }
+
+    /// Verify the owner's signature, and for encrypted files the owner's
+    /// signature over the envelope set.
+    pub fn verify_signature(&self) -> Result<(), String> {
+        self.verify_owner_signature("Signature", &self.signature, &self.signing_digest())?;
+        if let (Some(encryption), Some(digest)) = (&self.encryption, self.recipients_digest()) {
+            self.verify_owner_signature(
+                "Recipients signature",
+                &encryption.recipients_signature,
+                &digest,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn verify_owner_signature(
+        &self,
+        label: &str,
+        signature: &[u8],
+        digest: &[u8; 32],
+    ) -> Result<(), String> {
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| format!("{label} must be 64 bytes, got {}", signature.len()))?;
+        KeyPair::verify_with_public_key(digest, &signature, &self.owner)
+            .map_err(|_| format!("Invalid owner {}", label.to_lowercase()))
+    }
+
     /// Validate the descriptor fields.
     pub fn validate(&self) -> Result<(), String> {
+        if self.id != FileId::derive(&self.content_hash, &self.owner, self.created_at_us) {
+            return Err(
+                "File id is not derived from content hash, owner and creation time".to_string(),
+            );
+        }
+
         if self.size_bytes == 0 {
             return Err("File size cannot be zero".to_string());
         }
@@ -192,6 +334,10 @@ impl FileDescriptor {
             }
         }
 
+        if let Some(encryption) = &self.encryption {
+            encryption.validate().map_err(|e| e.to_string())?;
+        }
+
         Ok(())
     }
 }
@@ -266,6 +412,14 @@ mod tests {
         desc_zero_size.size_bytes = 0;
         assert!(desc_zero_size.validate().is_err());
 
+        // Id claimed by another owner or creation time
+        let mut desc_foreign_id = desc.clone();
+        desc_foreign_id.owner = [2u8; 32];
+        assert!(desc_foreign_id.validate().is_err());
+        let mut desc_shifted_time = desc.clone();
+        desc_shifted_time.created_at_us += 1;
+        assert!(desc_shifted_time.validate().is_err());
+
         // Too many tags
         let desc_many_tags = FileDescriptor::new(
             content_hash,
@@ -301,4 +455,96 @@ mod tests {
         assert_eq!(desc.tags, tags);
         assert!(desc.validate().is_ok());
     }
+
+    #[test]
+    fn test_descriptor_signature() {
+        let keypair = KeyPair::generate();
+        let mut desc = FileDescriptor::new(
+            ContentHash::from_data(b"signed"),
+            keypair.public_key(),
+            6,
+            Some("text/plain".to_string()),
+            vec!["doc".to_string()],
+        );
+        assert!(desc.verify_signature().is_err(), "unsigned descriptor");
+
+        desc.sign(&keypair.private_key()).unwrap();
+        assert!(desc.verify_signature().is_ok());
+
+        let mut tampered = desc.clone();
+        tampered.tags.push("extra".to_string());
+        assert!(tampered.verify_signature().is_err());
+
+        let mut stolen = desc.clone();
+        stolen.size_bytes = 7;
+        assert!(stolen.verify_signature().is_err());
+
+        let other = KeyPair::generate();
+        assert!(desc.clone().sign(&other.private_key()).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_descriptor_signs_recipients() {
+        let owner = KeyPair::generate();
+        let reader = KeyPair::generate();
+        let file =
+            crate::encryption::encrypt_file(b"secret", &[owner.public_key(), reader.public_key()])
+                .unwrap();
+        let mut desc = FileDescriptor::new(
+            ContentHash::from_data(&file.ciphertext),
+            owner.public_key(),
+            file.ciphertext.len() as u64,
+            None,
+            vec![],
+        )
+        .with_encryption(file.encryption);
+        let plain_digest = FileDescriptor {
+            encryption: None,
+            ..desc.clone()
+        }
+        .signing_digest();
+        assert_ne!(desc.signing_digest(), plain_digest);
+        desc.sign(&owner.private_key()).unwrap();
+        assert!(desc.validate().is_ok());
+
+        let mut revoked = desc.clone();
+        revoked
+            .encryption
+            .as_mut()
+            .unwrap()
+            .revoke_recipient(&reader.public_key());
+        assert!(revoked.verify_signature().is_err());
+        revoked.encryption.as_mut().unwrap().version = 1;
+        revoked.sign_recipients(&owner.private_key()).unwrap();
+        assert!(revoked.verify_signature().is_ok());
+
+        // The anchored digest and owner signature survive the update
+        assert_eq!(revoked.signing_digest(), desc.signing_digest());
+        assert_eq!(revoked.signature, desc.signature);
+        assert_ne!(revoked.recipients_digest(), desc.recipients_digest());
+    }
+
+    #[test]
+    fn test_recipients_signature_bound_to_descriptor() {
+        let owner = KeyPair::generate();
+        let file = crate::encryption::encrypt_file(b"secret", &[owner.public_key()]).unwrap();
+        let build = |data: &[u8]| {
+            let mut desc = FileDescriptor::new(
+                ContentHash::from_data(data),
+                owner.public_key(),
+                data.len() as u64,
+                None,
+                vec![],
+            )
+            .with_encryption(file.encryption.clone());
+            desc.sign(&owner.private_key()).unwrap();
+            desc
+        };
+        let first = build(b"one");
+        let mut second = build(b"two");
+
+        // An envelope set signed for one file cannot be moved to another
+        second.encryption = first.encryption.clone();
+        assert!(second.verify_signature().is_err());
+    }
 }
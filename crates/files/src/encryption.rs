@@ -0,0 +1,344 @@
+//! Client-side file encryption with per-recipient key envelopes.
+//!
+//! Private files are encrypted before upload with a random AES-256-GCM content
+//! key, so nodes only ever store and serve ciphertext (the descriptor's
+//! `content_hash` is the Merkle root of the ciphertext). The content key is
+//! sealed to each recipient's Ed25519 key with [`ippan_crypto::ecies`] and the
+//! resulting envelopes travel in the descriptor's [`FileEncryption`] block,
+//! which the owner signs along with the rest of the descriptor.
+//!
+//! Adding or revoking a recipient re-wraps the content key into a new envelope
+//! set; the ciphertext is unchanged. Revocation therefore only stops future
+//! key retrieval: a recipient who already unwrapped the key can still decrypt
+//! this content, so truly cutting access means re-encrypting under a new key.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use ippan_crypto::ecies::{self, EciesError, SEAL_OVERHEAD};
+use ippan_crypto::KeyPair;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// The only supported content cipher.
+pub const FILE_ENCRYPTION_ALGORITHM: &str = "AES-256-GCM";
+
+/// Maximum number of recipient envelopes per descriptor.
+pub const MAX_FILE_RECIPIENTS: usize = 64;
+
+/// ECIES context for content key envelopes.
+const KEY_ENVELOPE_INFO: &[u8] = b"ippan-file-key-v1";
+
+/// Length of a sealed 32-byte content key.
+const WRAPPED_KEY_LEN: usize = 32 + SEAL_OVERHEAD;
+
+/// Errors raised while encrypting files or managing recipients.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum FileEncryptionError {
+    #[error("unsupported encryption algorithm {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("an encrypted file needs at least one recipient")]
+    NoRecipients,
+    #[error("too many recipients (max {MAX_FILE_RECIPIENTS})")]
+    TooManyRecipients,
+    #[error("recipient {0} is listed twice")]
+    DuplicateRecipient(String),
+    #[error("malformed key envelope for {0}")]
+    MalformedEnvelope(String),
+    #[error("key is not a recipient of this file")]
+    NotARecipient,
+    #[error("key envelope error: {0}")]
+    Envelope(#[from] EciesError),
+    #[error("content encryption failed")]
+    EncryptionFailed,
+    #[error("content decryption failed")]
+    DecryptionFailed,
+}
+
+/// Content key sealed to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEnvelope {
+    /// Recipient Ed25519 public key.
+    pub recipient: [u8; 32],
+    /// Content key sealed with [`ippan_crypto::ecies::seal`].
+    pub wrapped_key: Vec<u8>,
+}
+
+/// Encryption metadata carried by an encrypted file's descriptor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEncryption {
+    /// Content cipher, always [`FILE_ENCRYPTION_ALGORITHM`].
+    pub algorithm: String,
+    /// AES-GCM nonce used for the content.
+    pub nonce: [u8; 12],
+    /// One envelope per recipient.
+    pub recipients: Vec<KeyEnvelope>,
+    /// Version of the envelope set; every recipient update must raise it.
+    #[serde(default)]
+    pub version: u64,
+    /// Owner's signature over [`FileDescriptor::recipients_digest`].
+    ///
+    /// [`FileDescriptor::recipients_digest`]: crate::FileDescriptor::recipients_digest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients_signature: Vec<u8>,
+}
+
+/// Output of [`encrypt_file`].
+#[derive(Debug, Clone)]
+pub struct EncryptedFile {
+    /// Ciphertext to upload in place of the plaintext.
+    pub ciphertext: Vec<u8>,
+    /// Content key; keep it to add recipients later.
+    pub content_key: [u8; 32],
+    /// Metadata to attach to the descriptor.
+    pub encryption: FileEncryption,
+}
+
+/// Encrypt `plaintext` under a fresh content key sealed to every recipient.
+///
+/// Include the owner's own key in `recipients` to be able to decrypt later.
+pub fn encrypt_file(
+    plaintext: &[u8],
+    recipients: &[[u8; 32]],
+) -> Result<EncryptedFile, FileEncryptionError> {
+    let mut content_key = [0u8; 32];
+    OsRng.fill_bytes(&mut content_key);
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher(&content_key)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| FileEncryptionError::EncryptionFailed)?;
+    let mut encryption = FileEncryption {
+        algorithm: FILE_ENCRYPTION_ALGORITHM.to_string(),
+        nonce,
+        recipients: Vec::new(),
+        version: 0,
+        recipients_signature: Vec::new(),
+    };
+    for recipient in recipients {
+        encryption.add_recipient(&content_key, recipient)?;
+    }
+    encryption.validate()?;
+
+    Ok(EncryptedFile {
+        ciphertext,
+        content_key,
+        encryption,
+    })
+}
+
+fn cipher(content_key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(content_key))
+}
+
+impl FileEncryption {
+    /// Seal `content_key` to `recipient`, replacing any existing envelope.
+    pub fn add_recipient(
+        &mut self,
+        content_key: &[u8; 32],
+        recipient: &[u8; 32],
+    ) -> Result<(), FileEncryptionError> {
+        let wrapped_key = ecies::seal(recipient, content_key, KEY_ENVELOPE_INFO)?;
+        match self
+            .recipients
+            .iter_mut()
+            .find(|envelope| envelope.recipient == *recipient)
+        {
+            Some(envelope) => envelope.wrapped_key = wrapped_key,
+            None => {
+                if self.recipients.len() >= MAX_FILE_RECIPIENTS {
+                    return Err(FileEncryptionError::TooManyRecipients);
+                }
+                self.recipients.push(KeyEnvelope {
+                    recipient: *recipient,
+                    wrapped_key,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop `recipient`'s envelope. Returns whether it was present.
+    pub fn revoke_recipient(&mut self, recipient: &[u8; 32]) -> bool {
+        let before = self.recipients.len();
+        self.recipients
+            .retain(|envelope| envelope.recipient != *recipient);
+        self.recipients.len() != before
+    }
+
+    /// Whether `recipient` holds an envelope.
+    pub fn has_recipient(&self, recipient: &[u8; 32]) -> bool {
+        self.recipients
+            .iter()
+            .any(|envelope| envelope.recipient == *recipient)
+    }
+
+    /// Recover the content key with a recipient's Ed25519 private key.
+    pub fn unwrap_key(&self, private_key: &[u8; 32]) -> Result<[u8; 32], FileEncryptionError> {
+        let public_key = KeyPair::from_private_key(private_key)
+            .map_err(|_| FileEncryptionError::NotARecipient)?
+            .public_key();
+        let envelope = self
+            .recipients
+            .iter()
+            .find(|envelope| envelope.recipient == public_key)
+            .ok_or(FileEncryptionError::NotARecipient)?;
+        let key = ecies::open(private_key, &envelope.wrapped_key, KEY_ENVELOPE_INFO)?;
+        key.try_into()
+            .map_err(|_| FileEncryptionError::MalformedEnvelope(hex::encode(public_key)))
+    }
+
+    /// Decrypt downloaded ciphertext with the content key.
+    pub fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        content_key: &[u8; 32],
+    ) -> Result<Vec<u8>, FileEncryptionError> {
+        cipher(content_key)
+            .decrypt(Nonce::from_slice(&self.nonce), ciphertext)
+            .map_err(|_| FileEncryptionError::DecryptionFailed)
+    }
+
+    /// Unwrap the content key with `private_key` and decrypt `ciphertext`.
+    pub fn decrypt_with_private_key(
+        &self,
+        ciphertext: &[u8],
+        private_key: &[u8; 32],
+    ) -> Result<Vec<u8>, FileEncryptionError> {
+        let content_key = self.unwrap_key(private_key)?;
+        self.decrypt(ciphertext, &content_key)
+    }
+
+    /// Check the algorithm and envelope set.
+    pub fn validate(&self) -> Result<(), FileEncryptionError> {
+        if self.algorithm != FILE_ENCRYPTION_ALGORITHM {
+            return Err(FileEncryptionError::UnsupportedAlgorithm(
+                self.algorithm.clone(),
+            ));
+        }
+        if self.recipients.is_empty() {
+            return Err(FileEncryptionError::NoRecipients);
+        }
+        if self.recipients.len() > MAX_FILE_RECIPIENTS {
+            return Err(FileEncryptionError::TooManyRecipients);
+        }
+        let mut seen = HashSet::new();
+        for envelope in &self.recipients {
+            if !seen.insert(envelope.recipient) {
+                return Err(FileEncryptionError::DuplicateRecipient(hex::encode(
+                    envelope.recipient,
+                )));
+            }
+            if envelope.wrapped_key.len() != WRAPPED_KEY_LEN {
+                return Err(FileEncryptionError::MalformedEnvelope(hex::encode(
+                    envelope.recipient,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Feed the fields covered by the owner's descriptor signature. The
+    /// envelope set is signed separately so it can change under an anchor.
+    pub(crate) fn update_digest(&self, hasher: &mut blake3::Hasher) {
+        hasher.update(&(self.algorithm.len() as u32).to_be_bytes());
+        hasher.update(self.algorithm.as_bytes());
+        hasher.update(&self.nonce);
+    }
+
+    /// Feed the versioned envelope set covered by the recipients signature.
+    pub(crate) fn update_recipients_digest(&self, hasher: &mut blake3::Hasher) {
+        hasher.update(&self.version.to_be_bytes());
+        hasher.update(&(self.recipients.len() as u32).to_be_bytes());
+        for envelope in &self.recipients {
+            hasher.update(&envelope.recipient);
+            hasher.update(&(envelope.wrapped_key.len() as u32).to_be_bytes());
+            hasher.update(&envelope.wrapped_key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt_for_recipients() {
+        let owner = KeyPair::generate();
+        let reader = KeyPair::generate();
+        let outsider = KeyPair::generate();
+        let file = encrypt_file(
+            b"private report",
+            &[owner.public_key(), reader.public_key()],
+        )
+        .unwrap();
+        assert_ne!(file.ciphertext, b"private report");
+        assert_eq!(file.encryption.recipients.len(), 2);
+
+        for key in [&owner, &reader] {
+            assert_eq!(
+                file.encryption
+                    .decrypt_with_private_key(&file.ciphertext, &key.private_key())
+                    .unwrap(),
+                b"private report"
+            );
+        }
+        assert_eq!(
+            file.encryption
+                .decrypt_with_private_key(&file.ciphertext, &outsider.private_key()),
+            Err(FileEncryptionError::NotARecipient)
+        );
+
+        let mut tampered = file.ciphertext.clone();
+        tampered[0] ^= 1;
+        assert_eq!(
+            file.encryption.decrypt(&tampered, &file.content_key),
+            Err(FileEncryptionError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_add_and_revoke_recipients() {
+        let owner = KeyPair::generate();
+        let reader = KeyPair::generate();
+        let file = encrypt_file(b"shared later", &[owner.public_key()]).unwrap();
+        let mut encryption = file.encryption.clone();
+
+        encryption
+            .add_recipient(&file.content_key, &reader.public_key())
+            .unwrap();
+        encryption
+            .add_recipient(&file.content_key, &reader.public_key())
+            .unwrap();
+        assert_eq!(encryption.recipients.len(), 2);
+        assert_eq!(
+            encryption
+                .decrypt_with_private_key(&file.ciphertext, &reader.private_key())
+                .unwrap(),
+            b"shared later"
+        );
+
+        assert!(encryption.revoke_recipient(&reader.public_key()));
+        assert!(!encryption.revoke_recipient(&reader.public_key()));
+        assert!(!encryption.has_recipient(&reader.public_key()));
+        assert_eq!(
+            encryption.unwrap_key(&reader.private_key()),
+            Err(FileEncryptionError::NotARecipient)
+        );
+
+        encryption.revoke_recipient(&owner.public_key());
+        assert_eq!(
+            encryption.validate(),
+            Err(FileEncryptionError::NoRecipients)
+        );
+
+        let mut duplicated = file.encryption.clone();
+        duplicated.recipients.push(duplicated.recipients[0].clone());
+        assert!(matches!(
+            duplicated.validate(),
+            Err(FileEncryptionError::DuplicateRecipient(_))
+        ));
+    }
+}
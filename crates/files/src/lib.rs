@@ -2,14 +2,35 @@
 //!
 //! Provides metadata tracking and DHT-based discovery for content-addressed files.
 //! File descriptors use HashTimer-based IDs for ordering and contain content hashes,
-//! owner information, and metadata without storing the actual file content.
+//! owner information, and metadata. File content itself is chunked into a
+//! BLAKE3 Merkle DAG and kept in a local content-addressed blob store.
+//! Descriptors are signed by their owner and can be anchored on L1. Private
+//! files are encrypted client-side with per-recipient key envelopes. Storage
+//! providers can be paid to pin content through the [`pinning`] market.
 
+pub mod anchors;
+pub mod blobs;
+pub mod content;
 pub mod descriptor;
 pub mod dht;
+pub mod encryption;
+pub mod pinning;
 pub mod storage;
 
+pub use anchors::{FileAnchorError, FileOwnershipAnchor, L1FileAnchorStorage};
+pub use blobs::{BlobStore, FsBlobStore, MemoryBlobStore};
+pub use content::{
+    ChunkProof, ContentError, ContentManifest, ContentStore, MerkleProof, DEFAULT_CHUNK_SIZE,
+};
 pub use descriptor::{FileDescriptor, FileId};
 pub use dht::{DhtLookupResult, DhtPublishResult, FileDhtService};
+pub use encryption::{
+    encrypt_file, EncryptedFile, FileEncryption, FileEncryptionError, KeyEnvelope,
+};
+pub use pinning::{
+    LocalProviderSet, PinChallenge, PinDeal, PinDealId, PinDealRequest, PinDealStatus, PinOutcome,
+    PinProof, PinSettlement, PinningConfig, PinningError, PinningMarket, PinningMarketState,
+};
 pub use storage::{FileStorage, MemoryFileStorage};
 
 #[cfg(test)]
@@ -0,0 +1,766 @@
+//! Storage-provider pinning market with proofs of retrievability.
+//!
+//! A client escrows `price_per_epoch * epochs` for a provider to keep a file
+//! pinned, and the provider posts collateral. Time is split into epochs of
+//! [`PinningConfig::epoch_rounds`] rounds. When an epoch starts, the caller
+//! supplies a seed (consensus uses `derive_selection_seed` over the round
+//! HashTimer) from which every active deal gets a deterministic set of random
+//! chunk indices to prove. Providers answer with the chunks and their Merkle
+//! proofs against the content root.
+//!
+//! Settling an epoch releases one epoch of payment to each provider that
+//! answered and slashes part of the collateral of each provider that did not;
+//! slashed collateral compensates the client. A deal completes after its last
+//! epoch, returning the remaining collateral to the provider and any unearned
+//! payment to the client. After too many missed epochs it is terminated and
+//! the client receives the remaining escrow and collateral.
+//!
+//! The market only tracks deal state; moving escrowed funds and persisting
+//! [`PinningMarket::snapshot`] are left to the caller, as with the handle
+//! auction book.
+
+use crate::content::{ChunkProof, ContentStore};
+use crate::descriptor::{ContentHash, FileId};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Default epoch length in rounds (~1 hour at 100ms rounds).
+pub const DEFAULT_PIN_EPOCH_ROUNDS: u64 = 36_000;
+
+/// Default number of chunks challenged per deal and epoch.
+pub const DEFAULT_PIN_CHALLENGES_PER_EPOCH: u32 = 4;
+
+/// Default share of the remaining collateral slashed per missed epoch (10%).
+pub const DEFAULT_PIN_SLASH_BPS: u32 = 1_000;
+
+/// Default number of missed epochs after which a deal is terminated.
+pub const DEFAULT_PIN_MAX_MISSED_EPOCHS: u32 = 3;
+
+const DEAL_ID_DOMAIN: &[u8] = b"ippan-pin-deal-v1";
+const CHALLENGE_DOMAIN: &[u8] = b"ippan-pin-challenge-v1";
+
+/// Errors raised by the [`PinningMarket`].
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum PinningError {
+    #[error("invalid deal: {0}")]
+    InvalidDeal(String),
+    #[error("provider already has an active deal for file {0}")]
+    DuplicateDeal(String),
+    #[error("deal {0} not found")]
+    DealNotFound(String),
+    #[error("deal {0} is not active")]
+    DealInactive(String),
+    #[error("deal {deal} has no open challenge for epoch {epoch}")]
+    NoChallenge { deal: String, epoch: u64 },
+    #[error("challenge for deal {0} was already answered")]
+    AlreadyProven(String),
+    #[error("invalid storage proof: {0}")]
+    InvalidProof(String),
+}
+
+/// Tunables for the pinning market.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinningConfig {
+    /// Rounds per epoch.
+    pub epoch_rounds: u64,
+    /// Chunks challenged per deal and epoch.
+    pub challenges_per_epoch: u32,
+    /// Share of the remaining collateral slashed per missed epoch, in basis points.
+    pub slash_bps: u32,
+    /// A deal is terminated once it has missed more than this many epochs.
+    pub max_missed_epochs: u32,
+}
+
+impl Default for PinningConfig {
+    fn default() -> Self {
+        Self {
+            epoch_rounds: DEFAULT_PIN_EPOCH_ROUNDS,
+            challenges_per_epoch: DEFAULT_PIN_CHALLENGES_PER_EPOCH,
+            slash_bps: DEFAULT_PIN_SLASH_BPS,
+            max_missed_epochs: DEFAULT_PIN_MAX_MISSED_EPOCHS,
+        }
+    }
+}
+
+/// Identifier of a pinning deal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PinDealId(pub [u8; 32]);
+
+impl PinDealId {
+    /// Hex encoding of the id.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+/// Terms proposed when opening a deal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinDealRequest {
+    pub file_id: FileId,
+    /// Merkle root of the pinned content.
+    pub content_root: ContentHash,
+    /// Number of chunks under `content_root`.
+    pub chunk_count: u64,
+    /// Account paying for the deal.
+    pub client: [u8; 32],
+    /// Account pinning the content.
+    pub provider: [u8; 32],
+    /// Payment released per proven epoch.
+    pub price_per_epoch: u128,
+    /// Number of epochs the content must stay pinned.
+    pub epochs: u64,
+    /// Collateral posted by the provider.
+    pub collateral: u128,
+}
+
+impl PinDealRequest {
+    /// Total payment the client has to escrow.
+    pub fn total_payment(&self) -> Option<u128> {
+        self.price_per_epoch.checked_mul(self.epochs as u128)
+    }
+}
+
+/// Lifecycle of a deal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinDealStatus {
+    Active,
+    /// Every epoch has been settled.
+    Completed,
+    /// The provider missed too many epochs.
+    Terminated,
+}
+
+/// A pinning deal and its remaining escrow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinDeal {
+    pub id: PinDealId,
+    pub terms: PinDealRequest,
+    /// First epoch in which the deal is challenged.
+    pub first_epoch: u64,
+    /// Client payment still held in escrow.
+    pub escrow: u128,
+    /// Provider collateral still held in escrow.
+    pub collateral: u128,
+    pub epochs_proven: u64,
+    pub epochs_missed: u64,
+    pub status: PinDealStatus,
+}
+
+impl PinDeal {
+    /// Whether `epoch` falls within the deal's term.
+    pub fn covers_epoch(&self, epoch: u64) -> bool {
+        epoch >= self.first_epoch && epoch - self.first_epoch < self.terms.epochs
+    }
+
+    fn epochs_settled(&self) -> u64 {
+        self.epochs_proven + self.epochs_missed
+    }
+}
+
+/// Chunks a provider has to prove for one epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinChallenge {
+    pub deal_id: PinDealId,
+    pub epoch: u64,
+    pub provider: [u8; 32],
+    pub content_root: ContentHash,
+    pub chunk_indices: Vec<u64>,
+}
+
+/// A provider's answer to a [`PinChallenge`], one proof per challenged index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinProof {
+    pub deal_id: PinDealId,
+    pub epoch: u64,
+    pub chunks: Vec<ChunkProof>,
+}
+
+/// Whether a deal answered its challenge for an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinOutcome {
+    Proven,
+    Missed,
+}
+
+/// Funds released from escrow when a deal's epoch is settled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinSettlement {
+    pub deal_id: PinDealId,
+    pub epoch: u64,
+    pub outcome: PinOutcome,
+    pub provider: [u8; 32],
+    pub client: [u8; 32],
+    /// Amount released to the provider (payment, plus collateral on completion).
+    pub to_provider: u128,
+    /// Amount released to the client (slashed collateral and refunds).
+    pub to_client: u128,
+    /// Collateral slashed this epoch; included in `to_client`.
+    pub slashed: u128,
+    /// Deal status after settlement.
+    pub status: PinDealStatus,
+}
+
+/// Derive the chunk indices challenged for `deal_id` in `epoch`.
+pub fn derive_challenge_indices(
+    seed: &[u8; 32],
+    deal_id: &PinDealId,
+    epoch: u64,
+    chunk_count: u64,
+    count: u32,
+) -> Vec<u64> {
+    if chunk_count == 0 {
+        return Vec::new();
+    }
+    (0..count)
+        .map(|i| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(CHALLENGE_DOMAIN);
+            hasher.update(seed);
+            hasher.update(&deal_id.0);
+            hasher.update(&epoch.to_be_bytes());
+            hasher.update(&i.to_be_bytes());
+            let hash = hasher.finalize();
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&hash.as_bytes()[..8]);
+            u64::from_be_bytes(word) % chunk_count
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenChallenge {
+    challenge: PinChallenge,
+    proven: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DealEntry {
+    deal: PinDeal,
+    challenge: Option<OpenChallenge>,
+}
+
+/// Serializable copy of every deal and its open challenge.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PinningMarketState {
+    deals: Vec<DealEntry>,
+}
+
+/// In-memory book of pinning deals.
+#[derive(Debug, Default)]
+pub struct PinningMarket {
+    config: PinningConfig,
+    deals: RwLock<HashMap<PinDealId, DealEntry>>,
+}
+
+impl PinningMarket {
+    pub fn new(config: PinningConfig) -> Self {
+        Self {
+            config,
+            deals: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PinningConfig {
+        &self.config
+    }
+
+    /// Epoch containing `round`.
+    pub fn epoch_for_round(&self, round: u64) -> u64 {
+        round / self.config.epoch_rounds.max(1)
+    }
+
+    /// Whether `round` is the first round of an epoch.
+    pub fn is_epoch_start(&self, round: u64) -> bool {
+        round.is_multiple_of(self.config.epoch_rounds.max(1))
+    }
+
+    /// Deal id for `request` opened at `round`.
+    pub fn deal_id(request: &PinDealRequest, round: u64) -> PinDealId {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(DEAL_ID_DOMAIN);
+        hasher.update(request.file_id.as_bytes());
+        hasher.update(&request.client);
+        hasher.update(&request.provider);
+        hasher.update(&round.to_be_bytes());
+        PinDealId(*hasher.finalize().as_bytes())
+    }
+
+    /// Validate a deal without recording it, so its funds can be moved into
+    /// escrow before [`PinningMarket::open_deal`] commits it.
+    pub fn validate_deal(&self, request: &PinDealRequest) -> Result<(), PinningError> {
+        if request.epochs == 0 {
+            return Err(PinningError::InvalidDeal("epochs must be positive".into()));
+        }
+        if request.chunk_count == 0 {
+            return Err(PinningError::InvalidDeal("content has no chunks".into()));
+        }
+        if request.total_payment().is_none() {
+            return Err(PinningError::InvalidDeal("total payment overflows".into()));
+        }
+        if request.client == request.provider {
+            return Err(PinningError::InvalidDeal(
+                "client and provider must differ".into(),
+            ));
+        }
+        let duplicate = self.deals.read().values().any(|entry| {
+            entry.deal.status == PinDealStatus::Active
+                && entry.deal.terms.file_id == request.file_id
+                && entry.deal.terms.provider == request.provider
+        });
+        if duplicate {
+            return Err(PinningError::DuplicateDeal(request.file_id.to_hex()));
+        }
+        Ok(())
+    }
+
+    /// Record a deal opened at `round`. It is first challenged in the next epoch.
+    pub fn open_deal(&self, request: PinDealRequest, round: u64) -> Result<PinDeal, PinningError> {
+        self.validate_deal(&request)?;
+        let id = Self::deal_id(&request, round);
+        let deal = PinDeal {
+            id,
+            first_epoch: self.epoch_for_round(round) + 1,
+            escrow: request.total_payment().unwrap_or_default(),
+            collateral: request.collateral,
+            epochs_proven: 0,
+            epochs_missed: 0,
+            status: PinDealStatus::Active,
+            terms: request,
+        };
+        let mut deals = self.deals.write();
+        if deals.contains_key(&id) {
+            return Err(PinningError::DuplicateDeal(deal.terms.file_id.to_hex()));
+        }
+        deals.insert(
+            id,
+            DealEntry {
+                deal: deal.clone(),
+                challenge: None,
+            },
+        );
+        Ok(deal)
+    }
+
+    /// Challenge every active deal covering `epoch`, using `seed` to pick the
+    /// chunks. Deals already challenged for `epoch` are skipped.
+    pub fn issue_challenges(&self, epoch: u64, seed: &[u8; 32]) -> Vec<PinChallenge> {
+        let mut deals = self.deals.write();
+        let mut issued = Vec::new();
+        for entry in deals.values_mut() {
+            let deal = &entry.deal;
+            if deal.status != PinDealStatus::Active || !deal.covers_epoch(epoch) {
+                continue;
+            }
+            if entry
+                .challenge
+                .as_ref()
+                .is_some_and(|open| open.challenge.epoch == epoch)
+            {
+                continue;
+            }
+            let challenge = PinChallenge {
+                deal_id: deal.id,
+                epoch,
+                provider: deal.terms.provider,
+                content_root: deal.terms.content_root,
+                chunk_indices: derive_challenge_indices(
+                    seed,
+                    &deal.id,
+                    epoch,
+                    deal.terms.chunk_count,
+                    self.config.challenges_per_epoch,
+                ),
+            };
+            entry.challenge = Some(OpenChallenge {
+                challenge: challenge.clone(),
+                proven: false,
+            });
+            issued.push(challenge);
+        }
+        issued.sort_by_key(|challenge| challenge.deal_id);
+        issued
+    }
+
+    /// Open challenge for a deal, if any.
+    pub fn challenge(&self, deal_id: &PinDealId) -> Option<PinChallenge> {
+        self.deals
+            .read()
+            .get(deal_id)
+            .and_then(|entry| entry.challenge.as_ref())
+            .map(|open| open.challenge.clone())
+    }
+
+    /// Check a provider's answer and mark the challenge as proven.
+    pub fn submit_proof(&self, proof: &PinProof) -> Result<(), PinningError> {
+        let mut deals = self.deals.write();
+        let entry = deals
+            .get_mut(&proof.deal_id)
+            .ok_or_else(|| PinningError::DealNotFound(proof.deal_id.to_hex()))?;
+        if entry.deal.status != PinDealStatus::Active {
+            return Err(PinningError::DealInactive(proof.deal_id.to_hex()));
+        }
+        let chunk_count = entry.deal.terms.chunk_count;
+        let open = entry
+            .challenge
+            .as_mut()
+            .filter(|open| open.challenge.epoch == proof.epoch)
+            .ok_or_else(|| PinningError::NoChallenge {
+                deal: proof.deal_id.to_hex(),
+                epoch: proof.epoch,
+            })?;
+        if open.proven {
+            return Err(PinningError::AlreadyProven(proof.deal_id.to_hex()));
+        }
+        if proof.chunks.len() != open.challenge.chunk_indices.len() {
+            return Err(PinningError::InvalidProof(format!(
+                "expected {} chunk proofs, got {}",
+                open.challenge.chunk_indices.len(),
+                proof.chunks.len()
+            )));
+        }
+        for (index, chunk) in open.challenge.chunk_indices.iter().zip(&proof.chunks) {
+            if chunk.proof.index != *index || chunk.proof.leaf_count != chunk_count {
+                return Err(PinningError::InvalidProof(format!(
+                    "proof does not cover challenged chunk {index}"
+                )));
+            }
+            if !chunk.verify(&open.challenge.content_root) {
+                return Err(PinningError::InvalidProof(format!(
+                    "chunk {index} does not match the content root"
+                )));
+            }
+        }
+        open.proven = true;
+        Ok(())
+    }
+
+    /// Settle every deal challenged in `epoch`.
+    pub fn settle_epoch(&self, epoch: u64) -> Vec<PinSettlement> {
+        let mut deals = self.deals.write();
+        let mut settlements = Vec::new();
+        for entry in deals.values_mut() {
+            let Some(open) = entry
+                .challenge
+                .take_if(|open| open.challenge.epoch == epoch)
+            else {
+                continue;
+            };
+            if entry.deal.status != PinDealStatus::Active {
+                continue;
+            }
+            settlements.push(self.settle_deal(&mut entry.deal, epoch, open.proven));
+        }
+        settlements.sort_by_key(|settlement| settlement.deal_id);
+        settlements
+    }
+
+    fn settle_deal(&self, deal: &mut PinDeal, epoch: u64, proven: bool) -> PinSettlement {
+        let mut to_provider = 0u128;
+        let mut to_client = 0u128;
+        let mut slashed = 0u128;
+        let outcome = if proven {
+            let payout = deal.terms.price_per_epoch.min(deal.escrow);
+            deal.escrow -= payout;
+            to_provider += payout;
+            deal.epochs_proven += 1;
+            PinOutcome::Proven
+        } else {
+            slashed = deal
+                .collateral
+                .saturating_mul(self.config.slash_bps as u128)
+                / 10_000;
+            deal.collateral -= slashed;
+            to_client += slashed;
+            deal.epochs_missed += 1;
+            PinOutcome::Missed
+        };
+
+        if deal.epochs_missed > self.config.max_missed_epochs as u64 {
+            to_client += deal.escrow + deal.collateral;
+            deal.escrow = 0;
+            deal.collateral = 0;
+            deal.status = PinDealStatus::Terminated;
+        } else if deal.epochs_settled() >= deal.terms.epochs {
+            to_provider += deal.collateral;
+            to_client += deal.escrow;
+            deal.escrow = 0;
+            deal.collateral = 0;
+            deal.status = PinDealStatus::Completed;
+        }
+
+        PinSettlement {
+            deal_id: deal.id,
+            epoch,
+            outcome,
+            provider: deal.terms.provider,
+            client: deal.terms.client,
+            to_provider,
+            to_client,
+            slashed,
+            status: deal.status,
+        }
+    }
+
+    /// Look up a deal.
+    pub fn deal(&self, deal_id: &PinDealId) -> Option<PinDeal> {
+        self.deals
+            .read()
+            .get(deal_id)
+            .map(|entry| entry.deal.clone())
+    }
+
+    /// All deals for a file, ordered by id.
+    pub fn deals_for_file(&self, file_id: &FileId) -> Vec<PinDeal> {
+        let mut deals: Vec<PinDeal> = self
+            .deals
+            .read()
+            .values()
+            .filter(|entry| entry.deal.terms.file_id == *file_id)
+            .map(|entry| entry.deal.clone())
+            .collect();
+        deals.sort_by_key(|deal| deal.id);
+        deals
+    }
+
+    /// Copy out every deal and its open challenge.
+    pub fn snapshot(&self) -> PinningMarketState {
+        let mut deals: Vec<DealEntry> = self.deals.read().values().cloned().collect();
+        deals.sort_by_key(|entry| entry.deal.id);
+        PinningMarketState { deals }
+    }
+
+    /// Replace the market's deals with a previously taken snapshot.
+    pub fn restore(&self, state: PinningMarketState) {
+        *self.deals.write() = state
+            .deals
+            .into_iter()
+            .map(|entry| (entry.deal.id, entry))
+            .collect();
+    }
+
+    /// Number of active deals.
+    pub fn active_count(&self) -> usize {
+        self.deals
+            .read()
+            .values()
+            .filter(|entry| entry.deal.status == PinDealStatus::Active)
+            .count()
+    }
+
+    /// Total client payments and collateral held for active deals.
+    pub fn escrowed(&self) -> u128 {
+        self.deals
+            .read()
+            .values()
+            .map(|entry| entry.deal.escrow + entry.deal.collateral)
+            .sum()
+    }
+}
+
+/// In-process storage providers, each answering challenges from its own
+/// content store. Intended for tests and local simulations of the market.
+#[derive(Default)]
+pub struct LocalProviderSet {
+    providers: HashMap<[u8; 32], ContentStore>,
+}
+
+impl LocalProviderSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `provider` as serving content from `store`.
+    pub fn add_provider(&mut self, provider: [u8; 32], store: ContentStore) {
+        self.providers.insert(provider, store);
+    }
+
+    /// Content store of `provider`.
+    pub fn store(&self, provider: &[u8; 32]) -> Option<&ContentStore> {
+        self.providers.get(provider)
+    }
+
+    /// Answer `challenge`, or `None` if the provider is unknown or cannot
+    /// produce every challenged chunk.
+    pub fn respond(&self, challenge: &PinChallenge) -> Option<PinProof> {
+        let store = self.providers.get(&challenge.provider)?;
+        let manifest = store.manifest(&challenge.content_root).ok()??;
+        let chunks = challenge
+            .chunk_indices
+            .iter()
+            .map(|index| store.prove_chunk(&manifest, *index as usize).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(PinProof {
+            deal_id: challenge.deal_id,
+            epoch: challenge.epoch,
+            chunks,
+        })
+    }
+
+    /// Answer every challenge the providers can, skipping the rest.
+    pub fn respond_all(&self, challenges: &[PinChallenge]) -> Vec<PinProof> {
+        challenges
+            .iter()
+            .filter_map(|challenge| self.respond(challenge))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blobs::{BlobStore, MemoryBlobStore};
+    use std::sync::Arc;
+
+    const CLIENT: [u8; 32] = [1u8; 32];
+    const HONEST: [u8; 32] = [2u8; 32];
+    const LAZY: [u8; 32] = [3u8; 32];
+
+    fn config() -> PinningConfig {
+        PinningConfig {
+            epoch_rounds: 10,
+            challenges_per_epoch: 3,
+            slash_bps: 5_000,
+            max_missed_epochs: 1,
+        }
+    }
+
+    fn request(provider: [u8; 32], root: ContentHash, chunk_count: u64) -> PinDealRequest {
+        PinDealRequest {
+            file_id: FileId::from_bytes(*root.as_bytes()),
+            content_root: root,
+            chunk_count,
+            client: CLIENT,
+            provider,
+            price_per_epoch: 100,
+            epochs: 3,
+            collateral: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_challenge_indices_are_deterministic() {
+        let deal = PinDealId([7u8; 32]);
+        let first = derive_challenge_indices(&[1u8; 32], &deal, 4, 50, 8);
+        assert_eq!(first, derive_challenge_indices(&[1u8; 32], &deal, 4, 50, 8));
+        assert_ne!(first, derive_challenge_indices(&[2u8; 32], &deal, 4, 50, 8));
+        assert!(first.iter().all(|index| *index < 50));
+        assert!(derive_challenge_indices(&[1u8; 32], &deal, 4, 0, 8).is_empty());
+    }
+
+    #[test]
+    fn test_deals_pay_honest_and_slash_lazy_providers() {
+        let market = PinningMarket::new(config());
+        let data: Vec<u8> = (0..200u32).map(|i| (i % 251) as u8).collect();
+
+        let chunks = Arc::new(MemoryBlobStore::new());
+        let honest_store =
+            ContentStore::new(chunks, Arc::new(MemoryBlobStore::new())).with_chunk_size(16);
+        let manifest = honest_store.put(&data).unwrap();
+        let lazy_chunks = Arc::new(MemoryBlobStore::new());
+        let lazy_store = ContentStore::new(lazy_chunks.clone(), Arc::new(MemoryBlobStore::new()))
+            .with_chunk_size(16);
+        lazy_store.put(&data).unwrap();
+
+        let mut providers = LocalProviderSet::new();
+        providers.add_provider(HONEST, honest_store);
+        providers.add_provider(LAZY, lazy_store);
+
+        let chunk_count = manifest.chunk_count() as u64;
+        let honest = market
+            .open_deal(request(HONEST, manifest.root, chunk_count), 5)
+            .unwrap();
+        let lazy = market
+            .open_deal(request(LAZY, manifest.root, chunk_count), 5)
+            .unwrap();
+        assert_eq!(honest.first_epoch, 1);
+        assert!(matches!(
+            market.open_deal(request(HONEST, manifest.root, chunk_count), 6),
+            Err(PinningError::DuplicateDeal(_))
+        ));
+        assert_eq!(market.escrowed(), 2 * (300 + 1_000));
+        assert!(market.issue_challenges(0, &[0u8; 32]).is_empty());
+
+        // The lazy provider drops its chunks after the deal starts.
+        for hash in &manifest.chunks {
+            lazy_chunks.remove(hash).unwrap();
+        }
+
+        let mut honest_paid = 0;
+        let mut client_refunds = 0;
+        for epoch in 1..=3u64 {
+            let challenges = market.issue_challenges(epoch, &[epoch as u8; 32]);
+            for proof in providers.respond_all(&challenges) {
+                market.submit_proof(&proof).unwrap();
+            }
+            for settlement in market.settle_epoch(epoch) {
+                if settlement.deal_id == honest.id {
+                    assert_eq!(settlement.outcome, PinOutcome::Proven);
+                    honest_paid += settlement.to_provider;
+                } else {
+                    assert_eq!(settlement.outcome, PinOutcome::Missed);
+                    assert_eq!(settlement.to_provider, 0);
+                    client_refunds += settlement.to_client;
+                }
+            }
+        }
+
+        assert_eq!(honest_paid, 300 + 1_000);
+        assert_eq!(client_refunds, 300 + 1_000);
+        assert_eq!(
+            market.deal(&honest.id).unwrap().status,
+            PinDealStatus::Completed
+        );
+        let lazy = market.deal(&lazy.id).unwrap();
+        assert_eq!(lazy.status, PinDealStatus::Terminated);
+        assert_eq!(lazy.epochs_missed, 2);
+        assert_eq!(market.escrowed(), 0);
+    }
+
+    #[test]
+    fn test_submit_proof_rejects_forgeries() {
+        let market = PinningMarket::new(config());
+        let store = ContentStore::in_memory().with_chunk_size(8);
+        let data: Vec<u8> = (0..64).collect();
+        let manifest = store.put(&data).unwrap();
+        let deal = market
+            .open_deal(request(HONEST, manifest.root, 8), 0)
+            .unwrap();
+        let mut providers = LocalProviderSet::new();
+        providers.add_provider(HONEST, store);
+
+        let challenge = market.issue_challenges(1, &[4u8; 32]).remove(0);
+        let proof = providers.respond(&challenge).unwrap();
+
+        let mut stale = proof.clone();
+        stale.epoch = 2;
+        assert!(matches!(
+            market.submit_proof(&stale),
+            Err(PinningError::NoChallenge { .. })
+        ));
+
+        let mut moved = proof.clone();
+        moved.chunks[0].proof.index = (moved.chunks[0].proof.index + 1) % 8;
+        assert!(matches!(
+            market.submit_proof(&moved),
+            Err(PinningError::InvalidProof(_))
+        ));
+
+        let mut short = proof.clone();
+        short.chunks.pop();
+        assert!(matches!(
+            market.submit_proof(&short),
+            Err(PinningError::InvalidProof(_))
+        ));
+
+        market.submit_proof(&proof).unwrap();
+        assert!(matches!(
+            market.submit_proof(&proof),
+            Err(PinningError::AlreadyProven(_))
+        ));
+        assert_eq!(market.challenge(&deal.id).unwrap().epoch, 1);
+    }
+}
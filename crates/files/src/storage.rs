@@ -80,7 +80,10 @@ impl FileStorage for MemoryFileStorage {
         // Update owner index
         {
             let mut by_owner = self.inner.by_owner.write();
-            by_owner.entry(owner).or_default().push(id);
+            let ids = by_owner.entry(owner).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
         }
 
         // Update time index
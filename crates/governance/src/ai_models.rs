@@ -337,6 +337,7 @@ impl ActivationManager {
 // -----------------------------------------------------------------------------
 // 🧠 AiModelGovernance — foundation-level governance over AI models
 // -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiModelGovernance {
     model_registry: HashMap<String, ModelRegistryEntry>,
     active_proposals: HashMap<String, AiModelProposal>,
@@ -374,20 +375,28 @@ impl AiModelGovernance {
         self.active_proposals.get(model_id)
     }
 
-    pub fn approve_proposal(&mut self, model_id: &str, round: u64) -> Result<()> {
-        if let Some(proposal) = self.active_proposals.remove(model_id) {
-            let entry = ModelRegistryEntry::new(
-                proposal.model_id.clone(),
-                proposal.model_hash,
-                proposal.version,
-                proposal.activation_round,
-                proposal.signature,
-                round,
-                proposal.model_url,
-            );
-            self.model_registry.insert(model_id.to_string(), entry);
-        }
-        Ok(())
+    /// Approve a submitted proposal at `round`, returning the registry entry
+    /// it created (`None` if no such proposal is pending).
+    pub fn approve_proposal(
+        &mut self,
+        model_id: &str,
+        round: u64,
+    ) -> Result<Option<ModelRegistryEntry>> {
+        let Some(proposal) = self.active_proposals.remove(model_id) else {
+            return Ok(None);
+        };
+        let entry = ModelRegistryEntry::new(
+            proposal.model_id.clone(),
+            proposal.model_hash,
+            proposal.version,
+            proposal.activation_round,
+            proposal.signature,
+            round,
+            proposal.model_url,
+        );
+        self.model_registry
+            .insert(model_id.to_string(), entry.clone());
+        Ok(Some(entry))
     }
 
     pub fn reject_proposal(&mut self, model_id: &str) -> Result<()> {
@@ -414,6 +423,18 @@ impl AiModelGovernance {
     pub fn registered_model_count(&self) -> usize {
         self.model_registry.len()
     }
+
+    /// The registered model with the latest activation round, i.e. the one
+    /// that is or will become active.
+    pub fn latest_approved(&self) -> Option<&ModelRegistryEntry> {
+        self.model_registry.values().max_by(|a, b| {
+            (a.activation_round, a.registered_at_round, &a.model_id).cmp(&(
+                b.activation_round,
+                b.registered_at_round,
+                &b.model_id,
+            ))
+        })
+    }
 }
 
 impl Default for AiModelGovernance {
@@ -609,8 +630,16 @@ mod tests {
         gov.submit_proposal(proposal).unwrap();
         assert_eq!(gov.active_proposal_count(), 1);
 
-        gov.approve_proposal("m1", 1000).unwrap();
+        let entry = gov.approve_proposal("m1", 1000).unwrap().unwrap();
+        assert_eq!(entry.registered_at_round, 1000);
         assert_eq!(gov.registered_model_count(), 1);
         assert!(gov.is_model_registered("m1"));
+        assert!(gov.approve_proposal("m1", 1001).unwrap().is_none());
+
+        let mut later = create_test_proposal("m2");
+        later.activation_round = 200;
+        gov.submit_proposal(later).unwrap();
+        gov.approve_proposal("m2", 1001).unwrap();
+        assert_eq!(gov.latest_approved().unwrap().model_id, "m2");
     }
 }
@@ -174,11 +174,101 @@ fn tx_serialized_size(tx: &Transaction) -> usize {
                 }
                 size += std::mem::size_of::<u32>() + data.signature.len();
             }
+            ippan_types::HandleOperation::Update(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u32>(); // metadata len prefix
+                for (key, value) in data.metadata.iter() {
+                    size += std::mem::size_of::<u32>() + key.len();
+                    size += std::mem::size_of::<u32>() + value.len();
+                }
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::Transfer(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len() + data.new_owner.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::Renew(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u64>(); // new expiry
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::Release(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::Bid(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len() + data.commitment.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::Reveal(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u128>() + data.salt.len();
+                size += 1; // expiry flag
+                if data.expires_at.is_some() {
+                    size += std::mem::size_of::<u64>();
+                }
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::SetPrimary(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::SetRecords(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                let mut records = Vec::new();
+                ippan_types::append_record_changes(&mut records, &data.set, &data.remove);
+                size += records.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::DelegateSubhandle(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len() + data.subhandle_owner.len();
+                size += 1; // expiry flag
+                if data.expires_at.is_some() {
+                    size += std::mem::size_of::<u64>();
+                }
+                size += std::mem::size_of::<u32>(); // metadata len prefix
+                for (key, value) in data.metadata.iter() {
+                    size += std::mem::size_of::<u32>() + key.len();
+                    size += std::mem::size_of::<u32>() + value.len();
+                }
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
+            ippan_types::HandleOperation::RevokeSubhandle(data) => {
+                size += 1; // variant discriminator
+                size += data.handle.len();
+                size += data.owner.len();
+                size += std::mem::size_of::<u32>() + data.signature.len();
+            }
         }
     } else {
         size += 1; // absence flag
     }
 
+    if let Some(anchor) = tx.file_anchor() {
+        size += 1; // presence flag
+        size += 32 * 4; // file id, content hash, owner, descriptor digest
+        size += std::mem::size_of::<u64>(); // created_at_us
+        size += std::mem::size_of::<u32>() + anchor.signature.len();
+    }
+
     size
 }
 
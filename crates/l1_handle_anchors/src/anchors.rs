@@ -17,6 +17,9 @@ pub struct L1HandleAnchorStorage {
     /// Owner to handle hashes mapping (for reverse lookup)
     #[allow(clippy::type_complexity)]
     owner_to_handles: Arc<RwLock<HashMap<[u8; 32], Vec<[u8; 32]>>>>,
+    /// Parent handle hash to subhandle hashes mapping
+    #[allow(clippy::type_complexity)]
+    children: Arc<RwLock<HashMap<[u8; 32], Vec<[u8; 32]>>>>,
 }
 
 impl L1HandleAnchorStorage {
@@ -25,25 +28,48 @@ impl L1HandleAnchorStorage {
         Self {
             anchors: Arc::new(RwLock::new(HashMap::new())),
             owner_to_handles: Arc::new(RwLock::new(HashMap::new())),
+            children: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
     /// Store a handle ownership anchor
+    ///
+    /// An existing anchor for the same handle is replaced, which is how
+    /// updates, transfers and renewals refresh the L1 record. A subhandle
+    /// anchor requires its parent to be anchored first.
     pub fn store_anchor(&self, anchor: HandleOwnershipAnchor) -> Result<()> {
         // Verify signature
         if !anchor.verify_signature() {
             return Err(HandleAnchorError::InvalidSignature);
         }
 
+        if let Some(parent_hash) = anchor.parent_hash {
+            if !self.anchors.read().contains_key(&parent_hash) {
+                return Err(HandleAnchorError::ParentAnchorNotFound {
+                    handle_hash: hex::encode(parent_hash),
+                });
+            }
+            let mut children = self.children.write();
+            let siblings = children.entry(parent_hash).or_default();
+            if !siblings.contains(&anchor.handle_hash) {
+                siblings.push(anchor.handle_hash);
+            }
+        }
+
         // Store anchor
-        {
+        let previous = {
             let mut anchors = self.anchors.write();
-            anchors.insert(anchor.handle_hash, anchor.clone());
-        }
+            anchors.insert(anchor.handle_hash, anchor.clone())
+        };
 
         // Update owner mapping
         {
             let mut owner_map = self.owner_to_handles.write();
+            if let Some(previous) = previous {
+                if let Some(handles) = owner_map.get_mut(&previous.owner) {
+                    handles.retain(|h| h != &anchor.handle_hash);
+                }
+            }
             owner_map
                 .entry(anchor.owner)
                 .or_default()
@@ -53,6 +79,57 @@ impl L1HandleAnchorStorage {
         Ok(())
     }
 
+    /// Remove the anchor for a handle string (e.g. after the handle is released)
+    ///
+    /// Anchors of subhandles delegated below the handle are removed with it.
+    pub fn remove_anchor_by_handle(&self, handle: &str) -> Result<HandleOwnershipAnchor> {
+        let handle_hash = HandleOwnershipAnchor::compute_handle_hash(handle);
+        self.remove_anchor_tree(&handle_hash)
+            .ok_or_else(|| HandleAnchorError::AnchorNotFound {
+                handle_hash: hex::encode(handle_hash),
+            })
+    }
+
+    /// List the anchors of subhandles delegated directly under a handle
+    pub fn list_subhandle_anchors(&self, handle: &str) -> Vec<HandleOwnershipAnchor> {
+        let handle_hash = HandleOwnershipAnchor::compute_handle_hash(handle);
+        let children = self.children.read();
+        let anchors = self.anchors.read();
+        children
+            .get(&handle_hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| anchors.get(child).cloned())
+            .collect()
+    }
+
+    /// Remove an anchor and, recursively, the anchors of its subhandles.
+    ///
+    /// Returns the removed root anchor.
+    fn remove_anchor_tree(&self, handle_hash: &[u8; 32]) -> Option<HandleOwnershipAnchor> {
+        let removed = self.anchors.write().remove(handle_hash)?;
+
+        if let Some(handles) = self.owner_to_handles.write().get_mut(&removed.owner) {
+            handles.retain(|h| h != handle_hash);
+        }
+        if let Some(parent_hash) = removed.parent_hash {
+            if let Some(siblings) = self.children.write().get_mut(&parent_hash) {
+                siblings.retain(|h| h != handle_hash);
+            }
+        }
+
+        let children = self
+            .children
+            .write()
+            .remove(handle_hash)
+            .unwrap_or_default();
+        for child in children {
+            self.remove_anchor_tree(&child);
+        }
+
+        Some(removed)
+    }
+
     /// Get ownership anchor by handle hash
     pub fn get_anchor(&self, handle_hash: &[u8; 32]) -> Result<HandleOwnershipAnchor> {
         let anchors = self.anchors.read();
@@ -79,7 +156,6 @@ impl L1HandleAnchorStorage {
     /// Create ownership proof for a handle
     pub fn create_ownership_proof(&self, handle: &str) -> Result<HandleOwnershipProof> {
         use ippan_crypto::MerkleTree;
-        use sha2::{Digest, Sha256};
 
         let anchor = self.get_anchor_by_handle(handle)?;
 
@@ -95,17 +171,7 @@ impl L1HandleAnchorStorage {
         all_anchors.sort_by(|a, b| a.handle_hash.cmp(&b.handle_hash));
 
         // Create leaves: hash of each anchor's critical fields
-        let leaves: Vec<Vec<u8>> = all_anchors
-            .iter()
-            .map(|a| {
-                let mut hasher = Sha256::new();
-                hasher.update(a.handle_hash);
-                hasher.update(a.owner);
-                hasher.update(a.l2_location);
-                hasher.update(a.timestamp.to_le_bytes());
-                hasher.finalize().to_vec()
-            })
-            .collect();
+        let leaves: Vec<Vec<u8>> = all_anchors.iter().map(|a| a.leaf_hash().to_vec()).collect();
 
         // Create merkle tree
         let tree = MerkleTree::new(leaves.clone()).map_err(|e| {
@@ -119,14 +185,7 @@ impl L1HandleAnchorStorage {
         state_root.copy_from_slice(state_root_vec.as_slice());
 
         // Find index of our anchor's leaf
-        let target_leaf = {
-            let mut hasher = Sha256::new();
-            hasher.update(anchor.handle_hash);
-            hasher.update(anchor.owner);
-            hasher.update(anchor.l2_location);
-            hasher.update(anchor.timestamp.to_le_bytes());
-            hasher.finalize().to_vec()
-        };
+        let target_leaf = anchor.leaf_hash().to_vec();
 
         let index = leaves
             .iter()
@@ -184,19 +243,11 @@ impl L1HandleAnchorStorage {
                 .collect()
         };
 
-        // Remove expired anchors
-        {
-            let mut anchors = self.anchors.write();
-            let mut owner_map = self.owner_to_handles.write();
-
-            for hash in expired_hashes {
-                if let Some(anchor) = anchors.remove(&hash) {
-                    // Remove from owner mapping
-                    if let Some(handles) = owner_map.get_mut(&anchor.owner) {
-                        handles.retain(|h| h != &hash);
-                    }
-                    removed += 1;
-                }
+        // Remove expired anchors along with their subhandles
+        for hash in expired_hashes {
+            let before = self.anchors.read().len();
+            if self.remove_anchor_tree(&hash).is_some() {
+                removed += before - self.anchors.read().len();
             }
         }
 
@@ -334,6 +385,7 @@ mod tests {
             round: 1,
             timestamp: 1,
             signature: Vec::new(),
+            parent_hash: None,
         };
 
         let err = storage.store_anchor(anchor).unwrap_err();
@@ -353,10 +405,75 @@ mod tests {
         assert!(storage.get_all_anchors().is_empty());
     }
 
+    #[test]
+    fn test_store_anchor_replaces_previous_owner() {
+        let storage = L1HandleAnchorStorage::new();
+        let handle = "@moving.ipn";
+        let first = HandleOwnershipAnchor::new(handle, [1u8; 32], [2u8; 32], 1, 1, vec![1]);
+        let second = HandleOwnershipAnchor::new(handle, [5u8; 32], [6u8; 32], 2, 2, vec![2]);
+
+        storage.store_anchor(first).unwrap();
+        storage.store_anchor(second.clone()).unwrap();
+
+        assert_eq!(storage.get_anchor_by_handle(handle).unwrap(), second);
+        assert!(storage.list_owner_handles(&[1u8; 32]).is_empty());
+        assert_eq!(storage.list_owner_handles(&[5u8; 32]).len(), 1);
+    }
+
+    #[test]
+    fn test_remove_anchor_by_handle() {
+        let storage = L1HandleAnchorStorage::new();
+        let anchor = HandleOwnershipAnchor::new("@gone.ipn", [7u8; 32], [8u8; 32], 1, 1, vec![1]);
+        storage.store_anchor(anchor).unwrap();
+
+        storage.remove_anchor_by_handle("@gone.ipn").unwrap();
+        assert!(storage.get_anchor_by_handle("@gone.ipn").is_err());
+        assert!(storage.list_owner_handles(&[7u8; 32]).is_empty());
+        assert!(storage.remove_anchor_by_handle("@gone.ipn").is_err());
+    }
+
     #[test]
     fn test_create_proof_missing_handle() {
         let storage = L1HandleAnchorStorage::new();
         let err = storage.create_ownership_proof("@missing.ipn").unwrap_err();
         assert!(matches!(err, HandleAnchorError::AnchorNotFound { .. }));
     }
+
+    #[test]
+    fn test_subhandle_anchors_follow_parent() {
+        let storage = L1HandleAnchorStorage::new();
+        let child =
+            HandleOwnershipAnchor::new("@alice.acme.ipn", [2u8; 32], [3u8; 32], 1, 1, vec![1]);
+        assert_eq!(
+            child.parent_hash,
+            Some(HandleOwnershipAnchor::compute_handle_hash("@acme.ipn"))
+        );
+
+        // The parent must be anchored first.
+        let err = storage.store_anchor(child.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            HandleAnchorError::ParentAnchorNotFound { .. }
+        ));
+
+        let parent = HandleOwnershipAnchor::new("@acme.ipn", [1u8; 32], [3u8; 32], 1, 1, vec![1]);
+        storage.store_anchor(parent).unwrap();
+        storage.store_anchor(child.clone()).unwrap();
+        let grandchild =
+            HandleOwnershipAnchor::new("@x.alice.acme.ipn", [4u8; 32], [3u8; 32], 1, 1, vec![1]);
+        storage.store_anchor(grandchild).unwrap();
+        assert_eq!(storage.list_subhandle_anchors("@acme.ipn"), vec![child]);
+
+        // Subhandle leaves commit to the parent link and still prove inclusion.
+        let proof = storage.create_ownership_proof("@alice.acme.ipn").unwrap();
+        assert!(storage.verify_ownership_proof(&proof));
+        let mut detached = proof.clone();
+        detached.anchor.parent_hash = None;
+        assert!(!storage.verify_ownership_proof(&detached));
+
+        // Removing the parent removes the whole subtree.
+        storage.remove_anchor_by_handle("@acme.ipn").unwrap();
+        assert!(storage.get_all_anchors().is_empty());
+        assert!(storage.list_owner_handles(&[4u8; 32]).is_empty());
+    }
 }
@@ -10,6 +10,9 @@ pub enum HandleAnchorError {
     #[error("Handle anchor not found: {handle_hash}")]
     AnchorNotFound { handle_hash: String },
 
+    #[error("Parent handle anchor not found: {handle_hash}")]
+    ParentAnchorNotFound { handle_hash: String },
+
     #[error("Invalid signature")]
     InvalidSignature,
 
@@ -1,5 +1,6 @@
 //! Types for L1 handle ownership anchors
 
+use ippan_l2_handle_registry::Handle;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
@@ -23,10 +24,15 @@ pub struct HandleOwnershipAnchor {
     pub timestamp: u64,
     /// Signature proving ownership (signed by owner)
     pub signature: Vec<u8>,
+    /// Hash of the parent handle when this anchors a subhandle
+    #[serde(default)]
+    pub parent_hash: Option<[u8; 32]>,
 }
 
 impl HandleOwnershipAnchor {
     /// Create a new ownership anchor
+    ///
+    /// Subhandles (`@alice.acme.ipn`) are linked to their parent's anchor.
     pub fn new(
         handle: &str,
         owner: [u8; 32],
@@ -36,6 +42,9 @@ impl HandleOwnershipAnchor {
         signature: Vec<u8>,
     ) -> Self {
         let handle_hash = Self::compute_handle_hash(handle);
+        let parent_hash = Handle::new(handle)
+            .parent()
+            .map(|parent| Self::compute_handle_hash(parent.as_str()));
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -49,6 +58,7 @@ impl HandleOwnershipAnchor {
             round,
             timestamp,
             signature,
+            parent_hash,
         }
     }
 
@@ -59,6 +69,22 @@ impl HandleOwnershipAnchor {
         hasher.finalize().into()
     }
 
+    /// Merkle leaf committing to the anchor's ownership fields
+    ///
+    /// The parent link is only hashed for subhandles, so top-level leaves are
+    /// unchanged by the hierarchy.
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.handle_hash);
+        hasher.update(self.owner);
+        hasher.update(self.l2_location);
+        hasher.update(self.timestamp.to_le_bytes());
+        if let Some(parent_hash) = self.parent_hash {
+            hasher.update(parent_hash);
+        }
+        hasher.finalize().into()
+    }
+
     /// Verify the ownership signature
     pub fn verify_signature(&self) -> bool {
         // In production, this would verify Ed25519 signatures
@@ -103,13 +129,7 @@ impl HandleOwnershipProof {
             return false;
         }
 
-        // Compute leaf hash
-        let mut hasher = Sha256::new();
-        hasher.update(self.anchor.handle_hash);
-        hasher.update(self.anchor.owner);
-        hasher.update(self.anchor.l2_location);
-        hasher.update(self.anchor.timestamp.to_le_bytes());
-        let leaf_hash: [u8; 32] = hasher.finalize().into();
+        let leaf_hash = self.anchor.leaf_hash();
 
         // If no merkle proof path (single leaf tree), leaf hash IS the root
         if self.merkle_proof.is_empty() {
@@ -0,0 +1,766 @@
+//! Sealed-bid auctions for premium handles
+//!
+//! Premium handles (see [`Handle::is_premium`]) are not registered first-come,
+//! first-served. They are allocated through a commit/reveal auction:
+//!
+//! 1. **Commit** – bidders publish `compute_bid_commitment(handle, bidder, amount, salt)`
+//!    and escrow a deposit that must cover their hidden bid.
+//! 2. **Reveal** – once the commit window closes, bidders reveal `amount` and `salt`
+//!    together with the registration signature used if they win.
+//! 3. **Settle** – at the reveal deadline round the highest revealed bid at or above
+//!    the reserve price wins. Ties go to the earliest commitment, then the lowest key.
+//!    Revealed losers are refunded in full, the winner is refunded `deposit - amount`,
+//!    and bids that were never revealed are forfeited along with the winning amount.
+//!
+//! The book only tracks auction state; moving escrowed funds is left to the caller
+//! so that the same settlement can be applied against any account storage.
+
+use crate::{Handle, PublicKey};
+use ippan_types::RegistryPriceScheduleV1;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Default length of the commit window in rounds (~1 hour at 100ms rounds).
+pub const DEFAULT_AUCTION_COMMIT_ROUNDS: u64 = 36_000;
+
+/// Default length of the reveal window in rounds (~1 hour at 100ms rounds).
+pub const DEFAULT_AUCTION_REVEAL_ROUNDS: u64 = 36_000;
+
+/// Errors raised while bidding on or revealing premium handle auctions.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HandleAuctionError {
+    #[error("handle {handle} is not a premium handle")]
+    NotPremium { handle: String },
+
+    #[error("handle {handle} is already registered")]
+    HandleTaken { handle: String },
+
+    #[error("no auction is running for handle {handle}")]
+    AuctionNotFound { handle: String },
+
+    #[error("commit window for {handle} closed at round {deadline}")]
+    CommitClosed { handle: String, deadline: u64 },
+
+    #[error("reveal window for {handle} is not open at round {round}")]
+    RevealNotOpen { handle: String, round: u64 },
+
+    #[error("bidder already committed to the auction for {handle}")]
+    DuplicateBid { handle: String },
+
+    #[error("no sealed bid from this bidder for {handle}")]
+    BidNotFound { handle: String },
+
+    #[error("bid for {handle} was already revealed")]
+    AlreadyRevealed { handle: String },
+
+    #[error("revealed bid does not match its commitment")]
+    CommitmentMismatch,
+
+    #[error("deposit {deposit} is below the reserve price {reserve}")]
+    BelowReserve { deposit: u128, reserve: u128 },
+
+    #[error("revealed amount {amount} exceeds escrowed deposit {deposit}")]
+    InsufficientDeposit { amount: u128, deposit: u128 },
+
+    #[error("invalid bid signature")]
+    InvalidSignature,
+}
+
+/// Tunables for premium handle auctions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionConfig {
+    /// Rounds during which sealed bids are accepted after an auction opens.
+    pub commit_rounds: u64,
+    /// Rounds during which bids may be revealed after the commit window.
+    pub reveal_rounds: u64,
+    /// Minimum deposit and winning bid.
+    pub reserve_price: u128,
+}
+
+impl Default for AuctionConfig {
+    fn default() -> Self {
+        // The reserve matches the flat premium price charged before auctions existed.
+        let premium_fee = RegistryPriceScheduleV1::default().compute_handle_register_fee(true);
+        Self {
+            commit_rounds: DEFAULT_AUCTION_COMMIT_ROUNDS,
+            reveal_rounds: DEFAULT_AUCTION_REVEAL_ROUNDS,
+            reserve_price: premium_fee as u128,
+        }
+    }
+}
+
+/// Current phase of an auction relative to a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionPhase {
+    Commit,
+    Reveal,
+    /// The reveal deadline has passed; the auction settles at the next finalization.
+    Closed,
+}
+
+/// Data disclosed when a sealed bid is revealed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevealedBid {
+    pub amount: u128,
+    pub expires_at: Option<u64>,
+    /// Registration signature applied to the registry if this bid wins.
+    pub registration_signature: Vec<u8>,
+    pub revealed_round: u64,
+}
+
+/// A sealed bid with its escrowed deposit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedBid {
+    pub bidder: PublicKey,
+    pub commitment: [u8; 32],
+    pub deposit: u128,
+    pub committed_round: u64,
+    pub revealed: Option<RevealedBid>,
+}
+
+/// Running auction for one premium handle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandleAuction {
+    pub handle: Handle,
+    pub opened_round: u64,
+    /// First round in which commitments are no longer accepted.
+    pub commit_deadline: u64,
+    /// Round at which the auction settles.
+    pub reveal_deadline: u64,
+    pub reserve_price: u128,
+    pub bids: Vec<SealedBid>,
+}
+
+impl HandleAuction {
+    /// Phase of the auction at `round`.
+    pub fn phase(&self, round: u64) -> AuctionPhase {
+        if round < self.commit_deadline {
+            AuctionPhase::Commit
+        } else if round < self.reveal_deadline {
+            AuctionPhase::Reveal
+        } else {
+            AuctionPhase::Closed
+        }
+    }
+
+    /// Total deposits currently held in escrow for this auction.
+    pub fn escrowed(&self) -> u128 {
+        self.bids
+            .iter()
+            .fold(0u128, |acc, bid| acc.saturating_add(bid.deposit))
+    }
+
+    fn settle(self, round: u64) -> AuctionSettlement {
+        let winner_index = self
+            .bids
+            .iter()
+            .enumerate()
+            .filter_map(|(index, bid)| bid.revealed.as_ref().map(|r| (index, bid, r)))
+            .filter(|(_, _, revealed)| revealed.amount >= self.reserve_price)
+            .min_by(|(_, a, ra), (_, b, rb)| {
+                rb.amount
+                    .cmp(&ra.amount)
+                    .then(a.committed_round.cmp(&b.committed_round))
+                    .then(a.bidder.as_bytes().cmp(b.bidder.as_bytes()))
+            })
+            .map(|(index, _, _)| index);
+
+        let mut winner = None;
+        let mut refunds = Vec::new();
+        let mut forfeited = 0u128;
+
+        for (index, bid) in self.bids.into_iter().enumerate() {
+            match bid.revealed {
+                Some(revealed) if Some(index) == winner_index => {
+                    let change = bid.deposit.saturating_sub(revealed.amount);
+                    if change > 0 {
+                        refunds.push((bid.bidder.clone(), change));
+                    }
+                    winner = Some(AuctionWinner {
+                        bidder: bid.bidder,
+                        amount: revealed.amount,
+                        expires_at: revealed.expires_at,
+                        registration_signature: revealed.registration_signature,
+                    });
+                }
+                Some(_) => refunds.push((bid.bidder, bid.deposit)),
+                None => forfeited = forfeited.saturating_add(bid.deposit),
+            }
+        }
+
+        AuctionSettlement {
+            handle: self.handle,
+            round,
+            winner,
+            refunds,
+            forfeited,
+        }
+    }
+}
+
+/// Winning bid of a settled auction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionWinner {
+    pub bidder: PublicKey,
+    pub amount: u128,
+    pub expires_at: Option<u64>,
+    pub registration_signature: Vec<u8>,
+}
+
+/// Deterministic outcome of an auction, describing how escrow must be released.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionSettlement {
+    pub handle: Handle,
+    pub round: u64,
+    pub winner: Option<AuctionWinner>,
+    /// Deposits (or deposit change) returned to bidders.
+    pub refunds: Vec<(PublicKey, u128)>,
+    /// Deposits of bids that were never revealed.
+    pub forfeited: u128,
+}
+
+impl AuctionSettlement {
+    /// Amount routed to the treasury: the winning bid plus forfeited deposits.
+    pub fn proceeds(&self) -> u128 {
+        self.winner
+            .as_ref()
+            .map(|w| w.amount)
+            .unwrap_or(0)
+            .saturating_add(self.forfeited)
+    }
+}
+
+/// Serializable contents of a [`HandleAuctionBook`], in handle order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandleAuctionBookState {
+    pub auctions: Vec<HandleAuction>,
+    pub settled: Vec<AuctionSettlement>,
+}
+
+/// Book of running and settled premium handle auctions.
+///
+/// The book lives in memory; callers persist [`HandleAuctionBook::snapshot`]
+/// after every change, because bid deposits sit in a persistent escrow account.
+#[derive(Debug, Default)]
+pub struct HandleAuctionBook {
+    config: AuctionConfig,
+    auctions: RwLock<HashMap<Handle, HandleAuction>>,
+    settled: RwLock<HashMap<Handle, AuctionSettlement>>,
+}
+
+impl HandleAuctionBook {
+    /// Create an auction book with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an auction book with a custom configuration.
+    pub fn with_config(config: AuctionConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &AuctionConfig {
+        &self.config
+    }
+
+    /// Check that a sealed bid would be accepted, without recording it.
+    ///
+    /// `handle_available` tells the book whether the registry considers the
+    /// handle free (unregistered or reclaimable) at the time of the bid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn validate_commit(
+        &self,
+        handle: &Handle,
+        bidder: &PublicKey,
+        commitment: &[u8; 32],
+        deposit: u128,
+        signature: &[u8],
+        handle_available: bool,
+        round: u64,
+    ) -> Result<(), HandleAuctionError> {
+        if !handle.is_valid() || !handle.is_premium() {
+            return Err(HandleAuctionError::NotPremium {
+                handle: handle.as_str().to_string(),
+            });
+        }
+        if deposit < self.config.reserve_price {
+            return Err(HandleAuctionError::BelowReserve {
+                deposit,
+                reserve: self.config.reserve_price,
+            });
+        }
+        if !verify_signature(
+            bidder,
+            &[
+                b"IPPAN_HANDLE_BID",
+                handle.as_str().as_bytes(),
+                bidder.as_bytes(),
+                commitment,
+            ],
+            signature,
+        ) {
+            return Err(HandleAuctionError::InvalidSignature);
+        }
+
+        let auctions = self.auctions.read();
+        match auctions.get(handle) {
+            Some(auction) => {
+                if auction.phase(round) != AuctionPhase::Commit {
+                    return Err(HandleAuctionError::CommitClosed {
+                        handle: handle.as_str().to_string(),
+                        deadline: auction.commit_deadline,
+                    });
+                }
+                if auction.bids.iter().any(|bid| &bid.bidder == bidder) {
+                    return Err(HandleAuctionError::DuplicateBid {
+                        handle: handle.as_str().to_string(),
+                    });
+                }
+            }
+            None if !handle_available => {
+                return Err(HandleAuctionError::HandleTaken {
+                    handle: handle.as_str().to_string(),
+                });
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Record a sealed bid, opening a new auction if none is running.
+    #[allow(clippy::too_many_arguments)]
+    pub fn commit_bid(
+        &self,
+        handle: &Handle,
+        bidder: &PublicKey,
+        commitment: [u8; 32],
+        deposit: u128,
+        signature: &[u8],
+        handle_available: bool,
+        round: u64,
+    ) -> Result<(), HandleAuctionError> {
+        self.validate_commit(
+            handle,
+            bidder,
+            &commitment,
+            deposit,
+            signature,
+            handle_available,
+            round,
+        )?;
+
+        let mut auctions = self.auctions.write();
+        let auction = auctions
+            .entry(handle.clone())
+            .or_insert_with(|| HandleAuction {
+                handle: handle.clone(),
+                opened_round: round,
+                commit_deadline: round.saturating_add(self.config.commit_rounds),
+                reveal_deadline: round
+                    .saturating_add(self.config.commit_rounds)
+                    .saturating_add(self.config.reveal_rounds),
+                reserve_price: self.config.reserve_price,
+                bids: Vec::new(),
+            });
+        auction.bids.push(SealedBid {
+            bidder: bidder.clone(),
+            commitment,
+            deposit,
+            committed_round: round,
+            revealed: None,
+        });
+        self.settled.write().remove(handle);
+        Ok(())
+    }
+
+    /// Reveal a previously committed bid.
+    ///
+    /// `registration_signature` must be the bidder's regular handle registration
+    /// signature so the handle can be registered on their behalf if they win.
+    #[allow(clippy::too_many_arguments)]
+    pub fn reveal_bid(
+        &self,
+        handle: &Handle,
+        bidder: &PublicKey,
+        amount: u128,
+        salt: &[u8; 32],
+        expires_at: Option<u64>,
+        registration_signature: Vec<u8>,
+        round: u64,
+    ) -> Result<(), HandleAuctionError> {
+        let mut auctions = self.auctions.write();
+        let auction =
+            auctions
+                .get_mut(handle)
+                .ok_or_else(|| HandleAuctionError::AuctionNotFound {
+                    handle: handle.as_str().to_string(),
+                })?;
+
+        if auction.phase(round) != AuctionPhase::Reveal {
+            return Err(HandleAuctionError::RevealNotOpen {
+                handle: handle.as_str().to_string(),
+                round,
+            });
+        }
+
+        let bid = auction
+            .bids
+            .iter_mut()
+            .find(|bid| &bid.bidder == bidder)
+            .ok_or_else(|| HandleAuctionError::BidNotFound {
+                handle: handle.as_str().to_string(),
+            })?;
+
+        if bid.revealed.is_some() {
+            return Err(HandleAuctionError::AlreadyRevealed {
+                handle: handle.as_str().to_string(),
+            });
+        }
+        if compute_bid_commitment(handle.as_str(), bidder.as_bytes(), amount, salt)
+            != bid.commitment
+        {
+            return Err(HandleAuctionError::CommitmentMismatch);
+        }
+        if amount > bid.deposit {
+            return Err(HandleAuctionError::InsufficientDeposit {
+                amount,
+                deposit: bid.deposit,
+            });
+        }
+
+        let mut registration = vec![
+            b"IPPAN_HANDLE_REGISTRATION".to_vec(),
+            handle.as_str().as_bytes().to_vec(),
+            bidder.as_bytes().to_vec(),
+        ];
+        if let Some(exp) = expires_at {
+            registration.push(exp.to_le_bytes().to_vec());
+        }
+        let parts: Vec<&[u8]> = registration.iter().map(Vec::as_slice).collect();
+        if !verify_signature(bidder, &parts, &registration_signature) {
+            return Err(HandleAuctionError::InvalidSignature);
+        }
+
+        bid.revealed = Some(RevealedBid {
+            amount,
+            expires_at,
+            registration_signature,
+            revealed_round: round,
+        });
+        Ok(())
+    }
+
+    /// Settle every auction whose reveal deadline is at or before `round`.
+    ///
+    /// Settlements are returned in handle order so callers apply them deterministically.
+    pub fn settle_due(&self, round: u64) -> Vec<AuctionSettlement> {
+        let mut due: Vec<HandleAuction> = {
+            let mut auctions = self.auctions.write();
+            let handles: Vec<Handle> = auctions
+                .iter()
+                .filter(|(_, auction)| auction.phase(round) == AuctionPhase::Closed)
+                .map(|(handle, _)| handle.clone())
+                .collect();
+            handles
+                .iter()
+                .filter_map(|handle| auctions.remove(handle))
+                .collect()
+        };
+        due.sort_by(|a, b| a.handle.as_str().cmp(b.handle.as_str()));
+
+        let settlements: Vec<AuctionSettlement> = due
+            .into_iter()
+            .map(|auction| auction.settle(round))
+            .collect();
+
+        let mut settled = self.settled.write();
+        for settlement in &settlements {
+            settled.insert(settlement.handle.clone(), settlement.clone());
+        }
+        settlements
+    }
+
+    /// Running auction for a handle, if any.
+    pub fn auction(&self, handle: &Handle) -> Option<HandleAuction> {
+        self.auctions.read().get(handle).cloned()
+    }
+
+    /// Outcome of the most recent settled auction for a handle, if any.
+    pub fn settlement(&self, handle: &Handle) -> Option<AuctionSettlement> {
+        self.settled.read().get(handle).cloned()
+    }
+
+    /// Whether an auction is currently running for the handle.
+    pub fn is_running(&self, handle: &Handle) -> bool {
+        self.auctions.read().contains_key(handle)
+    }
+
+    /// Number of running auctions.
+    pub fn active_count(&self) -> usize {
+        self.auctions.read().len()
+    }
+
+    /// Copy out every running and settled auction.
+    pub fn snapshot(&self) -> HandleAuctionBookState {
+        let mut auctions: Vec<HandleAuction> = self.auctions.read().values().cloned().collect();
+        auctions.sort_by(|a, b| a.handle.as_str().cmp(b.handle.as_str()));
+        let mut settled: Vec<AuctionSettlement> = self.settled.read().values().cloned().collect();
+        settled.sort_by(|a, b| a.handle.as_str().cmp(b.handle.as_str()));
+        HandleAuctionBookState { auctions, settled }
+    }
+
+    /// Replace the book's contents with a previously taken snapshot.
+    pub fn restore(&self, state: HandleAuctionBookState) {
+        *self.auctions.write() = state
+            .auctions
+            .into_iter()
+            .map(|auction| (auction.handle.clone(), auction))
+            .collect();
+        *self.settled.write() = state
+            .settled
+            .into_iter()
+            .map(|settlement| (settlement.handle.clone(), settlement))
+            .collect();
+    }
+}
+
+/// Commitment hiding a bid amount until reveal.
+pub fn compute_bid_commitment(
+    handle: &str,
+    bidder: &[u8; 32],
+    amount: u128,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"IPPAN_HANDLE_BID_COMMITMENT");
+    hasher.update(handle.as_bytes());
+    hasher.update(bidder);
+    hasher.update(amount.to_le_bytes());
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+fn verify_signature(owner: &PublicKey, parts: &[&[u8]], signature: &[u8]) -> bool {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    if signature.len() != 64 {
+        return false;
+    }
+
+    let Ok(verifying_key) = VerifyingKey::from_bytes(owner.as_bytes()) else {
+        return false;
+    };
+
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+
+    let mut message = Vec::new();
+    for part in parts {
+        message.extend_from_slice(part);
+    }
+    let message_hash = Sha256::digest(&message);
+    verifying_key.verify(&message_hash, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn sign(signing_key: &SigningKey, parts: &[&[u8]]) -> Vec<u8> {
+        let mut message = Vec::new();
+        for part in parts {
+            message.extend_from_slice(part);
+        }
+        let hash = Sha256::digest(&message);
+        signing_key.sign(&hash).to_bytes().to_vec()
+    }
+
+    fn test_book() -> HandleAuctionBook {
+        HandleAuctionBook::with_config(AuctionConfig {
+            commit_rounds: 10,
+            reveal_rounds: 10,
+            reserve_price: 100,
+        })
+    }
+
+    fn commit(
+        book: &HandleAuctionBook,
+        key: &SigningKey,
+        handle: &Handle,
+        amount: u128,
+        deposit: u128,
+        round: u64,
+    ) -> Result<[u8; 32], HandleAuctionError> {
+        let bidder = PublicKey::new(key.verifying_key().to_bytes());
+        let salt = [key.to_bytes()[0]; 32];
+        let commitment = compute_bid_commitment(handle.as_str(), bidder.as_bytes(), amount, &salt);
+        let signature = sign(
+            key,
+            &[
+                b"IPPAN_HANDLE_BID",
+                handle.as_str().as_bytes(),
+                bidder.as_bytes(),
+                &commitment,
+            ],
+        );
+        book.commit_bid(
+            handle, &bidder, commitment, deposit, &signature, true, round,
+        )?;
+        Ok(salt)
+    }
+
+    fn reveal(
+        book: &HandleAuctionBook,
+        key: &SigningKey,
+        handle: &Handle,
+        amount: u128,
+        salt: &[u8; 32],
+        round: u64,
+    ) -> Result<(), HandleAuctionError> {
+        let bidder = PublicKey::new(key.verifying_key().to_bytes());
+        let registration = sign(
+            key,
+            &[
+                b"IPPAN_HANDLE_REGISTRATION",
+                handle.as_str().as_bytes(),
+                bidder.as_bytes(),
+            ],
+        );
+        book.reveal_bid(handle, &bidder, amount, salt, None, registration, round)
+    }
+
+    #[test]
+    fn highest_revealed_bid_wins_and_losers_are_refunded() {
+        let book = test_book();
+        let handle = Handle::new("@neo.cyborg");
+        let alice = SigningKey::from_bytes(&[1u8; 32]);
+        let bob = SigningKey::from_bytes(&[2u8; 32]);
+        let carol = SigningKey::from_bytes(&[3u8; 32]);
+
+        let alice_salt = commit(&book, &alice, &handle, 500, 800, 1).unwrap();
+        let bob_salt = commit(&book, &bob, &handle, 700, 700, 2).unwrap();
+        commit(&book, &carol, &handle, 900, 1_000, 3).unwrap();
+
+        assert_eq!(
+            book.auction(&handle).unwrap().phase(5),
+            AuctionPhase::Commit
+        );
+        reveal(&book, &alice, &handle, 500, &alice_salt, 12).unwrap();
+        reveal(&book, &bob, &handle, 700, &bob_salt, 13).unwrap();
+        // Carol never reveals.
+
+        assert!(book.settle_due(20).is_empty());
+        let settlements = book.settle_due(21);
+        assert_eq!(settlements.len(), 1);
+        let settlement = &settlements[0];
+
+        let winner = settlement.winner.as_ref().unwrap();
+        assert_eq!(winner.bidder.as_bytes(), &bob.verifying_key().to_bytes());
+        assert_eq!(winner.amount, 700);
+        assert_eq!(settlement.forfeited, 1_000);
+        assert_eq!(settlement.proceeds(), 1_700);
+        assert_eq!(
+            settlement.refunds,
+            vec![(PublicKey::new(alice.verifying_key().to_bytes()), 800)]
+        );
+        assert!(!book.is_running(&handle));
+        assert_eq!(book.settlement(&handle).as_ref(), Some(settlement));
+    }
+
+    #[test]
+    fn restored_book_settles_like_the_original() {
+        let book = test_book();
+        let handle = Handle::new("@restart.cyborg");
+        let alice = SigningKey::from_bytes(&[7u8; 32]);
+        let bob = SigningKey::from_bytes(&[8u8; 32]);
+
+        let alice_salt = commit(&book, &alice, &handle, 400, 400, 1).unwrap();
+        commit(&book, &bob, &handle, 600, 600, 2).unwrap();
+        reveal(&book, &alice, &handle, 400, &alice_salt, 11).unwrap();
+
+        let encoded = serde_json::to_vec(&book.snapshot()).unwrap();
+        let restored = test_book();
+        restored.restore(serde_json::from_slice(&encoded).unwrap());
+
+        assert_eq!(restored.auction(&handle), book.auction(&handle));
+        assert_eq!(restored.settle_due(21), book.settle_due(21));
+        assert_eq!(restored.snapshot(), book.snapshot());
+    }
+
+    #[test]
+    fn ties_go_to_the_earliest_commitment() {
+        let book = test_book();
+        let handle = Handle::new("@tie.iot");
+        let first = SigningKey::from_bytes(&[9u8; 32]);
+        let second = SigningKey::from_bytes(&[4u8; 32]);
+
+        let first_salt = commit(&book, &first, &handle, 300, 300, 1).unwrap();
+        let second_salt = commit(&book, &second, &handle, 300, 300, 2).unwrap();
+        reveal(&book, &second, &handle, 300, &second_salt, 11).unwrap();
+        reveal(&book, &first, &handle, 300, &first_salt, 12).unwrap();
+
+        let settlement = book.settle_due(21).remove(0);
+        assert_eq!(
+            settlement.winner.unwrap().bidder.as_bytes(),
+            &first.verifying_key().to_bytes()
+        );
+    }
+
+    #[test]
+    fn rejects_bids_outside_their_window() {
+        let book = test_book();
+        let handle = Handle::new("@late.cyborg");
+        let alice = SigningKey::from_bytes(&[5u8; 32]);
+        let bob = SigningKey::from_bytes(&[6u8; 32]);
+
+        let salt = commit(&book, &alice, &handle, 200, 200, 1).unwrap();
+        assert!(matches!(
+            reveal(&book, &alice, &handle, 200, &salt, 5),
+            Err(HandleAuctionError::RevealNotOpen { .. })
+        ));
+        assert!(matches!(
+            commit(&book, &bob, &handle, 200, 200, 11),
+            Err(HandleAuctionError::CommitClosed { .. })
+        ));
+        assert!(matches!(
+            commit(&book, &alice, &handle, 200, 200, 2),
+            Err(HandleAuctionError::DuplicateBid { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_reveals_and_non_premium_handles() {
+        let book = test_book();
+        let alice = SigningKey::from_bytes(&[7u8; 32]);
+        let handle = Handle::new("@sealed.m");
+
+        assert!(matches!(
+            commit(&book, &alice, &Handle::new("@plain.ipn"), 200, 200, 1),
+            Err(HandleAuctionError::NotPremium { .. })
+        ));
+        assert!(matches!(
+            commit(&book, &alice, &handle, 50, 50, 1),
+            Err(HandleAuctionError::BelowReserve { .. })
+        ));
+
+        let salt = commit(&book, &alice, &handle, 200, 250, 1).unwrap();
+        assert_eq!(
+            reveal(&book, &alice, &handle, 250, &salt, 11),
+            Err(HandleAuctionError::CommitmentMismatch)
+        );
+        reveal(&book, &alice, &handle, 200, &salt, 11).unwrap();
+        assert!(matches!(
+            reveal(&book, &alice, &handle, 200, &salt, 12),
+            Err(HandleAuctionError::AlreadyRevealed { .. })
+        ));
+    }
+}
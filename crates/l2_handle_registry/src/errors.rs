@@ -19,6 +19,32 @@ pub enum HandleRegistryError {
     #[error("Handle expired: {handle}")]
     HandleExpired { handle: String },
 
+    #[error("Invalid renewal for handle {handle}: expiry must extend the current one")]
+    InvalidRenewal { handle: String },
+
+    #[error("Invalid record for handle {handle}: {reason}")]
+    InvalidRecord { handle: String, reason: String },
+
+    #[error("Handle {handle} cannot hold more than {max} records")]
+    TooManyRecords { handle: String, max: usize },
+
+    #[error("Handle {handle} is a subhandle and must be delegated by its parent")]
+    SubhandleRequiresDelegation { handle: String },
+
+    #[error("Handle {handle} is not a subhandle")]
+    NotASubhandle { handle: String },
+
+    #[error("Parent handle {parent} of {handle} does not resolve")]
+    ParentUnavailable { handle: String, parent: String },
+
+    #[error(
+        "Subhandle {handle} cannot outlive its parent (parent expires at {parent_expires_at})"
+    )]
+    SubhandleOutlivesParent {
+        handle: String,
+        parent_expires_at: u64,
+    },
+
     #[error("Registry storage error: {0}")]
     StorageError(#[from] anyhow::Error),
 
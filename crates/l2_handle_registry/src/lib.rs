@@ -6,14 +6,18 @@
 //! Layer 1 (L1) only stores the ownership anchors and root commitments,
 //! while Layer 2 (L2) manages the actual handle mappings, metadata, and renewals.
 
+pub mod auction;
 pub mod dht;
 pub mod errors;
+pub mod records;
 pub mod registry;
 pub mod resolution;
 pub mod types;
 
+pub use auction::*;
 pub use dht::*;
 pub use errors::*;
+pub use records::*;
 pub use registry::*;
 pub use resolution::*;
 pub use types::*;
@@ -0,0 +1,241 @@
+//! Typed resolver records for handles
+//!
+//! Records are keyed slots on a handle (see [`HandleRecord::key`]) holding a
+//! payment address, L2 account addresses, a file pointer, a node endpoint or
+//! free-form text. Every change is signed by the handle owner over
+//! `IPPAN_HANDLE_RECORDS` and validated per record type before it is applied.
+
+use crate::types::{Handle, PublicKey};
+use serde::{Deserialize, Serialize};
+
+pub use ippan_types::HandleRecord;
+
+/// Maximum number of records a single handle can hold.
+pub const MAX_HANDLE_RECORDS: usize = 32;
+/// Maximum length of an L2 network id.
+pub const MAX_L2_NETWORK_ID_LEN: usize = 64;
+/// Maximum length of an L2 account address.
+pub const MAX_L2_ADDRESS_LEN: usize = 128;
+/// Maximum length of a node multiaddr.
+pub const MAX_MULTIADDR_LEN: usize = 256;
+/// Maximum length of a TXT record name.
+pub const MAX_TXT_NAME_LEN: usize = 32;
+/// Maximum length of a TXT record value in bytes.
+pub const MAX_TXT_VALUE_LEN: usize = 512;
+
+/// Signed change set for the records of a handle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleRecordUpdate {
+    pub handle: Handle,
+    pub owner: PublicKey,
+    pub signature: Vec<u8>,
+    /// Records to insert or replace
+    pub set: Vec<HandleRecord>,
+    /// Record keys to delete
+    pub remove: Vec<String>,
+}
+
+impl HandleRecordUpdate {
+    /// Message the owner signs (SHA-256 digest, Ed25519) to authorise the update.
+    pub fn signing_message(&self) -> Vec<u8> {
+        record_update_message(&self.handle, &self.owner, &self.set, &self.remove)
+    }
+}
+
+/// Build the `IPPAN_HANDLE_RECORDS` message for a record change set.
+pub fn record_update_message(
+    handle: &Handle,
+    owner: &PublicKey,
+    set: &[HandleRecord],
+    remove: &[String],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"IPPAN_HANDLE_RECORDS");
+    message.extend_from_slice(handle.as_str().as_bytes());
+    message.extend_from_slice(owner.as_bytes());
+    ippan_types::append_record_changes(&mut message, set, remove);
+    message
+}
+
+/// Check a record against the rules for its type.
+///
+/// Returns a human-readable reason when the record is rejected.
+pub fn validate_record(record: &HandleRecord) -> Result<(), String> {
+    match record {
+        HandleRecord::Payment { address } => {
+            if address == &[0u8; 32] {
+                return Err("payment address must not be zero".into());
+            }
+        }
+        HandleRecord::L2Address { network, address } => {
+            if network.is_empty() || network.len() > MAX_L2_NETWORK_ID_LEN {
+                return Err(format!(
+                    "L2 network id must be 1-{MAX_L2_NETWORK_ID_LEN} characters"
+                ));
+            }
+            if !network
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                return Err(format!("L2 network id '{network}' has invalid characters"));
+            }
+            if address.is_empty() || address.len() > MAX_L2_ADDRESS_LEN {
+                return Err(format!(
+                    "L2 address must be 1-{MAX_L2_ADDRESS_LEN} characters"
+                ));
+            }
+            if !address.chars().all(|c| c.is_ascii_graphic()) {
+                return Err("L2 address must be printable ASCII without spaces".into());
+            }
+        }
+        HandleRecord::File { descriptor } => {
+            if descriptor.as_bytes() == &[0u8; 32] {
+                return Err("file descriptor id must not be zero".into());
+            }
+        }
+        HandleRecord::Node { multiaddr } => validate_multiaddr(multiaddr)?,
+        HandleRecord::Txt { name, value } => {
+            if name.is_empty() || name.len() > MAX_TXT_NAME_LEN {
+                return Err(format!("TXT name must be 1-{MAX_TXT_NAME_LEN} characters"));
+            }
+            if !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+            {
+                return Err(format!("TXT name '{name}' has invalid characters"));
+            }
+            if value.len() > MAX_TXT_VALUE_LEN {
+                return Err(format!("TXT value exceeds {MAX_TXT_VALUE_LEN} bytes"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Structural multiaddr check: `/<protocol>/<value>` pairs with a known protocol,
+/// ending in a transport (`tcp`, `udp`, `quic-v1`) or peer id (`p2p`).
+fn validate_multiaddr(multiaddr: &str) -> Result<(), String> {
+    const PROTOCOLS_WITH_VALUE: &[&str] = &[
+        "ip4", "ip6", "dns", "dns4", "dns6", "dnsaddr", "tcp", "udp", "p2p",
+    ];
+    const PROTOCOLS_WITHOUT_VALUE: &[&str] = &["quic", "quic-v1", "ws", "wss", "tls", "noise"];
+
+    if multiaddr.len() > MAX_MULTIADDR_LEN {
+        return Err(format!("multiaddr exceeds {MAX_MULTIADDR_LEN} characters"));
+    }
+    let Some(rest) = multiaddr.strip_prefix('/') else {
+        return Err("multiaddr must start with '/'".into());
+    };
+
+    let mut parts = rest.split('/');
+    let mut seen_any = false;
+    while let Some(protocol) = parts.next() {
+        seen_any = true;
+        if PROTOCOLS_WITHOUT_VALUE.contains(&protocol) {
+            continue;
+        }
+        if !PROTOCOLS_WITH_VALUE.contains(&protocol) {
+            return Err(format!("unsupported multiaddr protocol '{protocol}'"));
+        }
+        match parts.next() {
+            Some(value) if !value.is_empty() && value.chars().all(|c| c.is_ascii_graphic()) => {}
+            _ => {
+                return Err(format!(
+                    "multiaddr protocol '{protocol}' is missing a value"
+                ))
+            }
+        }
+    }
+
+    if seen_any {
+        Ok(())
+    } else {
+        Err("multiaddr must not be empty".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_types::FileDescriptorId;
+
+    #[test]
+    fn accepts_well_formed_records() {
+        let records = [
+            HandleRecord::Payment { address: [7u8; 32] },
+            HandleRecord::L2Address {
+                network: "zk-rollup-1".into(),
+                address: "0xabc123".into(),
+            },
+            HandleRecord::File {
+                descriptor: FileDescriptorId::from_bytes([3u8; 32]),
+            },
+            HandleRecord::Node {
+                multiaddr: "/ip4/10.0.0.1/tcp/9000/p2p/12D3KooWabc".into(),
+            },
+            HandleRecord::Node {
+                multiaddr: "/dns4/node.example.org/udp/443/quic-v1".into(),
+            },
+            HandleRecord::Txt {
+                name: "bio".into(),
+                value: "hello".into(),
+            },
+        ];
+        for record in &records {
+            assert!(validate_record(record).is_ok(), "{record:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_records() {
+        let records = [
+            HandleRecord::Payment { address: [0u8; 32] },
+            HandleRecord::L2Address {
+                network: "bad net".into(),
+                address: "0xabc".into(),
+            },
+            HandleRecord::L2Address {
+                network: "net".into(),
+                address: String::new(),
+            },
+            HandleRecord::Node {
+                multiaddr: "ip4/10.0.0.1".into(),
+            },
+            HandleRecord::Node {
+                multiaddr: "/ip4/10.0.0.1/tcp".into(),
+            },
+            HandleRecord::Node {
+                multiaddr: "/http/example.org".into(),
+            },
+            HandleRecord::Txt {
+                name: "Bio".into(),
+                value: "x".into(),
+            },
+            HandleRecord::Txt {
+                name: "bio".into(),
+                value: "x".repeat(MAX_TXT_VALUE_LEN + 1),
+            },
+        ];
+        for record in &records {
+            assert!(validate_record(record).is_err(), "{record:?}");
+        }
+    }
+
+    #[test]
+    fn record_keys_are_per_slot() {
+        let a = HandleRecord::L2Address {
+            network: "alpha".into(),
+            address: "a".into(),
+        };
+        let b = HandleRecord::Txt {
+            name: "site".into(),
+            value: "b".into(),
+        };
+        assert_eq!(a.key(), "l2:alpha");
+        assert_eq!(b.key(), "txt:site");
+        assert_eq!(
+            HandleRecord::Payment { address: [1u8; 32] }.key(),
+            "payment"
+        );
+    }
+}
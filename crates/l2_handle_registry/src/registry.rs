@@ -4,10 +4,11 @@
 //! and metadata management (e.g. `@alice.ipn`, `@device.iot`).
 
 use crate::errors::*;
+use crate::records::*;
 use crate::types::*;
 use parking_lot::RwLock;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -19,13 +20,17 @@ use std::time::SystemTime;
 /// **Current implementation:**
 /// - In-memory storage (not persisted across restarts)
 /// - Local-only lookups (no network distribution)
-/// - Signature-verified registration, updates, and transfers
+/// - Signature-verified registration, updates, transfers, renewals and releases
+/// - Expired handles are reclaimable after [`HANDLE_GRACE_PERIOD_SECS`]
+/// - Reverse resolution from an owner key to its primary handle
+/// - Typed resolver records (see [`crate::records`])
+/// - Subhandles delegated and revoked by the owner of their parent handle
 ///
 /// **Future enhancements (see `docs/ipndht/ipndht_hardening_plan.md`):**
 /// - DHT-based handle distribution (PUT/GET via Kademlia)
 /// - Cross-node handle synchronization
 /// - Persistent storage with disk-backed cache
-/// - Handle expiration and automatic renewal
+/// - Automatic renewal
 ///
 /// For distributed @handle resolution across the network, see Phase D3 of the
 /// IPNDHT hardening plan.
@@ -35,6 +40,8 @@ pub struct L2HandleRegistry {
     handles: Arc<RwLock<HashMap<Handle, HandleMetadata>>>,
     /// Owner public key → list of handles
     owner_to_handles: Arc<RwLock<HashMap<PublicKey, Vec<Handle>>>>,
+    /// Owner public key → primary handle (reverse index)
+    primary_handles: Arc<RwLock<HashMap<PublicKey, Handle>>>,
 }
 
 impl L2HandleRegistry {
@@ -43,6 +50,7 @@ impl L2HandleRegistry {
         Self {
             handles: Arc::new(RwLock::new(HashMap::new())),
             owner_to_handles: Arc::new(RwLock::new(HashMap::new())),
+            primary_handles: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -53,47 +61,210 @@ impl L2HandleRegistry {
                 handle: registration.handle.as_str().to_string(),
             });
         }
-
-        {
-            let handles = self.handles.read();
-            if handles.contains_key(&registration.handle) {
-                return Err(HandleRegistryError::HandleAlreadyExists {
-                    handle: registration.handle.as_str().to_string(),
-                });
-            }
+        if registration.handle.is_subhandle() {
+            return Err(HandleRegistryError::SubhandleRequiresDelegation {
+                handle: registration.handle.as_str().to_string(),
+            });
         }
 
+        let lapsed_owner = self.lapsed_owner(&registration.handle)?;
+
         if !self.verify_registration_signature(&registration) {
             return Err(HandleRegistryError::Unauthorized {
                 handle: registration.handle.as_str().to_string(),
             });
         }
 
-        let mut metadata = HandleMetadata {
-            owner: registration.owner.clone(),
-            expires_at: registration.expires_at.unwrap_or(0),
-            metadata: registration.metadata,
-            ..Default::default()
+        self.insert_handle(
+            registration.handle,
+            registration.owner,
+            registration.metadata,
+            registration.expires_at.unwrap_or(0),
+            lapsed_owner,
+        );
+        Ok(())
+    }
+
+    /// Issue a subhandle under a parent handle owned by `parent_owner`.
+    ///
+    /// The parent must resolve, and the subhandle's expiry defaults to the
+    /// parent's and may not exceed it.
+    pub fn register_subhandle(&self, delegation: HandleDelegation) -> Result<()> {
+        if !delegation.handle.is_valid() {
+            return Err(HandleRegistryError::InvalidHandleFormat {
+                handle: delegation.handle.as_str().to_string(),
+            });
+        }
+        let Some(parent) = delegation.handle.parent() else {
+            return Err(HandleRegistryError::NotASubhandle {
+                handle: delegation.handle.as_str().to_string(),
+            });
+        };
+
+        if self.resolve(&parent)? != delegation.parent_owner {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: delegation.handle.as_str().to_string(),
+            });
+        }
+        let parent_expires_at = self
+            .handles
+            .read()
+            .get(&parent)
+            .map(|meta| meta.expires_at)
+            .unwrap_or(0);
+        let expires_at = match (parent_expires_at, delegation.expires_at) {
+            (0, requested) => requested.unwrap_or(0),
+            (bound, None) => bound,
+            (bound, Some(requested)) if requested > 0 && requested <= bound => requested,
+            (bound, Some(_)) => {
+                return Err(HandleRegistryError::SubhandleOutlivesParent {
+                    handle: delegation.handle.as_str().to_string(),
+                    parent_expires_at: bound,
+                })
+            }
         };
 
-        metadata.l1_anchor =
-            Some(self.compute_l1_anchor(&registration.handle, &registration.owner));
+        let lapsed_owner = self.lapsed_owner(&delegation.handle)?;
 
-        {
-            let mut handles = self.handles.write();
-            handles.insert(registration.handle.clone(), metadata);
+        if !self.verify_delegation_signature(&delegation) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: delegation.handle.as_str().to_string(),
+            });
         }
 
+        self.insert_handle(
+            delegation.handle,
+            delegation.owner,
+            delegation.metadata,
+            expires_at,
+            lapsed_owner,
+        );
+        Ok(())
+    }
+
+    /// Revoke a subhandle, and everything delegated below it, on behalf of the
+    /// owner of its parent handle.
+    pub fn revoke_subhandle(&self, revocation: HandleRevocation) -> Result<()> {
+        let Some(parent) = revocation.handle.parent() else {
+            return Err(HandleRegistryError::NotASubhandle {
+                handle: revocation.handle.as_str().to_string(),
+            });
+        };
+
         {
-            let mut map = self.owner_to_handles.write();
-            map.entry(registration.owner)
-                .or_default()
-                .push(registration.handle);
+            let handles = self.handles.read();
+            if !handles.contains_key(&revocation.handle) {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: revocation.handle.as_str().to_string(),
+                });
+            }
+            let Some(parent_meta) = handles.get(&parent) else {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: parent.as_str().to_string(),
+                });
+            };
+            if parent_meta.owner != revocation.parent_owner {
+                return Err(HandleRegistryError::Unauthorized {
+                    handle: revocation.handle.as_str().to_string(),
+                });
+            }
         }
 
+        if !self.verify_revocation_signature(&revocation) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: revocation.handle.as_str().to_string(),
+            });
+        }
+
+        self.remove_handle(&revocation.handle);
+        self.remove_descendants(&revocation.handle);
         Ok(())
     }
 
+    /// Every subhandle delegated below `parent`, at any depth, sorted by name.
+    pub fn subhandles(&self, parent: &Handle) -> Vec<Handle> {
+        let handles = self.handles.read();
+        let mut descendants: Vec<Handle> = handles
+            .keys()
+            .filter(|handle| handle.is_descendant_of(parent))
+            .cloned()
+            .collect();
+        descendants.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        descendants
+    }
+
+    /// Previous owner of a handle that is free to be registered again.
+    ///
+    /// Expired handles become available to anyone once the grace period lapses;
+    /// any other existing handle is taken.
+    fn lapsed_owner(&self, handle: &Handle) -> Result<Option<PublicKey>> {
+        let handles = self.handles.read();
+        match handles.get(handle) {
+            Some(existing) if existing.is_reclaimable_at(current_timestamp()) => {
+                Ok(Some(existing.owner.clone()))
+            }
+            Some(_) => Err(HandleRegistryError::HandleAlreadyExists {
+                handle: handle.as_str().to_string(),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a newly registered or delegated handle and index it by owner.
+    ///
+    /// A lapsed handle taken over by a new owner loses its previous owner's
+    /// indexes and every subhandle delegated under it.
+    fn insert_handle(
+        &self,
+        handle: Handle,
+        owner: PublicKey,
+        metadata: HashMap<String, String>,
+        expires_at: u64,
+        lapsed_owner: Option<PublicKey>,
+    ) {
+        if lapsed_owner.is_some() {
+            self.remove_handle(&handle);
+            self.remove_descendants(&handle);
+        }
+
+        let mut meta = HandleMetadata {
+            owner: owner.clone(),
+            expires_at,
+            metadata,
+            ..Default::default()
+        };
+        meta.l1_anchor = Some(self.compute_l1_anchor(&handle, &owner));
+
+        self.handles.write().insert(handle.clone(), meta);
+        self.owner_to_handles
+            .write()
+            .entry(owner.clone())
+            .or_default()
+            .push(handle.clone());
+
+        // The first handle an owner registers becomes their primary handle.
+        if self.primary_handle(&owner).is_none() {
+            self.primary_handles.write().insert(owner, handle);
+        }
+    }
+
+    /// Remove a handle and its owner indexes, returning the owner it had.
+    fn remove_handle(&self, handle: &Handle) -> Option<PublicKey> {
+        let owner = self.handles.write().remove(handle)?.owner;
+        if let Some(list) = self.owner_to_handles.write().get_mut(&owner) {
+            list.retain(|h| h != handle);
+        }
+        self.clear_primary_if(&owner, handle);
+        Some(owner)
+    }
+
+    /// Remove every subhandle delegated below `ancestor`.
+    fn remove_descendants(&self, ancestor: &Handle) {
+        for handle in self.subhandles(ancestor) {
+            self.remove_handle(&handle);
+        }
+    }
+
     /// Update handle metadata
     pub fn update(&self, update: HandleUpdate) -> Result<()> {
         {
@@ -104,6 +275,11 @@ impl L2HandleRegistry {
                         handle: update.handle.as_str().to_string(),
                     });
                 }
+                if meta.is_expired_at(current_timestamp()) {
+                    return Err(HandleRegistryError::HandleExpired {
+                        handle: update.handle.as_str().to_string(),
+                    });
+                }
             } else {
                 return Err(HandleRegistryError::HandleNotFound {
                     handle: update.handle.as_str().to_string(),
@@ -141,6 +317,11 @@ impl L2HandleRegistry {
                         handle: transfer.handle.as_str().to_string(),
                     });
                 }
+                if meta.is_expired_at(current_timestamp()) {
+                    return Err(HandleRegistryError::HandleExpired {
+                        handle: transfer.handle.as_str().to_string(),
+                    });
+                }
             } else {
                 return Err(HandleRegistryError::HandleNotFound {
                     handle: transfer.handle.as_str().to_string(),
@@ -158,6 +339,8 @@ impl L2HandleRegistry {
             let mut handles = self.handles.write();
             if let Some(meta) = handles.get_mut(&transfer.handle) {
                 meta.owner = transfer.to_owner.clone();
+                // Records describe the previous owner's endpoints and addresses.
+                meta.records.clear();
                 meta.updated_at = SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
@@ -172,33 +355,261 @@ impl L2HandleRegistry {
             }
             map.entry(transfer.to_owner)
                 .or_default()
-                .push(transfer.handle);
+                .push(transfer.handle.clone());
+        }
+
+        self.clear_primary_if(&transfer.from_owner, &transfer.handle);
+
+        Ok(())
+    }
+
+    /// Renew a handle by moving its expiry forward.
+    ///
+    /// Renewal is allowed while the handle is active or within its grace period;
+    /// once the grace period lapses the handle must be registered again.
+    pub fn renew(&self, renewal: HandleRenewal) -> Result<()> {
+        let now = current_timestamp();
+        {
+            let handles = self.handles.read();
+            let Some(meta) = handles.get(&renewal.handle) else {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: renewal.handle.as_str().to_string(),
+                });
+            };
+            if meta.owner != renewal.owner {
+                return Err(HandleRegistryError::Unauthorized {
+                    handle: renewal.handle.as_str().to_string(),
+                });
+            }
+            if meta.is_reclaimable_at(now) {
+                return Err(HandleRegistryError::HandleExpired {
+                    handle: renewal.handle.as_str().to_string(),
+                });
+            }
+            if meta.expires_at == 0 || renewal.expires_at <= meta.expires_at.max(now) {
+                return Err(HandleRegistryError::InvalidRenewal {
+                    handle: renewal.handle.as_str().to_string(),
+                });
+            }
+            let parent_expires_at = renewal
+                .handle
+                .parent()
+                .and_then(|parent| handles.get(&parent))
+                .map(|parent| parent.expires_at)
+                .unwrap_or(0);
+            if parent_expires_at > 0 && renewal.expires_at > parent_expires_at {
+                return Err(HandleRegistryError::SubhandleOutlivesParent {
+                    handle: renewal.handle.as_str().to_string(),
+                    parent_expires_at,
+                });
+            }
+        }
+
+        if !self.verify_renewal_signature(&renewal) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: renewal.handle.as_str().to_string(),
+            });
+        }
+
+        {
+            let mut handles = self.handles.write();
+            if let Some(meta) = handles.get_mut(&renewal.handle) {
+                meta.expires_at = renewal.expires_at;
+                meta.status = HandleStatus::Active;
+                meta.updated_at = now;
+            }
         }
 
         Ok(())
     }
 
+    /// Release a handle, removing it and its subhandles from the registry so it
+    /// can be registered again.
+    pub fn release(&self, release: HandleRelease) -> Result<()> {
+        {
+            let handles = self.handles.read();
+            let Some(meta) = handles.get(&release.handle) else {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: release.handle.as_str().to_string(),
+                });
+            };
+            if meta.owner != release.owner {
+                return Err(HandleRegistryError::Unauthorized {
+                    handle: release.handle.as_str().to_string(),
+                });
+            }
+        }
+
+        if !self.verify_release_signature(&release) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: release.handle.as_str().to_string(),
+            });
+        }
+
+        self.remove_handle(&release.handle);
+        // Subhandles cannot outlive the handle they were delegated from.
+        self.remove_descendants(&release.handle);
+
+        Ok(())
+    }
+
+    /// Apply a signed change set to the resolver records of a handle.
+    ///
+    /// Removals are applied before insertions, so a key may be removed and set
+    /// again in one update.
+    pub fn update_records(&self, update: HandleRecordUpdate) -> Result<()> {
+        {
+            let handles = self.handles.read();
+            let Some(meta) = handles.get(&update.handle) else {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: update.handle.as_str().to_string(),
+                });
+            };
+            if meta.owner != update.owner {
+                return Err(HandleRegistryError::Unauthorized {
+                    handle: update.handle.as_str().to_string(),
+                });
+            }
+            if meta.is_expired_at(current_timestamp()) {
+                return Err(HandleRegistryError::HandleExpired {
+                    handle: update.handle.as_str().to_string(),
+                });
+            }
+        }
+
+        for record in &update.set {
+            validate_record(record).map_err(|reason| HandleRegistryError::InvalidRecord {
+                handle: update.handle.as_str().to_string(),
+                reason,
+            })?;
+        }
+
+        if !self.verify_records_signature(&update) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: update.handle.as_str().to_string(),
+            });
+        }
+
+        let mut handles = self.handles.write();
+        let Some(meta) = handles.get_mut(&update.handle) else {
+            return Err(HandleRegistryError::HandleNotFound {
+                handle: update.handle.as_str().to_string(),
+            });
+        };
+
+        let mut records = meta.records.clone();
+        for key in &update.remove {
+            records.remove(key);
+        }
+        for record in update.set {
+            records.insert(record.key(), record);
+        }
+        if records.len() > MAX_HANDLE_RECORDS {
+            return Err(HandleRegistryError::TooManyRecords {
+                handle: update.handle.as_str().to_string(),
+                max: MAX_HANDLE_RECORDS,
+            });
+        }
+
+        meta.records = records;
+        meta.updated_at = current_timestamp();
+        Ok(())
+    }
+
+    /// Resolver records of a handle, keyed by [`HandleRecord::key`].
+    pub fn records(&self, handle: &Handle) -> Result<BTreeMap<String, HandleRecord>> {
+        self.resolve(handle)?;
+        let handles = self.handles.read();
+        handles
+            .get(handle)
+            .map(|meta| meta.records.clone())
+            .ok_or_else(|| HandleRegistryError::HandleNotFound {
+                handle: handle.as_str().to_string(),
+            })
+    }
+
+    /// Select the primary handle used for reverse resolution of the owner's key.
+    pub fn set_primary(&self, selection: HandlePrimarySelection) -> Result<()> {
+        {
+            let handles = self.handles.read();
+            let Some(meta) = handles.get(&selection.handle) else {
+                return Err(HandleRegistryError::HandleNotFound {
+                    handle: selection.handle.as_str().to_string(),
+                });
+            };
+            if meta.owner != selection.owner {
+                return Err(HandleRegistryError::Unauthorized {
+                    handle: selection.handle.as_str().to_string(),
+                });
+            }
+            if meta.is_expired_at(current_timestamp()) {
+                return Err(HandleRegistryError::HandleExpired {
+                    handle: selection.handle.as_str().to_string(),
+                });
+            }
+        }
+
+        if !self.verify_primary_signature(&selection) {
+            return Err(HandleRegistryError::Unauthorized {
+                handle: selection.handle.as_str().to_string(),
+            });
+        }
+
+        self.primary_handles
+            .write()
+            .insert(selection.owner, selection.handle);
+        Ok(())
+    }
+
+    /// Reverse-resolve an owner key to its primary handle.
+    ///
+    /// Returns `None` when no primary handle is set or it has since expired.
+    pub fn primary_handle(&self, owner: &PublicKey) -> Option<Handle> {
+        let handle = self.primary_handles.read().get(owner).cloned()?;
+        let handles = self.handles.read();
+        let resolved = Self::resolve_in(&handles, &handle, current_timestamp()).ok()?;
+        (resolved == *owner).then_some(handle)
+    }
+
+    fn clear_primary_if(&self, owner: &PublicKey, handle: &Handle) {
+        let mut primaries = self.primary_handles.write();
+        if primaries.get(owner) == Some(handle) {
+            primaries.remove(owner);
+        }
+    }
+
     /// Resolve handle → owner key
+    ///
+    /// A subhandle only resolves while every handle above it resolves too.
     pub fn resolve(&self, handle: &Handle) -> Result<PublicKey> {
         let handles = self.handles.read();
-        if let Some(meta) = handles.get(handle) {
-            if meta.expires_at > 0
-                && meta.expires_at
-                    < SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()
-            {
-                return Err(HandleRegistryError::HandleExpired {
+        Self::resolve_in(&handles, handle, current_timestamp())
+    }
+
+    fn resolve_in(
+        handles: &HashMap<Handle, HandleMetadata>,
+        handle: &Handle,
+        now: u64,
+    ) -> Result<PublicKey> {
+        let Some(meta) = handles.get(handle) else {
+            return Err(HandleRegistryError::HandleNotFound {
+                handle: handle.as_str().to_string(),
+            });
+        };
+        if meta.is_expired_at(now) {
+            return Err(HandleRegistryError::HandleExpired {
+                handle: handle.as_str().to_string(),
+            });
+        }
+        if let Some(parent) = handle.parent() {
+            if Self::resolve_in(handles, &parent, now).is_err() {
+                return Err(HandleRegistryError::ParentUnavailable {
                     handle: handle.as_str().to_string(),
+                    parent: parent.as_str().to_string(),
                 });
             }
-            Ok(meta.owner.clone())
-        } else {
-            Err(HandleRegistryError::HandleNotFound {
-                handle: handle.as_str().to_string(),
-            })
         }
+        Ok(meta.owner.clone())
     }
 
     /// Fetch handle metadata
@@ -348,6 +759,174 @@ impl L2HandleRegistry {
         let message_hash = Sha256::digest(&message);
         verifying_key.verify(&message_hash, &signature).is_ok()
     }
+
+    /// Verify signature for handle renewal
+    fn verify_renewal_signature(&self, renewal: &HandleRenewal) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if renewal.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(renewal.owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&renewal.signature) else {
+            return false;
+        };
+
+        // Construct the message
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_RENEWAL");
+        message.extend_from_slice(renewal.handle.as_str().as_bytes());
+        message.extend_from_slice(renewal.owner.as_bytes());
+        message.extend_from_slice(&renewal.expires_at.to_le_bytes());
+
+        let message_hash = Sha256::digest(&message);
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+
+    /// Verify signature for handle release
+    fn verify_release_signature(&self, release: &HandleRelease) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if release.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(release.owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&release.signature) else {
+            return false;
+        };
+
+        // Construct the message
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_RELEASE");
+        message.extend_from_slice(release.handle.as_str().as_bytes());
+        message.extend_from_slice(release.owner.as_bytes());
+
+        let message_hash = Sha256::digest(&message);
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+
+    /// Verify signature for a record change set
+    fn verify_records_signature(&self, update: &HandleRecordUpdate) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if update.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(update.owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&update.signature) else {
+            return false;
+        };
+
+        let message_hash = Sha256::digest(update.signing_message());
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+
+    /// Verify the parent owner's signature over a subhandle delegation
+    fn verify_delegation_signature(&self, delegation: &HandleDelegation) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if delegation.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(delegation.parent_owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&delegation.signature) else {
+            return false;
+        };
+
+        // Construct the message
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_DELEGATION");
+        message.extend_from_slice(delegation.handle.as_str().as_bytes());
+        message.extend_from_slice(delegation.parent_owner.as_bytes());
+        message.extend_from_slice(delegation.owner.as_bytes());
+        if let Some(expires) = delegation.expires_at {
+            message.extend_from_slice(&expires.to_le_bytes());
+        }
+
+        let message_hash = Sha256::digest(&message);
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+
+    /// Verify the parent owner's signature over a subhandle revocation
+    fn verify_revocation_signature(&self, revocation: &HandleRevocation) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if revocation.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(revocation.parent_owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&revocation.signature) else {
+            return false;
+        };
+
+        // Construct the message
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_REVOCATION");
+        message.extend_from_slice(revocation.handle.as_str().as_bytes());
+        message.extend_from_slice(revocation.parent_owner.as_bytes());
+
+        let message_hash = Sha256::digest(&message);
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+
+    /// Verify signature for primary handle selection
+    fn verify_primary_signature(&self, selection: &HandlePrimarySelection) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use sha2::{Digest, Sha256};
+
+        if selection.signature.len() != 64 {
+            return false;
+        }
+
+        let Ok(verifying_key) = VerifyingKey::from_bytes(selection.owner.as_bytes()) else {
+            return false;
+        };
+
+        let Ok(signature) = Signature::from_slice(&selection.signature) else {
+            return false;
+        };
+
+        // Construct the message
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_PRIMARY");
+        message.extend_from_slice(selection.handle.as_str().as_bytes());
+        message.extend_from_slice(selection.owner.as_bytes());
+
+        let message_hash = Sha256::digest(&message);
+        verifying_key.verify(&message_hash, &signature).is_ok()
+    }
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 impl Default for L2HandleRegistry {
@@ -632,4 +1211,556 @@ mod tests {
         assert_eq!(handles.len(), 3);
         assert!(handles.iter().any(|h| h.as_str().contains("one")));
     }
+
+    fn sign_message(signing_key: &ed25519_dalek::SigningKey, parts: &[&[u8]]) -> Vec<u8> {
+        use ed25519_dalek::Signer;
+        use sha2::{Digest, Sha256};
+
+        let mut message = Vec::new();
+        for part in parts {
+            message.extend_from_slice(part);
+        }
+        let hash = Sha256::digest(&message);
+        signing_key.sign(&hash).to_bytes().to_vec()
+    }
+
+    fn register_with_expiry(
+        registry: &L2HandleRegistry,
+        signing_key: &ed25519_dalek::SigningKey,
+        handle: &Handle,
+        expires_at: u64,
+    ) -> Result<()> {
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let signature = sign_message(
+            signing_key,
+            &[
+                b"IPPAN_HANDLE_REGISTRATION",
+                handle.as_str().as_bytes(),
+                owner.as_bytes(),
+                &expires_at.to_le_bytes(),
+            ],
+        );
+        registry.register(HandleRegistration {
+            handle: handle.clone(),
+            owner,
+            signature,
+            metadata: HashMap::new(),
+            expires_at: Some(expires_at),
+        })
+    }
+
+    #[test]
+    fn test_renewal_extends_expiry() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let handle = Handle::new("@renew.ipn");
+        let signing_key = SigningKey::from_bytes(&[21u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let now = current_timestamp();
+        register_with_expiry(&registry, &signing_key, &handle, now - 10).unwrap();
+        assert!(registry.resolve(&handle).is_err());
+
+        let new_expiry = now + 3600;
+        let signature = sign_message(
+            &signing_key,
+            &[
+                b"IPPAN_HANDLE_RENEWAL",
+                handle.as_str().as_bytes(),
+                owner.as_bytes(),
+                &new_expiry.to_le_bytes(),
+            ],
+        );
+        registry
+            .renew(HandleRenewal {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature,
+                expires_at: new_expiry,
+            })
+            .unwrap();
+
+        assert_eq!(registry.resolve(&handle).unwrap(), owner);
+        assert_eq!(
+            registry.get_metadata(&handle).unwrap().expires_at,
+            new_expiry
+        );
+    }
+
+    #[test]
+    fn test_renewal_rejects_shorter_expiry() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let handle = Handle::new("@short.ipn");
+        let signing_key = SigningKey::from_bytes(&[22u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let expiry = current_timestamp() + 3600;
+        register_with_expiry(&registry, &signing_key, &handle, expiry).unwrap();
+
+        let shorter = expiry - 60;
+        let signature = sign_message(
+            &signing_key,
+            &[
+                b"IPPAN_HANDLE_RENEWAL",
+                handle.as_str().as_bytes(),
+                owner.as_bytes(),
+                &shorter.to_le_bytes(),
+            ],
+        );
+        let err = registry
+            .renew(HandleRenewal {
+                handle,
+                owner,
+                signature,
+                expires_at: shorter,
+            })
+            .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::InvalidRenewal { .. }));
+    }
+
+    #[test]
+    fn test_expired_handle_reclaimable_after_grace_period() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let handle = Handle::new("@lapsed.ipn");
+        let original = SigningKey::from_bytes(&[23u8; 32]);
+        let claimant = SigningKey::from_bytes(&[24u8; 32]);
+        let now = current_timestamp();
+
+        // Still inside the grace period: nobody else may take it.
+        register_with_expiry(&registry, &original, &handle, now - 10).unwrap();
+        let err = register_with_expiry(&registry, &claimant, &handle, now + 3600).unwrap_err();
+        assert!(matches!(
+            err,
+            HandleRegistryError::HandleAlreadyExists { .. }
+        ));
+
+        // Past the grace period the handle can be registered again.
+        let lapsed = L2HandleRegistry::new();
+        register_with_expiry(
+            &lapsed,
+            &original,
+            &handle,
+            now - HANDLE_GRACE_PERIOD_SECS - 10,
+        )
+        .unwrap();
+        register_with_expiry(&lapsed, &claimant, &handle, now + 3600).unwrap();
+
+        let claimant_key = PublicKey::new(claimant.verifying_key().to_bytes());
+        let original_key = PublicKey::new(original.verifying_key().to_bytes());
+        assert_eq!(lapsed.resolve(&handle).unwrap(), claimant_key);
+        assert!(lapsed.list_owner_handles(&original_key).is_empty());
+    }
+
+    #[test]
+    fn test_release_frees_handle() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let handle = Handle::new("@release.ipn");
+        let signing_key = SigningKey::from_bytes(&[25u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        register_with_expiry(&registry, &signing_key, &handle, current_timestamp() + 60).unwrap();
+
+        let signature = sign_message(
+            &signing_key,
+            &[
+                b"IPPAN_HANDLE_RELEASE",
+                handle.as_str().as_bytes(),
+                owner.as_bytes(),
+            ],
+        );
+        registry
+            .release(HandleRelease {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature,
+            })
+            .unwrap();
+
+        assert!(matches!(
+            registry.resolve(&handle).unwrap_err(),
+            HandleRegistryError::HandleNotFound { .. }
+        ));
+        assert!(registry.list_owner_handles(&owner).is_empty());
+    }
+
+    #[test]
+    fn test_primary_handle_reverse_resolution() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let signing_key = SigningKey::from_bytes(&[24u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let expiry = current_timestamp() + 3600;
+        let first = Handle::new("@first.ipn");
+        let second = Handle::new("@second.ipn");
+        register_with_expiry(&registry, &signing_key, &first, expiry).unwrap();
+        register_with_expiry(&registry, &signing_key, &second, expiry).unwrap();
+
+        // The first registered handle is the default primary.
+        assert_eq!(registry.primary_handle(&owner), Some(first.clone()));
+
+        let signature = sign_message(
+            &signing_key,
+            &[
+                b"IPPAN_HANDLE_PRIMARY",
+                second.as_str().as_bytes(),
+                owner.as_bytes(),
+            ],
+        );
+        registry
+            .set_primary(HandlePrimarySelection {
+                handle: second.clone(),
+                owner: owner.clone(),
+                signature,
+            })
+            .unwrap();
+        assert_eq!(registry.primary_handle(&owner), Some(second.clone()));
+
+        let signature = sign_message(
+            &signing_key,
+            &[
+                b"IPPAN_HANDLE_RELEASE",
+                second.as_str().as_bytes(),
+                owner.as_bytes(),
+            ],
+        );
+        registry
+            .release(HandleRelease {
+                handle: second,
+                owner: owner.clone(),
+                signature,
+            })
+            .unwrap();
+        assert_eq!(registry.primary_handle(&owner), None);
+    }
+
+    #[test]
+    fn test_set_primary_requires_ownership() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let owner_key = SigningKey::from_bytes(&[25u8; 32]);
+        let other_key = SigningKey::from_bytes(&[26u8; 32]);
+        let other = PublicKey::new(other_key.verifying_key().to_bytes());
+        let handle = Handle::new("@owned.ipn");
+        register_with_expiry(&registry, &owner_key, &handle, current_timestamp() + 3600).unwrap();
+
+        let signature = sign_message(
+            &other_key,
+            &[
+                b"IPPAN_HANDLE_PRIMARY",
+                handle.as_str().as_bytes(),
+                other.as_bytes(),
+            ],
+        );
+        let err = registry
+            .set_primary(HandlePrimarySelection {
+                handle,
+                owner: other.clone(),
+                signature,
+            })
+            .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::Unauthorized { .. }));
+        assert_eq!(registry.primary_handle(&other), None);
+    }
+
+    #[test]
+    fn test_update_records_sets_and_removes() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let signing_key = SigningKey::from_bytes(&[27u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let handle = Handle::new("@records.ipn");
+        register_with_expiry(&registry, &signing_key, &handle, current_timestamp() + 3600).unwrap();
+
+        let set = vec![
+            HandleRecord::Payment { address: [8u8; 32] },
+            HandleRecord::Txt {
+                name: "bio".into(),
+                value: "hi".into(),
+            },
+        ];
+        let signature = sign_message(
+            &signing_key,
+            &[&record_update_message(&handle, &owner, &set, &[])],
+        );
+        registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature,
+                set,
+                remove: Vec::new(),
+            })
+            .unwrap();
+        let records = registry.records(&handle).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.contains_key("payment"));
+
+        let remove = vec!["txt:bio".to_string()];
+        let signature = sign_message(
+            &signing_key,
+            &[&record_update_message(&handle, &owner, &[], &remove)],
+        );
+        registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature,
+                set: Vec::new(),
+                remove,
+            })
+            .unwrap();
+        let records = registry.records(&handle).unwrap();
+        assert_eq!(records.keys().collect::<Vec<_>>(), vec!["payment"]);
+    }
+
+    #[test]
+    fn test_update_records_rejects_invalid_record_and_signature() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let signing_key = SigningKey::from_bytes(&[28u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+        let handle = Handle::new("@strict.ipn");
+        register_with_expiry(&registry, &signing_key, &handle, current_timestamp() + 3600).unwrap();
+
+        let invalid = vec![HandleRecord::Node {
+            multiaddr: "not-a-multiaddr".into(),
+        }];
+        let signature = sign_message(
+            &signing_key,
+            &[&record_update_message(&handle, &owner, &invalid, &[])],
+        );
+        let err = registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature,
+                set: invalid,
+                remove: Vec::new(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::InvalidRecord { .. }));
+
+        // Signature over a different change set does not authorise this one.
+        let signed = vec![HandleRecord::Payment { address: [1u8; 32] }];
+        let applied = vec![HandleRecord::Payment { address: [2u8; 32] }];
+        let signature = sign_message(
+            &signing_key,
+            &[&record_update_message(&handle, &owner, &signed, &[])],
+        );
+        let err = registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner,
+                signature,
+                set: applied,
+                remove: Vec::new(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::Unauthorized { .. }));
+        assert!(registry.records(&handle).unwrap().is_empty());
+    }
+
+    fn delegate(
+        registry: &L2HandleRegistry,
+        parent_key: &ed25519_dalek::SigningKey,
+        handle: &Handle,
+        owner: &PublicKey,
+        expires_at: Option<u64>,
+    ) -> Result<()> {
+        let parent_owner = PublicKey::new(parent_key.verifying_key().to_bytes());
+        let expiry = expires_at.map(u64::to_le_bytes);
+        let mut parts: Vec<&[u8]> = vec![
+            b"IPPAN_HANDLE_DELEGATION",
+            handle.as_str().as_bytes(),
+            parent_owner.as_bytes(),
+            owner.as_bytes(),
+        ];
+        if let Some(expiry) = &expiry {
+            parts.push(expiry);
+        }
+        let signature = sign_message(parent_key, &parts);
+        registry.register_subhandle(HandleDelegation {
+            handle: handle.clone(),
+            parent_owner,
+            owner: owner.clone(),
+            signature,
+            metadata: HashMap::new(),
+            expires_at,
+        })
+    }
+
+    #[test]
+    fn test_handle_parent_parsing() {
+        assert_eq!(
+            Handle::new("@alice.acme.ipn").parent(),
+            Some(Handle::new("@acme.ipn"))
+        );
+        assert_eq!(Handle::new("@acme.ipn").parent(), None);
+        assert!(Handle::new("@a.b.acme.ipn").is_descendant_of(&Handle::new("@acme.ipn")));
+        assert!(!Handle::new("@xacme.ipn").is_descendant_of(&Handle::new("@acme.ipn")));
+        assert!(!Handle::new("@acme.ipn").is_descendant_of(&Handle::new("@acme.ipn")));
+    }
+
+    #[test]
+    fn test_subhandle_delegation_bounded_by_parent() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let org_key = SigningKey::from_bytes(&[30u8; 32]);
+        let employee = PublicKey::new(
+            SigningKey::from_bytes(&[31u8; 32])
+                .verifying_key()
+                .to_bytes(),
+        );
+        let parent = Handle::new("@acme.ipn");
+        let parent_expiry = current_timestamp() + 3600;
+        register_with_expiry(&registry, &org_key, &parent, parent_expiry).unwrap();
+
+        // Subhandles cannot be registered directly.
+        let direct = registry.register(HandleRegistration {
+            handle: Handle::new("@alice.acme.ipn"),
+            owner: employee.clone(),
+            signature: vec![0u8; 64],
+            metadata: HashMap::new(),
+            expires_at: None,
+        });
+        assert!(matches!(
+            direct,
+            Err(HandleRegistryError::SubhandleRequiresDelegation { .. })
+        ));
+
+        let alice = Handle::new("@alice.acme.ipn");
+        let err = delegate(
+            &registry,
+            &org_key,
+            &alice,
+            &employee,
+            Some(parent_expiry + 1),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            HandleRegistryError::SubhandleOutlivesParent { .. }
+        ));
+
+        delegate(&registry, &org_key, &alice, &employee, None).unwrap();
+        assert_eq!(registry.resolve(&alice).unwrap(), employee);
+        assert_eq!(
+            registry.get_metadata(&alice).unwrap().expires_at,
+            parent_expiry
+        );
+        assert_eq!(registry.primary_handle(&employee), Some(alice.clone()));
+        assert_eq!(registry.subhandles(&parent), vec![alice.clone()]);
+
+        // Only the parent owner may delegate.
+        let outsider = SigningKey::from_bytes(&[32u8; 32]);
+        let err = delegate(
+            &registry,
+            &outsider,
+            &Handle::new("@mallory.acme.ipn"),
+            &employee,
+            None,
+        )
+        .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::Unauthorized { .. }));
+
+        // Once the parent expires the subhandle stops resolving.
+        registry
+            .handles
+            .write()
+            .get_mut(&parent)
+            .unwrap()
+            .expires_at = current_timestamp() - 1;
+        assert!(matches!(
+            registry.resolve(&alice),
+            Err(HandleRegistryError::ParentUnavailable { .. })
+        ));
+        assert_eq!(registry.primary_handle(&employee), None);
+    }
+
+    #[test]
+    fn test_subhandle_revocation_and_parent_release() {
+        use ed25519_dalek::SigningKey;
+
+        let registry = L2HandleRegistry::new();
+        let org_key = SigningKey::from_bytes(&[33u8; 32]);
+        let org = PublicKey::new(org_key.verifying_key().to_bytes());
+        let team_key = SigningKey::from_bytes(&[34u8; 32]);
+        let team = PublicKey::new(team_key.verifying_key().to_bytes());
+        let parent = Handle::new("@corp.ipn");
+        register_with_expiry(&registry, &org_key, &parent, current_timestamp() + 3600).unwrap();
+
+        let eng = Handle::new("@eng.corp.ipn");
+        let bob = Handle::new("@bob.eng.corp.ipn");
+        delegate(&registry, &org_key, &eng, &team, None).unwrap();
+        delegate(&registry, &team_key, &bob, &org, None).unwrap();
+        assert_eq!(registry.subhandles(&parent), vec![bob.clone(), eng.clone()]);
+
+        // The subhandle owner cannot revoke on the parent's behalf.
+        let signature = sign_message(
+            &team_key,
+            &[
+                b"IPPAN_HANDLE_REVOCATION",
+                eng.as_str().as_bytes(),
+                team.as_bytes(),
+            ],
+        );
+        let err = registry
+            .revoke_subhandle(HandleRevocation {
+                handle: eng.clone(),
+                parent_owner: team.clone(),
+                signature,
+            })
+            .unwrap_err();
+        assert!(matches!(err, HandleRegistryError::Unauthorized { .. }));
+
+        // Revoking removes the subhandle and everything below it.
+        let signature = sign_message(
+            &org_key,
+            &[
+                b"IPPAN_HANDLE_REVOCATION",
+                eng.as_str().as_bytes(),
+                org.as_bytes(),
+            ],
+        );
+        registry
+            .revoke_subhandle(HandleRevocation {
+                handle: eng.clone(),
+                parent_owner: org.clone(),
+                signature,
+            })
+            .unwrap();
+        assert!(registry.subhandles(&parent).is_empty());
+        assert!(registry.list_owner_handles(&team).is_empty());
+
+        // Releasing the parent takes its subhandles with it.
+        delegate(&registry, &org_key, &eng, &team, None).unwrap();
+        let signature = sign_message(
+            &org_key,
+            &[
+                b"IPPAN_HANDLE_RELEASE",
+                parent.as_str().as_bytes(),
+                org.as_bytes(),
+            ],
+        );
+        registry
+            .release(HandleRelease {
+                handle: parent.clone(),
+                owner: org,
+                signature,
+            })
+            .unwrap();
+        assert!(matches!(
+            registry.resolve(&eng),
+            Err(HandleRegistryError::HandleNotFound { .. })
+        ));
+    }
 }
@@ -5,9 +5,10 @@
 //! and metadata retrieval.
 
 use crate::errors::*;
+use crate::records::HandleRecord;
 use crate::types::*;
 use crate::L2HandleRegistry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::time::Duration;
@@ -87,6 +88,26 @@ impl HandleResolver {
             .map_err(|_| HandleRegistryError::ResolutionTimeout)?
     }
 
+    /// Get the typed resolver records of a handle, keyed by record slot
+    pub async fn resolve_records(&self, handle: &Handle) -> Result<BTreeMap<String, HandleRecord>> {
+        let registry = self.registry.clone();
+        let handle_clone = handle.clone();
+        tokio::task::spawn_blocking(move || registry.records(&handle_clone))
+            .await
+            .map_err(|_| HandleRegistryError::ResolutionTimeout)?
+    }
+
+    /// Resolve the address payments to a handle should be sent to
+    ///
+    /// Uses the handle's `payment` record when set, otherwise the owner key.
+    pub async fn resolve_payment_address(&self, handle: &Handle) -> Result<PublicKey> {
+        let records = self.resolve_records(handle).await?;
+        match records.get("payment") {
+            Some(HandleRecord::Payment { address }) => Ok(PublicKey::new(*address)),
+            _ => self.resolve(handle).await,
+        }
+    }
+
     /// List all handles owned by a public key
     pub async fn list_owner_handles(&self, owner: &PublicKey) -> Vec<Handle> {
         self.registry.list_owner_handles(owner)
@@ -225,4 +246,58 @@ mod tests {
             assert!(results.get(&handle).unwrap().is_ok());
         }
     }
+
+    #[tokio::test]
+    async fn test_payment_address_prefers_payment_record() {
+        use crate::records::{record_update_message, HandleRecordUpdate};
+        use ed25519_dalek::{Signer, SigningKey};
+        use sha2::{Digest, Sha256};
+
+        let registry = Arc::new(L2HandleRegistry::new());
+        let resolver = HandleResolver::new(registry.clone());
+
+        let handle = Handle::new("@pay.ipn");
+        let signing_key = SigningKey::from_bytes(&[44u8; 32]);
+        let owner = PublicKey::new(signing_key.verifying_key().to_bytes());
+
+        let mut message = Vec::new();
+        message.extend_from_slice(b"IPPAN_HANDLE_REGISTRATION");
+        message.extend_from_slice(handle.as_str().as_bytes());
+        message.extend_from_slice(owner.as_bytes());
+        let signature = signing_key.sign(&Sha256::digest(&message));
+        registry
+            .register(HandleRegistration {
+                handle: handle.clone(),
+                owner: owner.clone(),
+                signature: signature.to_bytes().to_vec(),
+                metadata: HashMap::new(),
+                expires_at: None,
+            })
+            .unwrap();
+
+        // Without a payment record, payments go to the owner.
+        assert_eq!(
+            resolver.resolve_payment_address(&handle).await.unwrap(),
+            owner
+        );
+
+        let set = vec![HandleRecord::Payment { address: [9u8; 32] }];
+        let message = record_update_message(&handle, &owner, &set, &[]);
+        let signature = signing_key.sign(&Sha256::digest(&message));
+        registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner,
+                signature: signature.to_bytes().to_vec(),
+                set,
+                remove: Vec::new(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            resolver.resolve_payment_address(&handle).await.unwrap(),
+            PublicKey::new([9u8; 32])
+        );
+        assert_eq!(resolver.resolve_records(&handle).await.unwrap().len(), 1);
+    }
 }
@@ -1,7 +1,8 @@
 //! Types for L2 handle registry
 
+use crate::records::HandleRecord;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Human-readable handle identifier
@@ -34,6 +35,29 @@ impl Handle {
     pub fn is_premium(&self) -> bool {
         matches!(self.tld(), Some("cyborg") | Some("iot") | Some("m"))
     }
+
+    /// Parent handle this subhandle is delegated from
+    /// (`@alice.acme.ipn` → `@acme.ipn`), or `None` for a top-level handle.
+    pub fn parent(&self) -> Option<Handle> {
+        let name = self.0.strip_prefix('@')?;
+        let (_, parent) = name.split_once('.')?;
+        parent.contains('.').then(|| Handle(format!("@{parent}")))
+    }
+
+    /// Whether this handle is a subhandle delegated by a parent handle
+    pub fn is_subhandle(&self) -> bool {
+        self.parent().is_some()
+    }
+
+    /// Whether this handle sits anywhere below `ancestor` in the hierarchy
+    pub fn is_descendant_of(&self, ancestor: &Handle) -> bool {
+        let Some(suffix) = ancestor.0.strip_prefix('@') else {
+            return false;
+        };
+        self.0.len() > ancestor.0.len() + 1
+            && self.0.ends_with(suffix)
+            && self.0[..self.0.len() - suffix.len()].ends_with('.')
+    }
 }
 
 /// Public key identifier (Ed25519)
@@ -52,6 +76,10 @@ impl PublicKey {
     }
 }
 
+/// Seconds an expired handle stays reserved for its previous owner before it
+/// can be registered by anyone else (30 days).
+pub const HANDLE_GRACE_PERIOD_SECS: u64 = 30 * 24 * 60 * 60;
+
 /// Handle metadata stored on L2
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HandleMetadata {
@@ -67,6 +95,9 @@ pub struct HandleMetadata {
     pub status: HandleStatus,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// Typed resolver records keyed by [`HandleRecord::key`]
+    #[serde(default)]
+    pub records: BTreeMap<String, HandleRecord>,
     /// L1 anchor hash (points to L1 ownership proof)
     pub l1_anchor: Option<[u8; 32]>,
 }
@@ -95,6 +126,19 @@ pub struct HandleListItem {
     pub updated_at: Option<u64>,
 }
 
+impl HandleMetadata {
+    /// Whether the handle has passed its expiry at `now` (seconds).
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at > 0 && self.expires_at < now
+    }
+
+    /// Whether the handle has expired and its grace period has elapsed,
+    /// making it available for registration by any key.
+    pub fn is_reclaimable_at(&self, now: u64) -> bool {
+        self.expires_at > 0 && self.expires_at.saturating_add(HANDLE_GRACE_PERIOD_SECS) < now
+    }
+}
+
 impl Default for HandleMetadata {
     fn default() -> Self {
         let now = SystemTime::now()
@@ -109,6 +153,7 @@ impl Default for HandleMetadata {
             expires_at: 0, // Never expires by default
             status: HandleStatus::Active,
             metadata: HashMap::new(),
+            records: BTreeMap::new(),
             l1_anchor: None,
         }
     }
@@ -142,6 +187,55 @@ pub struct HandleTransfer {
     pub signature: Vec<u8>,
 }
 
+/// Handle renewal request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleRenewal {
+    pub handle: Handle,
+    pub owner: PublicKey,
+    pub signature: Vec<u8>,
+    /// New expiration timestamp (seconds since UNIX_EPOCH)
+    pub expires_at: u64,
+}
+
+/// Handle release request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleRelease {
+    pub handle: Handle,
+    pub owner: PublicKey,
+    pub signature: Vec<u8>,
+}
+
+/// Primary handle selection used for reverse resolution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandlePrimarySelection {
+    pub handle: Handle,
+    pub owner: PublicKey,
+    pub signature: Vec<u8>,
+}
+
+/// Subhandle issued by the owner of its parent handle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleDelegation {
+    /// Subhandle being issued, e.g. `@alice.acme.ipn`
+    pub handle: Handle,
+    /// Owner of the parent handle, who signs the delegation
+    pub parent_owner: PublicKey,
+    /// Key the subhandle is issued to
+    pub owner: PublicKey,
+    pub signature: Vec<u8>,
+    pub metadata: HashMap<String, String>,
+    /// Requested expiry; `None` inherits the parent's expiry
+    pub expires_at: Option<u64>,
+}
+
+/// Revocation of a subhandle by the owner of its parent handle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandleRevocation {
+    pub handle: Handle,
+    pub parent_owner: PublicKey,
+    pub signature: Vec<u8>,
+}
+
 /// L1 ownership anchor (stored on L1)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct L1OwnershipAnchor {
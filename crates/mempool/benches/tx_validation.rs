@@ -46,6 +46,8 @@ fn build_transaction(to: [u8; 32], nonce: u64) -> Transaction {
         handle_op: None,
         confidential: None,
         zk_proof: None,
+        file_anchor: None,
+        model_governance: None,
         signature: [0u8; 64],
     };
 
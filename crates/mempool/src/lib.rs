@@ -226,6 +226,15 @@ impl Mempool {
             .map(|meta| meta.transaction.clone())
     }
 
+    /// Snapshot of every pending transaction, in no particular order.
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.transactions
+            .read()
+            .values()
+            .map(|meta| meta.transaction.clone())
+            .collect()
+    }
+
     pub fn get_sender_transactions(&self, sender: &str) -> Vec<Transaction> {
         let transactions = self.transactions.read();
         let sender_nonces = self.sender_nonces.read();
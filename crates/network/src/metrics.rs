@@ -38,6 +38,13 @@ pub struct NetworkMetrics {
     connections_closed: AtomicU64,
     connections_failed: AtomicU64,
 
+    // Compact block relay
+    compact_blocks_sent: AtomicU64,
+    compact_blocks_reconstructed: AtomicU64,
+    compact_block_fallbacks: AtomicU64,
+    compact_missing_transactions: AtomicU64,
+    compact_bytes_saved: AtomicU64,
+
     // Timing
     start_time: Instant,
 
@@ -63,6 +70,11 @@ impl NetworkMetrics {
             connections_opened: AtomicU64::new(0),
             connections_closed: AtomicU64::new(0),
             connections_failed: AtomicU64::new(0),
+            compact_blocks_sent: AtomicU64::new(0),
+            compact_blocks_reconstructed: AtomicU64::new(0),
+            compact_block_fallbacks: AtomicU64::new(0),
+            compact_missing_transactions: AtomicU64::new(0),
+            compact_bytes_saved: AtomicU64::new(0),
             start_time: Instant::now(),
             avg_latency_micros: RwLock::new(0),
             max_latency_micros: AtomicU64::new(0),
@@ -106,6 +118,29 @@ impl NetworkMetrics {
         self.connections_failed.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a block relayed in compact form. `sent_bytes` covers the
+    /// compact block plus any missing transactions sent afterwards.
+    pub fn record_compact_block_sent(&self, full_bytes: usize, sent_bytes: usize) {
+        self.compact_blocks_sent.fetch_add(1, Ordering::Relaxed);
+        self.compact_bytes_saved.fetch_add(
+            full_bytes.saturating_sub(sent_bytes) as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Record a compact block rebuilt after requesting `missing` transactions.
+    pub fn record_compact_block_reconstructed(&self, missing: usize) {
+        self.compact_blocks_reconstructed
+            .fetch_add(1, Ordering::Relaxed);
+        self.compact_missing_transactions
+            .fetch_add(missing as u64, Ordering::Relaxed);
+    }
+
+    /// Record a compact block that had to be replaced by the full block.
+    pub fn record_compact_block_fallback(&self) {
+        self.compact_block_fallbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn record_latency(&self, latency: Duration) {
         let latency_micros = latency.as_micros() as u64;
 
@@ -147,6 +182,11 @@ impl NetworkMetrics {
             connections_closed: self.connections_closed.load(Ordering::Relaxed),
             connections_failed: self.connections_failed.load(Ordering::Relaxed),
             active_connections: self.active_connections(),
+            compact_blocks_sent: self.compact_blocks_sent.load(Ordering::Relaxed),
+            compact_blocks_reconstructed: self.compact_blocks_reconstructed.load(Ordering::Relaxed),
+            compact_block_fallbacks: self.compact_block_fallbacks.load(Ordering::Relaxed),
+            compact_missing_transactions: self.compact_missing_transactions.load(Ordering::Relaxed),
+            compact_bytes_saved: self.compact_bytes_saved.load(Ordering::Relaxed),
             uptime_seconds: self.start_time.elapsed().as_secs(),
             avg_latency_micros: *self.avg_latency_micros.read(),
             max_latency_micros: self.max_latency_micros.load(Ordering::Relaxed),
@@ -170,6 +210,13 @@ impl NetworkMetrics {
         self.connections_opened.store(0, Ordering::Relaxed);
         self.connections_closed.store(0, Ordering::Relaxed);
         self.connections_failed.store(0, Ordering::Relaxed);
+        self.compact_blocks_sent.store(0, Ordering::Relaxed);
+        self.compact_blocks_reconstructed
+            .store(0, Ordering::Relaxed);
+        self.compact_block_fallbacks.store(0, Ordering::Relaxed);
+        self.compact_missing_transactions
+            .store(0, Ordering::Relaxed);
+        self.compact_bytes_saved.store(0, Ordering::Relaxed);
         *self.avg_latency_micros.write() = 0;
         self.max_latency_micros.store(0, Ordering::Relaxed);
         self.latency_samples.store(0, Ordering::Relaxed);
@@ -232,6 +279,17 @@ pub struct NetworkMetricsSnapshot {
     pub connections_closed: u64,
     pub connections_failed: u64,
     pub active_connections: u64,
+    #[serde(default)]
+    pub compact_blocks_sent: u64,
+    #[serde(default)]
+    pub compact_blocks_reconstructed: u64,
+    #[serde(default)]
+    pub compact_block_fallbacks: u64,
+    #[serde(default)]
+    pub compact_missing_transactions: u64,
+    /// Bytes not sent thanks to compact block relay.
+    #[serde(default)]
+    pub compact_bytes_saved: u64,
     pub uptime_seconds: u64,
     pub avg_latency_micros: u64,
     pub max_latency_micros: u64,
@@ -346,6 +404,11 @@ mod tests {
             connections_closed: 3,
             connections_failed: 0,
             active_connections: 2,
+            compact_blocks_sent: 0,
+            compact_blocks_reconstructed: 0,
+            compact_block_fallbacks: 0,
+            compact_missing_transactions: 0,
+            compact_bytes_saved: 0,
             uptime_seconds: 10,
             avg_latency_micros: 12_300,
             max_latency_micros: 40_000,
@@ -137,6 +137,21 @@ impl ReputationManager {
         }
     }
 
+    /// Ban a peer outright, e.g. after it served provably forged data
+    pub fn ban(&self, peer_address: &str) {
+        let mut peers = self.peers.write();
+        let reputation = peers
+            .entry(peer_address.to_string())
+            .or_insert_with(PeerReputation::new);
+        reputation.invalid_messages += 1;
+        reputation.last_seen = Instant::now();
+        reputation.score = ReputationScore::new(ReputationScore::MIN);
+        warn!(
+            "Peer {} has been banned for serving invalid data",
+            peer_address
+        );
+    }
+
     /// Get the reputation score for a peer
     pub fn get_score(&self, peer_address: &str) -> ReputationScore {
         self.peers
@@ -287,4 +302,16 @@ mod tests {
 
         assert!(manager.should_ban(peer));
     }
+
+    #[test]
+    fn test_ban_is_immediate() {
+        let manager = ReputationManager::new(Duration::from_secs(60), 10);
+        let peer = "12D3KooWbad";
+
+        manager.record_success(peer);
+        manager.ban(peer);
+
+        assert!(manager.should_ban(peer));
+        assert_eq!(manager.get_stats(peer).unwrap().invalid_messages, 1);
+    }
 }
@@ -0,0 +1,811 @@
+//! Block synchronisation over libp2p request-response.
+//!
+//! Peers answer `/ippan/block-sync/1.0.0` requests for their latest finalized
+//! round, individual blocks by hash, the blocks of a range of rounds, round
+//! certificates and round finalization records, all read from local
+//! [`Storage`]. This replaces the HTTP `/p2p/block-request` side channel for
+//! nodes that only run the libp2p transport.
+//!
+//! [`BlockSyncer`] uses the protocol to fetch the blocks a node is missing
+//! from a peer. A peer's round certificate must match its aggregate
+//! signature before its block list is followed, and every block it lists is
+//! imported once it passes [`Block::is_valid`]; peers that serve anything
+//! else are banned through [`ReputationManager`]. Certificates and
+//! finalization records themselves are left to local consensus. The syncer
+//! records the last round it imported without gaps, so each catch-up
+//! resumes where the previous one stopped.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::io::{AsyncRead, AsyncWrite};
+use ippan_network::ReputationManager;
+use ippan_storage::Storage;
+use ippan_types::{Block, BlockId, RoundCertificate, RoundFinalizationRecord, RoundId};
+use libp2p::request_response;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::chunk_exchange::{read_json, write_json};
+
+/// Protocol name negotiated for block sync.
+pub const BLOCK_SYNC_PROTOCOL_NAME: &str = "/ippan/block-sync/1.0.0";
+
+/// Maximum number of rounds served for one [`BlockSyncRequest::Rounds`].
+pub const MAX_ROUNDS_PER_REQUEST: u64 = 32;
+
+/// Maximum number of blocks returned in one response.
+pub const MAX_BLOCKS_PER_RESPONSE: usize = 256;
+
+/// How many rounds past the local tip one [`BlockSyncer::catch_up`] covers,
+/// whatever round the peer advertises.
+pub const MAX_CATCH_UP_ROUNDS: u64 = 1024;
+
+/// Upper bound on an encoded request or response.
+const MAX_MESSAGE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Module state key holding the last round imported without gaps.
+const SYNCED_ROUND_STATE_KEY: &str = "block_sync_round";
+
+/// Request sent to a block sync peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlockSyncRequest {
+    /// Ask for the peer's latest finalized round.
+    Status,
+    /// Ask for a single block by hash.
+    Block { hash: BlockId },
+    /// Ask for the certified blocks of rounds `from..=to`.
+    Rounds { from: RoundId, to: RoundId },
+    /// Ask for the certificate of a round.
+    Certificate { round: RoundId },
+    /// Ask for the finalization record of a round.
+    Finalization { round: RoundId },
+}
+
+/// Response returned by a block sync peer. `None` means "not held".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlockSyncResponse {
+    Status {
+        latest_finalized_round: Option<RoundId>,
+    },
+    Block {
+        block: Option<Box<Block>>,
+    },
+    Rounds {
+        blocks: Vec<Block>,
+    },
+    Certificate {
+        certificate: Option<RoundCertificate>,
+    },
+    Finalization {
+        record: Option<Box<RoundFinalizationRecord>>,
+    },
+}
+
+/// Answer a block sync request from local storage.
+///
+/// Range requests are clamped to [`MAX_ROUNDS_PER_REQUEST`] rounds and
+/// [`MAX_BLOCKS_PER_RESPONSE`] blocks; callers fetch whatever is missing by
+/// hash.
+pub fn serve_block_sync_request(
+    storage: &dyn Storage,
+    request: &BlockSyncRequest,
+) -> BlockSyncResponse {
+    match request {
+        BlockSyncRequest::Status => BlockSyncResponse::Status {
+            latest_finalized_round: storage
+                .get_latest_round_finalization()
+                .unwrap_or_else(|err| {
+                    warn!("Not serving sync status: {err}");
+                    None
+                })
+                .map(|record| record.round),
+        },
+        BlockSyncRequest::Block { hash } => BlockSyncResponse::Block {
+            block: load_block(storage, hash).map(Box::new),
+        },
+        BlockSyncRequest::Rounds { from, to } => {
+            let last = (*to).min(from.saturating_add(MAX_ROUNDS_PER_REQUEST - 1));
+            let mut blocks = Vec::new();
+            'rounds: for round in *from..=last {
+                let Some(certificate) = round_certificate(storage, round) else {
+                    continue;
+                };
+                for hash in &certificate.block_ids {
+                    if blocks.len() >= MAX_BLOCKS_PER_RESPONSE {
+                        break 'rounds;
+                    }
+                    if let Some(block) = load_block(storage, hash) {
+                        blocks.push(block);
+                    }
+                }
+            }
+            BlockSyncResponse::Rounds { blocks }
+        }
+        BlockSyncRequest::Certificate { round } => BlockSyncResponse::Certificate {
+            certificate: round_certificate(storage, *round),
+        },
+        BlockSyncRequest::Finalization { round } => BlockSyncResponse::Finalization {
+            record: storage
+                .get_round_finalization(*round)
+                .unwrap_or_else(|err| {
+                    warn!(round, "Not serving finalization record: {err}");
+                    None
+                })
+                .map(Box::new),
+        },
+    }
+}
+
+fn load_block(storage: &dyn Storage, hash: &BlockId) -> Option<Block> {
+    storage.get_block(hash).unwrap_or_else(|err| {
+        warn!(hash = %hex::encode(hash), "Not serving block: {err}");
+        None
+    })
+}
+
+/// The stored certificate for `round`, falling back to the one embedded in
+/// its finalization record.
+fn round_certificate(storage: &dyn Storage, round: RoundId) -> Option<RoundCertificate> {
+    match storage.get_round_certificate(round) {
+        Ok(Some(certificate)) => Some(certificate),
+        Ok(None) => storage
+            .get_round_finalization(round)
+            .ok()
+            .flatten()
+            .map(|record| record.proof),
+        Err(err) => {
+            warn!(round, "Not serving round certificate: {err}");
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSyncProtocol;
+
+impl AsRef<str> for BlockSyncProtocol {
+    fn as_ref(&self) -> &str {
+        BLOCK_SYNC_PROTOCOL_NAME
+    }
+}
+
+/// JSON codec for [`BlockSyncRequest`] / [`BlockSyncResponse`].
+#[derive(Debug, Clone, Default)]
+pub struct BlockSyncCodec;
+
+#[async_trait]
+impl request_response::Codec for BlockSyncCodec {
+    type Protocol = BlockSyncProtocol;
+    type Request = BlockSyncRequest;
+    type Response = BlockSyncResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io, MAX_MESSAGE_BYTES).await
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io, MAX_MESSAGE_BYTES).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        request: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &request).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        response: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &response).await
+    }
+}
+
+/// Transport used by [`BlockSyncer`] to reach peers.
+#[async_trait]
+pub trait BlockSyncSource: Send + Sync {
+    /// Send `request` to `peer` and await its response.
+    async fn request(&self, peer: &str, request: BlockSyncRequest) -> Result<BlockSyncResponse>;
+}
+
+/// Errors returned by [`BlockSyncer`].
+#[derive(Debug, Error)]
+pub enum BlockSyncError {
+    #[error("peer {0} is banned")]
+    BannedPeer(String),
+    #[error("request to {peer} failed: {reason}")]
+    Request { peer: String, reason: String },
+    #[error("peer {peer} sent an invalid response: {reason}")]
+    Invalid { peer: String, reason: String },
+    #[error("block {0} listed in a certificate could not be fetched")]
+    MissingBlock(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// What a [`BlockSyncer::catch_up`] call imported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockSyncProgress {
+    /// Latest finalized round advertised by the peer.
+    pub remote_round: Option<RoundId>,
+    /// Rounds that gained at least one block.
+    pub rounds: u64,
+    /// Blocks imported.
+    pub blocks: u64,
+}
+
+/// Verifying catch-up client for the block sync protocol.
+#[derive(Clone)]
+pub struct BlockSyncer {
+    source: Arc<dyn BlockSyncSource>,
+    storage: Arc<dyn Storage + Send + Sync>,
+    reputation: Arc<ReputationManager>,
+    rounds_per_request: u64,
+}
+
+impl BlockSyncer {
+    pub fn new(
+        source: Arc<dyn BlockSyncSource>,
+        storage: Arc<dyn Storage + Send + Sync>,
+        reputation: Arc<ReputationManager>,
+    ) -> Self {
+        Self {
+            source,
+            storage,
+            reputation,
+            rounds_per_request: MAX_ROUNDS_PER_REQUEST,
+        }
+    }
+
+    /// Override how many rounds are requested at once (1..=[`MAX_ROUNDS_PER_REQUEST`]).
+    pub fn with_rounds_per_request(mut self, rounds: u64) -> Self {
+        self.rounds_per_request = rounds.clamp(1, MAX_ROUNDS_PER_REQUEST);
+        self
+    }
+
+    /// Import the blocks `peer` holds for rounds past our latest finalized
+    /// or synced one, looking at most [`MAX_CATCH_UP_ROUNDS`] rounds ahead.
+    ///
+    /// The last round imported without gaps is persisted after every range,
+    /// so the next call continues from there and an interrupted catch-up
+    /// only re-requests what it had not stored yet.
+    pub async fn catch_up(&self, peer: &str) -> Result<BlockSyncProgress, BlockSyncError> {
+        let remote_round = match self.request(peer, BlockSyncRequest::Status).await? {
+            BlockSyncResponse::Status {
+                latest_finalized_round,
+            } => latest_finalized_round,
+            _ => return Err(self.invalid(peer, "expected a status response")),
+        };
+        let mut progress = BlockSyncProgress {
+            remote_round,
+            ..BlockSyncProgress::default()
+        };
+        let Some(remote_round) = remote_round else {
+            return Ok(progress);
+        };
+        let local_round = self
+            .storage
+            .get_latest_round_finalization()?
+            .map(|record| record.round);
+        let mut synced = local_round.max(self.synced_round()?);
+        let mut next = match synced {
+            Some(round) if round >= remote_round => return Ok(progress),
+            // Cannot overflow: round < remote_round
+            Some(round) => round + 1,
+            None => 0,
+        };
+        let target = remote_round.min(next.saturating_add(MAX_CATCH_UP_ROUNDS - 1));
+
+        loop {
+            let last = target.min(next.saturating_add(self.rounds_per_request - 1));
+            let blocks = match self
+                .request(
+                    peer,
+                    BlockSyncRequest::Rounds {
+                        from: next,
+                        to: last,
+                    },
+                )
+                .await?
+            {
+                BlockSyncResponse::Rounds { blocks } => blocks,
+                _ => return Err(self.invalid(peer, "expected a rounds response")),
+            };
+            let mut by_hash: HashMap<BlockId, Block> = HashMap::new();
+            for block in blocks {
+                if block.header.round < next || block.header.round > last {
+                    return Err(self.invalid(peer, "block outside the requested rounds"));
+                }
+                by_hash.insert(block.hash(), block);
+            }
+
+            let mut advanced = false;
+            for round in next..=last {
+                // Rounds the peer holds no certificate for stay open until a
+                // later certified round shows they were skipped
+                let Some(imported) = self.import_round(peer, round, &mut by_hash).await? else {
+                    continue;
+                };
+                if imported > 0 {
+                    progress.rounds += 1;
+                    progress.blocks += imported;
+                }
+                synced = Some(round);
+                advanced = true;
+            }
+            if let (true, Some(round)) = (advanced, synced) {
+                self.record_synced_round(round)?;
+            }
+            match last.checked_add(1) {
+                Some(following) if following <= target => next = following,
+                _ => break,
+            }
+        }
+
+        self.reputation.record_success(peer);
+        debug!(%peer, ?progress, "Block sync caught up");
+        Ok(progress)
+    }
+
+    /// Last round a catch-up imported without gaps.
+    pub fn synced_round(&self) -> Result<Option<RoundId>, BlockSyncError> {
+        let Some(state) = self.storage.get_module_state(SYNCED_ROUND_STATE_KEY)? else {
+            return Ok(None);
+        };
+        let bytes: [u8; 8] = state
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("corrupt block sync state"))?;
+        Ok(Some(RoundId::from_be_bytes(bytes)))
+    }
+
+    fn record_synced_round(&self, round: RoundId) -> Result<(), BlockSyncError> {
+        self.storage
+            .put_module_state(SYNCED_ROUND_STATE_KEY, &round.to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Fetch and verify a single block by hash.
+    pub async fn fetch_block(
+        &self,
+        peer: &str,
+        hash: &BlockId,
+    ) -> Result<Option<Block>, BlockSyncError> {
+        match self
+            .request(peer, BlockSyncRequest::Block { hash: *hash })
+            .await?
+        {
+            BlockSyncResponse::Block { block: None } => Ok(None),
+            BlockSyncResponse::Block { block: Some(block) } => {
+                if block.hash() != *hash || !block.is_valid() {
+                    return Err(self.invalid(peer, "forged block"));
+                }
+                Ok(Some(*block))
+            }
+            _ => Err(self.invalid(peer, "expected a block response")),
+        }
+    }
+
+    /// Fetch the certificate of `round`.
+    pub async fn fetch_certificate(
+        &self,
+        peer: &str,
+        round: RoundId,
+    ) -> Result<Option<RoundCertificate>, BlockSyncError> {
+        match self
+            .request(peer, BlockSyncRequest::Certificate { round })
+            .await?
+        {
+            BlockSyncResponse::Certificate { certificate } => match certificate {
+                Some(certificate) if certificate.round != round => {
+                    Err(self.invalid(peer, "certificate for another round"))
+                }
+                Some(certificate) if !certificate.verify_aggregate() => {
+                    Err(self.invalid(peer, "certificate does not match its aggregate signature"))
+                }
+                certificate => Ok(certificate),
+            },
+            _ => Err(self.invalid(peer, "expected a certificate response")),
+        }
+    }
+
+    /// Fetch the finalization record of `round`.
+    pub async fn fetch_finalization(
+        &self,
+        peer: &str,
+        round: RoundId,
+    ) -> Result<Option<RoundFinalizationRecord>, BlockSyncError> {
+        match self
+            .request(peer, BlockSyncRequest::Finalization { round })
+            .await?
+        {
+            BlockSyncResponse::Finalization { record } => match record {
+                Some(record) if record.round != round || record.proof.round != round => {
+                    Err(self.invalid(peer, "finalization record for another round"))
+                }
+                record => Ok(record.map(|record| *record)),
+            },
+            _ => Err(self.invalid(peer, "expected a finalization response")),
+        }
+    }
+
+    /// Store the valid blocks the verified certificate of `round` lists,
+    /// taking them from `served` or fetching those cut from the range
+    /// response. Returns how many blocks were new, or `None` if the peer
+    /// holds no certificate for the round.
+    async fn import_round(
+        &self,
+        peer: &str,
+        round: RoundId,
+        served: &mut HashMap<BlockId, Block>,
+    ) -> Result<Option<u64>, BlockSyncError> {
+        let certificate = self.fetch_certificate(peer, round).await?;
+        let listed: HashSet<BlockId> = certificate
+            .iter()
+            .flat_map(|certificate| certificate.block_ids.iter().copied())
+            .collect();
+        if served
+            .iter()
+            .any(|(hash, block)| block.header.round == round && !listed.contains(hash))
+        {
+            return Err(self.invalid(peer, "block missing from the round certificate"));
+        }
+        let Some(certificate) = certificate else {
+            return Ok(None);
+        };
+
+        let mut imported = 0;
+        let mut seen = HashSet::new();
+        for hash in &certificate.block_ids {
+            let served_block = served.remove(hash);
+            if !seen.insert(*hash) || self.storage.get_block(hash)?.is_some() {
+                continue;
+            }
+            let block = match served_block {
+                Some(block) => block,
+                None => self
+                    .fetch_block(peer, hash)
+                    .await?
+                    .ok_or_else(|| BlockSyncError::MissingBlock(hex::encode(hash)))?,
+            };
+            if !block.is_valid() || block.header.round != round {
+                return Err(self.invalid(peer, "forged block"));
+            }
+            self.storage.store_block(block)?;
+            imported += 1;
+        }
+        Ok(Some(imported))
+    }
+
+    async fn request(
+        &self,
+        peer: &str,
+        request: BlockSyncRequest,
+    ) -> Result<BlockSyncResponse, BlockSyncError> {
+        if self.reputation.should_ban(peer) {
+            return Err(BlockSyncError::BannedPeer(peer.to_string()));
+        }
+        self.source.request(peer, request).await.map_err(|err| {
+            self.reputation.record_failure(peer);
+            BlockSyncError::Request {
+                peer: peer.to_string(),
+                reason: err.to_string(),
+            }
+        })
+    }
+
+    fn invalid(&self, peer: &str, reason: &str) -> BlockSyncError {
+        warn!(%peer, reason, "Peer served invalid block sync data");
+        self.reputation.ban(peer);
+        BlockSyncError::Invalid {
+            peer: peer.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_storage::MemoryStorage;
+    use ippan_types::{IppanTimeMicros, RoundWindow};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Peers answering from their own storage. The malicious peer tampers with
+    /// every block it serves; `advertised` overrides every status answer.
+    struct FakePeers {
+        peers: HashMap<String, Arc<dyn Storage + Send + Sync>>,
+        malicious: HashSet<String>,
+        advertised: Option<RoundId>,
+        range_requests: AtomicU64,
+    }
+
+    impl FakePeers {
+        fn new(
+            peers: impl IntoIterator<Item = (&'static str, Arc<dyn Storage + Send + Sync>)>,
+        ) -> Self {
+            Self {
+                peers: peers
+                    .into_iter()
+                    .map(|(name, storage)| (name.to_string(), storage))
+                    .collect(),
+                malicious: HashSet::new(),
+                advertised: None,
+                range_requests: AtomicU64::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlockSyncSource for FakePeers {
+        async fn request(
+            &self,
+            peer: &str,
+            request: BlockSyncRequest,
+        ) -> Result<BlockSyncResponse> {
+            let storage = self
+                .peers
+                .get(peer)
+                .ok_or_else(|| anyhow::anyhow!("unknown peer {peer}"))?;
+            let mut response = serve_block_sync_request(storage.as_ref(), &request);
+            match &mut response {
+                BlockSyncResponse::Status {
+                    latest_finalized_round,
+                } if self.advertised.is_some() => *latest_finalized_round = self.advertised,
+                BlockSyncResponse::Rounds { .. } => {
+                    self.range_requests.fetch_add(1, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+            if self.malicious.contains(peer) {
+                if let BlockSyncResponse::Rounds { blocks } = &mut response {
+                    for block in blocks {
+                        block.header.merkle_payload[0] ^= 1;
+                    }
+                }
+            }
+            Ok(response)
+        }
+    }
+
+    /// Finalize `rounds` rounds of two chained blocks each.
+    fn seeded_storage(rounds: u64) -> Arc<dyn Storage + Send + Sync> {
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let mut parents = Vec::new();
+        for round in 0..rounds {
+            let blocks: Vec<Block> = (0..2u8)
+                .map(|creator| Block::new(parents.clone(), vec![], round, [creator + 1; 32]))
+                .collect();
+            parents = blocks.iter().map(Block::hash).collect();
+            let proof = RoundCertificate::new(round, parents.clone());
+            for block in blocks {
+                storage.store_block(block).unwrap();
+            }
+            storage
+                .store_round_finalization(finalization(proof))
+                .unwrap();
+        }
+        storage
+    }
+
+    fn finalization(proof: RoundCertificate) -> RoundFinalizationRecord {
+        let round = proof.round;
+        RoundFinalizationRecord {
+            round,
+            window: RoundWindow {
+                id: round,
+                start_us: IppanTimeMicros(round.wrapping_mul(100)),
+                end_us: IppanTimeMicros(round.wrapping_mul(100).wrapping_add(99)),
+            },
+            ordered_tx_ids: Vec::new(),
+            fork_drops: Vec::new(),
+            state_root: [round as u8; 32],
+            proof,
+            total_fees_atomic: None,
+            treasury_fees_atomic: None,
+            applied_payments: None,
+            rejected_payments: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_imports_blocks_only() {
+        let source = seeded_storage(5);
+        let peers = Arc::new(FakePeers::new([("honest", source.clone())]));
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let syncer = BlockSyncer::new(peers, local.clone(), Arc::new(ReputationManager::default()))
+            .with_rounds_per_request(2);
+
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!(
+            progress,
+            BlockSyncProgress {
+                remote_round: Some(4),
+                rounds: 5,
+                blocks: 10,
+            }
+        );
+        for round in 0..5 {
+            let record = source.get_round_finalization(round).unwrap().unwrap();
+            for hash in &record.proof.block_ids {
+                assert!(local.get_block(hash).unwrap().is_some());
+            }
+            // The peer's unverified records are not taken over
+            assert!(local.get_round_finalization(round).unwrap().is_none());
+            assert!(local.get_round_certificate(round).unwrap().is_none());
+        }
+
+        // Nothing left to import.
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!((progress.rounds, progress.blocks), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_bans_peers_serving_forged_blocks() {
+        let mut peers =
+            FakePeers::new([("forger", seeded_storage(3)), ("honest", seeded_storage(3))]);
+        peers.malicious.insert("forger".to_string());
+        let peers = Arc::new(peers);
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let reputation = Arc::new(ReputationManager::default());
+        let syncer = BlockSyncer::new(peers, local.clone(), reputation.clone());
+
+        assert!(matches!(
+            syncer.catch_up("forger").await,
+            Err(BlockSyncError::Invalid { .. })
+        ));
+        assert!(reputation.should_ban("forger"));
+        assert!(local.get_latest_round_finalization().unwrap().is_none());
+        assert!(matches!(
+            syncer.catch_up("forger").await,
+            Err(BlockSyncError::BannedPeer(_))
+        ));
+
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!(progress.rounds, 3);
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_bounds_advertised_rounds() {
+        // A peer claiming the last possible round is only followed for a
+        // bounded window past the local tip.
+        let mut peers = FakePeers::new([("boastful", seeded_storage(3))]);
+        peers.advertised = Some(RoundId::MAX);
+        let peers = Arc::new(peers);
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let syncer = BlockSyncer::new(
+            peers.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+
+        let progress = syncer.catch_up("boastful").await.unwrap();
+        assert_eq!((progress.rounds, progress.blocks), (3, 6));
+        assert_eq!(
+            peers.range_requests.load(Ordering::Relaxed),
+            MAX_CATCH_UP_ROUNDS / MAX_ROUNDS_PER_REQUEST
+        );
+
+        // Near the top of the round space the window stops without wrapping.
+        local
+            .store_round_finalization(finalization(RoundCertificate::new(
+                RoundId::MAX - 2,
+                Vec::new(),
+            )))
+            .unwrap();
+        peers.range_requests.store(0, Ordering::Relaxed);
+        let progress = syncer.catch_up("boastful").await.unwrap();
+        assert_eq!((progress.rounds, progress.blocks), (0, 0));
+        assert_eq!(peers.range_requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_resumes_past_the_window() {
+        let rounds = MAX_CATCH_UP_ROUNDS + 100;
+        let source = seeded_storage(rounds);
+        let peers = Arc::new(FakePeers::new([("honest", source.clone())]));
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let syncer = BlockSyncer::new(
+            peers.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!(progress.rounds, MAX_CATCH_UP_ROUNDS);
+        assert_eq!(
+            syncer.synced_round().unwrap(),
+            Some(MAX_CATCH_UP_ROUNDS - 1)
+        );
+
+        // The next call continues from the synced round instead of the
+        // start of the window
+        peers.range_requests.store(0, Ordering::Relaxed);
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!((progress.rounds, progress.blocks), (100, 200));
+        assert_eq!(
+            peers.range_requests.load(Ordering::Relaxed),
+            100u64.div_ceil(MAX_ROUNDS_PER_REQUEST)
+        );
+        let tip = source.get_round_finalization(rounds - 1).unwrap().unwrap();
+        for hash in &tip.proof.block_ids {
+            assert!(local.get_block(hash).unwrap().is_some());
+        }
+
+        // At the peer's tip nothing is requested again
+        peers.range_requests.store(0, Ordering::Relaxed);
+        let progress = syncer.catch_up("honest").await.unwrap();
+        assert_eq!((progress.rounds, progress.blocks), (0, 0));
+        assert_eq!(peers.range_requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_rejects_unverified_certificates() {
+        let source = seeded_storage(3);
+        let mut record = source.get_round_finalization(1).unwrap().unwrap();
+        record.proof.block_ids.pop();
+        source.store_round_finalization(record).unwrap();
+        let peers = Arc::new(FakePeers::new([("forger", source)]));
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let reputation = Arc::new(ReputationManager::default());
+        let syncer = BlockSyncer::new(peers, local.clone(), reputation.clone());
+
+        assert!(matches!(
+            syncer.catch_up("forger").await,
+            Err(BlockSyncError::Invalid { .. })
+        ));
+        assert!(reputation.should_ban("forger"));
+        assert_eq!(syncer.synced_round().unwrap(), None);
+    }
+
+    #[test]
+    fn test_serves_bounded_round_ranges() {
+        let storage = seeded_storage(40);
+        match serve_block_sync_request(
+            storage.as_ref(),
+            &BlockSyncRequest::Rounds { from: 0, to: 100 },
+        ) {
+            BlockSyncResponse::Rounds { blocks } => {
+                assert_eq!(blocks.len(), 2 * MAX_ROUNDS_PER_REQUEST as usize);
+                assert!(blocks
+                    .iter()
+                    .all(|block| block.header.round < MAX_ROUNDS_PER_REQUEST));
+            }
+            other => panic!("unexpected response {other:?}"),
+        }
+        match serve_block_sync_request(storage.as_ref(), &BlockSyncRequest::Status) {
+            BlockSyncResponse::Status {
+                latest_finalized_round,
+            } => assert_eq!(latest_finalized_round, Some(39)),
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,671 @@
+//! Peer-to-peer exchange of IPNDHT file content.
+//!
+//! Providers found through [`crate::IpnDhtService::provider_peers`] are asked
+//! for a file's [`ContentManifest`] and then for individual chunks by hash over
+//! a libp2p request-response protocol (`/ippan/chunks/1.0.0`).
+//!
+//! [`ChunkDownloader`] spreads chunk requests across providers, verifies every
+//! chunk before it touches the local [`ContentStore`], bans peers that serve
+//! forged manifests or chunks, and resumes partial downloads: chunks already
+//! stored locally are never fetched again.
+
+use std::collections::HashSet;
+use std::io;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures::stream::{self, StreamExt};
+use ippan_files::content::MAX_CHUNK_SIZE;
+use ippan_files::descriptor::ContentHash;
+use ippan_files::{ContentError, ContentManifest, ContentStore};
+use ippan_network::ReputationManager;
+use libp2p::request_response;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+/// Protocol name negotiated for chunk exchange.
+pub const CHUNK_PROTOCOL_NAME: &str = "/ippan/chunks/1.0.0";
+
+/// Default number of chunks fetched concurrently.
+pub const DEFAULT_DOWNLOAD_PARALLELISM: usize = 8;
+
+/// Upper bound on an encoded request or response (hex-encoded chunk plus framing).
+const MAX_MESSAGE_BYTES: u64 = (MAX_CHUNK_SIZE as u64) * 2 + 64 * 1024;
+
+/// Request sent to a content provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChunkRequest {
+    /// Ask for the manifest of the content with this root.
+    Manifest { root: ContentHash },
+    /// Ask for a single chunk by its hash.
+    Chunk { hash: ContentHash },
+}
+
+/// Response returned by a content provider. `None` means "not held".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChunkResponse {
+    Manifest {
+        manifest: Option<ContentManifest>,
+    },
+    Chunk {
+        #[serde(with = "hex_bytes")]
+        data: Option<Vec<u8>>,
+    },
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(bytes) => s.serialize_some(&hex::encode(bytes)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|encoded| hex::decode(encoded).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Answer a chunk request from the local content store.
+pub fn serve_chunk_request(store: &ContentStore, request: &ChunkRequest) -> ChunkResponse {
+    match request {
+        ChunkRequest::Manifest { root } => ChunkResponse::Manifest {
+            manifest: store.manifest(root).unwrap_or_else(|err| {
+                warn!(root = %root.to_hex(), "Not serving manifest: {err}");
+                None
+            }),
+        },
+        ChunkRequest::Chunk { hash } => ChunkResponse::Chunk {
+            data: store.chunk(hash).unwrap_or_else(|err| {
+                warn!(hash = %hash.to_hex(), "Not serving chunk: {err}");
+                None
+            }),
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkProtocol;
+
+impl AsRef<str> for ChunkProtocol {
+    fn as_ref(&self) -> &str {
+        CHUNK_PROTOCOL_NAME
+    }
+}
+
+/// JSON codec for [`ChunkRequest`] / [`ChunkResponse`], bounded by
+/// `MAX_MESSAGE_BYTES`.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkCodec;
+
+/// Read one JSON message of at most `max_bytes` from a request-response stream.
+pub(crate) async fn read_json<T, M>(io: &mut T, max_bytes: u64) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: for<'de> Deserialize<'de>,
+{
+    let mut buf = Vec::new();
+    io.take(max_bytes + 1).read_to_end(&mut buf).await?;
+    if buf.len() as u64 > max_bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "request-response message too large",
+        ));
+    }
+    serde_json::from_slice(&buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write one JSON message and close the stream.
+pub(crate) async fn write_json<T, M>(io: &mut T, message: &M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Serialize,
+{
+    let payload = serde_json::to_vec(message)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    io.write_all(&payload).await?;
+    io.close().await
+}
+
+#[async_trait]
+impl request_response::Codec for ChunkCodec {
+    type Protocol = ChunkProtocol;
+    type Request = ChunkRequest;
+    type Response = ChunkResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io, MAX_MESSAGE_BYTES).await
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_json(io, MAX_MESSAGE_BYTES).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        request: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &request).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        response: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_json(io, &response).await
+    }
+}
+
+/// Transport used by [`ChunkDownloader`] to reach providers.
+#[async_trait]
+pub trait ChunkSource: Send + Sync {
+    /// Send `request` to `peer` and await its response.
+    async fn request(&self, peer: &str, request: ChunkRequest) -> Result<ChunkResponse>;
+}
+
+/// Errors returned by [`ChunkDownloader::download`].
+#[derive(Debug, Error)]
+pub enum DownloadError {
+    #[error("no usable providers for {0}")]
+    NoProviders(String),
+    #[error("no provider returned a valid manifest for {0}")]
+    ManifestUnavailable(String),
+    #[error("stored manifest for {root} does not match: {reason}")]
+    ManifestMismatch { root: String, reason: String },
+    #[error("{} chunk(s) of {root} could not be fetched", missing.len())]
+    Incomplete { root: String, missing: Vec<usize> },
+    #[error(transparent)]
+    Content(#[from] ContentError),
+}
+
+/// What the requester knows about content before fetching it, usually taken
+/// from a signed file descriptor.
+///
+/// A manifest is only accepted when it matches every known field, so a
+/// provider cannot substitute a different layout for the same root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedContent {
+    pub root: ContentHash,
+    pub size_bytes: u64,
+    /// Chunk size, when known.
+    pub chunk_size: Option<u32>,
+}
+
+impl ExpectedContent {
+    pub fn new(root: ContentHash, size_bytes: u64) -> Self {
+        Self {
+            root,
+            size_bytes,
+            chunk_size: None,
+        }
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: u32) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Expect exactly the layout of `manifest`.
+    pub fn of(manifest: &ContentManifest) -> Self {
+        Self::new(manifest.root, manifest.size_bytes).with_chunk_size(manifest.chunk_size)
+    }
+
+    /// Describe the first field of `manifest` that differs from the expectation.
+    fn mismatch(&self, manifest: &ContentManifest) -> Option<String> {
+        if manifest.root != self.root {
+            return Some(format!("root {}", manifest.root.to_hex()));
+        }
+        if manifest.size_bytes != self.size_bytes {
+            return Some(format!(
+                "{} bytes instead of {}",
+                manifest.size_bytes, self.size_bytes
+            ));
+        }
+        match self.chunk_size {
+            Some(chunk_size) if manifest.chunk_size != chunk_size => Some(format!(
+                "chunk size {} instead of {chunk_size}",
+                manifest.chunk_size
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Parallel, verifying, resumable multi-provider downloader.
+#[derive(Clone)]
+pub struct ChunkDownloader {
+    source: Arc<dyn ChunkSource>,
+    store: Arc<ContentStore>,
+    reputation: Arc<ReputationManager>,
+    parallelism: usize,
+}
+
+impl ChunkDownloader {
+    pub fn new(
+        source: Arc<dyn ChunkSource>,
+        store: Arc<ContentStore>,
+        reputation: Arc<ReputationManager>,
+    ) -> Self {
+        Self {
+            source,
+            store,
+            reputation,
+            parallelism: DEFAULT_DOWNLOAD_PARALLELISM,
+        }
+    }
+
+    /// Override how many chunks are fetched concurrently (minimum 1).
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Download the `expected` content from `providers` into the local store.
+    ///
+    /// Chunks already present locally are skipped, so calling this again after
+    /// an [`DownloadError::Incomplete`] result only fetches what is missing.
+    pub async fn download(
+        &self,
+        expected: &ExpectedContent,
+        providers: &[String],
+    ) -> Result<ContentManifest, DownloadError> {
+        let root = &expected.root;
+        if let Some(manifest) = self.store.manifest(root)? {
+            if let Some(reason) = expected.mismatch(&manifest) {
+                return Err(DownloadError::ManifestMismatch {
+                    root: root.to_hex(),
+                    reason,
+                });
+            }
+            return Ok(manifest);
+        }
+
+        let mut seen = HashSet::new();
+        let providers: Vec<String> = providers
+            .iter()
+            .filter(|peer| !self.reputation.should_ban(peer) && seen.insert(peer.as_str()))
+            .cloned()
+            .collect();
+        if providers.is_empty() {
+            return Err(DownloadError::NoProviders(root.to_hex()));
+        }
+
+        let manifest = self.fetch_manifest(expected, &providers).await?;
+        let missing = self.store.missing_chunks(&manifest)?;
+        debug!(
+            root = %root.to_hex(),
+            missing = missing.len(),
+            total = manifest.chunk_count(),
+            "Fetching file chunks"
+        );
+
+        let failed: Vec<usize> = stream::iter(missing)
+            .map(|index| {
+                let manifest = &manifest;
+                let providers = &providers;
+                async move {
+                    match self.fetch_chunk(manifest, index, providers).await {
+                        Ok(()) => None,
+                        Err(err) => {
+                            warn!(index, "Chunk fetch failed: {err}");
+                            Some(index)
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(self.parallelism)
+            .filter_map(|failed| async move { failed })
+            .collect()
+            .await;
+
+        if !failed.is_empty() {
+            let mut missing = failed;
+            missing.sort_unstable();
+            return Err(DownloadError::Incomplete {
+                root: root.to_hex(),
+                missing,
+            });
+        }
+
+        self.store.commit_manifest(&manifest)?;
+        Ok(manifest)
+    }
+
+    async fn fetch_manifest(
+        &self,
+        expected: &ExpectedContent,
+        providers: &[String],
+    ) -> Result<ContentManifest, DownloadError> {
+        let root = &expected.root;
+        for peer in providers {
+            if self.reputation.should_ban(peer) {
+                continue;
+            }
+            match self
+                .source
+                .request(peer, ChunkRequest::Manifest { root: *root })
+                .await
+            {
+                Ok(ChunkResponse::Manifest {
+                    manifest: Some(manifest),
+                }) => {
+                    if expected.mismatch(&manifest).is_none() && manifest.verify().is_ok() {
+                        self.reputation.record_success(peer);
+                        return Ok(manifest);
+                    }
+                    warn!(%peer, root = %root.to_hex(), "Peer served a forged manifest");
+                    self.reputation.ban(peer);
+                }
+                Ok(ChunkResponse::Manifest { manifest: None }) => {
+                    self.reputation.record_failure(peer);
+                }
+                Ok(ChunkResponse::Chunk { .. }) => {
+                    self.reputation.record_invalid(peer);
+                }
+                Err(err) => {
+                    debug!(%peer, "Manifest request failed: {err}");
+                    self.reputation.record_failure(peer);
+                }
+            }
+        }
+        Err(DownloadError::ManifestUnavailable(root.to_hex()))
+    }
+
+    /// Fetch chunk `index`, starting with a provider picked by index so load
+    /// is spread across providers, and falling back to the others in turn.
+    async fn fetch_chunk(
+        &self,
+        manifest: &ContentManifest,
+        index: usize,
+        providers: &[String],
+    ) -> Result<()> {
+        let hash = manifest.chunks[index];
+        for offset in 0..providers.len() {
+            let peer = &providers[(index + offset) % providers.len()];
+            if self.reputation.should_ban(peer) {
+                continue;
+            }
+            match self
+                .source
+                .request(peer, ChunkRequest::Chunk { hash })
+                .await
+            {
+                Ok(ChunkResponse::Chunk { data: Some(data) }) => {
+                    match self.store.put_chunk(manifest, index, &data) {
+                        Ok(()) => {
+                            self.reputation.record_success(peer);
+                            return Ok(());
+                        }
+                        Err(ContentError::CorruptChunk { .. }) => {
+                            warn!(%peer, index, "Peer served a corrupt chunk");
+                            self.reputation.ban(peer);
+                        }
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Ok(ChunkResponse::Chunk { data: None }) => {
+                    self.reputation.record_failure(peer);
+                }
+                Ok(ChunkResponse::Manifest { .. }) => {
+                    self.reputation.record_invalid(peer);
+                }
+                Err(err) => {
+                    debug!(%peer, index, "Chunk request failed: {err}");
+                    self.reputation.record_failure(peer);
+                }
+            }
+        }
+        Err(anyhow!("no provider served chunk {}", hash.to_hex()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+
+    /// In-process providers keyed by peer name. Malicious peers flip a byte in
+    /// every chunk they serve, forgers answer manifest requests with their own
+    /// manifest, and withheld chunks are reported as not held.
+    #[derive(Default)]
+    struct FakeProviders {
+        stores: HashMap<String, ContentStore>,
+        malicious: HashSet<String>,
+        forged_manifests: HashMap<String, ContentManifest>,
+        withheld: Mutex<HashSet<ContentHash>>,
+        requests: Mutex<Vec<(String, ChunkRequest)>>,
+    }
+
+    #[async_trait]
+    impl ChunkSource for FakeProviders {
+        async fn request(&self, peer: &str, request: ChunkRequest) -> Result<ChunkResponse> {
+            self.requests
+                .lock()
+                .push((peer.to_string(), request.clone()));
+            let store = self
+                .stores
+                .get(peer)
+                .ok_or_else(|| anyhow!("peer {peer} unreachable"))?;
+            if let ChunkRequest::Chunk { hash } = &request {
+                if self.withheld.lock().contains(hash) {
+                    return Ok(ChunkResponse::Chunk { data: None });
+                }
+            }
+            if let (ChunkRequest::Manifest { .. }, Some(forged)) =
+                (&request, self.forged_manifests.get(peer))
+            {
+                return Ok(ChunkResponse::Manifest {
+                    manifest: Some(forged.clone()),
+                });
+            }
+            let mut response = serve_chunk_request(store, &request);
+            if self.malicious.contains(peer) {
+                if let ChunkResponse::Chunk {
+                    data: Some(ref mut data),
+                } = response
+                {
+                    data[0] ^= 0xff;
+                }
+            }
+            Ok(response)
+        }
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 253) as u8).collect()
+    }
+
+    fn seeded_store(data: &[u8]) -> (ContentStore, ContentManifest) {
+        let store = ContentStore::in_memory().with_chunk_size(16);
+        let manifest = store.put(data).unwrap();
+        (store, manifest)
+    }
+
+    fn peers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn chunk_messages_roundtrip_as_json() {
+        let response = ChunkResponse::Chunk {
+            data: Some(vec![0xde, 0xad]),
+        };
+        let encoded = serde_json::to_string(&response).unwrap();
+        assert_eq!(encoded, r#"{"kind":"chunk","data":"dead"}"#);
+        assert_eq!(
+            serde_json::from_str::<ChunkResponse>(&encoded).unwrap(),
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn downloads_from_multiple_providers_and_verifies() {
+        let data = sample(100);
+        let (store_a, manifest) = seeded_store(&data);
+        let (store_b, _) = seeded_store(&data);
+        let source = Arc::new(FakeProviders {
+            stores: HashMap::from([("a".to_string(), store_a), ("b".to_string(), store_b)]),
+            ..Default::default()
+        });
+        let local = Arc::new(ContentStore::in_memory());
+        let downloader = ChunkDownloader::new(
+            source.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+
+        let fetched = downloader
+            .download(&ExpectedContent::of(&manifest), &peers(&["a", "b"]))
+            .await
+            .expect("download");
+        assert_eq!(fetched, manifest);
+        assert_eq!(local.read(&manifest.root).unwrap(), data);
+
+        let chunk_peers: HashSet<String> = source
+            .requests
+            .lock()
+            .iter()
+            .filter(|(_, req)| matches!(req, ChunkRequest::Chunk { .. }))
+            .map(|(peer, _)| peer.clone())
+            .collect();
+        assert_eq!(chunk_peers.len(), 2, "chunks spread across providers");
+    }
+
+    #[tokio::test]
+    async fn bans_providers_serving_corrupt_chunks() {
+        let data = sample(64);
+        let (honest, manifest) = seeded_store(&data);
+        let (liar, _) = seeded_store(&data);
+        let source = Arc::new(FakeProviders {
+            stores: HashMap::from([("honest".to_string(), honest), ("liar".to_string(), liar)]),
+            malicious: HashSet::from(["liar".to_string()]),
+            ..Default::default()
+        });
+        let reputation = Arc::new(ReputationManager::default());
+        let local = Arc::new(ContentStore::in_memory());
+        let downloader =
+            ChunkDownloader::new(source, local.clone(), reputation.clone()).with_parallelism(1);
+
+        downloader
+            .download(&ExpectedContent::of(&manifest), &peers(&["liar", "honest"]))
+            .await
+            .expect("honest provider completes download");
+        assert_eq!(local.read(&manifest.root).unwrap(), data);
+        assert!(reputation.should_ban("liar"));
+        assert!(!reputation.should_ban("honest"));
+    }
+
+    #[tokio::test]
+    async fn rejects_manifests_that_differ_from_the_descriptor() {
+        let data = sample(64);
+        let (honest, manifest) = seeded_store(&data);
+        let (forger, _) = seeded_store(&data);
+        // Same root and chunk list, but a size that still passes `verify`
+        let mut forged = manifest.clone();
+        forged.size_bytes -= 1;
+        forged.verify().unwrap();
+        let source = Arc::new(FakeProviders {
+            stores: HashMap::from([
+                ("forger".to_string(), forger),
+                ("honest".to_string(), honest),
+            ]),
+            forged_manifests: HashMap::from([("forger".to_string(), forged)]),
+            ..Default::default()
+        });
+        let reputation = Arc::new(ReputationManager::default());
+        let local = Arc::new(ContentStore::in_memory());
+        let downloader = ChunkDownloader::new(source, local.clone(), reputation.clone());
+
+        let expected = ExpectedContent::new(manifest.root, manifest.size_bytes);
+        let fetched = downloader
+            .download(&expected, &peers(&["forger", "honest"]))
+            .await
+            .expect("honest provider completes download");
+        assert_eq!(fetched, manifest);
+        assert!(reputation.should_ban("forger"));
+        assert!(!reputation.should_ban("honest"));
+
+        // A cached manifest is checked against the caller's expectation too
+        let wrong_size = ExpectedContent::new(manifest.root, manifest.size_bytes + 1);
+        assert!(matches!(
+            downloader.download(&wrong_size, &peers(&["honest"])).await,
+            Err(DownloadError::ManifestMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn resumes_partial_downloads() {
+        let data = sample(80);
+        let (store, manifest) = seeded_store(&data);
+        let source = Arc::new(FakeProviders {
+            stores: HashMap::from([("peer".to_string(), store)]),
+            withheld: Mutex::new(HashSet::from([manifest.chunks[3]])),
+            ..Default::default()
+        });
+        let local = Arc::new(ContentStore::in_memory());
+        let downloader = ChunkDownloader::new(
+            source.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+
+        match downloader
+            .download(&ExpectedContent::of(&manifest), &peers(&["peer"]))
+            .await
+        {
+            Err(DownloadError::Incomplete { missing, .. }) => assert_eq!(missing, vec![3]),
+            other => panic!("expected incomplete download, got {other:?}"),
+        }
+        assert!(!local.contains(&manifest.root).unwrap());
+        assert_eq!(local.missing_chunks(&manifest).unwrap(), vec![3]);
+
+        source.withheld.lock().clear();
+        source.requests.lock().clear();
+        downloader
+            .download(&ExpectedContent::of(&manifest), &peers(&["peer"]))
+            .await
+            .expect("resumed download");
+        assert_eq!(local.read(&manifest.root).unwrap(), data);
+
+        let refetched: Vec<ContentHash> = source
+            .requests
+            .lock()
+            .iter()
+            .filter_map(|(_, req)| match req {
+                ChunkRequest::Chunk { hash } => Some(*hash),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(refetched, vec![manifest.chunks[3]]);
+    }
+}
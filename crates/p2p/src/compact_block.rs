@@ -0,0 +1,406 @@
+//! Compact block relay.
+//!
+//! A [`CompactBlock`] carries the block header with its payload identifiers
+//! replaced by 6-byte short transaction IDs. Peers usually already hold the
+//! transactions from `/p2p/transactions` gossip, so the receiver rebuilds the
+//! block from its mempool and asks the sender only for the transactions it
+//! is missing. When the rebuilt block does not match the header commitments
+//! (a short ID collision or a stale mempool entry), the sender falls back to
+//! the full block.
+//!
+//! Short IDs are keyed by the block ID, so a collision in one block does not
+//! repeat in the next.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use ippan_network::NetworkMetrics;
+use ippan_types::{Block, BlockHeader, BlockId, Transaction};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Number of partially reconstructed blocks kept while waiting for
+/// missing transactions.
+const MAX_PENDING_BLOCKS: usize = 64;
+
+/// Short transaction ID: the first 6 bytes of a keyed BLAKE3 hash.
+pub type ShortTxId = u64;
+
+/// Short ID of `tx_hash` within the block `block_id`.
+pub fn short_tx_id(block_id: &BlockId, tx_hash: &[u8; 32]) -> ShortTxId {
+    let digest = blake3::keyed_hash(block_id, tx_hash);
+    let mut bytes = [0u8; 8];
+    bytes[..6].copy_from_slice(&digest.as_bytes()[..6]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Transaction sent alongside a compact block, with its index in the block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedTransaction {
+    pub index: u32,
+    pub transaction: Transaction,
+}
+
+/// Header plus short transaction IDs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactBlock {
+    /// Block header with `payload_ids` left empty.
+    pub header: BlockHeader,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "serde_bytes")]
+    pub signature: Vec<u8>,
+    #[serde(default)]
+    pub prev_hashes: Vec<String>,
+    /// Short IDs of the block's transactions, in block order.
+    pub short_ids: Vec<ShortTxId>,
+}
+
+impl CompactBlock {
+    /// Build the compact encoding of `block` from its payload identifiers.
+    pub fn from_block(block: &Block) -> Self {
+        let id = block.hash();
+        let short_ids = block
+            .header
+            .payload_ids
+            .iter()
+            .map(|tx_hash| short_tx_id(&id, tx_hash))
+            .collect();
+        let mut header = block.header.clone();
+        header.payload_ids = Vec::new();
+        Self {
+            header,
+            signature: block.signature.clone(),
+            prev_hashes: block.prev_hashes.clone(),
+            short_ids,
+        }
+    }
+
+    /// ID of the block this compact block encodes.
+    pub fn block_id(&self) -> BlockId {
+        self.header.id
+    }
+
+    /// Match `candidates` (typically the mempool) against the short IDs.
+    /// Ambiguous short IDs are left for the sender to fill in.
+    pub fn reconstruct<I>(&self, candidates: I) -> PartialBlock
+    where
+        I: IntoIterator<Item = Transaction>,
+    {
+        let id = self.block_id();
+        let mut by_short_id: HashMap<ShortTxId, Option<Transaction>> = HashMap::new();
+        for tx in candidates {
+            by_short_id
+                .entry(short_tx_id(&id, &tx.hash()))
+                .and_modify(|slot| *slot = None)
+                .or_insert(Some(tx));
+        }
+        let slots = self
+            .short_ids
+            .iter()
+            .map(|short_id| by_short_id.get(short_id).cloned().flatten())
+            .collect();
+        PartialBlock {
+            compact: self.clone(),
+            slots,
+        }
+    }
+}
+
+/// Why a compact block could not be turned back into a block.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CompactBlockError {
+    #[error("{0} transactions are still missing")]
+    MissingTransactions(usize),
+    #[error("transaction index {0} is out of range or does not match its short id")]
+    InvalidTransaction(u32),
+    #[error("reconstructed transactions do not match the header payload root")]
+    Mismatch,
+}
+
+/// Compact block whose transactions are being filled in.
+#[derive(Debug, Clone)]
+pub struct PartialBlock {
+    compact: CompactBlock,
+    slots: Vec<Option<Transaction>>,
+}
+
+impl PartialBlock {
+    /// ID of the block being reconstructed.
+    pub fn block_id(&self) -> BlockId {
+        self.compact.block_id()
+    }
+
+    /// Indexes of transactions not found locally.
+    pub fn missing(&self) -> Vec<u32> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_none())
+            .map(|(index, _)| index as u32)
+            .collect()
+    }
+
+    /// Fill in transactions sent by the peer.
+    pub fn fill(&mut self, transactions: Vec<IndexedTransaction>) -> Result<(), CompactBlockError> {
+        let id = self.block_id();
+        for IndexedTransaction { index, transaction } in transactions {
+            let expected = self
+                .compact
+                .short_ids
+                .get(index as usize)
+                .ok_or(CompactBlockError::InvalidTransaction(index))?;
+            if short_tx_id(&id, &transaction.hash()) != *expected {
+                return Err(CompactBlockError::InvalidTransaction(index));
+            }
+            self.slots[index as usize] = Some(transaction);
+        }
+        Ok(())
+    }
+
+    /// Rebuild the block once every transaction is known, checking the
+    /// result against the header's payload Merkle root.
+    pub fn finish(&self) -> Result<Block, CompactBlockError> {
+        let missing = self.slots.iter().filter(|slot| slot.is_none()).count();
+        if missing > 0 {
+            return Err(CompactBlockError::MissingTransactions(missing));
+        }
+        let transactions: Vec<Transaction> = self.slots.iter().flatten().cloned().collect();
+        let payload_ids: Vec<[u8; 32]> = transactions.iter().map(|tx| tx.hash()).collect();
+        if Block::compute_merkle_root_from_hashes(&payload_ids)
+            != self.compact.header.merkle_payload
+        {
+            return Err(CompactBlockError::Mismatch);
+        }
+
+        let mut header = self.compact.header.clone();
+        header.payload_ids = payload_ids;
+        Ok(Block {
+            header,
+            signature: self.compact.signature.clone(),
+            transactions,
+            prev_hashes: self.compact.prev_hashes.clone(),
+        })
+    }
+}
+
+/// Reply to a compact block or to the transactions sent to complete one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CompactBlockAck {
+    /// The block was reconstructed and accepted.
+    Accepted,
+    /// Send the transactions at these indexes.
+    Missing { indexes: Vec<u32> },
+    /// Reconstruction failed; send the full block instead.
+    SendFull,
+}
+
+/// Result of feeding a compact block or missing transactions to the relay.
+#[derive(Debug, Clone)]
+pub enum CompactBlockOutcome {
+    Complete(Box<Block>),
+    Missing(Vec<u32>),
+    Mismatch,
+}
+
+impl CompactBlockOutcome {
+    /// Acknowledgement to return to the sender.
+    pub fn ack(&self) -> CompactBlockAck {
+        match self {
+            Self::Complete(_) => CompactBlockAck::Accepted,
+            Self::Missing(indexes) => CompactBlockAck::Missing {
+                indexes: indexes.clone(),
+            },
+            Self::Mismatch => CompactBlockAck::SendFull,
+        }
+    }
+}
+
+/// Receiver-side state for compact blocks awaiting missing transactions.
+#[derive(Debug)]
+pub struct CompactBlockRelay {
+    pending: Mutex<HashMap<BlockId, PartialBlock>>,
+    order: Mutex<VecDeque<BlockId>>,
+    metrics: Arc<NetworkMetrics>,
+}
+
+impl CompactBlockRelay {
+    pub fn new(metrics: Arc<NetworkMetrics>) -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            order: Mutex::new(VecDeque::new()),
+            metrics,
+        }
+    }
+
+    /// Reconstruct `compact` from `candidates`, keeping it pending when
+    /// transactions are missing.
+    pub fn receive<I>(&self, compact: &CompactBlock, candidates: I) -> CompactBlockOutcome
+    where
+        I: IntoIterator<Item = Transaction>,
+    {
+        let partial = compact.reconstruct(candidates);
+        let missing = partial.missing();
+        if missing.is_empty() {
+            return self.complete(&partial, 0);
+        }
+
+        let id = partial.block_id();
+        let mut pending = self.pending.lock();
+        let mut order = self.order.lock();
+        if pending.insert(id, partial).is_none() {
+            order.push_back(id);
+            while order.len() > MAX_PENDING_BLOCKS {
+                if let Some(evicted) = order.pop_front() {
+                    pending.remove(&evicted);
+                }
+            }
+        }
+        CompactBlockOutcome::Missing(missing)
+    }
+
+    /// Complete a pending block with the transactions sent by its peer.
+    pub fn receive_transactions(
+        &self,
+        block_id: &BlockId,
+        transactions: Vec<IndexedTransaction>,
+    ) -> CompactBlockOutcome {
+        let Some(mut partial) = self.pending.lock().remove(block_id) else {
+            return CompactBlockOutcome::Mismatch;
+        };
+        self.order.lock().retain(|id| id != block_id);
+
+        let requested = partial.missing().len();
+        if partial.fill(transactions).is_err() {
+            self.metrics.record_compact_block_fallback();
+            return CompactBlockOutcome::Mismatch;
+        }
+        self.complete(&partial, requested)
+    }
+
+    fn complete(&self, partial: &PartialBlock, requested: usize) -> CompactBlockOutcome {
+        match partial.finish() {
+            Ok(block) => {
+                self.metrics.record_compact_block_reconstructed(requested);
+                CompactBlockOutcome::Complete(Box::new(block))
+            }
+            Err(_) => {
+                self.metrics.record_compact_block_fallback();
+                CompactBlockOutcome::Mismatch
+            }
+        }
+    }
+
+    /// Number of blocks waiting for missing transactions.
+    pub fn pending_len(&self) -> usize {
+        self.pending.lock().len()
+    }
+}
+
+/// Transactions of `block` at `indexes`, for answering a missing-transactions ack.
+pub fn indexed_transactions(block: &Block, indexes: &[u32]) -> Option<Vec<IndexedTransaction>> {
+    indexes
+        .iter()
+        .map(|&index| {
+            block
+                .transactions
+                .get(index as usize)
+                .map(|transaction| IndexedTransaction {
+                    index,
+                    transaction: transaction.clone(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_types::Amount;
+
+    fn transactions(count: u64) -> Vec<Transaction> {
+        (0..count)
+            .map(|nonce| {
+                let mut tx =
+                    Transaction::new([1u8; 32], [2u8; 32], Amount(10 + u128::from(nonce)), nonce);
+                tx.id = tx.hash();
+                tx
+            })
+            .collect()
+    }
+
+    fn assert_same_block(rebuilt: &Block, block: &Block) {
+        assert_eq!(
+            serde_json::to_value(rebuilt).unwrap(),
+            serde_json::to_value(block).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compact_block_reconstructs_from_mempool() {
+        let txs = transactions(5);
+        let block = Block::new(vec![[9u8; 32]], txs.clone(), 3, [4u8; 32]);
+        let compact = CompactBlock::from_block(&block);
+        assert!(
+            serde_json::to_vec(&compact).unwrap().len() < serde_json::to_vec(&block).unwrap().len()
+        );
+
+        let partial = compact.reconstruct(txs.into_iter().rev());
+        assert!(partial.missing().is_empty());
+        assert_same_block(&partial.finish().unwrap(), &block);
+    }
+
+    #[test]
+    fn test_relay_requests_only_missing_transactions() {
+        let txs = transactions(4);
+        let block = Block::new(vec![], txs.clone(), 8, [5u8; 32]);
+        let compact = CompactBlock::from_block(&block);
+        let metrics = Arc::new(NetworkMetrics::new());
+        let relay = CompactBlockRelay::new(metrics.clone());
+
+        let known = vec![txs[0].clone(), txs[3].clone()];
+        let CompactBlockOutcome::Missing(missing) = relay.receive(&compact, known) else {
+            panic!("expected missing transactions");
+        };
+        assert_eq!(missing, vec![1, 2]);
+        assert_eq!(relay.pending_len(), 1);
+
+        let filled = indexed_transactions(&block, &missing).unwrap();
+        match relay.receive_transactions(&block.hash(), filled) {
+            CompactBlockOutcome::Complete(rebuilt) => assert_same_block(&rebuilt, &block),
+            other => panic!("expected a complete block, got {other:?}"),
+        }
+        assert_eq!(relay.pending_len(), 0);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.compact_blocks_reconstructed, 1);
+        assert_eq!(snapshot.compact_missing_transactions, 2);
+    }
+
+    #[test]
+    fn test_wrong_transactions_fall_back_to_full_block() {
+        let txs = transactions(2);
+        let block = Block::new(vec![], txs, 2, [6u8; 32]);
+        let compact = CompactBlock::from_block(&block);
+        let metrics = Arc::new(NetworkMetrics::new());
+        let relay = CompactBlockRelay::new(metrics.clone());
+
+        assert!(matches!(
+            relay.receive(&compact, Vec::new()),
+            CompactBlockOutcome::Missing(_)
+        ));
+        let wrong = transactions(3)
+            .into_iter()
+            .skip(2)
+            .map(|transaction| IndexedTransaction {
+                index: 0,
+                transaction,
+            })
+            .collect();
+        let outcome = relay.receive_transactions(&block.hash(), wrong);
+        assert_eq!(outcome.ack(), CompactBlockAck::SendFull);
+        assert_eq!(metrics.snapshot().compact_block_fallbacks, 1);
+        assert_eq!(
+            relay.receive_transactions(&block.hash(), Vec::new()).ack(),
+            CompactBlockAck::SendFull
+        );
+    }
+}
@@ -1,20 +1,27 @@
+use crate::chunk_exchange::{ChunkDownloader, ExpectedContent};
+use crate::record_store::{RecordRejection, RecordValidator};
 use crate::Libp2pNetwork;
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use blake3;
 use ippan_files::descriptor::FileDescriptor;
 use ippan_files::dht::{DhtLookupResult, DhtPublishResult, FileDhtService};
-use ippan_files::FileId;
+use ippan_files::{ContentManifest, ContentStore, FileId, L1FileAnchorStorage};
+use ippan_l1_handle_anchors::L1HandleAnchorStorage;
 use ippan_l2_handle_registry::{
     dht::{HandleDhtError, HandleDhtRecord, HandleDhtService},
     Handle,
 };
+use ippan_network::ReputationManager;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::warn;
 
+const HANDLE_KEY_PREFIX: &[u8] = b"handle:";
+
 /// Minimal metadata cached for files that have been published either locally or
 /// discovered through the DHT.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,6 +114,44 @@ impl IpnDhtService {
         Ok(peers.into_iter().map(|peer| peer.to_string()).collect())
     }
 
+    /// Serve file content held in `store` to peers requesting it over the
+    /// chunk exchange protocol.
+    pub fn serve_content(&self, store: Arc<ContentStore>) {
+        if let Some(network) = &self.network {
+            network.serve_content(store);
+        }
+    }
+
+    /// Build a downloader that fetches content from IPNDHT providers into
+    /// `store`. Returns `None` when no libp2p network is configured.
+    pub fn chunk_downloader(
+        &self,
+        store: Arc<ContentStore>,
+        reputation: Arc<ReputationManager>,
+    ) -> Option<ChunkDownloader> {
+        let network = self.network.clone()?;
+        Some(ChunkDownloader::new(network, store, reputation))
+    }
+
+    /// Resolve `id` to its descriptor and providers, then download and verify
+    /// its content with `downloader`.
+    pub async fn fetch_file_content(
+        &self,
+        id: &FileId,
+        downloader: &ChunkDownloader,
+    ) -> Result<ContentManifest> {
+        let descriptor = self
+            .find_file(id)
+            .await?
+            .ok_or_else(|| anyhow!("file descriptor {} not found", id.to_hex()))?;
+        let providers = self.provider_peers(id).await?;
+        let expected = ExpectedContent::new(descriptor.content_hash, descriptor.size_bytes);
+        downloader
+            .download(&expected, &providers)
+            .await
+            .context("download file content")
+    }
+
     /// Publish a gossip message to a specific topic (for p2p-testkit feature).
     /// This method is used by the test RPC to publish gossip messages.
     pub fn publish_gossip(&self, topic: &str, data: Vec<u8>) -> Result<()> {
@@ -134,10 +179,14 @@ impl IpnDhtService {
             );
             return None;
         }
+        if let Err(err) = descriptor.verify_signature() {
+            warn!(?requested_id, %err, "Rejected descriptor with invalid owner signature");
+            return None;
+        }
 
         let mut cache = self.cache.write();
         if let Some(existing) = cache.get(requested_id) {
-            if existing.descriptor != descriptor {
+            if existing.descriptor != descriptor && !supersedes(&descriptor, &existing.descriptor) {
                 warn!(?requested_id, "Rejected conflicting descriptor fields");
                 return Some(existing.descriptor.clone());
             }
@@ -235,12 +284,120 @@ impl IpnDhtService {
 
     fn handle_key(handle: &Handle) -> Vec<u8> {
         let digest = blake3::hash(handle.as_str().as_bytes());
-        let mut key = b"handle:".to_vec();
+        let mut key = HANDLE_KEY_PREFIX.to_vec();
         key.extend_from_slice(digest.as_bytes());
         key
     }
 }
 
+/// Validates IPNDHT records received from remote peers.
+///
+/// Handle records must match the owner anchored on L1; a handle without an
+/// anchor may only carry an expired tombstone. File records must decode to a
+/// well-formed descriptor whose id equals the record key and which is signed
+/// by its owner; anchored files must match their L1 anchor. Without anchor
+/// storage only the structural and signature checks apply.
+/// Whether `incoming` is a later recipient update of `existing`: the same
+/// owner-signed descriptor with a higher envelope version. Anything else,
+/// including an older envelope set being replayed, is a conflict.
+fn supersedes(incoming: &FileDescriptor, existing: &FileDescriptor) -> bool {
+    incoming.signature == existing.signature
+        && incoming.signing_digest() == existing.signing_digest()
+        && incoming.recipients_version() > existing.recipients_version()
+}
+
+#[derive(Clone, Default)]
+pub struct IpnDhtRecordValidator {
+    anchors: Option<Arc<L1HandleAnchorStorage>>,
+    file_anchors: Option<Arc<L1FileAnchorStorage>>,
+}
+
+impl IpnDhtRecordValidator {
+    pub fn new(anchors: Option<Arc<L1HandleAnchorStorage>>) -> Self {
+        Self {
+            anchors,
+            file_anchors: None,
+        }
+    }
+
+    /// Also check file descriptors against their L1 ownership anchors.
+    pub fn with_file_anchors(mut self, file_anchors: Arc<L1FileAnchorStorage>) -> Self {
+        self.file_anchors = Some(file_anchors);
+        self
+    }
+
+    fn validate_handle(&self, key: &[u8], value: &[u8]) -> Result<(), RecordRejection> {
+        let record: HandleDhtRecord = serde_json::from_slice(value)
+            .map_err(|err| RecordRejection::Malformed(err.to_string()))?;
+        if !record.handle.is_valid() {
+            return Err(RecordRejection::Malformed(format!(
+                "invalid handle {}",
+                record.handle.as_str()
+            )));
+        }
+        if IpnDhtService::handle_key(&record.handle) != key {
+            return Err(RecordRejection::KeyMismatch);
+        }
+
+        let Some(anchors) = &self.anchors else {
+            return Ok(());
+        };
+        let tombstone = record.expires_at.is_some_and(|at| at <= now_secs());
+        match anchors.get_anchor_by_handle(record.handle.as_str()) {
+            Ok(_) if tombstone => Err(RecordRejection::Unanchored(format!(
+                "tombstone for anchored handle {}",
+                record.handle.as_str()
+            ))),
+            Ok(anchor) if anchor.owner != record.owner.0 => Err(RecordRejection::Unanchored(
+                format!("owner mismatch for {}", record.handle.as_str()),
+            )),
+            Ok(_) => Ok(()),
+            Err(_) if tombstone => Ok(()),
+            Err(_) => Err(RecordRejection::Unanchored(format!(
+                "no anchor for {}",
+                record.handle.as_str()
+            ))),
+        }
+    }
+
+    fn validate_file(&self, key: &[u8], value: &[u8]) -> Result<(), RecordRejection> {
+        let descriptor: FileDescriptor = serde_json::from_slice(value)
+            .map_err(|err| RecordRejection::Malformed(err.to_string()))?;
+        if descriptor.id.as_bytes()[..] != *key {
+            return Err(RecordRejection::KeyMismatch);
+        }
+        descriptor.validate().map_err(RecordRejection::Malformed)?;
+        descriptor
+            .verify_signature()
+            .map_err(|_| RecordRejection::BadSignature)?;
+        if let Some(file_anchors) = &self.file_anchors {
+            file_anchors
+                .check_descriptor(&descriptor)
+                .map_err(|err| RecordRejection::Unanchored(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl RecordValidator for IpnDhtRecordValidator {
+    fn validate(&self, key: &[u8], value: &[u8]) -> Result<(), RecordRejection> {
+        if key.starts_with(HANDLE_KEY_PREFIX) {
+            self.validate_handle(key, value)
+        } else if key.len() == 32 {
+            self.validate_file(key, value)
+        } else {
+            Err(RecordRejection::UnknownKey)
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// File DHT service backed by a shared `IpnDhtService` handle.
 #[derive(Clone)]
 pub struct Libp2pFileDhtService {
@@ -376,7 +533,7 @@ mod tests {
     #[test]
     fn conflicting_descriptor_keeps_cached_version() {
         let service = IpnDhtService::new(None);
-        let descriptor = sample_descriptor();
+        let descriptor = signed_descriptor([4u8; 32]);
         service.cache.write().insert(
             descriptor.id,
             CachedDescriptor {
@@ -386,6 +543,7 @@ mod tests {
 
         let mut conflicting = descriptor.clone();
         conflicting.size_bytes += 512;
+        conflicting.sign(&[4u8; 32]).unwrap();
 
         let returned = service
             .cache_descriptor_from_dht(&descriptor.id, conflicting)
@@ -399,10 +557,66 @@ mod tests {
         assert_eq!(cached.descriptor.size_bytes, descriptor.size_bytes);
     }
 
+    #[test]
+    fn newer_recipient_version_replaces_cached_descriptor() {
+        let private_key = [4u8; 32];
+        let owner = ippan_crypto::KeyPair::from_private_key(&private_key)
+            .unwrap()
+            .public_key();
+        let reader = ippan_crypto::KeyPair::generate().public_key();
+        let file = ippan_files::encryption::encrypt_file(b"secret", &[owner]).unwrap();
+        let mut original = FileDescriptor::new(
+            ContentHash::from_data(&file.ciphertext),
+            owner,
+            file.ciphertext.len() as u64,
+            None,
+            vec![],
+        )
+        .with_encryption(file.encryption);
+        original.sign(&private_key).unwrap();
+
+        let mut shared = original.clone();
+        let encryption = shared.encryption.as_mut().unwrap();
+        encryption
+            .add_recipient(&file.content_key, &reader)
+            .unwrap();
+        encryption.version = 1;
+        shared.sign_recipients(&private_key).unwrap();
+
+        let service = IpnDhtService::new(None);
+        service.cache_descriptor_from_dht(&original.id, original.clone());
+        let returned = service
+            .cache_descriptor_from_dht(&original.id, shared.clone())
+            .unwrap();
+        assert_eq!(returned, shared);
+
+        // Replaying the older envelope set does not roll the cache back
+        let returned = service
+            .cache_descriptor_from_dht(&original.id, original.clone())
+            .unwrap();
+        assert_eq!(returned, shared);
+        assert_eq!(
+            service.cache.read()[&original.id]
+                .descriptor
+                .recipients_version(),
+            Some(1)
+        );
+    }
+
     fn sample_handle_record() -> HandleDhtRecord {
         HandleDhtRecord::new(Handle::new("@demo.ipn"), PublicKey([9u8; 32]), Some(42))
     }
 
+    #[test]
+    fn rejects_unsigned_descriptor_from_dht() {
+        let service = IpnDhtService::new(None);
+        let descriptor = sample_descriptor();
+        assert!(service
+            .cache_descriptor_from_dht(&descriptor.id, descriptor.clone())
+            .is_none());
+        assert!(service.cache.read().get(&descriptor.id).is_none());
+    }
+
     #[test]
     fn rejects_mismatched_handle_from_dht() {
         let service = IpnDhtService::new(None);
@@ -473,4 +687,169 @@ mod tests {
             .expect("record present");
         assert_eq!(fetched.expires_at, record.expires_at);
     }
+
+    fn handle_value(record: &HandleDhtRecord) -> (Vec<u8>, Vec<u8>) {
+        (
+            IpnDhtService::handle_key(&record.handle),
+            serde_json::to_vec(record).unwrap(),
+        )
+    }
+
+    #[test]
+    fn validator_checks_handle_records_against_anchors() {
+        use ippan_l1_handle_anchors::HandleOwnershipAnchor;
+
+        let anchors = Arc::new(L1HandleAnchorStorage::new());
+        anchors
+            .store_anchor(HandleOwnershipAnchor::new(
+                "@demo.ipn",
+                [9u8; 32],
+                [0u8; 32],
+                1,
+                1,
+                vec![1],
+            ))
+            .unwrap();
+        let validator = IpnDhtRecordValidator::new(Some(anchors));
+
+        let live = HandleDhtRecord::new(
+            Handle::new("@demo.ipn"),
+            PublicKey([9u8; 32]),
+            Some(now_secs() + 3600),
+        );
+        let (key, value) = handle_value(&live);
+        assert_eq!(validator.validate(&key, &value), Ok(()));
+
+        let forged = HandleDhtRecord::new(Handle::new("@demo.ipn"), PublicKey([7u8; 32]), None);
+        let (key, value) = handle_value(&forged);
+        assert!(matches!(
+            validator.validate(&key, &value),
+            Err(RecordRejection::Unanchored(_))
+        ));
+
+        let tombstone =
+            HandleDhtRecord::new(Handle::new("@demo.ipn"), PublicKey([9u8; 32]), Some(1));
+        let (key, value) = handle_value(&tombstone);
+        assert!(matches!(
+            validator.validate(&key, &value),
+            Err(RecordRejection::Unanchored(_))
+        ));
+
+        let unanchored =
+            HandleDhtRecord::new(Handle::new("@ghost.ipn"), PublicKey([9u8; 32]), None);
+        let (key, value) = handle_value(&unanchored);
+        assert!(matches!(
+            validator.validate(&key, &value),
+            Err(RecordRejection::Unanchored(_))
+        ));
+
+        let released =
+            HandleDhtRecord::new(Handle::new("@ghost.ipn"), PublicKey([9u8; 32]), Some(1));
+        let (key, value) = handle_value(&released);
+        assert_eq!(validator.validate(&key, &value), Ok(()));
+
+        let (_, value) = handle_value(&sample_handle_record());
+        let other_key = IpnDhtService::handle_key(&Handle::new("@other.ipn"));
+        assert_eq!(
+            validator.validate(&other_key, &value),
+            Err(RecordRejection::KeyMismatch)
+        );
+    }
+
+    fn signed_descriptor(private_key: [u8; 32]) -> FileDescriptor {
+        let owner = ippan_crypto::KeyPair::from_private_key(&private_key)
+            .unwrap()
+            .public_key();
+        let mut descriptor = FileDescriptor::new(
+            ContentHash::from_data(b"signed"),
+            owner,
+            1024,
+            None,
+            vec!["test".into()],
+        );
+        descriptor.sign(&private_key).unwrap();
+        descriptor
+    }
+
+    #[test]
+    fn validator_checks_file_records() {
+        let validator = IpnDhtRecordValidator::default();
+        let descriptor = signed_descriptor([4u8; 32]);
+        let key = IpnDhtService::key_for(&descriptor.id);
+        let value = serde_json::to_vec(&descriptor).unwrap();
+        assert_eq!(validator.validate(&key, &value), Ok(()));
+
+        let unsigned = sample_descriptor();
+        assert_eq!(
+            validator.validate(
+                &IpnDhtService::key_for(&unsigned.id),
+                &serde_json::to_vec(&unsigned).unwrap()
+            ),
+            Err(RecordRejection::BadSignature)
+        );
+
+        let mut forged = descriptor.clone();
+        forged.tags = vec!["forged".into()];
+        assert_eq!(
+            validator.validate(&key, &serde_json::to_vec(&forged).unwrap()),
+            Err(RecordRejection::BadSignature)
+        );
+
+        assert_eq!(
+            validator.validate(&[0u8; 32], &value),
+            Err(RecordRejection::KeyMismatch)
+        );
+        assert!(matches!(
+            validator.validate(&key, b"not json"),
+            Err(RecordRejection::Malformed(_))
+        ));
+
+        let mut empty = descriptor.clone();
+        empty.size_bytes = 0;
+        let value = serde_json::to_vec(&empty).unwrap();
+        assert!(matches!(
+            validator.validate(&key, &value),
+            Err(RecordRejection::Malformed(_))
+        ));
+
+        assert_eq!(
+            validator.validate(b"short", &value),
+            Err(RecordRejection::UnknownKey)
+        );
+    }
+
+    #[test]
+    fn validator_checks_file_anchors() {
+        use ippan_files::FileOwnershipAnchor;
+
+        let file_anchors = Arc::new(L1FileAnchorStorage::new());
+        let validator = IpnDhtRecordValidator::default().with_file_anchors(file_anchors.clone());
+        let descriptor = signed_descriptor([4u8; 32]);
+        let key = IpnDhtService::key_for(&descriptor.id);
+        let value = serde_json::to_vec(&descriptor).unwrap();
+        assert_eq!(validator.validate(&key, &value), Ok(()));
+
+        // The owner anchored a different version of the descriptor on L1.
+        let mut anchored = descriptor.clone();
+        anchored.tags = vec!["final".into()];
+        anchored.sign(&[4u8; 32]).unwrap();
+        file_anchors
+            .store_anchor(FileOwnershipAnchor {
+                file_id: anchored.id,
+                content_hash: anchored.content_hash,
+                owner: anchored.owner,
+                descriptor_digest: anchored.signing_digest(),
+                block_height: 1,
+                round: 1,
+            })
+            .unwrap();
+        assert!(matches!(
+            validator.validate(&key, &value),
+            Err(RecordRejection::Unanchored(_))
+        ));
+        assert_eq!(
+            validator.validate(&key, &serde_json::to_vec(&anchored).unwrap()),
+            Ok(())
+        );
+    }
 }
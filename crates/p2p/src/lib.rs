@@ -5,6 +5,10 @@
 //! - **libp2p_network**: Production-grade, decentralized peer discovery and relay network
 //!   featuring Kademlia DHT, GossipSub, mDNS, Relay, and DCUtR for NAT traversal.
 //! - **parallel_gossip**: Concurrent gossip engine optimized for DAG-based consensus.
+//! - **block_sync**: libp2p request-response catch-up of blocks and finalized rounds.
+//! - **compact_block**: header plus short transaction IDs, rebuilt from the mempool.
+//! - **peer_auth**: signed HTTP message envelopes and optional mutual TLS.
+//! - **peer_scoring**: validity-driven peer scores, graylists and bans shared by both stacks.
 //!
 //! Features:
 //! - Deterministic peer connectivity
@@ -15,25 +19,55 @@
 //!
 //! Used by IPPAN RPC services, gateway nodes, and consensus layers.
 
+pub mod block_sync;
+pub mod chunk_exchange;
+pub mod compact_block;
 pub mod ipndht;
 pub mod libp2p_network;
 pub mod parallel_gossip;
+pub mod peer_auth;
+pub mod peer_scoring;
+pub mod record_store;
 
 pub use libp2p::Multiaddr;
 pub use libp2p_network::{
     Libp2pCommand, Libp2pConfig, Libp2pEvent, Libp2pNetwork, DEFAULT_GOSSIP_TOPICS,
 };
 
-pub use ipndht::{IpnDhtService, Libp2pFileDhtService, Libp2pHandleDhtService};
+pub use block_sync::{
+    BlockSyncError, BlockSyncProgress, BlockSyncRequest, BlockSyncResponse, BlockSyncSource,
+    BlockSyncer, BLOCK_SYNC_PROTOCOL_NAME,
+};
+pub use chunk_exchange::{
+    ChunkDownloader, ChunkRequest, ChunkResponse, ChunkSource, DownloadError, ExpectedContent,
+    CHUNK_PROTOCOL_NAME,
+};
+pub use compact_block::{
+    CompactBlock, CompactBlockAck, CompactBlockError, CompactBlockOutcome, CompactBlockRelay,
+    IndexedTransaction, PartialBlock, ShortTxId,
+};
+pub use ipndht::{
+    IpnDhtRecordValidator, IpnDhtService, Libp2pFileDhtService, Libp2pHandleDhtService,
+};
 pub use parallel_gossip::{
     DagVertexAnnouncement, GossipConfig, GossipError, GossipMessage, GossipMetricsSnapshot,
     GossipPayload, GossipTopic, ParallelGossipNetwork,
 };
+pub use peer_auth::{
+    MessageSignature, P2PTlsConfig, PeerAuthConfig, PeerAuthError, PeerAuthenticator, PeerIdentity,
+};
+pub use peer_scoring::{
+    peer_key, validate_network_message, GossipValidator, NetworkMessageValidator,
+    PeerScoreSnapshot, PeerScoring, PeerScoringConfig, PeerStanding, RejectReason,
+    ValidationOutcome,
+};
+pub use record_store::{RecordRejection, RecordStoreConfig, RecordValidator, SledRecordStore};
 
 use anyhow::{anyhow, Result};
 use igd::aio::search_gateway;
 use igd::SearchOptions;
-use ippan_types::{ippan_time_now, Block, Transaction};
+use ippan_network::NetworkMetrics;
+use ippan_types::{ippan_time_now, Block, BlockId, Transaction};
 use parking_lot::{Mutex, RwLock};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use reqwest::Client;
@@ -79,6 +113,13 @@ pub enum NetworkMessage {
         hash: [u8; 32],
     },
     BlockResponse(Block),
+    /// Header plus short transaction IDs; see [`compact_block`].
+    CompactBlock(CompactBlock),
+    /// Transactions a peer reported missing while rebuilding a compact block.
+    BlockTransactions {
+        block_id: BlockId,
+        transactions: Vec<IndexedTransaction>,
+    },
     PeerInfo {
         peer_id: String,
         addresses: Vec<String>,
@@ -180,7 +221,12 @@ impl ChaosConfig {
 #[derive(Debug, Clone)]
 pub struct P2PConfig {
     pub listen_address: String,
+    /// Expected local peer ID. The advertised ID is always derived from
+    /// `identity`; a different value here is ignored with a warning.
     pub local_peer_id: Option<String>,
+    /// Node identity key used to sign outbound messages. An ephemeral key is
+    /// generated when unset.
+    pub identity: Option<libp2p::identity::Keypair>,
     pub max_peers: usize,
     pub peer_discovery_interval: Duration,
     pub message_timeout: Duration,
@@ -188,6 +234,11 @@ pub struct P2PConfig {
     pub dht: DhtConfig,
     pub chaos: ChaosConfig,
     pub limits: P2PLimits,
+    pub auth: PeerAuthConfig,
+    /// Mutual TLS between peers; plain HTTP when unset.
+    pub tls: Option<P2PTlsConfig>,
+    /// Peer scores shared with the libp2p stack; in-memory when unset.
+    pub peer_scoring: Option<Arc<PeerScoring>>,
 }
 
 impl Default for P2PConfig {
@@ -195,6 +246,7 @@ impl Default for P2PConfig {
         Self {
             listen_address: "http://0.0.0.0:9000".to_string(),
             local_peer_id: None,
+            identity: None,
             max_peers: 50,
             peer_discovery_interval: Duration::from_secs(30),
             message_timeout: Duration::from_secs(10),
@@ -202,6 +254,9 @@ impl Default for P2PConfig {
             dht: DhtConfig::default(),
             chaos: ChaosConfig::default(),
             limits: P2PLimits::default(),
+            auth: PeerAuthConfig::default(),
+            tls: None,
+            peer_scoring: None,
         }
     }
 }
@@ -272,6 +327,7 @@ impl DhtConfig {
 pub struct HttpP2PNetwork {
     config: P2PConfig,
     client: Client,
+    peer_client: PeerClient,
     peers: Arc<RwLock<HashSet<String>>>,
     peer_metadata: Arc<RwLock<HashMap<String, PeerRecord>>>,
     peer_count: Arc<RwLock<usize>>,
@@ -288,6 +344,10 @@ pub struct HttpP2PNetwork {
     discovery_task: Arc<Mutex<Option<JoinHandle<()>>>>,
     announce_task: Arc<Mutex<Option<JoinHandle<()>>>>,
     chaos: ChaosHandle,
+    metrics: Arc<NetworkMetrics>,
+    compact_relay: Arc<CompactBlockRelay>,
+    authenticator: Arc<PeerAuthenticator>,
+    scoring: Arc<PeerScoring>,
 }
 
 impl HttpP2PNetwork {
@@ -300,6 +360,17 @@ impl HttpP2PNetwork {
         };
 
         let client = Client::builder().timeout(http_timeout).build()?;
+        let identity = config
+            .identity
+            .clone()
+            .map(PeerIdentity::new)
+            .unwrap_or_else(PeerIdentity::generate);
+        let peer_http = match &config.tls {
+            Some(tls) => tls
+                .apply_to_client(Client::builder().timeout(http_timeout))?
+                .build()?,
+            None => client.clone(),
+        };
         let (incoming_sender, incoming_receiver) = mpsc::unbounded_channel();
 
         let announce_address = config
@@ -309,14 +380,26 @@ impl HttpP2PNetwork {
 
         let chaos = ChaosHandle::new(config.chaos.clone(), &listen_address);
 
-        let local_peer_id = config
-            .local_peer_id
-            .clone()
-            .unwrap_or_else(|| derive_peer_id(&listen_address));
+        let local_peer_id = identity.peer_id().to_string();
+        if let Some(configured) = &config.local_peer_id {
+            if configured != &local_peer_id {
+                warn!(
+                    "Configured peer id {} does not match the identity key; using {}",
+                    configured, local_peer_id
+                );
+            }
+        }
+        let authenticator = Arc::new(PeerAuthenticator::new(config.auth.clone()));
+        let metrics = Arc::new(NetworkMetrics::new());
+        let scoring = config.peer_scoring.clone().unwrap_or_default();
 
         Ok(Self {
             config,
             client,
+            peer_client: PeerClient {
+                http: peer_http,
+                identity: Arc::new(identity),
+            },
             peers: Arc::new(RwLock::new(HashSet::new())),
             peer_metadata: Arc::new(RwLock::new(HashMap::new())),
             peer_count: Arc::new(RwLock::new(0)),
@@ -333,6 +416,10 @@ impl HttpP2PNetwork {
             discovery_task: Arc::new(Mutex::new(None)),
             announce_task: Arc::new(Mutex::new(None)),
             chaos,
+            compact_relay: Arc::new(CompactBlockRelay::new(metrics.clone())),
+            metrics,
+            authenticator,
+            scoring,
         })
     }
 
@@ -367,6 +454,127 @@ impl HttpP2PNetwork {
             .collect()
     }
 
+    /// Verifier for signed inbound messages.
+    pub fn authenticator(&self) -> Arc<PeerAuthenticator> {
+        self.authenticator.clone()
+    }
+
+    /// Validity-driven peer scores, graylists and bans.
+    pub fn peer_scoring(&self) -> Arc<PeerScoring> {
+        self.scoring.clone()
+    }
+
+    /// Record the outcome of a message from `peer_id` (a `peer-...` ID or,
+    /// for unsigned traffic, a remote address). Banned peers are dropped from
+    /// the peer set.
+    pub fn report_peer(&self, peer_id: &str, outcome: ValidationOutcome) -> PeerStanding {
+        let standing = self.scoring.report(peer_id, outcome);
+        if let PeerStanding::Banned { .. } = standing {
+            let addresses: Vec<String> = self
+                .peer_metadata
+                .read()
+                .values()
+                .filter(|record| record.peer_id.as_deref() == Some(peer_id))
+                .map(|record| record.address.clone())
+                .collect();
+            for address in addresses {
+                self.remove_peer(&address);
+            }
+        }
+        standing
+    }
+
+    /// Mutual TLS settings the P2P server should listen with, if any.
+    pub fn tls_config(&self) -> Option<&P2PTlsConfig> {
+        self.config.tls.as_ref()
+    }
+
+    /// Traffic and compact block relay counters.
+    pub fn metrics(&self) -> Arc<NetworkMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Receiver-side state for compact blocks served by `/p2p/compact-blocks`.
+    pub fn compact_relay(&self) -> Arc<CompactBlockRelay> {
+        self.compact_relay.clone()
+    }
+
+    /// Relay `block` to every peer as a compact block, sending missing
+    /// transactions on request and the full block when the peer cannot
+    /// rebuild it. Returns the number of peers that received the block.
+    pub async fn broadcast_block(&self, block: &Block) -> usize {
+        let compact = NetworkMessage::CompactBlock(CompactBlock::from_block(block));
+        let full = NetworkMessage::Block(block.clone());
+        let (Ok(compact_bytes), Ok(full_bytes)) =
+            (message_size_bytes(&compact), message_size_bytes(&full))
+        else {
+            return 0;
+        };
+
+        let mut delivered = 0;
+        for peer in self.get_peers() {
+            let result = self
+                .relay_compact_block(&peer, block, &compact, compact_bytes, &full, full_bytes)
+                .await;
+            match result {
+                Ok(()) => delivered += 1,
+                Err(err) => {
+                    self.metrics.record_message_failed();
+                    debug!("Failed to relay block to {}: {}", peer, err);
+                }
+            }
+        }
+        delivered
+    }
+
+    async fn relay_compact_block(
+        &self,
+        peer: &str,
+        block: &Block,
+        compact: &NetworkMessage,
+        compact_bytes: usize,
+        full: &NetworkMessage,
+        full_bytes: usize,
+    ) -> Result<()> {
+        let mut sent_bytes = compact_bytes;
+        let mut ack = post_for_ack(&self.peer_client, peer, compact, &self.chaos)
+            .await
+            .unwrap_or_else(|err| {
+                debug!("Compact block not accepted by {}: {}", peer, err);
+                Some(CompactBlockAck::SendFull)
+            });
+
+        if let Some(CompactBlockAck::Missing { indexes }) = &ack {
+            ack = match compact_block::indexed_transactions(block, indexes) {
+                Some(transactions) => {
+                    let message = NetworkMessage::BlockTransactions {
+                        block_id: block.hash(),
+                        transactions,
+                    };
+                    sent_bytes += message_size_bytes(&message)?;
+                    post_for_ack(&self.peer_client, peer, &message, &self.chaos)
+                        .await
+                        .unwrap_or(Some(CompactBlockAck::SendFull))
+                }
+                None => Some(CompactBlockAck::SendFull),
+            };
+        }
+
+        match ack {
+            Some(CompactBlockAck::Accepted) | None => {
+                self.metrics.record_message_sent(sent_bytes);
+                self.metrics
+                    .record_compact_block_sent(full_bytes, sent_bytes);
+            }
+            Some(CompactBlockAck::Missing { .. }) | Some(CompactBlockAck::SendFull) => {
+                self.metrics.record_compact_block_fallback();
+                post_message_with_chaos(&self.peer_client, peer, full, &self.chaos).await?;
+                self.metrics.record_message_sent(sent_bytes + full_bytes);
+            }
+        }
+        Ok(())
+    }
+
     pub fn take_incoming_events(&self) -> Option<mpsc::UnboundedReceiver<NetworkEvent>> {
         self.incoming_receiver.lock().take()
     }
@@ -534,6 +742,10 @@ impl HttpP2PNetwork {
                 from: from.clone(),
                 block,
             },
+            // Surfaced as `NetworkEvent::Block` once the block is rebuilt.
+            NetworkMessage::CompactBlock(_) | NetworkMessage::BlockTransactions { .. } => {
+                return Ok(());
+            }
         };
 
         self.incoming_sender.send(event)?;
@@ -668,7 +880,7 @@ impl HttpP2PNetwork {
         let peer_count = self.peer_count.clone();
         let incoming = self.incoming_sender.clone();
         let is_running = self.is_running.clone();
-        let client = self.client.clone();
+        let client = self.peer_client.clone();
         let listen_address = self.listen_address.clone();
         let max_peers = self.config.max_peers;
         let interval_duration = self
@@ -741,7 +953,7 @@ impl HttpP2PNetwork {
 
     fn spawn_announce_loop(&mut self) {
         let peers = self.peers.clone();
-        let client = self.client.clone();
+        let client = self.peer_client.clone();
         let is_running = self.is_running.clone();
         let peer_id = self.local_peer_id.clone();
         let announce_address = self.announce_address.clone();
@@ -794,7 +1006,7 @@ impl HttpP2PNetwork {
             addresses: vec![address],
             time_us: Some(ippan_time_now()),
         };
-        post_message_with_chaos(&self.client, peer, &message, &self.chaos).await
+        post_message_with_chaos(&self.peer_client, peer, &message, &self.chaos).await
     }
 
     fn touch_peer(&self, peer: &str) {
@@ -981,9 +1193,9 @@ fn normalize_peer(address: &str) -> Result<String> {
     Ok(normalized)
 }
 
-async fn fetch_peer_list(client: &Client, peer: &str) -> Result<Vec<String>> {
+async fn fetch_peer_list(client: &PeerClient, peer: &str) -> Result<Vec<String>> {
     let url = format!("{peer}/p2p/peers");
-    let response = client.get(url).send().await?;
+    let response = client.http.get(url).send().await?;
     if !response.status().is_success() {
         return Err(anyhow!(
             "peer {} responded with status {}",
@@ -994,18 +1206,46 @@ async fn fetch_peer_list(client: &Client, peer: &str) -> Result<Vec<String>> {
     Ok(response.json().await?)
 }
 
-async fn post_message(client: &Client, peer: &str, message: &NetworkMessage) -> Result<()> {
-    let endpoint = match message {
+fn message_endpoint(message: &NetworkMessage) -> &'static str {
+    match message {
         NetworkMessage::Block(_) => "/p2p/blocks",
         NetworkMessage::Transaction(_) => "/p2p/transactions",
         NetworkMessage::PeerInfo { .. } => "/p2p/peer-info",
         NetworkMessage::PeerDiscovery { .. } => "/p2p/peer-discovery",
         NetworkMessage::BlockRequest { .. } => "/p2p/block-request",
         NetworkMessage::BlockResponse { .. } => "/p2p/block-response",
-    };
+        NetworkMessage::CompactBlock(_) => "/p2p/compact-blocks",
+        NetworkMessage::BlockTransactions { .. } => "/p2p/block-transactions",
+    }
+}
+
+/// HTTP client for peer traffic: signs every message with the node identity
+/// and, when configured, connects over mutual TLS.
+#[derive(Clone)]
+struct PeerClient {
+    http: Client,
+    identity: Arc<PeerIdentity>,
+}
 
-    let url = format!("{peer}{endpoint}");
-    let response = client.post(url).json(message).send().await?;
+impl PeerClient {
+    fn signed_post(&self, peer: &str, message: &NetworkMessage) -> Result<reqwest::RequestBuilder> {
+        let endpoint = message_endpoint(message);
+        let body = serde_json::to_vec(message)?;
+        let signature = self.identity.sign(endpoint, &body, ippan_time_now())?;
+        let mut request = self
+            .http
+            .post(format!("{peer}{endpoint}"))
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in signature.headers() {
+            request = request.header(name, value);
+        }
+        Ok(request.body(body))
+    }
+}
+
+async fn post_message(client: &PeerClient, peer: &str, message: &NetworkMessage) -> Result<()> {
+    let endpoint = message_endpoint(message);
+    let response = client.signed_post(peer, message)?.send().await?;
     if !response.status().is_success() {
         return Err(anyhow!(
             "peer {} returned error status {} for {}",
@@ -1018,7 +1258,7 @@ async fn post_message(client: &Client, peer: &str, message: &NetworkMessage) ->
 }
 
 async fn post_message_with_chaos(
-    client: &Client,
+    client: &PeerClient,
     peer: &str,
     message: &NetworkMessage,
     chaos: &ChaosHandle,
@@ -1035,6 +1275,36 @@ async fn post_message_with_chaos(
     post_message(client, peer, message).await
 }
 
+/// Post a compact block message and read the peer's acknowledgement.
+/// Returns `None` when chaos testing dropped the message.
+async fn post_for_ack(
+    client: &PeerClient,
+    peer: &str,
+    message: &NetworkMessage,
+    chaos: &ChaosHandle,
+) -> Result<Option<CompactBlockAck>> {
+    if chaos.should_drop_outbound() {
+        debug!(target: "p2p::chaos", peer = %peer, "Dropping outbound message due to chaos configuration");
+        return Ok(None);
+    }
+
+    if let Some(delay) = chaos.latency_delay() {
+        sleep(delay).await;
+    }
+
+    let endpoint = message_endpoint(message);
+    let response = client.signed_post(peer, message)?.send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "peer {} returned error status {} for {}",
+            peer,
+            response.status(),
+            endpoint
+        ));
+    }
+    Ok(Some(response.json().await?))
+}
+
 #[derive(Clone)]
 struct ChaosHandle {
     config: ChaosConfig,
@@ -7,26 +7,31 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use blake3;
 use futures::StreamExt;
+use ippan_files::ContentStore;
 use ippan_network::load_identity_with_fallback;
+use ippan_storage::Storage;
 use libp2p::core::transport::OrTransport;
 use libp2p::core::upgrade;
 use libp2p::dcutr;
 use libp2p::gossipsub;
 use libp2p::identify;
 use libp2p::identity;
-use libp2p::kad::{self, Quorum, Record, RecordKey};
+use libp2p::kad::{self, store::RecordStore, Quorum, Record, RecordKey};
 use libp2p::multiaddr::Protocol;
 use libp2p::noise;
 use libp2p::ping;
 use libp2p::relay;
+use libp2p::request_response::{self, OutboundRequestId, ProtocolSupport};
 use libp2p::swarm::behaviour::toggle::Toggle;
 use libp2p::swarm::{NetworkBehaviour, Swarm, SwarmEvent};
 use libp2p::tcp;
@@ -37,6 +42,20 @@ use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tracing::{debug, info, trace, warn};
 
+use crate::block_sync::{
+    serve_block_sync_request, BlockSyncCodec, BlockSyncProtocol, BlockSyncRequest,
+    BlockSyncResponse, BlockSyncSource,
+};
+use crate::chunk_exchange::{
+    serve_chunk_request, ChunkCodec, ChunkProtocol, ChunkRequest, ChunkResponse, ChunkSource,
+};
+use crate::ipndht::IpnDhtRecordValidator;
+use crate::peer_scoring::{
+    peer_key, GossipValidator, NetworkMessageValidator, PeerScoring, PeerStanding,
+    ValidationOutcome,
+};
+use crate::record_store::{RecordStoreConfig, RecordValidator, SledRecordStore};
+
 /// Default gossip topics propagated across the libp2p fabric.
 pub const DEFAULT_GOSSIP_TOPICS: &[&str] =
     &["ippan/blocks", "ippan/transactions", "ippan/peer-info"];
@@ -45,6 +64,10 @@ const GOSSIP_MAX_MESSAGE_BYTES: usize = 512 * 1024;
 const GOSSIP_PER_PEER_LIMIT: u64 = 2_048;
 const GOSSIP_GLOBAL_LIMIT: u64 = 8_192;
 const GOSSIP_WINDOW: Duration = Duration::from_secs(60);
+const CHUNK_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const BLOCK_SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DHT_RECORD_TTL: Duration = Duration::from_secs(48 * 60 * 60);
+const DHT_REPUBLISH_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
 
 /// Configuration for the libp2p network.
 #[derive(Debug, Clone)]
@@ -73,6 +96,15 @@ pub struct Libp2pConfig {
     pub bootstrap_retry_interval: Duration,
     /// Maximum bootstrap retry attempts (0 = infinite).
     pub bootstrap_max_retries: usize,
+    /// Directory of the persistent DHT record store. If `None`, records are
+    /// kept in a temporary database and lost on restart.
+    pub record_store_path: Option<PathBuf>,
+    /// Lifetime of DHT records stored on behalf of other peers.
+    pub record_ttl: Duration,
+    /// Interval at which locally published DHT records are republished.
+    pub record_republish_interval: Duration,
+    /// Peer scores shared with the HTTP stack; in-memory when unset.
+    pub peer_scoring: Option<Arc<PeerScoring>>,
 }
 
 impl Default for Libp2pConfig {
@@ -95,6 +127,10 @@ impl Default for Libp2pConfig {
             agent_version: format!("ippan-p2p/{}", env!("CARGO_PKG_VERSION")),
             bootstrap_retry_interval: Duration::from_secs(30),
             bootstrap_max_retries: 0, // infinite retries by default
+            record_store_path: None,
+            record_ttl: DHT_RECORD_TTL,
+            record_republish_interval: DHT_REPUBLISH_INTERVAL,
+            peer_scoring: None,
         }
     }
 }
@@ -131,6 +167,21 @@ pub enum Libp2pCommand {
         key: Vec<u8>,
         respond_to: oneshot::Sender<Vec<PeerId>>,
     },
+    RequestChunks {
+        peer: PeerId,
+        request: ChunkRequest,
+        respond_to: oneshot::Sender<Result<ChunkResponse, String>>,
+    },
+    RequestBlockSync {
+        peer: PeerId,
+        request: BlockSyncRequest,
+        respond_to: oneshot::Sender<Result<BlockSyncResponse, String>>,
+    },
+    /// Push the current unified score of `peer` into gossipsub, disconnecting
+    /// it if banned.
+    RefreshPeerScore {
+        peer: PeerId,
+    },
     Shutdown,
 }
 
@@ -156,6 +207,20 @@ impl fmt::Debug for Libp2pCommand {
             Libp2pCommand::GetRecord { .. } => f.write_str("GetRecord"),
             Libp2pCommand::StartProviding { .. } => f.write_str("StartProviding"),
             Libp2pCommand::GetProviders { .. } => f.write_str("GetProviders"),
+            Libp2pCommand::RequestChunks { peer, request, .. } => f
+                .debug_struct("RequestChunks")
+                .field("peer", peer)
+                .field("request", request)
+                .finish_non_exhaustive(),
+            Libp2pCommand::RequestBlockSync { peer, request, .. } => f
+                .debug_struct("RequestBlockSync")
+                .field("peer", peer)
+                .field("request", request)
+                .finish_non_exhaustive(),
+            Libp2pCommand::RefreshPeerScore { peer } => f
+                .debug_struct("RefreshPeerScore")
+                .field("peer", peer)
+                .finish(),
             Libp2pCommand::Shutdown => f.write_str("Shutdown"),
         }
     }
@@ -204,6 +269,8 @@ pub enum Libp2pEvent {
 /// - **Relay + DCUtR**: NAT traversal for connectivity behind firewalls.
 /// - **Identify**: Automatic peer information exchange.
 /// - **Ping**: Connection health monitoring.
+/// - **Chunk exchange**: Request-response transfer of IPNDHT file manifests and chunks.
+/// - **Block sync**: Request-response catch-up of blocks, certificates and finalization records.
 ///
 /// See `docs/ipndht/ipndht_hardening_plan.md` for future DHT enhancements.
 #[derive(NetworkBehaviour)]
@@ -212,11 +279,13 @@ struct ComposedBehaviour {
     gossipsub: gossipsub::Behaviour,
     identify: identify::Behaviour,
     ping: ping::Behaviour,
-    /// Kademlia DHT for peer routing and future IPNDHT features (handle lookup, content addressing).
-    kademlia: kad::Behaviour<kad::store::MemoryStore>,
+    /// Kademlia DHT for peer routing and IPNDHT handle and file records.
+    kademlia: kad::Behaviour<SledRecordStore>,
     mdns: Toggle<mdns::tokio::Behaviour>,
     relay: Toggle<relay::client::Behaviour>,
     dcutr: Toggle<dcutr::Behaviour>,
+    chunks: request_response::Behaviour<ChunkCodec>,
+    block_sync: request_response::Behaviour<BlockSyncCodec>,
 }
 
 impl ComposedBehaviour {
@@ -234,15 +303,20 @@ impl ComposedBehaviour {
         let gossip_config = gossipsub::ConfigBuilder::default()
             .message_id_fn(message_id_fn)
             .validation_mode(gossipsub::ValidationMode::Strict)
+            // Messages are forwarded only after the gossip validator accepts them.
+            .validate_messages()
             .heartbeat_interval(Duration::from_secs(4))
             .build()
             .map_err(|e| anyhow!("failed to build gossipsub config: {e}"))?;
 
-        let gossipsub = gossipsub::Behaviour::new(
+        let mut gossipsub = gossipsub::Behaviour::new(
             gossipsub::MessageAuthenticity::Signed(local_key.clone()),
             gossip_config,
         )
         .map_err(|e| anyhow!("failed to construct gossipsub: {e}"))?;
+        gossipsub
+            .with_peer_score(gossip_peer_score_params(), gossip_peer_score_thresholds())
+            .map_err(|e| anyhow!("failed to enable gossipsub peer scoring: {e}"))?;
 
         let identify = identify::Behaviour::new(
             identify::Config::new(config.protocol_version.clone(), local_key.public())
@@ -251,9 +325,16 @@ impl ComposedBehaviour {
 
         let ping = ping::Behaviour::default();
 
-        let store = kad::store::MemoryStore::new(peer_id);
+        let store = match &config.record_store_path {
+            Some(path) => SledRecordStore::open(peer_id, path, RecordStoreConfig::default())?,
+            None => SledRecordStore::temporary(peer_id, RecordStoreConfig::default())?,
+        };
         let mut kad_cfg = kad::Config::default();
         kad_cfg.set_query_timeout(Duration::from_secs(5));
+        kad_cfg.set_record_ttl(Some(config.record_ttl));
+        kad_cfg.set_publication_interval(Some(config.record_republish_interval));
+        // Inbound records are validated before they reach the store.
+        kad_cfg.set_record_filtering(kad::StoreInserts::FilterBoth);
         let kademlia = kad::Behaviour::with_config(peer_id, store, kad_cfg);
 
         let mdns_behaviour = Toggle::from(if config.enable_mdns {
@@ -272,6 +353,15 @@ impl ComposedBehaviour {
             None
         });
 
+        let chunks = request_response::Behaviour::new(
+            std::iter::once((ChunkProtocol, ProtocolSupport::Full)),
+            request_response::Config::default().with_request_timeout(CHUNK_REQUEST_TIMEOUT),
+        );
+        let block_sync = request_response::Behaviour::new(
+            std::iter::once((BlockSyncProtocol, ProtocolSupport::Full)),
+            request_response::Config::default().with_request_timeout(BLOCK_SYNC_REQUEST_TIMEOUT),
+        );
+
         Ok(Self {
             gossipsub,
             identify,
@@ -280,10 +370,83 @@ impl ComposedBehaviour {
             mdns: mdns_behaviour,
             relay: relay_behaviour,
             dcutr: dcutr_behaviour,
+            chunks,
+            block_sync,
         })
     }
 }
 
+/// Gossipsub peer-score parameters. The unified [`PeerScoring`] score is fed
+/// in as the application-specific component (P5), one point per ten points of
+/// unified score, so graylisted peers fall below the gossip threshold and
+/// banned peers below the publish threshold.
+fn gossip_peer_score_params() -> gossipsub::PeerScoreParams {
+    gossipsub::PeerScoreParams {
+        app_specific_weight: 1.0,
+        // Local test networks and co-located nodes share loopback addresses.
+        ip_colocation_factor_whitelist: HashSet::from([
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn gossip_peer_score_thresholds() -> gossipsub::PeerScoreThresholds {
+    gossipsub::PeerScoreThresholds::default()
+}
+
+/// Per-topic score parameters. Traffic follows block production, so quiet
+/// meshes are not penalised; invalid messages are.
+fn gossip_topic_score_params() -> gossipsub::TopicScoreParams {
+    gossipsub::TopicScoreParams {
+        topic_weight: 1.0,
+        mesh_message_deliveries_weight: 0.0,
+        mesh_failure_penalty_weight: 0.0,
+        invalid_message_deliveries_weight: -10.0,
+        ..Default::default()
+    }
+}
+
+fn message_acceptance(outcome: ValidationOutcome) -> gossipsub::MessageAcceptance {
+    match outcome {
+        ValidationOutcome::Accept => gossipsub::MessageAcceptance::Accept,
+        ValidationOutcome::Ignore => gossipsub::MessageAcceptance::Ignore,
+        ValidationOutcome::Reject(_) => gossipsub::MessageAcceptance::Reject,
+    }
+}
+
+/// Subscribe to `topic` and register its score parameters.
+fn subscribe_topic(swarm: &mut Swarm<ComposedBehaviour>, topic: &gossipsub::IdentTopic) {
+    let gossipsub = &mut swarm.behaviour_mut().gossipsub;
+    if let Err(err) = gossipsub.subscribe(topic) {
+        warn!("Failed to subscribe to topic {topic}: {err}");
+    }
+    if let Err(err) = gossipsub.set_topic_params(topic.clone(), gossip_topic_score_params()) {
+        warn!("Failed to set score parameters for topic {topic}: {err}");
+    }
+}
+
+/// Push the unified score of `peer` into gossipsub and disconnect it once
+/// banned.
+fn apply_peer_score(
+    swarm: &mut Swarm<ComposedBehaviour>,
+    scoring: &PeerScoring,
+    peer: &PeerId,
+) -> PeerStanding {
+    let key = peer_key(peer);
+    let standing = scoring.standing(&key);
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .set_application_score(peer, f64::from(scoring.score(&key)) / 10.0);
+    if let PeerStanding::Banned { .. } = standing {
+        debug!("Disconnecting banned peer {}", peer);
+        let _ = swarm.disconnect_peer_id(*peer);
+    }
+    standing
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 enum ComposedEvent {
@@ -294,6 +457,8 @@ enum ComposedEvent {
     Mdns(mdns::Event),
     Relay(relay::client::Event),
     Dcutr(dcutr::Event),
+    Chunks(request_response::Event<ChunkRequest, ChunkResponse>),
+    BlockSync(request_response::Event<BlockSyncRequest, BlockSyncResponse>),
 }
 
 impl From<gossipsub::Event> for ComposedEvent {
@@ -331,6 +496,16 @@ impl From<dcutr::Event> for ComposedEvent {
         Self::Dcutr(v)
     }
 }
+impl From<request_response::Event<ChunkRequest, ChunkResponse>> for ComposedEvent {
+    fn from(v: request_response::Event<ChunkRequest, ChunkResponse>) -> Self {
+        Self::Chunks(v)
+    }
+}
+impl From<request_response::Event<BlockSyncRequest, BlockSyncResponse>> for ComposedEvent {
+    fn from(v: request_response::Event<BlockSyncRequest, BlockSyncResponse>) -> Self {
+        Self::BlockSync(v)
+    }
+}
 
 #[derive(Default)]
 struct DhtQueryBook {
@@ -338,6 +513,50 @@ struct DhtQueryBook {
     provider_queries: Mutex<HashMap<kad::QueryId, oneshot::Sender<Vec<PeerId>>>>,
 }
 
+type ChunkReply = oneshot::Sender<Result<ChunkResponse, String>>;
+
+/// Validator applied to DHT records received from remote peers.
+type RecordValidatorSlot = RwLock<Arc<dyn RecordValidator>>;
+
+/// Validator applied to inbound gossipsub messages.
+type GossipValidatorSlot = RwLock<Arc<dyn GossipValidator>>;
+
+/// State for the chunk exchange protocol: the store used to answer inbound
+/// requests and the callers waiting on outbound ones.
+#[derive(Default)]
+struct ChunkExchange {
+    store: RwLock<Option<Arc<ContentStore>>>,
+    pending: Mutex<HashMap<OutboundRequestId, ChunkReply>>,
+}
+
+impl ChunkExchange {
+    fn complete(&self, id: OutboundRequestId, result: Result<ChunkResponse, String>) {
+        if let Some(sender) = self.pending.lock().remove(&id) {
+            let _ = sender.send(result);
+        }
+    }
+}
+
+type BlockSyncReply = oneshot::Sender<Result<BlockSyncResponse, String>>;
+
+/// State for the block sync protocol: the storage used to answer inbound
+/// requests, the callers waiting on outbound ones, and the peers that are
+/// currently connected (the candidates for catch-up).
+#[derive(Default)]
+struct BlockSyncExchange {
+    storage: RwLock<Option<Arc<dyn Storage + Send + Sync>>>,
+    pending: Mutex<HashMap<OutboundRequestId, BlockSyncReply>>,
+    connected: RwLock<HashSet<PeerId>>,
+}
+
+impl BlockSyncExchange {
+    fn complete(&self, id: OutboundRequestId, result: Result<BlockSyncResponse, String>) {
+        if let Some(sender) = self.pending.lock().remove(&id) {
+            let _ = sender.send(result);
+        }
+    }
+}
+
 impl DhtQueryBook {
     fn insert_record_query(&self, id: kad::QueryId, sender: oneshot::Sender<Option<Vec<u8>>>) {
         self.record_queries.lock().insert(id, sender);
@@ -446,6 +665,11 @@ pub struct Libp2pNetwork {
     command_tx: mpsc::UnboundedSender<Libp2pCommand>,
     events_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<Libp2pEvent>>>>,
     listen_addresses: Arc<RwLock<HashSet<Multiaddr>>>,
+    chunk_exchange: Arc<ChunkExchange>,
+    block_sync: Arc<BlockSyncExchange>,
+    record_validator: Arc<RecordValidatorSlot>,
+    gossip_validator: Arc<GossipValidatorSlot>,
+    scoring: Arc<PeerScoring>,
     _task: JoinHandle<()>,
 }
 
@@ -497,6 +721,13 @@ impl Libp2pNetwork {
         let events_rx = Arc::new(Mutex::new(Some(events_rx)));
         let listen_addresses = Arc::new(RwLock::new(HashSet::<Multiaddr>::new()));
         let dht_queries = Arc::new(DhtQueryBook::default());
+        let chunk_exchange = Arc::new(ChunkExchange::default());
+        let block_sync = Arc::new(BlockSyncExchange::default());
+        let record_validator: Arc<RecordValidatorSlot> =
+            Arc::new(RwLock::new(Arc::new(IpnDhtRecordValidator::default())));
+        let gossip_validator: Arc<GossipValidatorSlot> =
+            Arc::new(RwLock::new(Arc::new(NetworkMessageValidator)));
+        let scoring = config.peer_scoring.clone().unwrap_or_default();
 
         let mut topic_map: HashMap<String, gossipsub::IdentTopic> = HashMap::new();
         let mut combined = HashSet::new();
@@ -508,9 +739,7 @@ impl Libp2pNetwork {
         }
         for topic_name in combined {
             let topic = gossipsub::IdentTopic::new(&topic_name);
-            if let Err(err) = swarm.behaviour_mut().gossipsub.subscribe(&topic) {
-                warn!("Failed to subscribe to topic {topic_name}: {err}");
-            }
+            subscribe_topic(&mut swarm, &topic);
             topic_map.insert(topic_name, topic);
         }
 
@@ -555,6 +784,11 @@ impl Libp2pNetwork {
         let events_tx_task = event_tx.clone();
         let dht_queries_for_events = dht_queries.clone();
         let dht_queries_for_commands = dht_queries;
+        let chunk_exchange_task = chunk_exchange.clone();
+        let block_sync_task = block_sync.clone();
+        let record_validator_task = record_validator.clone();
+        let gossip_validator_task = gossip_validator.clone();
+        let scoring_task = scoring.clone();
         let task = tokio::spawn(async move {
             let mut bootstrap_ticker = tokio::time::interval(bootstrap_retry_interval);
             bootstrap_ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
@@ -595,6 +829,11 @@ impl Libp2pNetwork {
                             &listen_task,
                             &relay_peer_ids,
                             &dht_queries_for_events,
+                            &chunk_exchange_task,
+                            &block_sync_task,
+                            &record_validator_task,
+                            &gossip_validator_task,
+                            &scoring_task,
                             &mut gossip_guards,
                         );
                     }
@@ -606,7 +845,7 @@ impl Libp2pNetwork {
                             }
                             Some(other) => {
                                 if let Err(e) =
-                                    handle_command(other, &mut swarm, &mut topic_map, &dht_queries_for_commands)
+                                    handle_command(other, &mut swarm, &mut topic_map, &dht_queries_for_commands, &chunk_exchange_task, &block_sync_task, &scoring_task)
                                 {
                                     warn!("Failed to handle libp2p command: {e}");
                                 }
@@ -624,6 +863,11 @@ impl Libp2pNetwork {
             command_tx,
             events_rx,
             listen_addresses,
+            chunk_exchange,
+            block_sync,
+            record_validator,
+            gossip_validator,
+            scoring,
             _task: task,
         })
     }
@@ -703,17 +947,114 @@ impl Libp2pNetwork {
             .map_err(|_| anyhow!("libp2p DHT providers query channel dropped"))
     }
 
+    /// Serve chunk exchange requests from `store`. Until this is called,
+    /// inbound manifest and chunk requests are answered with "not held".
+    pub fn serve_content(&self, store: Arc<ContentStore>) {
+        *self.chunk_exchange.store.write() = Some(store);
+    }
+
+    /// Send a chunk exchange request to `peer` and await the response.
+    pub async fn request_chunks(
+        &self,
+        peer: PeerId,
+        request: ChunkRequest,
+    ) -> Result<ChunkResponse> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(Libp2pCommand::RequestChunks {
+                peer,
+                request,
+                respond_to: tx,
+            })
+            .map_err(|_| anyhow!("libp2p command channel closed"))?;
+        rx.await
+            .map_err(|_| anyhow!("libp2p chunk request channel dropped"))?
+            .map_err(|err| anyhow!("chunk request to {peer} failed: {err}"))
+    }
+
+    /// Serve block sync requests from `storage`. Until this is called,
+    /// inbound requests are answered with "not held".
+    pub fn serve_block_sync(&self, storage: Arc<dyn Storage + Send + Sync>) {
+        *self.block_sync.storage.write() = Some(storage);
+    }
+
+    /// Send a block sync request to `peer` and await the response.
+    pub async fn request_block_sync(
+        &self,
+        peer: PeerId,
+        request: BlockSyncRequest,
+    ) -> Result<BlockSyncResponse> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send(Libp2pCommand::RequestBlockSync {
+                peer,
+                request,
+                respond_to: tx,
+            })
+            .map_err(|_| anyhow!("libp2p command channel closed"))?;
+        rx.await
+            .map_err(|_| anyhow!("libp2p block sync request channel dropped"))?
+            .map_err(|err| anyhow!("block sync request to {peer} failed: {err}"))
+    }
+
+    /// Peers with at least one open connection.
+    pub fn connected_peers(&self) -> Vec<PeerId> {
+        self.block_sync.connected.read().iter().copied().collect()
+    }
+
+    /// Replace the validator applied to DHT records received from peers.
+    pub fn set_record_validator(&self, validator: Arc<dyn RecordValidator>) {
+        *self.record_validator.write() = validator;
+    }
+
+    /// Replace the validator applied to inbound gossipsub messages.
+    pub fn set_gossip_validator(&self, validator: Arc<dyn GossipValidator>) {
+        *self.gossip_validator.write() = validator;
+    }
+
+    /// Validity-driven peer scores, graylists and bans.
+    pub fn peer_scoring(&self) -> Arc<PeerScoring> {
+        self.scoring.clone()
+    }
+
+    /// Record the outcome of a message from `peer` that was validated outside
+    /// the swarm (e.g. by consensus) and apply the new score to gossipsub.
+    pub fn report_peer(&self, peer: PeerId, outcome: ValidationOutcome) -> Result<PeerStanding> {
+        let standing = self.scoring.report(&peer_key(&peer), outcome);
+        self.command_tx
+            .send(Libp2pCommand::RefreshPeerScore { peer })
+            .map_err(|_| anyhow!("libp2p command channel closed"))?;
+        Ok(standing)
+    }
+
     pub fn shutdown(&self) {
         let _ = self.command_tx.send(Libp2pCommand::Shutdown);
     }
 }
 
+#[async_trait]
+impl ChunkSource for Libp2pNetwork {
+    async fn request(&self, peer: &str, request: ChunkRequest) -> Result<ChunkResponse> {
+        let peer = PeerId::from_str(peer).map_err(|e| anyhow!("invalid peer id {peer}: {e}"))?;
+        self.request_chunks(peer, request).await
+    }
+}
+
+#[async_trait]
+impl BlockSyncSource for Libp2pNetwork {
+    async fn request(&self, peer: &str, request: BlockSyncRequest) -> Result<BlockSyncResponse> {
+        let peer = PeerId::from_str(peer).map_err(|e| anyhow!("invalid peer id {peer}: {e}"))?;
+        self.request_block_sync(peer, request).await
+    }
+}
+
 impl Drop for Libp2pNetwork {
     fn drop(&mut self) {
         let _ = self.command_tx.send(Libp2pCommand::Shutdown);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_swarm_event(
     event: SwarmEvent<ComposedEvent>,
     swarm: &mut Swarm<ComposedBehaviour>,
@@ -721,14 +1062,19 @@ fn handle_swarm_event(
     listen_addresses: &Arc<RwLock<HashSet<Multiaddr>>>,
     relay_peers: &HashSet<PeerId>,
     dht_queries: &Arc<DhtQueryBook>,
+    chunk_exchange: &Arc<ChunkExchange>,
+    block_sync: &Arc<BlockSyncExchange>,
+    record_validator: &Arc<RecordValidatorSlot>,
+    gossip_validator: &Arc<GossipValidatorSlot>,
+    scoring: &PeerScoring,
     gossip_guards: &mut GossipIngressGuards,
 ) {
     match event {
         SwarmEvent::Behaviour(ComposedEvent::Gossipsub(event)) => {
             if let gossipsub::Event::Message {
                 propagation_source,
+                message_id,
                 message,
-                ..
             } = *event
             {
                 if let Err(reason) = gossip_guards.check(&propagation_source, message.data.len()) {
@@ -739,10 +1085,54 @@ fn handle_swarm_event(
                         ?reason,
                         "Dropping inbound gossipsub message",
                     );
+                    swarm
+                        .behaviour_mut()
+                        .gossipsub
+                        .report_message_validation_result(
+                            &message_id,
+                            &propagation_source,
+                            gossipsub::MessageAcceptance::Ignore,
+                        );
                     let _ = swarm.disconnect_peer_id(propagation_source);
                     return;
                 }
 
+                // Graylisted peers are still validated so that continued abuse
+                // ends in a ban, but their valid messages are not delivered.
+                let outcome = match scoring.standing(&peer_key(&propagation_source)) {
+                    PeerStanding::Banned { .. } => ValidationOutcome::Ignore,
+                    standing => match gossip_validator
+                        .read()
+                        .validate(message.topic.as_str(), &message.data)
+                    {
+                        ValidationOutcome::Accept if standing == PeerStanding::Graylisted => {
+                            ValidationOutcome::Ignore
+                        }
+                        outcome => outcome,
+                    },
+                };
+                swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .report_message_validation_result(
+                        &message_id,
+                        &propagation_source,
+                        message_acceptance(outcome),
+                    );
+                scoring.report(&peer_key(&propagation_source), outcome);
+                if outcome != ValidationOutcome::Accept {
+                    if let ValidationOutcome::Reject(reason) = outcome {
+                        warn!(
+                            peer = %propagation_source,
+                            topic = %message.topic,
+                            ?reason,
+                            "Rejected invalid gossipsub message",
+                        );
+                    }
+                    apply_peer_score(swarm, scoring, &propagation_source);
+                    return;
+                }
+
                 let _ = event_tx.send(Libp2pEvent::Gossip {
                     peer: propagation_source,
                     topic: message.topic.to_string(),
@@ -758,8 +1148,19 @@ fn handle_swarm_event(
         }
         SwarmEvent::Behaviour(ComposedEvent::Kademlia(event)) => match event {
             kad::Event::OutboundQueryProgressed { id, result, .. } => match result {
-                kad::QueryResult::GetRecord(Ok(kad::GetRecordOk::FoundRecord(record))) => {
-                    dht_queries.complete_record_query(id, Some(record.record.value.clone()));
+                kad::QueryResult::GetRecord(Ok(kad::GetRecordOk::FoundRecord(found))) => {
+                    let record = &found.record;
+                    match record_validator
+                        .read()
+                        .validate(record.key.as_ref(), &record.value)
+                    {
+                        Ok(()) => dht_queries.complete_record_query(id, Some(record.value.clone())),
+                        Err(reason) => warn!(
+                            peer = ?found.peer,
+                            %reason,
+                            "Ignoring invalid DHT record from lookup",
+                        ),
+                    }
                 }
                 kad::QueryResult::GetRecord(Ok(
                     kad::GetRecordOk::FinishedWithNoAdditionalRecord { .. },
@@ -783,6 +1184,40 @@ fn handle_swarm_event(
                 }
                 _ => {}
             },
+            kad::Event::InboundRequest {
+                request:
+                    kad::InboundRequest::PutRecord {
+                        source,
+                        record: Some(record),
+                        ..
+                    },
+            } => {
+                if let Err(reason) = record_validator
+                    .read()
+                    .validate(record.key.as_ref(), &record.value)
+                {
+                    warn!(peer = %source, %reason, "Rejected inbound DHT record");
+                    return;
+                }
+                if let Err(err) = swarm.behaviour_mut().kademlia.store_mut().put(record) {
+                    debug!(peer = %source, "DHT record not stored: {err}");
+                }
+            }
+            kad::Event::InboundRequest {
+                request:
+                    kad::InboundRequest::AddProvider {
+                        record: Some(record),
+                    },
+            } => {
+                if let Err(err) = swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .store_mut()
+                    .add_provider(record)
+                {
+                    debug!("DHT provider record not stored: {err}");
+                }
+            }
             _ => {
                 trace!("Received Kademlia event: {:?}", event);
             }
@@ -838,13 +1273,123 @@ fn handle_swarm_event(
                 });
             }
         },
+        SwarmEvent::Behaviour(ComposedEvent::Chunks(event)) => match event {
+            request_response::Event::Message {
+                peer,
+                message:
+                    request_response::Message::Request {
+                        request, channel, ..
+                    },
+                ..
+            } => {
+                let response = match chunk_exchange.store.read().as_ref() {
+                    Some(store) => serve_chunk_request(store, &request),
+                    None => match request {
+                        ChunkRequest::Manifest { .. } => ChunkResponse::Manifest { manifest: None },
+                        ChunkRequest::Chunk { .. } => ChunkResponse::Chunk { data: None },
+                    },
+                };
+                if swarm
+                    .behaviour_mut()
+                    .chunks
+                    .send_response(channel, response)
+                    .is_err()
+                {
+                    debug!("Failed to send chunk response to {}", peer);
+                }
+            }
+            request_response::Event::Message {
+                message:
+                    request_response::Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            } => {
+                chunk_exchange.complete(request_id, Ok(response));
+            }
+            request_response::Event::OutboundFailure {
+                request_id, error, ..
+            } => {
+                chunk_exchange.complete(request_id, Err(error.to_string()));
+            }
+            other => {
+                trace!("Received chunk exchange event: {:?}", other);
+            }
+        },
+        SwarmEvent::Behaviour(ComposedEvent::BlockSync(event)) => match event {
+            request_response::Event::Message {
+                peer,
+                message:
+                    request_response::Message::Request {
+                        request, channel, ..
+                    },
+                ..
+            } => {
+                let response = match block_sync.storage.read().as_ref() {
+                    Some(storage) => serve_block_sync_request(storage.as_ref(), &request),
+                    None => match request {
+                        BlockSyncRequest::Status => BlockSyncResponse::Status {
+                            latest_finalized_round: None,
+                        },
+                        BlockSyncRequest::Block { .. } => BlockSyncResponse::Block { block: None },
+                        BlockSyncRequest::Rounds { .. } => {
+                            BlockSyncResponse::Rounds { blocks: Vec::new() }
+                        }
+                        BlockSyncRequest::Certificate { .. } => {
+                            BlockSyncResponse::Certificate { certificate: None }
+                        }
+                        BlockSyncRequest::Finalization { .. } => {
+                            BlockSyncResponse::Finalization { record: None }
+                        }
+                    },
+                };
+                if swarm
+                    .behaviour_mut()
+                    .block_sync
+                    .send_response(channel, response)
+                    .is_err()
+                {
+                    debug!("Failed to send block sync response to {}", peer);
+                }
+            }
+            request_response::Event::Message {
+                message:
+                    request_response::Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            } => {
+                block_sync.complete(request_id, Ok(response));
+            }
+            request_response::Event::OutboundFailure {
+                request_id, error, ..
+            } => {
+                block_sync.complete(request_id, Err(error.to_string()));
+            }
+            other => {
+                trace!("Received block sync event: {:?}", other);
+            }
+        },
         SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+            if let PeerStanding::Banned { .. } = apply_peer_score(swarm, scoring, &peer_id) {
+                return;
+            }
             if relay_peers.contains(&peer_id) {
                 debug!("Connected to relay {}", peer_id);
             }
+            block_sync.connected.write().insert(peer_id);
             let _ = event_tx.send(Libp2pEvent::PeerConnected { peer: peer_id });
         }
-        SwarmEvent::ConnectionClosed { peer_id, .. } => {
+        SwarmEvent::ConnectionClosed {
+            peer_id,
+            num_established,
+            ..
+        } => {
+            if num_established == 0 {
+                block_sync.connected.write().remove(&peer_id);
+            }
             let _ = event_tx.send(Libp2pEvent::PeerDisconnected { peer: peer_id });
         }
         SwarmEvent::NewListenAddr { address, .. } => {
@@ -860,12 +1405,15 @@ fn handle_command(
     swarm: &mut Swarm<ComposedBehaviour>,
     topic_map: &mut HashMap<String, gossipsub::IdentTopic>,
     dht_queries: &Arc<DhtQueryBook>,
+    chunk_exchange: &Arc<ChunkExchange>,
+    block_sync: &Arc<BlockSyncExchange>,
+    scoring: &PeerScoring,
 ) -> Result<()> {
     match command {
         Libp2pCommand::Publish { topic, data } => {
             let entry = topic_map.entry(topic.clone()).or_insert_with(|| {
                 let topic_id = gossipsub::IdentTopic::new(&topic);
-                let _ = swarm.behaviour_mut().gossipsub.subscribe(&topic_id);
+                subscribe_topic(swarm, &topic_id);
                 topic_id
             });
             let _ = swarm.behaviour_mut().gossipsub.publish(entry.clone(), data);
@@ -931,6 +1479,28 @@ fn handle_command(
                 .get_providers(RecordKey::new(&key));
             dht_queries.insert_provider_query(query_id, respond_to);
         }
+        Libp2pCommand::RequestChunks {
+            peer,
+            request,
+            respond_to,
+        } => {
+            let request_id = swarm.behaviour_mut().chunks.send_request(&peer, request);
+            chunk_exchange.pending.lock().insert(request_id, respond_to);
+        }
+        Libp2pCommand::RequestBlockSync {
+            peer,
+            request,
+            respond_to,
+        } => {
+            let request_id = swarm
+                .behaviour_mut()
+                .block_sync
+                .send_request(&peer, request);
+            block_sync.pending.lock().insert(request_id, respond_to);
+        }
+        Libp2pCommand::RefreshPeerScore { peer } => {
+            apply_peer_score(swarm, scoring, &peer);
+        }
         Libp2pCommand::Shutdown => {}
     }
     Ok(())
@@ -970,6 +1540,10 @@ mod tests {
             agent_version: "ippan-test/0.0.1".to_string(),
             bootstrap_retry_interval: Duration::from_secs(30),
             bootstrap_max_retries: 0,
+            record_store_path: None,
+            record_ttl: Duration::from_secs(60 * 60),
+            record_republish_interval: Duration::from_secs(30 * 60),
+            peer_scoring: None,
         };
 
         let network = Libp2pNetwork::new(config).expect("expected network to initialise");
@@ -1007,4 +1581,145 @@ mod tests {
         assert!(guards.check(&peer_b, 64).is_ok());
         assert_eq!(guards.check(&peer_b, 64), Err(GossipGuardError::GlobalRate));
     }
+
+    fn local_test_config() -> Libp2pConfig {
+        Libp2pConfig {
+            listen_addresses: vec![Multiaddr::from_str("/ip4/127.0.0.1/tcp/0").unwrap()],
+            gossip_topics: Vec::new(),
+            enable_mdns: false,
+            enable_relay: false,
+            identity_keypair: Some(identity::Keypair::generate_ed25519()),
+            ..Libp2pConfig::default()
+        }
+    }
+
+    async fn listening_address(network: &Libp2pNetwork) -> Multiaddr {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(addr) = network.listen_addresses().into_iter().find(|addr| {
+                    !addr
+                        .iter()
+                        .any(|protocol| matches!(protocol, Protocol::Tcp(0)))
+                }) {
+                    break addr;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("peer listening")
+    }
+
+    #[tokio::test]
+    async fn block_sync_catches_up_between_swarms() {
+        use crate::block_sync::BlockSyncer;
+        use ippan_network::ReputationManager;
+        use ippan_storage::MemoryStorage;
+        use ippan_types::{
+            Block, IppanTimeMicros, RoundCertificate, RoundFinalizationRecord, RoundWindow,
+        };
+
+        let serving = Libp2pNetwork::new(local_test_config()).unwrap();
+        let syncing = Arc::new(Libp2pNetwork::new(local_test_config()).unwrap());
+
+        let source: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let mut parents = Vec::new();
+        for round in 1..=3u64 {
+            let block = Block::new(parents.clone(), vec![], round, [9u8; 32]);
+            parents = vec![block.hash()];
+            source.store_block(block).unwrap();
+            source
+                .store_round_finalization(RoundFinalizationRecord {
+                    round,
+                    window: RoundWindow {
+                        id: round,
+                        start_us: IppanTimeMicros(round),
+                        end_us: IppanTimeMicros(round + 1),
+                    },
+                    ordered_tx_ids: Vec::new(),
+                    fork_drops: Vec::new(),
+                    state_root: [0u8; 32],
+                    proof: RoundCertificate::new(round, parents.clone()),
+                    total_fees_atomic: None,
+                    treasury_fees_atomic: None,
+                    applied_payments: None,
+                    rejected_payments: None,
+                })
+                .unwrap();
+        }
+        serving.serve_block_sync(source);
+
+        let address = listening_address(&serving).await;
+        syncing
+            .add_explicit_peer(serving.peer_id(), Some(address))
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !syncing.connected_peers().contains(&serving.peer_id()) {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("peers connected");
+
+        let local: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        let syncer = BlockSyncer::new(
+            syncing.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+        let progress = tokio::time::timeout(
+            Duration::from_secs(10),
+            syncer.catch_up(&serving.peer_id().to_string()),
+        )
+        .await
+        .expect("catch-up finished in time")
+        .expect("catch-up succeeded");
+
+        assert_eq!((progress.rounds, progress.blocks), (3, 3));
+        assert!(local.get_block(&parents[0]).unwrap().is_some());
+        assert!(local.get_latest_round_finalization().unwrap().is_none());
+        serving.shutdown();
+        syncing.shutdown();
+    }
+
+    #[tokio::test]
+    async fn chunk_exchange_downloads_between_swarms() {
+        use crate::chunk_exchange::{ChunkDownloader, ExpectedContent};
+        use ippan_network::ReputationManager;
+
+        let provider = Libp2pNetwork::new(local_test_config()).unwrap();
+        let fetcher = Arc::new(Libp2pNetwork::new(local_test_config()).unwrap());
+
+        let seeded = Arc::new(ContentStore::in_memory().with_chunk_size(32));
+        let data: Vec<u8> = (0..200u8).collect();
+        let manifest = seeded.put(&data).unwrap();
+        provider.serve_content(seeded);
+
+        let address = listening_address(&provider).await;
+        fetcher
+            .add_explicit_peer(provider.peer_id(), Some(address))
+            .unwrap();
+
+        let local = Arc::new(ContentStore::in_memory());
+        let downloader = ChunkDownloader::new(
+            fetcher.clone(),
+            local.clone(),
+            Arc::new(ReputationManager::default()),
+        );
+        let fetched = tokio::time::timeout(
+            Duration::from_secs(10),
+            downloader.download(
+                &ExpectedContent::of(&manifest),
+                &[provider.peer_id().to_string()],
+            ),
+        )
+        .await
+        .expect("download finished in time")
+        .expect("download succeeded");
+
+        assert_eq!(fetched, manifest);
+        assert_eq!(local.read(&manifest.root).unwrap(), data);
+        provider.shutdown();
+        fetcher.shutdown();
+    }
 }
@@ -9,6 +9,8 @@ use tokio::time::timeout;
 
 use ippan_types::{Block, BlockId, IppanTimeMicros, Transaction};
 
+use crate::compact_block::CompactBlock;
+
 const ALL_TOPICS: [GossipTopic; 3] = [
     GossipTopic::Transactions,
     GossipTopic::Blocks,
@@ -46,6 +48,9 @@ pub enum GossipTopic {
 pub enum GossipPayload {
     Transaction(Transaction),
     Block(Block),
+    /// Block header plus short transaction IDs, rebuilt by subscribers from
+    /// their mempool; see [`crate::compact_block`].
+    CompactBlock(CompactBlock),
     DagMetadata(DagVertexAnnouncement),
 }
 
@@ -221,6 +226,15 @@ impl ParallelGossipNetwork {
         ))
     }
 
+    /// Publish `block` in compact form on the blocks topic. Subscribers that
+    /// cannot rebuild it should fall back to requesting the full block.
+    pub fn broadcast_compact_block(&self, block: &Block) -> Result<usize, GossipError> {
+        self.publish(GossipMessage::new(
+            GossipTopic::Blocks,
+            GossipPayload::CompactBlock(CompactBlock::from_block(block)),
+        ))
+    }
+
     pub fn broadcast_transaction(&self, tx: Transaction) -> Result<usize, GossipError> {
         self.publish(GossipMessage::new(
             GossipTopic::Transactions,
@@ -302,6 +316,25 @@ mod tests {
         assert!(metrics.published >= 2);
     }
 
+    #[tokio::test]
+    async fn compact_block_rebuilds_from_known_transactions() {
+        let network = ParallelGossipNetwork::new(GossipConfig::default());
+        let mut rng = StdRng::seed_from_u64(654);
+        let block = build_block(&mut rng, 7, Vec::new());
+        let mut subscriber = network.subscribe(GossipTopic::Blocks).unwrap();
+
+        network.broadcast_compact_block(&block).unwrap();
+
+        let received = subscriber.recv().await.unwrap();
+        match received.payload {
+            GossipPayload::CompactBlock(compact) => {
+                let partial = compact.reconstruct(block.transactions.clone());
+                assert_eq!(partial.finish().unwrap().hash(), block.hash());
+            }
+            other => panic!("Expected CompactBlock payload, got: {other:?}"),
+        }
+    }
+
     #[tokio::test]
     async fn dag_metadata_round_trip() {
         let network = ParallelGossipNetwork::new(GossipConfig::default());
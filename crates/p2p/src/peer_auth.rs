@@ -0,0 +1,386 @@
+//! Authenticated HTTP P2P transport.
+//!
+//! Every message posted by [`HttpP2PNetwork`](crate::HttpP2PNetwork) carries a
+//! [`MessageSignature`] in its HTTP headers: the sender's public key, a
+//! timestamp and a signature over the endpoint, timestamp and body bytes,
+//! made with the node identity key (see `ippan_network::identity_store`).
+//! The peer ID is derived from the public key, so a peer cannot claim an ID
+//! it does not hold the key for. [`PeerAuthenticator`] checks signatures,
+//! clock skew and replays on the receiving side.
+//!
+//! [`P2PTlsConfig`] optionally adds mutual TLS so the traffic is also
+//! encrypted and only peers holding a certificate from the configured CA can
+//! connect.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use libp2p::identity::{Keypair, PublicKey};
+use parking_lot::Mutex;
+use thiserror::Error;
+
+pub const PEER_ID_HEADER: &str = "x-ippan-peer-id";
+pub const PUBLIC_KEY_HEADER: &str = "x-ippan-public-key";
+pub const TIMESTAMP_HEADER: &str = "x-ippan-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-ippan-signature";
+
+const SIGNING_DOMAIN: &[u8] = b"ippan-p2p-http-v1";
+
+/// Peer ID string for `public`, in the `peer-<libp2p peer id>` form used by the node.
+pub fn peer_id_from_public_key(public: &PublicKey) -> String {
+    format!("peer-{}", public.to_peer_id())
+}
+
+fn signing_payload(endpoint: &str, timestamp_us: u64, body: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(SIGNING_DOMAIN.len() + endpoint.len() + 41);
+    payload.extend_from_slice(SIGNING_DOMAIN);
+    payload.push(0);
+    payload.extend_from_slice(endpoint.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(&timestamp_us.to_le_bytes());
+    payload.extend_from_slice(blake3::hash(body).as_bytes());
+    payload
+}
+
+/// Why an inbound P2P message was refused.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum PeerAuthError {
+    #[error("message is not signed")]
+    MissingSignature,
+    #[error("malformed {0} header")]
+    MalformedHeader(&'static str),
+    #[error("invalid public key")]
+    InvalidPublicKey,
+    #[error("peer id {claimed} does not match its public key ({derived})")]
+    PeerIdMismatch { claimed: String, derived: String },
+    #[error("message timestamp is outside the allowed clock skew")]
+    ClockSkew,
+    #[error("invalid message signature")]
+    InvalidSignature,
+    #[error("message was already received")]
+    Replayed,
+}
+
+/// Signature envelope carried in the HTTP headers of a P2P message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageSignature {
+    pub peer_id: String,
+    /// Protobuf-encoded libp2p public key.
+    pub public_key: Vec<u8>,
+    pub timestamp_us: u64,
+    pub signature: Vec<u8>,
+}
+
+impl MessageSignature {
+    /// Header name/value pairs to attach to the request.
+    pub fn headers(&self) -> [(&'static str, String); 4] {
+        [
+            (PEER_ID_HEADER, self.peer_id.clone()),
+            (PUBLIC_KEY_HEADER, hex::encode(&self.public_key)),
+            (TIMESTAMP_HEADER, self.timestamp_us.to_string()),
+            (SIGNATURE_HEADER, hex::encode(&self.signature)),
+        ]
+    }
+
+    /// Parse the envelope from request headers. Returns `Ok(None)` when the
+    /// request carries no signature headers at all.
+    pub fn from_headers<'a, F>(header: F) -> Result<Option<Self>, PeerAuthError>
+    where
+        F: Fn(&str) -> Option<&'a str>,
+    {
+        let names = [
+            PEER_ID_HEADER,
+            PUBLIC_KEY_HEADER,
+            TIMESTAMP_HEADER,
+            SIGNATURE_HEADER,
+        ];
+        if names.iter().all(|name| header(name).is_none()) {
+            return Ok(None);
+        }
+
+        let required =
+            |name: &'static str| header(name).ok_or(PeerAuthError::MalformedHeader(name));
+        let decode = |name: &'static str| {
+            hex::decode(required(name)?).map_err(|_| PeerAuthError::MalformedHeader(name))
+        };
+        Ok(Some(Self {
+            peer_id: required(PEER_ID_HEADER)?.to_string(),
+            public_key: decode(PUBLIC_KEY_HEADER)?,
+            timestamp_us: required(TIMESTAMP_HEADER)?
+                .parse()
+                .map_err(|_| PeerAuthError::MalformedHeader(TIMESTAMP_HEADER))?,
+            signature: decode(SIGNATURE_HEADER)?,
+        }))
+    }
+
+    /// Check the signature over `endpoint` and `body` and that the peer ID
+    /// belongs to the public key. Returns the verified peer ID.
+    pub fn verify(&self, endpoint: &str, body: &[u8]) -> Result<String, PeerAuthError> {
+        let public = PublicKey::try_decode_protobuf(&self.public_key)
+            .map_err(|_| PeerAuthError::InvalidPublicKey)?;
+        let derived = peer_id_from_public_key(&public);
+        if derived != self.peer_id {
+            return Err(PeerAuthError::PeerIdMismatch {
+                claimed: self.peer_id.clone(),
+                derived,
+            });
+        }
+        let payload = signing_payload(endpoint, self.timestamp_us, body);
+        if !public.verify(&payload, &self.signature) {
+            return Err(PeerAuthError::InvalidSignature);
+        }
+        Ok(derived)
+    }
+}
+
+/// Node identity used to sign outbound P2P messages.
+#[derive(Clone)]
+pub struct PeerIdentity {
+    keypair: Keypair,
+    peer_id: String,
+    public_key: Vec<u8>,
+}
+
+impl std::fmt::Debug for PeerIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PeerIdentity")
+            .field("peer_id", &self.peer_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PeerIdentity {
+    pub fn new(keypair: Keypair) -> Self {
+        let public = keypair.public();
+        Self {
+            peer_id: peer_id_from_public_key(&public),
+            public_key: public.encode_protobuf(),
+            keypair,
+        }
+    }
+
+    /// Fresh ed25519 identity, for nodes started without a persisted key.
+    pub fn generate() -> Self {
+        Self::new(Keypair::generate_ed25519())
+    }
+
+    pub fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    /// Sign a message body posted to `endpoint` at `timestamp_us`.
+    pub fn sign(&self, endpoint: &str, body: &[u8], timestamp_us: u64) -> Result<MessageSignature> {
+        let payload = signing_payload(endpoint, timestamp_us, body);
+        let signature = self.keypair.sign(&payload).context("sign P2P message")?;
+        Ok(MessageSignature {
+            peer_id: self.peer_id.clone(),
+            public_key: self.public_key.clone(),
+            timestamp_us,
+            signature,
+        })
+    }
+}
+
+/// Receiver-side authentication policy.
+#[derive(Debug, Clone)]
+pub struct PeerAuthConfig {
+    /// Reject messages without a signature envelope.
+    pub require_signatures: bool,
+    /// Maximum difference between the message timestamp and local time.
+    pub max_clock_skew: Duration,
+}
+
+impl Default for PeerAuthConfig {
+    fn default() -> Self {
+        Self {
+            require_signatures: true,
+            max_clock_skew: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Verifies inbound message envelopes and rejects replays within the
+/// clock-skew window.
+#[derive(Debug)]
+pub struct PeerAuthenticator {
+    config: PeerAuthConfig,
+    /// Signature digest -> message timestamp, pruned as messages age out.
+    seen: Mutex<HashMap<[u8; 32], u64>>,
+}
+
+impl PeerAuthenticator {
+    pub fn new(config: PeerAuthConfig) -> Self {
+        Self {
+            config,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PeerAuthConfig {
+        &self.config
+    }
+
+    /// Authenticate a message posted to `endpoint`. Returns the verified peer
+    /// ID, or `None` for an unsigned message when signatures are optional.
+    pub fn verify(
+        &self,
+        signature: Option<&MessageSignature>,
+        endpoint: &str,
+        body: &[u8],
+        now_us: u64,
+    ) -> Result<Option<String>, PeerAuthError> {
+        let Some(signature) = signature else {
+            return if self.config.require_signatures {
+                Err(PeerAuthError::MissingSignature)
+            } else {
+                Ok(None)
+            };
+        };
+
+        let skew = self.config.max_clock_skew.as_micros() as u64;
+        if signature.timestamp_us.abs_diff(now_us) > skew {
+            return Err(PeerAuthError::ClockSkew);
+        }
+        let peer_id = signature.verify(endpoint, body)?;
+
+        let digest = *blake3::hash(&signature.signature).as_bytes();
+        let mut seen = self.seen.lock();
+        seen.retain(|_, timestamp| timestamp.abs_diff(now_us) <= skew);
+        if seen.insert(digest, signature.timestamp_us).is_some() {
+            return Err(PeerAuthError::Replayed);
+        }
+        Ok(Some(peer_id))
+    }
+}
+
+/// PEM files for mutual TLS between HTTP P2P peers.
+#[derive(Debug, Clone)]
+pub struct P2PTlsConfig {
+    /// Certificate chain presented to peers.
+    pub cert_path: PathBuf,
+    /// PKCS#8 private key for `cert_path`.
+    pub key_path: PathBuf,
+    /// CA bundle that peer certificates must chain to.
+    pub ca_path: PathBuf,
+}
+
+impl P2PTlsConfig {
+    pub fn read_cert(&self) -> Result<Vec<u8>> {
+        std::fs::read(&self.cert_path)
+            .with_context(|| format!("read P2P TLS certificate {}", self.cert_path.display()))
+    }
+
+    pub fn read_key(&self) -> Result<Vec<u8>> {
+        std::fs::read(&self.key_path)
+            .with_context(|| format!("read P2P TLS key {}", self.key_path.display()))
+    }
+
+    pub fn read_ca(&self) -> Result<Vec<u8>> {
+        std::fs::read(&self.ca_path)
+            .with_context(|| format!("read P2P TLS CA bundle {}", self.ca_path.display()))
+    }
+
+    /// Configure `builder` to present the node certificate and trust only
+    /// the configured CA.
+    pub fn apply_to_client(
+        &self,
+        builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder> {
+        let mut identity_pem = self.read_cert()?;
+        identity_pem.push(b'\n');
+        identity_pem.extend_from_slice(&self.read_key()?);
+        let identity =
+            reqwest::Identity::from_pem(&identity_pem).context("parse P2P TLS identity")?;
+
+        let mut builder = builder
+            .use_rustls_tls()
+            .tls_built_in_root_certs(false)
+            .identity(identity);
+        for ca in reqwest::Certificate::from_pem_bundle(&self.read_ca()?)
+            .context("parse P2P TLS CA bundle")?
+        {
+            builder = builder.add_root_certificate(ca);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000_000_000;
+
+    fn headers(signature: &MessageSignature) -> HashMap<&'static str, String> {
+        signature.headers().into_iter().collect()
+    }
+
+    #[test]
+    fn signed_message_round_trips_through_headers() {
+        let identity = PeerIdentity::generate();
+        let signature = identity.sign("/p2p/blocks", b"{}", NOW).unwrap();
+        let headers = headers(&signature);
+
+        let parsed = MessageSignature::from_headers(|name| headers.get(name).map(String::as_str))
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed, signature);
+
+        let authenticator = PeerAuthenticator::new(PeerAuthConfig::default());
+        let peer = authenticator
+            .verify(Some(&parsed), "/p2p/blocks", b"{}", NOW + 1_000)
+            .unwrap();
+        assert_eq!(peer.as_deref(), Some(identity.peer_id()));
+        assert_eq!(
+            authenticator.verify(Some(&parsed), "/p2p/blocks", b"{}", NOW + 2_000),
+            Err(PeerAuthError::Replayed)
+        );
+    }
+
+    #[test]
+    fn tampered_or_impersonated_messages_are_rejected() {
+        let identity = PeerIdentity::generate();
+        let authenticator = PeerAuthenticator::new(PeerAuthConfig::default());
+        let signature = identity.sign("/p2p/transactions", b"tx", NOW).unwrap();
+
+        assert_eq!(
+            authenticator.verify(Some(&signature), "/p2p/transactions", b"other", NOW),
+            Err(PeerAuthError::InvalidSignature)
+        );
+        assert_eq!(
+            authenticator.verify(Some(&signature), "/p2p/blocks", b"tx", NOW),
+            Err(PeerAuthError::InvalidSignature)
+        );
+
+        let mut impersonated = signature.clone();
+        impersonated.peer_id = PeerIdentity::generate().peer_id().to_string();
+        assert!(matches!(
+            authenticator.verify(Some(&impersonated), "/p2p/transactions", b"tx", NOW),
+            Err(PeerAuthError::PeerIdMismatch { .. })
+        ));
+
+        let stale = NOW + Duration::from_secs(60).as_micros() as u64;
+        assert_eq!(
+            authenticator.verify(Some(&signature), "/p2p/transactions", b"tx", stale),
+            Err(PeerAuthError::ClockSkew)
+        );
+    }
+
+    #[test]
+    fn unsigned_messages_follow_policy() {
+        let strict = PeerAuthenticator::new(PeerAuthConfig::default());
+        assert_eq!(
+            strict.verify(None, "/p2p/blocks", b"{}", NOW),
+            Err(PeerAuthError::MissingSignature)
+        );
+
+        let permissive = PeerAuthenticator::new(PeerAuthConfig {
+            require_signatures: false,
+            ..Default::default()
+        });
+        assert_eq!(permissive.verify(None, "/p2p/blocks", b"{}", NOW), Ok(None));
+        assert_eq!(MessageSignature::from_headers(|_| None::<&str>), Ok(None));
+    }
+}
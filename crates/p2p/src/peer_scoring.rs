@@ -0,0 +1,572 @@
+//! Peer scoring shared by the HTTP mesh and the libp2p swarm.
+//!
+//! Both transports report the application-level outcome of every inbound
+//! message (see [`ValidationOutcome`]) to a single [`PeerScoring`] engine.
+//! Accepted messages slowly raise a peer's score; rejected ones (forged
+//! signatures, invalid blocks or transactions, bad HashTimers) lower it by a
+//! reason-specific penalty. Scores drift back towards zero over time.
+//!
+//! Peers below [`PeerScoringConfig::graylist_threshold`] have their valid
+//! messages ignored (invalid ones are still penalised); peers reaching [`PeerScoringConfig::ban_threshold`] are banned for
+//! [`PeerScoringConfig::ban_duration`] and disconnected. Penalised scores and
+//! bans are written to sled so they survive restarts.
+//!
+//! Peers are keyed by their `peer-<libp2p peer id>` string (see
+//! [`peer_key`]), which is the same ID HTTP peers sign their messages with, so
+//! a node misbehaving on either transport is penalised on both.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use ippan_network::ReputationScore;
+use libp2p::PeerId;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::peer_auth::PeerAuthError;
+use crate::NetworkMessage;
+
+const SCORES_TREE: &str = "peer_scores";
+
+/// Scoring key for a libp2p peer; matches the ID HTTP peers sign with.
+pub fn peer_key(peer: &PeerId) -> String {
+    format!("peer-{peer}")
+}
+
+/// Why a message was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// Forged or mismatched signature.
+    InvalidSignature,
+    /// HashTimer not derived from the message contents.
+    InvalidHashTimer,
+    /// Block failing header, merkle or transaction checks.
+    InvalidBlock,
+    /// Transaction failing validity checks.
+    InvalidTransaction,
+    /// Payload that does not decode.
+    Malformed,
+}
+
+impl RejectReason {
+    /// Score deducted for one rejected message.
+    pub fn penalty(self) -> i32 {
+        match self {
+            RejectReason::InvalidSignature => 200,
+            RejectReason::InvalidHashTimer => 100,
+            RejectReason::InvalidBlock => 100,
+            RejectReason::InvalidTransaction => 50,
+            RejectReason::Malformed => 20,
+        }
+    }
+}
+
+/// Application-level verdict on an inbound message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationOutcome {
+    /// Valid; propagate it.
+    Accept,
+    /// Not worth propagating, but not the sender's fault (duplicates, stale data).
+    Ignore,
+    /// Invalid; penalise the sender.
+    Reject(RejectReason),
+}
+
+impl From<&PeerAuthError> for ValidationOutcome {
+    fn from(err: &PeerAuthError) -> Self {
+        match err {
+            PeerAuthError::InvalidPublicKey
+            | PeerAuthError::PeerIdMismatch { .. }
+            | PeerAuthError::InvalidSignature => {
+                ValidationOutcome::Reject(RejectReason::InvalidSignature)
+            }
+            PeerAuthError::MalformedHeader(_) => ValidationOutcome::Reject(RejectReason::Malformed),
+            // Unsigned, late or retried messages are refused without penalty.
+            PeerAuthError::MissingSignature
+            | PeerAuthError::ClockSkew
+            | PeerAuthError::Replayed => ValidationOutcome::Ignore,
+        }
+    }
+}
+
+/// Validate a block or transaction carried by a P2P message.
+pub fn validate_network_message(message: &NetworkMessage) -> ValidationOutcome {
+    match message {
+        NetworkMessage::Block(block) | NetworkMessage::BlockResponse(block) => {
+            if !block.verify_hashtimer() {
+                ValidationOutcome::Reject(RejectReason::InvalidHashTimer)
+            } else if !block.is_valid() {
+                ValidationOutcome::Reject(RejectReason::InvalidBlock)
+            } else {
+                ValidationOutcome::Accept
+            }
+        }
+        NetworkMessage::Transaction(tx) => validate_transaction(tx),
+        NetworkMessage::BlockTransactions { transactions, .. } => transactions
+            .iter()
+            .map(|indexed| validate_transaction(&indexed.transaction))
+            .find(|outcome| *outcome != ValidationOutcome::Accept)
+            .unwrap_or(ValidationOutcome::Accept),
+        NetworkMessage::BlockRequest { .. }
+        | NetworkMessage::CompactBlock(_)
+        | NetworkMessage::PeerInfo { .. }
+        | NetworkMessage::PeerDiscovery { .. } => ValidationOutcome::Accept,
+    }
+}
+
+fn validate_transaction(tx: &ippan_types::Transaction) -> ValidationOutcome {
+    if !tx.verify() {
+        ValidationOutcome::Reject(RejectReason::InvalidSignature)
+    } else if !tx.verify_hashtimer() {
+        ValidationOutcome::Reject(RejectReason::InvalidHashTimer)
+    } else if !tx.is_valid() {
+        ValidationOutcome::Reject(RejectReason::InvalidTransaction)
+    } else {
+        ValidationOutcome::Accept
+    }
+}
+
+/// Decides whether a gossipsub message may be delivered and forwarded.
+pub trait GossipValidator: Send + Sync {
+    fn validate(&self, topic: &str, data: &[u8]) -> ValidationOutcome;
+}
+
+/// Default gossip validator: block and transaction topics must carry a valid
+/// JSON [`NetworkMessage`]; other topics are accepted as-is.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NetworkMessageValidator;
+
+impl GossipValidator for NetworkMessageValidator {
+    fn validate(&self, topic: &str, data: &[u8]) -> ValidationOutcome {
+        match topic {
+            "ippan/blocks" | "ippan/transactions" => {
+                match serde_json::from_slice::<NetworkMessage>(data) {
+                    Ok(message) => validate_network_message(&message),
+                    Err(_) => ValidationOutcome::Reject(RejectReason::Malformed),
+                }
+            }
+            _ => ValidationOutcome::Accept,
+        }
+    }
+}
+
+/// Thresholds and timing for [`PeerScoring`].
+#[derive(Debug, Clone)]
+pub struct PeerScoringConfig {
+    /// Messages from peers at or below this score are ignored.
+    pub graylist_threshold: i32,
+    /// Peers at or below this score are banned.
+    pub ban_threshold: i32,
+    /// How long a ban lasts.
+    pub ban_duration: Duration,
+    /// Points per hour by which scores drift back towards zero.
+    pub decay_per_hour: i32,
+}
+
+impl Default for PeerScoringConfig {
+    fn default() -> Self {
+        Self {
+            graylist_threshold: -200,
+            ban_threshold: ReputationScore::THRESHOLD_BAN,
+            ban_duration: Duration::from_secs(24 * 60 * 60),
+            decay_per_hour: 50,
+        }
+    }
+}
+
+/// Where a peer stands after its latest report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerStanding {
+    Good,
+    Graylisted,
+    /// Banned until the given Unix time in milliseconds.
+    Banned {
+        until_ms: u64,
+    },
+}
+
+impl PeerStanding {
+    /// Whether messages from the peer should be processed.
+    pub fn accepts_messages(&self) -> bool {
+        matches!(self, PeerStanding::Good)
+    }
+}
+
+/// Score summary for one peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerScoreSnapshot {
+    pub peer: String,
+    pub score: i32,
+    pub standing: PeerStanding,
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PeerScoreRecord {
+    score: i32,
+    accepted: u64,
+    rejected: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    banned_until_ms: Option<u64>,
+    /// Last time decay was applied, in milliseconds since the Unix epoch.
+    decayed_at_ms: u64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Unified peer scoring engine.
+#[derive(Debug)]
+pub struct PeerScoring {
+    config: PeerScoringConfig,
+    peers: RwLock<HashMap<String, PeerScoreRecord>>,
+    store: Option<sled::Tree>,
+}
+
+impl Default for PeerScoring {
+    fn default() -> Self {
+        Self::new(PeerScoringConfig::default())
+    }
+}
+
+impl PeerScoring {
+    /// In-memory scoring; nothing survives a restart.
+    pub fn new(config: PeerScoringConfig) -> Self {
+        Self {
+            config,
+            peers: RwLock::new(HashMap::new()),
+            store: None,
+        }
+    }
+
+    /// Open (or create) persistent scoring at `path`, restoring saved
+    /// scores and bans.
+    pub fn open(path: impl AsRef<Path>, config: PeerScoringConfig) -> Result<Self> {
+        let path = path.as_ref();
+        let db = sled::open(path)
+            .with_context(|| format!("failed to open peer score store at {}", path.display()))?;
+        let store = db
+            .open_tree(SCORES_TREE)
+            .context("failed to open peer score tree")?;
+
+        let mut peers = HashMap::new();
+        for (key, value) in store.iter().flatten() {
+            match (
+                String::from_utf8(key.to_vec()),
+                serde_json::from_slice::<PeerScoreRecord>(&value),
+            ) {
+                (Ok(peer), Ok(record)) => {
+                    peers.insert(peer, record);
+                }
+                _ => warn!("Skipping unreadable peer score entry"),
+            }
+        }
+        if !peers.is_empty() {
+            info!("Restored scores for {} peers", peers.len());
+        }
+
+        Ok(Self {
+            config,
+            peers: RwLock::new(peers),
+            store: Some(store),
+        })
+    }
+
+    pub fn config(&self) -> &PeerScoringConfig {
+        &self.config
+    }
+
+    /// Record the outcome of a message from `peer` and return its new standing.
+    pub fn report(&self, peer: &str, outcome: ValidationOutcome) -> PeerStanding {
+        self.report_at(peer, outcome, now_ms())
+    }
+
+    fn report_at(&self, peer: &str, outcome: ValidationOutcome, now: u64) -> PeerStanding {
+        let mut peers = self.peers.write();
+        let record = peers
+            .entry(peer.to_string())
+            .or_insert_with(|| PeerScoreRecord {
+                decayed_at_ms: now,
+                ..PeerScoreRecord::default()
+            });
+        self.refresh(record, now);
+
+        match outcome {
+            ValidationOutcome::Accept => {
+                record.accepted += 1;
+                record.score = (record.score + 1).min(ReputationScore::MAX);
+            }
+            ValidationOutcome::Ignore => {}
+            ValidationOutcome::Reject(reason) => {
+                record.rejected += 1;
+                record.score = (record.score - reason.penalty()).max(ReputationScore::MIN);
+                if record.banned_until_ms.is_none() && record.score <= self.config.ban_threshold {
+                    record.banned_until_ms =
+                        Some(now + self.config.ban_duration.as_millis() as u64);
+                    warn!(
+                        "Banning peer {} (score {}) after {:?}",
+                        peer, record.score, reason
+                    );
+                } else if record.score <= self.config.graylist_threshold {
+                    warn!(
+                        "Peer {} graylisted (score {}) after {:?}",
+                        peer, record.score, reason
+                    );
+                }
+                self.persist(peer, record);
+            }
+        }
+
+        self.standing_of(record)
+    }
+
+    /// Current standing of `peer`.
+    pub fn standing(&self, peer: &str) -> PeerStanding {
+        self.standing_at(peer, now_ms())
+    }
+
+    fn standing_at(&self, peer: &str, now: u64) -> PeerStanding {
+        let mut peers = self.peers.write();
+        match peers.get_mut(peer) {
+            Some(record) => {
+                self.refresh(record, now);
+                self.standing_of(record)
+            }
+            None => PeerStanding::Good,
+        }
+    }
+
+    /// Whether `peer` is currently banned.
+    pub fn is_banned(&self, peer: &str) -> bool {
+        matches!(self.standing(peer), PeerStanding::Banned { .. })
+    }
+
+    /// Ban `peer` for the configured duration regardless of its score.
+    pub fn ban(&self, peer: &str) {
+        let now = now_ms();
+        let mut peers = self.peers.write();
+        let record = peers
+            .entry(peer.to_string())
+            .or_insert_with(|| PeerScoreRecord {
+                decayed_at_ms: now,
+                ..PeerScoreRecord::default()
+            });
+        record.score = record.score.min(self.config.ban_threshold);
+        record.banned_until_ms = Some(now + self.config.ban_duration.as_millis() as u64);
+        warn!("Peer {} banned", peer);
+        self.persist(peer, record);
+    }
+
+    /// Lift a ban and reset the score of `peer`.
+    pub fn unban(&self, peer: &str) {
+        let mut peers = self.peers.write();
+        if let Some(record) = peers.get_mut(peer) {
+            record.score = ReputationScore::INITIAL;
+            record.banned_until_ms = None;
+            self.persist(peer, record);
+        }
+    }
+
+    /// Current score of `peer`; unknown peers score zero.
+    pub fn score(&self, peer: &str) -> i32 {
+        let now = now_ms();
+        let mut peers = self.peers.write();
+        peers
+            .get_mut(peer)
+            .map(|record| {
+                self.refresh(record, now);
+                record.score
+            })
+            .unwrap_or(ReputationScore::INITIAL)
+    }
+
+    /// Peers with an active ban.
+    pub fn banned_peers(&self) -> Vec<String> {
+        let now = now_ms();
+        self.peers
+            .read()
+            .iter()
+            .filter(|(_, record)| record.banned_until_ms.is_some_and(|until| until > now))
+            .map(|(peer, _)| peer.clone())
+            .collect()
+    }
+
+    /// Score summaries for every tracked peer.
+    pub fn snapshot(&self) -> Vec<PeerScoreSnapshot> {
+        let now = now_ms();
+        let mut peers = self.peers.write();
+        let mut snapshot: Vec<PeerScoreSnapshot> = peers
+            .iter_mut()
+            .map(|(peer, record)| {
+                self.refresh(record, now);
+                PeerScoreSnapshot {
+                    peer: peer.clone(),
+                    score: record.score,
+                    standing: self.standing_of(record),
+                    accepted: record.accepted,
+                    rejected: record.rejected,
+                }
+            })
+            .collect();
+        snapshot.sort_by_key(|entry| entry.score);
+        snapshot
+    }
+
+    /// Expire bans and decay the score towards zero.
+    fn refresh(&self, record: &mut PeerScoreRecord, now: u64) {
+        if record.banned_until_ms.is_some_and(|until| until <= now) {
+            record.banned_until_ms = None;
+            record.score = ReputationScore::INITIAL;
+            record.decayed_at_ms = now;
+            return;
+        }
+
+        let elapsed = now.saturating_sub(record.decayed_at_ms);
+        let decay = elapsed.saturating_mul(self.config.decay_per_hour.max(0) as u64) / 3_600_000;
+        if decay == 0 {
+            return;
+        }
+        let decay = decay.min(i32::MAX as u64) as i32;
+        record.score = if record.score > 0 {
+            (record.score - decay).max(0)
+        } else {
+            (record.score + decay).min(0)
+        };
+        record.decayed_at_ms = now;
+    }
+
+    fn standing_of(&self, record: &PeerScoreRecord) -> PeerStanding {
+        if let Some(until_ms) = record.banned_until_ms {
+            PeerStanding::Banned { until_ms }
+        } else if record.score <= self.config.graylist_threshold {
+            PeerStanding::Graylisted
+        } else {
+            PeerStanding::Good
+        }
+    }
+
+    fn persist(&self, peer: &str, record: &PeerScoreRecord) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let result = serde_json::to_vec(record)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(store.insert(peer.as_bytes(), bytes)?));
+        if let Err(err) = result {
+            warn!("Failed to persist score for peer {}: {}", peer, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ippan_types::{Amount, Transaction};
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    #[test]
+    fn test_rejections_graylist_then_ban() {
+        let scoring = PeerScoring::default();
+        let now = now_ms();
+        let invalid = ValidationOutcome::Reject(RejectReason::InvalidBlock);
+
+        assert_eq!(
+            scoring.report_at("peer-a", invalid, now),
+            PeerStanding::Good
+        );
+        assert_eq!(
+            scoring.report_at("peer-a", invalid, now),
+            PeerStanding::Graylisted
+        );
+        for _ in 0..3 {
+            scoring.report_at("peer-a", invalid, now);
+        }
+        let until_ms = now + scoring.config().ban_duration.as_millis() as u64;
+        assert_eq!(
+            scoring.standing_at("peer-a", now),
+            PeerStanding::Banned { until_ms }
+        );
+        assert_eq!(scoring.standing_at("peer-b", now), PeerStanding::Good);
+
+        // The ban lapses and the peer starts over.
+        assert_eq!(scoring.standing_at("peer-a", until_ms), PeerStanding::Good);
+        assert_eq!(scoring.score("peer-a"), 0);
+    }
+
+    #[test]
+    fn test_scores_decay_towards_zero() {
+        let scoring = PeerScoring::default();
+        let now = now_ms();
+        scoring.report_at(
+            "peer-a",
+            ValidationOutcome::Reject(RejectReason::InvalidSignature),
+            now,
+        );
+        assert_eq!(scoring.standing_at("peer-a", now), PeerStanding::Graylisted);
+        assert_eq!(
+            scoring.standing_at("peer-a", now + HOUR_MS),
+            PeerStanding::Good
+        );
+        scoring.standing_at("peer-a", now + 4 * HOUR_MS);
+        assert_eq!(scoring.peers.read()["peer-a"].score, 0);
+    }
+
+    #[test]
+    fn test_bans_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let scoring = PeerScoring::open(dir.path(), PeerScoringConfig::default()).unwrap();
+            scoring.ban("peer-forger");
+            scoring.report(
+                "peer-sloppy",
+                ValidationOutcome::Reject(RejectReason::Malformed),
+            );
+        }
+
+        let scoring = PeerScoring::open(dir.path(), PeerScoringConfig::default()).unwrap();
+        assert!(scoring.is_banned("peer-forger"));
+        assert_eq!(scoring.banned_peers(), vec!["peer-forger".to_string()]);
+        assert_eq!(scoring.score("peer-sloppy"), -20);
+    }
+
+    #[test]
+    fn test_network_message_validation() {
+        let key = libp2p::identity::ed25519::Keypair::generate();
+        let secret: [u8; 32] = key.secret().as_ref().try_into().unwrap();
+        let tx = |nonce: u64| {
+            let mut tx = Transaction::new(key.public().to_bytes(), [9u8; 32], Amount(10), nonce);
+            tx.sign(&secret).unwrap();
+            tx
+        };
+
+        let valid = NetworkMessage::Transaction(tx(1));
+        assert_eq!(validate_network_message(&valid), ValidationOutcome::Accept);
+
+        let mut forged = tx(2);
+        forged.signature = [0u8; 64];
+        assert_eq!(
+            validate_network_message(&NetworkMessage::Transaction(forged)),
+            ValidationOutcome::Reject(RejectReason::InvalidSignature)
+        );
+
+        let validator = NetworkMessageValidator;
+        assert_eq!(
+            validator.validate("ippan/transactions", b"not json"),
+            ValidationOutcome::Reject(RejectReason::Malformed)
+        );
+        assert_eq!(
+            validator.validate("ippan/files", b"opaque"),
+            ValidationOutcome::Accept
+        );
+    }
+}
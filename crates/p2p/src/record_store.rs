@@ -0,0 +1,333 @@
+//! Persistent, validated Kademlia record storage for IPNDHT.
+//!
+//! [`SledRecordStore`] keeps value records (handle and file descriptor
+//! records) in sled so they survive restarts; Kademlia's periodic publication
+//! job then republishes them from disk. Record expiry is stored as wall-clock
+//! time and enforced on every read. Provider records are short-lived and are
+//! kept in memory.
+//!
+//! Inbound records are filtered through a [`RecordValidator`] before they are
+//! stored or returned from lookups (see `libp2p_network.rs`).
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use libp2p::kad::store::{self, MemoryStore, MemoryStoreConfig, RecordStore};
+use libp2p::kad::{ProviderRecord, Record, RecordKey};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+const RECORDS_TREE: &str = "ipndht_records";
+
+/// Why an inbound DHT record was refused.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum RecordRejection {
+    #[error("unknown record key namespace")]
+    UnknownKey,
+    #[error("malformed record: {0}")]
+    Malformed(String),
+    #[error("record does not match its key")]
+    KeyMismatch,
+    #[error("record is not backed by L1 state: {0}")]
+    Unanchored(String),
+    #[error("invalid owner signature")]
+    BadSignature,
+}
+
+/// Decides whether a record received from the network may be stored or used.
+pub trait RecordValidator: Send + Sync {
+    fn validate(&self, key: &[u8], value: &[u8]) -> Result<(), RecordRejection>;
+}
+
+/// Limits applied by [`SledRecordStore`].
+#[derive(Debug, Clone)]
+pub struct RecordStoreConfig {
+    /// Maximum number of value records.
+    pub max_records: usize,
+    /// Maximum size of a record value in bytes.
+    pub max_value_bytes: usize,
+}
+
+impl Default for RecordStoreConfig {
+    fn default() -> Self {
+        let memory = MemoryStoreConfig::default();
+        Self {
+            max_records: memory.max_records,
+            max_value_bytes: memory.max_value_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRecord {
+    value: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publisher: Option<Vec<u8>>,
+    /// Expiry as milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at_ms: Option<u64>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// sled-backed Kademlia record store.
+pub struct SledRecordStore {
+    records: sled::Tree,
+    providers: MemoryStore,
+    config: RecordStoreConfig,
+}
+
+impl SledRecordStore {
+    /// Open (or create) a persistent store at `path`.
+    pub fn open(
+        local_id: PeerId,
+        path: impl AsRef<Path>,
+        config: RecordStoreConfig,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let db = sled::open(path)
+            .with_context(|| format!("failed to open DHT record store at {}", path.display()))?;
+        Self::from_db(local_id, &db, config)
+    }
+
+    /// Create a store backed by a temporary sled database.
+    pub fn temporary(local_id: PeerId, config: RecordStoreConfig) -> Result<Self> {
+        let db = sled::Config::new()
+            .temporary(true)
+            .open()
+            .context("failed to open temporary DHT record store")?;
+        Self::from_db(local_id, &db, config)
+    }
+
+    fn from_db(local_id: PeerId, db: &sled::Db, config: RecordStoreConfig) -> Result<Self> {
+        let records = db
+            .open_tree(RECORDS_TREE)
+            .context("failed to open DHT record tree")?;
+        Ok(Self {
+            records,
+            providers: MemoryStore::new(local_id),
+            config,
+        })
+    }
+
+    /// Number of unexpired value records.
+    pub fn len(&self) -> usize {
+        self.records().count()
+    }
+
+    /// Whether the store holds no unexpired value records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove expired records, returning how many were dropped.
+    pub fn purge_expired(&self) -> usize {
+        let now = now_ms();
+        let mut purged = 0;
+        for (key, stored) in self.records.iter().flatten() {
+            if let Ok(stored) = serde_json::from_slice::<StoredRecord>(&stored) {
+                if stored.expires_at_ms.is_some_and(|at| at <= now) {
+                    let _ = self.records.remove(key);
+                    purged += 1;
+                }
+            }
+        }
+        purged
+    }
+
+    fn encode(record: &Record) -> Option<Vec<u8>> {
+        let expires_at_ms = record.expires.map(|expires| {
+            let remaining = expires.saturating_duration_since(Instant::now());
+            now_ms().saturating_add(remaining.as_millis() as u64)
+        });
+        serde_json::to_vec(&StoredRecord {
+            value: record.value.clone(),
+            publisher: record.publisher.map(|peer| peer.to_bytes()),
+            expires_at_ms,
+        })
+        .ok()
+    }
+
+    /// Decode a stored record, returning `None` if it is corrupt or expired.
+    fn decode(key: &[u8], bytes: &[u8]) -> Option<Record> {
+        let stored: StoredRecord = match serde_json::from_slice(bytes) {
+            Ok(stored) => stored,
+            Err(err) => {
+                warn!("Dropping corrupt DHT record: {err}");
+                return None;
+            }
+        };
+        let expires = match stored.expires_at_ms {
+            Some(at) => {
+                let now = now_ms();
+                if at <= now {
+                    return None;
+                }
+                Some(Instant::now() + Duration::from_millis(at - now))
+            }
+            None => None,
+        };
+        Some(Record {
+            key: RecordKey::new(&key),
+            value: stored.value,
+            publisher: stored
+                .publisher
+                .and_then(|bytes| PeerId::from_bytes(&bytes).ok()),
+            expires,
+        })
+    }
+}
+
+impl RecordStore for SledRecordStore {
+    type RecordsIter<'a> = std::vec::IntoIter<Cow<'a, Record>>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        let bytes = self.records.get(k.as_ref()).ok()??;
+        match Self::decode(k.as_ref(), &bytes) {
+            Some(record) => Some(Cow::Owned(record)),
+            None => {
+                let _ = self.records.remove(k.as_ref());
+                None
+            }
+        }
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        if r.value.len() > self.config.max_value_bytes {
+            return Err(store::Error::ValueTooLarge);
+        }
+        let key = r.key.as_ref();
+        let exists = self.records.contains_key(key).unwrap_or(false);
+        if !exists && self.records.len() >= self.config.max_records {
+            self.purge_expired();
+            if self.records.len() >= self.config.max_records {
+                return Err(store::Error::MaxRecords);
+            }
+        }
+        let encoded = Self::encode(&r).ok_or(store::Error::ValueTooLarge)?;
+        // `store::Error` has no I/O variant; report a full store so Kademlia
+        // does not count the record as stored.
+        self.records.insert(key, encoded).map_err(|err| {
+            warn!("Failed to persist DHT record: {err}");
+            store::Error::MaxRecords
+        })?;
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        let _ = self.records.remove(k.as_ref());
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        let mut live = Vec::new();
+        for (key, bytes) in self.records.iter().flatten() {
+            match Self::decode(&key, &bytes) {
+                Some(record) => live.push(Cow::Owned(record)),
+                None => {
+                    let _ = self.records.remove(key);
+                }
+            }
+        }
+        live.into_iter()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        self.providers.add_provider(record)
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        self.providers.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.providers.provided()
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        self.providers.remove_provider(k, p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::identity;
+
+    fn peer() -> PeerId {
+        PeerId::from(identity::Keypair::generate_ed25519().public())
+    }
+
+    fn record(key: &[u8], value: &[u8], ttl: Option<Duration>) -> Record {
+        Record {
+            key: RecordKey::new(&key),
+            value: value.to_vec(),
+            publisher: Some(peer()),
+            expires: ttl.map(|ttl| Instant::now() + ttl),
+        }
+    }
+
+    #[test]
+    fn test_records_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = peer();
+        let original = record(b"key", b"value", Some(Duration::from_secs(3600)));
+        {
+            let mut store =
+                SledRecordStore::open(local, dir.path(), RecordStoreConfig::default()).unwrap();
+            store.put(original.clone()).unwrap();
+        }
+
+        let store = SledRecordStore::open(local, dir.path(), RecordStoreConfig::default()).unwrap();
+        let restored = store.get(&original.key).expect("record persisted");
+        assert_eq!(restored.value, original.value);
+        assert_eq!(restored.publisher, original.publisher);
+        assert!(restored.expires.is_some());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_expired_records_are_dropped() {
+        let mut store = SledRecordStore::temporary(peer(), RecordStoreConfig::default()).unwrap();
+        let mut stale = record(b"stale", b"old", None);
+        stale.expires = Some(Instant::now());
+        store.put(stale.clone()).unwrap();
+        store
+            .put(record(b"fresh", b"new", Some(Duration::from_secs(60))))
+            .unwrap();
+
+        assert!(store.get(&stale.key).is_none());
+        assert_eq!(store.records().count(), 1);
+        assert_eq!(store.purge_expired(), 0);
+    }
+
+    #[test]
+    fn test_enforces_limits() {
+        let config = RecordStoreConfig {
+            max_records: 1,
+            max_value_bytes: 4,
+        };
+        let mut store = SledRecordStore::temporary(peer(), config).unwrap();
+        assert!(matches!(
+            store.put(record(b"big", b"too large", None)),
+            Err(store::Error::ValueTooLarge)
+        ));
+        store.put(record(b"a", b"1", None)).unwrap();
+        store.put(record(b"a", b"2", None)).unwrap();
+        assert!(matches!(
+            store.put(record(b"b", b"3", None)),
+            Err(store::Error::MaxRecords)
+        ));
+        store.remove(&RecordKey::new(&b"a"));
+        assert!(store.is_empty());
+    }
+}
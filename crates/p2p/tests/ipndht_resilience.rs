@@ -73,6 +73,10 @@ impl DhtTestNode {
             agent_version: format!("ippan-test/{}", config.node_id),
             bootstrap_retry_interval: Duration::from_secs(5),
             bootstrap_max_retries: 10,
+            record_store_path: None,
+            record_ttl: Duration::from_secs(60 * 60),
+            record_republish_interval: Duration::from_secs(30 * 60),
+            peer_scoring: None,
         };
 
         let network = Libp2pNetwork::new(libp2p_config)?;
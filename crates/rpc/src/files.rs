@@ -1,13 +1,22 @@
-//! File descriptor RPC endpoints for IPNDHT file publishing and lookup.
+//! File descriptor RPC endpoints for IPNDHT file publishing and lookup, plus
+//! chunked content upload and verified ranged download.
 
 use std::net::SocketAddr;
+use std::ops::Range;
 use std::sync::Arc;
 
+use axum::body::Bytes;
 use axum::extract::{ConnectInfo, Path as AxumPath, State};
-use axum::http::StatusCode;
+use axum::http::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
-use ippan_files::{descriptor::ContentHash, FileDescriptor, FileId};
+use ippan_files::{
+    descriptor::ContentHash, ContentError, ContentManifest, ContentStore, FileDescriptor,
+    FileEncryption, FileId, KeyEnvelope,
+};
 use ippan_types::address::{decode_address, encode_address};
+use ippan_types::IppanTimeMicros;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
@@ -33,6 +42,125 @@ pub struct PublishFileRequest {
     /// Optional tags.
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Creation timestamp (microseconds) the descriptor id is derived from.
+    pub created_at_us: u64,
+
+    /// Recipient key envelopes for client-side encrypted content.
+    #[serde(default)]
+    pub encryption: Option<FileEncryptionView>,
+
+    /// Owner's Ed25519 signature over the descriptor signing digest (hex).
+    pub signature: String,
+}
+
+/// Request to replace the recipient envelopes of an encrypted file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateFileRecipientsRequest {
+    /// Full new envelope set, re-wrapped by the owner.
+    pub recipients: Vec<KeyEnvelopeView>,
+
+    /// Envelope set version; must exceed the stored one.
+    pub version: u64,
+
+    /// Owner's signature over the updated recipients digest (hex).
+    pub signature: String,
+}
+
+/// Encryption metadata of an encrypted file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEncryptionView {
+    /// Content cipher (`AES-256-GCM`).
+    pub algorithm: String,
+
+    /// Content nonce (hex).
+    pub nonce: String,
+
+    /// Recipient key envelopes.
+    pub recipients: Vec<KeyEnvelopeView>,
+
+    /// Envelope set version, raised on every recipient update.
+    #[serde(default)]
+    pub version: u64,
+
+    /// Owner's signature over the recipients digest (hex).
+    #[serde(default)]
+    pub recipients_signature: String,
+}
+
+/// Content key sealed to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEnvelopeView {
+    /// Recipient address.
+    pub recipient: String,
+
+    /// Sealed content key (hex).
+    pub wrapped_key: String,
+}
+
+impl From<FileEncryption> for FileEncryptionView {
+    fn from(encryption: FileEncryption) -> Self {
+        Self {
+            algorithm: encryption.algorithm,
+            nonce: hex::encode(encryption.nonce),
+            recipients: encryption
+                .recipients
+                .into_iter()
+                .map(KeyEnvelopeView::from)
+                .collect(),
+            version: encryption.version,
+            recipients_signature: hex::encode(encryption.recipients_signature),
+        }
+    }
+}
+
+impl From<KeyEnvelope> for KeyEnvelopeView {
+    fn from(envelope: KeyEnvelope) -> Self {
+        Self {
+            recipient: encode_address(&envelope.recipient),
+            wrapped_key: hex::encode(envelope.wrapped_key),
+        }
+    }
+}
+
+impl TryFrom<FileEncryptionView> for FileEncryption {
+    type Error = String;
+
+    fn try_from(view: FileEncryptionView) -> Result<Self, Self::Error> {
+        let nonce = hex::decode(&view.nonce)
+            .ok()
+            .and_then(|bytes| <[u8; 12]>::try_from(bytes).ok())
+            .ok_or_else(|| format!("Invalid encryption nonce: {}", view.nonce))?;
+        let recipients_signature = hex::decode(&view.recipients_signature)
+            .map_err(|e| format!("Invalid recipients signature hex: {e}"))?;
+        Ok(Self {
+            algorithm: view.algorithm,
+            nonce,
+            recipients: view
+                .recipients
+                .into_iter()
+                .map(KeyEnvelope::try_from)
+                .collect::<Result<_, _>>()?,
+            version: view.version,
+            recipients_signature,
+        })
+    }
+}
+
+impl TryFrom<KeyEnvelopeView> for KeyEnvelope {
+    type Error = String;
+
+    fn try_from(view: KeyEnvelopeView) -> Result<Self, Self::Error> {
+        let recipient = decode_address(&view.recipient)
+            .map_err(|e| format!("Invalid recipient address {}: {e}", view.recipient))?;
+        let wrapped_key = hex::decode(&view.wrapped_key)
+            .map_err(|e| format!("Invalid wrapped key for {}: {e}", view.recipient))?;
+        Ok(Self {
+            recipient,
+            wrapped_key,
+        })
+    }
 }
 
 /// Response from publishing a file descriptor.
@@ -63,6 +191,29 @@ pub struct PublishFileResponse {
 
     /// DHT publish status.
     pub dht_published: bool,
+
+    /// Whether the content bytes are held in this node's content store.
+    pub content_stored: bool,
+
+    /// Recipient key envelopes, for encrypted files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<FileEncryptionView>,
+}
+
+/// Response from uploading file content.
+#[derive(Debug, Serialize)]
+pub struct UploadFileContentResponse {
+    /// Merkle root of the uploaded chunks (equals the requested content hash).
+    pub content_hash: String,
+
+    /// Content length in bytes.
+    pub size_bytes: u64,
+
+    /// Chunk size used to split the content.
+    pub chunk_size: u32,
+
+    /// Number of stored chunks.
+    pub chunk_count: usize,
 }
 
 /// Response for file descriptor lookup.
@@ -90,6 +241,14 @@ pub struct FileDescriptorResponse {
     /// Optional tags.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+
+    /// Recipient key envelopes, for encrypted files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<FileEncryptionView>,
+
+    /// Owner signature (hex).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signature: String,
 }
 
 impl From<FileDescriptor> for FileDescriptorResponse {
@@ -102,6 +261,8 @@ impl From<FileDescriptor> for FileDescriptorResponse {
             created_at_us: desc.created_at_us,
             mime_type: desc.mime_type,
             tags: desc.tags,
+            encryption: desc.encryption.map(FileEncryptionView::from),
+            signature: hex::encode(desc.signature),
         }
     }
 }
@@ -153,14 +314,57 @@ pub async fn handle_publish_file(
         ));
     }
 
-    // Create file descriptor
-    let descriptor = FileDescriptor::new(
+    // If the bytes were uploaded here, the advertised size must match them.
+    let content_stored = match stored_manifest(&state, &content_hash)? {
+        Some(manifest) if manifest.size_bytes != request.size_bytes => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "size_mismatch",
+                    format!(
+                        "Stored content is {} bytes, descriptor claims {}",
+                        manifest.size_bytes, request.size_bytes
+                    ),
+                )),
+            ));
+        }
+        Some(_) => true,
+        None => false,
+    };
+
+    // Rebuild the descriptor the owner signed
+    let mut descriptor = FileDescriptor::new_at_time(
         content_hash,
         owner_bytes,
         request.size_bytes,
+        IppanTimeMicros(request.created_at_us),
         request.mime_type,
         request.tags,
     );
+    if let Some(encryption) = request.encryption {
+        let encryption = FileEncryption::try_from(encryption).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_encryption", e)),
+            )
+        })?;
+        descriptor = descriptor.with_encryption(encryption);
+    }
+    descriptor.signature = hex::decode(&request.signature).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_signature",
+                format!("Invalid signature hex: {e}"),
+            )),
+        )
+    })?;
+    if let Err(e) = descriptor.verify_signature() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_signature", &e)),
+        ));
+    }
 
     // Validate descriptor
     if let Err(e) = descriptor.validate() {
@@ -218,9 +422,360 @@ pub async fn handle_publish_file(
         mime_type: descriptor.mime_type,
         tags: descriptor.tags,
         dht_published,
+        content_stored,
+        encryption: descriptor.encryption.map(FileEncryptionView::from),
     }))
 }
 
+/// PUT /files/{id}/recipients - Replace an encrypted file's recipient envelopes
+///
+/// The owner re-wraps the content key for the new recipient set (adding or
+/// revoking readers), raises the envelope version and signs the recipients
+/// digest. The ciphertext, descriptor signature and L1 anchor are unchanged.
+pub async fn handle_update_file_recipients(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(id_hex): AxumPath<String>,
+    ValidatedJson(request): ValidatedJson<UpdateFileRecipientsRequest>,
+) -> Result<Json<FileDescriptorResponse>, (StatusCode, Json<ApiError>)> {
+    // Security check
+    if let Err(err) = guard_file_request(&state, &addr, "/files/{id}/recipients").await {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiError::new("security_error", err.to_string())),
+        ));
+    }
+
+    let file_id = FileId::from_hex(&id_hex).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_file_id",
+                format!("Invalid file ID: {e}"),
+            )),
+        )
+    })?;
+    let Some(file_storage) = &state.file_storage else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new(
+                "storage_unavailable",
+                "File storage not configured",
+            )),
+        ));
+    };
+
+    let mut descriptor = file_storage
+        .get(&file_id)
+        .map_err(|e| {
+            warn!("Storage error during lookup: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "storage_error",
+                    "Failed to load file descriptor",
+                )),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::new("not_found", "File descriptor not found")),
+            )
+        })?;
+    let Some(encryption) = descriptor.encryption.as_mut() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("not_encrypted", "File is not encrypted")),
+        ));
+    };
+    if request.version <= encryption.version {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new(
+                "stale_version",
+                format!(
+                    "Recipients version {} does not exceed current version {}",
+                    request.version, encryption.version
+                ),
+            )),
+        ));
+    }
+    encryption.version = request.version;
+    encryption.recipients = request
+        .recipients
+        .into_iter()
+        .map(KeyEnvelope::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_encryption", e)),
+            )
+        })?;
+
+    encryption.recipients_signature = hex::decode(&request.signature).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_signature",
+                format!("Invalid signature hex: {e}"),
+            )),
+        )
+    })?;
+    if let Err(e) = descriptor.verify_signature() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_signature", &e)),
+        ));
+    }
+    if let Err(e) = descriptor.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("validation_failed", &e)),
+        ));
+    }
+
+    file_storage.store(descriptor.clone()).map_err(|e| {
+        warn!("Failed to store file descriptor: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(
+                "storage_error",
+                "Failed to store file descriptor",
+            )),
+        )
+    })?;
+    if let Some(dht_service) = &state.file_dht {
+        if let Err(e) = dht_service.publish_file(&descriptor).await {
+            warn!("DHT publish failed (non-fatal): {}", e);
+        }
+    }
+
+    Ok(Json(FileDescriptorResponse::from(descriptor)))
+}
+
+/// PUT /files/content/{hash} - Upload file content
+///
+/// The body is chunked and hashed; it is only stored if the resulting Merkle
+/// root equals `hash`.
+pub async fn handle_upload_file_content(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(hash_hex): AxumPath<String>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<UploadFileContentResponse>), (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_file_request(&state, &addr, "/files/content/{hash}").await {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiError::new("security_error", err.to_string())),
+        ));
+    }
+
+    let content_hash = parse_content_hash(&hash_hex)?;
+    let store = content_store(&state)?;
+
+    if body.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_size",
+                "File content must not be empty",
+            )),
+        ));
+    }
+
+    let manifest = store
+        .put_verified(&content_hash, &body)
+        .map_err(content_error_response)?;
+    debug!(
+        "Stored {} bytes of content {} in {} chunks",
+        manifest.size_bytes,
+        manifest.root.to_hex(),
+        manifest.chunk_count()
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UploadFileContentResponse {
+            content_hash: manifest.root.to_hex(),
+            size_bytes: manifest.size_bytes,
+            chunk_size: manifest.chunk_size,
+            chunk_count: manifest.chunk_count(),
+        }),
+    ))
+}
+
+/// GET /files/content/{hash} - Download file content
+///
+/// Supports a single `Range: bytes=...` header. Every chunk touched by the
+/// response is verified against the manifest before any byte is returned.
+pub async fn handle_get_file_content(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(hash_hex): AxumPath<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_file_request(&state, &addr, "/files/content/{hash}").await {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiError::new("security_error", err.to_string())),
+        ));
+    }
+
+    let content_hash = parse_content_hash(&hash_hex)?;
+    let store = content_store(&state)?;
+    let manifest = stored_manifest(&state, &content_hash)?.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("not_found", "File content not found")),
+        )
+    })?;
+    let size = manifest.size_bytes;
+
+    let requested = match headers.get(RANGE) {
+        Some(value) => {
+            let range = value
+                .to_str()
+                .ok()
+                .and_then(|raw| parse_byte_range(raw, size))
+                .ok_or_else(|| {
+                    (
+                        StatusCode::RANGE_NOT_SATISFIABLE,
+                        Json(ApiError::new(
+                            "invalid_range",
+                            format!("Range not satisfiable for {size} bytes"),
+                        )),
+                    )
+                })?;
+            Some(range)
+        }
+        None => None,
+    };
+
+    let range = requested.clone().unwrap_or(0..size);
+    let bytes = store
+        .read_manifest_range(&manifest, range.clone())
+        .map_err(content_error_response)?;
+
+    let mut response = if requested.is_some() {
+        let mut response = (StatusCode::PARTIAL_CONTENT, bytes).into_response();
+        let content_range = format!("bytes {}-{}/{}", range.start, range.end - 1, size);
+        if let Ok(value) = content_range.parse() {
+            response.headers_mut().insert(CONTENT_RANGE, value);
+        }
+        response
+    } else {
+        (StatusCode::OK, bytes).into_response()
+    };
+    let headers = response.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        "application/octet-stream".parse().expect("static header"),
+    );
+    headers.insert(ACCEPT_RANGES, "bytes".parse().expect("static header"));
+    headers.insert(CONTENT_LENGTH, (range.end - range.start).into());
+    Ok(response)
+}
+
+/// Parse a single-range `Range` header (`bytes=a-b`, `bytes=a-`, `bytes=-n`)
+/// into a half-open byte range. Returns `None` if it is malformed or not
+/// satisfiable for `size` bytes.
+fn parse_byte_range(header: &str, size: u64) -> Option<Range<u64>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let len: u64 = suffix.parse().ok()?;
+            if len == 0 {
+                return None;
+            }
+            (size.saturating_sub(len), size)
+        }
+        (start, "") => (start.parse().ok()?, size),
+        (start, end) => {
+            let end: u64 = end.parse().ok()?;
+            (start.parse().ok()?, end.saturating_add(1).min(size))
+        }
+    };
+    (start < end && start < size).then_some(start..end)
+}
+
+fn parse_content_hash(hash_hex: &str) -> Result<ContentHash, (StatusCode, Json<ApiError>)> {
+    ContentHash::from_hex(hash_hex).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_content_hash",
+                format!("Invalid content hash: {e}"),
+            )),
+        )
+    })
+}
+
+fn content_store(state: &AppState) -> Result<&ContentStore, (StatusCode, Json<ApiError>)> {
+    state.content_store.as_deref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new(
+                "content_storage_unavailable",
+                "File content storage not configured",
+            )),
+        )
+    })
+}
+
+/// Load the verified manifest for `hash`, if content storage is configured
+/// and holds it.
+fn stored_manifest(
+    state: &AppState,
+    hash: &ContentHash,
+) -> Result<Option<ContentManifest>, (StatusCode, Json<ApiError>)> {
+    match &state.content_store {
+        Some(store) => store.manifest(hash).map_err(content_error_response),
+        None => Ok(None),
+    }
+}
+
+fn content_error_response(err: ContentError) -> (StatusCode, Json<ApiError>) {
+    match err {
+        ContentError::NotFound(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("not_found", "File content not found")),
+        ),
+        ContentError::HashMismatch { .. } => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("content_hash_mismatch", err.to_string())),
+        ),
+        ContentError::InvalidRange { .. } => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            Json(ApiError::new("invalid_range", err.to_string())),
+        ),
+        ContentError::MissingChunk { .. }
+        | ContentError::CorruptChunk { .. }
+        | ContentError::InvalidManifest(_) => {
+            warn!("Stored file content failed verification: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("content_corrupt", err.to_string())),
+            )
+        }
+        ContentError::Storage(e) => {
+            warn!("File content storage error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "storage_error",
+                    "Failed to access file content",
+                )),
+            )
+        }
+    }
+}
+
 /// GET /files/{id} - Lookup a file descriptor by ID
 pub async fn handle_get_file(
     State(state): State<Arc<AppState>>,
@@ -305,11 +860,15 @@ mod tests {
             "content_hash": "0000000000000000000000000000000000000000000000000000000000000001",
             "size_bytes": 1024,
             "mime_type": "text/plain",
-            "tags": ["test"]
+            "tags": ["test"],
+            "created_at_us": 1700000000000000,
+            "signature": "00"
         }"#;
 
         let req: PublishFileRequest = serde_json::from_str(json).unwrap();
+        assert!(req.encryption.is_none());
         assert_eq!(req.size_bytes, 1024);
+        assert_eq!(req.created_at_us, 1_700_000_000_000_000);
         assert_eq!(req.mime_type, Some("text/plain".to_string()));
         assert_eq!(req.tags, vec!["test".to_string()]);
     }
@@ -332,4 +891,16 @@ mod tests {
         assert_eq!(response.mime_type, Some("text/plain".to_string()));
         assert_eq!(response.tags, vec!["tag1".to_string()]);
     }
+
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range("bytes=0-9", 100), Some(0..10));
+        assert_eq!(parse_byte_range("bytes=90-", 100), Some(90..100));
+        assert_eq!(parse_byte_range("bytes=-10", 100), Some(90..100));
+        assert_eq!(parse_byte_range("bytes=50-500", 100), Some(50..100));
+        assert_eq!(parse_byte_range("bytes=100-", 100), None);
+        assert_eq!(parse_byte_range("bytes=9-3", 100), None);
+        assert_eq!(parse_byte_range("bytes=0-1,5-6", 100), None);
+        assert_eq!(parse_byte_range("items=0-1", 100), None);
+    }
 }
@@ -4,15 +4,20 @@
 mod tests {
     use anyhow::Result;
     use async_trait::async_trait;
+    use axum::body::Bytes;
     use axum::extract::{ConnectInfo, Path as AxumPath, State};
+    use axum::http::header::{CONTENT_RANGE, RANGE};
+    use axum::http::{HeaderMap, StatusCode};
+    use http_body_util::BodyExt;
     use ippan_files::{
         descriptor::ContentHash,
         dht::{DhtLookupResult, DhtPublishResult, StubFileDhtService},
-        FileDescriptor, FileDhtService, FileId, FileStorage, MemoryFileStorage,
+        encrypt_file, ContentStore, FileDescriptor, FileDhtService, FileId, FileStorage,
+        MemoryFileStorage,
     };
     use ippan_l1_handle_anchors::L1HandleAnchorStorage;
     use ippan_l2_handle_registry::{
-        dht::HandleDhtService, dht::StubHandleDhtService, L2HandleRegistry,
+        dht::HandleDhtService, dht::StubHandleDhtService, HandleAuctionBook, L2HandleRegistry,
     };
     use ippan_mempool::Mempool;
     use ippan_storage::MemoryStorage;
@@ -25,10 +30,43 @@ mod tests {
     use tokio::runtime::Runtime;
 
     use crate::files::{
-        handle_get_file, handle_publish_file, FileDescriptorResponse, PublishFileRequest,
+        handle_get_file, handle_get_file_content, handle_publish_file,
+        handle_update_file_recipients, handle_upload_file_content, FileDescriptorResponse,
+        KeyEnvelopeView, PublishFileRequest, UpdateFileRecipientsRequest,
     };
     use crate::server::{AppState, BatchLane, L2Config, ValidatedJson};
 
+    fn owner_key(seed: u8) -> ([u8; 32], [u8; 32]) {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+        (
+            signing_key.to_bytes(),
+            signing_key.verifying_key().to_bytes(),
+        )
+    }
+
+    /// Build a publish request signed by the owner derived from `seed`.
+    fn signed_request(
+        seed: u8,
+        content_hash: ContentHash,
+        size_bytes: u64,
+        mime_type: Option<String>,
+        tags: Vec<String>,
+    ) -> PublishFileRequest {
+        let (private_key, owner) = owner_key(seed);
+        let mut descriptor = FileDescriptor::new(content_hash, owner, size_bytes, mime_type, tags);
+        descriptor.sign(&private_key).expect("sign descriptor");
+        PublishFileRequest {
+            owner: encode_address(&owner),
+            content_hash: content_hash.to_hex(),
+            size_bytes,
+            mime_type: descriptor.mime_type,
+            tags: descriptor.tags,
+            created_at_us: descriptor.created_at_us,
+            encryption: None,
+            signature: hex::encode(descriptor.signature),
+        }
+    }
+
     #[derive(Clone, Default)]
     struct RecordingFileDht {
         published: Arc<Mutex<Vec<FileDescriptor>>>,
@@ -104,12 +142,14 @@ mod tests {
             metrics: None,
             file_storage: Some(file_storage),
             file_dht: Some(file_dht),
+            content_store: Some(Arc::new(ContentStore::in_memory().with_chunk_size(16))),
             dht_file_mode: "stub".into(),
             dev_mode: true,
             rpc_allowed_origins: vec!["http://localhost:3000".into()],
             handle_registry,
             handle_anchors,
             handle_dht: Some(handle_dht),
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             dht_handle_mode: "stub".into(),
             ipn_dht: None,
             batch_lane: BatchLane::from_env(),
@@ -121,14 +161,13 @@ mod tests {
         let _state = create_test_state();
 
         // Valid request
-        let valid = PublishFileRequest {
-            owner: encode_address(&[1u8; 32]),
-            content_hash: "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
-                .to_string(),
-            size_bytes: 1024,
-            mime_type: Some("text/plain".to_string()),
-            tags: vec!["test".to_string()],
-        };
+        let valid = signed_request(
+            1,
+            ContentHash::from_data(b"request"),
+            1024,
+            Some("text/plain".to_string()),
+            vec!["test".to_string()],
+        );
 
         assert_eq!(valid.size_bytes, 1024);
         assert_eq!(valid.mime_type, Some("text/plain".to_string()));
@@ -247,15 +286,13 @@ mod tests {
         state.file_dht = Some(recording.clone());
         let state = Arc::new(state);
 
-        let owner = [1u8; 32];
-        let request = PublishFileRequest {
-            owner: encode_address(&owner),
-            content_hash: "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
-                .to_string(),
-            size_bytes: 2048,
-            mime_type: Some("application/json".into()),
-            tags: vec!["rpc".into()],
-        };
+        let request = signed_request(
+            1,
+            ContentHash::from_data(b"rpc"),
+            2048,
+            Some("application/json".into()),
+            vec!["rpc".into()],
+        );
 
         let addr: SocketAddr = "127.0.0.1:9500".parse().unwrap();
         let response = handle_publish_file(
@@ -283,6 +320,191 @@ mod tests {
 
         let published = recording.last_published().expect("dht call");
         assert_eq!(published.id, file_id);
+        assert!(published.verify_signature().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_publish_file_rejects_bad_signatures() {
+        let state = Arc::new(create_test_state());
+        let addr: SocketAddr = "127.0.0.1:9501".parse().unwrap();
+        let publish = |request| {
+            handle_publish_file(
+                State(state.clone()),
+                ConnectInfo(addr),
+                ValidatedJson(request),
+            )
+        };
+        let code = |err: (StatusCode, axum::Json<crate::server::ApiError>)| {
+            assert_eq!(err.0, StatusCode::BAD_REQUEST);
+            serde_json::to_value(&err.1 .0).unwrap()["code"].clone()
+        };
+
+        // Claiming someone else's ownership.
+        let mut forged = signed_request(1, ContentHash::from_data(b"x"), 10, None, vec![]);
+        forged.owner = encode_address(&owner_key(2).1);
+        let err = publish(forged).await.expect_err("forged owner");
+        assert_eq!(code(err), "invalid_signature");
+
+        // Tampering with signed fields.
+        let mut tampered = signed_request(1, ContentHash::from_data(b"x"), 10, None, vec![]);
+        tampered.tags.push("injected".into());
+        let err = publish(tampered).await.expect_err("tampered tags");
+        assert_eq!(code(err), "invalid_signature");
+
+        let mut unsigned = signed_request(1, ContentHash::from_data(b"x"), 10, None, vec![]);
+        unsigned.signature = String::new();
+        let err = publish(unsigned).await.expect_err("unsigned");
+        assert_eq!(code(err), "invalid_signature");
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_file_recipients_can_be_rewrapped() {
+        let mut state = create_test_state();
+        let recording = Arc::new(RecordingFileDht::new());
+        state.file_dht = Some(recording.clone());
+        let state = Arc::new(state);
+        let addr: SocketAddr = "127.0.0.1:9502".parse().unwrap();
+
+        let (owner_private, owner) = owner_key(1);
+        let (reader_private, reader) = owner_key(3);
+        let encrypted = encrypt_file(b"quarterly numbers", &[owner]).unwrap();
+        let mut descriptor = FileDescriptor::new(
+            ContentHash::from_data(&encrypted.ciphertext),
+            owner,
+            encrypted.ciphertext.len() as u64,
+            None,
+            vec![],
+        )
+        .with_encryption(encrypted.encryption.clone());
+        descriptor.sign(&owner_private).unwrap();
+
+        let response = handle_publish_file(
+            State(state.clone()),
+            ConnectInfo(addr),
+            ValidatedJson(PublishFileRequest {
+                owner: encode_address(&owner),
+                content_hash: descriptor.content_hash.to_hex(),
+                size_bytes: descriptor.size_bytes,
+                mime_type: None,
+                tags: vec![],
+                created_at_us: descriptor.created_at_us,
+                encryption: descriptor.encryption.clone().map(Into::into),
+                signature: hex::encode(&descriptor.signature),
+            }),
+        )
+        .await
+        .expect("publish encrypted")
+        .0;
+        assert_eq!(response.id, descriptor.id.to_hex());
+        assert_eq!(response.encryption.unwrap().recipients.len(), 1);
+
+        // Share with a reader: re-wrap the content key, bump the version and
+        // re-sign the envelope set.
+        let mut shared = descriptor.clone();
+        let encryption = shared.encryption.as_mut().unwrap();
+        encryption
+            .add_recipient(&encrypted.content_key, &reader)
+            .unwrap();
+        encryption.version = 1;
+        let recipients: Vec<KeyEnvelopeView> = encryption
+            .recipients
+            .iter()
+            .cloned()
+            .map(KeyEnvelopeView::from)
+            .collect();
+        let update = |recipients: Vec<KeyEnvelopeView>, version: u64, signature: &[u8]| {
+            handle_update_file_recipients(
+                State(state.clone()),
+                ConnectInfo(addr),
+                AxumPath(descriptor.id.to_hex()),
+                ValidatedJson(UpdateFileRecipientsRequest {
+                    recipients,
+                    version,
+                    signature: hex::encode(signature),
+                }),
+            )
+        };
+        let recipients_signature = |desc: &FileDescriptor| {
+            desc.encryption
+                .as_ref()
+                .unwrap()
+                .recipients_signature
+                .clone()
+        };
+
+        // The old signature does not cover the new recipient set.
+        let err = update(recipients.clone(), 1, &recipients_signature(&descriptor))
+            .await
+            .expect_err("stale signature");
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+
+        shared.sign_recipients(&owner_private).unwrap();
+        let response = update(recipients.clone(), 1, &recipients_signature(&shared))
+            .await
+            .expect("add reader")
+            .0;
+        let view = response.encryption.unwrap();
+        assert_eq!(view.recipients.len(), 2);
+        assert_eq!(view.version, 1);
+        let published = recording.last_published().expect("republished");
+        assert_eq!(
+            published
+                .encryption
+                .as_ref()
+                .unwrap()
+                .decrypt_with_private_key(&encrypted.ciphertext, &reader_private)
+                .unwrap(),
+            b"quarterly numbers"
+        );
+        let owner_files = state
+            .file_storage
+            .as_ref()
+            .unwrap()
+            .list_by_owner(&owner)
+            .unwrap();
+        assert_eq!(owner_files.len(), 1);
+        // The descriptor signature, and so the L1 anchor, is untouched
+        assert_eq!(owner_files[0].signature, descriptor.signature);
+        assert_eq!(owner_files[0].signing_digest(), descriptor.signing_digest());
+
+        // Replaying the same update is rejected.
+        let err = update(recipients, 1, &recipients_signature(&shared))
+            .await
+            .expect_err("replayed version");
+        assert_eq!(err.0, StatusCode::CONFLICT);
+        assert_eq!(
+            serde_json::to_value(&err.1 .0).unwrap()["code"],
+            "stale_version"
+        );
+
+        // Revoke the reader again.
+        let mut revoked = shared.clone();
+        let encryption = revoked.encryption.as_mut().unwrap();
+        encryption.revoke_recipient(&reader);
+        encryption.version = 2;
+        revoked.sign_recipients(&owner_private).unwrap();
+        let recipients = revoked
+            .encryption
+            .as_ref()
+            .unwrap()
+            .recipients
+            .iter()
+            .cloned()
+            .map(KeyEnvelopeView::from)
+            .collect();
+        let response = update(recipients, 2, &recipients_signature(&revoked))
+            .await
+            .expect("revoke reader")
+            .0;
+        assert_eq!(response.encryption.unwrap().recipients.len(), 1);
+        let stored = state
+            .file_storage
+            .as_ref()
+            .unwrap()
+            .get(&descriptor.id)
+            .unwrap()
+            .unwrap();
+        assert!(!stored.encryption.unwrap().has_recipient(&reader));
     }
 
     #[tokio::test]
@@ -311,4 +533,123 @@ mod tests {
         assert_eq!(response.id, descriptor.id.to_hex());
         assert_eq!(response.owner, encode_address(&descriptor.owner));
     }
+
+    #[tokio::test]
+    async fn test_upload_and_ranged_download_verify_content() {
+        let state = Arc::new(create_test_state());
+        let addr: SocketAddr = "127.0.0.1:9502".parse().unwrap();
+        let content: Vec<u8> = (0..100u8).collect();
+        let root = ippan_files::content::content_root(&content, 16);
+
+        let wrong = ContentHash::from_data(&content);
+        let err = handle_upload_file_content(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(wrong.to_hex()),
+            Bytes::from(content.clone()),
+        )
+        .await
+        .expect_err("multi-chunk root differs from flat hash");
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            serde_json::to_value(&err.1 .0).unwrap()["code"],
+            "content_hash_mismatch"
+        );
+
+        let (status, uploaded) = handle_upload_file_content(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(root.to_hex()),
+            Bytes::from(content.clone()),
+        )
+        .await
+        .expect("upload ok");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(uploaded.0.chunk_count, 7);
+        assert_eq!(uploaded.0.size_bytes, 100);
+
+        let full = handle_get_file_content(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(root.to_hex()),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("download ok");
+        assert_eq!(full.status(), StatusCode::OK);
+        let body = full.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), content.as_slice());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, "bytes=10-39".parse().unwrap());
+        let partial = handle_get_file_content(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(root.to_hex()),
+            headers,
+        )
+        .await
+        .expect("ranged download ok");
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            partial.headers().get(CONTENT_RANGE).unwrap(),
+            "bytes 10-39/100"
+        );
+        let body = partial.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), &content[10..40]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RANGE, "bytes=200-".parse().unwrap());
+        let err = handle_get_file_content(
+            State(state),
+            ConnectInfo(addr),
+            AxumPath(root.to_hex()),
+            headers,
+        )
+        .await
+        .expect_err("range past end");
+        assert_eq!(err.0, StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+
+    #[tokio::test]
+    async fn test_publish_checks_size_of_uploaded_content() {
+        let state = Arc::new(create_test_state());
+        let addr: SocketAddr = "127.0.0.1:9503".parse().unwrap();
+        let content = b"uploaded bytes".to_vec();
+        let root = ContentHash::from_data(&content);
+
+        let (status, _) = handle_upload_file_content(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(root.to_hex()),
+            Bytes::from(content.clone()),
+        )
+        .await
+        .expect("upload ok");
+        assert_eq!(status, StatusCode::CREATED);
+
+        let request = |size_bytes| signed_request(3, root, size_bytes, None, vec![]);
+
+        let err = handle_publish_file(
+            State(state.clone()),
+            ConnectInfo(addr),
+            ValidatedJson(request(999)),
+        )
+        .await
+        .expect_err("size mismatch");
+        assert_eq!(
+            serde_json::to_value(&err.1 .0).unwrap()["code"],
+            "size_mismatch"
+        );
+
+        let response = handle_publish_file(
+            State(state),
+            ConnectInfo(addr),
+            ValidatedJson(request(content.len() as u64)),
+        )
+        .await
+        .expect("publish ok")
+        .0;
+        assert!(response.content_stored);
+    }
 }
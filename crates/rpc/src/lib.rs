@@ -13,7 +13,9 @@ pub use server::{start_p2p_server, start_server, AiStatusHandle, AppState, Batch
 
 // Re-export types from ippan_p2p for convenience
 pub use ippan_p2p::{
-    HttpP2PNetwork, NetworkMessage, P2PConfig, P2PError, P2PLimits, PeerInfo as P2PPeerInfo,
+    validate_network_message, CompactBlockAck, CompactBlockOutcome, HttpP2PNetwork,
+    MessageSignature, NetworkMessage, P2PConfig, P2PError, P2PLimits, P2PTlsConfig, PeerAuthError,
+    PeerInfo as P2PPeerInfo, PeerStanding, RejectReason, ValidationOutcome,
 };
 
 use serde::{Deserialize, Serialize};
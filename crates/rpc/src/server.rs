@@ -18,27 +18,32 @@ use axum::async_trait;
 use axum::body::Body;
 use axum::error_handling::HandleErrorLayer;
 use axum::extract::rejection::JsonRejection;
-use axum::extract::FromRequest;
 use axum::extract::{ConnectInfo, Path as AxumPath, Query, State};
+use axum::extract::{DefaultBodyLimit, FromRequest};
 use axum::http::header::{HeaderValue, CONTENT_TYPE};
 use axum::http::Request;
 use axum::http::StatusCode;
+use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Response};
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::{Json, Router};
+use axum_server::tls_rustls::RustlsConfig;
 use ed25519_dalek::{Signer, SigningKey};
 #[cfg(test)]
 use http_body_util::BodyExt;
 #[cfg(test)]
 use ippan_consensus::DLCConfig;
-use ippan_consensus::{DLCConsensus, PoAConsensus, ValidatorMetrics as DlcValidatorMetrics};
+use ippan_consensus::{
+    DLCConsensus, PoAConsensus, ReputationExplanation, ValidatorMetrics as DlcValidatorMetrics,
+};
 use ippan_consensus_dlc::AiConsensusStatus;
-use ippan_files::{FileDhtService, FileStorage};
+use ippan_files::{ContentStore, FileDhtService, FileStorage};
 use ippan_l1_fees::FeePolicy;
 use ippan_l1_handle_anchors::L1HandleAnchorStorage;
 use ippan_l2_handle_registry::{
-    dht::HandleDhtService, Handle, HandleMetadata, HandleRegistryError, HandleStatus,
-    L2HandleRegistry,
+    dht::HandleDhtService, AuctionPhase, AuctionSettlement, Handle, HandleAuction,
+    HandleAuctionBook, HandleMetadata, HandleRecord, HandleRegistryError, HandleStatus,
+    L2HandleRegistry, PublicKey,
 };
 use ippan_mempool::Mempool;
 use ippan_security::{SecurityError, SecurityManager};
@@ -47,9 +52,9 @@ use ippan_types::address::{decode_address, encode_address};
 use ippan_types::health::{HealthStatus, NodeHealth, NodeHealthContext};
 use ippan_types::time_service::ippan_time_now;
 use ippan_types::{
-    Amount, Block, HandleOperation, HandleRegisterOp, HashTimer, IppanTimeMicros, L2Commit,
-    L2ExitRecord, L2Network, RoundFinalizationRecord, Transaction, TransactionVisibility,
-    TransactionWireV1,
+    Amount, Block, FileAnchorOp, HandleOperation, HandleRegisterOp, HashTimer, IppanTimeMicros,
+    L2Commit, L2ExitRecord, L2Network, ModelGovernanceOp, RoundFinalizationRecord, Transaction,
+    TransactionVisibility, TransactionWireV1,
 };
 use metrics_exporter_prometheus::PrometheusHandle;
 use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
@@ -77,9 +82,14 @@ use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
 use crate::{
-    files::{handle_get_file, handle_publish_file},
+    files::{
+        handle_get_file, handle_get_file_content, handle_publish_file,
+        handle_update_file_recipients, handle_upload_file_content,
+    },
     ipndht::{handle_ipndht_files, handle_ipndht_handles, handle_ipndht_summary},
-    HttpP2PNetwork, NetworkMessage,
+    validate_network_message, CompactBlockAck, CompactBlockOutcome, HttpP2PNetwork,
+    MessageSignature, NetworkMessage, P2PTlsConfig, PeerAuthError, PeerStanding, RejectReason,
+    ValidationOutcome,
 };
 
 const RATE_LIMIT_PER_SECOND: u64 = 200;
@@ -93,6 +103,10 @@ const SUBMIT_BATCH_ENDPOINT: &str = "/tx/submit_batch";
 const RPC_PROTOCOL_VERSION: &str = "v1";
 const HANDLE_REGISTER_ENDPOINT: &str = "/handle/register";
 const HANDLE_LOOKUP_ENDPOINT: &str = "/handle/:handle";
+const HANDLE_AUCTION_ENDPOINT: &str = "/handle/:handle/auction";
+const HANDLE_REVERSE_ENDPOINT: &str = "/handle/reverse/:address";
+const HANDLE_RECORDS_ENDPOINT: &str = "/handle/:handle/records";
+const FILE_CONTENT_ENDPOINT: &str = "/files/content/:hash";
 const MAX_BODY_BYTES: usize = 64 * 1024; // 64 KiB default when security manager not configured
 const REQUEST_TIMEOUT_SECS: u64 = 10;
 const MAX_CONCURRENT_REQUESTS: usize = 128;
@@ -110,6 +124,8 @@ const BATCH_CONCURRENCY_LIMIT_ENV: &str = "IPPAN_BATCH_CONCURRENCY_LIMIT";
 const BATCH_QUEUE_CAPACITY_ENV: &str = "IPPAN_BATCH_QUEUE_CAPACITY";
 const BATCH_BODY_LIMIT_BYTES_ENV: &str = "IPPAN_BATCH_BODY_LIMIT_BYTES";
 const BATCH_DECODE_WORKERS_ENV: &str = "IPPAN_BATCH_DECODE_WORKERS";
+const FILE_UPLOAD_BODY_LIMIT_BYTES_ENV: &str = "IPPAN_FILE_UPLOAD_BODY_LIMIT_BYTES";
+const DEFAULT_FILE_UPLOAD_BODY_BYTES: usize = 64 * 1024 * 1024; // 64 MiB per content upload
 const DEFAULT_BATCH_CONCURRENCY_LIMIT: usize = 64; // requests in-flight on the batch lane
 const DEFAULT_BATCH_QUEUE_CAPACITY: usize = 4096; // txs queued into admission workers
 const DEFAULT_BATCH_DECODE_WORKERS: usize = 0; // reserved; 0 = decode inline (current behavior)
@@ -156,12 +172,14 @@ pub struct AppState {
     pub metrics: Option<PrometheusHandle>,
     pub file_storage: Option<Arc<dyn FileStorage>>,
     pub file_dht: Option<Arc<dyn FileDhtService>>,
+    pub content_store: Option<Arc<ContentStore>>,
     pub dht_file_mode: String,
     pub dev_mode: bool,
     pub rpc_allowed_origins: Vec<String>,
     pub handle_registry: Arc<L2HandleRegistry>,
     pub handle_anchors: Arc<L1HandleAnchorStorage>,
     pub handle_dht: Option<Arc<dyn HandleDhtService>>,
+    pub handle_auctions: Arc<HandleAuctionBook>,
     pub dht_handle_mode: String,
     /// DLC consensus handle (if DLC mode is enabled)
     pub dlc_consensus: Option<Arc<parking_lot::RwLock<DLCConsensus>>>,
@@ -558,6 +576,19 @@ impl From<AiConsensusStatus> for AiStatus {
     }
 }
 
+#[derive(Debug, Serialize)]
+struct ValidatorExplanation {
+    validator_id: String,
+    round: u64,
+    #[serde(flatten)]
+    explanation: ReputationExplanation,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplainQuery {
+    round: Option<u64>,
+}
+
 /// Transaction lookup response payload used by JSON responses.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -644,6 +675,8 @@ struct BlockView {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     height: Option<u64>,
     creator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator_handle: Option<String>,
     hash_timer: String,
     timestamp: u64,
     parent_ids: Vec<String>,
@@ -721,6 +754,8 @@ impl TransactionView {
             tx_id: None,
             from: encode_address(&tx.from),
             to: encode_address(&tx.to),
+            from_handle: None,
+            to_handle: None,
             amount_atomic: format_atomic(tx.amount.atomic()),
             fee_atomic: format_fee(fee_required),
             nonce: tx.nonce,
@@ -734,12 +769,20 @@ impl TransactionView {
             visibility: tx.visibility,
             memo: tx.topics.first().cloned(),
             handle_operation: tx.handle_op.clone(),
+            file_anchor: tx.file_anchor().cloned(),
+            model_governance: tx.model_governance().cloned(),
         }
     }
+
+    fn with_handles(mut self, registry: &L2HandleRegistry, tx: &Transaction) -> Self {
+        self.from_handle = primary_handle_name(registry, &tx.from);
+        self.to_handle = primary_handle_name(registry, &tx.to);
+        self
+    }
 }
 
 impl BlockView {
-    fn from_block(block: &Block, height: Option<u64>) -> Self {
+    fn from_block(block: &Block, height: Option<u64>, registry: &L2HandleRegistry) -> Self {
         let transaction_hashes = block
             .transactions
             .iter()
@@ -748,7 +791,10 @@ impl BlockView {
         let transactions = block
             .transactions
             .iter()
-            .map(|tx| TransactionView::from_transaction(tx, TransactionStatus::Finalized))
+            .map(|tx| {
+                TransactionView::from_transaction(tx, TransactionStatus::Finalized)
+                    .with_handles(registry, tx)
+            })
             .collect::<Vec<_>>();
         let timestamp = block.header.hashtimer.timestamp_us.max(0) as u64;
         Self {
@@ -756,6 +802,7 @@ impl BlockView {
             round: block.header.round,
             height,
             creator: hex_encode(block.header.creator),
+            creator_handle: primary_handle_name(registry, &block.header.creator),
             hash_timer: block.header.hashtimer.to_hex(),
             timestamp,
             parent_ids: block.header.parent_ids.iter().map(hex_encode).collect(),
@@ -783,6 +830,12 @@ struct TransactionView {
     tx_id: Option<String>,
     from: String,
     to: String,
+    /// Primary handle of the sender, if one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from_handle: Option<String>,
+    /// Primary handle of the recipient, if one is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to_handle: Option<String>,
     amount_atomic: String,
     fee_atomic: String,
     nonce: u64,
@@ -806,6 +859,10 @@ struct TransactionView {
     memo: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     handle_operation: Option<HandleOperation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_anchor: Option<FileAnchorOp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model_governance: Option<ModelGovernanceOp>,
 }
 
 #[derive(Debug, Serialize)]
@@ -821,6 +878,8 @@ struct TxInclusionView {
 #[serde(rename_all = "snake_case")]
 struct AccountResponse {
     address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    primary_handle: Option<String>,
     balance_atomic: String,
     nonce: u64,
     recent_transactions: Vec<TransactionView>,
@@ -893,11 +952,6 @@ enum AdmissionResult {
     },
 }
 
-fn append_length_prefixed(out: &mut Vec<u8>, data: &[u8]) {
-    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
-    out.extend_from_slice(data);
-}
-
 fn tx_hashtimer_payload(tx: &Transaction) -> Vec<u8> {
     let mut payload = Vec::new();
     payload.extend_from_slice(&tx.from);
@@ -906,26 +960,7 @@ fn tx_hashtimer_payload(tx: &Transaction) -> Vec<u8> {
     payload.extend_from_slice(&tx.nonce.to_be_bytes());
     if let Some(op) = &tx.handle_op {
         payload.push(1);
-        match op {
-            HandleOperation::Register(reg) => {
-                // Must match `Transaction::append_handle_operation`.
-                payload.push(0);
-                append_length_prefixed(&mut payload, reg.handle.as_bytes());
-                payload.extend_from_slice(&reg.owner);
-                if let Some(exp) = reg.expires_at {
-                    payload.push(1);
-                    payload.extend_from_slice(&exp.to_be_bytes());
-                } else {
-                    payload.push(0);
-                }
-                payload.extend_from_slice(&(reg.metadata.len() as u32).to_be_bytes());
-                for (k, v) in &reg.metadata {
-                    append_length_prefixed(&mut payload, k.as_bytes());
-                    append_length_prefixed(&mut payload, v.as_bytes());
-                }
-                append_length_prefixed(&mut payload, &reg.signature);
-            }
-        }
+        op.append_canonical_bytes(&mut payload);
     } else {
         payload.push(0);
     }
@@ -934,7 +969,7 @@ fn tx_hashtimer_payload(tx: &Transaction) -> Vec<u8> {
 
 fn validate_tx_for_admission(tx: &Transaction) -> Result<(), (&'static str, String)> {
     // Keep this deterministic and aligned with Transaction::is_valid() / mempool checks.
-    let has_handle = tx.handle_op.is_some();
+    let has_operation = tx.carries_operation();
     if tx.visibility == ippan_types::TransactionVisibility::Confidential {
         if tx.confidential.is_none() || tx.zk_proof.is_none() {
             return Err((
@@ -942,7 +977,7 @@ fn validate_tx_for_admission(tx: &Transaction) -> Result<(), (&'static str, Stri
                 "confidential tx missing envelope/proof".to_string(),
             ));
         }
-    } else if !has_handle {
+    } else if !has_operation {
         if tx.amount.is_zero() {
             return Err(("amount_zero", "amount must be non-zero".to_string()));
         }
@@ -986,6 +1021,18 @@ fn validate_tx_for_admission(tx: &Transaction) -> Result<(), (&'static str, Stri
         }
     }
 
+    if tx.handle_op.is_some() && tx.file_anchor.is_some() {
+        return Err((
+            "multiple_operations",
+            "transaction carries both a handle operation and a file anchor".to_string(),
+        ));
+    }
+    if let Some(anchor) = &tx.file_anchor {
+        if let Err(err) = anchor.validate_for_sender(&tx.from) {
+            return Err(("file_anchor_invalid", err.to_string()));
+        }
+    }
+
     if tx.hashtimer.time().0 > IppanTimeMicros::now().0 {
         return Err(("hashtimer_future", "hashtimer from the future".to_string()));
     }
@@ -1089,6 +1136,10 @@ struct PaymentView {
     hash: String,
     from: String,
     to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from_handle: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to_handle: Option<String>,
     direction: PaymentDirection,
     amount_atomic: String,
     fee_atomic: String,
@@ -1149,6 +1200,9 @@ enum HandleSubmissionStatus {
 struct HandleInfoResponse {
     handle: String,
     owner: String,
+    /// Parent handle that delegated this subhandle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
     status: String,
     expires_at: Option<u64>,
     metadata: BTreeMap<String, String>,
@@ -1156,6 +1210,79 @@ struct HandleInfoResponse {
     updated_at: u64,
 }
 
+#[derive(Debug, Serialize)]
+struct HandleRecordsResponse {
+    handle: String,
+    owner: String,
+    records: Vec<HandleRecordView>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct HandleRecordView {
+    key: String,
+    #[serde(rename = "type")]
+    kind: String,
+    /// Payment addresses use the `i…` encoding, file pointers are hex descriptor ids.
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+impl HandleRecordView {
+    fn from_record(record: &HandleRecord) -> Self {
+        let (value, network, name) = match record {
+            HandleRecord::Payment { address } => (encode_address(address), None, None),
+            HandleRecord::L2Address { network, address } => {
+                (address.clone(), Some(network.clone()), None)
+            }
+            HandleRecord::File { descriptor } => (descriptor.to_hex(), None, None),
+            HandleRecord::Node { multiaddr } => (multiaddr.clone(), None, None),
+            HandleRecord::Txt { name, value } => (value.clone(), None, Some(name.clone())),
+        };
+        Self {
+            key: record.key(),
+            kind: record.kind().to_string(),
+            value,
+            network,
+            name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HandleReverseResponse {
+    address: String,
+    handle: String,
+    /// All handles currently registered to the address.
+    handles: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct HandleAuctionResponse {
+    handle: String,
+    /// `commit`, `reveal`, `closed` (awaiting settlement) or `settled`.
+    phase: String,
+    opened_round: Option<u64>,
+    commit_deadline: Option<u64>,
+    reveal_deadline: Option<u64>,
+    reserve_price: String,
+    escrowed: String,
+    bids: Vec<HandleAuctionBidView>,
+    winner: Option<String>,
+    winning_amount: Option<String>,
+    settled_round: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct HandleAuctionBidView {
+    bidder: String,
+    committed_round: u64,
+    deposit: String,
+    revealed_amount: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "lowercase")]
 enum PaymentDirection {
@@ -1352,6 +1479,10 @@ enum PaymentError {
 enum HandleRegistrationError {
     #[error("handle must include @ prefix and suffix (e.g. @user.ipn)")]
     InvalidHandleFormat,
+    #[error("premium handles can only be acquired through an auction")]
+    PremiumRequiresAuction,
+    #[error("subhandles are issued by the owner of their parent handle")]
+    SubhandleRequiresDelegation,
     #[error("invalid owner address: {0}")]
     InvalidOwner(String),
     #[error("missing signing key")]
@@ -1378,6 +1509,12 @@ impl HandleRegistrationError {
             HandleRegistrationError::InvalidHandleFormat => {
                 (StatusCode::BAD_REQUEST, "invalid_handle")
             }
+            HandleRegistrationError::PremiumRequiresAuction => {
+                (StatusCode::BAD_REQUEST, "premium_handle_auction")
+            }
+            HandleRegistrationError::SubhandleRequiresDelegation => {
+                (StatusCode::BAD_REQUEST, "subhandle_requires_delegation")
+            }
             HandleRegistrationError::InvalidOwner(_) => (StatusCode::BAD_REQUEST, "invalid_owner"),
             HandleRegistrationError::MissingSigningKey => {
                 (StatusCode::BAD_REQUEST, "missing_signing_key")
@@ -1668,6 +1805,12 @@ fn build_handle_registration_transaction(
     request: HandleRegisterRequest,
 ) -> Result<BuiltHandleRegistration, HandleRegistrationError> {
     let handle = normalize_handle_input(&request.handle)?;
+    if Handle::new(handle.clone()).is_premium() {
+        return Err(HandleRegistrationError::PremiumRequiresAuction);
+    }
+    if Handle::new(handle.clone()).is_subhandle() {
+        return Err(HandleRegistrationError::SubhandleRequiresDelegation);
+    }
     let owner_bytes = decode_address(&request.owner)
         .map_err(|err| HandleRegistrationError::InvalidOwner(err.to_string()))?;
     let signing_key = parse_handle_signing_key(&request.signing_key)?;
@@ -1760,6 +1903,7 @@ fn handle_info_from_metadata(handle: &Handle, metadata: HandleMetadata) -> Handl
     HandleInfoResponse {
         handle: handle.as_str().to_string(),
         owner,
+        parent: handle.parent().map(|parent| parent.as_str().to_string()),
         status: format_handle_status(&metadata.status).to_string(),
         expires_at: if metadata.expires_at == 0 {
             None
@@ -1772,6 +1916,80 @@ fn handle_info_from_metadata(handle: &Handle, metadata: HandleMetadata) -> Handl
     }
 }
 
+fn format_auction_phase(phase: AuctionPhase) -> &'static str {
+    match phase {
+        AuctionPhase::Commit => "commit",
+        AuctionPhase::Reveal => "reveal",
+        AuctionPhase::Closed => "closed",
+    }
+}
+
+fn handle_auction_from_running(
+    handle: &Handle,
+    auction: HandleAuction,
+    current_round: u64,
+) -> HandleAuctionResponse {
+    let bids = auction
+        .bids
+        .iter()
+        .map(|bid| HandleAuctionBidView {
+            bidder: encode_address(bid.bidder.as_bytes()),
+            committed_round: bid.committed_round,
+            deposit: format_atomic(bid.deposit),
+            revealed_amount: bid
+                .revealed
+                .as_ref()
+                .map(|revealed| format_atomic(revealed.amount)),
+        })
+        .collect();
+    HandleAuctionResponse {
+        handle: handle.as_str().to_string(),
+        phase: format_auction_phase(auction.phase(current_round)).to_string(),
+        opened_round: Some(auction.opened_round),
+        commit_deadline: Some(auction.commit_deadline),
+        reveal_deadline: Some(auction.reveal_deadline),
+        reserve_price: format_atomic(auction.reserve_price),
+        escrowed: format_atomic(auction.escrowed()),
+        bids,
+        winner: None,
+        winning_amount: None,
+        settled_round: None,
+    }
+}
+
+fn handle_auction_from_settlement(
+    handle: &Handle,
+    settlement: AuctionSettlement,
+    reserve_price: u128,
+) -> HandleAuctionResponse {
+    HandleAuctionResponse {
+        handle: handle.as_str().to_string(),
+        phase: "settled".to_string(),
+        opened_round: None,
+        commit_deadline: None,
+        reveal_deadline: None,
+        reserve_price: format_atomic(reserve_price),
+        escrowed: format_atomic(0),
+        bids: Vec::new(),
+        winner: settlement
+            .winner
+            .as_ref()
+            .map(|winner| encode_address(winner.bidder.as_bytes())),
+        winning_amount: settlement
+            .winner
+            .as_ref()
+            .map(|winner| format_atomic(winner.amount)),
+        settled_round: Some(settlement.round),
+    }
+}
+
+/// Reverse-resolve an address to its primary handle for display in responses.
+fn primary_handle_name(registry: &L2HandleRegistry, address: &[u8; 32]) -> Option<String> {
+    registry
+        .primary_handle(&PublicKey::new(*address))
+        .map(|handle| handle.as_str().to_string())
+}
+
 fn map_handle_lookup_error(err: HandleRegistryError) -> (StatusCode, &'static str, String) {
     match err {
         HandleRegistryError::HandleNotFound { .. } => {
@@ -1780,6 +1998,11 @@ fn map_handle_lookup_error(err: HandleRegistryError) -> (StatusCode, &'static st
         HandleRegistryError::HandleExpired { .. } => {
             (StatusCode::NOT_FOUND, "handle_expired", err.to_string())
         }
+        HandleRegistryError::ParentUnavailable { .. } => (
+            StatusCode::NOT_FOUND,
+            "handle_parent_unavailable",
+            err.to_string(),
+        ),
         _ => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "handle_registry_error",
@@ -1815,6 +2038,8 @@ impl PaymentView {
             hash,
             from,
             to,
+            from_handle: None,
+            to_handle: None,
             direction,
             amount_atomic: format_atomic(tx.amount.atomic()),
             fee_atomic,
@@ -1826,6 +2051,12 @@ impl PaymentView {
             status,
         }
     }
+
+    fn with_handles(mut self, registry: &L2HandleRegistry, tx: &Transaction) -> Self {
+        self.from_handle = primary_handle_name(registry, &tx.from);
+        self.to_handle = primary_handle_name(registry, &tx.to);
+        self
+    }
 }
 
 impl PaymentDirection {
@@ -1892,11 +2123,57 @@ async fn bind_listener(addr: &str) -> Result<tokio::net::TcpListener> {
         .with_context(|| format!("failed to bind RPC listener on {socket_addr}"))
 }
 
+/// Server-side mutual TLS: present the node certificate and require a client
+/// certificate issued by the configured CA.
+fn p2p_tls_server_config(tls: &P2PTlsConfig) -> Result<rustls::ServerConfig> {
+    let certs = rustls_pemfile::certs(&mut tls.read_cert()?.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .context("parse P2P TLS certificate")?;
+    let key = rustls_pemfile::private_key(&mut tls.read_key()?.as_slice())
+        .context("parse P2P TLS key")?
+        .ok_or_else(|| anyhow!("no private key in {}", tls.key_path.display()))?;
+    let mut roots = rustls::RootCertStore::empty();
+    for ca in rustls_pemfile::certs(&mut tls.read_ca()?.as_slice()) {
+        roots
+            .add(ca.context("parse P2P TLS CA bundle")?)
+            .context("add P2P TLS CA certificate")?;
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+        Arc::new(roots),
+        provider.clone(),
+    )
+    .build()
+    .context("build P2P client certificate verifier")?;
+    let mut config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .context("configure P2P TLS protocol versions")?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)
+        .context("load P2P TLS certificate")?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
 /// Start the P2P HTTP server on a dedicated port
 pub async fn start_p2p_server(state: AppState, addr: &str) -> Result<()> {
     info!("Starting P2P HTTP server on {}", addr);
     let shared = Arc::new(state);
     let app = build_p2p_router(shared.clone());
+    let tls = shared
+        .p2p_network
+        .as_ref()
+        .and_then(|net| net.tls_config().cloned());
+    if let Some(tls) = tls {
+        let socket_addr: SocketAddr = addr.parse()?;
+        let tls_config = RustlsConfig::from_config(Arc::new(p2p_tls_server_config(&tls)?));
+        info!("P2P HTTPS server (mutual TLS) listening on {}", socket_addr);
+        return axum_server::bind_rustls(socket_addr, tls_config)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .context("P2P HTTPS server terminated unexpectedly");
+    }
     let listener = bind_listener(addr).await?;
     let bound_addr = listener.local_addr()?;
     info!("P2P HTTP server listening on {}", bound_addr);
@@ -1961,6 +2238,159 @@ async fn handle_test_gossip_publish(
     }
 }
 
+/// Peer-to-peer message endpoints. Every request must carry a valid
+/// signature envelope from the sending node (see [`authenticate_p2p_message`]).
+fn p2p_message_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/p2p/blocks", post(handle_p2p_blocks))
+        .route("/p2p/transactions", post(handle_p2p_transactions))
+        .route("/p2p/peer-info", post(handle_p2p_peer_info))
+        .route("/p2p/peer-discovery", post(handle_p2p_peer_discovery))
+        .route("/p2p/block-request", post(handle_p2p_block_request))
+        .route("/p2p/block-response", post(handle_p2p_block_response))
+        .route("/p2p/compact-blocks", post(handle_p2p_compact_blocks))
+        .route(
+            "/p2p/block-transactions",
+            post(handle_p2p_block_transactions),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            authenticate_p2p_message,
+        ))
+}
+
+/// Verify the signature envelope of an inbound P2P message, refuse graylisted
+/// and banned peers, and report the validity of the carried block or
+/// transaction to the shared peer scores. A `PeerInfo` announcement must name
+/// the peer ID of its signing key.
+async fn authenticate_p2p_message(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let endpoint = request.uri().path().to_string();
+    let Some(net) = &state.p2p_network else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new(
+                "p2p_unavailable",
+                "P2P network is not running",
+            )),
+        )
+            .into_response();
+    };
+
+    let signature = MessageSignature::from_headers(|name| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    });
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, configured_body_limit(&state)).await {
+        Ok(body) => body,
+        Err(_) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(ApiError::new("payload_too_large", "Request body too large")),
+            )
+                .into_response();
+        }
+    };
+    let message = serde_json::from_slice::<NetworkMessage>(&body);
+
+    let verified = signature.and_then(|signature| {
+        net.authenticator()
+            .verify(signature.as_ref(), &endpoint, &body, ippan_time_now())
+    });
+    let verified = verified.and_then(|peer_id| match (&peer_id, &message) {
+        (
+            Some(signer),
+            Ok(NetworkMessage::PeerInfo {
+                peer_id: claimed, ..
+            }),
+        ) if claimed != signer => Err(PeerAuthError::PeerIdMismatch {
+            claimed: claimed.clone(),
+            derived: signer.clone(),
+        }),
+        _ => Ok(peer_id),
+    });
+
+    let remote = addr.ip().to_string();
+    let peer_id = match verified {
+        Ok(peer_id) => peer_id,
+        Err(err) => {
+            warn!(
+                "Rejected P2P message on {} from {}: {}",
+                endpoint, addr, err
+            );
+            // A forged signature cannot be pinned on the peer it names, so the
+            // remote address takes the penalty; a genuine signer announcing
+            // someone else's peer ID is penalised itself.
+            let offender = match &err {
+                PeerAuthError::PeerIdMismatch { derived, .. } => derived.as_str(),
+                _ => remote.as_str(),
+            };
+            net.report_peer(offender, ValidationOutcome::from(&err));
+            record_security_failure(&state, &addr, &endpoint, &err.to_string()).await;
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiError::new("unauthenticated_peer", err.to_string())),
+            )
+                .into_response();
+        }
+    };
+
+    let peer = peer_id.unwrap_or_else(|| remote.clone());
+    let scoring = net.peer_scoring();
+    let standings = [scoring.standing(&remote), scoring.standing(&peer)];
+    let refuse = |code: &'static str| {
+        debug!(
+            "Refused P2P message on {} from {} ({})",
+            endpoint, peer, code
+        );
+        (
+            StatusCode::FORBIDDEN,
+            Json(ApiError::new(code, format!("Peer {peer} is not accepted"))),
+        )
+            .into_response()
+    };
+    if standings
+        .iter()
+        .any(|standing| matches!(standing, PeerStanding::Banned { .. }))
+    {
+        return refuse("peer_banned");
+    }
+
+    // Graylisted peers are still validated so that continued abuse ends in a
+    // ban, but their valid messages are not processed.
+    let outcome = match &message {
+        Ok(message) => validate_network_message(message),
+        Err(_) => ValidationOutcome::Reject(RejectReason::Malformed),
+    };
+    if let ValidationOutcome::Reject(reason) = outcome {
+        net.report_peer(&peer, outcome);
+        warn!(
+            "Rejected invalid P2P message on {} from {}: {:?}",
+            endpoint, peer, reason
+        );
+        let reason = format!("Invalid message: {reason:?}");
+        record_security_failure(&state, &addr, &endpoint, &reason).await;
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_message", reason)),
+        )
+            .into_response();
+    }
+    if standings.contains(&PeerStanding::Graylisted) {
+        return refuse("peer_graylisted");
+    }
+    net.report_peer(&peer, outcome);
+
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
 /// Build P2P-only router with minimal middleware
 fn build_p2p_router(state: Arc<AppState>) -> Router {
     // P2P server uses lighter middleware - allow all origins for peer communication
@@ -1975,12 +2405,7 @@ fn build_p2p_router(state: Arc<AppState>) -> Router {
     #[allow(unused_mut)]
     let mut router = Router::new()
         .route("/p2p/peers", get(handle_get_p2p_peers))
-        .route("/p2p/blocks", post(handle_p2p_blocks))
-        .route("/p2p/transactions", post(handle_p2p_transactions))
-        .route("/p2p/peer-info", post(handle_p2p_peer_info))
-        .route("/p2p/peer-discovery", post(handle_p2p_peer_discovery))
-        .route("/p2p/block-request", post(handle_p2p_block_request))
-        .route("/p2p/block-response", post(handle_p2p_block_response));
+        .merge(p2p_message_routes(&state));
 
     #[cfg(feature = "p2p-testkit")]
     {
@@ -2004,6 +2429,7 @@ fn build_p2p_router(state: Arc<AppState>) -> Router {
 fn build_router(state: Arc<AppState>) -> Router {
     let max_body_bytes = configured_body_limit(&state);
     let batch_body_bytes = configured_batch_body_limit();
+    let file_upload_body_bytes = configured_file_upload_body_limit();
     let request_timeout = configured_request_timeout(&state);
     let global_rps = configured_global_rps(&state);
     let cors = build_cors_layer(&state);
@@ -2037,7 +2463,11 @@ fn build_router(state: Arc<AppState>) -> Router {
         .route("/tx/:hash", get(handle_get_transaction))
         .route(HANDLE_REGISTER_ENDPOINT, post(handle_register_handle))
         .route(HANDLE_LOOKUP_ENDPOINT, get(handle_get_handle))
+        .route(HANDLE_AUCTION_ENDPOINT, get(handle_get_handle_auction))
+        .route(HANDLE_REVERSE_ENDPOINT, get(handle_get_primary_handle))
+        .route(HANDLE_RECORDS_ENDPOINT, get(handle_get_handle_records))
         .route("/files/publish", post(handle_publish_file))
+        .route("/files/:id/recipients", put(handle_update_file_recipients))
         .layer(tx_stack);
 
     if state.dev_mode {
@@ -2049,6 +2479,20 @@ fn build_router(state: Arc<AppState>) -> Router {
         tx_routes = tx_routes.route("/p2p/test/gossip", post(handle_test_gossip_publish));
     }
 
+    // Content lane: raw file uploads need a far larger body budget than JSON RPC.
+    let content_stack = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(handle_service_error))
+        .layer(ConcurrencyLimitLayer::new(MAX_CONCURRENT_REQUESTS))
+        .layer(TimeoutLayer::new(request_timeout))
+        .layer(RequestBodyLimitLayer::new(file_upload_body_bytes));
+    let content_routes = Router::new()
+        .route(
+            FILE_CONTENT_ENDPOINT,
+            put(handle_upload_file_content).get(handle_get_file_content),
+        )
+        .layer(DefaultBodyLimit::max(file_upload_body_bytes))
+        .layer(content_stack);
+
     // Read lane: safe to circuit-break. Importantly, keep this separate so breaker never starves /health
     // and never blocks batch ingest.
     let read_stack = ServiceBuilder::new()
@@ -2063,6 +2507,7 @@ fn build_router(state: Arc<AppState>) -> Router {
         .route("/version", get(handle_version))
         .route("/metrics", get(handle_metrics))
         .route("/ai/status", get(handle_get_ai_status))
+        .route("/ai/explain/:validator_id", get(handle_get_ai_explain))
         .route("/files/:id", get(handle_get_file))
         .route("/blocks", get(handle_get_blocks))
         .route("/tx/recent", get(handle_get_tx_recent))
@@ -2076,12 +2521,7 @@ fn build_router(state: Arc<AppState>) -> Router {
         )
         .route("/peers", get(handle_get_peers))
         .route("/p2p/peers", get(handle_get_p2p_peers))
-        .route("/p2p/blocks", post(handle_p2p_blocks))
-        .route("/p2p/transactions", post(handle_p2p_transactions))
-        .route("/p2p/peer-info", post(handle_p2p_peer_info))
-        .route("/p2p/peer-discovery", post(handle_p2p_peer_discovery))
-        .route("/p2p/block-request", post(handle_p2p_block_request))
-        .route("/p2p/block-response", post(handle_p2p_block_response))
+        .merge(p2p_message_routes(&state))
         .route("/l2/config", get(handle_get_l2_config))
         .route("/l2/networks", get(handle_list_l2_networks))
         .route("/l2/commits", get(handle_list_l2_commits))
@@ -2106,6 +2546,7 @@ fn build_router(state: Arc<AppState>) -> Router {
     // - `/tx/submit_batch` (has its own dedicated 429-only overload gate)
     let limited_routes = Router::new()
         .merge(tx_routes)
+        .merge(content_routes)
         .merge(read_routes)
         .layer(rate_limiter);
 
@@ -2154,6 +2595,14 @@ fn configured_batch_body_limit() -> usize {
         .unwrap_or(DEFAULT_BATCH_MAX_BODY_BYTES)
 }
 
+fn configured_file_upload_body_limit() -> usize {
+    std::env::var(FILE_UPLOAD_BODY_LIMIT_BYTES_ENV)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_FILE_UPLOAD_BODY_BYTES)
+}
+
 fn configured_batch_max_txs() -> usize {
     std::env::var(BATCH_MAX_TX_PER_BATCH_ENV)
         .ok()
@@ -2339,12 +2788,12 @@ fn tx_basic_sanity(tx: &Transaction) -> bool {
     if !tx.verify() {
         return false;
     }
-    let has_handle = tx.handle_op.is_some();
+    let has_operation = tx.carries_operation();
     if tx.visibility == TransactionVisibility::Confidential {
         if tx.confidential.is_none() || tx.zk_proof.is_none() {
             return false;
         }
-    } else if !has_handle {
+    } else if !has_operation {
         if tx.amount.is_zero() {
             return false;
         }
@@ -2940,6 +3389,42 @@ async fn handle_get_ai_status(
     Ok(Json(response))
 }
 
+async fn handle_get_ai_explain(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(validator_id): AxumPath<String>,
+    Query(query): Query<ExplainQuery>,
+) -> Result<Json<ValidatorExplanation>, (StatusCode, &'static str)> {
+    const ENDPOINT: &str = "/ai/explain/:validator_id";
+    if let Err(err) = guard_request(&state, &addr, ENDPOINT).await {
+        return Err(deny_request(&state, &addr, ENDPOINT, err).await);
+    }
+
+    let id = match parse_hex_32(&validator_id) {
+        Ok(id) => id,
+        Err(_) => {
+            record_security_failure(&state, &addr, ENDPOINT, "Invalid validator id").await;
+            return Err((StatusCode::BAD_REQUEST, "Invalid validator id"));
+        }
+    };
+
+    let Some(dlc) = &state.dlc_consensus else {
+        record_security_success(&state, &addr, ENDPOINT).await;
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "DLC consensus not active"));
+    };
+
+    let explained = dlc.read().explain_validator(&id, query.round);
+    record_security_success(&state, &addr, ENDPOINT).await;
+    match explained {
+        Some((round, explanation)) => Ok(Json(ValidatorExplanation {
+            validator_id: hex::encode(id),
+            round,
+            explanation,
+        })),
+        None => Err((StatusCode::NOT_FOUND, "No score recorded for validator")),
+    }
+}
+
 async fn handle_submit_tx(
     State(state): State<Arc<AppState>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -3480,6 +3965,141 @@ async fn handle_get_handle(
     }
 }
 
+async fn handle_get_handle_records(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(raw_handle): AxumPath<String>,
+) -> Result<Json<HandleRecordsResponse>, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_request(&state, &addr, HANDLE_RECORDS_ENDPOINT).await {
+        let (status, message) = deny_request(&state, &addr, HANDLE_RECORDS_ENDPOINT, err).await;
+        return Err((status, Json(ApiError::new("security_error", message))));
+    }
+
+    let handle = match normalize_handle_query(&raw_handle) {
+        Ok(handle) => handle,
+        Err(err) => {
+            return Err(handle_error_response(&state, &addr, HANDLE_RECORDS_ENDPOINT, err).await)
+        }
+    };
+
+    match state.handle_registry.get_metadata(&handle) {
+        Ok(metadata) => {
+            let response = HandleRecordsResponse {
+                handle: handle.as_str().to_string(),
+                owner: encode_address(metadata.owner.as_bytes()),
+                records: metadata
+                    .records
+                    .values()
+                    .map(HandleRecordView::from_record)
+                    .collect(),
+            };
+            record_security_success(&state, &addr, HANDLE_RECORDS_ENDPOINT).await;
+            Ok(Json(response))
+        }
+        Err(err) => {
+            let (status, code, message) = map_handle_lookup_error(err);
+            if status.is_server_error() {
+                record_security_failure(&state, &addr, HANDLE_RECORDS_ENDPOINT, &message).await;
+            } else {
+                record_security_success(&state, &addr, HANDLE_RECORDS_ENDPOINT).await;
+            }
+            Err((status, Json(ApiError::new(code, message))))
+        }
+    }
+}
+
+async fn handle_get_primary_handle(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(address): AxumPath<String>,
+) -> Result<Json<HandleReverseResponse>, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_request(&state, &addr, HANDLE_REVERSE_ENDPOINT).await {
+        let (status, message) = deny_request(&state, &addr, HANDLE_REVERSE_ENDPOINT, err).await;
+        return Err((status, Json(ApiError::new("security_error", message))));
+    }
+
+    let owner = match decode_any_address(address.trim()) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            record_security_failure(&state, &addr, HANDLE_REVERSE_ENDPOINT, &err).await;
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_address", err)),
+            ));
+        }
+    };
+
+    let key = PublicKey::new(owner);
+    record_security_success(&state, &addr, HANDLE_REVERSE_ENDPOINT).await;
+    match state.handle_registry.primary_handle(&key) {
+        Some(handle) => Ok(Json(HandleReverseResponse {
+            address: encode_address(&owner),
+            handle: handle.as_str().to_string(),
+            handles: state
+                .handle_registry
+                .list_owner_handles(&key)
+                .iter()
+                .map(|handle| handle.as_str().to_string())
+                .collect(),
+        })),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new(
+                "primary_handle_not_found",
+                format!("no primary handle for {}", encode_address(&owner)),
+            )),
+        )),
+    }
+}
+
+async fn handle_get_handle_auction(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    AxumPath(raw_handle): AxumPath<String>,
+) -> Result<Json<HandleAuctionResponse>, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_request(&state, &addr, HANDLE_AUCTION_ENDPOINT).await {
+        let (status, message) = deny_request(&state, &addr, HANDLE_AUCTION_ENDPOINT, err).await;
+        return Err((status, Json(ApiError::new("security_error", message))));
+    }
+
+    let handle = match normalize_handle_query(&raw_handle) {
+        Ok(handle) => handle,
+        Err(err) => {
+            return Err(handle_error_response(&state, &addr, HANDLE_AUCTION_ENDPOINT, err).await)
+        }
+    };
+
+    let current_round = state
+        .storage
+        .get_latest_round_finalization()
+        .ok()
+        .flatten()
+        .map(|record| record.round.saturating_add(1))
+        .unwrap_or(0);
+
+    let response = if let Some(auction) = state.handle_auctions.auction(&handle) {
+        handle_auction_from_running(&handle, auction, current_round)
+    } else if let Some(settlement) = state.handle_auctions.settlement(&handle) {
+        handle_auction_from_settlement(
+            &handle,
+            settlement,
+            state.handle_auctions.config().reserve_price,
+        )
+    } else {
+        record_security_success(&state, &addr, HANDLE_AUCTION_ENDPOINT).await;
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new(
+                "auction_not_found",
+                format!("no auction for handle {}", handle.as_str()),
+            )),
+        ));
+    };
+
+    record_security_success(&state, &addr, HANDLE_AUCTION_ENDPOINT).await;
+    Ok(Json(response))
+}
+
 async fn handle_payment_tx(
     State(state): State<Arc<AppState>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -3716,7 +4336,8 @@ async fn handle_get_transaction(
     match state.storage.get_transaction(&hash_bytes) {
         Ok(Some(tx)) => {
             let meta = state.storage.get_tx_meta(&hash_bytes).ok().flatten();
-            let mut envelope = TransactionView::from_transaction(&tx, TransactionStatus::Finalized);
+            let mut envelope = TransactionView::from_transaction(&tx, TransactionStatus::Finalized)
+                .with_handles(&state.handle_registry, &tx);
             envelope.tx_id = Some(envelope.hash.clone());
             if let Some(meta) = meta {
                 envelope.status_v2 = Some(lifecycle_status_label(meta.status).to_string());
@@ -3752,7 +4373,8 @@ async fn handle_get_transaction(
         Ok(Some(tx)) => {
             let meta = state.storage.get_tx_meta(&hash_bytes).ok().flatten();
             let mut envelope =
-                TransactionView::from_transaction(&tx, TransactionStatus::AcceptedToMempool);
+                TransactionView::from_transaction(&tx, TransactionStatus::AcceptedToMempool)
+                    .with_handles(&state.handle_registry, &tx);
             envelope.tx_id = Some(envelope.hash.clone());
             envelope.status_v2 = Some("Mempool".to_string());
             if let Some(meta) = meta {
@@ -4050,7 +4672,12 @@ async fn handle_get_block(
                 debug!("Failed to opportunistically index block: {}", e);
             }
 
-            let response = block_response_with_fee_summary(&state.storage, block, height_hint);
+            let response = block_response_with_fee_summary(
+                &state.storage,
+                &state.handle_registry,
+                block,
+                height_hint,
+            );
             Ok(Json(response))
         }
         Ok(None) => {
@@ -4288,7 +4915,7 @@ async fn handle_get_account(
     match state.storage.get_account(&address_bytes) {
         Ok(Some(account)) => match state.storage.get_transactions_by_address(&address_bytes) {
             Ok(transactions) => {
-                let response = account_to_response(account, transactions);
+                let response = account_to_response(account, transactions, &state.handle_registry);
                 record_security_success(&state, &addr, "/account/:address").await;
                 Ok(Json(response))
             }
@@ -4354,6 +4981,7 @@ async fn handle_get_account_payments(
                         Some(&address_bytes),
                         PaymentStatus::Finalized,
                     )
+                    .with_handles(&state.handle_registry, tx)
                 })
                 .collect();
             record_security_success(&state, &addr, ENDPOINT).await;
@@ -4456,6 +5084,117 @@ async fn handle_p2p_blocks(
     }
 }
 
+async fn handle_p2p_compact_blocks(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ValidatedJson(message): ValidatedJson<NetworkMessage>,
+) -> Result<Json<CompactBlockAck>, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_request(&state, &addr, "/p2p/compact-blocks").await {
+        let (status, message) = deny_request(&state, &addr, "/p2p/compact-blocks", err).await;
+        return Err((status, Json(ApiError::new("security_error", message))));
+    }
+
+    let from = resolve_peer_address(&state, &addr, &message);
+    match message {
+        NetworkMessage::CompactBlock(compact) => {
+            let Some(net) = &state.p2p_network else {
+                record_security_success(&state, &addr, "/p2p/compact-blocks").await;
+                return Ok(Json(CompactBlockAck::SendFull));
+            };
+            let outcome = net
+                .compact_relay()
+                .receive(&compact, state.mempool.transactions());
+            finish_compact_block(&state, &addr, &from, "/p2p/compact-blocks", outcome).await
+        }
+        other => {
+            warn!(
+                "Unexpected payload on /p2p/compact-blocks from {}: {:?}",
+                from, other
+            );
+            let reason = format!("Unexpected payload: {other:?}");
+            record_security_failure(&state, &addr, "/p2p/compact-blocks", &reason).await;
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "invalid_message",
+                    "Expected compact block message",
+                )),
+            ))
+        }
+    }
+}
+
+async fn handle_p2p_block_transactions(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ValidatedJson(message): ValidatedJson<NetworkMessage>,
+) -> Result<Json<CompactBlockAck>, (StatusCode, Json<ApiError>)> {
+    if let Err(err) = guard_request(&state, &addr, "/p2p/block-transactions").await {
+        let (status, message) = deny_request(&state, &addr, "/p2p/block-transactions", err).await;
+        return Err((status, Json(ApiError::new("security_error", message))));
+    }
+
+    let from = resolve_peer_address(&state, &addr, &message);
+    match message {
+        NetworkMessage::BlockTransactions {
+            block_id,
+            transactions,
+        } => {
+            let Some(net) = &state.p2p_network else {
+                record_security_success(&state, &addr, "/p2p/block-transactions").await;
+                return Ok(Json(CompactBlockAck::SendFull));
+            };
+            let outcome = net
+                .compact_relay()
+                .receive_transactions(&block_id, transactions);
+            finish_compact_block(&state, &addr, &from, "/p2p/block-transactions", outcome).await
+        }
+        other => {
+            warn!(
+                "Unexpected payload on /p2p/block-transactions from {}: {:?}",
+                from, other
+            );
+            let reason = format!("Unexpected payload: {other:?}");
+            record_security_failure(&state, &addr, "/p2p/block-transactions", &reason).await;
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "invalid_message",
+                    "Expected block transactions message",
+                )),
+            ))
+        }
+    }
+}
+
+/// Import a block rebuilt from a compact block and acknowledge the sender.
+async fn finish_compact_block(
+    state: &Arc<AppState>,
+    addr: &SocketAddr,
+    from: &str,
+    endpoint: &str,
+    outcome: CompactBlockOutcome,
+) -> Result<Json<CompactBlockAck>, (StatusCode, Json<ApiError>)> {
+    let ack = outcome.ack();
+    if let CompactBlockOutcome::Complete(block) = outcome {
+        if let Err(err) = ingest_block_from_peer(state, &block) {
+            error!("Failed to persist compact block from {}: {}", from, err);
+            record_security_failure(state, addr, endpoint, &err.to_string()).await;
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "block_persist_failed",
+                    "Failed to persist block",
+                )),
+            ));
+        }
+        forward_to_network(state, from, NetworkMessage::Block(*block)).await;
+    }
+
+    record_security_success(state, addr, endpoint).await;
+    Ok(Json(ack))
+}
+
 async fn handle_p2p_block_response(
     State(state): State<Arc<AppState>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
@@ -4911,6 +5650,13 @@ fn resolve_address_or_handle(
         let handle = Handle::new(normalized.clone());
         match state.handle_registry.get_metadata(&handle) {
             Ok(metadata) => {
+                // Payments follow the handle's payment record when one is set.
+                if field == "to" {
+                    if let Some(HandleRecord::Payment { address }) = metadata.records.get("payment")
+                    {
+                        return Ok(*address);
+                    }
+                }
                 let mut owner = [0u8; 32];
                 owner.copy_from_slice(metadata.owner.as_bytes());
                 Ok(owner)
@@ -4941,15 +5687,23 @@ fn parse_block_identifier(input: &str) -> Option<BlockIdentifier> {
     parse_hex_32(trimmed).ok().map(BlockIdentifier::Hash)
 }
 
-fn account_to_response(account: Account, transactions: Vec<Transaction>) -> AccountResponse {
-    let payments = build_payment_views(&transactions, &account.address);
+fn account_to_response(
+    account: Account,
+    transactions: Vec<Transaction>,
+    registry: &L2HandleRegistry,
+) -> AccountResponse {
+    let payments = build_payment_views(&transactions, &account.address, registry);
     let recent_transactions = transactions
         .iter()
-        .map(|tx| TransactionView::from_transaction(tx, TransactionStatus::Finalized))
+        .map(|tx| {
+            TransactionView::from_transaction(tx, TransactionStatus::Finalized)
+                .with_handles(registry, tx)
+        })
         .collect();
 
     AccountResponse {
         address: hex_encode(account.address),
+        primary_handle: primary_handle_name(registry, &account.address),
         balance_atomic: format_atomic(account.balance as u128),
         nonce: account.nonce,
         recent_transactions,
@@ -4959,6 +5713,7 @@ fn account_to_response(account: Account, transactions: Vec<Transaction>) -> Acco
 
 fn block_response_with_fee_summary(
     storage: &Arc<dyn Storage + Send + Sync>,
+    registry: &L2HandleRegistry,
     block: Block,
     height_hint: Option<u64>,
 ) -> BlockResponse {
@@ -5010,7 +5765,7 @@ fn block_response_with_fee_summary(
         },
         canonical_encoding_version: 1,
     };
-    let block_view = BlockView::from_block(&block, height_hint);
+    let block_view = BlockView::from_block(&block, height_hint, registry);
     BlockResponse {
         block: block_view,
         fee_summary,
@@ -5019,10 +5774,17 @@ fn block_response_with_fee_summary(
     }
 }
 
-fn build_payment_views(transactions: &[Transaction], perspective: &[u8; 32]) -> Vec<PaymentView> {
+fn build_payment_views(
+    transactions: &[Transaction],
+    perspective: &[u8; 32],
+    registry: &L2HandleRegistry,
+) -> Vec<PaymentView> {
     let mut views: Vec<_> = transactions
         .iter()
-        .map(|tx| PaymentView::from_transaction(tx, Some(perspective), PaymentStatus::Finalized))
+        .map(|tx| {
+            PaymentView::from_transaction(tx, Some(perspective), PaymentStatus::Finalized)
+                .with_handles(registry, tx)
+        })
         .collect();
     views.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
     views
@@ -5291,7 +6053,8 @@ mod tests {
     use ippan_consensus_dlc::{AiConsensusStatus, DlcConfig as AiDlcConfig, DlcConsensus};
     use ippan_files::{dht::StubFileDhtService, FileDhtService, FileStorage, MemoryFileStorage};
     use ippan_l2_handle_registry::{
-        HandleRegistration, HandleRegistryError, PublicKey, StubHandleDhtService,
+        compute_bid_commitment, record_update_message, HandleRecordUpdate, HandleRegistration,
+        HandleRegistryError, PublicKey, StubHandleDhtService,
     };
     use ippan_p2p::NetworkEvent;
     use ippan_security::{RateLimitConfig, SecurityConfig, SecurityManager};
@@ -5431,12 +6194,14 @@ mod tests {
             metrics: None,
             file_storage: Some(file_storage),
             file_dht: Some(file_dht),
+            content_store: None,
             dht_file_mode: "stub".into(),
             dev_mode: true,
             rpc_allowed_origins: vec!["http://localhost:3000".into()],
             handle_registry,
             handle_anchors,
             handle_dht: Some(handle_dht),
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             dht_handle_mode: "stub".into(),
             dlc_consensus: None,
             ipn_dht: None,
@@ -5540,6 +6305,54 @@ mod tests {
         assert!(status.enabled);
     }
 
+    #[tokio::test]
+    async fn test_handle_get_ai_explain_from_dlc_consensus() {
+        let addr: SocketAddr = "127.0.0.1:6004".parse().unwrap();
+        let validator = [4u8; 32];
+
+        let missing = handle_get_ai_explain(
+            State(make_app_state()),
+            ConnectInfo(addr),
+            AxumPath(hex::encode(validator)),
+            Query(ExplainQuery { round: None }),
+        )
+        .await
+        .expect_err("dlc disabled");
+        assert_eq!(missing.0, StatusCode::SERVICE_UNAVAILABLE);
+
+        let dlc = DLCConsensus::new(DLCConfig::default(), [0u8; 32]);
+        dlc.update_validator_metrics(validator, DlcValidatorMetrics::default());
+        let expected = dlc
+            .dgbdt_engine
+            .read()
+            .calculate_reputation(&DlcValidatorMetrics::default());
+        let mut state = (*make_app_state()).clone();
+        state.dlc_consensus = Some(Arc::new(parking_lot::RwLock::new(dlc)));
+        let state = Arc::new(state);
+
+        let Json(response) = handle_get_ai_explain(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(hex::encode(validator)),
+            Query(ExplainQuery { round: None }),
+        )
+        .await
+        .expect("explanation");
+        assert_eq!(response.round, 1);
+        assert_eq!(response.explanation.score, expected);
+        assert_eq!(response.explanation.raw_score(), expected as i64);
+
+        let unknown_round = handle_get_ai_explain(
+            State(state),
+            ConnectInfo(addr),
+            AxumPath(hex::encode(validator)),
+            Query(ExplainQuery { round: Some(99) }),
+        )
+        .await
+        .expect_err("round not recorded");
+        assert_eq!(unknown_round.0, StatusCode::NOT_FOUND);
+    }
+
     fn build_app_state(
         security: Option<Arc<SecurityManager>>,
         unified_ui_dist: Option<PathBuf>,
@@ -5574,12 +6387,14 @@ mod tests {
             metrics: None,
             file_storage: Some(file_storage),
             file_dht: Some(file_dht),
+            content_store: None,
             dht_file_mode: "stub".into(),
             dev_mode: true,
             rpc_allowed_origins: vec!["http://localhost:3000".into()],
             handle_registry,
             handle_anchors,
             handle_dht: Some(handle_dht),
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             dht_handle_mode: "stub".into(),
             dlc_consensus: None,
             ipn_dht: None,
@@ -6037,7 +6852,7 @@ mod tests {
             nonce: 2,
         };
         let tx = sample_transaction([1u8; 32], sample_public_key([2u8; 32]), 3);
-        let response = account_to_response(account, vec![tx.clone()]);
+        let response = account_to_response(account, vec![tx.clone()], &L2HandleRegistry::new());
         assert_eq!(response.address, hex::encode(sample_public_key([1u8; 32])));
         assert_eq!(response.balance_atomic, "1000");
         assert_eq!(response.recent_transactions.len(), 1);
@@ -6797,6 +7612,7 @@ mod tests {
                 assert_eq!(op.handle, "@alice.ipn");
                 assert_eq!(op.owner, owner);
             }
+            other => panic!("unexpected handle operation: {other:?}"),
         }
     }
 
@@ -6981,6 +7797,205 @@ mod tests {
         assert_eq!(missing.0, StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_handle_auction_endpoint_reports_running_auction() {
+        let state = make_app_state();
+        let addr: SocketAddr = "127.0.0.1:9454".parse().unwrap();
+        let signer = sample_private_key([86u8; 32]);
+        let bidder = signer.verifying_key().to_bytes();
+        let handle = Handle::new("@auction.cyborg");
+        let commitment = compute_bid_commitment(handle.as_str(), &bidder, 150_000, &[7u8; 32]);
+        let mut payload = Vec::new();
+        payload.extend_from_slice(b"IPPAN_HANDLE_BID");
+        payload.extend_from_slice(handle.as_str().as_bytes());
+        payload.extend_from_slice(&bidder);
+        payload.extend_from_slice(&commitment);
+        let signature = signer.sign(&Sha256::digest(&payload)).to_bytes();
+        let deposit = state.handle_auctions.config().reserve_price;
+        state
+            .handle_auctions
+            .commit_bid(
+                &handle,
+                &PublicKey::new(bidder),
+                commitment,
+                deposit,
+                &signature,
+                true,
+                0,
+            )
+            .expect("bid recorded");
+
+        let response = handle_get_handle_auction(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(handle.as_str().to_string()),
+        )
+        .await
+        .expect("auction lookup");
+        assert_eq!(response.0.phase, "commit");
+        assert_eq!(response.0.bids.len(), 1);
+        assert_eq!(response.0.bids[0].bidder, encode_address(&bidder));
+        assert_eq!(response.0.bids[0].revealed_amount, None);
+
+        let missing = handle_get_handle_auction(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath("@idle.cyborg".to_string()),
+        )
+        .await
+        .expect_err("no auction");
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_primary_handle_reverse_lookup_and_views() {
+        let state = make_app_state();
+        let addr: SocketAddr = "127.0.0.1:9455".parse().unwrap();
+        let signer = sample_private_key([88u8; 32]);
+        let owner = signer.verifying_key().to_bytes();
+        let handle = Handle::new("@reverse.ipn");
+        let signature = sign_handle_registration_payload(&signer, handle.as_str(), &owner, None);
+        state
+            .handle_registry
+            .register(HandleRegistration {
+                handle: handle.clone(),
+                owner: PublicKey::new(owner),
+                signature,
+                metadata: HashMap::new(),
+                expires_at: None,
+            })
+            .expect("registry insert");
+
+        let response = handle_get_primary_handle(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(encode_address(&owner)),
+        )
+        .await
+        .expect("reverse lookup");
+        assert_eq!(response.0.handle, "@reverse.ipn");
+        assert_eq!(response.0.handles, vec!["@reverse.ipn".to_string()]);
+
+        let recipient = sample_public_key([89u8; 32]);
+        let tx = sample_transaction([88u8; 32], recipient, 1);
+        let views = build_payment_views(&[tx], &owner, &state.handle_registry);
+        assert_eq!(views[0].from_handle.as_deref(), Some("@reverse.ipn"));
+        assert_eq!(views[0].to_handle, None);
+
+        let missing = handle_get_primary_handle(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath(encode_address(&recipient)),
+        )
+        .await
+        .expect_err("no primary handle");
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_handle_records_endpoint_and_payment_routing() {
+        let state = make_app_state();
+        let addr: SocketAddr = "127.0.0.1:9456".parse().unwrap();
+        let signer = sample_private_key([90u8; 32]);
+        let owner = signer.verifying_key().to_bytes();
+        let handle = Handle::new("@records.ipn");
+        let signature = sign_handle_registration_payload(&signer, handle.as_str(), &owner, None);
+        state
+            .handle_registry
+            .register(HandleRegistration {
+                handle: handle.clone(),
+                owner: PublicKey::new(owner),
+                signature,
+                metadata: HashMap::new(),
+                expires_at: None,
+            })
+            .expect("registry insert");
+
+        let payee = sample_public_key([91u8; 32]);
+        let set = vec![
+            HandleRecord::Payment { address: payee },
+            HandleRecord::L2Address {
+                network: "rollup-a".into(),
+                address: "0xfeed".into(),
+            },
+        ];
+        let message = record_update_message(&handle, &PublicKey::new(owner), &set, &[]);
+        state
+            .handle_registry
+            .update_records(HandleRecordUpdate {
+                handle: handle.clone(),
+                owner: PublicKey::new(owner),
+                signature: signer.sign(&Sha256::digest(&message)).to_bytes().to_vec(),
+                set,
+                remove: Vec::new(),
+            })
+            .expect("records update");
+
+        let response = handle_get_handle_records(
+            State(state.clone()),
+            ConnectInfo(addr),
+            AxumPath("records.ipn".to_string()),
+        )
+        .await
+        .expect("records lookup");
+        assert_eq!(response.0.records.len(), 2);
+        let l2 = &response.0.records[0];
+        assert_eq!(l2.key, "l2:rollup-a");
+        assert_eq!(l2.network.as_deref(), Some("rollup-a"));
+        let payment = &response.0.records[1];
+        assert_eq!(payment.kind, "payment");
+        assert_eq!(payment.value, encode_address(&payee));
+
+        assert_eq!(
+            resolve_address_or_handle(&state, "to", "@records.ipn").unwrap(),
+            payee
+        );
+        assert_eq!(
+            resolve_address_or_handle(&state, "from", "@records.ipn").unwrap(),
+            owner
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_register_rejects_premium_handles() {
+        let state = make_app_state();
+        let signer = sample_private_key([87u8; 32]);
+        let request = HandleRegisterRequest {
+            handle: "@shiny.iot".into(),
+            owner: encode_address(&signer.verifying_key().to_bytes()),
+            metadata: BTreeMap::new(),
+            expires_at: None,
+            fee: None,
+            nonce: Some(1),
+            signing_key: hex::encode(signer.to_bytes()),
+        };
+        let result = build_handle_registration_transaction(&state, request);
+        assert!(matches!(
+            result,
+            Err(HandleRegistrationError::PremiumRequiresAuction)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_handle_register_rejects_subhandles() {
+        let state = make_app_state();
+        let signer = sample_private_key([86u8; 32]);
+        let request = HandleRegisterRequest {
+            handle: "@alice.acme.ipn".into(),
+            owner: encode_address(&signer.verifying_key().to_bytes()),
+            metadata: BTreeMap::new(),
+            expires_at: None,
+            fee: None,
+            nonce: Some(1),
+            signing_key: hex::encode(signer.to_bytes()),
+        };
+        let result = build_handle_registration_transaction(&state, request);
+        assert!(matches!(
+            result,
+            Err(HandleRegistrationError::SubhandleRequiresDelegation)
+        ));
+    }
+
     #[tokio::test]
     async fn test_handle_dev_fund_requires_dev_mode() {
         let base = make_app_state();
@@ -7788,6 +8803,120 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_p2p_messages_require_peer_signature() {
+        let network = HttpP2PNetwork::new(P2PConfig::default(), "http://127.0.0.1:9701".into())
+            .expect("network");
+        let mut state = (*build_app_state(None, None)).clone();
+        state.p2p_network = Some(Arc::new(network));
+        let router = build_p2p_router(Arc::new(state));
+        let addr: SocketAddr = "127.0.0.1:9421".parse().unwrap();
+
+        let identity = ippan_p2p::PeerIdentity::generate();
+        let peer_info = |peer_id: &str| {
+            serde_json::to_vec(&NetworkMessage::PeerInfo {
+                peer_id: peer_id.to_string(),
+                addresses: vec!["http://198.51.100.7:9000".into()],
+                time_us: None,
+            })
+            .unwrap()
+        };
+        let request = |body: Vec<u8>, signature: Option<MessageSignature>| {
+            let mut builder = Request::builder()
+                .method("POST")
+                .uri("/p2p/peer-info")
+                .header(CONTENT_TYPE, "application/json");
+            for (name, value) in signature.iter().flat_map(MessageSignature::headers) {
+                builder = builder.header(name, value);
+            }
+            let mut request = builder.body(Body::from(body)).expect("request");
+            request.extensions_mut().insert(ConnectInfo(addr));
+            request
+        };
+
+        let unsigned = router
+            .clone()
+            .oneshot(request(peer_info(identity.peer_id()), None))
+            .await
+            .expect("response");
+        assert_eq!(unsigned.status(), StatusCode::UNAUTHORIZED);
+
+        let spoofer = ippan_p2p::PeerIdentity::generate();
+        let spoofed = peer_info("peer-spoofed");
+        let signature = spoofer
+            .sign("/p2p/peer-info", &spoofed, ippan_time_now())
+            .unwrap();
+        let response = router
+            .clone()
+            .oneshot(request(spoofed, Some(signature)))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = peer_info(identity.peer_id());
+        let signature = identity
+            .sign("/p2p/peer-info", &body, ippan_time_now())
+            .unwrap();
+        let response = router
+            .clone()
+            .oneshot(request(body, Some(signature)))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The spoofer is graylisted even when it later tells the truth.
+        let body = peer_info(spoofer.peer_id());
+        let signature = spoofer
+            .sign("/p2p/peer-info", &body, ippan_time_now())
+            .unwrap();
+        let response = router
+            .oneshot(request(body, Some(signature)))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_p2p_invalid_messages_ban_peer() {
+        let network = Arc::new(
+            HttpP2PNetwork::new(P2PConfig::default(), "http://127.0.0.1:9702".into())
+                .expect("network"),
+        );
+        let mut state = (*build_app_state(None, None)).clone();
+        state.p2p_network = Some(network.clone());
+        let router = build_p2p_router(Arc::new(state));
+        let addr: SocketAddr = "127.0.0.1:9422".parse().unwrap();
+
+        let identity = ippan_p2p::PeerIdentity::generate();
+        let send_forged_transaction = |nonce: u64| {
+            // Unsigned transaction: fails signature verification.
+            let tx = Transaction::new([3u8; 32], [4u8; 32], Amount(10), nonce);
+            let body = serde_json::to_vec(&NetworkMessage::Transaction(tx)).unwrap();
+            let signature = identity
+                .sign("/p2p/transactions", &body, ippan_time_now())
+                .unwrap();
+            let mut builder = Request::builder()
+                .method("POST")
+                .uri("/p2p/transactions")
+                .header(CONTENT_TYPE, "application/json");
+            for (name, value) in signature.headers() {
+                builder = builder.header(name, value);
+            }
+            let mut request = builder.body(Body::from(body)).expect("request");
+            request.extensions_mut().insert(ConnectInfo(addr));
+            router.clone().oneshot(request)
+        };
+
+        for nonce in 0..3 {
+            let response = send_forged_transaction(nonce).await.expect("response");
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        assert!(network.peer_scoring().is_banned(identity.peer_id()));
+
+        let response = send_forged_transaction(3).await.expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
     #[tokio::test]
     async fn test_handle_p2p_block_request() {
         let state = make_app_state();
@@ -8298,12 +9427,14 @@ mod tests {
             metrics: None,
             file_storage: Some(file_storage),
             file_dht: Some(file_dht),
+            content_store: None,
             dht_file_mode: "stub".into(),
             dev_mode: true,
             rpc_allowed_origins: vec!["http://localhost:3000".into()],
             handle_registry,
             handle_anchors,
             handle_dht: Some(handle_dht),
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             dht_handle_mode: "stub".into(),
             dlc_consensus: None,
             ipn_dht: None,
@@ -8570,7 +9701,9 @@ mod tests {
             dht_file_mode: "disabled".to_string(),
             dht_handle_mode: "disabled".to_string(),
             file_dht: None,
+            content_store: None,
             handle_dht: None,
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             security: None,
             unified_ui_dist: None,
             consensus_mode: "poa".to_string(),
@@ -8678,7 +9811,9 @@ mod tests {
             dht_file_mode: "disabled".to_string(),
             dht_handle_mode: "disabled".to_string(),
             file_dht: None,
+            content_store: None,
             handle_dht: None,
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             security: None,
             unified_ui_dist: None,
             consensus_mode: "poa".to_string(),
@@ -8761,7 +9896,9 @@ mod tests {
             dht_file_mode: "disabled".to_string(),
             dht_handle_mode: "disabled".to_string(),
             file_dht: None,
+            content_store: None,
             handle_dht: None,
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             security: None,
             unified_ui_dist: None,
             consensus_mode: "poa".to_string(),
@@ -8833,7 +9970,9 @@ mod tests {
             dht_file_mode: "disabled".to_string(),
             dht_handle_mode: "disabled".to_string(),
             file_dht: None,
+            content_store: None,
             handle_dht: None,
+            handle_auctions: Arc::new(HandleAuctionBook::new()),
             security: None,
             unified_ui_dist: None,
             consensus_mode: "poa".to_string(),
@@ -3,6 +3,8 @@
 //! mempool, and AI telemetry pipelines. Handles blocks, accounts, L2 anchors,
 //! and validator telemetry with deterministic serialization.
 //!
+pub mod round_history;
+
 use anyhow::{anyhow, Result};
 use ippan_types::{
     ippan_time_now, Address, Block, ChainState, FileDescriptor, FileDescriptorId, HashTimer,
@@ -864,6 +866,20 @@ pub trait Storage {
     fn blocks_index_size(&self) -> usize {
         0
     }
+
+    // ---------------------------------------------------------------------
+    // Module state (opaque blobs owned by consensus modules)
+    // ---------------------------------------------------------------------
+
+    /// Persist the serialized state of a consensus module under `key`.
+    fn put_module_state(&self, _key: &str, _state: &[u8]) -> Result<()> {
+        Err(anyhow!("module state is not supported by this backend"))
+    }
+
+    /// Load the state last written by [`Storage::put_module_state`].
+    fn get_module_state(&self, _key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
 }
 
 /// Validator telemetry for AI consensus
@@ -913,6 +929,7 @@ struct MemoryStorageInner {
     tx_meta: RwLock<HashMap<[u8; 32], TxMetaV1>>,
     mempool_txs: RwLock<HashMap<[u8; 32], Transaction>>,
     recent_txs: RwLock<BTreeMap<[u8; 72], RecentTxEntryV1>>,
+    module_state: RwLock<HashMap<String, Vec<u8>>>,
 }
 
 impl MemoryStorage {
@@ -1308,6 +1325,18 @@ impl Storage for MemoryStorage {
     fn blocks_index_size(&self) -> usize {
         self.inner.blocks_by_time.read().len()
     }
+
+    fn put_module_state(&self, key: &str, state: &[u8]) -> Result<()> {
+        self.inner
+            .module_state
+            .write()
+            .insert(key.to_string(), state.to_vec());
+        Ok(())
+    }
+
+    fn get_module_state(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.inner.module_state.read().get(key).cloned())
+    }
 }
 
 impl StorageLike for MemoryStorage {
@@ -2135,6 +2164,19 @@ impl Storage for SledStorage {
     fn blocks_index_size(&self) -> usize {
         SledStorage::blocks_index_size(self)
     }
+
+    fn put_module_state(&self, key: &str, state: &[u8]) -> Result<()> {
+        self.metadata
+            .insert(format!("module_state:{key}").as_bytes(), state)?;
+        Ok(())
+    }
+
+    fn get_module_state(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .metadata
+            .get(format!("module_state:{key}").as_bytes())?
+            .map(|v| v.to_vec()))
+    }
 }
 
 impl StorageLike for SledStorage {
@@ -0,0 +1,194 @@
+//! Validator activity rebuilt from finalized rounds.
+//!
+//! The counterfactual replay in `ippan-consensus` and the dataset export in
+//! `ippan-ai-trainer` both need, for every finalized round, how many blocks
+//! each validator created. [`RoundHistory::load`] reads that once from the
+//! round certificates and the blocks they reference, and
+//! [`FinalizedRound::activity`] turns it into the block counters the fairness
+//! metrics accumulate.
+//!
+//! Only block activity is historical. Storage keeps the latest
+//! [`ValidatorTelemetry`] snapshot per validator and a cumulative slash
+//! count, not a bond or slash event log, so latency, uptime, slashing and
+//! stake can only come from that snapshot for every past round.
+
+use crate::{Storage, ValidatorTelemetry};
+use anyhow::{bail, Context, Result};
+use ippan_types::{RoundId, ValidatorId};
+use std::collections::{BTreeMap, HashMap};
+
+/// Blocks credited to one validator in one round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockActivity {
+    /// Blocks the validator created
+    pub proposed: u64,
+    /// Blocks other validators created in the round
+    pub verified: u64,
+}
+
+/// A finalized round and the creators of the blocks its certificate lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalizedRound {
+    pub round: RoundId,
+    pub state_root: [u8; 32],
+    /// Blocks created per validator; certified blocks missing from storage
+    /// are not counted
+    pub creators: BTreeMap<ValidatorId, u64>,
+}
+
+impl FinalizedRound {
+    /// Activity of `validator`, or `None` if it created no block this round.
+    pub fn activity(&self, validator: &ValidatorId) -> Option<BlockActivity> {
+        let proposed = *self.creators.get(validator)?;
+        let total: u64 = self.creators.values().sum();
+        Some(BlockActivity {
+            proposed,
+            verified: total - proposed,
+        })
+    }
+
+    /// Activity of every validator that created a block, by validator id.
+    pub fn activities(&self) -> impl Iterator<Item = (ValidatorId, BlockActivity)> + '_ {
+        let total: u64 = self.creators.values().sum();
+        self.creators.iter().map(move |(validator, &proposed)| {
+            (
+                *validator,
+                BlockActivity {
+                    proposed,
+                    verified: total - proposed,
+                },
+            )
+        })
+    }
+}
+
+/// Finalized rounds in a range, with the latest telemetry snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct RoundHistory {
+    /// Rounds in ascending order; rounds without a finalization record are
+    /// skipped
+    pub rounds: Vec<FinalizedRound>,
+    pub telemetry: HashMap<ValidatorId, ValidatorTelemetry>,
+}
+
+impl RoundHistory {
+    /// Read rounds `from_round..=to_round` (default: all), clamped to the
+    /// latest finalized round.
+    pub fn load<S: Storage + ?Sized>(
+        storage: &S,
+        from_round: Option<RoundId>,
+        to_round: Option<RoundId>,
+    ) -> Result<Self> {
+        let latest = storage
+            .get_latest_round_finalization()?
+            .context("storage has no finalized rounds")?
+            .round;
+        let from = from_round.unwrap_or(0);
+        let to = to_round.unwrap_or(latest).min(latest);
+        if from > to {
+            bail!("round range {from}..={to} is empty");
+        }
+
+        let mut rounds = Vec::new();
+        for round in from..=to {
+            let Some(record) = storage.get_round_finalization(round)? else {
+                continue;
+            };
+            let mut creators = BTreeMap::new();
+            for block_id in &record.proof.block_ids {
+                if let Some(block) = storage.get_block(block_id)? {
+                    *creators.entry(block.header.creator).or_insert(0u64) += 1;
+                }
+            }
+            rounds.push(FinalizedRound {
+                round,
+                state_root: record.state_root,
+                creators,
+            });
+        }
+
+        Ok(Self {
+            rounds,
+            telemetry: storage.get_all_validator_telemetry()?,
+        })
+    }
+
+    /// Every validator that created a block in the loaded rounds, sorted.
+    pub fn creators(&self) -> Vec<ValidatorId> {
+        let mut validators: Vec<ValidatorId> = self
+            .rounds
+            .iter()
+            .flat_map(|round| round.creators.keys().copied())
+            .collect();
+        validators.sort_unstable();
+        validators.dedup();
+        validators
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+    use ippan_types::{
+        Block, IppanTimeMicros, RoundCertificate, RoundFinalizationRecord, RoundWindow,
+    };
+
+    fn finalize(storage: &MemoryStorage, round: RoundId, creators: &[ValidatorId]) {
+        let mut block_ids = Vec::new();
+        for creator in creators {
+            let block = Block::new(vec![], vec![], round, *creator);
+            block_ids.push(block.hash());
+            storage.store_block(block).unwrap();
+        }
+        storage
+            .store_round_finalization(RoundFinalizationRecord {
+                round,
+                window: RoundWindow {
+                    id: round,
+                    start_us: IppanTimeMicros(round * 100),
+                    end_us: IppanTimeMicros(round * 100 + 100),
+                },
+                ordered_tx_ids: vec![],
+                fork_drops: vec![],
+                state_root: [round as u8; 32],
+                proof: RoundCertificate {
+                    round,
+                    block_ids,
+                    agg_sig: vec![],
+                },
+                total_fees_atomic: None,
+                treasury_fees_atomic: None,
+                applied_payments: None,
+                rejected_payments: None,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_load_counts_block_creators() {
+        let storage = MemoryStorage::default();
+        let (alice, bob) = ([1u8; 32], [2u8; 32]);
+        finalize(&storage, 1, &[alice]);
+        finalize(&storage, 3, &[alice, bob]);
+
+        let history = RoundHistory::load(&storage, None, None).unwrap();
+        assert_eq!(
+            history.rounds.iter().map(|r| r.round).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(history.creators(), vec![alice, bob]);
+        assert_eq!(history.rounds[0].activity(&bob), None);
+        assert_eq!(
+            history.rounds[1].activity(&bob),
+            Some(BlockActivity {
+                proposed: 1,
+                verified: 1
+            })
+        );
+        assert_eq!(history.rounds[1].activities().count(), 2);
+
+        assert!(RoundHistory::load(&storage, Some(4), None).is_err());
+        assert!(RoundHistory::load(&MemoryStorage::default(), None, None).is_err());
+    }
+}
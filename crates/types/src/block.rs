@@ -305,6 +305,33 @@ impl Block {
         header_size + tx_size + metadata_size
     }
 
+    /// Check that the header HashTimer was derived from the header components.
+    pub fn verify_hashtimer(&self) -> bool {
+        let hashtimer_payload = Self::build_hashtimer_payload(
+            self.header.round,
+            &self.header.creator,
+            &self.header.parent_ids,
+            &self.header.payload_ids,
+            &self.header.merkle_payload,
+            &self.header.merkle_parents,
+        );
+        let expected_nonce = Self::derive_hashtimer_nonce(
+            self.header.round,
+            &self.header.creator,
+            &self.header.parent_ids,
+            &self.header.payload_ids,
+        );
+        let expected_hashtimer = HashTimer::derive(
+            HASH_CONTEXT,
+            self.header.hashtimer.time(),
+            HASH_DOMAIN,
+            &hashtimer_payload,
+            &expected_nonce,
+            &self.header.creator,
+        );
+        expected_hashtimer == self.header.hashtimer
+    }
+
     /// Verify block integrity against the deterministic header rules.
     pub fn is_valid(&self) -> bool {
         // Merkle roots must match inputs.
@@ -382,30 +409,7 @@ impl Block {
         }
 
         // HashTimer must be reproducible from header components (same time).
-        let hashtimer_payload = Self::build_hashtimer_payload(
-            self.header.round,
-            &self.header.creator,
-            &self.header.parent_ids,
-            &self.header.payload_ids,
-            &self.header.merkle_payload,
-            &self.header.merkle_parents,
-        );
-        let expected_nonce = Self::derive_hashtimer_nonce(
-            self.header.round,
-            &self.header.creator,
-            &self.header.parent_ids,
-            &self.header.payload_ids,
-        );
-        let expected_hashtimer = HashTimer::derive(
-            HASH_CONTEXT,
-            self.header.hashtimer.time(),
-            HASH_DOMAIN,
-            &hashtimer_payload,
-            &expected_nonce,
-            &self.header.creator,
-        );
-
-        if expected_hashtimer != self.header.hashtimer {
+        if !self.verify_hashtimer() {
             return false;
         }
 
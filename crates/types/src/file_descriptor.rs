@@ -1,5 +1,6 @@
 use crate::{Address, HashTimer};
 use blake3::Hasher;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use thiserror::Error;
@@ -135,6 +136,69 @@ impl FileDescriptor {
     }
 }
 
+/// Payload anchoring ownership of an IPNDHT file descriptor on L1.
+///
+/// The enclosing transaction must be sent by `owner`. `signature` is the
+/// owner's descriptor signature over `descriptor_digest`, so the anchor
+/// commits to the exact descriptor the owner published.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileAnchorOp {
+    pub file_id: FileDescriptorId,
+    #[serde(
+        serialize_with = "serialize_content_hash",
+        deserialize_with = "deserialize_content_hash"
+    )]
+    pub content_hash: ContentHash,
+    pub owner: [u8; 32],
+    /// Descriptor creation time; with `content_hash` and `owner` it derives `file_id`.
+    pub created_at_us: u64,
+    /// Digest signed by the owner (`ippan_files::FileDescriptor::signing_digest`).
+    pub descriptor_digest: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Stateless validation failures for [`FileAnchorOp`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FileAnchorOpError {
+    #[error("file anchor owner must match transaction sender")]
+    OwnerMismatch,
+    #[error("file anchor signature must be 64 bytes")]
+    InvalidSignatureLength,
+    #[error("file anchor signature does not verify against the owner key")]
+    InvalidSignature,
+}
+
+impl FileAnchorOp {
+    /// Check the anchor against the transaction sender and verify the owner's
+    /// descriptor signature.
+    pub fn validate_for_sender(&self, sender: &[u8; 32]) -> Result<(), FileAnchorOpError> {
+        if self.owner != *sender {
+            return Err(FileAnchorOpError::OwnerMismatch);
+        }
+        let signature: [u8; 64] = self
+            .signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| FileAnchorOpError::InvalidSignatureLength)?;
+        let key = VerifyingKey::from_bytes(&self.owner)
+            .map_err(|_| FileAnchorOpError::InvalidSignature)?;
+        key.verify(&self.descriptor_digest, &Signature::from_bytes(&signature))
+            .map_err(|_| FileAnchorOpError::InvalidSignature)
+    }
+
+    /// Append the canonical byte encoding used for transaction hashing and signing.
+    pub fn append_canonical_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.file_id.as_bytes());
+        bytes.extend_from_slice(&self.content_hash);
+        bytes.extend_from_slice(&self.owner);
+        bytes.extend_from_slice(&self.created_at_us.to_be_bytes());
+        bytes.extend_from_slice(&self.descriptor_digest);
+        bytes.extend_from_slice(&(self.signature.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.signature);
+    }
+}
+
 fn serialize_content_hash<S>(hash: &ContentHash, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -210,4 +274,37 @@ mod tests {
         assert_eq!(restored.id, descriptor.id);
         assert_eq!(restored.content_hash, descriptor.content_hash);
     }
+
+    #[test]
+    fn file_anchor_op_checks_sender_and_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[5u8; 32]);
+        let owner = signing_key.verifying_key().to_bytes();
+        let digest = [8u8; 32];
+        let op = FileAnchorOp {
+            file_id: sample_descriptor().id,
+            content_hash: [3u8; 32],
+            owner,
+            created_at_us: 1_000_000,
+            descriptor_digest: digest,
+            signature: signing_key.sign(&digest).to_bytes().to_vec(),
+        };
+        assert_eq!(op.validate_for_sender(&owner), Ok(()));
+        assert_eq!(
+            op.validate_for_sender(&[1u8; 32]),
+            Err(FileAnchorOpError::OwnerMismatch)
+        );
+
+        let mut forged = op.clone();
+        forged.descriptor_digest = [9u8; 32];
+        assert_eq!(
+            forged.validate_for_sender(&owner),
+            Err(FileAnchorOpError::InvalidSignature)
+        );
+
+        let json = serde_json::to_string(&op).expect("serialize anchor");
+        let restored: FileAnchorOp = serde_json::from_str(&json).expect("deserialize anchor");
+        assert_eq!(restored, op);
+    }
 }
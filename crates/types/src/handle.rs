@@ -1,21 +1,65 @@
+use crate::currency::Amount;
+use crate::file_descriptor::FileDescriptorId;
 use serde::{Deserialize, Serialize};
 use serde_bytes;
 use std::collections::BTreeMap;
 use thiserror::Error;
 
+/// Empty metadata map returned for operations that do not carry metadata.
+static EMPTY_METADATA: BTreeMap<String, String> = BTreeMap::new();
+
+/// Account holding deposits escrowed by sealed bids on premium handle auctions.
+///
+/// Bid transactions transfer their deposit here; settlement releases it to the
+/// treasury and back to bidders.
+pub const HANDLE_AUCTION_ESCROW_ACCOUNT: [u8; 32] = *b"IPPAN_HANDLE_AUCTION_ESCROW_0000";
+
 /// Structured payload describing a handle-specific transaction operation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum HandleOperation {
     /// Register a new handle on-chain.
     Register(HandleRegisterOp),
+    /// Merge new metadata into an existing handle.
+    Update(HandleUpdateOp),
+    /// Move ownership of a handle to a new key.
+    Transfer(HandleTransferOp),
+    /// Extend the expiry of an existing handle.
+    Renew(HandleRenewOp),
+    /// Give up a handle so it can be registered again.
+    Release(HandleReleaseOp),
+    /// Commit a sealed bid on a premium handle auction.
+    Bid(HandleBidOp),
+    /// Reveal a previously committed sealed bid.
+    Reveal(HandleRevealOp),
+    /// Select the handle used for reverse resolution of the owner's address.
+    SetPrimary(HandleSetPrimaryOp),
+    /// Set or remove typed resolver records on a handle.
+    SetRecords(HandleRecordsOp),
+    /// Issue a subhandle (`@alice.acme.ipn`) under a handle owned by the sender.
+    DelegateSubhandle(HandleDelegateOp),
+    /// Revoke a subhandle previously issued under a handle owned by the sender.
+    RevokeSubhandle(HandleRevokeOp),
 }
 
 impl HandleOperation {
     /// Returns the owner bytes associated with the operation.
+    ///
+    /// For transfers this is the current owner, i.e. the key authorising the move;
+    /// for subhandle delegation and revocation it is the parent handle's owner.
     pub fn owner_bytes(&self) -> &[u8; 32] {
         match self {
             HandleOperation::Register(op) => &op.owner,
+            HandleOperation::Update(op) => &op.owner,
+            HandleOperation::Transfer(op) => &op.owner,
+            HandleOperation::Renew(op) => &op.owner,
+            HandleOperation::Release(op) => &op.owner,
+            HandleOperation::Bid(op) => &op.owner,
+            HandleOperation::Reveal(op) => &op.owner,
+            HandleOperation::SetPrimary(op) => &op.owner,
+            HandleOperation::SetRecords(op) => &op.owner,
+            HandleOperation::DelegateSubhandle(op) => &op.owner,
+            HandleOperation::RevokeSubhandle(op) => &op.owner,
         }
     }
 
@@ -23,6 +67,16 @@ impl HandleOperation {
     pub fn handle(&self) -> &str {
         match self {
             HandleOperation::Register(op) => op.handle.as_str(),
+            HandleOperation::Update(op) => op.handle.as_str(),
+            HandleOperation::Transfer(op) => op.handle.as_str(),
+            HandleOperation::Renew(op) => op.handle.as_str(),
+            HandleOperation::Release(op) => op.handle.as_str(),
+            HandleOperation::Bid(op) => op.handle.as_str(),
+            HandleOperation::Reveal(op) => op.handle.as_str(),
+            HandleOperation::SetPrimary(op) => op.handle.as_str(),
+            HandleOperation::SetRecords(op) => op.handle.as_str(),
+            HandleOperation::DelegateSubhandle(op) => op.handle.as_str(),
+            HandleOperation::RevokeSubhandle(op) => op.handle.as_str(),
         }
     }
 
@@ -30,6 +84,16 @@ impl HandleOperation {
     pub fn expires_at(&self) -> Option<u64> {
         match self {
             HandleOperation::Register(op) => op.expires_at,
+            HandleOperation::Renew(op) => Some(op.expires_at),
+            HandleOperation::Reveal(op) => op.expires_at,
+            HandleOperation::DelegateSubhandle(op) => op.expires_at,
+            HandleOperation::Update(_)
+            | HandleOperation::Transfer(_)
+            | HandleOperation::Release(_)
+            | HandleOperation::Bid(_)
+            | HandleOperation::SetPrimary(_)
+            | HandleOperation::SetRecords(_)
+            | HandleOperation::RevokeSubhandle(_) => None,
         }
     }
 
@@ -37,6 +101,16 @@ impl HandleOperation {
     pub fn signature(&self) -> &[u8] {
         match self {
             HandleOperation::Register(op) => &op.signature,
+            HandleOperation::Update(op) => &op.signature,
+            HandleOperation::Transfer(op) => &op.signature,
+            HandleOperation::Renew(op) => &op.signature,
+            HandleOperation::Release(op) => &op.signature,
+            HandleOperation::Bid(op) => &op.signature,
+            HandleOperation::Reveal(op) => &op.signature,
+            HandleOperation::SetPrimary(op) => &op.signature,
+            HandleOperation::SetRecords(op) => &op.signature,
+            HandleOperation::DelegateSubhandle(op) => &op.signature,
+            HandleOperation::RevokeSubhandle(op) => &op.signature,
         }
     }
 
@@ -44,24 +118,260 @@ impl HandleOperation {
     pub fn metadata(&self) -> &BTreeMap<String, String> {
         match self {
             HandleOperation::Register(op) => &op.metadata,
+            HandleOperation::Update(op) => &op.metadata,
+            HandleOperation::DelegateSubhandle(op) => &op.metadata,
+            HandleOperation::Transfer(_)
+            | HandleOperation::Renew(_)
+            | HandleOperation::Release(_)
+            | HandleOperation::Bid(_)
+            | HandleOperation::Reveal(_)
+            | HandleOperation::SetPrimary(_)
+            | HandleOperation::SetRecords(_)
+            | HandleOperation::RevokeSubhandle(_) => &EMPTY_METADATA,
+        }
+    }
+
+    /// Short label for the operation kind, matching the serde tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HandleOperation::Register(_) => "register",
+            HandleOperation::Update(_) => "update",
+            HandleOperation::Transfer(_) => "transfer",
+            HandleOperation::Renew(_) => "renew",
+            HandleOperation::Release(_) => "release",
+            HandleOperation::Bid(_) => "bid",
+            HandleOperation::Reveal(_) => "reveal",
+            HandleOperation::SetPrimary(_) => "set_primary",
+            HandleOperation::SetRecords(_) => "set_records",
+            HandleOperation::DelegateSubhandle(_) => "delegate_subhandle",
+            HandleOperation::RevokeSubhandle(_) => "revoke_subhandle",
         }
     }
 
     /// Basic semantic validation tied to the originating transaction sender.
     pub fn validate_for_sender(&self, sender: &[u8; 32]) -> Result<(), HandleOperationError> {
+        if self.owner_bytes() != sender {
+            return Err(HandleOperationError::OwnerMismatch);
+        }
+        validate_handle_str(self.handle())?;
+        if self.signature().len() != 64 {
+            return Err(HandleOperationError::InvalidSignatureLength);
+        }
+        if let HandleOperation::Transfer(op) = self {
+            if op.new_owner == op.owner {
+                return Err(HandleOperationError::SelfTransfer);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the canonical byte encoding used for transaction hashing and signing.
+    pub fn append_canonical_bytes(&self, bytes: &mut Vec<u8>) {
         match self {
-            HandleOperation::Register(op) => {
-                if &op.owner != sender {
-                    return Err(HandleOperationError::OwnerMismatch);
+            HandleOperation::Register(data) => {
+                bytes.push(0);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                if let Some(exp) = data.expires_at {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&exp.to_be_bytes());
+                } else {
+                    bytes.push(0);
+                }
+                append_metadata(bytes, &data.metadata);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Update(data) => {
+                bytes.push(1);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                append_metadata(bytes, &data.metadata);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Transfer(data) => {
+                bytes.push(2);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                bytes.extend_from_slice(&data.new_owner);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Renew(data) => {
+                bytes.push(3);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                bytes.extend_from_slice(&data.expires_at.to_be_bytes());
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Release(data) => {
+                bytes.push(4);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Bid(data) => {
+                bytes.push(5);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                bytes.extend_from_slice(&data.commitment);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::Reveal(data) => {
+                bytes.push(6);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                bytes.extend_from_slice(&data.amount.atomic().to_be_bytes());
+                bytes.extend_from_slice(&data.salt);
+                if let Some(exp) = data.expires_at {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&exp.to_be_bytes());
+                } else {
+                    bytes.push(0);
                 }
-                op.validate_handle()?;
-                op.validate_signature_length()?;
-                Ok(())
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::SetPrimary(data) => {
+                bytes.push(7);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::SetRecords(data) => {
+                bytes.push(8);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                append_record_changes(bytes, &data.set, &data.remove);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::DelegateSubhandle(data) => {
+                bytes.push(9);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                bytes.extend_from_slice(&data.subhandle_owner);
+                if let Some(exp) = data.expires_at {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&exp.to_be_bytes());
+                } else {
+                    bytes.push(0);
+                }
+                append_metadata(bytes, &data.metadata);
+                append_length_prefixed(bytes, &data.signature);
+            }
+            HandleOperation::RevokeSubhandle(data) => {
+                bytes.push(10);
+                append_length_prefixed(bytes, data.handle.as_bytes());
+                bytes.extend_from_slice(&data.owner);
+                append_length_prefixed(bytes, &data.signature);
+            }
+        }
+    }
+}
+
+/// Typed resolver record attached to a handle, similar to a DNS record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HandleRecord {
+    /// Address that payments to the handle should be sent to.
+    Payment { address: [u8; 32] },
+    /// Account address on an L2 network, keyed by the `L2Network` id.
+    L2Address { network: String, address: String },
+    /// Pointer to a published file, e.g. a profile or website.
+    File { descriptor: FileDescriptorId },
+    /// Node endpoint as a multiaddr.
+    Node { multiaddr: String },
+    /// Free-form named text record.
+    Txt { name: String, value: String },
+}
+
+impl HandleRecord {
+    /// Key identifying the record slot on a handle.
+    ///
+    /// A handle holds at most one record per key: `payment`, `file`, `node`,
+    /// `l2:<network>` and `txt:<name>`.
+    pub fn key(&self) -> String {
+        match self {
+            HandleRecord::Payment { .. } => "payment".to_string(),
+            HandleRecord::L2Address { network, .. } => format!("l2:{network}"),
+            HandleRecord::File { .. } => "file".to_string(),
+            HandleRecord::Node { .. } => "node".to_string(),
+            HandleRecord::Txt { name, .. } => format!("txt:{name}"),
+        }
+    }
+
+    /// Short label for the record type, matching the serde tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HandleRecord::Payment { .. } => "payment",
+            HandleRecord::L2Address { .. } => "l2_address",
+            HandleRecord::File { .. } => "file",
+            HandleRecord::Node { .. } => "node",
+            HandleRecord::Txt { .. } => "txt",
+        }
+    }
+
+    /// Append the canonical byte encoding of the record.
+    pub fn append_canonical_bytes(&self, bytes: &mut Vec<u8>) {
+        match self {
+            HandleRecord::Payment { address } => {
+                bytes.push(0);
+                bytes.extend_from_slice(address);
+            }
+            HandleRecord::L2Address { network, address } => {
+                bytes.push(1);
+                append_length_prefixed(bytes, network.as_bytes());
+                append_length_prefixed(bytes, address.as_bytes());
+            }
+            HandleRecord::File { descriptor } => {
+                bytes.push(2);
+                bytes.extend_from_slice(descriptor.as_bytes());
+            }
+            HandleRecord::Node { multiaddr } => {
+                bytes.push(3);
+                append_length_prefixed(bytes, multiaddr.as_bytes());
+            }
+            HandleRecord::Txt { name, value } => {
+                bytes.push(4);
+                append_length_prefixed(bytes, name.as_bytes());
+                append_length_prefixed(bytes, value.as_bytes());
             }
         }
     }
 }
 
+/// Append the canonical encoding of a record change set.
+///
+/// Shared by transaction hashing and the `IPPAN_HANDLE_RECORDS` signature message.
+pub fn append_record_changes(bytes: &mut Vec<u8>, set: &[HandleRecord], remove: &[String]) {
+    bytes.extend_from_slice(&(set.len() as u32).to_be_bytes());
+    for record in set {
+        record.append_canonical_bytes(bytes);
+    }
+    bytes.extend_from_slice(&(remove.len() as u32).to_be_bytes());
+    for key in remove {
+        append_length_prefixed(bytes, key.as_bytes());
+    }
+}
+
+fn append_length_prefixed(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(data);
+}
+
+fn append_metadata(bytes: &mut Vec<u8>, metadata: &BTreeMap<String, String>) {
+    bytes.extend_from_slice(&(metadata.len() as u32).to_be_bytes());
+    for (key, value) in metadata {
+        append_length_prefixed(bytes, key.as_bytes());
+        append_length_prefixed(bytes, value.as_bytes());
+    }
+}
+
+fn validate_handle_str(handle: &str) -> Result<(), HandleOperationError> {
+    let handle = handle.trim();
+    if !(handle.starts_with('@') && handle.contains('.') && handle.len() > 3) {
+        return Err(HandleOperationError::InvalidHandle);
+    }
+    Ok(())
+}
+
 /// Registration payload embedded inside a transaction.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HandleRegisterOp {
@@ -75,21 +385,132 @@ pub struct HandleRegisterOp {
     pub signature: Vec<u8>,
 }
 
-impl HandleRegisterOp {
-    fn validate_handle(&self) -> Result<(), HandleOperationError> {
-        let handle = self.handle.trim();
-        if !(handle.starts_with('@') && handle.contains('.') && handle.len() > 3) {
-            return Err(HandleOperationError::InvalidHandle);
-        }
-        Ok(())
-    }
+/// Metadata update payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleUpdateOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
 
-    fn validate_signature_length(&self) -> Result<(), HandleOperationError> {
-        if self.signature.len() != 64 {
-            return Err(HandleOperationError::InvalidSignatureLength);
-        }
-        Ok(())
-    }
+/// Ownership transfer payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleTransferOp {
+    pub handle: String,
+    /// Current owner authorising the transfer.
+    pub owner: [u8; 32],
+    pub new_owner: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Renewal payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleRenewOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    /// New expiry timestamp (seconds since UNIX_EPOCH).
+    pub expires_at: u64,
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Release payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleReleaseOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Sealed bid payload for a premium handle auction.
+///
+/// The enclosing transaction must send the bid deposit to
+/// [`HANDLE_AUCTION_ESCROW_ACCOUNT`]; the deposit has to cover the hidden amount.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleBidOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    /// Commitment to the bid amount and salt (see `compute_bid_commitment`).
+    pub commitment: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Reveal payload opening a sealed bid.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleRevealOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    pub amount: Amount,
+    pub salt: [u8; 32],
+    /// Expiry requested for the handle if this bid wins.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Registration signature over the handle, owner and expiry, used to
+    /// register the handle for the winner.
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Primary handle selection payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleSetPrimaryOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Resolver record change set embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleRecordsOp {
+    pub handle: String,
+    pub owner: [u8; 32],
+    /// Records to insert or replace (keyed by [`HandleRecord::key`]).
+    #[serde(default)]
+    pub set: Vec<HandleRecord>,
+    /// Record keys to delete.
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Subhandle delegation payload embedded inside a transaction.
+///
+/// Signed and sent by the owner of the parent handle, who pays for the
+/// transaction on behalf of `subhandle_owner`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleDelegateOp {
+    /// Full subhandle name, e.g. `@alice.acme.ipn`.
+    pub handle: String,
+    /// Owner of the parent handle authorising the delegation.
+    pub owner: [u8; 32],
+    /// Key the subhandle is issued to.
+    pub subhandle_owner: [u8; 32],
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Requested expiry; defaults to, and may not exceed, the parent's expiry.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+/// Subhandle revocation payload embedded inside a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HandleRevokeOp {
+    /// Subhandle to revoke.
+    pub handle: String,
+    /// Owner of the parent handle authorising the revocation.
+    pub owner: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
 }
 
 /// Errors raised during embedded handle validation.
@@ -101,4 +522,6 @@ pub enum HandleOperationError {
     InvalidHandle,
     #[error("handle registration signature must be 64 bytes")]
     InvalidSignatureLength,
+    #[error("handle transfer target must differ from the current owner")]
+    SelfTransfer,
 }
@@ -12,6 +12,7 @@ pub mod file_descriptor;
 pub mod handle;
 pub mod health;
 pub mod l2;
+pub mod model_governance;
 pub mod receipt;
 pub mod round;
 pub mod scalars;
@@ -48,6 +49,9 @@ pub use ippan_time::{random_nonce, HashTimer, IppanTimeMicros};
 // L2 types
 pub use l2::*;
 
+// AI model governance operations
+pub use model_governance::*;
+
 // Receipt types
 pub use receipt::*;
 
@@ -0,0 +1,170 @@
+//! AI model governance operations carried by transactions.
+//!
+//! Fairness model upgrades are decided on chain: a [`ModelProposalOp`] puts
+//! a signed model artifact up for a vote and validators answer with
+//! [`ModelVoteOp`]s. Consensus applies both while finalizing a round, so
+//! every node records an approval at the same round.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Structured payload describing a model governance operation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ModelGovernanceOp {
+    /// Propose a model for activation.
+    Propose(ModelProposalOp),
+    /// Vote on a pending proposal with the sender's validator stake.
+    Vote(ModelVoteOp),
+}
+
+/// A model artifact proposed for activation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelProposalOp {
+    pub model_id: String,
+    pub version: u32,
+    /// Where nodes fetch the artifact, usually an `ipndht://<file id>` URL
+    pub model_url: String,
+    /// BLAKE3 digest of the canonical model artifact
+    pub model_hash: [u8; 32],
+    /// Ed25519 signature of `model_hash` by `signer_pubkey`
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+    pub signer_pubkey: [u8; 32],
+    /// Round at which the model replaces the active one
+    pub activation_round: u64,
+    pub description: String,
+}
+
+/// A validator's vote on the pending proposal for `model_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelVoteOp {
+    pub model_id: String,
+    pub approve: bool,
+}
+
+/// Stateless validation failures for [`ModelGovernanceOp`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ModelGovernanceOpError {
+    #[error("model id cannot be empty")]
+    EmptyModelId,
+    #[error("model proposal description cannot be empty")]
+    EmptyDescription,
+    #[error("model proposal signature must be 64 bytes")]
+    InvalidSignatureLength,
+    #[error("model proposal signature does not verify against the signer key")]
+    InvalidSignature,
+}
+
+impl ModelGovernanceOp {
+    pub fn model_id(&self) -> &str {
+        match self {
+            ModelGovernanceOp::Propose(op) => &op.model_id,
+            ModelGovernanceOp::Vote(op) => &op.model_id,
+        }
+    }
+
+    /// Check the operation's fields and, for proposals, the signature over
+    /// the model hash.
+    pub fn validate(&self) -> Result<(), ModelGovernanceOpError> {
+        if self.model_id().is_empty() {
+            return Err(ModelGovernanceOpError::EmptyModelId);
+        }
+        let ModelGovernanceOp::Propose(op) = self else {
+            return Ok(());
+        };
+        if op.description.is_empty() {
+            return Err(ModelGovernanceOpError::EmptyDescription);
+        }
+        op.signature_bytes()?;
+        Ok(())
+    }
+
+    /// Append the canonical byte encoding used for transaction hashing and signing.
+    pub fn append_canonical_bytes(&self, bytes: &mut Vec<u8>) {
+        match self {
+            ModelGovernanceOp::Propose(op) => {
+                bytes.push(0);
+                append_length_prefixed(bytes, op.model_id.as_bytes());
+                bytes.extend_from_slice(&op.version.to_be_bytes());
+                append_length_prefixed(bytes, op.model_url.as_bytes());
+                bytes.extend_from_slice(&op.model_hash);
+                append_length_prefixed(bytes, &op.signature);
+                bytes.extend_from_slice(&op.signer_pubkey);
+                bytes.extend_from_slice(&op.activation_round.to_be_bytes());
+                append_length_prefixed(bytes, op.description.as_bytes());
+            }
+            ModelGovernanceOp::Vote(op) => {
+                bytes.push(1);
+                append_length_prefixed(bytes, op.model_id.as_bytes());
+                bytes.push(op.approve as u8);
+            }
+        }
+    }
+}
+
+impl ModelProposalOp {
+    /// The signature, once verified against `signer_pubkey` and `model_hash`.
+    pub fn signature_bytes(&self) -> Result<[u8; 64], ModelGovernanceOpError> {
+        let signature: [u8; 64] = self
+            .signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| ModelGovernanceOpError::InvalidSignatureLength)?;
+        let key = VerifyingKey::from_bytes(&self.signer_pubkey)
+            .map_err(|_| ModelGovernanceOpError::InvalidSignature)?;
+        key.verify(&self.model_hash, &Signature::from_bytes(&signature))
+            .map_err(|_| ModelGovernanceOpError::InvalidSignature)?;
+        Ok(signature)
+    }
+}
+
+fn append_length_prefixed(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn proposal(signing_key: &SigningKey) -> ModelProposalOp {
+        let model_hash = [7u8; 32];
+        ModelProposalOp {
+            model_id: "fairness".into(),
+            version: 2,
+            model_url: "ipndht://00".into(),
+            model_hash,
+            signature: signing_key.sign(&model_hash).to_bytes().to_vec(),
+            signer_pubkey: signing_key.verifying_key().to_bytes(),
+            activation_round: 100,
+            description: "retrained on round history".into(),
+        }
+    }
+
+    #[test]
+    fn validates_proposal_signature() {
+        let signing_key = SigningKey::from_bytes(&[5u8; 32]);
+        let op = ModelGovernanceOp::Propose(proposal(&signing_key));
+        assert_eq!(op.validate(), Ok(()));
+
+        let mut forged = proposal(&signing_key);
+        forged.model_hash = [8u8; 32];
+        assert_eq!(
+            ModelGovernanceOp::Propose(forged).validate(),
+            Err(ModelGovernanceOpError::InvalidSignature)
+        );
+
+        let vote = ModelGovernanceOp::Vote(ModelVoteOp {
+            model_id: String::new(),
+            approve: true,
+        });
+        assert_eq!(vote.validate(), Err(ModelGovernanceOpError::EmptyModelId));
+
+        let json = serde_json::to_string(&op).expect("serialize op");
+        let restored: ModelGovernanceOp = serde_json::from_str(&json).expect("deserialize op");
+        assert_eq!(restored, op);
+    }
+}
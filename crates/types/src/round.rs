@@ -20,6 +20,33 @@ pub struct RoundCertificate {
     pub agg_sig: Vec<u8>,
 }
 
+impl RoundCertificate {
+    /// Certificate whose `agg_sig` commits to `round` and `block_ids`.
+    pub fn new(round: RoundId, block_ids: Vec<BlockId>) -> Self {
+        let agg_sig = Self::aggregate_signature(round, &block_ids);
+        Self {
+            round,
+            block_ids,
+            agg_sig,
+        }
+    }
+
+    /// BLAKE3 commitment to a round and its ordered block ids.
+    pub fn aggregate_signature(round: RoundId, block_ids: &[BlockId]) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&round.to_be_bytes());
+        for id in block_ids {
+            hasher.update(id);
+        }
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    /// Whether `agg_sig` commits to this certificate's round and block ids.
+    pub fn verify_aggregate(&self) -> bool {
+        self.agg_sig == Self::aggregate_signature(self.round, &self.block_ids)
+    }
+}
+
 /// Finalization record describing the ordered execution of a round.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RoundFinalizationRecord {
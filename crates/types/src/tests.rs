@@ -90,6 +90,40 @@ mod type_tests {
         assert!(tx.is_valid());
     }
 
+    #[test]
+    fn test_handle_lifecycle_operations_change_transaction_hash() {
+        use crate::{HandleRenewOp, HandleTransferOp};
+        use ed25519_dalek::SigningKey;
+        let secret = SigningKey::from_bytes(&[22u8; 32]);
+        let from = secret.verifying_key().to_bytes();
+
+        let mut renew = Transaction::new(from, [0u8; 32], Amount::zero(), 1);
+        renew.set_handle_operation(HandleOperation::Renew(HandleRenewOp {
+            handle: "@alice.ipn".to_string(),
+            owner: from,
+            expires_at: 1_800_000_000,
+            signature: vec![0u8; 64],
+        }));
+        renew.sign(&secret.to_bytes()).unwrap();
+        assert!(renew.is_valid());
+
+        let mut transfer = renew.clone();
+        transfer.set_handle_operation(HandleOperation::Transfer(HandleTransferOp {
+            handle: "@alice.ipn".to_string(),
+            owner: from,
+            new_owner: [9u8; 32],
+            signature: vec![0u8; 64],
+        }));
+        transfer.sign(&secret.to_bytes()).unwrap();
+        assert!(transfer.is_valid());
+        assert_ne!(renew.hash(), transfer.hash());
+
+        let op = transfer.handle_operation().unwrap();
+        assert_eq!(op.kind(), "transfer");
+        assert!(op.validate_for_sender(&from).is_ok());
+        assert!(op.validate_for_sender(&[9u8; 32]).is_err());
+    }
+
     #[test]
     fn test_block_creation() {
         let tx1 = Transaction::new([1u8; 32], [2u8; 32], Amount::from_atomic(1000), 1);
@@ -434,6 +468,25 @@ mod serialization_tests {
         assert_eq!(cert, deserialized, "RoundCertificate equality check failed");
     }
 
+    /// Test that the aggregate signature commits to the round and its blocks
+    #[test]
+    fn test_round_certificate_aggregate_verification() {
+        let cert = RoundCertificate::new(50, vec![[1u8; 32], [2u8; 32]]);
+        assert!(cert.verify_aggregate());
+
+        let mut other_round = cert.clone();
+        other_round.round = 51;
+        assert!(!other_round.verify_aggregate());
+
+        let mut dropped = cert.clone();
+        dropped.block_ids.pop();
+        assert!(!dropped.verify_aggregate());
+
+        let mut unsigned = cert;
+        unsigned.agg_sig.clear();
+        assert!(!unsigned.verify_aggregate());
+    }
+
     /// Test round-trip serialization/deserialization for RoundFinalizationRecord (JSON)
     #[test]
     fn test_round_finalization_record_json_roundtrip() {
@@ -1,5 +1,7 @@
 use crate::currency::Amount;
+use crate::file_descriptor::FileAnchorOp;
 use crate::handle::HandleOperation;
+use crate::model_governance::ModelGovernanceOp;
 use crate::{HashTimer, IppanTimeMicros};
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
@@ -109,6 +111,12 @@ pub struct Transaction {
     /// Optional zero-knowledge proof metadata.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub zk_proof: Option<ConfidentialProof>,
+    /// Optional file descriptor ownership anchor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_anchor: Option<Box<FileAnchorOp>>,
+    /// Optional AI model governance proposal or vote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_governance: Option<Box<ModelGovernanceOp>>,
     /// Transaction signature (64 bytes)
     #[serde(with = "serde_bytes")]
     pub signature: [u8; 64],
@@ -139,6 +147,8 @@ impl Transaction {
             handle_op: None,
             confidential: None,
             zk_proof: None,
+            file_anchor: None,
+            model_governance: None,
             signature: [0u8; 64], // Will be set after signing
             hashtimer,
             timestamp,
@@ -161,6 +171,32 @@ impl Transaction {
         self.handle_op.as_ref()
     }
 
+    /// Attach a file descriptor ownership anchor to the transaction.
+    pub fn set_file_anchor(&mut self, anchor: FileAnchorOp) {
+        self.file_anchor = Some(Box::new(anchor));
+    }
+
+    /// Returns the embedded file anchor, if any.
+    pub fn file_anchor(&self) -> Option<&FileAnchorOp> {
+        self.file_anchor.as_deref()
+    }
+
+    /// Attach an AI model governance proposal or vote to the transaction.
+    pub fn set_model_governance(&mut self, op: ModelGovernanceOp) {
+        self.model_governance = Some(Box::new(op));
+    }
+
+    /// Returns the embedded model governance operation, if any.
+    pub fn model_governance(&self) -> Option<&ModelGovernanceOp> {
+        self.model_governance.as_deref()
+    }
+
+    /// Whether the transaction carries a handle, file anchor or model
+    /// governance operation instead of a plain transfer.
+    pub fn carries_operation(&self) -> bool {
+        self.handle_op.is_some() || self.file_anchor.is_some() || self.model_governance.is_some()
+    }
+
     /// Attach a confidential envelope and mark the transaction as confidential.
     pub fn set_confidential_envelope(&mut self, envelope: ConfidentialEnvelope) {
         self.visibility = TransactionVisibility::Confidential;
@@ -230,7 +266,7 @@ impl Transaction {
         payload.extend_from_slice(&nonce.to_be_bytes());
         if let Some(op) = handle_op {
             payload.push(1);
-            Self::append_handle_operation(&mut payload, op);
+            op.append_canonical_bytes(&mut payload);
         } else {
             payload.push(0);
         }
@@ -256,7 +292,7 @@ impl Transaction {
         match &self.handle_op {
             Some(op) => {
                 bytes.push(1);
-                Self::append_handle_operation(&mut bytes, op);
+                op.append_canonical_bytes(&mut bytes);
             }
             None => bytes.push(0),
         }
@@ -274,6 +310,15 @@ impl Transaction {
             }
             None => bytes.push(0),
         }
+        // Appended only when present so existing transaction hashes are unchanged.
+        if let Some(anchor) = &self.file_anchor {
+            bytes.push(1);
+            anchor.append_canonical_bytes(&mut bytes);
+        }
+        if let Some(op) = &self.model_governance {
+            bytes.push(2);
+            op.append_canonical_bytes(&mut bytes);
+        }
         bytes
     }
 
@@ -299,28 +344,6 @@ impl Transaction {
         }
     }
 
-    fn append_handle_operation(bytes: &mut Vec<u8>, op: &HandleOperation) {
-        match op {
-            HandleOperation::Register(data) => {
-                bytes.push(0);
-                Self::append_length_prefixed(bytes, data.handle.as_bytes());
-                bytes.extend_from_slice(&data.owner);
-                if let Some(exp) = data.expires_at {
-                    bytes.push(1);
-                    bytes.extend_from_slice(&exp.to_be_bytes());
-                } else {
-                    bytes.push(0);
-                }
-                bytes.extend_from_slice(&(data.metadata.len() as u32).to_be_bytes());
-                for (key, value) in &data.metadata {
-                    Self::append_length_prefixed(bytes, key.as_bytes());
-                    Self::append_length_prefixed(bytes, value.as_bytes());
-                }
-                Self::append_length_prefixed(bytes, &data.signature);
-            }
-        }
-    }
-
     fn append_confidential(bytes: &mut Vec<u8>, envelope: &ConfidentialEnvelope) {
         Self::append_length_prefixed(bytes, envelope.enc_algo.as_bytes());
         Self::append_length_prefixed(bytes, envelope.iv.as_bytes());
@@ -378,6 +401,26 @@ impl Transaction {
         }
     }
 
+    /// Check that the HashTimer was derived from the transaction contents
+    pub fn verify_hashtimer(&self) -> bool {
+        let payload = Self::create_payload(
+            &self.from,
+            &self.to,
+            self.amount,
+            self.nonce,
+            self.handle_op.as_ref(),
+        );
+        let expected_hashtimer = HashTimer::derive(
+            "transaction",
+            self.timestamp,
+            b"transaction",
+            &payload,
+            &self.nonce.to_be_bytes(),
+            &self.from,
+        );
+        expected_hashtimer == self.hashtimer
+    }
+
     /// Get transaction hash
     pub fn hash(&self) -> [u8; 32] {
         self.compute_hash()
@@ -386,12 +429,19 @@ impl Transaction {
     /// Check if transaction is valid
     pub fn is_valid(&self) -> bool {
         // Basic validation checks
-        let has_handle = self.handle_op.is_some();
+        let operations = [
+            self.handle_op.is_some(),
+            self.file_anchor.is_some(),
+            self.model_governance.is_some(),
+        ];
+        if operations.into_iter().filter(|&carried| carried).count() > 1 {
+            return false;
+        }
         if self.visibility == TransactionVisibility::Confidential {
             if self.confidential.is_none() || self.zk_proof.is_none() {
                 return false;
             }
-        } else if !has_handle {
+        } else if !self.carries_operation() {
             if self.amount.is_zero() {
                 return false;
             }
@@ -412,23 +462,7 @@ impl Transaction {
         }
 
         // Verify HashTimer is valid and consistent with contents
-        let payload = Self::create_payload(
-            &self.from,
-            &self.to,
-            self.amount,
-            self.nonce,
-            self.handle_op.as_ref(),
-        );
-        let expected_hashtimer = HashTimer::derive(
-            "transaction",
-            self.timestamp,
-            b"transaction",
-            &payload,
-            &self.nonce.to_be_bytes(),
-            &self.from,
-        );
-
-        if expected_hashtimer != self.hashtimer {
+        if !self.verify_hashtimer() {
             return false;
         }
 
@@ -438,6 +472,18 @@ impl Transaction {
             }
         }
 
+        if let Some(anchor) = &self.file_anchor {
+            if anchor.validate_for_sender(&self.from).is_err() {
+                return false;
+            }
+        }
+
+        if let Some(op) = &self.model_governance {
+            if op.validate().is_err() {
+                return false;
+            }
+        }
+
         // HashTimer should not be from the future
         self.hashtimer.time().0 <= IppanTimeMicros::now().0
     }
@@ -537,6 +583,8 @@ impl TryFrom<TransactionWireV1> for Transaction {
             handle_op: None,
             confidential: None,
             zk_proof: None,
+            file_anchor: None,
+            model_governance: None,
             signature: wire.signature,
         })
     }
@@ -586,6 +634,59 @@ mod tests {
         assert_ne!(tx.id, [0u8; 32]);
     }
 
+    #[test]
+    fn test_file_anchor_transaction_validation() {
+        let (private_key, owner) = generate_account();
+        let signing_key = SigningKey::from_bytes(&private_key);
+        let digest = [4u8; 32];
+        let anchor = FileAnchorOp {
+            file_id: crate::FileDescriptorId::from_bytes([2u8; 32]),
+            content_hash: [3u8; 32],
+            owner,
+            created_at_us: 1_000_000,
+            descriptor_digest: digest,
+            signature: signing_key.sign(&digest).to_bytes().to_vec(),
+        };
+
+        let mut tx = Transaction::new(owner, [0u8; 32], Amount::zero(), 1);
+        let unsigned_anchor_hash = tx.message_digest();
+        tx.set_file_anchor(anchor.clone());
+        assert_ne!(tx.message_digest(), unsigned_anchor_hash);
+        tx.sign(&private_key).unwrap();
+        assert!(tx.is_valid());
+
+        let mut tampered = tx.clone();
+        tampered.file_anchor.as_mut().unwrap().descriptor_digest = [5u8; 32];
+        assert!(!tampered.is_valid());
+
+        let (other_key, other) = generate_account();
+        let mut wrong_sender = Transaction::new(other, [0u8; 32], Amount::zero(), 1);
+        wrong_sender.set_file_anchor(anchor);
+        wrong_sender.sign(&other_key).unwrap();
+        assert!(!wrong_sender.is_valid());
+    }
+
+    #[test]
+    fn test_model_vote_transaction_validation() {
+        let (private_key, validator) = generate_account();
+        let mut tx = Transaction::new(validator, [0u8; 32], Amount::zero(), 1);
+        let plain_hash = tx.message_digest();
+        tx.set_model_governance(ModelGovernanceOp::Vote(crate::ModelVoteOp {
+            model_id: "fairness".into(),
+            approve: true,
+        }));
+        assert_ne!(tx.message_digest(), plain_hash);
+        tx.sign(&private_key).unwrap();
+        assert!(tx.is_valid());
+
+        let mut tampered = tx.clone();
+        tampered.model_governance = Some(Box::new(ModelGovernanceOp::Vote(crate::ModelVoteOp {
+            model_id: "fairness".into(),
+            approve: false,
+        })));
+        assert!(!tampered.is_valid());
+    }
+
     #[test]
     fn test_transaction_verification() {
         let (private_key, from) = generate_account();
@@ -0,0 +1,196 @@
+//! Encrypted file helpers
+//!
+//! Encrypt content before uploading it to `/files/content/{hash}`, build the
+//! signed descriptor for `/files/publish`, re-wrap the content key when
+//! sharing or revoking access, and decrypt downloaded ciphertext.
+
+use crate::errors::*;
+use ippan_crypto::KeyPair;
+use ippan_files::content::{content_root, DEFAULT_CHUNK_SIZE};
+use ippan_files::{encrypt_file, FileDescriptor, FileEncryption};
+
+/// Ciphertext and signed descriptor ready to upload and publish.
+#[derive(Debug, Clone)]
+pub struct EncryptedUpload {
+    /// Bytes to upload in place of the plaintext.
+    pub ciphertext: Vec<u8>,
+    /// Content key; keep it to share the file later.
+    pub content_key: [u8; 32],
+    /// Descriptor signed by the owner, carrying the recipient envelopes.
+    pub descriptor: FileDescriptor,
+}
+
+/// Encrypt `plaintext` for the owner and `recipients` and sign its descriptor.
+pub fn encrypt_for_upload(
+    plaintext: &[u8],
+    owner_private_key: &[u8; 32],
+    recipients: &[[u8; 32]],
+    mime_type: Option<String>,
+    tags: Vec<String>,
+) -> Result<EncryptedUpload> {
+    let owner = owner_key(owner_private_key)?;
+    let mut keys = vec![owner];
+    for recipient in recipients {
+        if !keys.contains(recipient) {
+            keys.push(*recipient);
+        }
+    }
+
+    let encrypted =
+        encrypt_file(plaintext, &keys).map_err(|e| WalletError::EncryptionError(e.to_string()))?;
+    let mut descriptor = FileDescriptor::new(
+        content_root(&encrypted.ciphertext, DEFAULT_CHUNK_SIZE),
+        owner,
+        encrypted.ciphertext.len() as u64,
+        mime_type,
+        tags,
+    )
+    .with_encryption(encrypted.encryption);
+    descriptor
+        .sign(owner_private_key)
+        .map_err(WalletError::CryptoError)?;
+
+    Ok(EncryptedUpload {
+        ciphertext: encrypted.ciphertext,
+        content_key: encrypted.content_key,
+        descriptor,
+    })
+}
+
+/// Give `recipient` access by sealing the content key to it, then re-sign
+/// the envelope set under the next version.
+pub fn share_file(
+    descriptor: &mut FileDescriptor,
+    content_key: &[u8; 32],
+    recipient: &[u8; 32],
+    owner_private_key: &[u8; 32],
+) -> Result<()> {
+    encryption_of(descriptor)?
+        .add_recipient(content_key, recipient)
+        .map_err(|e| WalletError::EncryptionError(e.to_string()))?;
+    resign_recipients(descriptor, owner_private_key)
+}
+
+/// Remove `recipient`'s key envelope, then re-sign the envelope set under
+/// the next version.
+///
+/// This only stops future key retrieval; re-encrypt the content to cut off
+/// a recipient who already holds the content key.
+pub fn revoke_file_access(
+    descriptor: &mut FileDescriptor,
+    recipient: &[u8; 32],
+    owner_private_key: &[u8; 32],
+) -> Result<bool> {
+    let revoked = encryption_of(descriptor)?.revoke_recipient(recipient);
+    resign_recipients(descriptor, owner_private_key)?;
+    Ok(revoked)
+}
+
+/// Decrypt downloaded ciphertext with a recipient's private key.
+pub fn decrypt_download(
+    descriptor: &FileDescriptor,
+    ciphertext: &[u8],
+    private_key: &[u8; 32],
+) -> Result<Vec<u8>> {
+    descriptor
+        .encryption
+        .as_ref()
+        .ok_or_else(|| WalletError::DecryptionError("file is not encrypted".to_string()))?
+        .decrypt_with_private_key(ciphertext, private_key)
+        .map_err(|e| WalletError::DecryptionError(e.to_string()))
+}
+
+fn owner_key(private_key: &[u8; 32]) -> Result<[u8; 32]> {
+    KeyPair::from_private_key(private_key)
+        .map(|keypair| keypair.public_key())
+        .map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))
+}
+
+/// The descriptor signature, and so its L1 anchor, stays as it is; nodes
+/// only accept an envelope set with a higher version than the one they hold.
+fn resign_recipients(descriptor: &mut FileDescriptor, owner_private_key: &[u8; 32]) -> Result<()> {
+    let encryption = encryption_of(descriptor)?;
+    encryption.version = encryption
+        .version
+        .checked_add(1)
+        .ok_or_else(|| WalletError::EncryptionError("recipient version exhausted".to_string()))?;
+    descriptor
+        .sign_recipients(owner_private_key)
+        .map_err(WalletError::CryptoError)
+}
+
+fn encryption_of(descriptor: &mut FileDescriptor) -> Result<&mut FileEncryption> {
+    descriptor
+        .encryption
+        .as_mut()
+        .ok_or_else(|| WalletError::EncryptionError("file is not encrypted".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_upload_roundtrip() {
+        let owner = KeyPair::generate();
+        let reader = KeyPair::generate();
+        let mut upload = encrypt_for_upload(
+            b"wallet backup notes",
+            &owner.private_key(),
+            &[],
+            Some("text/plain".to_string()),
+            vec![],
+        )
+        .unwrap();
+        assert!(upload.descriptor.verify_signature().is_ok());
+        assert_eq!(
+            upload.descriptor.content_hash,
+            content_root(&upload.ciphertext, DEFAULT_CHUNK_SIZE)
+        );
+        assert!(decrypt_download(
+            &upload.descriptor,
+            &upload.ciphertext,
+            &reader.private_key()
+        )
+        .is_err());
+        let signature = upload.descriptor.signature.clone();
+
+        share_file(
+            &mut upload.descriptor,
+            &upload.content_key,
+            &reader.public_key(),
+            &owner.private_key(),
+        )
+        .unwrap();
+        assert!(upload.descriptor.verify_signature().is_ok());
+        assert_eq!(
+            decrypt_download(
+                &upload.descriptor,
+                &upload.ciphertext,
+                &reader.private_key()
+            )
+            .unwrap(),
+            b"wallet backup notes"
+        );
+
+        assert!(revoke_file_access(
+            &mut upload.descriptor,
+            &reader.public_key(),
+            &owner.private_key()
+        )
+        .unwrap());
+        assert!(upload.descriptor.verify_signature().is_ok());
+        assert_eq!(upload.descriptor.recipients_version(), Some(2));
+        assert_eq!(upload.descriptor.signature, signature);
+        assert!(decrypt_download(
+            &upload.descriptor,
+            &upload.ciphertext,
+            &reader.private_key()
+        )
+        .is_err());
+        assert_eq!(
+            decrypt_download(&upload.descriptor, &upload.ciphertext, &owner.private_key()).unwrap(),
+            b"wallet backup notes"
+        );
+    }
+}
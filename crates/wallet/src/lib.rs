@@ -6,6 +6,7 @@
 pub mod cli;
 pub mod crypto;
 pub mod errors;
+pub mod files;
 pub mod keyfile;
 pub mod operations;
 pub mod rpc;
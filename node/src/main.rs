@@ -4,20 +4,28 @@ use clap::{value_parser, Arg, ArgAction, Command, ValueEnum};
 use config::{Config, File as ConfigFile};
 use fs2::FileExt;
 use hex::encode as hex_encode;
+use ippan_ai_registry::{load_manifest, ArtifactSource, ModelArtifactCache, ModelFetcher};
 use ippan_consensus::{
     DLCConfig, DLCIntegratedConsensus, PoAConfig, PoAConsensus, Validator,
     ValidatorMetrics as DlcValidatorMetrics, VALIDATOR_BOND_AMOUNT,
 };
 use ippan_consensus_dlc::{DlcConfig as AiDlcConfig, DlcConsensus};
 use ippan_crypto::KeyPair;
-use ippan_files::{dht::StubFileDhtService, FileDhtService, FileStorage, MemoryFileStorage};
+use ippan_files::{
+    dht::StubFileDhtService, ContentStore, FileDescriptor, FileDhtService, FileId, FileStorage,
+    L1FileAnchorStorage, MemoryFileStorage,
+};
 use ippan_l1_handle_anchors::L1HandleAnchorStorage;
-use ippan_l2_handle_registry::{HandleDhtService, L2HandleRegistry, StubHandleDhtService};
+use ippan_l2_handle_registry::{
+    HandleAuctionBook, HandleDhtService, L2HandleRegistry, StubHandleDhtService,
+};
 use ippan_mempool::Mempool;
-use ippan_network::load_identity_with_fallback;
+use ippan_network::{load_identity_with_fallback, ReputationManager};
 use ippan_p2p::{
-    ChaosConfig, DhtConfig, HttpP2PNetwork, IpnDhtService, Libp2pConfig, Libp2pFileDhtService,
-    Libp2pHandleDhtService, Libp2pNetwork, Multiaddr, NetworkEvent, P2PConfig, P2PLimits,
+    BlockSyncer, ChaosConfig, DhtConfig, HttpP2PNetwork, IpnDhtRecordValidator, IpnDhtService,
+    Libp2pConfig, Libp2pFileDhtService, Libp2pHandleDhtService, Libp2pNetwork, Multiaddr,
+    NetworkEvent, P2PConfig, P2PLimits, P2PTlsConfig, PeerAuthConfig, PeerScoring,
+    PeerScoringConfig,
 };
 use ippan_rpc::server::ConsensusHandle;
 use ippan_rpc::{start_p2p_server, start_server, AiStatusHandle, AppState, BatchLane, L2Config};
@@ -201,6 +209,8 @@ impl ProfileDefaults {
 }
 
 const DEVNET_BOOTSTRAPS: &[&str] = &[];
+/// How often libp2p peers are polled for finalized rounds we are missing.
+const LIBP2P_BLOCK_SYNC_INTERVAL: Duration = Duration::from_secs(15);
 const TESTNET_BOOTSTRAPS: &[&str] = &[
     "http://rc-node1.testnet.ippan.network:29000",
     "http://rc-node2.testnet.ippan.network:29000",
@@ -301,6 +311,8 @@ struct AppConfig {
     p2p_host: String,
     p2p_port: u16,
     p2p_identity_key_path: Option<PathBuf>,
+    p2p_tls: Option<P2PTlsConfig>,
+    p2p_require_signed_messages: bool,
 
     // Storage
     data_dir: String,
@@ -312,6 +324,8 @@ struct AppConfig {
     max_transactions_per_block: usize,
     block_reward: u64,
     finalization_interval_ms: u64,
+    enable_ai_reputation: bool,
+    model_manifest_path: PathBuf,
 
     // DLC Configuration
     enable_dlc: bool,
@@ -441,6 +455,22 @@ impl AppConfig {
             get_string_value(&config, &["P2P_IDENTITY_KEY_PATH", "p2p.identity_key_path"])
                 .map(PathBuf::from);
 
+        let p2p_tls = match (
+            get_string_value(&config, &["P2P_TLS_CERT_PATH", "p2p.tls_cert_path"]),
+            get_string_value(&config, &["P2P_TLS_KEY_PATH", "p2p.tls_key_path"]),
+            get_string_value(&config, &["P2P_TLS_CA_PATH", "p2p.tls_ca_path"]),
+        ) {
+            (Some(cert), Some(key), Some(ca)) => Some(P2PTlsConfig {
+                cert_path: PathBuf::from(cert),
+                key_path: PathBuf::from(key),
+                ca_path: PathBuf::from(ca),
+            }),
+            (None, None, None) => None,
+            _ => anyhow::bail!(
+                "P2P_TLS_CERT_PATH, P2P_TLS_KEY_PATH and P2P_TLS_CA_PATH must be set together"
+            ),
+        };
+
         let allowed_origins_value =
             get_string_value(&config, &["RPC_ALLOWED_ORIGINS", "rpc.allowed_origins"])
                 .unwrap_or_else(|| "http://127.0.0.1:3000,http://localhost:3000".to_string());
@@ -537,6 +567,17 @@ impl AppConfig {
             consensus_mode: get_string_value(&config, &["CONSENSUS_MODE", "consensus.mode"])
                 .unwrap_or_else(|| "POA".to_string()),
             enable_dlc: get_bool_value(&config, &["ENABLE_DLC", "consensus.enable_dlc"], false),
+            enable_ai_reputation: get_bool_value(
+                &config,
+                &["ENABLE_AI_REPUTATION", "consensus.enable_ai_reputation"],
+                false,
+            ),
+            model_manifest_path: get_string_value(
+                &config,
+                &["MODEL_MANIFEST_PATH", "consensus.model_manifest"],
+            )
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("models/canonical_manifest.json")),
             temporal_finality_ms: config
                 .get_string("TEMPORAL_FINALITY_MS")
                 .unwrap_or_else(|_| "250".to_string())
@@ -564,6 +605,12 @@ impl AppConfig {
                 .unwrap_or_else(|| defaults.p2p_port.to_string())
                 .parse()?,
             p2p_identity_key_path,
+            p2p_tls,
+            p2p_require_signed_messages: get_bool_value(
+                &config,
+                &["P2P_REQUIRE_SIGNED_MESSAGES", "p2p.require_signed_messages"],
+                true,
+            ),
             data_dir,
             db_path,
             slot_duration_ms: config
@@ -1251,22 +1298,40 @@ async fn main() -> Result<()> {
 
     let handle_registry = Arc::new(L2HandleRegistry::new());
     let handle_anchors = Arc::new(L1HandleAnchorStorage::new());
+    let file_anchors = Arc::new(L1FileAnchorStorage::new());
+    let handle_auctions = Arc::new(HandleAuctionBook::new());
 
     let need_ipn_dht_network = matches!(config.file_dht_mode, FileDhtMode::Libp2p)
         || matches!(config.handle_dht_mode, HandleDhtMode::Libp2p);
 
-    // Load libp2p identity only when libp2p networking is enabled
-    let (local_peer_id_string, ipn_dht_backend) = if need_ipn_dht_network {
-        let (identity_key_path, libp2p_identity) =
-            load_identity_with_fallback(config.p2p_identity_key_path.as_deref())?;
-        let libp2p_peer_id = PeerId::from(libp2p_identity.public());
-        let peer_id_string = format!("peer-{libp2p_peer_id}");
-        info!(
-            "Loaded P2P identity from {} (peer: {})",
-            identity_key_path.display(),
-            libp2p_peer_id
-        );
+    // The node identity signs HTTP P2P messages and, when enabled, drives libp2p.
+    // Existing keys at the legacy paths still take precedence over the data dir.
+    let default_identity_path = PathBuf::from(&config.data_dir)
+        .join("p2p")
+        .join("identity.key");
+    let (identity_key_path, libp2p_identity) = load_identity_with_fallback(Some(
+        config
+            .p2p_identity_key_path
+            .as_deref()
+            .unwrap_or(&default_identity_path),
+    ))?;
+    let libp2p_peer_id = PeerId::from(libp2p_identity.public());
+    let local_peer_id_string = format!("peer-{libp2p_peer_id}");
+    info!(
+        "Loaded P2P identity from {} (peer: {})",
+        identity_key_path.display(),
+        libp2p_peer_id
+    );
+
+    // Peer scores and bans are shared by the HTTP and libp2p stacks.
+    let peer_scoring = Arc::new(PeerScoring::open(
+        PathBuf::from(&config.data_dir)
+            .join("p2p")
+            .join("peer_scores"),
+        PeerScoringConfig::default(),
+    )?);
 
+    let (libp2p_network, ipn_dht_backend) = if need_ipn_dht_network {
         let listen_multiaddrs =
             parse_multiaddrs(&config.file_dht_listen_multiaddrs, "FILE_DHT_LIBP2P_LISTEN");
         let bootstrap_multiaddrs = parse_multiaddrs(
@@ -1280,6 +1345,8 @@ async fn main() -> Result<()> {
         libp2p_config.bootstrap_peers = bootstrap_multiaddrs;
         libp2p_config.identity_keypair = Some(libp2p_identity.clone());
         libp2p_config.identity_key_path = Some(identity_key_path.clone());
+        libp2p_config.record_store_path = Some(PathBuf::from(&config.data_dir).join("ipndht"));
+        libp2p_config.peer_scoring = Some(peer_scoring.clone());
         for topic in ["ippan/files", "ippan/handles"] {
             if !libp2p_config
                 .gossip_topics
@@ -1289,22 +1356,29 @@ async fn main() -> Result<()> {
                 libp2p_config.gossip_topics.push(topic.to_string());
             }
         }
-        let backend = match Libp2pNetwork::new(libp2p_config) {
+        let (network, backend) = match Libp2pNetwork::new(libp2p_config) {
             Ok(network) => {
+                network.set_record_validator(Arc::new(
+                    IpnDhtRecordValidator::new(Some(handle_anchors.clone()))
+                        .with_file_anchors(file_anchors.clone()),
+                ));
                 let network = Arc::new(network);
                 let addresses = network.listen_addresses();
                 info!("IPNDHT libp2p listening on {:?}", addresses);
-                Some(Arc::new(IpnDhtService::new(Some(network))))
+                (
+                    Some(network.clone()),
+                    Some(Arc::new(IpnDhtService::new(Some(network)))),
+                )
             }
             Err(err) => {
                 warn!(
                     "Failed to initialise libp2p IPNDHT (fallback to stub services): {}",
                     err
                 );
-                None
+                (None, None)
             }
         };
-        (Some(peer_id_string), backend)
+        (network, backend)
     } else {
         (None, None)
     };
@@ -1360,7 +1434,7 @@ async fn main() -> Result<()> {
         max_transactions_per_block: config.max_transactions_per_block,
         block_reward: config.block_reward,
         finalization_interval_ms: config.finalization_interval_ms,
-        enable_ai_reputation: false,
+        enable_ai_reputation: config.enable_ai_reputation,
         enable_fee_caps: true,
         enable_dag_fair_emission: true,
     };
@@ -1381,7 +1455,9 @@ async fn main() -> Result<()> {
             handle_registry.clone(),
             handle_anchors.clone(),
             Some(handle_dht.clone()),
-        );
+            handle_auctions.clone(),
+        )
+        .with_file_anchors(file_anchors.clone());
 
         // Create DLC configuration
         let dlc_config = DLCConfig {
@@ -1396,6 +1472,8 @@ async fn main() -> Result<()> {
             dag_config: Default::default(),
         };
 
+        load_model_manifest(&poa_instance, &config.model_manifest_path);
+
         // Create integrated DLC consensus
         let mut dlc_integrated =
             DLCIntegratedConsensus::new(poa_instance, dlc_config, config.validator_id);
@@ -1476,7 +1554,10 @@ async fn main() -> Result<()> {
             handle_registry.clone(),
             handle_anchors.clone(),
             Some(handle_dht.clone()),
-        );
+            handle_auctions.clone(),
+        )
+        .with_file_anchors(file_anchors.clone());
+        load_model_manifest(&consensus_instance, &config.model_manifest_path);
         tx_sender = consensus_instance.get_tx_sender();
         mempool = consensus_instance.mempool();
         consensus = Arc::new(Mutex::new(consensus_instance));
@@ -1493,7 +1574,12 @@ async fn main() -> Result<()> {
     // Initialize P2P network
     let p2p_host = &config.p2p_host;
     let p2p_port = config.p2p_port;
-    let listen_address = format!("http://{p2p_host}:{p2p_port}");
+    let p2p_scheme = if config.p2p_tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let listen_address = format!("{p2p_scheme}://{p2p_host}:{p2p_port}");
     let dht_config = DhtConfig {
         bootstrap_peers: config.bootstrap_nodes.clone(),
         public_host: config.p2p_public_host.clone(),
@@ -1504,7 +1590,8 @@ async fn main() -> Result<()> {
 
     let p2p_config = P2PConfig {
         listen_address: listen_address.clone(),
-        local_peer_id: local_peer_id_string.clone(),
+        local_peer_id: Some(local_peer_id_string.clone()),
+        identity: Some(libp2p_identity.clone()),
         max_peers: config.max_peers,
         peer_discovery_interval: Duration::from_secs(config.peer_discovery_interval_secs),
         message_timeout: Duration::from_secs(10),
@@ -1517,6 +1604,12 @@ async fn main() -> Result<()> {
             extra_latency_ms_max: config.chaos_extra_latency_ms_max,
         },
         limits: P2PLimits::default(),
+        auth: PeerAuthConfig {
+            require_signatures: config.p2p_require_signed_messages,
+            ..PeerAuthConfig::default()
+        },
+        tls: config.p2p_tls.clone(),
+        peer_scoring: Some(peer_scoring.clone()),
     };
 
     let mut p2p_network = HttpP2PNetwork::new(p2p_config, listen_address.clone())?;
@@ -1544,6 +1637,12 @@ async fn main() -> Result<()> {
     let consensus_for_shutdown = consensus.clone();
     peer_count.store(p2p_network_arc.get_peer_count(), Ordering::Relaxed);
 
+    if let Some(network) = &libp2p_network {
+        let storage: Arc<dyn Storage + Send + Sync> = storage.clone();
+        network.serve_block_sync(storage.clone());
+        spawn_libp2p_block_sync(network.clone(), storage);
+    }
+
     let consensus_for_events = consensus_handle.clone();
     if let Some(mut events) = incoming_events.take() {
         let network_for_events = p2p_network_arc.clone();
@@ -1592,6 +1691,35 @@ async fn main() -> Result<()> {
     };
 
     let file_storage: Arc<dyn FileStorage> = Arc::new(MemoryFileStorage::new());
+    let content_dir = PathBuf::from(&config.data_dir).join("files");
+    let content_store = match ContentStore::open(&content_dir) {
+        Ok(store) => {
+            info!("File content store at {}", content_dir.display());
+            Some(Arc::new(store))
+        }
+        Err(err) => {
+            warn!("File content store unavailable: {}", err);
+            None
+        }
+    };
+    if let (Some(ipn), Some(store)) = (&ipn_dht_backend, &content_store) {
+        ipn.serve_content(store.clone());
+
+        let model_dir = PathBuf::from(&config.data_dir).join("models");
+        match ModelArtifactCache::open(&model_dir) {
+            Ok(cache) => {
+                let source = IpnDhtArtifactSource::new(ipn.clone(), store.clone());
+                let mut guard = consensus.lock().await;
+                guard.model_fetcher = Some(Arc::new(ModelFetcher::new(Arc::new(source), cache)));
+                info!("Model artifacts cached at {}", model_dir.display());
+                // Approved models are fetched and staged for their activation round
+                if let Err(err) = guard.spawn_model_staging() {
+                    warn!("Governance model staging unavailable: {}", err);
+                }
+            }
+            Err(err) => warn!("Model artifact cache unavailable: {}", err),
+        }
+    }
 
     let app_state = AppState {
         storage: storage.clone(),
@@ -1611,12 +1739,14 @@ async fn main() -> Result<()> {
         metrics: prometheus_handle.clone(),
         file_storage: Some(file_storage),
         file_dht: Some(file_dht),
+        content_store,
         dht_file_mode: config.file_dht_mode.to_string(),
         dev_mode: config.dev_mode,
         rpc_allowed_origins: config.rpc_allowed_origins.clone(),
         handle_registry: handle_registry.clone(),
         handle_anchors: handle_anchors.clone(),
         handle_dht: Some(handle_dht.clone()),
+        handle_auctions: handle_auctions.clone(),
         dht_handle_mode: config.handle_dht_mode.to_string(),
         dlc_consensus: dlc_handle,
         ipn_dht: ipn_dht_backend,
@@ -1989,6 +2119,64 @@ fn parse_hex_validator_id(id: &str) -> Option<[u8; 32]> {
     Some(buf)
 }
 
+/// Load the training distributions recorded in the model manifest, so inputs
+/// of the active fairness model, and of every model activated later, are
+/// monitored for drift.
+fn load_model_manifest(consensus: &PoAConsensus, path: &Path) {
+    if !path.exists() {
+        info!(
+            "No model manifest at {}; fairness input drift is not monitored",
+            path.display()
+        );
+        return;
+    }
+    match load_manifest(path).and_then(|manifest| consensus.load_model_manifest(&manifest)) {
+        Ok(true) => info!("Monitoring fairness input drift against {}", path.display()),
+        Ok(false) => info!(
+            "Loaded model manifest {}; no training distribution for the active fairness model",
+            path.display()
+        ),
+        Err(err) => warn!("Failed to load model manifest {}: {}", path.display(), err),
+    }
+}
+
+/// Downloads governance-approved model artifacts from IPNDHT providers.
+struct IpnDhtArtifactSource {
+    ipn: Arc<IpnDhtService>,
+    store: Arc<ContentStore>,
+    downloader: Option<ippan_p2p::ChunkDownloader>,
+}
+
+impl IpnDhtArtifactSource {
+    fn new(ipn: Arc<IpnDhtService>, store: Arc<ContentStore>) -> Self {
+        let downloader =
+            ipn.chunk_downloader(store.clone(), Arc::new(ReputationManager::default()));
+        Self {
+            ipn,
+            store,
+            downloader,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactSource for IpnDhtArtifactSource {
+    async fn fetch(&self, id: &FileId) -> Result<(FileDescriptor, Vec<u8>)> {
+        let downloader = self
+            .downloader
+            .as_ref()
+            .ok_or_else(|| anyhow!("libp2p network not available"))?;
+        let manifest = self.ipn.fetch_file_content(id, downloader).await?;
+        let descriptor = self
+            .ipn
+            .find_file(id)
+            .await?
+            .ok_or_else(|| anyhow!("file descriptor {} not found", id.to_hex()))?;
+        let bytes = self.store.read(&manifest.root)?;
+        Ok((descriptor, bytes))
+    }
+}
+
 fn seed_dlc_validator_metrics(
     handle: &Arc<RwLock<ippan_consensus::DLCConsensus>>,
     validators: &[[u8; 32]],
@@ -1999,6 +2187,34 @@ fn seed_dlc_validator_metrics(
     }
 }
 
+/// Catch up with connected libp2p peers over the block sync protocol, so
+/// libp2p-only nodes do not depend on the HTTP `/p2p/block-request` path.
+fn spawn_libp2p_block_sync(network: Arc<Libp2pNetwork>, storage: Arc<dyn Storage + Send + Sync>) {
+    let syncer = BlockSyncer::new(
+        network.clone(),
+        storage,
+        Arc::new(ReputationManager::default()),
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(LIBP2P_BLOCK_SYNC_INTERVAL);
+        loop {
+            ticker.tick().await;
+            for peer in network.connected_peers() {
+                match syncer.catch_up(&peer.to_string()).await {
+                    Ok(progress) if progress.blocks > 0 => info!(
+                        %peer,
+                        rounds = progress.rounds,
+                        blocks = progress.blocks,
+                        "Imported blocks over libp2p block sync"
+                    ),
+                    Ok(_) => {}
+                    Err(err) => debug!(%peer, "libp2p block sync failed: {err}"),
+                }
+            }
+        }
+    });
+}
+
 fn spawn_localnet_metrics_drift(
     dlc_handle: Arc<RwLock<ippan_consensus::DLCConsensus>>,
     validator_ids: Vec<[u8; 32]>,
@@ -2446,6 +2662,8 @@ mod tests {
             p2p_host: "0.0.0.0".to_string(),
             p2p_port: 29000,
             p2p_identity_key_path: None,
+            p2p_tls: None,
+            p2p_require_signed_messages: true,
             data_dir: "./data/testnet".to_string(),
             db_path: "./data/testnet/db".to_string(),
             consensus_mode: "POA".to_string(),
@@ -2453,6 +2671,8 @@ mod tests {
             max_transactions_per_block: 1000,
             block_reward: 10,
             finalization_interval_ms: 200,
+            enable_ai_reputation: false,
+            model_manifest_path: PathBuf::from("models/canonical_manifest.json"),
             enable_dlc: false,
             temporal_finality_ms: 250,
             shadow_verifier_count: 3,
@@ -2496,4 +2716,62 @@ mod tests {
             "unexpected error: {err}"
         );
     }
+
+    #[test]
+    fn model_manifest_drives_input_drift_metrics() {
+        use ippan_consensus_dlc::dgbdt::FairnessModel;
+        use ippan_consensus_dlc::fairness_drift::DistributionSummary;
+        use ippan_storage::MemoryStorage;
+
+        let model = FairnessModel::testing_stub();
+        let mut manifest = load_manifest(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../models/canonical_manifest.json"),
+        )
+        .unwrap();
+        let rows: Vec<Vec<i64>> = (0..200)
+            .map(|i| vec![9_000 + i * 5, 5_000 + i * 10, 10_000, 5_000, 5_000, 5_000])
+            .collect();
+        manifest.models[0].artifact.blake3 = model.raw_model().hash_hex().unwrap();
+        manifest.models[0].training_distribution = Some(DistributionSummary::from_rows(
+            &FairnessModel::FEATURE_NAMES,
+            &rows,
+            10,
+        ));
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("canonical_manifest.json");
+        manifest.write_to_path(&manifest_path).unwrap();
+
+        let validators = (1..=4u8)
+            .map(|i| Validator {
+                id: [i; 32],
+                address: [i; 32],
+                stake: 1_000_000,
+                is_active: true,
+            })
+            .collect();
+        let consensus = PoAConsensus::new(
+            PoAConfig {
+                validators,
+                enable_ai_reputation: true,
+                ..PoAConfig::default()
+            },
+            Arc::new(MemoryStorage::default()),
+            [1u8; 32],
+        );
+        consensus
+            .dgbdt_engine
+            .write()
+            .set_fairness_model(Some(model));
+        load_model_manifest(&consensus, &manifest_path);
+
+        // 4 validators per round reach the default 100-sample minimum
+        for slot in 1..=25 {
+            *consensus.current_slot.write() = slot;
+            consensus.get_state();
+        }
+
+        let output = consensus.get_metrics_prometheus();
+        assert!(output.contains("ippan_model_feature_samples{feature=\"uptime\"} 100"));
+        assert!(output.contains("ippan_model_feature_psi{feature=\"uptime\"}"));
+    }
 }